@@ -0,0 +1,21 @@
+// GENERATED CODE! DO NOT MODIFY BY HAND!
+
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type CodeGraphUpdatedEvent = { 
+/**
+ * Files re-indexed or dropped from the graph. Empty for a full rebuild.
+ */
+files: Array<string>, 
+/**
+ * Whether the whole repository was indexed again.
+ */
+full_rebuild: boolean, 
+/**
+ * Indexed files after the update.
+ */
+file_count: number, 
+/**
+ * Indexed code elements after the update.
+ */
+element_count: number, };
@@ -30,6 +30,7 @@ export type { CancelLoginChatGptResponse } from "./CancelLoginChatGptResponse";
 export type { ClientInfo } from "./ClientInfo";
 export type { ClientNotification } from "./ClientNotification";
 export type { ClientRequest } from "./ClientRequest";
+export type { CodeGraphUpdatedEvent } from "./CodeGraphUpdatedEvent";
 export type { CodexErrorInfo } from "./CodexErrorInfo";
 export type { CollabAgentInteractionBeginEvent } from "./CollabAgentInteractionBeginEvent";
 export type { CollabAgentInteractionEndEvent } from "./CollabAgentInteractionEndEvent";
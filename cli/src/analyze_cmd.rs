@@ -0,0 +1,70 @@
+use crate::index_args::IndexArgs;
+use anyhow::Context;
+use clap::Parser;
+use happy_core::graph::RepositoryGraph;
+use happy_core::graph::analysis::AnalysisLimits;
+use happy_core::graph::analysis::DEFAULT_ANALYSIS_BUDGET;
+use happy_core::graph::analysis::DEFAULT_SECTION_LIMIT;
+use std::path::PathBuf;
+
+#[derive(Debug, Parser)]
+pub struct AnalyzeCommand {
+    /// File to analyze: a path, a path relative to the repository root, or a
+    /// unique suffix such as `auth.py`.
+    #[arg(value_name = "FILE")]
+    pub file: String,
+
+    /// Repository the file belongs to.
+    #[arg(long, value_name = "PATH", default_value = ".")]
+    pub repo: PathBuf,
+
+    /// Entries listed per section.
+    #[arg(long, default_value_t = DEFAULT_SECTION_LIMIT)]
+    pub limit: usize,
+
+    /// Largest report, in bytes of JSON; sections are cut further to fit.
+    #[arg(long, default_value_t = DEFAULT_ANALYSIS_BUDGET)]
+    pub max_bytes: usize,
+
+    /// Print the report as JSON.
+    #[arg(long)]
+    pub json: bool,
+
+    #[command(flatten)]
+    pub index: IndexArgs,
+}
+
+pub async fn run_analyze(cmd: AnalyzeCommand) -> anyhow::Result<()> {
+    let repo_root = std::fs::canonicalize(&cmd.repo)
+        .with_context(|| format!("failed to resolve {}", cmd.repo.display()))?;
+    // A path that exists from here is matched exactly; anything else is
+    // looked up relative to the repo root or as a suffix.
+    let file = std::fs::canonicalize(&cmd.file)
+        .map(|path| path.to_string_lossy().to_string())
+        .unwrap_or_else(|_| cmd.file.clone());
+    let limits = AnalysisLimits {
+        section_limit: cmd.limit,
+        max_bytes: cmd.max_bytes,
+    };
+    let options = cmd.index.options(&repo_root);
+    let workspace = cmd.index.workspace(&repo_root)?;
+    let analysis = tokio::task::spawn_blocking(move || {
+        let elements = workspace
+            .load_or_index(&cmd.index.load_options(&options))
+            .elements;
+        let mut graph = RepositoryGraph::new();
+        graph.build_from_workspace(&elements, &workspace);
+        graph.analyze_file(&file, &limits)
+    })
+    .await??;
+
+    #[allow(clippy::print_stdout)]
+    {
+        if cmd.json {
+            println!("{}", serde_json::to_string_pretty(&analysis)?);
+        } else {
+            print!("{}", analysis.render());
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,154 @@
+use crate::index_args::IndexArgs;
+use anyhow::Context;
+use clap::Parser;
+use happy_core::bookmarks::BookmarkStatus;
+use happy_core::bookmarks::Bookmarks;
+use happy_core::bookmarks::ElementKey;
+use happy_core::graph::RepositoryGraph;
+use happy_core::indexer::IndexOptions;
+use happy_core::workspace::Workspace;
+use std::path::PathBuf;
+
+/// Renames persisted by `happycode lsp`, so bookmarks follow renamed elements.
+const RENAMES_CACHE: &str = ".happy/renames.bin";
+
+/// Subcommands:
+/// - `add` — bookmark an element with a note
+/// - `list` — show bookmarks with their current locations
+/// - `remove` — delete the bookmarks on an element
+#[derive(Debug, Parser)]
+pub struct BookmarkCommand {
+    /// Repository the bookmarks belong to.
+    #[arg(long, value_name = "PATH", default_value = ".", global = true)]
+    pub repo: PathBuf,
+
+    #[command(flatten)]
+    pub index: IndexArgs,
+
+    #[command(subcommand)]
+    pub subcommand: BookmarkSubcommand,
+}
+
+#[derive(Debug, clap::Subcommand)]
+pub enum BookmarkSubcommand {
+    Add(AddArgs),
+    List,
+    Remove(RemoveArgs),
+}
+
+#[derive(Debug, Parser)]
+pub struct AddArgs {
+    /// Element to bookmark: a name, qualified name (`Class.method`), or ID.
+    #[arg(value_name = "SYMBOL")]
+    pub symbol: String,
+
+    /// Note to attach; replaces the note of an existing bookmark.
+    #[arg(long, short = 'm', value_name = "NOTE", default_value = "")]
+    pub message: String,
+}
+
+#[derive(Debug, Parser)]
+pub struct RemoveArgs {
+    /// Element whose bookmarks to remove; a stale bookmark is matched by name.
+    #[arg(value_name = "SYMBOL")]
+    pub symbol: String,
+}
+
+pub async fn run_bookmark(cmd: BookmarkCommand) -> anyhow::Result<()> {
+    let repo_root = std::fs::canonicalize(&cmd.repo)
+        .with_context(|| format!("failed to resolve {}", cmd.repo.display()))?;
+    let options = cmd.index.options(&repo_root);
+    let workspace = cmd.index.workspace(&repo_root)?;
+    let index = cmd.index.clone();
+    let graph =
+        tokio::task::spawn_blocking(move || load_graph(&workspace, &index, &options)).await?;
+    let mut bookmarks = Bookmarks::load(&repo_root)
+        .with_context(|| format!("failed to read bookmarks in {}", repo_root.display()))?;
+
+    match cmd.subcommand {
+        BookmarkSubcommand::Add(args) => {
+            let element = graph
+                .resolve_element_id(&args.symbol)?
+                .and_then(|id| graph.get_element(&id))
+                .with_context(|| format!("element '{}' not found in index", args.symbol))?;
+            let key = bookmarks.add(&element, &args.message).key.clone();
+            bookmarks.save()?;
+            #[allow(clippy::print_stdout)]
+            {
+                println!(
+                    "Bookmarked {key} at {}:{}",
+                    element.relative_path, element.start_line
+                );
+            }
+        }
+        BookmarkSubcommand::List => print_bookmarks(&bookmarks, &graph),
+        BookmarkSubcommand::Remove(args) => {
+            let key = graph
+                .resolve_element_id(&args.symbol)
+                .ok()
+                .flatten()
+                .and_then(|id| graph.get_element(&id))
+                .map(|element| ElementKey::of(&element));
+            let removed = bookmarks.remove(|bookmark| match &key {
+                Some(key) => &bookmark.key == key,
+                None => bookmark.key.name == args.symbol,
+            });
+            if removed.is_empty() {
+                anyhow::bail!("no bookmark on '{}'", args.symbol);
+            }
+            bookmarks.save()?;
+            #[allow(clippy::print_stdout)]
+            for bookmark in &removed {
+                println!("Removed bookmark on {}", bookmark.key);
+            }
+        }
+    }
+    Ok(())
+}
+
+fn load_graph(workspace: &Workspace, index: &IndexArgs, options: &IndexOptions) -> RepositoryGraph {
+    let elements = workspace
+        .load_or_index(&index.load_options(options))
+        .elements;
+    let mut graph = RepositoryGraph::new();
+    graph.build_from_workspace(&elements, workspace);
+    let root = workspace.primary().path.as_path();
+    if let Ok(renames) = happy_core::store::load_renames(&root.join(RENAMES_CACHE)) {
+        graph.restore_renames(renames);
+    }
+    graph
+}
+
+fn print_bookmarks(bookmarks: &Bookmarks, graph: &RepositoryGraph) {
+    let resolved = bookmarks.resolve(graph);
+    #[allow(clippy::print_stdout)]
+    {
+        if resolved.is_empty() {
+            println!("No bookmarks. Add one with `happycode bookmark add <SYMBOL> -m <NOTE>`.");
+            return;
+        }
+        for entry in &resolved {
+            let location = match &entry.status {
+                BookmarkStatus::Live(element) => {
+                    format!("{}:{}", element.relative_path, element.start_line)
+                }
+                BookmarkStatus::Renamed { element, .. } => format!(
+                    "{}:{} (renamed to {})",
+                    element.relative_path, element.start_line, element.name
+                ),
+                BookmarkStatus::Stale => "STALE: no longer in the index".to_string(),
+            };
+            println!("{}  {location}", entry.bookmark.key);
+            if !entry.bookmark.note.is_empty() {
+                println!("    {}", entry.bookmark.note);
+            }
+        }
+        let stale = resolved.iter().filter(|entry| entry.is_stale()).count();
+        if stale > 0 {
+            println!(
+                "{stale} stale bookmark{}; remove with `happycode bookmark remove <NAME>`.",
+                if stale == 1 { "" } else { "s" }
+            );
+        }
+    }
+}
@@ -0,0 +1,54 @@
+use crate::index_args::IndexArgs;
+use anyhow::Context;
+use clap::Parser;
+use happy_core::graph::RepositoryGraph;
+use happy_core::utils::relative_to;
+use std::path::PathBuf;
+
+#[derive(Debug, Parser)]
+pub struct CyclesCommand {
+    /// Repository to index and check for circular imports.
+    #[arg(value_name = "PATH", default_value = ".")]
+    pub path: PathBuf,
+
+    #[command(flatten)]
+    pub index: IndexArgs,
+}
+
+pub async fn run_cycles(cmd: CyclesCommand) -> anyhow::Result<()> {
+    let repo_root = std::fs::canonicalize(&cmd.path)
+        .with_context(|| format!("failed to resolve {}", cmd.path.display()))?;
+    let options = cmd.index.options(&repo_root);
+    let workspace = cmd.index.workspace(&repo_root)?;
+    let cycles = tokio::task::spawn_blocking(move || {
+        let elements = workspace
+            .load_or_index(&cmd.index.load_options(&options))
+            .elements;
+        let mut graph = RepositoryGraph::new();
+        graph.build_from_workspace(&elements, &workspace);
+        graph.find_import_cycles()
+    })
+    .await?;
+
+    #[allow(clippy::print_stdout)]
+    {
+        if cycles.is_empty() {
+            println!("No import cycles found in {}", repo_root.display());
+            return Ok(());
+        }
+        println!(
+            "Found {} import cycle{}:",
+            cycles.len(),
+            if cycles.len() == 1 { "" } else { "s" }
+        );
+        for cycle in &cycles {
+            let files: Vec<String> = cycle
+                .iter()
+                .chain(cycle.first())
+                .map(|path| relative_to(&repo_root, path))
+                .collect();
+            println!("  {}", files.join(" -> "));
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,114 @@
+use crate::index_args::IndexArgs;
+use anyhow::Context;
+use clap::Parser;
+use happy_core::graph::RepositoryGraph;
+use happy_core::graph::impact::DEFAULT_IMPACT_DEPTH;
+use happy_core::graph::impact::DEFAULT_IMPACT_LIMIT;
+use happy_core::graph::impact::git_diff;
+use happy_core::graph::impact::parse_unified_diff;
+use happy_core::utils::relative_to;
+use std::path::PathBuf;
+
+#[derive(Debug, Parser)]
+pub struct DiffImpactCommand {
+    /// Repository whose uncommitted changes to analyze.
+    #[arg(value_name = "PATH", default_value = ".")]
+    pub path: PathBuf,
+
+    /// Diff the working tree against this branch or commit instead of `HEAD`.
+    #[arg(long, value_name = "REF")]
+    pub base: Option<String>,
+
+    /// Call and import hops to follow from the changed elements.
+    #[arg(long, default_value_t = DEFAULT_IMPACT_DEPTH)]
+    pub depth: usize,
+
+    /// Stop after this many impacted elements.
+    #[arg(long, default_value_t = DEFAULT_IMPACT_LIMIT)]
+    pub limit: usize,
+
+    /// Print the impact as JSON.
+    #[arg(long)]
+    pub json: bool,
+
+    #[command(flatten)]
+    pub index: IndexArgs,
+}
+
+pub async fn run_diff_impact(cmd: DiffImpactCommand) -> anyhow::Result<()> {
+    let repo_root = std::fs::canonicalize(&cmd.path)
+        .with_context(|| format!("failed to resolve {}", cmd.path.display()))?;
+    let options = cmd.index.options(&repo_root);
+    let workspace = cmd.index.workspace(&repo_root)?;
+    let root = repo_root.clone();
+    let base = cmd.base.clone();
+    let (depth, limit) = (cmd.depth, cmd.limit);
+    let impact = tokio::task::spawn_blocking(move || {
+        let diff = git_diff(&root, base.as_deref())?;
+        let changes = parse_unified_diff(&diff);
+        let elements = workspace
+            .load_or_index(&cmd.index.load_options(&options))
+            .elements;
+        let mut graph = RepositoryGraph::new();
+        graph.build_from_workspace(&elements, &workspace);
+        anyhow::Ok(graph.diff_impact(&root, &changes, depth, limit))
+    })
+    .await??;
+
+    #[allow(clippy::print_stdout)]
+    {
+        if cmd.json {
+            println!("{}", serde_json::to_string_pretty(&impact)?);
+            return Ok(());
+        }
+        let base = cmd.base.as_deref().unwrap_or("HEAD");
+        if impact.changed_files.is_empty() {
+            println!("No changes against {base}");
+            return Ok(());
+        }
+        println!(
+            "{} changed file{} against {base}",
+            impact.changed_files.len(),
+            if impact.changed_files.len() == 1 {
+                ""
+            } else {
+                "s"
+            }
+        );
+        for level in &impact.levels {
+            match level.distance {
+                0 => println!("\nChanged:"),
+                1 => println!("\n1 hop away:"),
+                hops => println!("\n{hops} hops away:"),
+            }
+            for elem in &level.elements {
+                let via = match (elem.via, &elem.from) {
+                    (Some(kind), Some(from)) => {
+                        format!("  ({} {from})", format!("{kind:?}").to_ascii_lowercase())
+                    }
+                    _ => String::new(),
+                };
+                println!(
+                    "  {:<10} {}  {}:{}{via}",
+                    elem.kind.as_str(),
+                    elem.name,
+                    relative_to(&repo_root, &elem.file_path),
+                    elem.start_line,
+                );
+            }
+            for file in &level.files {
+                println!("  {:<10} {}", "file", relative_to(&repo_root, file));
+            }
+        }
+        if !impact.tests.is_empty() {
+            println!("\nTests to run:");
+            for test in &impact.tests {
+                println!("  {}", relative_to(&repo_root, test));
+            }
+        }
+        if impact.truncated {
+            println!("\nStopped after {limit} elements; raise --limit to see more.");
+        }
+    }
+    Ok(())
+}
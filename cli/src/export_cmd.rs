@@ -0,0 +1,60 @@
+use crate::index_args::IndexArgs;
+use anyhow::Context;
+use clap::Parser;
+use happy_core::graph::RepositoryGraph;
+use happy_core::graph::export::ExportFormat;
+use happy_core::graph::types::EdgeKind;
+use std::io::BufWriter;
+use std::io::Write;
+use std::path::PathBuf;
+
+#[derive(Debug, Parser)]
+pub struct ExportCommand {
+    /// Repository to export.
+    #[arg(value_name = "PATH", default_value = ".")]
+    pub path: PathBuf,
+
+    /// `dot` (Graphviz, one cluster per file), `graphml` (Gephi, yEd), or `json`.
+    #[arg(long, value_name = "FORMAT", default_value = "dot")]
+    pub format: ExportFormat,
+
+    /// Only export these edge kinds and the nodes they connect, e.g. `calls`
+    /// for the call graph or `imports` for the import graph.
+    #[arg(long = "edge-kind", value_name = "KINDS", value_delimiter = ',')]
+    pub edge_kinds: Vec<EdgeKind>,
+
+    /// Write to this file instead of stdout.
+    #[arg(long, short = 'o', value_name = "FILE")]
+    pub output: Option<PathBuf>,
+
+    #[command(flatten)]
+    pub index: IndexArgs,
+}
+
+pub async fn run_export(cmd: ExportCommand) -> anyhow::Result<()> {
+    let repo_root = std::fs::canonicalize(&cmd.path)
+        .with_context(|| format!("failed to resolve {}", cmd.path.display()))?;
+    let options = cmd.index.options(&repo_root);
+    let workspace = cmd.index.workspace(&repo_root)?;
+    tokio::task::spawn_blocking(move || {
+        let elements = workspace
+            .load_or_index(&cmd.index.load_options(&options))
+            .elements;
+        let root = repo_root.to_string_lossy();
+        let mut graph = RepositoryGraph::new();
+        graph.build_from_workspace(&elements, &workspace);
+
+        let mut out: BufWriter<Box<dyn Write>> = match &cmd.output {
+            Some(path) => BufWriter::new(Box::new(
+                std::fs::File::create(path)
+                    .with_context(|| format!("failed to create {}", path.display()))?,
+            )),
+            None => BufWriter::new(Box::new(std::io::stdout().lock())),
+        };
+        graph
+            .export(&mut out, cmd.format, &cmd.edge_kinds, &root)
+            .and_then(|()| out.flush())
+            .context("failed to write export")
+    })
+    .await?
+}
@@ -0,0 +1,68 @@
+use anyhow::Context;
+use clap::Args;
+use happy_core::indexer::IndexOptions;
+use happy_core::store::cache::LoadOptions;
+use happy_core::workspace::Workspace;
+use std::path::Path;
+use std::path::PathBuf;
+
+/// Indexing limits shared by the commands that index a repository.
+#[derive(Debug, Clone, Default, Args)]
+pub struct IndexArgs {
+    /// Ignore `.happy/elements.bin` and index the repository from scratch.
+    #[arg(long, alias = "reindex")]
+    pub no_cache: bool,
+
+    /// Skip source files larger than this many bytes. Defaults to
+    /// `[index] max_file_size` in `.happy/agent.toml`, or 2 MiB.
+    #[arg(long, value_name = "BYTES")]
+    pub max_file_size: Option<u64>,
+
+    /// Index the functions and classes of generated files (`DO NOT EDIT`,
+    /// `@generated`), not just the files themselves.
+    #[arg(long)]
+    pub include_generated: bool,
+
+    /// Index source files that are not valid UTF-8, replacing the invalid
+    /// bytes, instead of skipping them.
+    #[arg(long)]
+    pub lossy_utf8: bool,
+
+    /// Index another repository with this one, so imports between them
+    /// resolve. Repeatable; added to the roots in `.happy/workspace.toml`.
+    #[arg(long, value_name = "PATH", value_delimiter = ',')]
+    pub workspace_root: Vec<PathBuf>,
+}
+
+impl IndexArgs {
+    /// The repo's configured options with these flags applied.
+    pub fn options(&self, repo_root: &Path) -> IndexOptions {
+        let mut options = IndexOptions::load(repo_root);
+        if let Some(max_file_size) = self.max_file_size {
+            options.max_file_size = max_file_size;
+        }
+        if self.include_generated {
+            options.include_generated = true;
+        }
+        if self.lossy_utf8 {
+            options.lossy_utf8 = true;
+        }
+        options
+    }
+
+    /// How to load the index built with `options`: from the cache unless
+    /// `--no-cache` was passed.
+    pub fn load_options<'a>(&self, options: &'a IndexOptions) -> LoadOptions<'a> {
+        LoadOptions {
+            use_cache: !self.no_cache,
+            ..LoadOptions::new(options)
+        }
+    }
+
+    /// The repo's workspace with `--workspace-root` added.
+    pub fn workspace(&self, repo_root: &Path) -> anyhow::Result<Workspace> {
+        Workspace::load(repo_root)
+            .and_then(|workspace| workspace.with_roots(&self.workspace_root))
+            .with_context(|| format!("failed to load the workspace of {}", repo_root.display()))
+    }
+}
@@ -0,0 +1,320 @@
+use crate::index_args::IndexArgs;
+use anyhow::Context;
+use clap::Parser;
+use codex_core::CodeGraphUserConfig;
+use codex_core::config::Config;
+use codex_utils_cli::CliConfigOverrides;
+use happy_core::events::EventSink;
+use happy_core::events::IndexEvent;
+use happy_core::graph::RepositoryGraph;
+use happy_core::indexer::IndexSummary;
+use happy_core::store::cache::CacheStatus;
+use happy_core::summary::HeuristicSummarizer;
+use happy_core::summary::summarize_cache;
+use happy_core::vector::EmbeddingClient;
+use happy_core::vector::EmbeddingConfig;
+use happy_core::vector::pipeline::VectorRefresh;
+use happy_core::vector::pipeline::refresh_cached_vectors;
+use happy_core::workspace::LoadedWorkspace;
+use serde_json::json;
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::path::PathBuf;
+use std::time::Instant;
+
+#[derive(Debug, Parser)]
+pub struct IndexCommand {
+    /// Repository to index. With `--workspace`, the repositories to index
+    /// together, the first one primary.
+    #[arg(value_name = "PATH")]
+    pub paths: Vec<PathBuf>,
+
+    /// Index every PATH as one workspace, so imports between them resolve.
+    /// Each repository keeps its own `.happy` cache.
+    #[arg(long)]
+    pub workspace: bool,
+
+    /// Print the per-root counts as JSON.
+    #[arg(long)]
+    pub json: bool,
+
+    /// List every file skipped while indexing, with the reason.
+    #[arg(long, short = 'v')]
+    pub verbose: bool,
+
+    /// Summarize elements without a summary from their docstrings and
+    /// signatures; see `happycode summarize` for model-written summaries.
+    #[arg(long)]
+    pub summarize: bool,
+
+    /// Embed new and changed elements with the server under `[embeddings]`
+    /// in `$CODEX_HOME/config.toml` and save the vectors to the primary
+    /// repository's `.happy/vectors.bin`, as a session does in the
+    /// background.
+    #[arg(long)]
+    pub embeddings: bool,
+
+    /// Write newline-delimited JSON index events (index_started,
+    /// index_completed, error) to this Unix socket or FIFO, as
+    /// `happycode serve --notify` does.
+    #[arg(long, value_name = "PATH")]
+    pub notify: Option<PathBuf>,
+
+    #[command(flatten)]
+    pub index: IndexArgs,
+}
+
+pub async fn run_index(
+    cmd: IndexCommand,
+    config_overrides: CliConfigOverrides,
+) -> anyhow::Result<()> {
+    if cmd.paths.len() > 1 && !cmd.workspace {
+        anyhow::bail!("pass --workspace to index several repositories together");
+    }
+    let primary = cmd
+        .paths
+        .first()
+        .cloned()
+        .unwrap_or_else(|| PathBuf::from("."));
+    let repo_root = std::fs::canonicalize(&primary)
+        .with_context(|| format!("failed to resolve {}", primary.display()))?;
+    let options = cmd.index.options(&repo_root);
+    let workspace = cmd
+        .index
+        .workspace(&repo_root)?
+        .with_roots(cmd.paths.get(1..).unwrap_or_default())
+        .context("failed to resolve a workspace root")?;
+    let embeddings = if cmd.embeddings {
+        Some(embedding_config(&repo_root, config_overrides).await?)
+    } else {
+        None
+    };
+    let roots = workspace.roots().to_vec();
+    let events = cmd.notify.as_deref().map(EventSink::new);
+    let started = Instant::now();
+    if let Some(events) = &events {
+        events.send(IndexEvent::IndexStarted {
+            repo_root: repo_root.to_string_lossy().to_string(),
+        });
+    }
+    let result = tokio::task::spawn_blocking(move || {
+        let LoadedWorkspace {
+            elements,
+            statuses,
+            summaries,
+        } = workspace.load_or_index(&cmd.index.load_options(&options));
+        let mut graph = RepositoryGraph::new();
+        graph.build_from_workspace(&elements, &workspace);
+        if cmd.summarize {
+            // The caches were just written, so this reloads rather than
+            // re-indexes them.
+            for root in workspace.roots() {
+                summarize_cache(
+                    &root.path,
+                    true,
+                    &options,
+                    &HeuristicSummarizer,
+                    true,
+                    &mut |_| {},
+                )
+                .with_context(|| format!("failed to summarize {}", root.path.display()))?;
+            }
+        }
+        let refresh = embeddings
+            .map(|config| {
+                let client = EmbeddingClient::new(config)
+                    .context("failed to create the embedding client")?;
+                anyhow::Ok(refresh_cached_vectors(
+                    &client,
+                    &graph.all_elements(),
+                    &repo_root.join(".happy").join("vectors.bin"),
+                    &mut |progress| {
+                        #[allow(clippy::print_stderr)]
+                        {
+                            eprint!("\rembedding: {}/{}", progress.embedded, progress.total);
+                        }
+                    },
+                ))
+            })
+            .transpose()?;
+        anyhow::Ok((
+            statuses,
+            summaries,
+            graph.stats(),
+            graph.generation(),
+            refresh,
+        ))
+    })
+    .await?;
+    let (statuses, summaries, stats, generation, refresh) = match result {
+        Ok(indexed) => indexed,
+        Err(err) => {
+            if let Some(events) = &events {
+                events.send(IndexEvent::Error {
+                    message: format!("{err:#}"),
+                });
+            }
+            return Err(err);
+        }
+    };
+    if let Some(events) = &events {
+        events.send(IndexEvent::IndexCompleted {
+            files: stats.file_count,
+            elements: stats.element_count,
+            nodes: stats.node_count,
+            edges: stats.edge_count,
+            generation,
+            duration_ms: started.elapsed().as_millis() as u64,
+        });
+    }
+    if let Some(refresh) = &refresh {
+        report_embeddings(refresh)?;
+    }
+
+    #[allow(clippy::print_stdout)]
+    {
+        // A single root gets no per-root breakdown, so count it from the
+        // totals.
+        let counts: Vec<(usize, usize)> = if stats.roots.is_empty() {
+            vec![(stats.file_count, stats.element_count)]
+        } else {
+            stats
+                .roots
+                .iter()
+                .map(|count| (count.files, count.elements))
+                .collect()
+        };
+        if cmd.json {
+            let report: Vec<_> = roots
+                .iter()
+                .zip(&counts)
+                .zip(&statuses)
+                .zip(&summaries)
+                .map(|(((root, (files, elements)), status), summary)| {
+                    let skipped: Option<Vec<_>> = summary.as_ref().map(|summary| {
+                        summary
+                            .skipped_paths
+                            .iter()
+                            .map(
+                                |(path, reason)| json!({ "path": path, "reason": reason.as_str() }),
+                            )
+                            .collect()
+                    });
+                    json!({
+                        "name": root.name,
+                        "path": root.path,
+                        "files": files,
+                        "elements": elements,
+                        "cache": cache_label(status),
+                        "skipped": skipped,
+                    })
+                })
+                .collect();
+            println!("{}", serde_json::to_string_pretty(&report)?);
+            return Ok(());
+        }
+        for (((root, (files, elements)), status), summary) in
+            roots.iter().zip(&counts).zip(&statuses).zip(&summaries)
+        {
+            println!(
+                "{:<16} {files} files, {elements} elements ({})  {}",
+                root.name,
+                cache_label(status),
+                root.path.display()
+            );
+            let Some(summary) = summary else {
+                if cmd.verbose {
+                    println!(
+                        "{:<16} loaded from cache; pass --no-cache to list skipped files",
+                        ""
+                    );
+                }
+                continue;
+            };
+            if !summary.skipped_paths.is_empty() {
+                println!("{:<16} {}", "", skipped_label(summary));
+            }
+            if cmd.verbose {
+                for (path, reason) in &summary.skipped_paths {
+                    println!("{:<16}   {path} ({})", "", reason.as_str());
+                }
+            }
+        }
+        if roots.len() > 1 {
+            println!(
+                "Workspace: {} files, {} elements, {} edges",
+                stats.file_count, stats.element_count, stats.edge_count
+            );
+        }
+    }
+    Ok(())
+}
+
+/// The embedding server and tuning for `--embeddings`: the endpoint from the
+/// user's config, the tuning from the repository's `.happy/agent.toml`.
+async fn embedding_config(
+    repo_root: &Path,
+    config_overrides: CliConfigOverrides,
+) -> anyhow::Result<EmbeddingConfig> {
+    let overrides = config_overrides
+        .parse_overrides()
+        .map_err(anyhow::Error::msg)?;
+    let config = Config::load_with_cli_overrides(overrides)
+        .await
+        .context("failed to load configuration")?;
+    let endpoint = CodeGraphUserConfig::load(&config.config_layer_stack)
+        .embeddings
+        .unwrap_or_default();
+    EmbeddingConfig::load(repo_root, &endpoint)
+        .context("failed to read [embeddings] in .happy/agent.toml")?
+        .context("--embeddings needs an endpoint under [embeddings] in $CODEX_HOME/config.toml")
+}
+
+/// Print what `--embeddings` did; an embedding failure fails the command,
+/// after the vectors embedded before it were saved.
+fn report_embeddings(refresh: &VectorRefresh) -> anyhow::Result<()> {
+    #[allow(clippy::print_stderr)]
+    {
+        if refresh.report.embedded > 0 || refresh.error.is_some() {
+            eprintln!();
+        }
+        eprintln!(
+            "Embeddings: {} embedded, {} unchanged, {} over budget, {} too large ({} vectors)",
+            refresh.report.embedded,
+            refresh.reused,
+            refresh.report.skipped_budget,
+            refresh.report.skipped_too_large,
+            refresh.vectors.len()
+        );
+    }
+    match &refresh.error {
+        Some(err) => Err(anyhow::anyhow!("embedding stopped early: {err}")),
+        None => Ok(()),
+    }
+}
+
+/// `"3 of 120 files skipped or limited: 1 failed to parse, 2 not valid UTF-8"`.
+fn skipped_label(summary: &IndexSummary) -> String {
+    let mut by_reason: BTreeMap<&str, usize> = BTreeMap::new();
+    for (_, reason) in &summary.skipped_paths {
+        *by_reason.entry(reason.as_str()).or_default() += 1;
+    }
+    let reasons = by_reason
+        .iter()
+        .map(|(reason, count)| format!("{count} {reason}"))
+        .collect::<Vec<_>>()
+        .join(", ");
+    format!(
+        "{} of {} files skipped or limited: {reasons}",
+        summary.skipped_paths.len(),
+        summary.files
+    )
+}
+
+fn cache_label(status: &CacheStatus) -> String {
+    match status {
+        CacheStatus::Fresh => "cache fresh".to_string(),
+        CacheStatus::Patched(changes) => format!("re-indexed {} changed files", changes.len()),
+        CacheStatus::Rebuilt(reason) => format!("cache rebuilt: {reason}"),
+    }
+}
@@ -0,0 +1,72 @@
+use crate::index_args::IndexArgs;
+use anyhow::Context;
+use clap::Parser;
+use happy_core::graph::RepositoryGraph;
+use happy_core::graph::importance::FileOrder;
+use happy_core::utils::relative_to;
+use std::path::PathBuf;
+
+#[derive(Debug, Parser)]
+pub struct LsCommand {
+    /// Repository to list.
+    #[arg(value_name = "PATH", default_value = ".")]
+    pub path: PathBuf,
+
+    /// `alpha` lists files by path; `importance` lists the files the rest of
+    /// the repo imports first, with their importer counts.
+    #[arg(long, value_name = "ORDER", default_value = "alpha")]
+    pub order: FileOrder,
+
+    #[command(flatten)]
+    pub index: IndexArgs,
+}
+
+pub async fn run_ls(cmd: LsCommand) -> anyhow::Result<()> {
+    let repo_root = std::fs::canonicalize(&cmd.path)
+        .with_context(|| format!("failed to resolve {}", cmd.path.display()))?;
+    let root = repo_root.clone();
+    let order = cmd.order;
+    let options = cmd.index.options(&repo_root);
+    let workspace = cmd.index.workspace(&repo_root)?;
+    let lines = tokio::task::spawn_blocking(move || {
+        let elements = workspace
+            .load_or_index(&cmd.index.load_options(&options))
+            .elements;
+        let mut graph = RepositoryGraph::new();
+        graph.build_from_workspace(&elements, &workspace);
+        match order {
+            FileOrder::Alpha => {
+                let mut files: Vec<String> = graph
+                    .file_paths()
+                    .iter()
+                    .map(|path| relative_to(&root, path))
+                    .collect();
+                files.sort();
+                files
+            }
+            FileOrder::Importance => graph
+                .files_by_importance()
+                .iter()
+                .map(|file| {
+                    format!(
+                        "{:>5}  {}",
+                        file.dependents,
+                        relative_to(&root, &file.file_path)
+                    )
+                })
+                .collect(),
+        }
+    })
+    .await?;
+
+    #[allow(clippy::print_stdout)]
+    {
+        if order == FileOrder::Importance {
+            println!("{:>5}  FILE", "USERS");
+        }
+        for line in &lines {
+            println!("{line}");
+        }
+    }
+    Ok(())
+}
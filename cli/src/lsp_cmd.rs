@@ -0,0 +1,609 @@
+//! `happycode lsp`: a Language Server over stdio backed by the code graph.
+//!
+//! Implements `textDocument/definition`, `textDocument/references` (call
+//! sites in callers plus uses in importing files), and `workspace/symbol`.
+//! Saved files are re-indexed incrementally with `update_file`.
+
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::path::Path;
+use std::path::PathBuf;
+
+use anyhow::Context;
+use clap::Parser;
+use happy_core::graph::RepositoryGraph;
+use happy_core::indexer::CodeElement;
+use happy_core::indexer::ElementType;
+use happy_core::indexer::IndexOptions;
+use happy_core::store::cache::LoadOptions;
+use happy_core::store::cache::LoadedIndex;
+use happy_core::store::cache::RENAMES_CACHE;
+use happy_core::vector::bm25::BM25Index;
+use lsp_server::Connection;
+use lsp_server::ErrorCode;
+use lsp_server::Message;
+use lsp_server::Notification;
+use lsp_server::Request;
+use lsp_server::Response;
+use lsp_types::DidChangeTextDocumentParams;
+use lsp_types::DidCloseTextDocumentParams;
+use lsp_types::DidOpenTextDocumentParams;
+use lsp_types::DidSaveTextDocumentParams;
+use lsp_types::GotoDefinitionParams;
+use lsp_types::GotoDefinitionResponse;
+use lsp_types::InitializeParams;
+use lsp_types::Location;
+use lsp_types::OneOf;
+use lsp_types::Position;
+use lsp_types::Range;
+use lsp_types::ReferenceParams;
+use lsp_types::ServerCapabilities;
+use lsp_types::SymbolInformation;
+use lsp_types::SymbolKind;
+use lsp_types::TextDocumentSyncCapability;
+use lsp_types::TextDocumentSyncKind;
+use lsp_types::TextDocumentSyncOptions;
+use lsp_types::TextDocumentSyncSaveOptions;
+use lsp_types::Url;
+use lsp_types::WorkspaceSymbolParams;
+use serde::de::DeserializeOwned;
+
+use crate::index_args::IndexArgs;
+
+/// Maximum results for a `workspace/symbol` query.
+const WORKSPACE_SYMBOL_LIMIT: usize = 100;
+
+#[derive(Debug, Parser)]
+pub struct LspCommand {
+    /// Repository to index when the client does not send a workspace root.
+    #[arg(value_name = "PATH", default_value = ".")]
+    pub path: PathBuf,
+
+    #[command(flatten)]
+    pub index: IndexArgs,
+}
+
+pub async fn run_lsp(cmd: LspCommand) -> anyhow::Result<()> {
+    tokio::task::spawn_blocking(move || serve_stdio(cmd)).await?
+}
+
+fn serve_stdio(cmd: LspCommand) -> anyhow::Result<()> {
+    let (connection, io_threads) = Connection::stdio();
+    let capabilities = serde_json::to_value(server_capabilities())?;
+    let params: InitializeParams = serde_json::from_value(connection.initialize(capabilities)?)?;
+
+    let root = workspace_root(&params).unwrap_or(cmd.path);
+    let root = std::fs::canonicalize(&root)
+        .with_context(|| format!("failed to resolve workspace root {}", root.display()))?;
+    let options = cmd.index.options(&root);
+    let index = CodeIndex::load(&root, cmd.index.no_cache, options);
+    main_loop(connection, LspServer::new(index))?;
+    io_threads.join()?;
+    Ok(())
+}
+
+fn server_capabilities() -> ServerCapabilities {
+    ServerCapabilities {
+        text_document_sync: Some(TextDocumentSyncCapability::Options(
+            TextDocumentSyncOptions {
+                open_close: Some(true),
+                change: Some(TextDocumentSyncKind::FULL),
+                save: Some(TextDocumentSyncSaveOptions::Supported(true)),
+                ..Default::default()
+            },
+        )),
+        definition_provider: Some(OneOf::Left(true)),
+        references_provider: Some(OneOf::Left(true)),
+        workspace_symbol_provider: Some(OneOf::Left(true)),
+        ..Default::default()
+    }
+}
+
+#[allow(deprecated)]
+fn workspace_root(params: &InitializeParams) -> Option<PathBuf> {
+    params
+        .workspace_folders
+        .as_ref()
+        .and_then(|folders| folders.first())
+        .map(|folder| &folder.uri)
+        .or(params.root_uri.as_ref())
+        .and_then(|uri| uri.to_file_path().ok())
+}
+
+fn main_loop(connection: Connection, mut server: LspServer) -> anyhow::Result<()> {
+    for message in &connection.receiver {
+        match message {
+            Message::Request(request) => {
+                if connection.handle_shutdown(&request)? {
+                    break;
+                }
+                let response = server.handle_request(request);
+                connection.sender.send(Message::Response(response))?;
+            }
+            Message::Notification(notification) => server.handle_notification(notification),
+            Message::Response(_) => {}
+        }
+    }
+    Ok(())
+}
+
+/// The code graph and keyword index for one workspace.
+struct CodeIndex {
+    root: String,
+    /// Limits for re-indexing saved files, as for the initial index.
+    options: IndexOptions,
+    graph: RepositoryGraph,
+    bm25: BM25Index,
+}
+
+impl CodeIndex {
+    /// Build from the cached `.happy/elements.bin` when it still matches the
+    /// working tree, re-indexing files changed since it was written;
+    /// otherwise index the repository and write the cache.
+    fn load(root: &Path, reindex: bool, options: IndexOptions) -> Self {
+        let root_str = root.to_string_lossy().to_string();
+        let LoadedIndex {
+            elements, status, ..
+        } = happy_core::store::cache::load_or_index(
+            root,
+            &LoadOptions {
+                use_cache: !reindex,
+                ..LoadOptions::new(&options)
+            },
+        );
+        tracing::info!(elements = elements.len(), ?status, "loaded code index");
+
+        let mut graph = RepositoryGraph::new();
+        graph.build_from_elements(&elements, &root_str);
+        if let Ok(renames) = happy_core::store::load_renames(&root.join(RENAMES_CACHE)) {
+            graph.restore_renames(renames);
+        }
+        let mut bm25 = BM25Index::new();
+        for elem in &elements {
+            bm25.add_element(elem);
+        }
+        Self {
+            root: root_str,
+            options,
+            graph,
+            bm25,
+        }
+    }
+
+    /// Re-index one file, or drop it when it no longer parses or exists.
+    /// Persists the rename redirects when the update detected a rename.
+    fn update_file(&mut self, path: &str) {
+        for id in self.graph.element_ids_for_file(path) {
+            self.bm25.remove_document(&id);
+        }
+        let renames_before = self.graph.recent_renames(1);
+        match happy_core::indexer::index_single_file(path, &self.root, &self.options) {
+            Some(elements) => {
+                self.graph.update_file(path, &elements, &self.root);
+                for elem in &elements {
+                    self.bm25.add_element(elem);
+                }
+            }
+            None => self.graph.remove_file(path),
+        }
+        if self.graph.recent_renames(1) != renames_before {
+            let cache = Path::new(&self.root).join(RENAMES_CACHE);
+            if let Err(err) = happy_core::store::save_renames(self.graph.renames(), &cache) {
+                tracing::warn!(error = %err, "failed to write rename cache");
+            }
+        }
+    }
+
+    /// Definitions of `symbol`, preferring those in `current_file`.
+    fn definitions(&self, symbol: &str, current_file: &str) -> Vec<CodeElement> {
+        let mut matches = self.graph.resolve_symbol(symbol);
+        matches.sort_by_key(|(file, _)| file != current_file);
+        matches
+            .into_iter()
+            .filter_map(|(_, id)| self.graph.get_element(&id))
+            .collect()
+    }
+}
+
+struct LspServer {
+    index: CodeIndex,
+    /// Text of open documents by file path, kept in sync with the editor.
+    documents: HashMap<String, String>,
+}
+
+impl LspServer {
+    fn new(index: CodeIndex) -> Self {
+        Self {
+            index,
+            documents: HashMap::new(),
+        }
+    }
+
+    fn handle_request(&self, request: Request) -> Response {
+        let result = match request.method.as_str() {
+            "textDocument/definition" => parse::<GotoDefinitionParams>(request.params)
+                .and_then(|params| to_value(self.definition(params))),
+            "textDocument/references" => parse::<ReferenceParams>(request.params)
+                .and_then(|params| to_value(self.references(params))),
+            "workspace/symbol" => parse::<WorkspaceSymbolParams>(request.params)
+                .and_then(|params| to_value(self.workspace_symbols(&params.query))),
+            method => {
+                return Response::new_err(
+                    request.id,
+                    ErrorCode::MethodNotFound as i32,
+                    format!("unsupported request: {method}"),
+                );
+            }
+        };
+        match result {
+            Ok(value) => Response::new_ok(request.id, value),
+            Err(err) => Response::new_err(request.id, ErrorCode::InvalidParams as i32, err),
+        }
+    }
+
+    fn handle_notification(&mut self, notification: Notification) {
+        match notification.method.as_str() {
+            "textDocument/didOpen" => {
+                if let Ok(params) = parse::<DidOpenTextDocumentParams>(notification.params)
+                    && let Some(path) = uri_to_path(&params.text_document.uri)
+                {
+                    self.documents.insert(path, params.text_document.text);
+                }
+            }
+            "textDocument/didChange" => {
+                if let Ok(params) = parse::<DidChangeTextDocumentParams>(notification.params)
+                    && let Some(path) = uri_to_path(&params.text_document.uri)
+                    && let Some(change) = params.content_changes.into_iter().last()
+                {
+                    self.documents.insert(path, change.text);
+                }
+            }
+            "textDocument/didClose" => {
+                if let Ok(params) = parse::<DidCloseTextDocumentParams>(notification.params)
+                    && let Some(path) = uri_to_path(&params.text_document.uri)
+                {
+                    self.documents.remove(&path);
+                }
+            }
+            "textDocument/didSave" => {
+                if let Ok(params) = parse::<DidSaveTextDocumentParams>(notification.params)
+                    && let Some(path) = uri_to_path(&params.text_document.uri)
+                {
+                    if let Some(text) = params.text {
+                        self.documents.insert(path.clone(), text);
+                    }
+                    self.index.update_file(&path);
+                    tracing::debug!(file = %path, "re-indexed saved file");
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Current text of a file: the editor's copy when open, else the disk.
+    fn text(&self, path: &str) -> Option<String> {
+        self.documents
+            .get(path)
+            .cloned()
+            .or_else(|| std::fs::read_to_string(path).ok())
+    }
+
+    /// The identifier under the cursor and the file it is in.
+    fn symbol_at(&self, uri: &Url, position: Position) -> Option<(String, String)> {
+        let path = uri_to_path(uri)?;
+        let text = self.text(&path)?;
+        let word = word_at(&text, position)?.to_string();
+        Some((path, word))
+    }
+
+    fn definition(&self, params: GotoDefinitionParams) -> GotoDefinitionResponse {
+        let position = params.text_document_position_params;
+        let locations = self
+            .symbol_at(&position.text_document.uri, position.position)
+            .map(|(path, word)| {
+                self.index
+                    .definitions(&word, &path)
+                    .iter()
+                    .filter_map(|elem| self.definition_location(elem))
+                    .collect()
+            })
+            .unwrap_or_default();
+        GotoDefinitionResponse::Array(locations)
+    }
+
+    /// Location of an element's name on its first line.
+    fn definition_location(&self, elem: &CodeElement) -> Option<Location> {
+        let line_no = elem.start_line.saturating_sub(1);
+        let line = self
+            .text(&elem.file_path)
+            .and_then(|text| text.lines().nth(line_no).map(str::to_string))
+            .unwrap_or_default();
+        let range = word_occurrences(&line, &elem.name)
+            .first()
+            .map(|&start| span(&line, line_no, start, elem.name.len()))
+            .unwrap_or_else(|| Range::new(position(line_no, 0), position(line_no, 0)));
+        Some(Location::new(path_to_uri(&elem.file_path)?, range))
+    }
+
+    fn references(&self, params: ReferenceParams) -> Vec<Location> {
+        let position = params.text_document_position;
+        let Some((path, word)) = self.symbol_at(&position.text_document.uri, position.position)
+        else {
+            return Vec::new();
+        };
+        let definitions = self.index.definitions(&word, &path);
+
+        let mut locations = Vec::new();
+        if params.context.include_declaration {
+            locations.extend(
+                definitions
+                    .iter()
+                    .filter_map(|elem| self.definition_location(elem)),
+            );
+        }
+
+        // Call sites inside each caller's body, and strings naming the
+        // symbol when the repo detects string references.
+        let callers = self.index.graph.find_callers(&word);
+        let referrers = self.index.graph.find_string_references(&word);
+        for caller in callers.into_iter().chain(referrers) {
+            let lines = caller.start_line.saturating_sub(1)..caller.end_line;
+            locations.extend(self.occurrences(&caller.file_path, &word, lines));
+        }
+
+        // Uses in files that import a defining file.
+        let defining_files: HashSet<&str> = definitions
+            .iter()
+            .map(|elem| elem.file_path.as_str())
+            .collect();
+        for file in defining_files {
+            for importer in self.index.graph.get_dependents(file).unwrap_or_default() {
+                locations.extend(self.occurrences(&importer.file_path, &word, 0..usize::MAX));
+            }
+        }
+
+        let mut seen = HashSet::new();
+        locations.retain(|location| {
+            seen.insert((
+                location.uri.to_string(),
+                location.range.start.line,
+                location.range.start.character,
+            ))
+        });
+        locations
+    }
+
+    /// Every occurrence of `word` in `path` on the given 0-based lines.
+    fn occurrences(&self, path: &str, word: &str, lines: std::ops::Range<usize>) -> Vec<Location> {
+        let (Some(text), Some(uri)) = (self.text(path), path_to_uri(path)) else {
+            return Vec::new();
+        };
+        text.lines()
+            .enumerate()
+            .skip(lines.start)
+            .take(lines.end.saturating_sub(lines.start))
+            .flat_map(|(line_no, line)| {
+                word_occurrences(line, word)
+                    .into_iter()
+                    .map(move |start| span(line, line_no, start, word.len()))
+            })
+            .map(|range| Location::new(uri.clone(), range))
+            .collect()
+    }
+
+    /// Exact name matches first, then BM25 hits.
+    #[allow(deprecated)]
+    fn workspace_symbols(&self, query: &str) -> Vec<SymbolInformation> {
+        let query = query.trim();
+        if query.is_empty() {
+            return Vec::new();
+        }
+        let mut ids: Vec<String> = self
+            .index
+            .graph
+            .resolve_symbol(query)
+            .into_iter()
+            .map(|(_, id)| id)
+            .collect();
+        ids.extend(
+            self.index
+                .bm25
+                .search(query, WORKSPACE_SYMBOL_LIMIT)
+                .into_iter()
+                .map(|(id, _)| id),
+        );
+
+        let mut seen = HashSet::new();
+        ids.into_iter()
+            .filter(|id| seen.insert(id.clone()))
+            .filter_map(|id| self.index.graph.get_element(&id))
+            .filter(|elem| {
+                !matches!(
+                    elem.element_type,
+                    ElementType::File | ElementType::Import | ElementType::Chunk
+                )
+            })
+            .filter_map(|elem| {
+                let start = position(elem.start_line.saturating_sub(1), 0);
+                let end = position(elem.end_line.saturating_sub(1), 0);
+                Some(SymbolInformation {
+                    name: elem.name,
+                    kind: symbol_kind(elem.element_type),
+                    tags: None,
+                    deprecated: None,
+                    location: Location::new(path_to_uri(&elem.file_path)?, Range::new(start, end)),
+                    container_name: Some(elem.relative_path),
+                })
+            })
+            .take(WORKSPACE_SYMBOL_LIMIT)
+            .collect()
+    }
+}
+
+fn parse<T: DeserializeOwned>(params: serde_json::Value) -> Result<T, String> {
+    serde_json::from_value(params).map_err(|err| format!("invalid params: {err}"))
+}
+
+fn to_value<T: serde::Serialize>(result: T) -> Result<serde_json::Value, String> {
+    serde_json::to_value(result).map_err(|err| format!("failed to encode result: {err}"))
+}
+
+fn symbol_kind(element_type: ElementType) -> SymbolKind {
+    match element_type {
+        ElementType::File => SymbolKind::FILE,
+        ElementType::Class => SymbolKind::CLASS,
+        ElementType::Function => SymbolKind::FUNCTION,
+        ElementType::Method => SymbolKind::METHOD,
+        ElementType::Module | ElementType::Import => SymbolKind::MODULE,
+        ElementType::Variable => SymbolKind::VARIABLE,
+        ElementType::Constant => SymbolKind::CONSTANT,
+        ElementType::TypeAlias => SymbolKind::TYPE_PARAMETER,
+        ElementType::Interface => SymbolKind::INTERFACE,
+        ElementType::Struct => SymbolKind::STRUCT,
+        ElementType::Enum => SymbolKind::ENUM,
+        ElementType::Chunk => SymbolKind::STRING,
+    }
+}
+
+fn uri_to_path(uri: &Url) -> Option<String> {
+    uri.to_file_path()
+        .ok()
+        .map(|path| path.to_string_lossy().to_string())
+}
+
+fn path_to_uri(path: &str) -> Option<Url> {
+    Url::from_file_path(path).ok()
+}
+
+fn position(line: usize, character: usize) -> Position {
+    Position::new(line as u32, character as u32)
+}
+
+/// Range of `len` bytes at byte offset `start` of `line`, in UTF-16 columns.
+fn span(line: &str, line_no: usize, start: usize, len: usize) -> Range {
+    let column = |byte: usize| line[..byte].encode_utf16().count();
+    Range::new(
+        position(line_no, column(start)),
+        position(line_no, column(start + len)),
+    )
+}
+
+fn is_ident_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '$'
+}
+
+/// The identifier at an LSP position (UTF-16 column), if any.
+fn word_at(text: &str, position: Position) -> Option<&str> {
+    let line = text.lines().nth(position.line as usize)?;
+    let mut units = 0;
+    let cursor = line
+        .char_indices()
+        .find(|(_, c)| {
+            units += c.len_utf16();
+            units > position.character as usize
+        })
+        .map_or(line.len(), |(i, _)| i);
+    let start = line[..cursor]
+        .char_indices()
+        .rev()
+        .find(|&(_, c)| !is_ident_char(c))
+        .map_or(0, |(i, c)| i + c.len_utf8());
+    let end = line[cursor..]
+        .find(|c: char| !is_ident_char(c))
+        .map_or(line.len(), |i| cursor + i);
+    (start < end).then(|| &line[start..end])
+}
+
+/// Byte offsets where `word` appears in `line` as a whole identifier.
+fn word_occurrences(line: &str, word: &str) -> Vec<usize> {
+    if word.is_empty() {
+        return Vec::new();
+    }
+    line.match_indices(word)
+        .map(|(start, _)| start)
+        .filter(|&start| {
+            let before = line[..start].chars().next_back();
+            let after = line[start + word.len()..].chars().next();
+            !before.is_some_and(is_ident_char) && !after.is_some_and(is_ident_char)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lsp_types::PartialResultParams;
+    use lsp_types::ReferenceContext;
+    use lsp_types::TextDocumentIdentifier;
+    use lsp_types::TextDocumentPositionParams;
+    use lsp_types::WorkDoneProgressParams;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn word_at_finds_identifier_under_cursor() {
+        let text = "x = 1\nresult = helper_fn(value)\n";
+        assert_eq!(word_at(text, Position::new(1, 12)), Some("helper_fn"));
+        assert_eq!(word_at(text, Position::new(1, 9)), Some("helper_fn"));
+        assert_eq!(word_at(text, Position::new(1, 18)), Some("helper_fn"));
+        assert_eq!(word_at(text, Position::new(1, 7)), None);
+        assert_eq!(word_at("é = foo", Position::new(0, 5)), Some("foo"));
+
+        assert_eq!(
+            word_occurrences("helper(helpers, helper)", "helper"),
+            vec![0, 16]
+        );
+    }
+
+    #[test]
+    fn definition_and_references_use_the_graph() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        std::fs::write(dir.path().join("a.py"), "def helper(x):\n    return x\n")
+            .expect("write a.py");
+        std::fs::write(
+            dir.path().join("b.py"),
+            "from a import helper\n\ndef main():\n    return helper(1)\n",
+        )
+        .expect("write b.py");
+        let root = std::fs::canonicalize(dir.path()).expect("canonicalize");
+        let server = LspServer::new(CodeIndex::load(&root, false, IndexOptions::default()));
+        assert!(root.join(happy_core::store::cache::ELEMENTS_CACHE).exists());
+
+        let uri = Url::from_file_path(root.join("b.py")).expect("uri");
+        let at_call = TextDocumentPositionParams::new(
+            TextDocumentIdentifier::new(uri.clone()),
+            Position::new(3, 12),
+        );
+
+        let GotoDefinitionResponse::Array(definitions) = server.definition(GotoDefinitionParams {
+            text_document_position_params: at_call.clone(),
+            work_done_progress_params: WorkDoneProgressParams::default(),
+            partial_result_params: PartialResultParams::default(),
+        }) else {
+            panic!("expected an array of locations");
+        };
+        let a_uri = Url::from_file_path(root.join("a.py")).expect("uri");
+        assert_eq!(
+            definitions,
+            vec![Location::new(
+                a_uri,
+                Range::new(Position::new(0, 4), Position::new(0, 10))
+            )]
+        );
+
+        let references = server.references(ReferenceParams {
+            text_document_position: at_call,
+            work_done_progress_params: WorkDoneProgressParams::default(),
+            partial_result_params: PartialResultParams::default(),
+            context: ReferenceContext {
+                include_declaration: false,
+            },
+        });
+        assert!(references.contains(&Location::new(
+            uri,
+            Range::new(Position::new(3, 11), Position::new(3, 17))
+        )));
+
+        let symbols = server.workspace_symbols("helper");
+        assert_eq!(symbols.first().map(|s| s.name.as_str()), Some("helper"));
+    }
+}
@@ -31,15 +31,50 @@ use std::io::IsTerminal;
 use std::path::PathBuf;
 use supports_color::Stream;
 
+mod analyze_cmd;
 #[cfg(target_os = "macos")]
 mod app_cmd;
+mod bookmark_cmd;
+mod cycles_cmd;
 #[cfg(target_os = "macos")]
 mod desktop_app;
+mod diff_impact_cmd;
+mod export_cmd;
+mod index_args;
+mod index_cmd;
+mod ls_cmd;
+mod lsp_cmd;
 mod mcp_cmd;
+mod path_cmd;
+mod query_cmd;
+mod search_cmd;
+mod search_eval_cmd;
+mod serve_cmd;
+mod session_cmd;
+mod stats_cmd;
+mod summarize_cmd;
+mod wait_cmd;
 #[cfg(not(windows))]
 mod wsl_paths;
 
+use crate::analyze_cmd::AnalyzeCommand;
+use crate::bookmark_cmd::BookmarkCommand;
+use crate::cycles_cmd::CyclesCommand;
+use crate::diff_impact_cmd::DiffImpactCommand;
+use crate::export_cmd::ExportCommand;
+use crate::index_cmd::IndexCommand;
+use crate::ls_cmd::LsCommand;
+use crate::lsp_cmd::LspCommand;
 use crate::mcp_cmd::McpCli;
+use crate::path_cmd::PathCommand;
+use crate::query_cmd::QueryCommand;
+use crate::search_cmd::SearchCommand;
+use crate::search_eval_cmd::SearchEvalCommand;
+use crate::serve_cmd::ServeCommand;
+use crate::session_cmd::SessionCli;
+use crate::stats_cmd::StatsCommand;
+use crate::summarize_cmd::SummarizeCommand;
+use crate::wait_cmd::WaitForIndexCommand;
 
 use codex_core::config::Config;
 use codex_core::config::ConfigOverrides;
@@ -99,6 +134,55 @@ enum Subcommand {
     /// Start HappyCode as an MCP server (stdio).
     McpServer,
 
+    /// Index a repository and serve its code graph over a local JSON-RPC socket.
+    Serve(ServeCommand),
+
+    /// Index a repository, or several as one workspace, and update their caches.
+    Index(IndexCommand),
+
+    /// Run a Language Server (stdio) backed by the code graph.
+    Lsp(LspCommand),
+
+    /// Index a repository and list its circular imports.
+    Cycles(CyclesCommand),
+
+    /// Show code graph counts and how fresh the `.happy` cache is.
+    Stats(StatsCommand),
+
+    /// List indexed files by path, or with the most imported files first.
+    Ls(LsCommand),
+
+    /// Export the code graph as Graphviz DOT, GraphML, or JSON.
+    Export(ExportCommand),
+
+    /// Report a file's outline, imports, outgoing calls, outside uses, and diagnostics.
+    Analyze(AnalyzeCommand),
+
+    /// List a symbol's callers or callees, optionally scoped by file glob and kind.
+    Query(QueryCommand),
+
+    /// Show the shortest path between two symbols, with the kind of each edge.
+    Path(PathCommand),
+
+    /// Keyword search over indexed code, optionally scoped by language, kind, and path.
+    Search(SearchCommand),
+
+    /// List the symbols and files affected, transitively, by uncommitted changes.
+    DiffImpact(DiffImpactCommand),
+
+    /// Fill in one-line summaries of functions, classes, and files for search.
+    Summarize(SummarizeCommand),
+
+    /// Add, list, or remove notes bookmarked on code elements.
+    Bookmark(BookmarkCommand),
+
+    /// Wait until `serve --notify` reports a completed index, then print the event.
+    WaitForIndex(WaitForIndexCommand),
+
+    /// Score search relevance against a judgments fixture (nDCG@10, MRR, recall@20).
+    #[clap(hide = true)]
+    SearchEval(SearchEvalCommand),
+
     /// [experimental] Run the app server or related tooling.
     AppServer(AppServerCommand),
 
@@ -129,6 +213,9 @@ enum Subcommand {
     /// Fork a previous interactive session (picker by default; use --last to fork the most recent).
     Fork(ForkCommand),
 
+    /// Export a session to a handoff bundle, or import one from a teammate.
+    Session(SessionCli),
+
     /// [EXPERIMENTAL] Browse tasks from HappyCode Cloud and apply changes locally.
     #[clap(name = "cloud", alias = "cloud-tasks")]
     Cloud(CloudTasksCli),
@@ -592,6 +679,61 @@ async fn cli_main(codex_linux_sandbox_exe: Option<PathBuf>) -> anyhow::Result<()
         Some(Subcommand::McpServer) => {
             codex_mcp_server::run_main(codex_linux_sandbox_exe, root_config_overrides).await?;
         }
+        Some(Subcommand::Serve(serve_cli)) => {
+            serve_cmd::run_serve(serve_cli, root_config_overrides.clone()).await?;
+        }
+        Some(Subcommand::Index(index_cli)) => {
+            index_cmd::run_index(index_cli, root_config_overrides.clone()).await?;
+        }
+        Some(Subcommand::Lsp(lsp_cli)) => {
+            lsp_cmd::run_lsp(lsp_cli).await?;
+        }
+        Some(Subcommand::Cycles(cycles_cli)) => {
+            cycles_cmd::run_cycles(cycles_cli).await?;
+        }
+        Some(Subcommand::Stats(stats_cli)) => {
+            stats_cmd::run_stats(stats_cli).await?;
+        }
+        Some(Subcommand::Ls(ls_cli)) => {
+            ls_cmd::run_ls(ls_cli).await?;
+        }
+        Some(Subcommand::Export(export_cli)) => {
+            export_cmd::run_export(export_cli).await?;
+        }
+        Some(Subcommand::Analyze(analyze_cli)) => {
+            analyze_cmd::run_analyze(analyze_cli).await?;
+        }
+        Some(Subcommand::Query(query_cli)) => {
+            query_cmd::run_query(query_cli).await?;
+        }
+        Some(Subcommand::Path(path_cli)) => {
+            path_cmd::run_path(path_cli).await?;
+        }
+        Some(Subcommand::Search(search_cli)) => {
+            search_cmd::run_search(search_cli, root_config_overrides.clone()).await?;
+        }
+        Some(Subcommand::DiffImpact(diff_impact_cli)) => {
+            diff_impact_cmd::run_diff_impact(diff_impact_cli).await?;
+        }
+        Some(Subcommand::Summarize(summarize_cli)) => {
+            summarize_cmd::run_summarize(summarize_cli, root_config_overrides.clone()).await?;
+        }
+        Some(Subcommand::Bookmark(bookmark_cli)) => {
+            bookmark_cmd::run_bookmark(bookmark_cli).await?;
+        }
+        Some(Subcommand::WaitForIndex(wait_cli)) => {
+            wait_cmd::run_wait_for_index(wait_cli).await?;
+        }
+        Some(Subcommand::SearchEval(search_eval_cli)) => {
+            search_eval_cmd::run_search_eval(search_eval_cli).await?;
+        }
+        Some(Subcommand::Session(mut session_cli)) => {
+            prepend_config_flags(
+                &mut session_cli.config_overrides,
+                root_config_overrides.clone(),
+            );
+            session_cli.run().await?;
+        }
         Some(Subcommand::Mcp(mut mcp_cli)) => {
             // Propagate any root-level config overrides (e.g. `-c key=value`).
             prepend_config_flags(&mut mcp_cli.config_overrides, root_config_overrides.clone());
@@ -1016,6 +1158,15 @@ fn merge_interactive_cli_flags(interactive: &mut TuiCli, subcommand_cli: TuiCli)
     if !subcommand_cli.add_dir.is_empty() {
         interactive.add_dir.extend(subcommand_cli.add_dir);
     }
+    if subcommand_cli.verbose > 0 {
+        interactive.verbose = subcommand_cli.verbose;
+    }
+    if let Some(log_file) = subcommand_cli.log_file {
+        interactive.log_file = Some(log_file);
+    }
+    if subcommand_cli.reindex {
+        interactive.reindex = true;
+    }
     if let Some(prompt) = subcommand_cli.prompt {
         // Normalize CRLF/CR to LF so CLI-provided text can't leak `\r` into TUI state.
         interactive.prompt = Some(prompt.replace("\r\n", "\n").replace('\r', "\n"));
@@ -1310,6 +1461,35 @@ mod tests {
         assert_eq!(interactive.resume_session_id, None);
     }
 
+    #[test]
+    fn resume_merges_logging_flags() {
+        let interactive = finalize_resume_from_args(
+            ["codex", "resume", "-vv", "--log-file", "/tmp/happy.log"].as_ref(),
+        );
+        assert_eq!(interactive.verbose, 2);
+        assert_eq!(
+            interactive.log_file,
+            Some(std::path::PathBuf::from("/tmp/happy.log"))
+        );
+
+        let interactive =
+            finalize_fork_from_args(["codex", "fork", "--verbose", "--last"].as_ref());
+        assert_eq!(interactive.verbose, 1);
+    }
+
+    #[test]
+    fn resume_and_fork_merge_reindex_flag() {
+        let interactive = finalize_resume_from_args(["codex", "resume", "--reindex"].as_ref());
+        assert!(interactive.reindex);
+
+        let interactive =
+            finalize_fork_from_args(["codex", "fork", "--last", "--reindex"].as_ref());
+        assert!(interactive.reindex);
+
+        let interactive = finalize_resume_from_args(["codex", "resume", "--last"].as_ref());
+        assert!(!interactive.reindex);
+    }
+
     #[test]
     fn fork_picker_logic_none_and_not_last() {
         let interactive = finalize_fork_from_args(["codex", "fork"].as_ref());
@@ -0,0 +1,136 @@
+use crate::index_args::IndexArgs;
+use anyhow::Context;
+use clap::Parser;
+use happy_core::graph::RepositoryGraph;
+use happy_core::graph::queries::DEFAULT_MAX_PATH_LEN;
+use happy_core::graph::queries::GraphPath;
+use happy_core::graph::types::EdgeKind;
+use happy_core::graph::types::GraphNode;
+use happy_core::utils::relative_to;
+use serde::Serialize;
+use std::path::PathBuf;
+
+#[derive(Debug, Parser)]
+pub struct PathCommand {
+    /// Repository to query.
+    #[arg(value_name = "PATH")]
+    pub path: PathBuf,
+
+    /// Symbol the path starts from.
+    #[arg(value_name = "SOURCE")]
+    pub source: String,
+
+    /// Symbol the path ends at.
+    #[arg(value_name = "TARGET")]
+    pub target: String,
+
+    /// Only follow these edge kinds, e.g. `calls` or `calls,imports`.
+    /// Every kind but `defines` is followed by default.
+    #[arg(long = "edges", value_name = "KINDS", value_delimiter = ',')]
+    pub edge_kinds: Vec<EdgeKind>,
+
+    /// List up to this many distinct paths instead of the shortest one.
+    /// Among paths of the same length, those through widely used helpers
+    /// such as loggers come last.
+    #[arg(short = 'k', value_name = "N")]
+    pub k: Option<usize>,
+
+    /// With `-k`, the most edges a path may have.
+    #[arg(long, value_name = "EDGES", default_value_t = DEFAULT_MAX_PATH_LEN)]
+    pub max_len: usize,
+
+    /// Print the path as JSON; with `-k`, a list of paths.
+    #[arg(long)]
+    pub json: bool,
+
+    #[command(flatten)]
+    pub index: IndexArgs,
+}
+
+#[derive(Debug, Serialize)]
+struct PathStep {
+    #[serde(flatten)]
+    node: GraphNode,
+    /// The kind of the edge from the previous step; `None` for the first.
+    via: Option<EdgeKind>,
+}
+
+pub async fn run_path(cmd: PathCommand) -> anyhow::Result<()> {
+    let repo_root = std::fs::canonicalize(&cmd.path)
+        .with_context(|| format!("failed to resolve {}", cmd.path.display()))?;
+    let source = cmd.source.clone();
+    let target = cmd.target.clone();
+    let edge_kinds = cmd.edge_kinds.clone();
+    let (k, max_len) = (cmd.k, cmd.max_len);
+    let options = cmd.index.options(&repo_root);
+    let workspace = cmd.index.workspace(&repo_root)?;
+    let found = tokio::task::spawn_blocking(move || {
+        let elements = workspace
+            .load_or_index(&cmd.index.load_options(&options))
+            .elements;
+        let mut graph = RepositoryGraph::new();
+        graph.build_from_workspace(&elements, &workspace);
+        graph.check_symbol(&source)?;
+        graph.check_symbol(&target)?;
+        let paths = match k {
+            Some(k) => graph.find_paths(&source, &target, &edge_kinds, k, max_len),
+            None => graph
+                .find_path(&source, &target, &edge_kinds)
+                .into_iter()
+                .collect(),
+        };
+        anyhow::Ok(
+            paths
+                .iter()
+                .map(|path| (path.to_string(), steps(path)))
+                .collect::<Vec<_>>(),
+        )
+    })
+    .await??;
+
+    #[allow(clippy::print_stdout)]
+    {
+        if cmd.json {
+            let steps: Vec<&Vec<PathStep>> = found.iter().map(|(_, steps)| steps).collect();
+            match cmd.k {
+                Some(_) => println!("{}", serde_json::to_string_pretty(&steps)?),
+                None => println!("{}", serde_json::to_string_pretty(&steps.first())?),
+            }
+            return Ok(());
+        }
+        if found.is_empty() {
+            println!("No path from {} to {}", cmd.source, cmd.target);
+            return Ok(());
+        }
+        for (i, (annotated, steps)) in found.iter().enumerate() {
+            if i > 0 {
+                println!();
+            }
+            println!("{annotated}");
+            for step in steps {
+                println!(
+                    "  {:<10} {:<10} {}  {}:{}",
+                    step.via
+                        .map(|kind| format!("{kind:?}").to_ascii_lowercase())
+                        .unwrap_or_default(),
+                    step.node.kind.as_str(),
+                    step.node.name,
+                    relative_to(&repo_root, &step.node.file_path),
+                    step.node.start_line,
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+fn steps(path: &GraphPath<'_>) -> Vec<PathStep> {
+    path.nodes
+        .iter()
+        .enumerate()
+        .map(|(i, node)| PathStep {
+            node: (*node).clone(),
+            via: i.checked_sub(1).map(|i| path.via[i]),
+        })
+        .collect()
+}
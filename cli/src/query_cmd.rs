@@ -0,0 +1,298 @@
+use crate::index_args::IndexArgs;
+use anyhow::Context;
+use clap::Parser;
+use happy_core::graph::RepositoryGraph;
+use happy_core::graph::queries::DEFAULT_RELATED_HOPS;
+use happy_core::graph::queries::DEFAULT_TRANSITIVE_DEPTH;
+use happy_core::graph::queries::DEFAULT_TRANSITIVE_LIMIT;
+use happy_core::graph::queries::DEFAULT_UNREFERENCED_KINDS;
+use happy_core::graph::queries::FileGlob;
+use happy_core::graph::queries::QueryFilter;
+use happy_core::graph::queries::RelatedDirection;
+use happy_core::graph::types::EdgeKind;
+use happy_core::graph::types::GraphNode;
+use happy_core::graph::types::NodeKind;
+use happy_core::utils::relative_to;
+use serde::Serialize;
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+#[derive(Debug, Parser)]
+pub struct QueryCommand {
+    /// Repository to query.
+    #[arg(value_name = "PATH")]
+    pub path: PathBuf,
+
+    /// Symbol to query; qualify members as `Class.method` or `Type::method`.
+    #[arg(value_name = "SYMBOL", required_unless_present = "unreferenced")]
+    pub symbol: Option<String>,
+
+    /// `callers` lists what calls the symbol, `callees` what it calls,
+    /// `callers-transitive` callers up to `--depth` hops away, and `related`
+    /// anything within `--depth` hops.
+    #[arg(
+        long = "type",
+        short = 't',
+        value_name = "TYPE",
+        default_value = "callers"
+    )]
+    pub query_type: QueryType,
+
+    /// Only query definitions of the symbol in files matching this glob,
+    /// relative to the repository root, e.g. `src/api/*.py` or `*.rs`.
+    #[arg(long, value_name = "GLOB")]
+    pub file: Option<String>,
+
+    /// Only list results in files matching this glob.
+    #[arg(long, value_name = "GLOB")]
+    pub result_file: Option<String>,
+
+    /// Only query definitions of the symbol of these kinds, e.g. `function`
+    /// or `method,class`.
+    #[arg(long, value_name = "KINDS", value_delimiter = ',')]
+    pub kind: Vec<NodeKind>,
+
+    /// Hops to follow for `callers-transitive` (default 3) and `related`
+    /// (default 2).
+    #[arg(long, value_name = "N")]
+    pub depth: Option<usize>,
+
+    /// Edge direction `related` follows: `in`, `out`, or `both`.
+    #[arg(long, value_name = "DIR", default_value = "both")]
+    pub direction: RelatedDirection,
+
+    /// Edge kinds `related` follows, e.g. `calls,inherits`; all when unset.
+    #[arg(long, value_name = "KINDS", value_delimiter = ',')]
+    pub edge_kinds: Vec<EdgeKind>,
+
+    /// Group `related` results by relationship (callers, callees, importers,
+    /// ...) instead of listing them by distance.
+    #[arg(long)]
+    pub summarize: bool,
+
+    /// List likely dead code instead of querying a symbol: elements of the
+    /// `--kind` kinds (functions, methods and classes by default) that
+    /// nothing references.
+    #[arg(long, conflicts_with = "symbol")]
+    pub unreferenced: bool,
+
+    /// Print the results as JSON.
+    #[arg(long)]
+    pub json: bool,
+
+    #[command(flatten)]
+    pub index: IndexArgs,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QueryType {
+    Callers,
+    Callees,
+    CallersTransitive,
+    Related,
+}
+
+impl QueryType {
+    fn label(self) -> &'static str {
+        match self {
+            QueryType::Callers => "callers",
+            QueryType::Callees => "callees",
+            QueryType::CallersTransitive => "transitive callers",
+            QueryType::Related => "related elements",
+        }
+    }
+}
+
+impl FromStr for QueryType {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "callers" => Ok(QueryType::Callers),
+            "callees" => Ok(QueryType::Callees),
+            "callers-transitive" => Ok(QueryType::CallersTransitive),
+            "related" => Ok(QueryType::Related),
+            other => Err(format!(
+                "unknown query type '{other}', expected callers, callees, callers-transitive, or related"
+            )),
+        }
+    }
+}
+
+/// One block of results, under a heading when a query returns several.
+#[derive(Debug, Serialize)]
+struct ResultGroup {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    heading: Option<String>,
+    nodes: Vec<GraphNode>,
+}
+
+impl ResultGroup {
+    fn plain(nodes: Vec<&GraphNode>) -> Self {
+        Self {
+            heading: None,
+            nodes: nodes.into_iter().cloned().collect(),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct QueryResults {
+    groups: Vec<ResultGroup>,
+    /// IDs of results reached only over low-confidence call edges.
+    guessed: HashSet<String>,
+    /// Whether a transitive query stopped at its node cap.
+    truncated: bool,
+}
+
+pub async fn run_query(cmd: QueryCommand) -> anyhow::Result<()> {
+    let repo_root = std::fs::canonicalize(&cmd.path)
+        .with_context(|| format!("failed to resolve {}", cmd.path.display()))?;
+    let filter = QueryFilter {
+        symbol_files: cmd.file.as_deref().map(file_glob).transpose()?,
+        kinds: cmd.kind.clone(),
+        result_files: cmd.result_file.as_deref().map(file_glob).transpose()?,
+    };
+    let symbol = cmd.symbol.clone().unwrap_or_default();
+    let query_type = cmd.query_type;
+    let unreferenced = cmd.unreferenced;
+    let depth = cmd.depth;
+    let direction = cmd.direction;
+    let edge_kinds = cmd.edge_kinds.clone();
+    let summarize = cmd.summarize;
+    let options = cmd.index.options(&repo_root);
+    let workspace = cmd.index.workspace(&repo_root)?;
+    let results = tokio::task::spawn_blocking(move || {
+        let elements = workspace
+            .load_or_index(&cmd.index.load_options(&options))
+            .elements;
+        let mut graph = RepositoryGraph::new();
+        graph.build_from_workspace(&elements, &workspace);
+        if unreferenced {
+            let kinds = if filter.kinds.is_empty() {
+                DEFAULT_UNREFERENCED_KINDS.to_vec()
+            } else {
+                filter.kinds.clone()
+            };
+            return anyhow::Ok(QueryResults {
+                groups: vec![ResultGroup::plain(graph.find_unreferenced(&kinds))],
+                ..QueryResults::default()
+            });
+        }
+        graph.check_symbol(&symbol)?;
+        let results = match query_type {
+            QueryType::Callers => QueryResults {
+                groups: vec![ResultGroup::plain(
+                    graph.find_callers_filtered(&symbol, &filter),
+                )],
+                guessed: graph.low_confidence_callers(&symbol, &filter),
+                truncated: false,
+            },
+            QueryType::Callees => QueryResults {
+                groups: vec![ResultGroup::plain(
+                    graph.find_callees_filtered(&symbol, &filter),
+                )],
+                guessed: graph.low_confidence_callees(&symbol, &filter),
+                truncated: false,
+            },
+            QueryType::CallersTransitive => {
+                let chain = graph.find_callers_transitive(
+                    &symbol,
+                    depth.unwrap_or(DEFAULT_TRANSITIVE_DEPTH),
+                    DEFAULT_TRANSITIVE_LIMIT,
+                );
+                QueryResults {
+                    groups: chain
+                        .levels
+                        .into_iter()
+                        .enumerate()
+                        .map(|(level, nodes)| ResultGroup {
+                            heading: Some(format!("depth {}", level + 1)),
+                            ..ResultGroup::plain(nodes)
+                        })
+                        .collect(),
+                    guessed: HashSet::new(),
+                    truncated: chain.truncated,
+                }
+            }
+            QueryType::Related if summarize => QueryResults {
+                groups: graph
+                    .summarize_related(&symbol, direction, &edge_kinds)
+                    .into_iter()
+                    .map(|group| ResultGroup {
+                        heading: Some(format!("{} ({})", group.name, group.total)),
+                        ..ResultGroup::plain(group.nodes)
+                    })
+                    .collect(),
+                ..QueryResults::default()
+            },
+            QueryType::Related => QueryResults {
+                groups: vec![ResultGroup::plain(graph.get_related_filtered(
+                    &symbol,
+                    depth.unwrap_or(DEFAULT_RELATED_HOPS),
+                    direction,
+                    &edge_kinds,
+                ))],
+                ..QueryResults::default()
+            },
+        };
+        anyhow::Ok(results)
+    })
+    .await??;
+
+    #[allow(clippy::print_stdout)]
+    {
+        if cmd.json {
+            match results.groups.as_slice() {
+                [group] if group.heading.is_none() => {
+                    println!("{}", serde_json::to_string_pretty(&group.nodes)?);
+                }
+                groups => println!("{}", serde_json::to_string_pretty(groups)?),
+            }
+            return Ok(());
+        }
+        if results
+            .groups
+            .iter()
+            .all(|group| group.heading.is_none() && group.nodes.is_empty())
+        {
+            match &cmd.symbol {
+                Some(symbol) => println!("No {} found for {symbol}", query_type.label()),
+                None => println!("No unreferenced elements found"),
+            }
+            return Ok(());
+        }
+        for group in &results.groups {
+            let indent = match &group.heading {
+                Some(heading) => {
+                    println!("{heading}:");
+                    "  "
+                }
+                None => "",
+            };
+            for node in &group.nodes {
+                println!(
+                    "{indent}{:<10} {}  {}:{}{}",
+                    node.kind.as_str(),
+                    node.name,
+                    relative_to(&repo_root, &node.file_path),
+                    node.start_line,
+                    if results.guessed.contains(&node.id) {
+                        "  (low confidence)"
+                    } else {
+                        ""
+                    }
+                );
+            }
+        }
+        if results.truncated {
+            println!("(stopped after {DEFAULT_TRANSITIVE_LIMIT} callers)");
+        }
+    }
+    Ok(())
+}
+
+fn file_glob(pattern: &str) -> anyhow::Result<FileGlob> {
+    FileGlob::new(pattern).with_context(|| format!("invalid file glob '{pattern}'"))
+}
@@ -0,0 +1,302 @@
+use crate::index_args::IndexArgs;
+use anyhow::Context;
+use clap::Parser;
+use clap::ValueEnum;
+use codex_core::CodeGraphUserConfig;
+use codex_core::config::Config;
+use codex_utils_cli::CliConfigOverrides;
+use happy_core::graph::queries::FileGlob;
+use happy_core::graph::queries::SearchFilter;
+use happy_core::graph::snippet::Snippet;
+use happy_core::graph::types::NodeKind;
+use happy_core::indexer::CodeElement;
+use happy_core::redact::Redactor;
+use happy_core::store::cache::CacheStatus;
+use happy_core::store::cache::LoadOptions;
+use happy_core::store::cache::LoadedIndex;
+use happy_core::vector::Embedder;
+use happy_core::vector::EmbeddingBudget;
+use happy_core::vector::EmbeddingClient;
+use happy_core::vector::EmbeddingConfig;
+use happy_core::vector::EmbeddingEndpoint;
+use happy_core::vector::HashingEmbedder;
+use happy_core::vector::VectorIndex;
+use happy_core::vector::bm25::BM25Index;
+use happy_core::vector::hybrid_search;
+use happy_core::vector::pipeline::embed_in_batches;
+use happy_core::workspace::Workspace;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::Path;
+use std::path::PathBuf;
+
+#[derive(Debug, Parser)]
+pub struct SearchCommand {
+    /// Keywords to search for.
+    #[arg(value_name = "QUERY")]
+    pub query: String,
+
+    /// Repository to search.
+    #[arg(value_name = "PATH", default_value = ".")]
+    pub path: PathBuf,
+
+    /// Number of results to print.
+    #[arg(long, short = 'k', default_value_t = 10)]
+    pub limit: usize,
+
+    /// Only return elements in these languages, e.g. `python,rust`.
+    #[arg(long = "lang", value_name = "LANGS", value_delimiter = ',')]
+    pub languages: Vec<String>,
+
+    /// Only return elements of these kinds, e.g. `function` or
+    /// `method,class`.
+    #[arg(long, value_name = "KINDS", value_delimiter = ',')]
+    pub kind: Vec<NodeKind>,
+
+    /// Only return elements in files matching this glob, relative to the
+    /// repository root, e.g. `src/api/**` or `*.rs`.
+    #[arg(long = "path", value_name = "GLOB")]
+    pub files: Option<String>,
+
+    /// `bm25` ranks by keywords, as `search_code` does; `hybrid` blends in
+    /// embedding similarity, as `semantic_search` does.
+    #[arg(long, value_enum, default_value_t = SearchMode::Bm25)]
+    pub mode: SearchMode,
+
+    /// With `--mode hybrid`, the weight of embedding similarity against
+    /// keyword score, from 0 to 1.
+    #[arg(long, default_value_t = 0.5)]
+    pub alpha: f64,
+
+    /// Print the results as JSON.
+    #[arg(long)]
+    pub json: bool,
+
+    #[command(flatten)]
+    pub index: IndexArgs,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum SearchMode {
+    Bm25,
+    Hybrid,
+}
+
+#[derive(Debug, Serialize)]
+struct SearchHit {
+    id: String,
+    score: f64,
+    kind: NodeKind,
+    name: String,
+    file_path: String,
+    start_line: usize,
+    /// File line number of the first snippet line.
+    snippet_line: usize,
+    /// The lines of the element that best match the query.
+    snippet: String,
+    /// Query terms found in the element's code, in query order.
+    matched_terms: Vec<String>,
+}
+
+pub async fn run_search(
+    cmd: SearchCommand,
+    config_overrides: CliConfigOverrides,
+) -> anyhow::Result<()> {
+    let repo_root = std::fs::canonicalize(&cmd.path)
+        .with_context(|| format!("failed to resolve {}", cmd.path.display()))?;
+    let filter = SearchFilter {
+        languages: cmd
+            .languages
+            .iter()
+            .map(|lang| lang.trim().to_ascii_lowercase())
+            .filter(|lang| !lang.is_empty())
+            .collect(),
+        kinds: cmd.kind.clone(),
+        files: cmd
+            .files
+            .as_deref()
+            .map(|pattern| {
+                FileGlob::new(pattern).with_context(|| format!("invalid file glob '{pattern}'"))
+            })
+            .transpose()?,
+    };
+    // Hybrid search embeds the query with the server under `[embeddings]`
+    // in the user's config, when its vectors are cached.
+    let endpoint = match cmd.mode {
+        SearchMode::Bm25 => None,
+        SearchMode::Hybrid => {
+            let overrides = config_overrides
+                .parse_overrides()
+                .map_err(anyhow::Error::msg)?;
+            let config = Config::load_with_cli_overrides(overrides)
+                .await
+                .context("failed to load configuration")?;
+            CodeGraphUserConfig::load(&config.config_layer_stack).embeddings
+        }
+    };
+    let query = cmd.query.clone();
+    let limit = cmd.limit;
+    let (mode, alpha) = (cmd.mode, cmd.alpha);
+    let options = cmd.index.options(&repo_root);
+    let workspace = cmd.index.workspace(&repo_root)?;
+    let hits = tokio::task::spawn_blocking(move || {
+        let (elements, bm25) =
+            load_elements_and_bm25(&workspace, &cmd.index.load_options(&options));
+        let by_id: HashMap<&str, &CodeElement> = elements
+            .iter()
+            .map(|elem| (elem.id.as_str(), elem))
+            .collect();
+        let keep = |id: &str| by_id.get(id).is_some_and(|elem| filter.matches(elem));
+        let results = match mode {
+            SearchMode::Bm25 => bm25.search_where(&query, limit, keep),
+            SearchMode::Hybrid => {
+                let (vectors, embedder) = load_vectors(&repo_root, &elements, endpoint.as_ref())?;
+                // Hybrid search takes no filter, so rank everything when
+                // there is one and keep the first matches.
+                let k = if filter.is_empty() {
+                    limit
+                } else {
+                    elements.len()
+                };
+                let mut results =
+                    hybrid_search(&bm25, &vectors, embedder.as_ref(), &query, k, alpha);
+                results.retain(|(id, _)| keep(id));
+                results.truncate(limit);
+                results
+            }
+        };
+        let redactor = Redactor::load(&repo_root);
+        anyhow::Ok(
+            results
+                .into_iter()
+                .filter_map(|(id, score)| {
+                    let elem = by_id.get(id.as_str())?;
+                    let snippet = Snippet::extract(&elem.code, elem.start_line, &query);
+                    Some(SearchHit {
+                        score,
+                        kind: NodeKind::from(elem.element_type),
+                        name: elem.name.clone(),
+                        file_path: elem.relative_path.clone(),
+                        start_line: elem.start_line,
+                        snippet_line: snippet.line,
+                        snippet: redactor.redact_text(&snippet.text).into_owned(),
+                        matched_terms: snippet.matched_terms,
+                        id,
+                    })
+                })
+                .collect::<Vec<_>>(),
+        )
+    })
+    .await??;
+
+    #[allow(clippy::print_stdout)]
+    {
+        if cmd.json {
+            println!("{}", serde_json::to_string_pretty(&hits)?);
+            return Ok(());
+        }
+        if hits.is_empty() {
+            println!("No results for {}", cmd.query);
+            return Ok(());
+        }
+        for hit in &hits {
+            println!(
+                "{:>7.2}  {:<10} {}  {}:{}",
+                hit.score,
+                hit.kind.as_str(),
+                hit.name,
+                hit.file_path,
+                hit.start_line,
+            );
+            if !hit.matched_terms.is_empty() {
+                println!("{:>9}matched: {}", "", hit.matched_terms.join(", "));
+            }
+            for (offset, line) in hit.snippet.lines().enumerate() {
+                println!("{:>9}{:>5}  {line}", "", hit.snippet_line + offset);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// The elements of `workspace` and their BM25 index. A single repository
+/// whose element cache is fresh reuses `.happy/bm25.bin`, as a session
+/// does; otherwise the index is built and, for a single repository, saved.
+fn load_elements_and_bm25(
+    workspace: &Workspace,
+    options: &LoadOptions<'_>,
+) -> (Vec<CodeElement>, BM25Index) {
+    let build = |elements: &[CodeElement]| {
+        let mut bm25 = BM25Index::new();
+        for elem in elements {
+            bm25.add_element(elem);
+        }
+        bm25
+    };
+    if workspace.is_multi_root() {
+        let elements = workspace.load_or_index(options).elements;
+        let bm25 = build(&elements);
+        return (elements, bm25);
+    }
+
+    let repo_root = workspace.primary().path.as_path();
+    let LoadedIndex {
+        elements,
+        status,
+        meta,
+        ..
+    } = happy_core::store::cache::load_or_index(repo_root, options);
+    let generations = [meta.generation];
+    if status == CacheStatus::Fresh
+        && let Some(bm25) = happy_core::store::cache::load_bm25(repo_root, &generations)
+    {
+        return (elements, bm25);
+    }
+    let bm25 = build(&elements);
+    happy_core::store::cache::store_bm25(repo_root, &generations, &bm25);
+    (elements, bm25)
+}
+
+/// Vectors for hybrid search and the embedder for the query: the cached
+/// remote vectors when `endpoint` is configured and `.happy/vectors.bin`
+/// holds vectors from its model, otherwise local hashing vectors of
+/// `elements`.
+fn load_vectors(
+    repo_root: &Path,
+    elements: &[CodeElement],
+    endpoint: Option<&EmbeddingEndpoint>,
+) -> anyhow::Result<(VectorIndex, Box<dyn Embedder>)> {
+    if let Some(endpoint) = endpoint
+        && let Some(config) = EmbeddingConfig::load(repo_root, endpoint)
+            .context("failed to read [embeddings] in .happy/agent.toml")?
+    {
+        let model = config.model.clone();
+        match happy_core::store::load_vectors(&repo_root.join(".happy").join("vectors.bin")) {
+            Ok(vectors) if vectors.model() == model && !vectors.is_empty() => {
+                let client = EmbeddingClient::new(config)
+                    .context("failed to create the embedding client")?;
+                return Ok((vectors, Box::new(client)));
+            }
+            _ => {
+                #[allow(clippy::print_stderr)]
+                {
+                    eprintln!(
+                        "No embeddings from {model} cached; run `happycode index --embeddings`. \
+                         Using local vectors."
+                    );
+                }
+            }
+        }
+    }
+    let embedder = HashingEmbedder::default();
+    let mut vectors = VectorIndex::new(embedder.dimension());
+    embed_in_batches(
+        &embedder,
+        elements,
+        &mut vectors,
+        &EmbeddingBudget::local(),
+        &mut |_| {},
+    )
+    .context("failed to embed elements")?;
+    Ok((vectors, Box::new(embedder)))
+}
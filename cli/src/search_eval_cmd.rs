@@ -0,0 +1,138 @@
+use anyhow::Context;
+use clap::Parser;
+use happy_core::vector::eval::EvalReport;
+use happy_core::vector::eval::SearchMode;
+use happy_core::vector::eval::SearchParams;
+use std::path::Path;
+use std::path::PathBuf;
+
+#[derive(Debug, Parser)]
+pub struct SearchEvalCommand {
+    /// Fixtures directory holding `judgments.json` and the `repo/` to index.
+    #[arg(value_name = "FIXTURES")]
+    pub fixtures: PathBuf,
+
+    /// JSON file overriding search parameters (`k1`, `b`, `boosts`, `alpha`).
+    #[arg(long, value_name = "FILE")]
+    pub params: Option<PathBuf>,
+
+    /// Second parameter file to compare against `--params`, printing per-query deltas.
+    #[arg(long, value_name = "FILE")]
+    pub compare: Option<PathBuf>,
+}
+
+pub async fn run_search_eval(cmd: SearchEvalCommand) -> anyhow::Result<()> {
+    let baseline_params = match &cmd.params {
+        Some(path) => load_params(path)?,
+        None => SearchParams::default(),
+    };
+    let candidate_params = cmd.compare.as_deref().map(load_params).transpose()?;
+    let fixtures = cmd.fixtures.clone();
+    let (judgments, runs) = tokio::task::spawn_blocking(move || {
+        let (elements, judgments) = happy_core::vector::eval::load_fixtures(&fixtures)?;
+        let mut runs = Vec::new();
+        for mode in SearchMode::ALL {
+            let baseline =
+                happy_core::vector::eval::evaluate(&elements, &judgments, &baseline_params, mode)?;
+            let candidate = candidate_params
+                .map(|params| {
+                    happy_core::vector::eval::evaluate(&elements, &judgments, &params, mode)
+                })
+                .transpose()?;
+            runs.push((baseline, candidate));
+        }
+        anyhow::Ok((judgments, runs))
+    })
+    .await??;
+
+    let mut failures = Vec::new();
+    #[allow(clippy::print_stdout)]
+    for (baseline, candidate) in &runs {
+        match candidate {
+            None => print_report(baseline),
+            Some(candidate) => print_comparison(baseline, candidate),
+        }
+        println!();
+        // Floors are recorded for the default parameters only.
+        if cmd.params.is_none()
+            && let Some(floor) = judgments.floors.get(&baseline.mode)
+        {
+            for (metric, value, floor) in baseline.mean.below(floor) {
+                failures.push(format!(
+                    "{} {metric} {value:.3} is below its floor of {floor:.3}",
+                    baseline.mode.as_str()
+                ));
+            }
+        }
+    }
+    if !failures.is_empty() {
+        anyhow::bail!("{}", failures.join("\n"));
+    }
+    Ok(())
+}
+
+fn load_params(path: &Path) -> anyhow::Result<SearchParams> {
+    let text = std::fs::read_to_string(path)
+        .with_context(|| format!("failed to read {}", path.display()))?;
+    serde_json::from_str(&text).with_context(|| format!("invalid parameters in {}", path.display()))
+}
+
+#[allow(clippy::print_stdout)]
+fn print_report(report: &EvalReport) {
+    println!("{} search", report.mode.as_str());
+    println!(
+        "  {:<48} {:>8} {:>6} {:>10}",
+        "query", "nDCG@10", "MRR", "recall@20"
+    );
+    for query in &report.queries {
+        let metrics = query.metrics;
+        println!(
+            "  {:<48} {:>8.3} {:>6.3} {:>10.3}",
+            truncate(&query.query),
+            metrics.ndcg_at_10,
+            metrics.mrr,
+            metrics.recall_at_20
+        );
+    }
+    println!(
+        "  {:<48} {:>8.3} {:>6.3} {:>10.3}",
+        "mean", report.mean.ndcg_at_10, report.mean.mrr, report.mean.recall_at_20
+    );
+}
+
+#[allow(clippy::print_stdout)]
+fn print_comparison(baseline: &EvalReport, candidate: &EvalReport) {
+    println!(
+        "{} search: --compare minus --params",
+        baseline.mode.as_str()
+    );
+    println!(
+        "  {:<48} {:>8} {:>7} {:>10}",
+        "query", "nDCG@10", "MRR", "recall@20"
+    );
+    for delta in happy_core::vector::eval::compare(baseline, candidate) {
+        let change = delta.delta();
+        println!(
+            "  {:<48} {:>+8.3} {:>+7.3} {:>+10.3}",
+            truncate(&delta.query),
+            change.ndcg_at_10,
+            change.mrr,
+            change.recall_at_20
+        );
+    }
+    let change = candidate.mean.delta(&baseline.mean);
+    println!(
+        "  {:<48} {:>+8.3} {:>+7.3} {:>+10.3}",
+        "mean", change.ndcg_at_10, change.mrr, change.recall_at_20
+    );
+}
+
+fn truncate(query: &str) -> String {
+    const WIDTH: usize = 48;
+    if query.chars().count() <= WIDTH {
+        return query.to_string();
+    }
+    let mut short: String = query.chars().take(WIDTH - 3).collect();
+    short.push_str("...");
+    short
+}
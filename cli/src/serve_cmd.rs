@@ -0,0 +1,73 @@
+use anyhow::Context;
+use clap::Parser;
+use codex_core::CodeGraphUserConfig;
+use codex_core::config::Config;
+use codex_utils_cli::CliConfigOverrides;
+use happy_core::events::EventSink;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// Token clients must send when `--token` is not given.
+const SERVE_TOKEN_ENV: &str = "HAPPY_SERVE_TOKEN";
+
+#[derive(Debug, Parser)]
+pub struct ServeCommand {
+    /// Repository to index and serve.
+    #[arg(value_name = "PATH", default_value = ".")]
+    pub path: PathBuf,
+
+    /// Address to bind.
+    #[arg(long, default_value = "127.0.0.1")]
+    pub host: String,
+
+    /// Port to listen on.
+    #[arg(long, default_value_t = 8745)]
+    pub port: u16,
+
+    /// Require clients to send this token with every request
+    /// (defaults to $HAPPY_SERVE_TOKEN; no auth when unset).
+    #[arg(long)]
+    pub token: Option<String>,
+
+    /// Write newline-delimited JSON index events (index_started, progress,
+    /// index_completed, incremental_update, error) to this Unix socket or
+    /// FIFO. Events are dropped while nothing is listening.
+    #[arg(long, value_name = "PATH")]
+    pub notify: Option<PathBuf>,
+}
+
+pub async fn run_serve(
+    cmd: ServeCommand,
+    config_overrides: CliConfigOverrides,
+) -> anyhow::Result<()> {
+    let repo_root = std::fs::canonicalize(&cmd.path)?;
+    let overrides = config_overrides
+        .parse_overrides()
+        .map_err(anyhow::Error::msg)?;
+    let config = Config::load_with_cli_overrides(overrides)
+        .await
+        .context("failed to load configuration")?;
+    let user_config = CodeGraphUserConfig::load(&config.config_layer_stack);
+    let token = cmd
+        .token
+        .or_else(|| std::env::var(SERVE_TOKEN_ENV).ok())
+        .filter(|token| !token.is_empty());
+    let listener = std::net::TcpListener::bind((cmd.host.as_str(), cmd.port))?;
+
+    #[allow(clippy::print_stderr)]
+    {
+        eprintln!(
+            "Serving code graph for {} on {} (newline-delimited JSON{})",
+            repo_root.display(),
+            listener.local_addr()?,
+            if token.is_some() {
+                ", token required"
+            } else {
+                ""
+            }
+        );
+    }
+    let events = cmd.notify.map(|path| Arc::new(EventSink::new(path)));
+    codex_core::serve_code_graph(listener, repo_root, token, user_config, events).await?;
+    Ok(())
+}
@@ -0,0 +1,118 @@
+use std::path::PathBuf;
+
+use anyhow::Context;
+use anyhow::Result;
+use codex_core::config::Config;
+use codex_core::handoff::HANDOFF_EXTENSION;
+use codex_core::handoff::export_session;
+use codex_core::handoff::import_session;
+use codex_utils_cli::CliConfigOverrides;
+
+/// Subcommands:
+/// - `export` — write a session to a `.happysession` handoff bundle
+/// - `import` — restore a bundle's session so `resume` can pick it up
+#[derive(Debug, clap::Parser)]
+pub struct SessionCli {
+    #[clap(flatten)]
+    pub config_overrides: CliConfigOverrides,
+
+    #[command(subcommand)]
+    pub subcommand: SessionSubcommand,
+}
+
+#[derive(Debug, clap::Subcommand)]
+pub enum SessionSubcommand {
+    Export(ExportArgs),
+    Import(ImportArgs),
+}
+
+#[derive(Debug, clap::Parser)]
+pub struct ExportArgs {
+    /// Id of the session to export.
+    #[arg(value_name = "SESSION_ID")]
+    pub session_id: String,
+
+    /// Bundle to write; defaults to `<SESSION_ID>.happysession` here.
+    #[arg(long, short = 'o', value_name = "FILE")]
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Debug, clap::Parser)]
+pub struct ImportArgs {
+    /// Bundle written by `session export` or `/handoff`.
+    #[arg(value_name = "FILE")]
+    pub bundle: PathBuf,
+
+    /// Import even if the bundle comes from a different repository, replacing
+    /// a local session with the same id.
+    #[arg(long)]
+    pub force: bool,
+}
+
+impl SessionCli {
+    pub async fn run(self) -> Result<()> {
+        let overrides = self
+            .config_overrides
+            .parse_overrides()
+            .map_err(anyhow::Error::msg)?;
+        let config = Config::load_with_cli_overrides(overrides)
+            .await
+            .context("failed to load configuration")?;
+
+        match self.subcommand {
+            SessionSubcommand::Export(args) => run_export(&config, args).await,
+            SessionSubcommand::Import(args) => run_import(&config, args).await,
+        }
+    }
+}
+
+async fn run_export(config: &Config, args: ExportArgs) -> Result<()> {
+    let output = args
+        .output
+        .unwrap_or_else(|| PathBuf::from(format!("{}.{HANDOFF_EXTENSION}", args.session_id)));
+    let manifest = export_session(
+        &config.codex_home,
+        &args.session_id,
+        &config.config_layer_stack.effective_config(),
+        &output,
+    )
+    .await?;
+
+    #[allow(clippy::print_stdout)]
+    {
+        println!("Wrote {}", output.display());
+        println!(
+            "  session {} at commit {} ({} uncommitted file(s))",
+            manifest.session_id,
+            manifest.repo.commit.as_deref().unwrap_or("(none)"),
+            manifest.repo.dirty_files.len()
+        );
+    }
+    Ok(())
+}
+
+async fn run_import(config: &Config, args: ImportArgs) -> Result<()> {
+    let imported =
+        import_session(&config.codex_home, &config.cwd, &args.bundle, args.force).await?;
+
+    #[allow(clippy::print_stdout, clippy::print_stderr)]
+    {
+        for warning in &imported.warnings {
+            eprintln!("warning: {warning}");
+        }
+        println!(
+            "Imported session {} to {}",
+            imported.manifest.session_id,
+            imported.rollout_path.display()
+        );
+        println!(
+            "  config and plan saved in {}",
+            imported.extras_dir.display()
+        );
+        println!(
+            "  continue it with: happycode resume {}",
+            imported.manifest.session_id
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,329 @@
+use crate::index_args::IndexArgs;
+use anyhow::Context;
+use clap::Parser;
+use happy_core::graph::RepositoryGraph;
+use happy_core::graph::fanout::HOT_NAMES_REPORTED;
+use happy_core::graph::hotspots::FileCount;
+use happy_core::graph::hotspots::HOTSPOTS_REPORTED;
+use happy_core::store::cache::CacheStatus;
+use happy_core::utils::relative_to;
+use happy_core::watcher::WatchConfig;
+use happy_core::watcher::WatchStatus;
+use happy_core::workspace::LoadedWorkspace;
+use serde_json::json;
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::path::PathBuf;
+use std::time::Duration;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+
+#[derive(Debug, Parser)]
+pub struct StatsCommand {
+    /// Repository to report on.
+    #[arg(value_name = "PATH", default_value = ".")]
+    pub path: PathBuf,
+
+    /// Files and functions listed per ranking.
+    #[arg(long, value_name = "N", default_value_t = HOTSPOTS_REPORTED)]
+    pub top: usize,
+
+    /// Print the report as JSON.
+    #[arg(long)]
+    pub json: bool,
+
+    #[command(flatten)]
+    pub index: IndexArgs,
+}
+
+pub async fn run_stats(cmd: StatsCommand) -> anyhow::Result<()> {
+    let repo_root = std::fs::canonicalize(&cmd.path)
+        .with_context(|| format!("failed to resolve {}", cmd.path.display()))?;
+    let root = repo_root.clone();
+    let options = cmd.index.options(&repo_root);
+    let workspace = cmd.index.workspace(&repo_root)?;
+    let roots = workspace.roots().to_vec();
+    let top = cmd.top;
+    let (freshness, statuses, stats, hot_names, hotspots, redactions, watcher) =
+        tokio::task::spawn_blocking(move || {
+            // Measure freshness before `load_or_index` patches the cache.
+            let freshness = happy_core::store::cache::load_meta(&root)
+                .ok()
+                .map(|meta| (meta.changes(&root), meta));
+            let LoadedWorkspace {
+                elements, statuses, ..
+            } = workspace.load_or_index(&cmd.index.load_options(&options));
+            let redactions = happy_core::redact::redactions_by_file(&elements);
+            let mut graph = RepositoryGraph::new();
+            graph.build_from_workspace(&elements, &workspace);
+            // Report the configured backend; starting a watcher would write a
+            // probe file into the repo and wait for it.
+            let watcher = WatchConfig::load(&root)
+                .map(|config| WatchStatus::from_config(&config))
+                .map_err(|err| err.to_string());
+            let hot_names = graph.hot_names(HOT_NAMES_REPORTED);
+            let hotspots = graph.hotspots(top);
+            (
+                freshness,
+                statuses,
+                graph.stats(),
+                hot_names,
+                hotspots,
+                redactions,
+                watcher,
+            )
+        })
+        .await?;
+
+    #[allow(clippy::print_stdout)]
+    {
+        if cmd.json {
+            let relative = |files: &[FileCount]| -> Vec<_> {
+                files
+                    .iter()
+                    .map(|file| {
+                        json!({
+                            "file": relative_to(&repo_root, &file.file_path),
+                            "count": file.count,
+                        })
+                    })
+                    .collect()
+            };
+            let most_called: Vec<_> = hotspots
+                .most_called
+                .iter()
+                .map(|function| {
+                    json!({
+                        "name": function.name,
+                        "file": relative_to(&repo_root, &function.file_path),
+                        "line": function.start_line,
+                        "callers": function.callers,
+                    })
+                })
+                .collect();
+            let report = json!({
+                "repository": repo_root,
+                "files": stats.file_count,
+                "elements": stats.element_count,
+                "modules": stats.module_count,
+                "nodes": stats.node_count,
+                "edges": stats.edge_count,
+                "languages": stats.languages,
+                "roots": roots
+                    .iter()
+                    .zip(&statuses)
+                    .map(|(root, status)| {
+                        let count = stats
+                            .roots
+                            .iter()
+                            .find(|count| Path::new(&count.root) == root.path);
+                        json!({
+                            "name": root.name,
+                            "path": root.path,
+                            "files": count.map(|count| count.files),
+                            "elements": count.map(|count| count.elements),
+                            "cache_status": cache_status_json(status),
+                        })
+                    })
+                    .collect::<Vec<_>>(),
+                "largest_files": relative(&hotspots.largest_files),
+                "most_depended_upon": relative(&hotspots.most_depended_upon),
+                "most_dependent": relative(&hotspots.most_dependent),
+                "most_called": most_called,
+                "hot_names": hot_names,
+                "redactions": redactions,
+                "watcher": match &watcher {
+                    Ok(status) => json!(status),
+                    Err(err) => json!({"error": err}),
+                },
+                "cache": freshness.as_ref().map(|(changes, meta)| json!({
+                    "indexed_at_ms": meta.indexed_at_ms,
+                    "happy_version": meta.happy_version,
+                    "changed_files": changes.len(),
+                    "total_files": changes.total_files,
+                })),
+                "cache_status": cache_status_json(&statuses[0]),
+            });
+            println!("{}", serde_json::to_string_pretty(&report)?);
+            return Ok(());
+        }
+        println!("Repository: {}", repo_root.display());
+        println!(
+            "Indexed {} files: {} elements, {} modules, {} nodes, {} edges",
+            stats.file_count,
+            stats.element_count,
+            stats.module_count,
+            stats.node_count,
+            stats.edge_count
+        );
+        if !stats.languages.is_empty() {
+            let languages: Vec<String> = stats
+                .languages
+                .iter()
+                .map(|lang| {
+                    format!(
+                        "{} ({} files, {} elements)",
+                        lang.language, lang.files, lang.elements
+                    )
+                })
+                .collect();
+            println!("Languages: {}", languages.join(", "));
+        }
+        if !stats.roots.is_empty() {
+            println!("Roots:");
+            for (root, count) in roots.iter().zip(&stats.roots) {
+                println!(
+                    "  {:<16} {} files, {} elements  {}",
+                    root.name,
+                    count.files,
+                    count.elements,
+                    root.path.display()
+                );
+            }
+        }
+        print_files(
+            &repo_root,
+            "Largest files",
+            "elements",
+            &hotspots.largest_files,
+        );
+        print_files(
+            &repo_root,
+            "Most depended upon",
+            "incoming edges",
+            &hotspots.most_depended_upon,
+        );
+        print_files(
+            &repo_root,
+            "Most dependent",
+            "outgoing edges",
+            &hotspots.most_dependent,
+        );
+        if !hotspots.most_called.is_empty() {
+            println!("Most called:");
+            for function in &hotspots.most_called {
+                println!(
+                    "  {:>6}  {}  {}:{}",
+                    function.callers,
+                    function.name,
+                    relative_to(&repo_root, &function.file_path),
+                    function.start_line
+                );
+            }
+        }
+        if !hot_names.is_empty() {
+            let names: Vec<String> = hot_names
+                .iter()
+                .map(|hot| {
+                    format!(
+                        "{} ({} definitions, {} fallbacks skipped)",
+                        hot.name, hot.definitions, hot.skipped_fallbacks
+                    )
+                })
+                .collect();
+            println!("Hot names: {}", names.join(", "));
+        }
+        match freshness {
+            Some((changes, meta)) => {
+                let age = format_age(meta.indexed_at_ms);
+                if changes.is_empty() {
+                    println!(
+                        "Cache: fresh, indexed {age} by happy {}",
+                        meta.happy_version
+                    );
+                } else {
+                    println!(
+                        "Cache: {} of {} files changed since last index {age} ({} added, {} modified, {} removed)",
+                        changes.len(),
+                        changes.total_files,
+                        changes.added.len(),
+                        changes.modified.len(),
+                        changes.removed.len()
+                    );
+                }
+            }
+            None => println!("Cache: none"),
+        }
+        if redactions.is_empty() {
+            println!("Redactions: none");
+        } else {
+            let mut totals: BTreeMap<&str, usize> = BTreeMap::new();
+            for counts in redactions.values() {
+                for (name, count) in counts {
+                    *totals.entry(name).or_default() += count;
+                }
+            }
+            let by_kind: Vec<String> = totals
+                .iter()
+                .map(|(name, count)| format!("{name}: {count}"))
+                .collect();
+            println!(
+                "Redactions: {} in {} files ({})",
+                totals.values().sum::<usize>(),
+                redactions.len(),
+                by_kind.join(", ")
+            );
+        }
+        match watcher {
+            Ok(status) => println!("Watcher: {status}"),
+            Err(err) => println!("Watcher: unavailable ({err})"),
+        }
+        for (root, status) in roots.iter().zip(statuses) {
+            let of_root = if roots.len() > 1 {
+                format!(" ({})", root.name)
+            } else {
+                String::new()
+            };
+            match status {
+                CacheStatus::Fresh => {}
+                CacheStatus::Patched(changes) => {
+                    println!(
+                        "Cache updated{of_root}: re-indexed {} changed files",
+                        changes.len()
+                    );
+                }
+                CacheStatus::Rebuilt(reason) => println!("Cache rebuilt{of_root}: {reason}"),
+            }
+        }
+    }
+    Ok(())
+}
+
+/// One ranking of files under `title`, each with its count of `unit`.
+#[allow(clippy::print_stdout)]
+fn print_files(root: &Path, title: &str, unit: &str, files: &[FileCount]) {
+    if files.is_empty() {
+        return;
+    }
+    println!("{title} ({unit}):");
+    for file in files {
+        println!(
+            "  {:>6}  {}",
+            file.count,
+            relative_to(root, &file.file_path)
+        );
+    }
+}
+
+fn cache_status_json(status: &CacheStatus) -> serde_json::Value {
+    match status {
+        CacheStatus::Fresh => json!("fresh"),
+        CacheStatus::Patched(changes) => json!({"patched_files": changes.len()}),
+        CacheStatus::Rebuilt(reason) => json!({"rebuilt": reason.to_string()}),
+    }
+}
+
+/// How long ago `millis` (since the Unix epoch) was, e.g. `5m ago`.
+fn format_age(millis: u64) -> String {
+    let then = UNIX_EPOCH + Duration::from_millis(millis);
+    let secs = SystemTime::now()
+        .duration_since(then)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or(0);
+    match secs {
+        0..60 => format!("{secs}s ago"),
+        60..3_600 => format!("{}m ago", secs / 60),
+        3_600..86_400 => format!("{}h ago", secs / 3_600),
+        _ => format!("{}d ago", secs / 86_400),
+    }
+}
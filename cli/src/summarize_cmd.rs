@@ -0,0 +1,94 @@
+use crate::index_args::IndexArgs;
+use anyhow::Context;
+use clap::Parser;
+use codex_core::code_summaries::CodexChatModel;
+use codex_core::config::Config;
+use codex_utils_cli::CliConfigOverrides;
+use happy_core::summary::HeuristicSummarizer;
+use happy_core::summary::LlmSummarizer;
+use happy_core::summary::Summarizer;
+use happy_core::summary::SummaryConfig;
+use happy_core::summary::summarize_cache;
+use std::path::PathBuf;
+
+#[derive(Debug, Parser)]
+pub struct SummarizeCommand {
+    /// Repository whose functions, classes, and files to summarize.
+    #[arg(value_name = "PATH", default_value = ".")]
+    pub path: PathBuf,
+
+    /// Ask the model of your Codex config instead of summarizing from
+    /// docstrings and signatures. `[summaries]` in `.happy/agent.toml`
+    /// tunes batching and rate limits.
+    #[arg(long)]
+    pub llm: bool,
+
+    /// Keep existing summaries and only summarize elements without one.
+    #[arg(long)]
+    pub only_missing: bool,
+
+    #[command(flatten)]
+    pub index: IndexArgs,
+}
+
+pub async fn run_summarize(
+    cmd: SummarizeCommand,
+    config_overrides: CliConfigOverrides,
+) -> anyhow::Result<()> {
+    let repo_root = std::fs::canonicalize(&cmd.path)
+        .with_context(|| format!("failed to resolve {}", cmd.path.display()))?;
+    let options = cmd.index.options(&repo_root);
+    let workspace = cmd.index.workspace(&repo_root)?;
+    let summarizer: Box<dyn Summarizer> = if cmd.llm {
+        let summary_config =
+            SummaryConfig::load(&repo_root).context("failed to read .happy/agent.toml")?;
+        let overrides = config_overrides
+            .parse_overrides()
+            .map_err(anyhow::Error::msg)?;
+        let config = Config::load_with_cli_overrides(overrides)
+            .await
+            .context("failed to load configuration")?;
+        let model = CodexChatModel::new(&config).await;
+        #[allow(clippy::print_stderr)]
+        {
+            eprintln!("Summarizing with {}", model.model());
+        }
+        Box::new(LlmSummarizer::new(summary_config, model))
+    } else {
+        Box::new(HeuristicSummarizer)
+    };
+
+    tokio::task::spawn_blocking(move || {
+        for root in workspace.roots() {
+            let report = summarize_cache(
+                &root.path,
+                !cmd.index.no_cache,
+                &options,
+                summarizer.as_ref(),
+                cmd.only_missing,
+                &mut |progress| {
+                    #[allow(clippy::print_stderr)]
+                    {
+                        if cmd.llm {
+                            eprint!("\r{}: {}/{}", root.name, progress.done, progress.total);
+                        }
+                    }
+                },
+            )
+            .with_context(|| format!("failed to summarize {}", root.path.display()))?;
+
+            #[allow(clippy::print_stdout, clippy::print_stderr)]
+            {
+                if cmd.llm {
+                    eprintln!();
+                }
+                println!(
+                    "{:<16} {} summarized, {} without a summary, {} kept",
+                    root.name, report.summarized, report.empty, report.skipped_existing
+                );
+            }
+        }
+        anyhow::Ok(())
+    })
+    .await?
+}
@@ -0,0 +1,36 @@
+use anyhow::Context;
+use clap::Parser;
+use happy_core::events::IndexEvent;
+use std::path::PathBuf;
+use std::time::Duration;
+
+#[derive(Debug, Parser)]
+pub struct WaitForIndexCommand {
+    /// Unix socket to listen on (or existing FIFO to read), as passed to
+    /// `happycode serve --notify`.
+    #[arg(long, value_name = "PATH")]
+    pub notify: PathBuf,
+
+    /// Give up after this many seconds.
+    #[arg(long, value_name = "SECONDS")]
+    pub timeout: Option<u64>,
+}
+
+/// Block until an `index_completed` event arrives, then print it.
+pub async fn run_wait_for_index(cmd: WaitForIndexCommand) -> anyhow::Result<()> {
+    let path = cmd.notify.clone();
+    let timeout = cmd.timeout.map(Duration::from_secs);
+    let record = tokio::task::spawn_blocking(move || {
+        happy_core::events::wait_for_event(&path, timeout, |event| {
+            matches!(event, IndexEvent::IndexCompleted { .. })
+        })
+    })
+    .await?
+    .with_context(|| format!("no index_completed event on {}", cmd.notify.display()))?;
+
+    #[allow(clippy::print_stdout)]
+    {
+        println!("{}", serde_json::to_string(&record)?);
+    }
+    Ok(())
+}
@@ -0,0 +1,160 @@
+//! The chat model behind `happycode summarize --llm`: the model and provider
+//! of the user's Codex config, signed in the same way as a session.
+
+use crate::AuthManager;
+use crate::CodexAuth;
+use crate::ModelClient;
+use crate::Prompt;
+use crate::config::Config;
+use crate::error::Result;
+use crate::models_manager::manager::ModelsManager;
+use crate::models_manager::manager::RefreshStrategy;
+use codex_api::ResponseEvent;
+use codex_otel::OtelManager;
+use codex_otel::TelemetryAuthMode;
+use codex_protocol::ThreadId;
+use codex_protocol::config_types::ReasoningSummary as ReasoningSummaryConfig;
+use codex_protocol::models::BaseInstructions;
+use codex_protocol::models::ContentItem;
+use codex_protocol::models::ResponseItem;
+use codex_protocol::openai_models::ModelInfo;
+use codex_protocol::openai_models::ReasoningEffort as ReasoningEffortConfig;
+use codex_protocol::protocol::SessionSource;
+use futures::StreamExt;
+use happy_core::summary::ChatModel;
+use happy_core::summary::SummaryError;
+use tokio::runtime::Handle;
+
+/// A [`ChatModel`] that streams each prompt through a [`ModelClient`].
+///
+/// [`ChatModel::complete`] blocks on the runtime that built it, so call it
+/// from a blocking thread such as `tokio::task::spawn_blocking`.
+pub struct CodexChatModel {
+    client: ModelClient,
+    model_info: ModelInfo,
+    otel_manager: OtelManager,
+    reasoning_effort: Option<ReasoningEffortConfig>,
+    reasoning_summary: ReasoningSummaryConfig,
+    runtime: Handle,
+}
+
+impl CodexChatModel {
+    pub async fn new(config: &Config) -> Self {
+        let auth_manager = AuthManager::shared(
+            config.codex_home.clone(),
+            true,
+            config.cli_auth_credentials_store_mode,
+        );
+        let models_manager = ModelsManager::new(
+            config.codex_home.clone(),
+            auth_manager.clone(),
+            config.model_catalog.clone(),
+        );
+        let model = models_manager
+            .get_default_model(&config.model, RefreshStrategy::OnlineIfUncached)
+            .await;
+        let model_info = models_manager.get_model_info(&model, config).await;
+
+        let conversation_id = ThreadId::new();
+        let auth = auth_manager.auth().await;
+        let otel_manager = OtelManager::new(
+            conversation_id,
+            model.as_str(),
+            model_info.slug.as_str(),
+            auth.as_ref().and_then(CodexAuth::get_account_id),
+            auth.as_ref().and_then(CodexAuth::get_account_email),
+            auth.as_ref()
+                .map(CodexAuth::auth_mode)
+                .map(TelemetryAuthMode::from),
+            crate::default_client::originator().value,
+            config.otel.log_user_prompt,
+            crate::terminal::user_agent(),
+            SessionSource::Exec,
+        );
+        let client = ModelClient::new(
+            Some(auth_manager),
+            conversation_id,
+            config.model_provider.clone(),
+            SessionSource::Exec,
+            config.model_verbosity,
+            false,
+            false,
+            false,
+            false,
+            None,
+        );
+        Self {
+            client,
+            model_info,
+            otel_manager,
+            reasoning_effort: config.model_reasoning_effort,
+            reasoning_summary: config.model_reasoning_summary,
+            runtime: Handle::current(),
+        }
+    }
+
+    /// The slug of the model that writes the summaries.
+    pub fn model(&self) -> &str {
+        &self.model_info.slug
+    }
+
+    async fn sample(&self, instructions: &str, input: &str) -> Result<String> {
+        let prompt = Prompt {
+            input: vec![ResponseItem::Message {
+                id: None,
+                role: "user".to_string(),
+                content: vec![ContentItem::InputText {
+                    text: input.to_string(),
+                }],
+                end_turn: None,
+                phase: None,
+            }],
+            base_instructions: BaseInstructions {
+                text: instructions.to_string(),
+            },
+            ..Prompt::default()
+        };
+
+        let mut client_session = self.client.new_session();
+        let mut stream = client_session
+            .stream(
+                &prompt,
+                &self.model_info,
+                &self.otel_manager,
+                self.reasoning_effort,
+                self.reasoning_summary,
+                None,
+            )
+            .await?;
+
+        let mut result = String::new();
+        while let Some(event) = stream.next().await.transpose()? {
+            match event {
+                ResponseEvent::OutputTextDelta(delta) => result.push_str(&delta),
+                ResponseEvent::OutputItemDone(item) => {
+                    if result.is_empty()
+                        && let ResponseItem::Message { content, .. } = item
+                        && let Some(text) = crate::compact::content_items_to_text(&content)
+                    {
+                        result.push_str(&text);
+                    }
+                }
+                ResponseEvent::Completed { .. } => break,
+                _ => {}
+            }
+        }
+        Ok(result)
+    }
+}
+
+impl ChatModel for CodexChatModel {
+    fn complete(
+        &self,
+        instructions: &str,
+        prompt: &str,
+    ) -> std::result::Result<String, SummaryError> {
+        self.runtime
+            .block_on(self.sample(instructions, prompt))
+            .map_err(|err| SummaryError::Backend(err.to_string()))
+    }
+}
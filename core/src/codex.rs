@@ -228,6 +228,7 @@ use crate::tasks::SessionTask;
 use crate::tasks::SessionTaskContext;
 use crate::tools::ToolRouter;
 use crate::tools::context::SharedTurnDiffTracker;
+use crate::tools::handlers::GroundingAction;
 use crate::tools::handlers::SEARCH_TOOL_BM25_TOOL_NAME;
 use crate::tools::js_repl::JsReplHandle;
 use crate::tools::network_approval::NetworkApprovalService;
@@ -566,6 +567,15 @@ pub(crate) struct TurnContext {
     pub(crate) turn_metadata_state: Arc<TurnMetadataState>,
 }
 impl TurnContext {
+    /// Truncation policy for outputs of `tool_name`, honouring
+    /// `tool_output_token_limits` overrides.
+    pub(crate) fn tool_output_policy(&self, tool_name: &str) -> TruncationPolicy {
+        match self.config.tool_output_token_limits.get(tool_name) {
+            Some(tokens) => self.truncation_policy.with_token_limit(*tokens),
+            None => self.truncation_policy,
+        }
+    }
+
     pub(crate) fn model_context_window(&self) -> Option<i64> {
         let effective_context_window_percent = self.model_info.effective_context_window_percent;
         self.model_info.context_window.map(|context_window| {
@@ -912,6 +922,28 @@ impl Session {
         });
     }
 
+    /// Forward code graph updates from the background indexer and file
+    /// watcher to clients as [`EventMsg::CodeGraphUpdated`].
+    fn start_code_graph_update_listener(
+        self: &Arc<Self>,
+    ) -> crate::tools::handlers::CodeGraphUpdates {
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let weak_sess = Arc::downgrade(self);
+        tokio::spawn(async move {
+            while let Some(update) = rx.recv().await {
+                let Some(sess) = weak_sess.upgrade() else {
+                    break;
+                };
+                let event = Event {
+                    id: sess.next_internal_sub_id(),
+                    msg: EventMsg::CodeGraphUpdated(update),
+                };
+                sess.send_event_raw(event).await;
+            }
+        });
+        tx
+    }
+
     #[allow(clippy::too_many_arguments)]
     fn make_turn_context(
         auth_manager: Option<Arc<AuthManager>>,
@@ -1333,6 +1365,9 @@ impl Session {
                 Self::build_model_client_beta_features_header(config.as_ref()),
             ),
             code_graph_repo: Arc::new(tokio::sync::RwLock::new(None)),
+            code_graph_tool_examples: std::sync::OnceLock::new(),
+            read_redactor: std::sync::OnceLock::new(),
+            path_confinement: std::sync::OnceLock::new(),
         };
         let js_repl = Arc::new(JsReplHandle::with_node_path(
             config.js_repl_node_path.clone(),
@@ -1470,6 +1505,9 @@ impl Session {
         crate::tools::handlers::start_code_graph_indexing(
             Arc::clone(&sess.services.code_graph_repo),
             session_configuration.cwd.clone(),
+            !config.reindex_code_graph,
+            crate::tools::handlers::CodeGraphUserConfig::load(&config.config_layer_stack),
+            Some(sess.start_code_graph_update_listener()),
         );
 
         Ok(sess)
@@ -1574,6 +1612,22 @@ impl Session {
         state.merge_connector_selection(connector_ids)
     }
 
+    /// The tools config for this turn, carrying the session's code graph
+    /// tool examples. They are sampled the first time tools are built and
+    /// then kept, so the tool descriptions don't change between requests.
+    pub(crate) fn tools_config_with_examples(&self, tools_config: &ToolsConfig) -> ToolsConfig {
+        let examples = self
+            .services
+            .code_graph_tool_examples
+            .get_or_init(|| {
+                crate::tools::handlers::code_graph::code_graph_tool_examples(
+                    &self.services.code_graph_repo,
+                )
+            })
+            .clone();
+        tools_config.clone().with_code_graph_examples(examples)
+    }
+
     // Returns the connector IDs currently selected for this session.
     pub(crate) async fn get_connector_selection(&self) -> HashSet<String> {
         let state = self.state.lock().await;
@@ -2473,7 +2527,23 @@ impl Session {
         turn_context: &TurnContext,
     ) {
         let mut state = self.state.lock().await;
-        state.record_items(items.iter(), turn_context.truncation_policy);
+        state.record_items_with_tool_policies(items, |tool_name| {
+            tool_name.map_or(turn_context.truncation_policy, |name| {
+                turn_context.tool_output_policy(name)
+            })
+        });
+    }
+
+    /// Keep the untruncated output of `call_id` for `get_tool_output`.
+    pub(crate) async fn stash_tool_output(&self, call_id: &str, output: String) {
+        let mut state = self.state.lock().await;
+        state.tool_outputs.insert(call_id, output);
+    }
+
+    /// The untruncated output of `call_id`, if it was stashed and not yet evicted.
+    pub(crate) async fn stashed_tool_output(&self, call_id: &str) -> Option<String> {
+        let mut state = self.state.lock().await;
+        state.tool_outputs.get(call_id).map(str::to_string)
     }
 
     pub(crate) async fn record_model_warning(&self, message: impl Into<String>, ctx: &TurnContext) {
@@ -3250,6 +3320,9 @@ async fn submission_loop(sess: Arc<Session>, config: Arc<Config>, rx_sub: Receiv
             Op::ListCustomPrompts => {
                 handlers::list_custom_prompts(&sess, sub.id.clone()).await;
             }
+            Op::ReindexCodeGraph => {
+                handlers::reindex_code_graph(&sess, sub.id.clone());
+            }
             Op::ListSkills { cwds, force_reload } => {
                 handlers::list_skills(&sess, sub.id.clone(), cwds, force_reload).await;
             }
@@ -3333,6 +3406,7 @@ mod handlers {
     use crate::tasks::UserShellCommandTask;
     use crate::tasks::execute_user_shell_command;
     use codex_protocol::custom_prompts::CustomPrompt;
+    use codex_protocol::protocol::BackgroundEventEvent;
     use codex_protocol::protocol::CodexErrorInfo;
     use codex_protocol::protocol::ErrorEvent;
     use codex_protocol::protocol::Event;
@@ -3684,6 +3758,26 @@ mod handlers {
         sess.send_event_raw(event).await;
     }
 
+    /// Rebuild the code graph in the background, so the submission loop
+    /// keeps serving ops while the repository is walked.
+    pub fn reindex_code_graph(sess: &Arc<Session>, sub_id: String) {
+        let sess = Arc::clone(sess);
+        tokio::spawn(async move {
+            let msg =
+                match crate::tools::handlers::reindex_code_graph(&sess.services.code_graph_repo)
+                    .await
+                {
+                    Some(update) => EventMsg::CodeGraphUpdated(update),
+                    None => EventMsg::BackgroundEvent(BackgroundEventEvent {
+                        message:
+                            "The code graph is still being indexed; it will be reported once built."
+                                .to_string(),
+                    }),
+                };
+            sess.send_event_raw(Event { id: sub_id, msg }).await;
+        });
+    }
+
     pub async fn list_custom_prompts(sess: &Session, sub_id: String) {
         let custom_prompts: Vec<CustomPrompt> =
             if let Some(dir) = crate::custom_prompts::default_prompts_dir() {
@@ -4445,6 +4539,9 @@ pub(crate) async fn run_turn(
     // many turns, from the perspective of the user, it is a single turn.
     let turn_diff_tracker = Arc::new(tokio::sync::Mutex::new(TurnDiffTracker::new()));
     let mut server_model_warning_emitted_for_turn = false;
+    // Final answers get at most one grounding check per turn, so a model that
+    // keeps restating a claim cannot loop.
+    let mut grounding_checked = false;
 
     // `ModelClientSession` is turn-scoped and caches WebSocket + sticky routing state, so we reuse
     // one instance across retries within this turn.
@@ -4541,6 +4638,54 @@ pub(crate) async fn run_turn(
                     continue;
                 }
 
+                if needs_follow_up
+                    && let Some(findings) = crate::tools::handlers::verify_code_graph_edits(
+                        &sess.services.code_graph_repo,
+                    )
+                    .await
+                {
+                    // Structural feedback on this step's edits, ahead of the
+                    // model's next step.
+                    let message: ResponseItem = DeveloperInstructions::new(findings).into();
+                    sess.record_conversation_items(&turn_context, std::slice::from_ref(&message))
+                        .await;
+                }
+
+                if !needs_follow_up
+                    && !grounding_checked
+                    && let Some(answer) = sampling_request_last_agent_message.as_deref()
+                    && crate::tools::handlers::grounding_enabled(&sess.services.code_graph_repo)
+                        .await
+                {
+                    grounding_checked = true;
+                    let history = sess.clone_history().await;
+                    match crate::tools::handlers::check_answer_grounding(
+                        &sess.services.code_graph_repo,
+                        answer,
+                        history.raw_items(),
+                    )
+                    .await
+                    {
+                        Some(GroundingAction::FollowUp(request)) => {
+                            let message: ResponseItem = DeveloperInstructions::new(request).into();
+                            sess.record_conversation_items(
+                                &turn_context,
+                                std::slice::from_ref(&message),
+                            )
+                            .await;
+                            continue;
+                        }
+                        Some(GroundingAction::Caveat(message)) => {
+                            sess.send_event(
+                                &turn_context,
+                                EventMsg::Warning(WarningEvent { message }),
+                            )
+                            .await;
+                        }
+                        None => {}
+                    }
+                }
+
                 if !needs_follow_up {
                     last_agent_message = sampling_request_last_agent_message;
                     let hook_outcomes = sess
@@ -5058,7 +5203,7 @@ async fn built_tools(
     }
 
     Ok(Arc::new(ToolRouter::from_config(
-        &turn_context.tools_config,
+        &sess.tools_config_with_examples(&turn_context.tools_config),
         has_mcp_servers.then(|| {
             mcp_tools
                 .into_iter()
@@ -5998,6 +6143,26 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn code_graph_tool_examples_stay_fixed_for_the_session() {
+        let (session, turn_context) = make_session_and_context().await;
+        let first = session.tools_config_with_examples(&turn_context.tools_config);
+        assert_eq!(
+            first.code_graph_examples,
+            Some(happy_core::tool_examples::ToolExamples::fallback())
+        );
+
+        // Indexing finishing mid-session must not change the tool descriptions.
+        *session.services.code_graph_repo.write().await =
+            Some(crate::tools::handlers::code_graph::test_repo_handle(
+                "/repo".to_string(),
+                happy_core::graph::RepositoryGraph::new(),
+                happy_core::vector::bm25::BM25Index::new(),
+            ));
+        let later = session.tools_config_with_examples(&turn_context.tools_config);
+        assert_eq!(later.code_graph_examples, first.code_graph_examples);
+    }
+
     #[tokio::test]
     async fn reload_user_config_layer_updates_effective_apps_config() {
         let (session, _turn_context) = make_session_and_context().await;
@@ -7449,6 +7614,9 @@ mod tests {
                 Session::build_model_client_beta_features_header(config.as_ref()),
             ),
             code_graph_repo: Arc::new(tokio::sync::RwLock::new(None)),
+            code_graph_tool_examples: std::sync::OnceLock::new(),
+            read_redactor: std::sync::OnceLock::new(),
+            path_confinement: std::sync::OnceLock::new(),
         };
         let js_repl = Arc::new(JsReplHandle::with_node_path(
             config.js_repl_node_path.clone(),
@@ -7606,6 +7774,9 @@ mod tests {
                 Session::build_model_client_beta_features_header(config.as_ref()),
             ),
             code_graph_repo: Arc::new(tokio::sync::RwLock::new(None)),
+            code_graph_tool_examples: std::sync::OnceLock::new(),
+            read_redactor: std::sync::OnceLock::new(),
+            path_confinement: std::sync::OnceLock::new(),
         };
         let js_repl = Arc::new(JsReplHandle::with_node_path(
             config.js_repl_node_path.clone(),
@@ -3,6 +3,7 @@ use crate::config::edit::ConfigEdit;
 use crate::config::edit::ConfigEditsBuilder;
 use crate::config::types::AppsConfigToml;
 use crate::config::types::DEFAULT_OTEL_ENVIRONMENT;
+use crate::config::types::EmbeddingsToml;
 use crate::config::types::History;
 use crate::config::types::McpServerConfig;
 use crate::config::types::McpServerDisabledReason;
@@ -16,6 +17,7 @@ use crate::config::types::OtelConfig;
 use crate::config::types::OtelConfigToml;
 use crate::config::types::OtelExporterKind;
 use crate::config::types::SandboxWorkspaceWrite;
+use crate::config::types::SecurityToml;
 use crate::config::types::ShellEnvironmentPolicy;
 use crate::config::types::ShellEnvironmentPolicyToml;
 use crate::config::types::SkillsConfig;
@@ -116,6 +118,7 @@ pub use codex_git::GhostSnapshotConfig;
 pub(crate) const PROJECT_DOC_MAX_BYTES: usize = 32 * 1024; // 32 KiB
 pub(crate) const DEFAULT_AGENT_MAX_THREADS: Option<usize> = Some(6);
 pub(crate) const DEFAULT_AGENT_MAX_DEPTH: i32 = 1;
+pub(crate) const DEFAULT_MAX_PARALLEL_TOOL_CALLS: usize = 4;
 
 pub const CONFIG_TOML_FILE: &str = "config.toml";
 
@@ -318,6 +321,13 @@ pub struct Config {
     /// Token budget applied when storing tool/function outputs in the context manager.
     pub tool_output_token_limit: Option<usize>,
 
+    /// Per-tool overrides of the stored output budget, in tokens, keyed by tool name.
+    pub tool_output_token_limits: HashMap<String, usize>,
+
+    /// Maximum number of parallel-safe tool calls from one model response
+    /// that run at the same time.
+    pub max_parallel_tool_calls: usize,
+
     /// Maximum number of agent threads that can be open concurrently.
     pub agent_max_threads: Option<usize>,
 
@@ -343,6 +353,10 @@ pub struct Config {
     /// When true, session is not persisted on disk. Default to `false`
     pub ephemeral: bool,
 
+    /// When true, the code graph is indexed from scratch at startup instead
+    /// of loading a fresh `.happy/elements.bin`. Default to `false`
+    pub reindex_code_graph: bool,
+
     /// Optional URI-based file opener. If set, citations to files in the model
     /// output will be hyperlinked using the specified URI scheme.
     pub file_opener: UriBasedFileOpener,
@@ -1039,6 +1053,17 @@ pub struct ConfigToml {
     /// Token budget applied when storing tool/function outputs in the context manager.
     pub tool_output_token_limit: Option<usize>,
 
+    /// Per-tool token budgets for stored outputs, keyed by tool name (for
+    /// example `shell = 20000`). Tools not listed use `tool_output_token_limit`.
+    #[serde(default)]
+    pub tool_output_token_limits: HashMap<String, usize>,
+
+    /// Maximum number of read-only tool calls from one model response that
+    /// run at the same time. Tools that change state always run one at a
+    /// time. Default: `4`.
+    #[schemars(range(min = 1))]
+    pub max_parallel_tool_calls: Option<usize>,
+
     /// Maximum poll window for background terminal output (`write_stdin`), in milliseconds.
     /// Default: `300000` (5 minutes).
     pub background_terminal_timeout: Option<u64>,
@@ -1117,6 +1142,14 @@ pub struct ConfigToml {
     /// User-level skill config entries keyed by SKILL.md path.
     pub skills: Option<SkillsConfig>,
 
+    /// Paths the read tools may open outside the repository. Only honored
+    /// in the user's config.toml, never in a project's.
+    pub security: Option<SecurityToml>,
+
+    /// Embedding server for the code graph. Only honored in the user's
+    /// config.toml, never in a project's.
+    pub embeddings: Option<EmbeddingsToml>,
+
     /// Centralized feature flags (new). Prefer this over individual toggles.
     #[serde(default)]
     // Injects known feature keys into the schema and forbids unknown keys.
@@ -1449,6 +1482,7 @@ pub struct ConfigOverrides {
     pub show_raw_agent_reasoning: Option<bool>,
     pub tools_web_search_request: Option<bool>,
     pub ephemeral: Option<bool>,
+    pub reindex_code_graph: Option<bool>,
     /// Additional directories that should be treated as writable roots for this session.
     pub additional_writable_roots: Vec<PathBuf>,
 }
@@ -1578,6 +1612,7 @@ impl Config {
             show_raw_agent_reasoning,
             tools_web_search_request: override_tools_web_search_request,
             ephemeral,
+            reindex_code_graph,
             additional_writable_roots,
         } = overrides;
 
@@ -1988,6 +2023,11 @@ impl Config {
                 })
                 .collect(),
             tool_output_token_limit: cfg.tool_output_token_limit,
+            tool_output_token_limits: cfg.tool_output_token_limits,
+            max_parallel_tool_calls: cfg
+                .max_parallel_tool_calls
+                .unwrap_or(DEFAULT_MAX_PARALLEL_TOOL_CALLS)
+                .max(1),
             agent_max_threads,
             agent_max_depth,
             agent_roles,
@@ -1997,6 +2037,7 @@ impl Config {
             config_layer_stack,
             history,
             ephemeral: ephemeral.unwrap_or_default(),
+            reindex_code_graph: reindex_code_graph.unwrap_or_default(),
             file_opener: cfg.file_opener.unwrap_or(UriBasedFileOpener::VsCode),
             codex_linux_sandbox_exe,
             js_repl_node_path,
@@ -4536,6 +4577,8 @@ model_verbosity = "high"
                 project_doc_max_bytes: PROJECT_DOC_MAX_BYTES,
                 project_doc_fallback_filenames: Vec::new(),
                 tool_output_token_limit: None,
+                tool_output_token_limits: HashMap::new(),
+                max_parallel_tool_calls: DEFAULT_MAX_PARALLEL_TOOL_CALLS,
                 agent_max_threads: DEFAULT_AGENT_MAX_THREADS,
                 agent_max_depth: DEFAULT_AGENT_MAX_DEPTH,
                 agent_roles: BTreeMap::new(),
@@ -4546,6 +4589,7 @@ model_verbosity = "high"
                 startup_warnings: Vec::new(),
                 history: History::default(),
                 ephemeral: false,
+                reindex_code_graph: false,
                 file_opener: UriBasedFileOpener::VsCode,
                 codex_linux_sandbox_exe: None,
                 js_repl_node_path: None,
@@ -4654,6 +4698,8 @@ model_verbosity = "high"
             project_doc_max_bytes: PROJECT_DOC_MAX_BYTES,
             project_doc_fallback_filenames: Vec::new(),
             tool_output_token_limit: None,
+            tool_output_token_limits: HashMap::new(),
+            max_parallel_tool_calls: DEFAULT_MAX_PARALLEL_TOOL_CALLS,
             agent_max_threads: DEFAULT_AGENT_MAX_THREADS,
             agent_max_depth: DEFAULT_AGENT_MAX_DEPTH,
             agent_roles: BTreeMap::new(),
@@ -4664,6 +4710,7 @@ model_verbosity = "high"
             startup_warnings: Vec::new(),
             history: History::default(),
             ephemeral: false,
+            reindex_code_graph: false,
             file_opener: UriBasedFileOpener::VsCode,
             codex_linux_sandbox_exe: None,
             js_repl_node_path: None,
@@ -4770,6 +4817,8 @@ model_verbosity = "high"
             project_doc_max_bytes: PROJECT_DOC_MAX_BYTES,
             project_doc_fallback_filenames: Vec::new(),
             tool_output_token_limit: None,
+            tool_output_token_limits: HashMap::new(),
+            max_parallel_tool_calls: DEFAULT_MAX_PARALLEL_TOOL_CALLS,
             agent_max_threads: DEFAULT_AGENT_MAX_THREADS,
             agent_max_depth: DEFAULT_AGENT_MAX_DEPTH,
             agent_roles: BTreeMap::new(),
@@ -4780,6 +4829,7 @@ model_verbosity = "high"
             startup_warnings: Vec::new(),
             history: History::default(),
             ephemeral: false,
+            reindex_code_graph: false,
             file_opener: UriBasedFileOpener::VsCode,
             codex_linux_sandbox_exe: None,
             js_repl_node_path: None,
@@ -4872,6 +4922,8 @@ model_verbosity = "high"
             project_doc_max_bytes: PROJECT_DOC_MAX_BYTES,
             project_doc_fallback_filenames: Vec::new(),
             tool_output_token_limit: None,
+            tool_output_token_limits: HashMap::new(),
+            max_parallel_tool_calls: DEFAULT_MAX_PARALLEL_TOOL_CALLS,
             agent_max_threads: DEFAULT_AGENT_MAX_THREADS,
             agent_max_depth: DEFAULT_AGENT_MAX_DEPTH,
             agent_roles: BTreeMap::new(),
@@ -4882,6 +4934,7 @@ model_verbosity = "high"
             startup_warnings: Vec::new(),
             history: History::default(),
             ephemeral: false,
+            reindex_code_graph: false,
             file_opener: UriBasedFileOpener::VsCode,
             codex_linux_sandbox_exe: None,
             js_repl_node_path: None,
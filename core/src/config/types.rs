@@ -669,6 +669,36 @@ pub struct SkillsConfig {
     pub config: Vec<SkillConfig>,
 }
 
+/// Paths the read tools may open outside the repository. Only honored in
+/// the user's config.toml.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq, JsonSchema)]
+#[schemars(deny_unknown_fields)]
+pub struct SecurityToml {
+    /// Directories outside the repository that `read_file`, `list_dir`, and
+    /// `grep_files` may open too, absolute or relative to the repository root.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub extra_roots: Vec<PathBuf>,
+    /// Let the read tools open any path on the filesystem.
+    #[serde(default)]
+    pub allow_outside_repo: bool,
+}
+
+/// The server the code graph sends elements to for embedding. Only honored
+/// in the user's config.toml: it receives the repository's code and the
+/// user's key.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq, JsonSchema)]
+#[schemars(deny_unknown_fields)]
+pub struct EmbeddingsToml {
+    /// Base URL of the embedding server, e.g. `http://localhost:11434`.
+    pub endpoint: Option<String>,
+    /// Embedding model name sent with each request.
+    pub model: Option<String>,
+    /// Bearer token for `endpoint`. `HAPPY_EMBEDDINGS_API_KEY` overrides it.
+    pub api_key: Option<String>,
+    /// Wire format: `openai` (the default), `tei`, or `ollama`.
+    pub api: Option<String>,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default, JsonSchema)]
 #[schemars(deny_unknown_fields)]
 pub struct SandboxWorkspaceWrite {
@@ -111,6 +111,19 @@ impl ContextManager {
         &self.items
     }
 
+    /// Name of the tool invoked by the call with `call_id`, if that call is in history.
+    pub(crate) fn tool_name_for_call(&self, call_id: &str) -> Option<String> {
+        self.items.iter().rev().find_map(|item| match item {
+            ResponseItem::FunctionCall {
+                name, call_id: id, ..
+            }
+            | ResponseItem::CustomToolCall {
+                name, call_id: id, ..
+            } if id == call_id => Some(name.clone()),
+            _ => None,
+        })
+    }
+
     // Estimate token usage using byte-based heuristics from the truncation helpers.
     // This is a coarse lower bound, not a tokenizer-accurate count.
     pub(crate) fn estimate_token_count(&self, turn_context: &TurnContext) -> Option<i64> {
@@ -9,6 +9,8 @@ use codex_protocol::protocol::GitInfo;
 use futures::future::join_all;
 use serde::Deserialize;
 use serde::Serialize;
+use sha2::Digest;
+use sha2::Sha256;
 use tokio::process::Command;
 use tokio::time::Duration as TokioDuration;
 use tokio::time::timeout;
@@ -159,6 +161,55 @@ pub async fn get_has_changes(cwd: &Path) -> Option<bool> {
     Some(!output.stdout.is_empty())
 }
 
+/// Paths with uncommitted changes, untracked files included, relative to the
+/// repo root.
+pub async fn get_dirty_files(cwd: &Path) -> Option<Vec<String>> {
+    let output = run_git_command_with_timeout(
+        &[
+            "status",
+            "--porcelain",
+            "--untracked-files=all",
+            "--no-renames",
+        ],
+        cwd,
+    )
+    .await?;
+    if !output.status.success() {
+        return None;
+    }
+
+    let stdout = String::from_utf8(output.stdout).ok()?;
+    Some(
+        stdout
+            .lines()
+            .filter_map(|line| line.get(3..))
+            .map(|path| path.trim_matches('"').to_string())
+            .collect(),
+    )
+}
+
+/// Identify the repository independently of where it is checked out: a hash
+/// of its root commits, which every clone shares. `None` outside a git repo
+/// or before the first commit.
+pub async fn get_repo_fingerprint(cwd: &Path) -> Option<String> {
+    let output =
+        run_git_command_with_timeout(&["rev-list", "--max-parents=0", "HEAD"], cwd).await?;
+    if !output.status.success() {
+        return None;
+    }
+
+    let stdout = String::from_utf8(output.stdout).ok()?;
+    let mut roots: Vec<&str> = stdout.lines().map(str::trim).collect();
+    roots.retain(|root| !root.is_empty());
+    if roots.is_empty() {
+        return None;
+    }
+    roots.sort_unstable();
+    let mut hasher = Sha256::new();
+    hasher.update(roots.join("\n").as_bytes());
+    Some(format!("{:x}", hasher.finalize()))
+}
+
 fn parse_git_remote_urls(stdout: &str) -> Option<BTreeMap<String, String>> {
     let mut remotes = BTreeMap::new();
     for line in stdout.lines() {
@@ -12,6 +12,7 @@ mod apps;
 pub mod auth;
 mod client;
 mod client_common;
+pub mod code_summaries;
 pub mod codex;
 pub use codex::SteerInputError;
 mod codex_thread;
@@ -37,6 +38,7 @@ pub mod features;
 mod file_watcher;
 mod flags;
 pub mod git_info;
+pub mod handoff;
 pub mod instructions;
 pub mod landlock;
 pub mod mcp;
@@ -151,6 +153,8 @@ pub use exec_policy::format_exec_policy_error_with_source;
 pub use exec_policy::load_exec_policy;
 pub use file_watcher::FileWatcherEvent;
 pub use safety::get_platform_sandbox;
+pub use tools::handlers::CodeGraphUserConfig;
+pub use tools::handlers::serve_code_graph;
 pub use tools::spec::parse_tool_input_schema;
 pub use turn_metadata::build_turn_metadata_header;
 pub use zsh_exec_bridge::maybe_run_zsh_exec_wrapper_mode;
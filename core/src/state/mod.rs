@@ -1,9 +1,12 @@
 mod service;
 mod session;
+mod tool_output_stash;
 mod turn;
 
 pub(crate) use service::SessionServices;
 pub(crate) use session::SessionState;
+pub(crate) use tool_output_stash::ToolOutputStash;
+pub(crate) use tool_output_stash::tool_output_truncation_notice;
 pub(crate) use turn::ActiveTurn;
 pub(crate) use turn::RunningTask;
 pub(crate) use turn::TaskKind;
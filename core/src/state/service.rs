@@ -18,6 +18,9 @@ use crate::unified_exec::UnifiedExecProcessManager;
 use crate::zsh_exec_bridge::ZshExecBridge;
 use codex_hooks::Hooks;
 use codex_otel::OtelManager;
+use happy_core::redact::PathConfinement;
+use happy_core::redact::Redactor;
+use happy_core::tool_examples::ToolExamples;
 use tokio::sync::Mutex;
 use tokio::sync::RwLock;
 use tokio::sync::watch;
@@ -50,4 +53,13 @@ pub(crate) struct SessionServices {
     /// Shared handle to the indexed repo state (code graph + BM25).
     /// Starts as None and is populated by background indexing.
     pub(crate) code_graph_repo: crate::tools::handlers::SharedRepoHandle,
+    /// Code graph tool examples, sampled when the first turn builds its tools
+    /// and reused for the rest of the session.
+    pub(crate) code_graph_tool_examples: std::sync::OnceLock<ToolExamples>,
+    /// Secret redaction for `read_file` output, compiled from `[security]`
+    /// on first use.
+    pub(crate) read_redactor: std::sync::OnceLock<Redactor>,
+    /// Paths the read tools may open outside the repository, from
+    /// `[security]` in the user's config.toml, read on first use.
+    pub(crate) path_confinement: std::sync::OnceLock<PathConfinement>,
 }
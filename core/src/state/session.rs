@@ -9,8 +9,12 @@ use crate::context_manager::ContextManager;
 use crate::protocol::RateLimitSnapshot;
 use crate::protocol::TokenUsage;
 use crate::protocol::TokenUsageInfo;
+use crate::state::ToolOutputStash;
+use crate::state::tool_output_truncation_notice;
 use crate::tasks::RegularTask;
 use crate::truncate::TruncationPolicy;
+use crate::truncate::truncate_text;
+use codex_protocol::models::FunctionCallOutputBody;
 use codex_protocol::protocol::TurnContextItem;
 
 /// Persistent, session-scoped state previously stored directly on `Session`.
@@ -32,6 +36,8 @@ pub(crate) struct SessionState {
     pub(crate) startup_regular_task: Option<RegularTask>,
     pub(crate) active_mcp_tool_selection: Option<Vec<String>>,
     pub(crate) active_connector_selection: HashSet<String>,
+    /// Untruncated outputs of tool calls whose results were cut to fit the budget.
+    pub(crate) tool_outputs: ToolOutputStash,
 }
 
 impl SessionState {
@@ -50,6 +56,7 @@ impl SessionState {
             startup_regular_task: None,
             active_mcp_tool_selection: None,
             active_connector_selection: HashSet::new(),
+            tool_outputs: ToolOutputStash::default(),
         }
     }
 
@@ -62,6 +69,66 @@ impl SessionState {
         self.history.record_items(items, policy);
     }
 
+    /// Record `items`, truncating each tool output with the policy `policy_for`
+    /// returns for the tool that produced it. Outputs that are cut are stashed
+    /// in full so the model can fetch the rest with `get_tool_output`.
+    pub(crate) fn record_items_with_tool_policies<F>(
+        &mut self,
+        items: &[ResponseItem],
+        policy_for: F,
+    ) where
+        F: Fn(Option<&str>) -> TruncationPolicy,
+    {
+        for item in items {
+            let tool_name = match item {
+                ResponseItem::FunctionCallOutput { call_id, .. }
+                | ResponseItem::CustomToolCallOutput { call_id, .. } => {
+                    self.history.tool_name_for_call(call_id)
+                }
+                _ => None,
+            };
+            let policy = policy_for(tool_name.as_deref());
+            let item = self.stash_oversized_output(item, policy);
+            self.history.record_items(std::iter::once(&item), policy);
+        }
+    }
+
+    fn stash_oversized_output(
+        &mut self,
+        item: &ResponseItem,
+        policy: TruncationPolicy,
+    ) -> ResponseItem {
+        let (call_id, text) = match item {
+            ResponseItem::FunctionCallOutput { call_id, output } => match &output.body {
+                FunctionCallOutputBody::Text(text) => (call_id, text),
+                FunctionCallOutputBody::ContentItems(_) => return item.clone(),
+            },
+            ResponseItem::CustomToolCallOutput { call_id, output } => (call_id, output),
+            _ => return item.clone(),
+        };
+        // Leave room for the serialization slack the context manager allows.
+        if text.len() <= (policy * 1.2).byte_budget() {
+            return item.clone();
+        }
+
+        let mut truncated = truncate_text(text, policy);
+        truncated.push_str(&tool_output_truncation_notice(
+            call_id,
+            text.lines().count(),
+        ));
+        self.tool_outputs.insert(call_id, text.clone());
+
+        let mut item = item.clone();
+        match &mut item {
+            ResponseItem::FunctionCallOutput { output, .. } => {
+                output.body = FunctionCallOutputBody::Text(truncated);
+            }
+            ResponseItem::CustomToolCallOutput { output, .. } => *output = truncated,
+            _ => {}
+        }
+        item
+    }
+
     pub(crate) fn previous_model(&self) -> Option<String> {
         self.previous_model.clone()
     }
@@ -252,6 +319,7 @@ mod tests {
     use super::*;
     use crate::codex::make_session_configuration_for_tests;
     use crate::protocol::RateLimitWindow;
+    use codex_protocol::models::FunctionCallOutputPayload;
     use pretty_assertions::assert_eq;
 
     #[tokio::test]
@@ -286,6 +354,53 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn oversized_tool_outputs_use_per_tool_policy_and_are_stashed() {
+        let session_configuration = make_session_configuration_for_tests().await;
+        let mut state = SessionState::new(session_configuration);
+        let output = (0..400)
+            .map(|i| format!("line {i}"))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let call = |name: &str, call_id: &str| ResponseItem::FunctionCall {
+            id: None,
+            name: name.to_string(),
+            arguments: "{}".to_string(),
+            call_id: call_id.to_string(),
+        };
+        let result = |call_id: &str| ResponseItem::FunctionCallOutput {
+            call_id: call_id.to_string(),
+            output: FunctionCallOutputPayload::from_text(output.clone()),
+        };
+
+        state.record_items_with_tool_policies(
+            &[
+                call("shell", "big"),
+                result("big"),
+                call("read_file", "small"),
+                result("small"),
+            ],
+            |tool_name| match tool_name {
+                Some("shell") => TruncationPolicy::Tokens(10_000),
+                _ => TruncationPolicy::Tokens(50),
+            },
+        );
+
+        let texts = state
+            .history
+            .raw_items()
+            .iter()
+            .filter_map(|item| match item {
+                ResponseItem::FunctionCallOutput { output, .. } => output.text_content(),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(texts[0], output);
+        assert!(texts[1].contains("call_id \"small\""));
+        assert_eq!(state.tool_outputs.get("small"), Some(output.as_str()));
+        assert_eq!(state.tool_outputs.get("big"), None);
+    }
+
     #[tokio::test]
     async fn merge_mcp_tool_selection_empty_input_is_noop() {
         let session_configuration = make_session_configuration_for_tests().await;
@@ -0,0 +1,103 @@
+//! Full tool outputs kept around after truncation so the model can page
+//! through them with `get_tool_output`.
+
+use std::collections::VecDeque;
+
+/// Upper bound on the bytes held across all stashed outputs.
+pub(crate) const TOOL_OUTPUT_STASH_MAX_BYTES: usize = 16 * 1024 * 1024;
+
+/// Least-recently-used store of untruncated tool outputs, keyed by call id.
+pub(crate) struct ToolOutputStash {
+    entries: VecDeque<(String, String)>,
+    total_bytes: usize,
+    max_bytes: usize,
+}
+
+impl Default for ToolOutputStash {
+    fn default() -> Self {
+        Self::with_capacity_bytes(TOOL_OUTPUT_STASH_MAX_BYTES)
+    }
+}
+
+impl ToolOutputStash {
+    pub(crate) fn with_capacity_bytes(max_bytes: usize) -> Self {
+        Self {
+            entries: VecDeque::new(),
+            total_bytes: 0,
+            max_bytes,
+        }
+    }
+
+    /// Store the full output of `call_id`, evicting the least recently used
+    /// entries once the byte budget is exceeded. Outputs larger than the whole
+    /// budget are not kept.
+    pub(crate) fn insert(&mut self, call_id: &str, output: String) {
+        self.remove(call_id);
+        if output.len() > self.max_bytes {
+            return;
+        }
+        self.total_bytes += output.len();
+        self.entries.push_back((call_id.to_string(), output));
+        while self.total_bytes > self.max_bytes {
+            let Some((_, evicted)) = self.entries.pop_front() else {
+                break;
+            };
+            self.total_bytes -= evicted.len();
+        }
+    }
+
+    /// The full output of `call_id`, marking it as recently used.
+    pub(crate) fn get(&mut self, call_id: &str) -> Option<&str> {
+        let index = self.entries.iter().position(|(id, _)| id == call_id)?;
+        let entry = self.entries.remove(index)?;
+        self.entries.push_back(entry);
+        self.entries.back().map(|(_, output)| output.as_str())
+    }
+
+    fn remove(&mut self, call_id: &str) {
+        if let Some(index) = self.entries.iter().position(|(id, _)| id == call_id)
+            && let Some((_, output)) = self.entries.remove(index)
+        {
+            self.total_bytes -= output.len();
+        }
+    }
+}
+
+/// Appended to a truncated tool output so the model knows how to fetch the rest.
+pub(crate) fn tool_output_truncation_notice(call_id: &str, total_lines: usize) -> String {
+    format!(
+        "\n[Output truncated ({total_lines} lines in full). Call get_tool_output with call_id \"{call_id}\" and a start_line/end_line or start_byte/end_byte range to read more.]"
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn evicts_least_recently_used_outputs() {
+        let mut stash = ToolOutputStash::with_capacity_bytes(10);
+        stash.insert("a", "aaaa".to_string());
+        stash.insert("b", "bbbb".to_string());
+        assert_eq!(stash.get("a"), Some("aaaa"));
+
+        stash.insert("c", "cccc".to_string());
+
+        assert_eq!(stash.get("b"), None);
+        assert_eq!(stash.get("a"), Some("aaaa"));
+        assert_eq!(stash.get("c"), Some("cccc"));
+    }
+
+    #[test]
+    fn replaces_existing_entries_and_skips_oversized_outputs() {
+        let mut stash = ToolOutputStash::with_capacity_bytes(8);
+        stash.insert("a", "1234".to_string());
+        stash.insert("a", "56".to_string());
+        stash.insert("big", "123456789".to_string());
+
+        assert_eq!(stash.get("a"), Some("56"));
+        assert_eq!(stash.get("big"), None);
+        assert_eq!(stash.total_bytes, 2);
+    }
+}
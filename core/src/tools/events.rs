@@ -15,6 +15,7 @@ use crate::protocol::PatchApplyBeginEvent;
 use crate::protocol::PatchApplyEndEvent;
 use crate::protocol::PatchApplyStatus;
 use crate::protocol::TurnDiffEvent;
+use crate::state::tool_output_truncation_notice;
 use crate::tools::context::SharedTurnDiffTracker;
 use crate::tools::sandboxing::ToolError;
 use codex_protocol::parse_command::ParsedCommand;
@@ -31,6 +32,7 @@ pub(crate) struct ToolEventCtx<'a> {
     pub turn: &'a TurnContext,
     pub call_id: &'a str,
     pub turn_diff_tracker: Option<&'a SharedTurnDiffTracker>,
+    pub tool_name: Option<&'a str>,
 }
 
 impl<'a> ToolEventCtx<'a> {
@@ -45,8 +47,16 @@ impl<'a> ToolEventCtx<'a> {
             turn,
             call_id,
             turn_diff_tracker,
+            tool_name: None,
         }
     }
+
+    /// Use the `tool_output_token_limits` override for `tool_name` when
+    /// formatting output for the model.
+    pub fn with_tool_name(mut self, tool_name: &'a str) -> Self {
+        self.tool_name = Some(tool_name);
+        self
+    }
 }
 
 pub(crate) enum ToolEventStage {
@@ -179,6 +189,21 @@ impl ToolEmitter {
                     let mut guard = tracker.lock().await;
                     guard.on_patch_begin(changes);
                 }
+                let edited_paths: Vec<PathBuf> = changes
+                    .iter()
+                    .flat_map(|(path, change)| match change {
+                        FileChange::Update {
+                            move_path: Some(dest),
+                            ..
+                        } => vec![path.clone(), dest.clone()],
+                        _ => vec![path.clone()],
+                    })
+                    .collect();
+                crate::tools::handlers::record_code_graph_edit_baseline(
+                    &ctx.session.services.code_graph_repo,
+                    &edited_paths,
+                )
+                .await;
                 ctx.session
                     .send_event(
                         ctx.turn,
@@ -283,16 +308,29 @@ impl ToolEmitter {
         self.emit(ctx, ToolEventStage::Begin).await;
     }
 
-    fn format_exec_output_for_model(
+    async fn format_exec_output_for_model(
         &self,
         output: &ExecToolCallOutput,
         ctx: ToolEventCtx<'_>,
     ) -> String {
+        let policy = ctx.tool_name.map_or(ctx.turn.truncation_policy, |name| {
+            ctx.turn.tool_output_policy(name)
+        });
+        // Keep the full output so the model can page through what was cut.
+        let content = super::build_content_with_timeout(output);
+        let notice = if content.len() > policy.byte_budget() {
+            let notice = tool_output_truncation_notice(ctx.call_id, content.lines().count());
+            ctx.session.stash_tool_output(ctx.call_id, content).await;
+            Some(notice)
+        } else {
+            None
+        };
+
         match self {
             Self::Shell { freeform: true, .. } => {
-                super::format_exec_output_for_model_freeform(output, ctx.turn.truncation_policy)
+                super::format_exec_output_for_model_freeform(output, policy, notice.as_deref())
             }
-            _ => super::format_exec_output_for_model_structured(output, ctx.turn.truncation_policy),
+            _ => super::format_exec_output_for_model_structured(output, policy, notice.as_deref()),
         }
     }
 
@@ -303,7 +341,7 @@ impl ToolEmitter {
     ) -> Result<String, FunctionCallError> {
         let (event, result) = match out {
             Ok(output) => {
-                let content = self.format_exec_output_for_model(&output, ctx);
+                let content = self.format_exec_output_for_model(&output, ctx).await;
                 let exit_code = output.exit_code;
                 let event = ToolEventStage::Success(output);
                 let result = if exit_code == 0 {
@@ -315,7 +353,7 @@ impl ToolEmitter {
             }
             Err(ToolError::Codex(CodexErr::Sandbox(SandboxErr::Timeout { output })))
             | Err(ToolError::Codex(CodexErr::Sandbox(SandboxErr::Denied { output, .. }))) => {
-                let response = self.format_exec_output_for_model(&output, ctx);
+                let response = self.format_exec_output_for_model(&output, ctx).await;
                 let event = ToolEventStage::Failure(ToolEventFailure::Output(*output));
                 let result = Err(FunctionCallError::RespondToModel(response));
                 (event, result)
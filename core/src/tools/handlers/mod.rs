@@ -13,18 +13,39 @@ mod request_user_input;
 mod search_tool_bm25;
 mod shell;
 mod test_sync;
+mod tool_output;
 pub(crate) mod unified_exec;
 mod view_image;
 
+use codex_protocol::protocol::EventMsg;
+use codex_protocol::protocol::ToolCallResultEvent;
+use happy_core::redact::PathConfinement;
 pub use plan::PLAN_TOOL;
 use serde::Deserialize;
+use serde::de::DeserializeOwned;
+use std::path::Path;
+use std::path::PathBuf;
 
+use crate::codex::Session;
+use crate::codex::TurnContext;
+use crate::config::types::SecurityToml;
+use crate::config_loader::ConfigLayerStack;
 use crate::function_tool::FunctionCallError;
+use crate::git_info::get_git_repo_root;
 pub use apply_patch::ApplyPatchHandler;
 pub use code_graph::CodeGraphDispatcher;
 pub use code_graph::CodeGraphToolHandler;
+pub use code_graph::CodeGraphUpdates;
+pub use code_graph::CodeGraphUserConfig;
+pub(crate) use code_graph::GroundingAction;
 pub use code_graph::SharedRepoHandle;
+pub(crate) use code_graph::check_answer_grounding;
+pub(crate) use code_graph::grounding_enabled;
+pub(crate) use code_graph::record_code_graph_edit_baseline;
+pub(crate) use code_graph::reindex_code_graph;
+pub use code_graph::serve_code_graph;
 pub use code_graph::start_code_graph_indexing;
+pub(crate) use code_graph::verify_code_graph_edits;
 pub use dynamic::DynamicToolHandler;
 pub use grep_files::GrepFilesHandler;
 pub use js_repl::JsReplHandler;
@@ -43,6 +64,7 @@ pub use search_tool_bm25::SearchToolBm25Handler;
 pub use shell::ShellCommandHandler;
 pub use shell::ShellHandler;
 pub use test_sync::TestSyncHandler;
+pub use tool_output::GetToolOutputHandler;
 pub use unified_exec::UnifiedExecHandler;
 pub use view_image::ViewImageHandler;
 
@@ -54,3 +76,170 @@ where
         FunctionCallError::RespondToModel(format!("failed to parse function arguments: {err}"))
     })
 }
+
+/// Tell clients which files a finished tool call read or returned; relative
+/// paths are resolved against the turn's cwd. Calls that touched nothing
+/// send no event.
+async fn notify_touched_files(
+    session: &Session,
+    turn: &TurnContext,
+    call_id: &str,
+    tool_name: &str,
+    files: impl IntoIterator<Item = PathBuf>,
+) {
+    let mut touched_files: Vec<PathBuf> = Vec::new();
+    for file in files {
+        let file = turn.cwd.join(file);
+        if !touched_files.contains(&file) {
+            touched_files.push(file);
+        }
+    }
+    if touched_files.is_empty() {
+        return;
+    }
+    session
+        .send_event(
+            turn,
+            EventMsg::ToolCallResult(ToolCallResultEvent {
+                call_id: call_id.to_string(),
+                tool_name: tool_name.to_string(),
+                touched_files,
+            }),
+        )
+        .await;
+}
+
+/// Refuse paths outside the git repository containing the turn's cwd (or
+/// the cwd itself outside git), unless `[security]` in the user's
+/// config.toml allows them.
+fn check_repo_path(
+    session: &Session,
+    turn: &TurnContext,
+    path: &Path,
+) -> Result<(), FunctionCallError> {
+    let confinement = session
+        .services
+        .path_confinement
+        .get_or_init(|| user_path_confinement(&turn.config.config_layer_stack));
+    let repo_root = get_git_repo_root(&turn.cwd).unwrap_or_else(|| turn.cwd.clone());
+    confinement
+        .check_path(&repo_root, path)
+        .map_err(|err| FunctionCallError::RespondToModel(err.to_string()))
+}
+
+/// `[security]` from the user layer only; project layers live in the
+/// repository, which must not widen its own confinement.
+fn user_path_confinement(config_layer_stack: &ConfigLayerStack) -> PathConfinement {
+    let Some(security) = user_config_section::<SecurityToml>(config_layer_stack, "security") else {
+        return PathConfinement::default();
+    };
+    PathConfinement {
+        extra_roots: security.extra_roots,
+        allow_outside_repo: security.allow_outside_repo,
+    }
+}
+
+/// The `[name]` table of the user's config.toml, ignoring project layers.
+/// An invalid table is logged and treated as absent.
+fn user_config_section<T: DeserializeOwned>(
+    config_layer_stack: &ConfigLayerStack,
+    name: &str,
+) -> Option<T> {
+    let section = config_layer_stack
+        .get_user_layer()
+        .and_then(|layer| layer.config.get(name))?;
+    match section.clone().try_into() {
+        Ok(section) => Some(section),
+        Err(err) => {
+            tracing::warn!(error = %err, "ignoring invalid [{name}] config in config.toml");
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config_loader::ConfigLayerEntry;
+    use crate::config_loader::ConfigRequirements;
+    use crate::config_loader::ConfigRequirementsToml;
+    use codex_app_server_protocol::ConfigLayerSource;
+    use codex_utils_absolute_path::AbsolutePathBuf;
+    use pretty_assertions::assert_eq;
+    use toml::Value as TomlValue;
+
+    fn security_layer(roots: &str) -> TomlValue {
+        toml::from_str(&format!("[security]\nextra_roots = [{roots}]\n")).expect("parse toml")
+    }
+
+    #[test]
+    fn path_confinement_comes_from_the_user_layer_only() -> anyhow::Result<()> {
+        let tmp = tempfile::tempdir()?;
+        let user_file = AbsolutePathBuf::from_absolute_path(tmp.path().join("config.toml"))?;
+        let dot_codex = AbsolutePathBuf::from_absolute_path(tmp.path().join("repo/.codex"))?;
+        let stack = ConfigLayerStack::new(
+            vec![
+                ConfigLayerEntry::new(
+                    ConfigLayerSource::User { file: user_file },
+                    security_layer("\"../shared\""),
+                ),
+                ConfigLayerEntry::new(
+                    ConfigLayerSource::Project {
+                        dot_codex_folder: dot_codex,
+                    },
+                    security_layer("\"/\""),
+                ),
+            ],
+            ConfigRequirements::default(),
+            ConfigRequirementsToml::default(),
+        )?;
+
+        assert_eq!(
+            user_path_confinement(&stack),
+            PathConfinement {
+                extra_roots: vec![PathBuf::from("../shared")],
+                allow_outside_repo: false,
+            }
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn embeddings_endpoint_comes_from_the_user_layer_only() -> anyhow::Result<()> {
+        let tmp = tempfile::tempdir()?;
+        let user_file = AbsolutePathBuf::from_absolute_path(tmp.path().join("config.toml"))?;
+        let dot_codex = AbsolutePathBuf::from_absolute_path(tmp.path().join("repo/.codex"))?;
+        let project = ConfigLayerEntry::new(
+            ConfigLayerSource::Project {
+                dot_codex_folder: dot_codex,
+            },
+            toml::from_str("[embeddings]\nendpoint = \"http://attacker.example\"\n")?,
+        );
+        let stack = ConfigLayerStack::new(
+            vec![project.clone()],
+            ConfigRequirements::default(),
+            ConfigRequirementsToml::default(),
+        )?;
+        assert_eq!(CodeGraphUserConfig::load(&stack).embeddings, None);
+
+        let stack = ConfigLayerStack::new(
+            vec![
+                ConfigLayerEntry::new(
+                    ConfigLayerSource::User { file: user_file },
+                    toml::from_str(
+                        "[embeddings]\nendpoint = \"http://localhost:11434\"\napi = \"ollama\"\n",
+                    )?,
+                ),
+                project,
+            ],
+            ConfigRequirements::default(),
+            ConfigRequirementsToml::default(),
+        )?;
+        let endpoint = CodeGraphUserConfig::load(&stack)
+            .embeddings
+            .expect("user endpoint");
+        assert_eq!(endpoint.endpoint, "http://localhost:11434");
+        assert_eq!(endpoint.api, happy_core::vector::EmbeddingApi::Ollama);
+        Ok(())
+    }
+}
@@ -333,7 +333,8 @@ impl ShellHandler {
             .run(&mut runtime, &req, &tool_ctx, &turn, turn.approval_policy)
             .await
             .map(|result| result.output);
-        let event_ctx = ToolEventCtx::new(session.as_ref(), turn.as_ref(), &call_id, None);
+        let event_ctx = ToolEventCtx::new(session.as_ref(), turn.as_ref(), &call_id, None)
+            .with_tool_name(&tool_ctx.tool_name);
         let content = emitter.finish(event_ctx, out).await?;
         Ok(ToolOutput::Function {
             body: FunctionCallOutputBody::Text(content),
@@ -0,0 +1,135 @@
+use async_trait::async_trait;
+use codex_protocol::models::FunctionCallOutputBody;
+use serde::Deserialize;
+
+use crate::function_tool::FunctionCallError;
+use crate::tools::context::ToolInvocation;
+use crate::tools::context::ToolOutput;
+use crate::tools::context::ToolPayload;
+use crate::tools::handlers::parse_arguments;
+use crate::tools::registry::ToolHandler;
+use crate::tools::registry::ToolKind;
+
+/// Serves ranges of tool outputs that were truncated before reaching the model.
+pub struct GetToolOutputHandler;
+
+#[derive(Deserialize)]
+struct GetToolOutputArgs {
+    call_id: String,
+    #[serde(default)]
+    start_line: Option<usize>,
+    #[serde(default)]
+    end_line: Option<usize>,
+    #[serde(default)]
+    start_byte: Option<usize>,
+    #[serde(default)]
+    end_byte: Option<usize>,
+}
+
+#[async_trait]
+impl ToolHandler for GetToolOutputHandler {
+    fn kind(&self) -> ToolKind {
+        ToolKind::Function
+    }
+
+    async fn handle(&self, invocation: ToolInvocation) -> Result<ToolOutput, FunctionCallError> {
+        let ToolInvocation {
+            session, payload, ..
+        } = invocation;
+
+        let arguments = match payload {
+            ToolPayload::Function { arguments } => arguments,
+            _ => {
+                return Err(FunctionCallError::RespondToModel(
+                    "get_tool_output handler received unsupported payload".to_string(),
+                ));
+            }
+        };
+        let args: GetToolOutputArgs = parse_arguments(&arguments)?;
+
+        let Some(output) = session.stashed_tool_output(&args.call_id).await else {
+            return Err(FunctionCallError::RespondToModel(format!(
+                "no stored output for call_id `{}`; only truncated outputs from this session are kept, and older ones may have been evicted",
+                args.call_id
+            )));
+        };
+
+        let content = if args.start_byte.is_some() || args.end_byte.is_some() {
+            byte_range(&output, args.start_byte, args.end_byte)
+        } else {
+            line_range(&output, args.start_line, args.end_line)
+        }
+        .map_err(FunctionCallError::RespondToModel)?;
+
+        Ok(ToolOutput::Function {
+            body: FunctionCallOutputBody::Text(content),
+            success: Some(true),
+        })
+    }
+}
+
+/// Lines `start..=end` (1-indexed) of `output`.
+fn line_range(output: &str, start: Option<usize>, end: Option<usize>) -> Result<String, String> {
+    let total = output.lines().count();
+    let start = start.unwrap_or(1).max(1);
+    let end = end.unwrap_or(total).min(total);
+    if start > end {
+        return Err(format!(
+            "line range {start}..={end} is empty; the output has {total} lines"
+        ));
+    }
+    Ok(output
+        .lines()
+        .skip(start - 1)
+        .take(end - start + 1)
+        .collect::<Vec<_>>()
+        .join("\n"))
+}
+
+/// Bytes `start..end` of `output`, widened to the nearest character boundaries.
+fn byte_range(output: &str, start: Option<usize>, end: Option<usize>) -> Result<String, String> {
+    let total = output.len();
+    let mut start = start.unwrap_or(0).min(total);
+    let mut end = end.unwrap_or(total).min(total);
+    if start >= end {
+        return Err(format!(
+            "byte range {start}..{end} is empty; the output has {total} bytes"
+        ));
+    }
+    while !output.is_char_boundary(start) {
+        start -= 1;
+    }
+    while !output.is_char_boundary(end) {
+        end += 1;
+    }
+    Ok(output[start..end].to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn line_range_is_one_indexed_and_inclusive() {
+        let output = "a\nb\nc\nd";
+        assert_eq!(line_range(output, Some(2), Some(3)), Ok("b\nc".to_string()));
+        assert_eq!(line_range(output, None, Some(1)), Ok("a".to_string()));
+        assert_eq!(
+            line_range(output, Some(3), Some(99)),
+            Ok("c\nd".to_string())
+        );
+        assert!(line_range(output, Some(5), None).is_err());
+    }
+
+    #[test]
+    fn byte_range_widens_to_char_boundaries() {
+        let output = "ab\u{e9}cd";
+        assert_eq!(
+            byte_range(output, Some(3), Some(4)),
+            Ok("\u{e9}".to_string())
+        );
+        assert_eq!(byte_range(output, Some(4), None), Ok("cd".to_string()));
+        assert!(byte_range(output, Some(6), Some(6)).is_err());
+    }
+}
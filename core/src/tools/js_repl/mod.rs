@@ -1009,7 +1009,9 @@ impl JsReplManager {
             .await;
 
         let router = ToolRouter::from_config(
-            &exec.turn.tools_config,
+            &exec
+                .session
+                .tools_config_with_examples(&exec.turn.tools_config),
             Some(
                 mcp_tools
                     .into_iter()
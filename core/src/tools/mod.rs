@@ -26,9 +26,11 @@ pub(crate) const TELEMETRY_PREVIEW_TRUNCATION_NOTICE: &str =
 
 /// Format the combined exec output for sending back to the model.
 /// Includes exit code and duration metadata; truncates large bodies safely.
+/// `truncation_notice`, when given, is appended to the output text.
 pub fn format_exec_output_for_model_structured(
     exec_output: &ExecToolCallOutput,
     truncation_policy: TruncationPolicy,
+    truncation_notice: Option<&str>,
 ) -> String {
     let ExecToolCallOutput {
         exit_code,
@@ -51,7 +53,10 @@ pub fn format_exec_output_for_model_structured(
     // round to 1 decimal place
     let duration_seconds = ((duration.as_secs_f32()) * 10.0).round() / 10.0;
 
-    let formatted_output = format_exec_output_str(exec_output, truncation_policy);
+    let mut formatted_output = format_exec_output_str(exec_output, truncation_policy);
+    if let Some(notice) = truncation_notice {
+        formatted_output.push_str(notice);
+    }
 
     let payload = ExecOutput {
         output: &formatted_output,
@@ -68,6 +73,7 @@ pub fn format_exec_output_for_model_structured(
 pub fn format_exec_output_for_model_freeform(
     exec_output: &ExecToolCallOutput,
     truncation_policy: TruncationPolicy,
+    truncation_notice: Option<&str>,
 ) -> String {
     // round to 1 decimal place
     let duration_seconds = ((exec_output.duration.as_secs_f32()) * 10.0).round() / 10.0;
@@ -76,7 +82,10 @@ pub fn format_exec_output_for_model_freeform(
 
     let total_lines = content.lines().count();
 
-    let formatted_output = truncate_text(&content, truncation_policy);
+    let mut formatted_output = truncate_text(&content, truncation_policy);
+    if let Some(notice) = truncation_notice {
+        formatted_output.push_str(notice);
+    }
 
     let mut sections = Vec::new();
 
@@ -2,6 +2,7 @@ use std::sync::Arc;
 use std::time::Instant;
 
 use tokio::sync::RwLock;
+use tokio::sync::Semaphore;
 use tokio_util::either::Either;
 use tokio_util::sync::CancellationToken;
 use tokio_util::task::AbortOnDropHandle;
@@ -28,6 +29,8 @@ pub(crate) struct ToolCallRuntime {
     turn_context: Arc<TurnContext>,
     tracker: SharedTurnDiffTracker,
     parallel_execution: Arc<RwLock<()>>,
+    /// Caps how many parallel-safe tools run at once.
+    parallel_slots: Arc<Semaphore>,
 }
 
 impl ToolCallRuntime {
@@ -37,12 +40,16 @@ impl ToolCallRuntime {
         turn_context: Arc<TurnContext>,
         tracker: SharedTurnDiffTracker,
     ) -> Self {
+        let parallel_slots = Arc::new(Semaphore::new(
+            turn_context.config.max_parallel_tool_calls.max(1),
+        ));
         Self {
             router,
             session,
             turn_context,
             tracker,
             parallel_execution: Arc::new(RwLock::new(())),
+            parallel_slots,
         }
     }
 
@@ -59,6 +66,7 @@ impl ToolCallRuntime {
         let turn = Arc::clone(&self.turn_context);
         let tracker = Arc::clone(&self.tracker);
         let lock = Arc::clone(&self.parallel_execution);
+        let slots = Arc::clone(&self.parallel_slots);
         let started = Instant::now();
 
         let dispatch_span = trace_span!(
@@ -78,6 +86,13 @@ impl ToolCallRuntime {
                         Ok(Self::aborted_response(&call, secs))
                     },
                     res = async {
+                        // Tools that mutate state run alone; the rest share
+                        // the read lock, at most `max_parallel_tool_calls` at a time.
+                        let _slot = if supports_parallel {
+                            slots.acquire_owned().await.ok()
+                        } else {
+                            None
+                        };
                         let _guard = if supports_parallel {
                             Either::Left(lock.read().await)
                         } else {
@@ -22,6 +22,7 @@ use codex_protocol::models::VIEW_IMAGE_TOOL_NAME;
 use codex_protocol::openai_models::ApplyPatchToolType;
 use codex_protocol::openai_models::ConfigShellToolType;
 use codex_protocol::openai_models::ModelInfo;
+use happy_core::tool_examples::ToolExamples;
 use serde::Deserialize;
 use serde::Serialize;
 use serde_json::Value as JsonValue;
@@ -44,6 +45,9 @@ pub(crate) struct ToolsConfig {
     pub collab_tools: bool,
     pub collaboration_modes_tools: bool,
     pub experimental_supported_tools: Vec<String>,
+    /// Example calls for the code graph tool descriptions, fixed for the
+    /// session so the tool list stays byte-identical between requests.
+    pub code_graph_examples: Option<ToolExamples>,
 }
 
 pub(crate) struct ToolsConfigParams<'a> {
@@ -105,6 +109,7 @@ impl ToolsConfig {
             collab_tools: include_collab_tools,
             collaboration_modes_tools: include_collaboration_modes_tools,
             experimental_supported_tools: model_info.experimental_supported_tools.clone(),
+            code_graph_examples: None,
         }
     }
 
@@ -112,6 +117,11 @@ impl ToolsConfig {
         self.agent_roles = agent_roles;
         self
     }
+
+    pub fn with_code_graph_examples(mut self, examples: ToolExamples) -> Self {
+        self.code_graph_examples = Some(examples);
+        self
+    }
 }
 
 /// Generic JSON‑Schema subset needed for our tool definitions
@@ -469,6 +479,59 @@ fn create_view_image_tool() -> ToolSpec {
     })
 }
 
+fn create_get_tool_output_tool() -> ToolSpec {
+    let properties = BTreeMap::from([
+        (
+            "call_id".to_string(),
+            JsonSchema::String {
+                description: Some("call_id of the tool call whose output was truncated.".to_string()),
+            },
+        ),
+        (
+            "start_line".to_string(),
+            JsonSchema::Number {
+                description: Some("1-indexed first line to return. Defaults to 1.".to_string()),
+            },
+        ),
+        (
+            "end_line".to_string(),
+            JsonSchema::Number {
+                description: Some(
+                    "1-indexed last line to return, inclusive. Defaults to the end of the output."
+                        .to_string(),
+                ),
+            },
+        ),
+        (
+            "start_byte".to_string(),
+            JsonSchema::Number {
+                description: Some(
+                    "0-indexed byte offset to start at. Use instead of lines for single-line output."
+                        .to_string(),
+                ),
+            },
+        ),
+        (
+            "end_byte".to_string(),
+            JsonSchema::Number {
+                description: Some("Byte offset to stop at, exclusive.".to_string()),
+            },
+        ),
+    ]);
+
+    ToolSpec::Function(ResponsesApiTool {
+        name: "get_tool_output".to_string(),
+        description: "Read more of a tool output that was truncated. Pass the call_id from the truncation notice and a line or byte range; the result is itself truncated to the usual budget, so request narrower ranges for large outputs."
+            .to_string(),
+        strict: false,
+        parameters: JsonSchema::Object {
+            properties,
+            required: Some(vec!["call_id".to_string()]),
+            additional_properties: Some(false.into()),
+        },
+    })
+}
+
 fn create_collab_input_items_schema() -> JsonSchema {
     let properties = BTreeMap::from([
         (
@@ -1404,6 +1467,7 @@ pub(crate) fn build_specs(
 ) -> ToolRegistryBuilder {
     use crate::tools::handlers::ApplyPatchHandler;
     use crate::tools::handlers::DynamicToolHandler;
+    use crate::tools::handlers::GetToolOutputHandler;
     use crate::tools::handlers::GrepFilesHandler;
     use crate::tools::handlers::JsReplHandler;
     use crate::tools::handlers::JsReplResetHandler;
@@ -1564,6 +1628,9 @@ pub(crate) fn build_specs(
     builder.push_spec_with_parallel_support(create_view_image_tool(), true);
     builder.register_handler("view_image", view_image_handler);
 
+    builder.push_spec_with_parallel_support(create_get_tool_output_tool(), true);
+    builder.register_handler("get_tool_output", Arc::new(GetToolOutputHandler));
+
     if config.collab_tools {
         let multi_agent_handler = Arc::new(MultiAgentHandler);
         builder.push_spec(create_spawn_agent_tool(config));
@@ -1616,7 +1683,11 @@ pub(crate) fn build_specs(
     // These are always registered. The handler checks at runtime
     // whether a repo has been indexed; if not, tools return a
     // helpful error message telling the user to index first.
-    register_code_graph_tools(&mut builder, code_graph_repo);
+    register_code_graph_tools(
+        &mut builder,
+        code_graph_repo,
+        config.code_graph_examples.clone(),
+    );
 
     builder
 }
@@ -1624,32 +1695,37 @@ pub(crate) fn build_specs(
 /// Register the happy-core code graph tools into the builder.
 /// A single `CodeGraphToolHandler` (backed by the session-level `SharedRepoHandle`)
 /// is registered for all code graph tool names so dispatch works as soon as the repo
-/// has been indexed.
+/// has been indexed. Descriptions carry the session's example calls, or ones sampled
+/// now when the config has none.
 fn register_code_graph_tools(
     builder: &mut ToolRegistryBuilder,
     shared_repo: crate::tools::handlers::SharedRepoHandle,
+    examples: Option<ToolExamples>,
 ) {
     use crate::tools::handlers::CodeGraphDispatcher;
     use crate::tools::handlers::CodeGraphToolHandler;
-    use crate::tools::handlers::code_graph::CODE_GRAPH_TOOL_NAMES;
+    use crate::tools::handlers::code_graph::code_graph_tool_examples;
+    use crate::tools::handlers::code_graph::code_graph_tool_parameters;
+    use happy_core::tool_spec::CODE_GRAPH_TOOLS;
     use std::sync::Arc;
 
+    let examples = examples.unwrap_or_else(|| code_graph_tool_examples(&shared_repo));
     let dispatcher = Arc::new(CodeGraphDispatcher::new(shared_repo));
     let handler = Arc::new(CodeGraphToolHandler {
         dispatcher: dispatcher.clone(),
     });
 
-    for &(name, description, params_fn) in CODE_GRAPH_TOOL_NAMES {
+    for tool in CODE_GRAPH_TOOLS {
         builder.push_spec_with_parallel_support(
             ToolSpec::Function(ResponsesApiTool {
-                name: name.to_string(),
-                description: description.to_string(),
+                name: tool.name.to_string(),
+                description: examples.describe(tool),
                 strict: false,
-                parameters: params_fn(),
+                parameters: code_graph_tool_parameters(tool),
             }),
             true,
         );
-        builder.register_handler(name, handler.clone());
+        builder.register_handler(tool.name, handler.clone());
     }
 }
 
@@ -1851,6 +1927,7 @@ mod tests {
                 external_web_access: Some(true),
             },
             create_view_image_tool(),
+            create_get_tool_output_tool(),
         ] {
             expected.insert(tool_name(&spec).to_string(), spec);
         }
@@ -2113,6 +2190,7 @@ mod tests {
                 "apply_patch",
                 "web_search",
                 "view_image",
+                "get_tool_output",
             ],
         );
     }
@@ -2132,6 +2210,7 @@ mod tests {
                 "apply_patch",
                 "web_search",
                 "view_image",
+                "get_tool_output",
             ],
         );
     }
@@ -2153,6 +2232,7 @@ mod tests {
                 "apply_patch",
                 "web_search",
                 "view_image",
+                "get_tool_output",
             ],
         );
     }
@@ -2174,6 +2254,7 @@ mod tests {
                 "apply_patch",
                 "web_search",
                 "view_image",
+                "get_tool_output",
             ],
         );
     }
@@ -2193,6 +2274,7 @@ mod tests {
                 "apply_patch",
                 "web_search",
                 "view_image",
+                "get_tool_output",
             ],
         );
     }
@@ -2212,6 +2294,7 @@ mod tests {
                 "apply_patch",
                 "web_search",
                 "view_image",
+                "get_tool_output",
             ],
         );
     }
@@ -2230,6 +2313,7 @@ mod tests {
                 "request_user_input",
                 "web_search",
                 "view_image",
+                "get_tool_output",
             ],
         );
     }
@@ -2249,6 +2333,7 @@ mod tests {
                 "apply_patch",
                 "web_search",
                 "view_image",
+                "get_tool_output",
             ],
         );
     }
@@ -2270,6 +2355,7 @@ mod tests {
                 "apply_patch",
                 "web_search",
                 "view_image",
+                "get_tool_output",
             ],
         );
     }
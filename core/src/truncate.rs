@@ -59,6 +59,15 @@ impl TruncationPolicy {
             TruncationPolicy::Tokens(tokens) => approx_bytes_for_tokens(*tokens),
         }
     }
+
+    /// A policy of the same kind with a budget of `tokens`, converted to
+    /// bytes for a byte policy.
+    pub fn with_token_limit(self, tokens: usize) -> Self {
+        match self {
+            TruncationPolicy::Bytes(_) => TruncationPolicy::Bytes(approx_bytes_for_tokens(tokens)),
+            TruncationPolicy::Tokens(_) => TruncationPolicy::Tokens(tokens),
+        }
+    }
 }
 
 impl std::ops::Mul<f64> for TruncationPolicy {
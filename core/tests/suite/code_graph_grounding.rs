@@ -0,0 +1,141 @@
+#![cfg(not(target_os = "windows"))]
+
+use codex_core::protocol::AskForApproval;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::Op;
+use codex_core::protocol::SandboxPolicy;
+use codex_protocol::config_types::ReasoningSummary;
+use codex_protocol::user_input::UserInput;
+use core_test_support::responses::ResponsesRequest;
+use core_test_support::responses::ev_assistant_message;
+use core_test_support::responses::ev_completed;
+use core_test_support::responses::ev_response_created;
+use core_test_support::responses::mount_sse_once;
+use core_test_support::responses::mount_sse_sequence;
+use core_test_support::responses::sse;
+use core_test_support::responses::start_mock_server;
+use core_test_support::skip_if_no_network;
+use core_test_support::test_codex::TestCodex;
+use core_test_support::test_codex::test_codex;
+use core_test_support::wait_for_event;
+use pretty_assertions::assert_eq;
+
+/// A final answer claiming a call relationship no graph tool was asked about.
+const UNGROUNDED_ANSWER: &str = "`main()` calls `helper()`, which returns 1.";
+
+/// Start a session whose cwd holds a small Python repo with
+/// `[agent] grounding = "<mode>"`, and wait until its code graph is indexed.
+async fn indexed_session(server: &wiremock::MockServer, mode: &str) -> anyhow::Result<TestCodex> {
+    let agent_toml = format!("[agent]\ngrounding = \"{mode}\"\n");
+    let test = test_codex()
+        .with_config(move |config| {
+            let cwd = &config.cwd;
+            std::fs::write(
+                cwd.join("app.py"),
+                "def helper():\n    return 1\n\ndef main():\n    return helper()\n",
+            )
+            .expect("write app.py");
+            std::fs::create_dir_all(cwd.join(".happy")).expect("create .happy");
+            std::fs::write(cwd.join(".happy/agent.toml"), agent_toml).expect("write agent.toml");
+        })
+        .build(server)
+        .await?;
+    wait_for_event(
+        &test.codex,
+        |event| matches!(event, EventMsg::CodeGraphUpdated(update) if update.file_count > 0),
+    )
+    .await;
+    Ok(test)
+}
+
+/// Submit a turn without waiting for it, so its events can be inspected.
+async fn submit(test: &TestCodex, prompt: &str) -> anyhow::Result<()> {
+    test.codex
+        .submit(Op::UserTurn {
+            items: vec![UserInput::Text {
+                text: prompt.to_string(),
+                text_elements: Vec::new(),
+            }],
+            final_output_json_schema: None,
+            cwd: test.cwd_path().to_path_buf(),
+            approval_policy: AskForApproval::Never,
+            sandbox_policy: SandboxPolicy::DangerFullAccess,
+            model: test.session_configured.model.clone(),
+            effort: None,
+            summary: ReasoningSummary::Auto,
+            collaboration_mode: None,
+            personality: None,
+        })
+        .await?;
+    Ok(())
+}
+
+fn answer(id: &str, text: &str) -> String {
+    sse(vec![
+        ev_response_created(id),
+        ev_assistant_message(&format!("{id}-msg"), text),
+        ev_completed(id),
+    ])
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn warn_mode_adds_a_caveat_to_ungrounded_answers() -> anyhow::Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let test = indexed_session(&server, "warn").await?;
+    let mock = mount_sse_once(&server, answer("resp-1", UNGROUNDED_ANSWER)).await;
+
+    submit(&test, "what does main do?").await?;
+    let warning = wait_for_event(&test.codex, |event| {
+        matches!(event, EventMsg::Warning(warning) if warning.message.contains("Unverified code relationships"))
+    })
+    .await;
+    let EventMsg::Warning(warning) = warning else {
+        panic!("expected warning event");
+    };
+    assert!(warning.message.contains("`helper`"), "{}", warning.message);
+    wait_for_event(&test.codex, |event| {
+        matches!(event, EventMsg::TurnComplete(_))
+    })
+    .await;
+
+    // The answer stands; warn mode sends no follow-up request.
+    assert_eq!(mock.requests().len(), 1);
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn enforce_mode_asks_the_model_to_verify_once() -> anyhow::Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let test = indexed_session(&server, "enforce").await?;
+    // The second answer repeats the claim; a turn is checked only once.
+    let mock = mount_sse_sequence(
+        &server,
+        vec![
+            answer("resp-1", UNGROUNDED_ANSWER),
+            answer("resp-2", UNGROUNDED_ANSWER),
+        ],
+    )
+    .await;
+
+    submit(&test, "what does main do?").await?;
+    wait_for_event(&test.codex, |event| {
+        matches!(event, EventMsg::TurnComplete(_))
+    })
+    .await;
+
+    let requests = mock.requests();
+    assert_eq!(requests.len(), 2);
+    let asks_to_verify = |request: &ResponsesRequest| {
+        request
+            .message_input_texts("developer")
+            .iter()
+            .any(|text| text.contains("verify these code relationship claims"))
+    };
+    assert!(!asks_to_verify(&requests[0]));
+    assert!(asks_to_verify(&requests[1]));
+    Ok(())
+}
@@ -64,6 +64,7 @@ mod auth_refresh;
 mod cli_stream;
 mod client;
 mod client_websockets;
+mod code_graph_grounding;
 mod codex_delegate;
 mod collaboration_instructions;
 mod compact;
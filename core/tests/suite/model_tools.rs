@@ -79,6 +79,7 @@ async fn model_selects_expected_tools() {
                 "apply_patch",
                 "web_search",
                 "view_image",
+                "get_tool_output",
             ],
         ),
         "gpt-5.1-codex-max should expose the apply_patch tool",
@@ -95,6 +96,7 @@ async fn model_selects_expected_tools() {
                 "apply_patch",
                 "web_search",
                 "view_image",
+                "get_tool_output",
             ],
         ),
         "gpt-5-codex should expose the apply_patch tool",
@@ -111,6 +113,7 @@ async fn model_selects_expected_tools() {
                 "apply_patch",
                 "web_search",
                 "view_image",
+                "get_tool_output",
             ],
         ),
         "gpt-5.1-codex should expose the apply_patch tool",
@@ -126,6 +129,7 @@ async fn model_selects_expected_tools() {
                 "request_user_input",
                 "web_search",
                 "view_image",
+                "get_tool_output",
             ],
         ),
         "gpt-5 should expose the apply_patch tool",
@@ -142,6 +146,7 @@ async fn model_selects_expected_tools() {
                 "apply_patch",
                 "web_search",
                 "view_image",
+                "get_tool_output",
             ],
         ),
         "gpt-5.1 should expose the apply_patch tool",
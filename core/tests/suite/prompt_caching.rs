@@ -149,6 +149,7 @@ async fn prompt_tools_are_consistent_across_requests() -> anyhow::Result<()> {
         "apply_patch",
         "web_search",
         "view_image",
+        "get_tool_output",
     ]);
     let body0 = req1.single_request().body_json();
 
@@ -413,3 +413,61 @@ async fn shell_tools_start_before_response_completed_when_stream_delayed() -> an
 
     Ok(())
 }
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn parallel_tools_respect_max_parallel_tool_calls() -> anyhow::Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let mut builder = test_codex().with_model("gpt-5.1");
+    let test = builder.build(&server).await?;
+
+    // Each call logs when it starts and ends, so the log shows how many ran
+    // at once.
+    let trace = tempfile::NamedTempFile::new()?;
+    let command = format!(
+        "echo start >> \"{path}\"; sleep 0.5; echo end >> \"{path}\"",
+        path = trace.path().display()
+    );
+    let args = json!({
+        "command": command,
+        // Avoid user-specific shell startup cost in timing assertions.
+        "login": false,
+        "timeout_ms": 5_000,
+    });
+
+    // Two more calls than the default limit of 4.
+    let mut events = vec![ev_response_created("resp-1")];
+    events.extend((1..=6).map(|i| ev_shell_command_call_with_args(&format!("call-{i}"), &args)));
+    events.push(ev_completed("resp-1"));
+    mount_sse_sequence(
+        &server,
+        vec![
+            sse(events),
+            sse(vec![
+                ev_assistant_message("msg-1", "done"),
+                ev_completed("resp-2"),
+            ]),
+        ],
+    )
+    .await;
+
+    run_turn(&test, "run six slow shell commands").await?;
+
+    let log = fs::read_to_string(trace.path())?;
+    let mut running = 0usize;
+    let mut peak = 0usize;
+    for line in log.lines() {
+        match line.trim() {
+            "start" => running += 1,
+            "end" => running -= 1,
+            other => panic!("unexpected trace line {other:?}"),
+        }
+        peak = peak.max(running);
+    }
+    assert_eq!(log.lines().count(), 12, "{log}");
+    assert!(peak <= 4, "{peak} calls ran at once:\n{log}");
+    assert!(peak > 1, "calls ran one at a time:\n{log}");
+
+    Ok(())
+}
@@ -0,0 +1,98 @@
+//! The per-repo agent config in `.happy/agent.toml`.
+//!
+//! Every subsystem reads its own table (`[index]`, `[watch]`, `[security]`,
+//! ...) from this one file. [`AgentToml`] reads and parses it once; each
+//! config type then deserializes its table with [`AgentToml::section`].
+
+use std::io;
+use std::path::Path;
+
+use serde::de::DeserializeOwned;
+
+/// Path of the agent config, relative to the repo root.
+pub const AGENT_TOML_FILE: &str = ".happy/agent.toml";
+
+/// The parsed top-level tables of `.happy/agent.toml`.
+#[derive(Debug, Clone, Default)]
+pub struct AgentToml {
+    table: toml::Table,
+}
+
+impl AgentToml {
+    /// Read `<repo_root>/.happy/agent.toml`, or an empty config when the file
+    /// is missing.
+    pub fn load(repo_root: &Path) -> io::Result<Self> {
+        let text = match std::fs::read_to_string(repo_root.join(AGENT_TOML_FILE)) {
+            Ok(text) => text,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(err) => return Err(err),
+        };
+        Self::parse(&text)
+    }
+
+    pub fn parse(text: &str) -> io::Result<Self> {
+        let table = toml::from_str(text).map_err(invalid_data)?;
+        Ok(Self { table })
+    }
+
+    /// Deserialize the `[name]` table, or `None` when it is absent.
+    pub fn section<T: DeserializeOwned>(&self, name: &str) -> io::Result<Option<T>> {
+        self.table
+            .get(name)
+            .cloned()
+            .map(|value| value.try_into().map_err(invalid_data))
+            .transpose()
+    }
+}
+
+fn invalid_data(err: impl std::error::Error + Send + Sync + 'static) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, err)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use serde::Deserialize;
+
+    #[derive(Debug, Default, PartialEq, Deserialize)]
+    #[serde(default)]
+    struct Section {
+        flag: bool,
+        limit: u32,
+    }
+
+    #[test]
+    fn test_sections_deserialize_independently() {
+        let config = AgentToml::parse("[a]\nflag = true\n\n[b]\nlimit = 3\n").unwrap();
+        assert_eq!(
+            config.section::<Section>("a").unwrap(),
+            Some(Section {
+                flag: true,
+                limit: 0
+            })
+        );
+        assert_eq!(
+            config.section::<Section>("b").unwrap(),
+            Some(Section {
+                flag: false,
+                limit: 3
+            })
+        );
+        assert_eq!(config.section::<Section>("c").unwrap(), None);
+    }
+
+    #[test]
+    fn test_missing_file_is_empty() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = AgentToml::load(dir.path()).unwrap();
+        assert_eq!(config.section::<Section>("a").unwrap(), None);
+    }
+
+    #[test]
+    fn test_invalid_section_is_invalid_data() {
+        let config = AgentToml::parse("[a]\nlimit = \"many\"\n").unwrap();
+        let err = config.section::<Section>("a").unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+}
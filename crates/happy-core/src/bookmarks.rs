@@ -0,0 +1,338 @@
+//! Bookmarks: notes pinned to code elements, kept in `.happy/bookmarks.json`.
+//!
+//! A bookmark is keyed by element kind, name, and file rather than by line, so
+//! it keeps pointing at its element while the code around it moves. When the
+//! key no longer matches anything, a rename recorded by the graph is followed;
+//! failing that the bookmark is reported as stale instead of being dropped.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::graph::RepositoryGraph;
+use crate::graph::renames::{RenameRecord, unix_now};
+use crate::indexer::{CodeElement, ElementType};
+
+/// Bookmarks file, under the repo's `.happy` directory.
+pub const BOOKMARKS_FILE: &str = "bookmarks.json";
+
+/// What a bookmark points at: stable across edits that move the element.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct ElementKey {
+    pub kind: ElementType,
+    pub name: String,
+    pub relative_path: String,
+}
+
+impl ElementKey {
+    pub fn of(element: &CodeElement) -> Self {
+        Self {
+            kind: element.element_type,
+            name: element.name.clone(),
+            relative_path: element.relative_path.clone(),
+        }
+    }
+
+    pub fn matches(&self, element: &CodeElement) -> bool {
+        element.element_type == self.kind
+            && element.name == self.name
+            && element.relative_path == self.relative_path
+    }
+}
+
+impl fmt::Display for ElementKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} ({})",
+            self.kind.as_str(),
+            self.name,
+            self.relative_path
+        )
+    }
+}
+
+/// A note attached to one element.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Bookmark {
+    #[serde(flatten)]
+    pub key: ElementKey,
+    pub note: String,
+    /// Unix timestamp (seconds) the bookmark was last written.
+    pub created_at: u64,
+    /// Element ID when bookmarked, used to follow renames.
+    pub element_id: String,
+}
+
+/// Where a bookmark points in the current index.
+#[derive(Debug, Clone)]
+pub enum BookmarkStatus {
+    /// The bookmarked element is indexed under its key.
+    Live(CodeElement),
+    /// The element was renamed; `element` is what it is called now.
+    Renamed {
+        element: CodeElement,
+        rename: RenameRecord,
+    },
+    /// Nothing in the index matches the bookmark any more.
+    Stale,
+}
+
+/// A bookmark with its status against a graph.
+#[derive(Debug, Clone)]
+pub struct ResolvedBookmark {
+    pub bookmark: Bookmark,
+    pub status: BookmarkStatus,
+}
+
+impl ResolvedBookmark {
+    /// The element the bookmark points at, unless it is stale.
+    pub fn element(&self) -> Option<&CodeElement> {
+        match &self.status {
+            BookmarkStatus::Live(element) | BookmarkStatus::Renamed { element, .. } => {
+                Some(element)
+            }
+            BookmarkStatus::Stale => None,
+        }
+    }
+
+    pub fn is_stale(&self) -> bool {
+        matches!(self.status, BookmarkStatus::Stale)
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct BookmarksFile {
+    bookmarks: Vec<Bookmark>,
+}
+
+/// The bookmarks of one repository.
+#[derive(Debug)]
+pub struct Bookmarks {
+    path: PathBuf,
+    entries: Vec<Bookmark>,
+}
+
+impl Bookmarks {
+    /// Where the bookmarks of the repo at `repo_root` are kept.
+    pub fn path_in(repo_root: &Path) -> PathBuf {
+        repo_root.join(".happy").join(BOOKMARKS_FILE)
+    }
+
+    /// Load `.happy/bookmarks.json` under `repo_root`; empty when it is missing.
+    pub fn load(repo_root: &Path) -> io::Result<Self> {
+        let path = Self::path_in(repo_root);
+        let entries = match fs::read(&path) {
+            Ok(bytes) => {
+                serde_json::from_slice::<BookmarksFile>(&bytes)
+                    .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?
+                    .bookmarks
+            }
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Vec::new(),
+            Err(err) => return Err(err),
+        };
+        Ok(Self { path, entries })
+    }
+
+    /// Write the bookmarks back atomically.
+    pub fn save(&self) -> io::Result<()> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let file = BookmarksFile {
+            bookmarks: self.entries.clone(),
+        };
+        let json = serde_json::to_vec_pretty(&file).map_err(io::Error::other)?;
+        let temp_path = self
+            .path
+            .with_extension(format!("json.{}.tmp", std::process::id()));
+        if let Err(err) =
+            fs::write(&temp_path, json).and_then(|()| fs::rename(&temp_path, &self.path))
+        {
+            let _ = fs::remove_file(&temp_path);
+            return Err(err);
+        }
+        Ok(())
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn entries(&self) -> &[Bookmark] {
+        &self.entries
+    }
+
+    /// Bookmark `element`, replacing the note of an existing bookmark on it.
+    pub fn add(&mut self, element: &CodeElement, note: &str) -> &Bookmark {
+        let bookmark = Bookmark {
+            key: ElementKey::of(element),
+            note: note.to_string(),
+            created_at: unix_now(),
+            element_id: element.id.clone(),
+        };
+        let index = match self.entries.iter().position(|b| b.key == bookmark.key) {
+            Some(index) => {
+                self.entries[index] = bookmark;
+                index
+            }
+            None => {
+                self.entries.push(bookmark);
+                self.entries.len() - 1
+            }
+        };
+        &self.entries[index]
+    }
+
+    /// Remove the bookmarks matching `pred`, returning them.
+    pub fn remove(&mut self, pred: impl Fn(&Bookmark) -> bool) -> Vec<Bookmark> {
+        let (removed, kept) = self.entries.drain(..).partition(|b| pred(b));
+        self.entries = kept;
+        removed
+    }
+
+    /// Every bookmark with where it points in `graph`, in insertion order.
+    pub fn resolve(&self, graph: &RepositoryGraph) -> Vec<ResolvedBookmark> {
+        self.entries
+            .iter()
+            .map(|bookmark| ResolvedBookmark {
+                bookmark: bookmark.clone(),
+                status: graph.bookmark_status(bookmark),
+            })
+            .collect()
+    }
+
+    /// Notes of the bookmarks that still resolve, by current element ID.
+    pub fn notes_by_element(&self, graph: &RepositoryGraph) -> HashMap<String, String> {
+        self.resolve(graph)
+            .into_iter()
+            .filter_map(|resolved| {
+                let id = resolved.element()?.id.clone();
+                Some((id, resolved.bookmark.note))
+            })
+            .collect()
+    }
+}
+
+impl RepositoryGraph {
+    /// The indexed element matching `key`, if any.
+    pub fn find_element_by_key(&self, key: &ElementKey) -> Option<CodeElement> {
+        let nodes = self.name_to_nodes.get(&key.name)?;
+        nodes.iter().find_map(|&idx| {
+            let element = self.element_arena.get(&self.graph[idx].id)?;
+            key.matches(&element).then(|| element.clone())
+        })
+    }
+
+    fn bookmark_status(&self, bookmark: &Bookmark) -> BookmarkStatus {
+        if let Some(element) = self.find_element_by_key(&bookmark.key) {
+            return BookmarkStatus::Live(element);
+        }
+        match self.resolve_renamed(&bookmark.element_id) {
+            Some(rename) => match self.get_element(&rename.new_id) {
+                Some(element) => BookmarkStatus::Renamed { element, rename },
+                None => BookmarkStatus::Stale,
+            },
+            None => BookmarkStatus::Stale,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indexer::index_single_file;
+    use pretty_assertions::assert_eq;
+
+    fn index(root: &Path, files: &[(&str, &str)]) -> RepositoryGraph {
+        let root_str = root.to_string_lossy().to_string();
+        let mut elements = Vec::new();
+        for (name, source) in files {
+            let path = root.join(name);
+            fs::write(&path, source).unwrap();
+            elements.extend(
+                index_single_file(&path.to_string_lossy(), &root_str, &Default::default()).unwrap(),
+            );
+        }
+        let mut graph = RepositoryGraph::new();
+        graph.build_from_elements(&elements, &root_str);
+        graph
+    }
+
+    fn function(graph: &RepositoryGraph, name: &str) -> CodeElement {
+        let id = graph.resolve_element_id(name).unwrap().unwrap();
+        graph.get_element(&id).unwrap()
+    }
+
+    #[test]
+    fn bookmark_follows_its_element_when_lines_shift() {
+        let dir = tempfile::tempdir().unwrap();
+        let graph = index(dir.path(), &[("auth.py", "def login():\n    return 1\n")]);
+        let mut bookmarks = Bookmarks::load(dir.path()).unwrap();
+        bookmarks.add(&function(&graph, "login"), "entry point for SSO");
+        bookmarks.save().unwrap();
+
+        let graph = index(
+            dir.path(),
+            &[("auth.py", "import os\n\n\n\ndef login():\n    return 1\n")],
+        );
+        let resolved = Bookmarks::load(dir.path()).unwrap().resolve(&graph);
+
+        assert_eq!(resolved.len(), 1);
+        let element = resolved[0].element().unwrap();
+        assert_eq!(element.name, "login");
+        assert_eq!(element.start_line, 5);
+        assert_eq!(resolved[0].bookmark.note, "entry point for SSO");
+    }
+
+    #[test]
+    fn bookmark_is_stale_once_its_element_is_gone() {
+        let dir = tempfile::tempdir().unwrap();
+        let graph = index(
+            dir.path(),
+            &[(
+                "auth.py",
+                "def login():\n    return 1\n\ndef logout():\n    return 0\n",
+            )],
+        );
+        let mut bookmarks = Bookmarks::load(dir.path()).unwrap();
+        bookmarks.add(&function(&graph, "logout"), "clears the session");
+
+        let graph = index(dir.path(), &[("auth.py", "def login():\n    return 1\n")]);
+        let resolved = bookmarks.resolve(&graph);
+
+        assert!(resolved[0].is_stale());
+        assert_eq!(resolved[0].bookmark.key.name, "logout");
+        assert!(bookmarks.notes_by_element(&graph).is_empty());
+    }
+
+    #[test]
+    fn notes_are_keyed_by_current_element_id() {
+        let dir = tempfile::tempdir().unwrap();
+        let graph = index(
+            dir.path(),
+            &[(
+                "auth.py",
+                "def login():\n    return 1\n\ndef logout():\n    return 0\n",
+            )],
+        );
+        let login = function(&graph, "login");
+        let mut bookmarks = Bookmarks::load(dir.path()).unwrap();
+        bookmarks.add(&login, "first note");
+        bookmarks.add(&login, "second note");
+
+        assert_eq!(bookmarks.entries().len(), 1);
+        assert_eq!(
+            bookmarks.notes_by_element(&graph),
+            HashMap::from([(login.id, "second note".to_string())])
+        );
+
+        let removed = bookmarks.remove(|b| b.key.name == "login");
+        assert_eq!(removed.len(), 1);
+        assert!(bookmarks.entries().is_empty());
+    }
+}
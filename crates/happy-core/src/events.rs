@@ -0,0 +1,454 @@
+//! Newline-delimited JSON index events for build systems (`--notify`).
+//!
+//! An indexing process writes one JSON object per line to a Unix socket or
+//! a FIFO so an orchestrator can schedule graph-dependent jobs as soon as the
+//! index is fresh, instead of polling. Delivery is best effort: events are
+//! dropped, never queued without bound, while no reader is attached or the
+//! reader falls behind, so notifications can never stall indexing.
+
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+/// Version of the event schema, sent as `v` with every event.
+pub const EVENT_SCHEMA_VERSION: u32 = 1;
+
+/// Minimum time between two progress events.
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Bytes held back while the reader is not keeping up; events that do not
+/// fit are dropped.
+const MAX_PENDING_BYTES: usize = 64 * 1024;
+
+/// How often [`wait_for_event`] checks for a writer or new data.
+const WAIT_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Something that happened to the index.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum IndexEvent {
+    IndexStarted {
+        repo_root: String,
+    },
+    /// Source files parsed so far, sent at most every 250ms.
+    Progress {
+        files_done: usize,
+        files_total: usize,
+    },
+    IndexCompleted {
+        files: usize,
+        elements: usize,
+        nodes: usize,
+        edges: usize,
+        generation: u64,
+        duration_ms: u64,
+    },
+    /// The watcher applied file changes to the index.
+    IncrementalUpdate {
+        changed: Vec<String>,
+        removed: Vec<String>,
+        full_rebuild: bool,
+        generation: u64,
+    },
+    Error {
+        message: String,
+    },
+}
+
+/// One line on the wire: `{"v":1,"event":"index_started",...}`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EventRecord {
+    pub v: u32,
+    #[serde(flatten)]
+    pub event: IndexEvent,
+}
+
+/// Writes index events to a Unix socket or FIFO at a fixed path.
+///
+/// The path is opened lazily and reopened after the reader goes away, so a
+/// reader can attach at any time. A path that is a FIFO is opened for
+/// writing; anything else is connected to as a Unix socket.
+pub struct EventSink {
+    path: PathBuf,
+    state: Mutex<SinkState>,
+    last_progress: Mutex<Option<Instant>>,
+}
+
+#[derive(Default)]
+struct SinkState {
+    target: Option<Box<dyn Write + Send>>,
+    /// Part of the stream the reader has not accepted yet.
+    pending: Vec<u8>,
+}
+
+impl EventSink {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            state: Mutex::default(),
+            last_progress: Mutex::default(),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Send `event` without blocking. Returns whether it was handed to the
+    /// reader (or queued behind earlier events); `false` means it was
+    /// dropped.
+    pub fn send(&self, event: IndexEvent) -> bool {
+        let record = EventRecord {
+            v: EVENT_SCHEMA_VERSION,
+            event,
+        };
+        let Ok(mut line) = serde_json::to_vec(&record) else {
+            return false;
+        };
+        line.push(b'\n');
+
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        let SinkState { target, pending } = &mut *state;
+        if target.is_none() {
+            pending.clear();
+            *target = open_target(&self.path).ok();
+        }
+        let Some(writer) = target.as_mut() else {
+            return false;
+        };
+        if flush_pending(writer.as_mut(), pending).is_err() {
+            *target = None;
+            return false;
+        }
+        if pending.len() + line.len() > MAX_PENDING_BYTES {
+            return false;
+        }
+        pending.extend_from_slice(&line);
+        if flush_pending(writer.as_mut(), pending).is_err() {
+            *target = None;
+            return false;
+        }
+        true
+    }
+
+    /// Send a [`IndexEvent::Progress`] unless one went out in the last
+    /// 250ms. The final one (`done == total`) is always sent.
+    pub fn progress(&self, done: usize, total: usize) {
+        {
+            let mut last = self
+                .last_progress
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner);
+            let now = Instant::now();
+            if done < total && last.is_some_and(|at| now.duration_since(at) < PROGRESS_INTERVAL) {
+                return;
+            }
+            *last = Some(now);
+        }
+        self.send(IndexEvent::Progress {
+            files_done: done,
+            files_total: total,
+        });
+    }
+}
+
+/// Write as much of `pending` as the reader accepts without blocking.
+fn flush_pending(writer: &mut dyn Write, pending: &mut Vec<u8>) -> io::Result<()> {
+    while !pending.is_empty() {
+        match writer.write(pending) {
+            Ok(0) => return Err(io::ErrorKind::WriteZero.into()),
+            Ok(written) => {
+                pending.drain(..written);
+            }
+            Err(err) if err.kind() == io::ErrorKind::WouldBlock => return Ok(()),
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+            Err(err) => return Err(err),
+        }
+    }
+    Ok(())
+}
+
+#[cfg(unix)]
+fn open_target(path: &Path) -> io::Result<Box<dyn Write + Send>> {
+    use std::os::unix::fs::{FileTypeExt, OpenOptionsExt};
+
+    if std::fs::metadata(path)?.file_type().is_fifo() {
+        // Fails with ENXIO instead of blocking while no reader has it open.
+        let file = std::fs::OpenOptions::new()
+            .write(true)
+            .custom_flags(libc::O_NONBLOCK)
+            .open(path)?;
+        return Ok(Box::new(file));
+    }
+    let stream = std::os::unix::net::UnixStream::connect(path)?;
+    stream.set_nonblocking(true)?;
+    Ok(Box::new(stream))
+}
+
+#[cfg(not(unix))]
+fn open_target(_path: &Path) -> io::Result<Box<dyn Write + Send>> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "index event notification needs Unix sockets or FIFOs",
+    ))
+}
+
+/// Listen at `path` until an event matching `done` arrives, and return it.
+///
+/// A FIFO at `path` is read; otherwise a Unix socket is bound there
+/// (replacing a stale socket file) and removed again on return. Writers may
+/// come and go while waiting. Lines that are not events are skipped.
+/// Returns `TimedOut` when `timeout` passes first.
+#[cfg(unix)]
+pub fn wait_for_event(
+    path: &Path,
+    timeout: Option<Duration>,
+    done: impl Fn(&IndexEvent) -> bool,
+) -> io::Result<EventRecord> {
+    use std::io::{BufRead, Read};
+    use std::os::unix::fs::{FileTypeExt, OpenOptionsExt};
+    use std::os::unix::net::UnixListener;
+
+    let deadline = timeout.map(|timeout| Instant::now() + timeout);
+    let timed_out = || deadline.is_some_and(|deadline| Instant::now() >= deadline);
+    let matching = |line: &str| {
+        serde_json::from_str::<EventRecord>(line)
+            .ok()
+            .filter(|record| done(&record.event))
+    };
+    let file_type = match std::fs::symlink_metadata(path) {
+        Ok(meta) => Some(meta.file_type()),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => None,
+        Err(err) => return Err(err),
+    };
+
+    if file_type.is_some_and(|file_type| file_type.is_fifo()) {
+        // Opening for reading without O_NONBLOCK would block until a writer
+        // appears, past any timeout.
+        let mut fifo = std::fs::OpenOptions::new()
+            .read(true)
+            .custom_flags(libc::O_NONBLOCK)
+            .open(path)?;
+        let mut buffered = Vec::new();
+        let mut chunk = [0u8; 4096];
+        loop {
+            match fifo.read(&mut chunk) {
+                Ok(0) => {}
+                Ok(read) => {
+                    buffered.extend_from_slice(&chunk[..read]);
+                    while let Some(end) = buffered.iter().position(|&b| b == b'\n') {
+                        let line: Vec<u8> = buffered.drain(..=end).collect();
+                        if let Some(record) = matching(&String::from_utf8_lossy(&line)) {
+                            return Ok(record);
+                        }
+                    }
+                    continue;
+                }
+                Err(err) if err.kind() == io::ErrorKind::WouldBlock => {}
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                Err(err) => return Err(err),
+            }
+            if timed_out() {
+                return Err(io::ErrorKind::TimedOut.into());
+            }
+            std::thread::sleep(WAIT_POLL_INTERVAL);
+        }
+    }
+
+    if file_type.is_some_and(|file_type| file_type.is_socket()) {
+        std::fs::remove_file(path)?;
+    }
+    let listener = UnixListener::bind(path)?;
+    let _cleanup = RemoveOnDrop(path);
+    listener.set_nonblocking(true)?;
+    loop {
+        match listener.accept() {
+            Ok((stream, _)) => {
+                stream.set_nonblocking(false)?;
+                stream.set_read_timeout(Some(WAIT_POLL_INTERVAL))?;
+                let mut reader = io::BufReader::new(stream);
+                let mut line = String::new();
+                loop {
+                    match reader.read_line(&mut line) {
+                        Ok(0) => break,
+                        Ok(_) if line.ends_with('\n') => {
+                            if let Some(record) = matching(&line) {
+                                return Ok(record);
+                            }
+                            line.clear();
+                        }
+                        Ok(_) => {}
+                        Err(err)
+                            if matches!(
+                                err.kind(),
+                                io::ErrorKind::WouldBlock
+                                    | io::ErrorKind::TimedOut
+                                    | io::ErrorKind::Interrupted
+                            ) => {}
+                        Err(err) => return Err(err),
+                    }
+                    if timed_out() {
+                        return Err(io::ErrorKind::TimedOut.into());
+                    }
+                }
+            }
+            Err(err) if err.kind() == io::ErrorKind::WouldBlock => {}
+            Err(err) => return Err(err),
+        }
+        if timed_out() {
+            return Err(io::ErrorKind::TimedOut.into());
+        }
+        std::thread::sleep(WAIT_POLL_INTERVAL);
+    }
+}
+
+#[cfg(not(unix))]
+pub fn wait_for_event(
+    _path: &Path,
+    _timeout: Option<Duration>,
+    _done: impl Fn(&IndexEvent) -> bool,
+) -> io::Result<EventRecord> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "index event notification needs Unix sockets or FIFOs",
+    ))
+}
+
+#[cfg(unix)]
+struct RemoveOnDrop<'a>(&'a Path);
+
+#[cfg(unix)]
+impl Drop for RemoveOnDrop<'_> {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(self.0);
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::io::BufRead;
+    use std::os::unix::net::UnixListener;
+
+    fn completed(generation: u64) -> IndexEvent {
+        IndexEvent::IndexCompleted {
+            files: 1,
+            elements: 2,
+            nodes: 3,
+            edges: 4,
+            generation,
+            duration_ms: 5,
+        }
+    }
+
+    #[test]
+    fn test_event_lines_are_versioned() {
+        let record = EventRecord {
+            v: EVENT_SCHEMA_VERSION,
+            event: IndexEvent::IndexStarted {
+                repo_root: "/repo".to_string(),
+            },
+        };
+        assert_eq!(
+            serde_json::to_string(&record).unwrap(),
+            r#"{"v":1,"event":"index_started","repo_root":"/repo"}"#
+        );
+        let parsed: EventRecord =
+            serde_json::from_str(r#"{"v":1,"event":"progress","files_done":2,"files_total":9}"#)
+                .unwrap();
+        assert_eq!(
+            parsed.event,
+            IndexEvent::Progress {
+                files_done: 2,
+                files_total: 9
+            }
+        );
+    }
+
+    #[test]
+    fn test_sink_writes_lines_to_socket() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("events.sock");
+        let listener = UnixListener::bind(&path).unwrap();
+        let sink = EventSink::new(&path);
+
+        assert!(sink.send(IndexEvent::IndexStarted {
+            repo_root: "/repo".to_string()
+        }));
+        sink.progress(1, 3);
+        // Throttled: too soon after the previous progress event.
+        sink.progress(2, 3);
+        sink.progress(3, 3);
+        assert!(sink.send(completed(7)));
+        drop(sink);
+
+        let (stream, _) = listener.accept().unwrap();
+        let events: Vec<String> = io::BufReader::new(stream)
+            .lines()
+            .map(|line| {
+                let record: EventRecord = serde_json::from_str(&line.unwrap()).unwrap();
+                match record.event {
+                    IndexEvent::Progress { files_done, .. } => format!("progress {files_done}"),
+                    other => serde_json::to_value(other).unwrap()["event"]
+                        .as_str()
+                        .unwrap()
+                        .to_string(),
+                }
+            })
+            .collect();
+        assert_eq!(
+            events,
+            vec![
+                "index_started",
+                "progress 1",
+                "progress 3",
+                "index_completed"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_sink_without_reader_drops_events() {
+        let dir = tempfile::tempdir().unwrap();
+        let sink = EventSink::new(dir.path().join("nobody.sock"));
+        assert!(!sink.send(completed(1)));
+
+        let fifo = dir.path().join("events.fifo");
+        let c_path = std::ffi::CString::new(fifo.to_string_lossy().as_bytes()).unwrap();
+        assert_eq!(unsafe { libc::mkfifo(c_path.as_ptr(), 0o600) }, 0);
+        let started = Instant::now();
+        let sink = EventSink::new(&fifo);
+        assert!(!sink.send(completed(1)));
+        assert!(started.elapsed() < Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_wait_for_event_returns_on_completion() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("events.sock");
+        let writer_path = path.clone();
+        let writer = std::thread::spawn(move || {
+            let sink = EventSink::new(&writer_path);
+            while !sink.send(IndexEvent::IndexStarted {
+                repo_root: "/repo".to_string(),
+            }) {
+                std::thread::sleep(Duration::from_millis(10));
+            }
+            sink.send(completed(3));
+        });
+
+        let record = wait_for_event(&path, Some(Duration::from_secs(10)), |event| {
+            matches!(event, IndexEvent::IndexCompleted { .. })
+        })
+        .unwrap();
+        writer.join().unwrap();
+        assert_eq!(record.event, completed(3));
+        assert!(!path.exists());
+
+        let err = wait_for_event(&path, Some(Duration::from_millis(100)), |_| true).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::TimedOut);
+    }
+}
@@ -1,16 +1,38 @@
 pub mod module_resolver;
+pub mod rust_resolver;
 pub mod symbol_resolver;
+pub mod ts_resolver;
+
+use std::path::Path;
+use std::sync::{Mutex, PoisonError};
 
 use dashmap::DashMap;
 
+use crate::indexer::{NAMESPACES_KEY, Visibility};
+use rust_resolver::RustModules;
+use ts_resolver::TsModules;
+
 /// Global index providing fast lookups across the entire repository.
 pub struct GlobalIndex {
     /// file_path -> module path (e.g., "src/app/auth.py" -> "src.app.auth")
     pub file_map: DashMap<String, String>,
     /// module path -> file_path
     pub module_map: DashMap<String, String>,
-    /// symbol name -> Vec<(file_path, element_id)>
+    /// symbol name -> Vec<(file_path, element_id)>, for symbols any file can
+    /// reference. Private symbols are left out, so they never resolve across
+    /// files.
     pub export_map: DashMap<String, Vec<(String, String)>>,
+    /// symbol name -> Vec<(file_path, element_id)>, for symbols visible only
+    /// within their directory (Java package-private, lowercase Go names)
+    pub package_map: DashMap<String, Vec<(String, String)>>,
+    /// C# namespace -> the files declaring it, which `using` imports
+    pub namespace_map: DashMap<String, Vec<String>>,
+    /// Rust crates' module trees, for `crate::`/`super::` use paths
+    pub rust_modules: RustModules,
+    /// JS/TS files and their tsconfig, for relative and aliased specifiers
+    pub ts_modules: TsModules,
+    /// The repository roots indexed, the primary one first
+    roots: Mutex<Vec<String>>,
 }
 
 impl GlobalIndex {
@@ -19,35 +41,139 @@ impl GlobalIndex {
             file_map: DashMap::new(),
             module_map: DashMap::new(),
             export_map: DashMap::new(),
+            package_map: DashMap::new(),
+            namespace_map: DashMap::new(),
+            rust_modules: RustModules::new(),
+            ts_modules: TsModules::new(),
+            roots: Mutex::new(Vec::new()),
         }
     }
 
-    /// Build the index from code elements.
+    /// Build the index from code elements. Elements added under one of the
+    /// roots already indexed keep the other roots in place.
     pub fn build(&self, elements: &[crate::indexer::CodeElement], repo_root: &str) {
+        let roots = self.roots();
+        if roots.iter().any(|root| root == repo_root) {
+            let roots: Vec<&str> = roots.iter().map(String::as_str).collect();
+            self.build_roots(elements, &roots);
+        } else {
+            self.build_roots(elements, &[repo_root]);
+        }
+    }
+
+    /// Build the index from the code elements of several repository roots,
+    /// the primary one first. Module paths are relative to the root holding
+    /// each file, so an import in one root resolves to a module in another.
+    /// When two roots define the same module path, the earlier root's file
+    /// keeps it.
+    pub fn build_roots(&self, elements: &[crate::indexer::CodeElement], repo_roots: &[&str]) {
+        let roots: Vec<String> = repo_roots
+            .iter()
+            .filter(|root| !root.is_empty())
+            .map(|root| root.to_string())
+            .collect();
+        if !roots.is_empty() {
+            *self.roots.lock().unwrap_or_else(PoisonError::into_inner) = roots;
+        }
+        self.rust_modules.build(
+            elements
+                .iter()
+                .filter(|elem| {
+                    elem.element_type == crate::indexer::ElementType::File
+                        && elem.file_path.ends_with(".rs")
+                })
+                .map(|elem| elem.file_path.as_str()),
+        );
+        self.ts_modules.build_roots(
+            elements
+                .iter()
+                .filter(|elem| {
+                    elem.element_type == crate::indexer::ElementType::File
+                        && ts_resolver::is_js_ts(&elem.file_path)
+                })
+                .map(|elem| elem.file_path.as_str()),
+            repo_roots,
+        );
+        let roots = self.roots();
         for elem in elements {
             if elem.element_type == crate::indexer::ElementType::File {
+                for namespace in elem
+                    .metadata
+                    .get(NAMESPACES_KEY)
+                    .into_iter()
+                    .flat_map(|namespaces| namespaces.split(','))
+                {
+                    let mut files = self.namespace_map.entry(namespace.to_string()).or_default();
+                    if !files.contains(&elem.file_path) {
+                        files.push(elem.file_path.clone());
+                        files.sort();
+                    }
+                }
+                // Without roots, module paths are taken from the paths as given.
+                let root = match self.root_of(&elem.file_path) {
+                    Some(root) => root,
+                    None if roots.is_empty() => String::new(),
+                    None => continue,
+                };
                 if let Some(module_path) =
-                    crate::utils::file_path_to_module_path(&elem.file_path, repo_root)
+                    crate::utils::file_path_to_module_path(&elem.file_path, &root)
                 {
                     self.file_map
                         .insert(elem.file_path.clone(), module_path.clone());
-                    self.module_map.insert(module_path, elem.file_path.clone());
+                    if roots.first().is_none_or(|primary| *primary == root) {
+                        self.module_map.insert(module_path, elem.file_path.clone());
+                    } else {
+                        self.module_map
+                            .entry(module_path)
+                            .or_insert_with(|| elem.file_path.clone());
+                    }
                 }
-            } else {
-                // Register as exported symbol
-                self.export_map
-                    .entry(elem.name.clone())
+            } else if elem.element_type != crate::indexer::ElementType::Chunk {
+                let map = match elem.visibility() {
+                    Visibility::Public | Visibility::Protected => &self.export_map,
+                    Visibility::Package => &self.package_map,
+                    Visibility::Private => continue,
+                };
+                map.entry(elem.name.clone())
                     .or_default()
                     .push((elem.file_path.clone(), elem.id.clone()));
             }
         }
     }
 
+    /// The repository roots indexed, the primary one first.
+    pub fn roots(&self) -> Vec<String> {
+        self.roots
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// The indexed root holding `file_path`: the deepest one, when roots
+    /// are nested.
+    pub fn root_of(&self, file_path: &str) -> Option<String> {
+        self.roots
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .iter()
+            .filter(|root| Path::new(file_path).starts_with(root))
+            .max_by_key(|root| root.len())
+            .cloned()
+    }
+
     /// Look up which file a module path resolves to.
     pub fn resolve_module(&self, module_path: &str) -> Option<String> {
         self.module_map.get(module_path).map(|v| v.clone())
     }
 
+    /// The files declaring a C# namespace, sorted.
+    pub fn resolve_namespace(&self, namespace: &str) -> Vec<String> {
+        self.namespace_map
+            .get(namespace)
+            .map(|v| v.clone())
+            .unwrap_or_default()
+    }
+
     /// Look up which elements export a given symbol name.
     pub fn resolve_symbol(&self, symbol: &str) -> Vec<(String, String)> {
         self.export_map
@@ -56,6 +182,21 @@ impl GlobalIndex {
             .unwrap_or_default()
     }
 
+    /// Look up package-visible elements named `symbol` in `file_path`'s
+    /// directory.
+    pub fn resolve_package_symbol(&self, symbol: &str, file_path: &str) -> Vec<(String, String)> {
+        let dir = Path::new(file_path).parent();
+        self.package_map
+            .get(symbol)
+            .map(|v| {
+                v.iter()
+                    .filter(|(fp, _)| Path::new(fp).parent() == dir)
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
     /// Get the module path for a file.
     pub fn file_to_module(&self, file_path: &str) -> Option<String> {
         self.file_map.get(file_path).map(|v| v.clone())
@@ -63,21 +204,32 @@ impl GlobalIndex {
 
     /// Remove all entries associated with a file path.
     pub fn remove_file(&self, file_path: &str) {
+        self.rust_modules.remove_file(file_path);
+        self.ts_modules.remove_file(file_path);
+
         // Remove file_map → module_map entry
         if let Some((_, module_path)) = self.file_map.remove(file_path) {
-            self.module_map.remove(&module_path);
+            self.module_map
+                .remove_if(&module_path, |_, owner| owner == file_path);
         }
 
-        // Remove all export_map entries from this file
-        let mut empty_keys = Vec::new();
-        for mut entry in self.export_map.iter_mut() {
-            entry.value_mut().retain(|(fp, _)| fp != file_path);
-            if entry.value().is_empty() {
-                empty_keys.push(entry.key().clone());
+        self.namespace_map.retain(|_, files| {
+            files.retain(|fp| fp != file_path);
+            !files.is_empty()
+        });
+
+        // Remove all symbol entries from this file
+        for map in [&self.export_map, &self.package_map] {
+            let mut empty_keys = Vec::new();
+            for mut entry in map.iter_mut() {
+                entry.value_mut().retain(|(fp, _)| fp != file_path);
+                if entry.value().is_empty() {
+                    empty_keys.push(entry.key().clone());
+                }
+            }
+            for key in empty_keys {
+                map.remove(&key);
             }
-        }
-        for key in empty_keys {
-            self.export_map.remove(&key);
         }
     }
 
@@ -114,10 +266,11 @@ mod tests {
                 language: "python".into(),
                 start_line: 1,
                 end_line: 50,
-                code: String::new(),
+                code: Default::default(),
                 signature: None,
                 docstring: None,
                 summary: None,
+                parent_id: None,
                 metadata: HashMap::new(),
             },
             CodeElement {
@@ -133,6 +286,7 @@ mod tests {
                 signature: Some("def login():".into()),
                 docstring: None,
                 summary: None,
+                parent_id: None,
                 metadata: HashMap::new(),
             },
         ];
@@ -163,10 +317,11 @@ mod tests {
                 language: "python".into(),
                 start_line: 1,
                 end_line: 50,
-                code: String::new(),
+                code: Default::default(),
                 signature: None,
                 docstring: None,
                 summary: None,
+                parent_id: None,
                 metadata: HashMap::new(),
             },
             CodeElement {
@@ -182,6 +337,7 @@ mod tests {
                 signature: Some("def login():".into()),
                 docstring: None,
                 summary: None,
+                parent_id: None,
                 metadata: HashMap::new(),
             },
         ];
@@ -200,4 +356,57 @@ mod tests {
         // Export map should be cleaned
         assert_eq!(index.resolve_symbol("login").len(), 0);
     }
+
+    fn function(id: &str, name: &str, file_path: &str, visibility: Visibility) -> CodeElement {
+        CodeElement {
+            id: id.into(),
+            element_type: ElementType::Function,
+            name: name.into(),
+            file_path: file_path.into(),
+            relative_path: file_path.trim_start_matches("/repo/").into(),
+            language: "go".into(),
+            start_line: 1,
+            end_line: 3,
+            code: Default::default(),
+            signature: None,
+            docstring: None,
+            summary: None,
+            parent_id: None,
+            metadata: HashMap::from([(VISIBILITY_KEY.into(), visibility.as_str().into())]),
+        }
+    }
+
+    #[test]
+    fn test_private_symbols_are_not_exported() {
+        let elements = vec![
+            function("a_helper", "helper", "/repo/a/util.go", Visibility::Package),
+            function("b_helper", "helper", "/repo/b/util.go", Visibility::Package),
+            function("c_helper", "helper", "/repo/c/util.rs", Visibility::Private),
+            function("d_helper", "helper", "/repo/d/util.rs", Visibility::Public),
+        ];
+
+        let index = GlobalIndex::new();
+        index.build(&elements, "/repo");
+
+        assert_eq!(
+            index.resolve_symbol("helper"),
+            vec![("/repo/d/util.rs".to_string(), "d_helper".to_string())]
+        );
+        assert_eq!(
+            index.resolve_package_symbol("helper", "/repo/b/main.go"),
+            vec![("/repo/b/util.go".to_string(), "b_helper".to_string())]
+        );
+        assert!(
+            index
+                .resolve_package_symbol("helper", "/repo/c/main.rs")
+                .is_empty()
+        );
+
+        index.remove_file("/repo/b/util.go");
+        assert!(
+            index
+                .resolve_package_symbol("helper", "/repo/b/main.go")
+                .is_empty()
+        );
+    }
 }
@@ -1,4 +1,7 @@
+use std::path::Path;
+
 use super::GlobalIndex;
+use super::ts_resolver;
 use crate::parser::imports::ImportInfo;
 
 /// Resolve import statements to file paths using the global index.
@@ -13,6 +16,24 @@ impl<'a> ModuleResolver<'a> {
 
     /// Resolve an import to a file path.
     pub fn resolve_import(&self, import: &ImportInfo, current_file: &str) -> Option<String> {
+        if current_file.ends_with(".rs")
+            && let Some(file) = self
+                .index
+                .rust_modules
+                .resolve(&import.module, current_file)
+        {
+            return Some(file);
+        }
+        if ts_resolver::is_js_ts(current_file)
+            && let Some(file) = self.index.ts_modules.resolve(&import.module, current_file)
+        {
+            return Some(file);
+        }
+        if current_file.ends_with(".cs")
+            && let Some(file) = self.resolve_csharp_using(import)
+        {
+            return Some(file);
+        }
         if import.level > 0 {
             self.resolve_relative_import(import, current_file)
         } else {
@@ -20,6 +41,27 @@ impl<'a> ModuleResolver<'a> {
         }
     }
 
+    /// A C# `using` of a namespace resolves to the first file declaring it;
+    /// a `using static` or alias of a type, to the file in the type's
+    /// namespace that defines it.
+    fn resolve_csharp_using(&self, import: &ImportInfo) -> Option<String> {
+        if let Some(file) = self
+            .index
+            .resolve_namespace(&import.module)
+            .into_iter()
+            .next()
+        {
+            return Some(file);
+        }
+        let (namespace, type_name) = import.module.rsplit_once('.')?;
+        let files = self.index.resolve_namespace(namespace);
+        self.index
+            .resolve_symbol(type_name)
+            .into_iter()
+            .map(|(file, _)| file)
+            .find(|file| files.contains(file))
+    }
+
     fn resolve_absolute_import(&self, import: &ImportInfo) -> Option<String> {
         // Try direct module path match
         if let Some(file) = self.index.resolve_module(&import.module) {
@@ -43,29 +85,76 @@ impl<'a> ModuleResolver<'a> {
         None
     }
 
+    /// Resolve `from <level dots><module> import ...` against the importing
+    /// file's package. `from . import name` with no `__init__.py` in the
+    /// package resolves to the first imported name that is a submodule.
     fn resolve_relative_import(&self, import: &ImportInfo, current_file: &str) -> Option<String> {
-        let current_module = self.index.file_to_module(current_file)?;
-
-        // Go up `level` packages
-        let parts: Vec<&str> = current_module.split('.').collect();
-        if import.level as usize > parts.len() {
-            return None;
+        let base = self.relative_base(import.level, current_file)?;
+        let target_module = join_module(&base, &import.module);
+        if !target_module.is_empty()
+            && let Some(file) = self.index.resolve_module(&target_module)
+        {
+            return Some(file);
         }
+        if import.module.is_empty() {
+            return import
+                .names
+                .iter()
+                .find_map(|name| self.index.resolve_module(&join_module(&base, name)));
+        }
+        None
+    }
 
-        let base_parts = &parts[..parts.len() - import.level as usize];
-        let base = base_parts.join(".");
-
-        let target_module = if import.module.is_empty() {
-            base
+    /// The file of `name` when `from <import> import name` imports a
+    /// submodule rather than a symbol, e.g. `from . import sibling`.
+    pub fn resolve_imported_module(
+        &self,
+        import: &ImportInfo,
+        name: &str,
+        current_file: &str,
+    ) -> Option<String> {
+        if current_file.ends_with(".rs") {
+            return self
+                .index
+                .rust_modules
+                .resolve_exact(&format!("{}::{name}", import.module), current_file);
+        }
+        let package = if import.level > 0 {
+            join_module(
+                &self.relative_base(import.level, current_file)?,
+                &import.module,
+            )
         } else {
-            if base.is_empty() {
-                import.module.clone()
-            } else {
-                format!("{}.{}", base, import.module)
-            }
+            import.module.clone()
         };
+        self.index.resolve_module(&join_module(&package, name))
+    }
 
-        self.index.resolve_module(&target_module)
+    /// The package a relative import with `level` leading dots starts from:
+    /// the importing module's package (the package itself for an
+    /// `__init__.py`), then one package up per extra dot. Empty at the repo
+    /// root; `None` when the dots climb above it.
+    fn relative_base(&self, level: u32, current_file: &str) -> Option<String> {
+        let current_module = self.index.file_to_module(current_file)?;
+        let mut parts: Vec<&str> = current_module.split('.').collect();
+        let is_package = Path::new(current_file)
+            .file_stem()
+            .is_some_and(|stem| stem == "__init__");
+        let up = (level as usize).saturating_sub(usize::from(is_package));
+        if up > parts.len() {
+            return None;
+        }
+        parts.truncate(parts.len() - up);
+        Some(parts.join("."))
+    }
+}
+
+/// `package.module`, either side possibly empty.
+fn join_module(package: &str, module: &str) -> String {
+    match (package.is_empty(), module.is_empty()) {
+        (true, _) => module.to_string(),
+        (_, true) => package.to_string(),
+        _ => format!("{package}.{module}"),
     }
 }
 
@@ -121,4 +210,98 @@ mod tests {
             Some("/repo/app/services/auth.py".into())
         );
     }
+
+    fn relative(module: &str, names: &[&str], level: u32) -> ImportInfo {
+        ImportInfo {
+            module: module.into(),
+            names: names.iter().map(|n| n.to_string()).collect(),
+            level,
+            start_line: 1,
+            end_line: 1,
+        }
+    }
+
+    fn app_index() -> GlobalIndex {
+        let index = GlobalIndex::new();
+        index.build(
+            &[
+                "/repo/app/__init__.py",
+                "/repo/app/models.py",
+                "/repo/app/api/__init__.py",
+                "/repo/app/api/views.py",
+                "/repo/app/api/sibling.py",
+            ]
+            .map(|path| crate::indexer::CodeElement {
+                id: path.into(),
+                element_type: crate::indexer::ElementType::File,
+                name: path.rsplit('/').next().unwrap_or_default().into(),
+                file_path: path.into(),
+                relative_path: path.trim_start_matches("/repo/").into(),
+                language: "python".into(),
+                start_line: 1,
+                end_line: 1,
+                code: Default::default(),
+                signature: None,
+                docstring: None,
+                summary: None,
+                parent_id: None,
+                metadata: Default::default(),
+            }),
+            "/repo",
+        );
+        index
+    }
+
+    #[test]
+    fn test_from_dot_import_sibling() {
+        let index = app_index();
+        let resolver = ModuleResolver::new(&index);
+        let import = relative("", &["sibling"], 1);
+
+        // The package has an `__init__.py`; the name is its submodule.
+        assert_eq!(
+            resolver.resolve_import(&import, "/repo/app/api/views.py"),
+            Some("/repo/app/api/__init__.py".into())
+        );
+        assert_eq!(
+            resolver.resolve_imported_module(&import, "sibling", "/repo/app/api/views.py"),
+            Some("/repo/app/api/sibling.py".into())
+        );
+        // In the package's own `__init__.py`, `.` is the package itself.
+        assert_eq!(
+            resolver.resolve_imported_module(&import, "sibling", "/repo/app/api/__init__.py"),
+            Some("/repo/app/api/sibling.py".into())
+        );
+    }
+
+    #[test]
+    fn test_from_dot_dot_models_import() {
+        let index = app_index();
+        let resolver = ModuleResolver::new(&index);
+        let import = relative("models", &["User"], 2);
+
+        assert_eq!(
+            resolver.resolve_import(&import, "/repo/app/api/views.py"),
+            Some("/repo/app/models.py".into())
+        );
+        assert_eq!(
+            resolver.resolve_import(
+                &relative("models", &["User"], 1),
+                "/repo/app/api/__init__.py"
+            ),
+            None
+        );
+        assert_eq!(
+            resolver.resolve_import(
+                &relative("models", &["User"], 2),
+                "/repo/app/api/__init__.py"
+            ),
+            Some("/repo/app/models.py".into())
+        );
+        // Climbing above the repo root resolves nothing.
+        assert_eq!(
+            resolver.resolve_import(&relative("models", &["User"], 4), "/repo/app/api/views.py"),
+            None
+        );
+    }
 }
@@ -0,0 +1,309 @@
+//! Rust module tree: which file each `crate::a::b` module lives in.
+//!
+//! Crates are found by their roots (`lib.rs` or `main.rs`); the crate is
+//! named after the directory holding its `src/`, with `-` as `_`. Every
+//! other `.rs` file below a root is a module whose path follows the
+//! directory layout, both as `foo/mod.rs` and as `foo.rs` next to `foo/`.
+//! A file belongs to the crate of its nearest root.
+
+use std::path::{Path, PathBuf};
+
+use dashmap::DashMap;
+
+/// A file's place in the module tree.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RustModule {
+    /// Crate name as written in `use` paths.
+    pub krate: String,
+    /// Module path below the crate root, empty for the root itself.
+    pub path: Vec<String>,
+}
+
+impl RustModule {
+    fn key(krate: &str, path: &[&str]) -> String {
+        if path.is_empty() {
+            krate.to_string()
+        } else {
+            format!("{krate}::{}", path.join("::"))
+        }
+    }
+}
+
+/// Module tree of the workspace's Rust crates.
+#[derive(Default)]
+pub struct RustModules {
+    /// Directory holding a crate root -> crate name
+    roots: DashMap<PathBuf, String>,
+    /// file_path -> its module
+    files: DashMap<String, RustModule>,
+    /// `krate::a::b` -> file_path
+    modules: DashMap<String, String>,
+}
+
+impl RustModules {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add `.rs` files to the tree. Crate roots among them are registered
+    /// first, so files can come in any order.
+    pub fn build<'a>(&self, file_paths: impl Iterator<Item = &'a str> + Clone) {
+        for file_path in file_paths.clone() {
+            if is_crate_root(file_path)
+                && let Some(dir) = Path::new(file_path).parent()
+            {
+                self.roots.insert(dir.to_path_buf(), crate_name(dir));
+            }
+        }
+        for file_path in file_paths {
+            let Some(module) = self.module_of(file_path) else {
+                continue;
+            };
+            let path: Vec<&str> = module.path.iter().map(String::as_str).collect();
+            let key = RustModule::key(&module.krate, &path);
+            // A crate with both keeps `lib.rs` as its root module.
+            let is_main = Path::new(file_path).file_name() == Some("main.rs".as_ref());
+            if !(is_main && self.modules.contains_key(&key)) {
+                self.modules.insert(key, file_path.to_string());
+            }
+            self.files.insert(file_path.to_string(), module);
+        }
+    }
+
+    /// Remove a file from the tree.
+    pub fn remove_file(&self, file_path: &str) {
+        let Some((_, module)) = self.files.remove(file_path) else {
+            return;
+        };
+        let path: Vec<&str> = module.path.iter().map(String::as_str).collect();
+        self.modules
+            .remove_if(&RustModule::key(&module.krate, &path), |_, file| {
+                file == file_path
+            });
+    }
+
+    /// The module of an indexed file.
+    pub fn file_module(&self, file_path: &str) -> Option<RustModule> {
+        self.files.get(file_path).map(|m| m.clone())
+    }
+
+    /// The file a `use` path or `mod` name written in `current_file` refers
+    /// to: the deepest module on the path, so `crate::graph::RepositoryGraph`
+    /// resolves to the file of `crate::graph`. Handles `crate::`, `self::`,
+    /// `super::`, child modules of the current one, and paths starting with
+    /// the name of another crate in the workspace.
+    pub fn resolve(&self, path: &str, current_file: &str) -> Option<String> {
+        self.resolve_path(path, current_file, false)
+    }
+
+    /// Like [`Self::resolve`], but only when the whole path is a module.
+    pub fn resolve_exact(&self, path: &str, current_file: &str) -> Option<String> {
+        self.resolve_path(path, current_file, true)
+    }
+
+    fn resolve_path(&self, path: &str, current_file: &str, exact: bool) -> Option<String> {
+        let current = self.file_module(current_file)?;
+        let segments: Vec<&str> = path.split("::").filter(|s| !s.is_empty()).collect();
+        let (&first, rest) = segments.split_first()?;
+        let here: Vec<&str> = current.path.iter().map(String::as_str).collect();
+        match first {
+            "crate" => self.deepest(&current.krate, &[], rest, exact),
+            "self" | "super" => {
+                let mut base = here;
+                let mut rest = segments.as_slice();
+                while let Some((&segment, tail)) = rest.split_first() {
+                    match segment {
+                        "self" => {}
+                        "super" => {
+                            base.pop()?;
+                        }
+                        _ => break,
+                    }
+                    rest = tail;
+                }
+                self.deepest(&current.krate, &base, rest, exact)
+            }
+            // A child of the current module, else another workspace crate
+            _ => self
+                .deepest_nonempty(&current.krate, &here, &segments, exact)
+                .or_else(|| {
+                    self.roots
+                        .iter()
+                        .any(|root| root.value() == first)
+                        .then(|| self.deepest(first, &[], rest, exact))
+                        .flatten()
+                }),
+        }
+    }
+
+    /// File of the longest module `krate::base::rest[..n]`, with `n` at
+    /// least 0 (all of `rest` when `exact`).
+    fn deepest(&self, krate: &str, base: &[&str], rest: &[&str], exact: bool) -> Option<String> {
+        let shortest = if exact { rest.len() } else { 0 };
+        self.longest_prefix(krate, base, rest, shortest)
+    }
+
+    /// Like [`Self::deepest`], but `rest` must name at least one module.
+    fn deepest_nonempty(
+        &self,
+        krate: &str,
+        base: &[&str],
+        rest: &[&str],
+        exact: bool,
+    ) -> Option<String> {
+        let shortest = if exact { rest.len() } else { 1 };
+        self.longest_prefix(krate, base, rest, shortest.max(1))
+    }
+
+    fn longest_prefix(
+        &self,
+        krate: &str,
+        base: &[&str],
+        rest: &[&str],
+        shortest: usize,
+    ) -> Option<String> {
+        (shortest..=rest.len()).rev().find_map(|n| {
+            let path: Vec<&str> = base.iter().chain(&rest[..n]).copied().collect();
+            self.modules
+                .get(&RustModule::key(krate, &path))
+                .map(|file| file.clone())
+        })
+    }
+
+    /// The module `file_path` is, below its nearest crate root.
+    fn module_of(&self, file_path: &str) -> Option<RustModule> {
+        let path = Path::new(file_path);
+        let (root, krate) = path.ancestors().skip(1).find_map(|dir| {
+            self.roots
+                .get(dir)
+                .map(|krate| (dir.to_path_buf(), krate.clone()))
+        })?;
+        if is_crate_root(file_path) && path.parent() == Some(root.as_path()) {
+            return Some(RustModule {
+                krate,
+                path: Vec::new(),
+            });
+        }
+        let relative = path.strip_prefix(&root).ok()?.with_extension("");
+        let mut segments: Vec<String> = relative
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy().into_owned())
+            .collect();
+        if segments.last().is_some_and(|last| last == "mod") {
+            segments.pop();
+        }
+        Some(RustModule {
+            krate,
+            path: segments,
+        })
+    }
+}
+
+fn is_crate_root(file_path: &str) -> bool {
+    matches!(
+        Path::new(file_path).file_name().and_then(|n| n.to_str()),
+        Some("lib.rs" | "main.rs")
+    )
+}
+
+/// `crates/happy-core/src` -> `happy_core`; a root outside `src/` is named
+/// after its own directory.
+fn crate_name(root_dir: &Path) -> String {
+    let dir = if root_dir.file_name() == Some("src".as_ref()) {
+        root_dir.parent().unwrap_or(root_dir)
+    } else {
+        root_dir
+    };
+    dir.file_name()
+        .map(|name| name.to_string_lossy().replace('-', "_"))
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn tree(files: &[&str]) -> RustModules {
+        let modules = RustModules::new();
+        modules.build(files.iter().copied());
+        modules
+    }
+
+    #[test]
+    fn mod_rs_layout() {
+        let modules = tree(&[
+            "/ws/crates/happy-core/src/parser/calls.rs",
+            "/ws/crates/happy-core/src/parser/mod.rs",
+            "/ws/crates/happy-core/src/graph/mod.rs",
+            "/ws/crates/happy-core/src/lib.rs",
+        ]);
+        let graph = "/ws/crates/happy-core/src/graph/mod.rs";
+
+        assert_eq!(
+            modules.file_module("/ws/crates/happy-core/src/parser/calls.rs"),
+            Some(RustModule {
+                krate: "happy_core".into(),
+                path: vec!["parser".into(), "calls".into()],
+            })
+        );
+        assert_eq!(
+            modules.resolve("crate::parser::calls::extract_calls", graph),
+            Some("/ws/crates/happy-core/src/parser/calls.rs".into())
+        );
+        assert_eq!(
+            modules.resolve("super::parser", graph),
+            Some("/ws/crates/happy-core/src/parser/mod.rs".into())
+        );
+        // An item at the crate root resolves to the root file.
+        assert_eq!(
+            modules.resolve("crate::Error", graph),
+            Some("/ws/crates/happy-core/src/lib.rs".into())
+        );
+        // `mod parser;` in lib.rs
+        assert_eq!(
+            modules.resolve("parser", "/ws/crates/happy-core/src/lib.rs"),
+            Some("/ws/crates/happy-core/src/parser/mod.rs".into())
+        );
+        assert_eq!(modules.resolve("std::collections::HashMap", graph), None);
+        assert_eq!(modules.resolve("super::super::x", graph), None);
+    }
+
+    #[test]
+    fn file_and_directory_layout() {
+        let modules = tree(&[
+            "/ws/cli/src/main.rs",
+            "/ws/cli/src/commands.rs",
+            "/ws/cli/src/commands/export.rs",
+            "/ws/core/src/lib.rs",
+            "/ws/core/src/exec.rs",
+        ]);
+        let export = "/ws/cli/src/commands/export.rs";
+
+        assert_eq!(
+            modules.resolve("export", "/ws/cli/src/commands.rs"),
+            Some(export.into())
+        );
+        assert_eq!(
+            modules.resolve("super::super::commands", export),
+            Some("/ws/cli/src/commands.rs".into())
+        );
+        assert_eq!(
+            modules.resolve("self::Command", "/ws/cli/src/commands.rs"),
+            Some("/ws/cli/src/commands.rs".into())
+        );
+        // Another crate of the workspace, by name.
+        assert_eq!(
+            modules.resolve("core::exec::spawn", export),
+            Some("/ws/core/src/exec.rs".into())
+        );
+        assert_eq!(modules.resolve_exact("core::exec::spawn", export), None);
+        assert_eq!(
+            modules.resolve_exact("crate::commands", export),
+            Some("/ws/cli/src/commands.rs".into())
+        );
+
+        modules.remove_file(export);
+        assert_eq!(modules.resolve("export", "/ws/cli/src/commands.rs"), None);
+    }
+}
@@ -1,4 +1,46 @@
+use std::path::Path;
+
+use serde::Serialize;
+
 use super::GlobalIndex;
+use crate::parser::languages::SupportedLanguage;
+
+/// Score for a package-visible symbol from the caller's own directory,
+/// which needs no import.
+pub const SAME_PACKAGE_SCORE: u32 = 60;
+
+/// Score for a symbol from a module the caller's file imports.
+pub const IMPORTED_MODULE_SCORE: u32 = 50;
+
+/// Score for a symbol defined in the caller's language.
+pub const SAME_LANGUAGE_SCORE: u32 = 10;
+
+/// Score for a symbol in the caller's directory, less
+/// [`DIRECTORY_STEP_PENALTY`] per directory between the two.
+pub const PROXIMITY_SCORE: u32 = 20;
+
+/// Proximity lost per directory step between caller and symbol.
+pub const DIRECTORY_STEP_PENALTY: u32 = 5;
+
+/// Lowest score a call target needs to be linked with confidence: an import
+/// or a shared package, not just a shared language and nearby path.
+pub const MIN_CONFIDENT_SCORE: u32 = IMPORTED_MODULE_SCORE;
+
+/// A definition a symbol may refer to, scored by how well the context
+/// supports it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ScoredSymbol {
+    pub file_path: String,
+    pub element_id: String,
+    pub score: u32,
+}
+
+impl ScoredSymbol {
+    /// Whether the score clears [`MIN_CONFIDENT_SCORE`].
+    pub fn is_confident(&self) -> bool {
+        self.score >= MIN_CONFIDENT_SCORE
+    }
+}
 
 /// Resolve symbol names to element IDs using the global index.
 pub struct SymbolResolver<'a> {
@@ -16,33 +58,78 @@ impl<'a> SymbolResolver<'a> {
         self.index.resolve_symbol(symbol)
     }
 
-    /// Resolve a symbol in the context of a specific file's imports.
-    /// This narrows results to symbols that are actually imported by the file.
+    /// Every definition `symbol` may refer to from `current_file`, best
+    /// first. Package-visible symbols from the file's own directory score
+    /// [`SAME_PACKAGE_SCORE`] and exported symbols from modules in
+    /// `imported_modules` [`IMPORTED_MODULE_SCORE`]; both gain for a shared
+    /// language and a nearby directory. Private symbols never resolve here.
     pub fn resolve_in_context(
         &self,
         symbol: &str,
-        _current_file: &str,
+        current_file: &str,
         imported_modules: &[String],
-    ) -> Vec<(String, String)> {
-        let all = self.index.resolve_symbol(symbol);
-
-        if imported_modules.is_empty() {
-            return all;
-        }
-
-        // Filter to only symbols from imported modules
-        all.into_iter()
-            .filter(|(file_path, _)| {
-                if let Some(module) = self.index.file_to_module(file_path) {
-                    imported_modules.iter().any(|m| module.starts_with(m))
-                } else {
-                    false
+    ) -> Vec<ScoredSymbol> {
+        let package = self
+            .index
+            .resolve_package_symbol(symbol, current_file)
+            .into_iter()
+            .map(|candidate| (candidate, SAME_PACKAGE_SCORE));
+        let exported = self
+            .index
+            .resolve_symbol(symbol)
+            .into_iter()
+            .map(|candidate| {
+                let imported = self
+                    .index
+                    .file_to_module(&candidate.0)
+                    .is_some_and(|module| imported_modules.iter().any(|m| module.starts_with(m)));
+                (candidate, if imported { IMPORTED_MODULE_SCORE } else { 0 })
+            });
+
+        let language = SupportedLanguage::from_extension_any(current_file);
+        let mut scored: Vec<ScoredSymbol> = package
+            .chain(exported)
+            .map(|((file_path, element_id), base)| {
+                let mut score = base + proximity_score(current_file, &file_path);
+                if language.is_some()
+                    && SupportedLanguage::from_extension_any(&file_path) == language
+                {
+                    score += SAME_LANGUAGE_SCORE;
+                }
+                ScoredSymbol {
+                    file_path,
+                    element_id,
+                    score,
                 }
             })
-            .collect()
+            .collect();
+        scored.sort_by(|a, b| {
+            b.score
+                .cmp(&a.score)
+                .then_with(|| a.file_path.cmp(&b.file_path))
+        });
+        scored
     }
 }
 
+/// [`PROXIMITY_SCORE`] less [`DIRECTORY_STEP_PENALTY`] for each directory
+/// between the directories of `from` and `to` through their shared prefix.
+fn proximity_score(from: &str, to: &str) -> u32 {
+    let from: Vec<_> = Path::new(from)
+        .parent()
+        .into_iter()
+        .flat_map(Path::components)
+        .collect();
+    let to: Vec<_> = Path::new(to)
+        .parent()
+        .into_iter()
+        .flat_map(Path::components)
+        .collect();
+    let shared = from.iter().zip(&to).take_while(|(a, b)| a == b).count();
+    let steps = (from.len() - shared + to.len() - shared) as u32;
+    PROXIMITY_SCORE.saturating_sub(steps.saturating_mul(DIRECTORY_STEP_PENALTY))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -62,4 +149,65 @@ mod tests {
         let results = resolver.resolve("login");
         assert_eq!(results.len(), 2);
     }
+
+    #[test]
+    fn test_resolve_in_context_keeps_package_symbols_local() {
+        let index = GlobalIndex::new();
+        index.package_map.insert(
+            "helper".into(),
+            vec![
+                ("pkg/a/util.go".into(), "a_helper".into()),
+                ("pkg/b/util.go".into(), "b_helper".into()),
+            ],
+        );
+
+        let resolver = SymbolResolver::new(&index);
+        assert_eq!(
+            resolver.resolve_in_context("helper", "pkg/b/main.go", &[]),
+            vec![ScoredSymbol {
+                file_path: "pkg/b/util.go".to_string(),
+                element_id: "b_helper".to_string(),
+                score: SAME_PACKAGE_SCORE + PROXIMITY_SCORE + SAME_LANGUAGE_SCORE,
+            }]
+        );
+        assert!(
+            resolver
+                .resolve_in_context("helper", "pkg/c/main.go", &[])
+                .is_empty()
+        );
+        assert!(resolver.resolve("helper").is_empty());
+    }
+
+    #[test]
+    fn test_imported_symbols_outrank_nearby_ones() {
+        let index = GlobalIndex::new();
+        index.export_map.insert(
+            "helper".into(),
+            vec![
+                ("app/views/util.py".into(), "near_helper".into()),
+                ("lib/util.py".into(), "imported_helper".into()),
+                ("vendor/deep/pkg/util.py".into(), "far_helper".into()),
+                ("app/views/util.js".into(), "js_helper".into()),
+            ],
+        );
+        index
+            .file_map
+            .insert("lib/util.py".into(), "lib.util".into());
+
+        let resolver = SymbolResolver::new(&index);
+        let scored =
+            resolver.resolve_in_context("helper", "app/views/main.py", &["lib.util".into()]);
+        let ids: Vec<&str> = scored.iter().map(|s| s.element_id.as_str()).collect();
+        assert_eq!(
+            ids,
+            vec!["imported_helper", "near_helper", "js_helper", "far_helper"]
+        );
+        assert!(scored[0].is_confident());
+        assert!(!scored[1].is_confident());
+
+        // Without the import, the nearest definition leads but is a guess.
+        let scored = resolver.resolve_in_context("helper", "app/views/main.py", &[]);
+        assert_eq!(scored[0].element_id, "near_helper");
+        assert!(scored.iter().all(|s| !s.is_confident()));
+    }
 }
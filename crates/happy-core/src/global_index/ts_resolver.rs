@@ -0,0 +1,523 @@
+//! JavaScript/TypeScript import specifiers to indexed files.
+//!
+//! Relative specifiers (`./components`) resolve against the importing
+//! file's directory. Others go through the nearest `tsconfig.json` (or
+//! `jsconfig.json`) above the importing file: its `compilerOptions.paths`
+//! aliases first, then `baseUrl`. Configs are read from disk when first
+//! needed after each build, follow relative `extends`, and may contain
+//! comments and trailing commas. A bare package name that matches neither
+//! resolves when it names the `package.json` of an indexed root, so imports
+//! between the repositories of a workspace resolve; other package names
+//! resolve to nothing.
+//!
+//! A candidate path matches the file itself, the file with one of
+//! [`EXTENSIONS`], or an `index` file in the directory it names. A `.js`
+//! specifier also matches the `.ts` file it is compiled from.
+
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Arc, Mutex, PoisonError};
+
+use dashmap::{DashMap, DashSet};
+use serde_json::Value;
+
+use crate::parser::languages::SupportedLanguage;
+
+/// Extensions tried, in order, for a specifier without one.
+pub const EXTENSIONS: &[&str] = &["ts", "tsx", "d.ts", "js", "jsx", "mjs", "cjs"];
+
+/// Config files looked for in each directory, in order.
+const CONFIG_FILES: &[&str] = &["tsconfig.json", "jsconfig.json"];
+
+/// Longest `extends` chain followed.
+const MAX_EXTENDS: usize = 8;
+
+/// The module resolution settings of one config, with those it extends.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+struct TsConfig {
+    /// `baseUrl`, absolute
+    base_url: Option<PathBuf>,
+    /// `paths` patterns and their targets, in file order
+    paths: Vec<(String, Vec<String>)>,
+    /// Directory `paths` targets are relative to when there is no `baseUrl`
+    paths_dir: PathBuf,
+}
+
+/// Indexed JS/TS files and the configs governing them.
+#[derive(Default)]
+pub struct TsModules {
+    files: DashSet<String>,
+    /// Directory -> the config nearest to it, `None` when there is none
+    configs: DashMap<PathBuf, Option<Arc<TsConfig>>>,
+    /// Configs are not looked for above the root containing a file
+    repo_roots: Mutex<Vec<PathBuf>>,
+    /// `package.json` name -> the root it names
+    packages: DashMap<String, PathBuf>,
+}
+
+impl TsModules {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add JS/TS files. Configs are re-read on the next lookup, so edits to
+    /// them are picked up by the next build. Without a `repo_root` only
+    /// relative specifiers resolve.
+    pub fn build<'a>(&self, file_paths: impl Iterator<Item = &'a str>, repo_root: &str) {
+        self.build_roots(file_paths, &[repo_root]);
+    }
+
+    /// Like [`build`](Self::build), for files under several roots. Each
+    /// root's `package.json` name resolves to that root.
+    pub fn build_roots<'a>(&self, file_paths: impl Iterator<Item = &'a str>, repo_roots: &[&str]) {
+        let repo_roots: Vec<PathBuf> = repo_roots
+            .iter()
+            .filter(|root| !root.is_empty())
+            .map(PathBuf::from)
+            .collect();
+        if !repo_roots.is_empty() {
+            self.packages.clear();
+            for root in &repo_roots {
+                if let Some(name) = package_name(root) {
+                    self.packages.insert(name, root.clone());
+                }
+            }
+            *self
+                .repo_roots
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner) = repo_roots;
+        }
+        self.configs.clear();
+        for file_path in file_paths {
+            self.files.insert(file_path.to_string());
+        }
+    }
+
+    pub fn remove_file(&self, file_path: &str) {
+        self.files.remove(file_path);
+    }
+
+    /// The indexed file `specifier`, imported from `current_file`, names.
+    pub fn resolve(&self, specifier: &str, current_file: &str) -> Option<String> {
+        let dir = Path::new(current_file).parent()?;
+        if is_relative(specifier) {
+            return self.find_file(&dir.join(specifier));
+        }
+        if let Some(config) = self.config_for(dir) {
+            if let Some(targets) = match_paths(&config.paths, specifier) {
+                let targets_dir = config.base_url.as_deref().unwrap_or(&config.paths_dir);
+                for target in targets {
+                    if let Some(file) = self.find_file(&targets_dir.join(target)) {
+                        return Some(file);
+                    }
+                }
+            }
+            if let Some(file) = config
+                .base_url
+                .as_ref()
+                .and_then(|base_url| self.find_file(&base_url.join(specifier)))
+            {
+                return Some(file);
+            }
+        }
+        self.resolve_package(specifier)
+    }
+
+    /// The indexed file `specifier` names in a root whose `package.json`
+    /// name it starts with: `@acme/api/client` is `client` in the root
+    /// named `@acme/api`, and `@acme/api` alone its `index` or `src/index`.
+    fn resolve_package(&self, specifier: &str) -> Option<String> {
+        self.packages.iter().find_map(|entry| {
+            let root = entry.value();
+            match specifier.strip_prefix(entry.key().as_str())? {
+                "" => self
+                    .find_file(&root.join("index"))
+                    .or_else(|| self.find_file(&root.join("src").join("index"))),
+                rest => self.find_file(&root.join(rest.strip_prefix('/')?)),
+            }
+        })
+    }
+
+    /// The indexed file `path` names, trying extensions and index files.
+    fn find_file(&self, path: &Path) -> Option<String> {
+        let path = normalize(path);
+        let path = path.to_str()?;
+        let mut candidates = vec![path.to_string()];
+        for (js, ts) in [
+            (".js", ".ts"),
+            (".jsx", ".tsx"),
+            (".mjs", ".mts"),
+            (".cjs", ".cts"),
+        ] {
+            if let Some(stem) = path.strip_suffix(js) {
+                candidates.push(format!("{stem}{ts}"));
+            }
+        }
+        candidates.extend(EXTENSIONS.iter().map(|ext| format!("{path}.{ext}")));
+        candidates.extend(EXTENSIONS.iter().map(|ext| format!("{path}/index.{ext}")));
+        candidates
+            .into_iter()
+            .find(|candidate| self.files.contains(candidate))
+    }
+
+    /// The nearest config in `dir` or above it, up to the repo root
+    /// containing `dir`.
+    fn config_for(&self, dir: &Path) -> Option<Arc<TsConfig>> {
+        let dir = normalize(dir);
+        let repo_root = self
+            .repo_roots
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .iter()
+            .filter(|root| dir.starts_with(root))
+            .max_by_key(|root| root.as_os_str().len())?
+            .clone();
+        if let Some(cached) = self.configs.get(&dir) {
+            return cached.clone();
+        }
+        let config = CONFIG_FILES
+            .iter()
+            .map(|name| dir.join(name))
+            .find(|path| path.is_file())
+            .and_then(|path| load_config(&path, 0))
+            .map(Arc::new)
+            .or_else(|| {
+                dir.parent()
+                    .filter(|parent| parent.starts_with(&repo_root))
+                    .and_then(|parent| self.config_for(parent))
+            });
+        self.configs.insert(dir, config.clone());
+        config
+    }
+}
+
+/// Whether `file_path` is a JavaScript or TypeScript file.
+pub fn is_js_ts(file_path: &str) -> bool {
+    matches!(
+        SupportedLanguage::from_extension_any(file_path),
+        Some(
+            SupportedLanguage::JavaScript | SupportedLanguage::TypeScript | SupportedLanguage::Tsx
+        )
+    )
+}
+
+/// The `name` in `root`'s `package.json`, if it has one.
+fn package_name(root: &Path) -> Option<String> {
+    let text = std::fs::read_to_string(root.join("package.json")).ok()?;
+    let json: Value = serde_json::from_str(&text).ok()?;
+    json.get("name")
+        .and_then(Value::as_str)
+        .filter(|name| !name.is_empty())
+        .map(str::to_string)
+}
+
+fn is_relative(specifier: &str) -> bool {
+    specifier == "."
+        || specifier == ".."
+        || specifier.starts_with("./")
+        || specifier.starts_with("../")
+}
+
+/// Read a config and the chain it `extends`; settings in `path` override
+/// inherited ones. Unreadable or invalid configs are skipped with a warning.
+fn load_config(path: &Path, depth: usize) -> Option<TsConfig> {
+    let text = std::fs::read_to_string(path).ok()?;
+    let json: Value = match serde_json::from_str(&strip_jsonc(&text)) {
+        Ok(json) => json,
+        Err(err) => {
+            tracing::warn!(path = %path.display(), error = %err, "ignoring invalid tsconfig");
+            return None;
+        }
+    };
+    let dir = path.parent().unwrap_or(Path::new(""));
+
+    let mut config = json
+        .get("extends")
+        .and_then(Value::as_str)
+        .filter(|extends| depth < MAX_EXTENDS && is_relative(extends))
+        .and_then(|extends| {
+            let mut parent = dir.join(extends);
+            if parent.extension().is_none() {
+                parent.set_extension("json");
+            }
+            load_config(&normalize(&parent), depth + 1)
+        })
+        .unwrap_or_default();
+
+    let options = json.get("compilerOptions");
+    if let Some(base_url) = options
+        .and_then(|o| o.get("baseUrl"))
+        .and_then(Value::as_str)
+    {
+        config.base_url = Some(normalize(&dir.join(base_url)));
+    }
+    if let Some(paths) = options
+        .and_then(|o| o.get("paths"))
+        .and_then(Value::as_object)
+    {
+        config.paths = paths
+            .iter()
+            .map(|(pattern, targets)| {
+                let targets = targets
+                    .as_array()
+                    .map(|targets| {
+                        targets
+                            .iter()
+                            .filter_map(Value::as_str)
+                            .map(str::to_string)
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                (pattern.clone(), targets)
+            })
+            .collect();
+        config.paths_dir = dir.to_path_buf();
+    }
+    Some(config)
+}
+
+/// Targets of the `paths` pattern matching `specifier`, with its `*`
+/// filled in: an exact pattern first, else the wildcard pattern with the
+/// longest prefix.
+fn match_paths(paths: &[(String, Vec<String>)], specifier: &str) -> Option<Vec<String>> {
+    if let Some((_, targets)) = paths.iter().find(|(pattern, _)| pattern == specifier) {
+        return Some(targets.clone());
+    }
+    let (_, captured, targets) = paths
+        .iter()
+        .filter_map(|(pattern, targets)| {
+            let (prefix, suffix) = pattern.split_once('*')?;
+            let captured = specifier.strip_prefix(prefix)?.strip_suffix(suffix)?;
+            Some((prefix.len(), captured, targets))
+        })
+        .max_by_key(|(prefix_len, _, _)| *prefix_len)?;
+    Some(
+        targets
+            .iter()
+            .map(|target| target.replacen('*', captured, 1))
+            .collect(),
+    )
+}
+
+/// `path` with `.` and `..` components folded away, without touching the
+/// file system.
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if !normalized.pop() {
+                    normalized.push("..");
+                }
+            }
+            other => normalized.push(other),
+        }
+    }
+    normalized
+}
+
+/// JSON with comments and trailing commas, as tsconfig allows, to plain
+/// JSON.
+fn strip_jsonc(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    let mut in_string = false;
+    while let Some(c) = chars.next() {
+        if in_string {
+            out.push(c);
+            match c {
+                '\\' => out.extend(chars.next()),
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match (c, chars.peek()) {
+            ('"', _) => {
+                in_string = true;
+                out.push(c);
+            }
+            ('/', Some('/')) => {
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        out.push('\n');
+                        break;
+                    }
+                }
+            }
+            ('/', Some('*')) => {
+                chars.next();
+                let mut prev = ' ';
+                for c in chars.by_ref() {
+                    if prev == '*' && c == '/' {
+                        break;
+                    }
+                    prev = c;
+                }
+            }
+            ('}' | ']', _) => {
+                // Drop a trailing comma before the closing bracket.
+                let kept = out.trim_end().len();
+                if out[..kept].ends_with(',') {
+                    out.truncate(kept - 1);
+                }
+                out.push(c);
+            }
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn strips_comments_and_trailing_commas() {
+        let text = r#"{
+            // line comment
+            "a": "http://x/*y*/", /* block */
+            "b": [1, 2,],
+        }"#;
+        let json: Value = serde_json::from_str(&strip_jsonc(text)).unwrap();
+        assert_eq!(json["a"], "http://x/*y*/");
+        assert_eq!(json["b"], serde_json::json!([1, 2]));
+    }
+
+    #[test]
+    fn resolves_relative_alias_and_index_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let write = |rel: &str, text: &str| {
+            let path = root.join(rel);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, text).unwrap();
+        };
+        write(
+            "tsconfig.json",
+            r#"{
+                // Shared aliases
+                "compilerOptions": {
+                    "baseUrl": ".",
+                    "paths": {
+                        "@app/*": ["src/app/*"],
+                        "@shared": ["src/shared/index.ts"],
+                    },
+                },
+            }"#,
+        );
+        write(
+            "packages/web/tsconfig.json",
+            r#"{
+                "extends": "../../tsconfig",
+                "compilerOptions": { "baseUrl": ".", "paths": { "@app/*": ["app/*"] } }
+            }"#,
+        );
+        write(
+            "packages/api/tsconfig.json",
+            r#"{ "extends": "../../tsconfig.json" }"#,
+        );
+        let files = [
+            "src/app/services/index.ts",
+            "src/app/services/user.ts",
+            "src/app/components/index.tsx",
+            "src/app/main.ts",
+            "src/lib/util.ts",
+            "src/shared/index.ts",
+            "packages/web/app/page.ts",
+            "packages/web/src/main.ts",
+            "packages/api/src/main.ts",
+        ];
+        for file in files {
+            write(file, "");
+        }
+        let paths: Vec<String> = files
+            .iter()
+            .map(|file| root.join(file).to_string_lossy().into_owned())
+            .collect();
+        let modules = TsModules::new();
+        modules.build(paths.iter().map(String::as_str), &root.to_string_lossy());
+        let path = |rel: &str| Some(root.join(rel).to_string_lossy().into_owned());
+        let resolve = |specifier: &str, from: &str| {
+            modules.resolve(specifier, &root.join(from).to_string_lossy())
+        };
+
+        let main = "src/app/main.ts";
+        assert_eq!(
+            resolve("./components", main),
+            path("src/app/components/index.tsx")
+        );
+        assert_eq!(
+            resolve("./services/user.js", main),
+            path("src/app/services/user.ts")
+        );
+        assert_eq!(resolve("../lib/util", main), path("src/lib/util.ts"));
+        assert_eq!(
+            resolve("@app/services", main),
+            path("src/app/services/index.ts")
+        );
+        assert_eq!(resolve("@shared", main), path("src/shared/index.ts"));
+        // `baseUrl` alone
+        assert_eq!(resolve("src/lib/util", main), path("src/lib/util.ts"));
+        assert_eq!(resolve("react", main), None);
+
+        // The nearest config wins.
+        assert_eq!(
+            resolve("@app/page", "packages/web/src/main.ts"),
+            path("packages/web/app/page.ts")
+        );
+        // Inherited through `extends`, relative to the config defining them.
+        assert_eq!(
+            resolve("@app/services", "packages/api/src/main.ts"),
+            path("src/app/services/index.ts")
+        );
+    }
+
+    #[test]
+    fn relative_specifiers_resolve_without_a_repo_root() {
+        let modules = TsModules::new();
+        modules.build(["web/a.ts", "web/lib/index.js"].into_iter(), "");
+        assert_eq!(
+            modules.resolve("./lib", "web/a.ts"),
+            Some("web/lib/index.js".into())
+        );
+        assert_eq!(modules.resolve("lib", "web/a.ts"), None);
+    }
+
+    #[test]
+    fn package_names_resolve_across_roots() {
+        let dir = tempfile::tempdir().unwrap();
+        let (web, api) = (dir.path().join("web"), dir.path().join("api"));
+        for (path, text) in [
+            (web.join("src/main.ts"), ""),
+            (api.join("package.json"), r#"{ "name": "@acme/api" }"#),
+            (api.join("src/index.ts"), ""),
+            (api.join("client.ts"), ""),
+        ] {
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, text).unwrap();
+        }
+        let files: Vec<String> = [
+            web.join("src/main.ts"),
+            api.join("src/index.ts"),
+            api.join("client.ts"),
+        ]
+        .iter()
+        .map(|path| path.to_string_lossy().into_owned())
+        .collect();
+        let modules = TsModules::new();
+        modules.build_roots(
+            files.iter().map(String::as_str),
+            &[&*web.to_string_lossy(), &*api.to_string_lossy()],
+        );
+        let main = &files[0];
+        assert_eq!(modules.resolve("@acme/api", main), Some(files[1].clone()));
+        assert_eq!(
+            modules.resolve("@acme/api/client", main),
+            Some(files[2].clone())
+        );
+        assert_eq!(modules.resolve("@acme/api-client", main), None);
+    }
+}
@@ -0,0 +1,680 @@
+//! One-call review of a single file: outline, imports, outgoing calls,
+//! outside uses of its symbols, diagnostics and size.
+//!
+//! The report is composed from the existing queries ([`file_outline`],
+//! [`file_diagnostics`]) plus a single pass over the file's nodes and their
+//! incident edges, so it costs about as much as the outline alone. Every list
+//! is capped per section and the whole report is shrunk until its JSON fits
+//! [`AnalysisLimits::max_bytes`]; each section keeps its full `total`.
+//!
+//! [`file_outline`]: RepositoryGraph::file_outline
+//! [`file_diagnostics`]: RepositoryGraph::file_diagnostics
+
+use std::collections::{BTreeMap, HashSet};
+
+use petgraph::Direction;
+use petgraph::visit::EdgeRef;
+use serde::Serialize;
+
+use super::RepositoryGraph;
+use super::queries::{FileLookupError, OutlineNode};
+use super::types::{EdgeKind, GraphNode, NodeKind};
+use crate::global_index::module_resolver::ModuleResolver;
+use crate::parser::imports::{ImportInfo, extract_imports};
+use crate::parser::languages::SupportedLanguage;
+use crate::parser::with_thread_parser;
+
+/// Entries kept per section by default.
+pub const DEFAULT_SECTION_LIMIT: usize = 25;
+
+/// Default size budget of a serialized report, in bytes.
+pub const DEFAULT_ANALYSIS_BUDGET: usize = 16_000;
+
+/// Edges through which other files use a symbol.
+const USE_EDGES: &[EdgeKind] = &[
+    EdgeKind::Calls,
+    EdgeKind::Imports,
+    EdgeKind::Inherits,
+    EdgeKind::Implements,
+    EdgeKind::References,
+];
+
+/// Caps applied to a [`FileAnalysis`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AnalysisLimits {
+    /// Entries kept in each list.
+    pub section_limit: usize,
+    /// Largest serialized report; sections are cut further to fit.
+    pub max_bytes: usize,
+}
+
+impl Default for AnalysisLimits {
+    fn default() -> Self {
+        Self {
+            section_limit: DEFAULT_SECTION_LIMIT,
+            max_bytes: DEFAULT_ANALYSIS_BUDGET,
+        }
+    }
+}
+
+/// A capped list, with the number of entries before capping.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Section<T> {
+    pub total: usize,
+    pub items: Vec<T>,
+}
+
+impl<T> Section<T> {
+    fn new(items: Vec<T>) -> Self {
+        Self {
+            total: items.len(),
+            items,
+        }
+    }
+
+    fn truncate(&mut self, limit: usize) {
+        self.items.truncate(limit);
+    }
+
+    /// Entries left out by the cap.
+    pub fn omitted(&self) -> usize {
+        self.total - self.items.len()
+    }
+}
+
+/// Everything [`RepositoryGraph::analyze_file`] reports about one file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FileAnalysis {
+    /// Path relative to the repo root.
+    pub file_path: String,
+    pub language: String,
+    pub metrics: FileMetrics,
+    /// Top-level elements, with their members nested.
+    pub outline: Section<OutlineNode>,
+    pub imports: Section<ImportReport>,
+    /// Symbols in other files called from here, grouped by file.
+    pub calls_out: Section<CallTarget>,
+    /// Symbols defined here that other files use, most used first.
+    pub used_elsewhere: Section<ExternalUse>,
+    /// Files importing this one.
+    pub dependents: Section<String>,
+    pub diagnostics: AnalysisDiagnostics,
+}
+
+/// Size of a file and of its largest function.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct FileMetrics {
+    pub lines: usize,
+    pub bytes: usize,
+    pub classes: usize,
+    pub functions: usize,
+    pub methods: usize,
+    /// Longest function or method and its length in lines.
+    pub longest_function: Option<(String, usize)>,
+}
+
+/// How an import statement resolved.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportStatus {
+    /// Points at an indexed file.
+    Resolved,
+    /// Looks like repo code but matches no indexed file.
+    Missing,
+    /// Standard library or third-party.
+    External,
+}
+
+/// One import statement of the file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ImportReport {
+    pub module: String,
+    pub names: Vec<String>,
+    pub line: usize,
+    pub status: ImportStatus,
+    /// Imported file, relative to the repo root.
+    pub target: Option<String>,
+}
+
+/// Calls from this file into one other file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CallTarget {
+    pub file_path: String,
+    /// Call sites in total.
+    pub calls: u32,
+    /// Called symbols and their call sites, most called first.
+    pub symbols: Vec<(String, u32)>,
+}
+
+/// A symbol defined in this file and its uses from other files.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ExternalUse {
+    /// Qualified where the symbol is a member (`Class.method`).
+    pub symbol: String,
+    pub kind: NodeKind,
+    pub line: usize,
+    /// Call sites, imports and subclasses across other files.
+    pub uses: u32,
+    /// Distinct elements using it.
+    pub users: usize,
+    pub files: usize,
+}
+
+/// Problems specific to the file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AnalysisDiagnostics {
+    /// Lines where the parser hit a syntax error.
+    pub parse_errors: Section<usize>,
+    /// Imports that look repo-internal but match no indexed file.
+    pub missing_imports: Section<String>,
+    /// Called names with no indexed definition (builtins, externals, typos).
+    pub unresolved_calls: Section<String>,
+    pub in_import_cycle: bool,
+}
+
+impl RepositoryGraph {
+    /// Analyse one indexed file, capped by `limits`.
+    ///
+    /// `file_path` is resolved with [`resolve_file`](Self::resolve_file).
+    pub fn analyze_file(
+        &self,
+        file_path: &str,
+        limits: &AnalysisLimits,
+    ) -> Result<FileAnalysis, FileLookupError> {
+        let key = self.resolve_file(file_path)?;
+        let outline = self.file_outline(&key)?;
+        let diagnostics = self.file_diagnostics(&key).unwrap_or_default();
+        let indices = self
+            .file_to_nodes
+            .get(&key)
+            .map(|nodes| nodes.clone())
+            .unwrap_or_default();
+        let file_elem = indices
+            .iter()
+            .find(|&&idx| self.graph[idx].kind == NodeKind::File)
+            .and_then(|&idx| self.element_arena.get(&self.graph[idx].id))
+            .map(|elem| elem.clone());
+
+        let mut metrics = FileMetrics::default();
+        let mut language = String::new();
+        let mut imports = Vec::new();
+        let mut parse_errors = Vec::new();
+        if let Some(elem) = &file_elem {
+            metrics.lines = elem.code.lines().count();
+            metrics.bytes = elem.code.len();
+            language = elem.language.clone();
+            if let Some(lang) = SupportedLanguage::from_extension(&key)
+                && let Some(tree) = with_thread_parser(|parser| parser.parse(&elem.code, lang))
+            {
+                parse_errors = error_lines(tree.root_node());
+                imports = extract_imports(&tree, &elem.code, lang)
+                    .into_iter()
+                    .map(|import| {
+                        let target = self.import_target(&import, &key);
+                        let status = if target.is_some() {
+                            ImportStatus::Resolved
+                        } else if diagnostics
+                            .missing_imports
+                            .contains(&import.written_module())
+                        {
+                            ImportStatus::Missing
+                        } else {
+                            ImportStatus::External
+                        };
+                        ImportReport {
+                            line: import.start_line,
+                            target: target.map(|path| self.display_path(&path)),
+                            status,
+                            module: import.written_module(),
+                            names: import.names,
+                        }
+                    })
+                    .collect();
+            }
+        }
+
+        // One pass over the file's nodes and their incident edges.
+        let mut calls_out: BTreeMap<String, BTreeMap<String, u32>> = BTreeMap::new();
+        let mut used_elsewhere = Vec::new();
+        let mut dependents = Vec::new();
+        for &idx in &indices {
+            let node = &self.graph[idx];
+            match node.kind {
+                NodeKind::File => {
+                    for edge in self.graph.edges_directed(idx, Direction::Incoming) {
+                        let source = &self.graph[edge.source()];
+                        if edge.weight().kind == EdgeKind::Imports && source.file_path != key {
+                            dependents.push(self.display_path(&source.file_path));
+                        }
+                    }
+                    continue;
+                }
+                NodeKind::Class => metrics.classes += 1,
+                NodeKind::Function | NodeKind::Method => {
+                    if node.kind == NodeKind::Function {
+                        metrics.functions += 1;
+                    } else {
+                        metrics.methods += 1;
+                    }
+                    let length = node.end_line.saturating_sub(node.start_line) + 1;
+                    if metrics
+                        .longest_function
+                        .as_ref()
+                        .is_none_or(|(_, longest)| length > *longest)
+                    {
+                        metrics.longest_function = Some((self.display_name(node), length));
+                    }
+                }
+                _ => {}
+            }
+
+            for edge in self.graph.edges_directed(idx, Direction::Outgoing) {
+                let target = &self.graph[edge.target()];
+                if edge.weight().kind == EdgeKind::Calls && target.file_path != key {
+                    *calls_out
+                        .entry(self.display_path(&target.file_path))
+                        .or_default()
+                        .entry(self.display_name(target))
+                        .or_default() += edge.weight().count;
+                }
+            }
+
+            let mut uses = 0;
+            let mut users = HashSet::new();
+            let mut files = HashSet::new();
+            for edge in self.graph.edges_directed(idx, Direction::Incoming) {
+                let source = &self.graph[edge.source()];
+                if USE_EDGES.contains(&edge.weight().kind) && source.file_path != key {
+                    uses += edge.weight().count;
+                    users.insert(edge.source());
+                    files.insert(source.file_path.as_str());
+                }
+            }
+            if uses > 0 {
+                used_elsewhere.push(ExternalUse {
+                    symbol: self.display_name(node),
+                    kind: node.kind,
+                    line: node.start_line,
+                    uses,
+                    users: users.len(),
+                    files: files.len(),
+                });
+            }
+        }
+
+        let mut calls_out: Vec<CallTarget> = calls_out
+            .into_iter()
+            .map(|(file_path, symbols)| {
+                let mut symbols: Vec<(String, u32)> = symbols.into_iter().collect();
+                symbols.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+                CallTarget {
+                    file_path,
+                    calls: symbols.iter().map(|(_, count)| count).sum(),
+                    symbols,
+                }
+            })
+            .collect();
+        calls_out.sort_by(|a, b| {
+            b.calls
+                .cmp(&a.calls)
+                .then_with(|| a.file_path.cmp(&b.file_path))
+        });
+        used_elsewhere.sort_by(|a, b| {
+            (b.uses, b.files)
+                .cmp(&(a.uses, a.files))
+                .then_with(|| a.symbol.cmp(&b.symbol))
+        });
+        dependents.sort();
+        dependents.dedup();
+
+        let mut analysis = FileAnalysis {
+            file_path: self.display_path(&key),
+            language,
+            metrics,
+            outline: Section::new(outline),
+            imports: Section::new(imports),
+            calls_out: Section::new(calls_out),
+            used_elsewhere: Section::new(used_elsewhere),
+            dependents: Section::new(dependents),
+            diagnostics: AnalysisDiagnostics {
+                parse_errors: Section::new(parse_errors),
+                missing_imports: Section::new(diagnostics.missing_imports.into_iter().collect()),
+                unresolved_calls: Section::new(diagnostics.unresolved_calls.into_iter().collect()),
+                in_import_cycle: diagnostics.in_import_cycle,
+            },
+        };
+        analysis.fit(limits);
+        Ok(analysis)
+    }
+
+    /// The indexed file an import points at, resolved the way import edges
+    /// are built.
+    fn import_target(&self, import: &ImportInfo, file_path: &str) -> Option<String> {
+        ModuleResolver::new(&self.global_index)
+            .resolve_import(import, file_path)
+            .filter(|path| self.file_to_nodes.contains_key(path))
+            .or_else(|| {
+                self.resolve_import_target_heuristic(import)
+                    .map(|idx| self.graph[idx].file_path.clone())
+            })
+            .filter(|path| path != file_path)
+    }
+
+    /// `file_path` relative to the repo root, when it is indexed.
+    fn display_path(&self, file_path: &str) -> String {
+        self.file_to_nodes
+            .get(file_path)
+            .and_then(|nodes| {
+                nodes.iter().find_map(|&idx| {
+                    self.element_arena
+                        .get(&self.graph[idx].id)
+                        .map(|elem| elem.relative_path.clone())
+                        .filter(|relative| !relative.is_empty())
+                })
+            })
+            .unwrap_or_else(|| file_path.to_string())
+    }
+
+    fn display_name(&self, node: &GraphNode) -> String {
+        self.qualified_name(node)
+            .unwrap_or_else(|| node.name.to_string())
+    }
+}
+
+impl FileAnalysis {
+    /// Cap every section at `limits.section_limit`, then halve the caps
+    /// until the serialized report fits `limits.max_bytes`.
+    fn fit(&mut self, limits: &AnalysisLimits) {
+        let mut limit = limits.section_limit;
+        loop {
+            self.truncate(limit);
+            let size = serde_json::to_string(self).map_or(0, |json| json.len());
+            if size <= limits.max_bytes || limit <= 1 {
+                break;
+            }
+            limit /= 2;
+        }
+    }
+
+    fn truncate(&mut self, limit: usize) {
+        self.outline.truncate(limit);
+        for node in &mut self.outline.items {
+            node.children.truncate(limit);
+        }
+        self.imports.truncate(limit);
+        self.calls_out.truncate(limit);
+        for target in &mut self.calls_out.items {
+            target.symbols.truncate(limit);
+        }
+        self.used_elsewhere.truncate(limit);
+        self.dependents.truncate(limit);
+        self.diagnostics.parse_errors.truncate(limit);
+        self.diagnostics.missing_imports.truncate(limit);
+        self.diagnostics.unresolved_calls.truncate(limit);
+    }
+
+    /// Plain-text rendering for the terminal.
+    pub fn render(&self) -> String {
+        let mut out = format!(
+            "{} ({}): {} lines, {} bytes, {} classes, {} functions, {} methods\n",
+            self.file_path,
+            self.language,
+            self.metrics.lines,
+            self.metrics.bytes,
+            self.metrics.classes,
+            self.metrics.functions,
+            self.metrics.methods
+        );
+        if let Some((name, lines)) = &self.metrics.longest_function {
+            out.push_str(&format!("Longest function: {name} ({lines} lines)\n"));
+        }
+
+        out.push_str(&heading("Outline", &self.outline));
+        for node in &self.outline.items {
+            render_outline(&mut out, node, 1);
+        }
+
+        out.push_str(&heading("Imports", &self.imports));
+        for import in &self.imports.items {
+            let names = if import.names.is_empty() {
+                String::new()
+            } else {
+                format!(" ({})", import.names.join(", "))
+            };
+            let status = match (&import.target, import.status) {
+                (Some(target), _) => format!("-> {target}"),
+                (None, ImportStatus::Missing) => "MISSING".to_string(),
+                (None, _) => "external".to_string(),
+            };
+            out.push_str(&format!(
+                "  line {}: {}{names} {status}\n",
+                import.line, import.module
+            ));
+        }
+
+        out.push_str(&heading("Calls into other files", &self.calls_out));
+        for target in &self.calls_out.items {
+            let symbols: Vec<String> = target
+                .symbols
+                .iter()
+                .map(|(name, count)| format!("{name} x{count}"))
+                .collect();
+            out.push_str(&format!(
+                "  {} ({} calls): {}\n",
+                target.file_path,
+                target.calls,
+                symbols.join(", ")
+            ));
+        }
+
+        out.push_str(&heading("Used elsewhere", &self.used_elsewhere));
+        for used in &self.used_elsewhere.items {
+            out.push_str(&format!(
+                "  {} (line {}): {} uses by {} elements in {} files\n",
+                used.symbol, used.line, used.uses, used.users, used.files
+            ));
+        }
+
+        out.push_str(&heading("Imported by", &self.dependents));
+        for file in &self.dependents.items {
+            out.push_str(&format!("  {file}\n"));
+        }
+
+        let diagnostics = &self.diagnostics;
+        out.push_str("Diagnostics:\n");
+        if !diagnostics.parse_errors.items.is_empty() {
+            let lines: Vec<String> = diagnostics
+                .parse_errors
+                .items
+                .iter()
+                .map(usize::to_string)
+                .collect();
+            out.push_str(&format!("  syntax errors on lines {}\n", lines.join(", ")));
+        }
+        if !diagnostics.missing_imports.items.is_empty() {
+            out.push_str(&format!(
+                "  missing imports: {}\n",
+                diagnostics.missing_imports.items.join(", ")
+            ));
+        }
+        if !diagnostics.unresolved_calls.items.is_empty() {
+            out.push_str(&format!(
+                "  unresolved calls: {}\n",
+                diagnostics.unresolved_calls.items.join(", ")
+            ));
+        }
+        if diagnostics.in_import_cycle {
+            out.push_str("  part of an import cycle\n");
+        }
+        out
+    }
+}
+
+fn heading<T>(title: &str, section: &Section<T>) -> String {
+    match section.omitted() {
+        0 => format!("{title} ({}):\n", section.total),
+        omitted => format!(
+            "{title} ({}, {} shown, {omitted} omitted):\n",
+            section.total,
+            section.items.len()
+        ),
+    }
+}
+
+fn render_outline(out: &mut String, node: &OutlineNode, depth: usize) {
+    let label = node.signature.as_deref().unwrap_or(&node.name);
+    out.push_str(&format!(
+        "{}{label} [{}-{}]\n",
+        "  ".repeat(depth),
+        node.start_line,
+        node.end_line
+    ));
+    for child in &node.children {
+        render_outline(out, child, depth + 1);
+    }
+}
+
+/// 1-based lines of the syntax errors under `root`, sorted.
+fn error_lines(root: tree_sitter::Node<'_>) -> Vec<usize> {
+    let mut lines = Vec::new();
+    let mut stack = vec![root];
+    while let Some(node) = stack.pop() {
+        if !node.has_error() {
+            continue;
+        }
+        if node.is_error() || node.is_missing() {
+            lines.push(node.start_position().row + 1);
+            continue;
+        }
+        let mut cursor = node.walk();
+        stack.extend(node.children(&mut cursor));
+    }
+    lines.sort_unstable();
+    lines.dedup();
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indexer::walk_and_index;
+
+    #[test]
+    fn test_analyze_file_reports_every_section() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        std::fs::create_dir_all(root.join("pkg")).unwrap();
+        std::fs::write(
+            root.join("pkg/util.py"),
+            "def helper():\n    return 1\n\ndef other():\n    return 2\n",
+        )
+        .unwrap();
+        std::fs::write(
+            root.join("pkg/service.py"),
+            "import os\nfrom pkg.util import helper\nfrom pkg.helpers import missing\n\n\
+             class Service:\n    def run(self):\n        return helper() + helper() + len([])\n\n\
+             def build():\n    return Service()\n",
+        )
+        .unwrap();
+        std::fs::write(
+            root.join("app.py"),
+            "from pkg.service import build\n\ndef main():\n    return build()\n",
+        )
+        .unwrap();
+        let root = root.to_string_lossy().to_string();
+        let mut graph = RepositoryGraph::new();
+        graph.build_from_elements(&walk_and_index(&root, &Default::default()), &root);
+
+        let analysis = graph
+            .analyze_file("pkg/service.py", &AnalysisLimits::default())
+            .unwrap();
+        assert_eq!(analysis.file_path, "pkg/service.py");
+        assert_eq!(analysis.language, "python");
+
+        let outline: Vec<(&str, Vec<&str>)> = analysis
+            .outline
+            .items
+            .iter()
+            .map(|node| {
+                let children = node.children.iter().map(|c| c.name.as_str()).collect();
+                (node.name.as_str(), children)
+            })
+            .collect();
+        assert_eq!(outline, vec![("Service", vec!["run"]), ("build", vec![])]);
+
+        let imports: Vec<(&str, ImportStatus, Option<&str>)> = analysis
+            .imports
+            .items
+            .iter()
+            .map(|i| (i.module.as_str(), i.status, i.target.as_deref()))
+            .collect();
+        assert_eq!(
+            imports,
+            vec![
+                ("os", ImportStatus::External, None),
+                ("pkg.util", ImportStatus::Resolved, Some("pkg/util.py")),
+                ("pkg.helpers", ImportStatus::Missing, None),
+            ]
+        );
+
+        assert_eq!(
+            analysis.calls_out.items,
+            vec![CallTarget {
+                file_path: "pkg/util.py".to_string(),
+                calls: 2,
+                symbols: vec![("helper".to_string(), 2)],
+            }]
+        );
+
+        let used: Vec<(&str, usize)> = analysis
+            .used_elsewhere
+            .items
+            .iter()
+            .map(|u| (u.symbol.as_str(), u.files))
+            .collect();
+        assert_eq!(used, vec![("build", 1)]);
+        assert_eq!(analysis.dependents.items, vec!["app.py"]);
+
+        let diagnostics = &analysis.diagnostics;
+        assert!(diagnostics.parse_errors.items.is_empty());
+        assert_eq!(diagnostics.missing_imports.items, vec!["pkg.helpers"]);
+        assert!(
+            diagnostics
+                .unresolved_calls
+                .items
+                .contains(&"len".to_string())
+        );
+        assert!(!diagnostics.in_import_cycle);
+
+        assert_eq!(analysis.metrics.lines, 10);
+        assert_eq!(
+            (
+                analysis.metrics.classes,
+                analysis.metrics.functions,
+                analysis.metrics.methods
+            ),
+            (1, 1, 1)
+        );
+
+        let text = analysis.render();
+        assert!(
+            text.contains("line 3: pkg.helpers (missing) MISSING"),
+            "{text}"
+        );
+        assert!(text.contains("pkg/util.py (2 calls): helper x2"), "{text}");
+
+        // A tight budget cuts sections down but keeps their totals.
+        let small = graph
+            .analyze_file(
+                "service.py",
+                &AnalysisLimits {
+                    section_limit: 1,
+                    max_bytes: 0,
+                },
+            )
+            .unwrap();
+        assert_eq!(small.imports.items.len(), 1);
+        assert_eq!(small.imports.total, 3);
+        assert_eq!(small.outline.omitted(), 1);
+    }
+}
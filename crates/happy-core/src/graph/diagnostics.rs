@@ -0,0 +1,414 @@
+use std::collections::{BTreeMap, BTreeSet, HashSet, VecDeque};
+use std::io;
+use std::path::Path;
+
+use petgraph::Direction;
+use petgraph::visit::EdgeRef;
+use serde::Deserialize;
+
+use super::RepositoryGraph;
+use super::digest::ResultBudget;
+use super::types::{EdgeKind, NodeKind};
+use crate::agent_toml::AgentToml;
+use crate::global_index::module_resolver::ModuleResolver;
+use crate::grounding::GroundingMode;
+use crate::indexer::ElementType;
+use crate::parser::calls::extract_calls;
+use crate::parser::imports::{ImportInfo, extract_imports};
+use crate::parser::languages::SupportedLanguage;
+use crate::parser::with_thread_parser;
+
+/// Findings listed in one verification report; the rest are counted.
+pub const MAX_EDIT_FINDINGS: usize = 12;
+
+/// Direct dependents checked per edited file.
+const MAX_DEPENDENTS_PER_FILE: usize = 50;
+
+/// The `[agent]` table of `.happy/agent.toml`.
+///
+/// ```toml
+/// [agent]
+/// verify_edits = true
+/// grounding = "warn"   # or "enforce", "off" (default)
+/// rlm_timeout_secs = 600
+///
+/// [agent.results]   # see ResultBudget
+/// max_results = 50
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct VerifyConfig {
+    /// Re-index files after the agent edits them and report structural
+    /// regressions before its next step.
+    pub verify_edits: bool,
+    /// How to treat final answers that claim code relationships no graph
+    /// query checked.
+    pub grounding: GroundingMode,
+    /// Seconds an `rlm_analyze` run may take before the orchestrator is
+    /// stopped and the partial analysis abandoned.
+    pub rlm_timeout_secs: u64,
+    /// When long node lists returned to the model are digested.
+    pub results: ResultBudget,
+}
+
+/// Default for [`VerifyConfig::rlm_timeout_secs`].
+pub const DEFAULT_RLM_TIMEOUT_SECS: u64 = 600;
+
+impl Default for VerifyConfig {
+    fn default() -> Self {
+        Self {
+            verify_edits: true,
+            grounding: GroundingMode::Off,
+            rlm_timeout_secs: DEFAULT_RLM_TIMEOUT_SECS,
+            results: ResultBudget::default(),
+        }
+    }
+}
+
+impl VerifyConfig {
+    /// Load `[agent]` from `<repo_root>/.happy/agent.toml`.
+    ///
+    /// Returns the default (verification on) when the file or table is
+    /// missing.
+    pub fn load(repo_root: &Path) -> io::Result<Self> {
+        Ok(AgentToml::load(repo_root)?
+            .section("agent")?
+            .unwrap_or_default())
+    }
+}
+
+/// Structural health of one indexed file.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FileDiagnostics {
+    /// Imports that look repo-internal but resolve to no indexed file.
+    pub missing_imports: BTreeSet<String>,
+    /// Called names with at least one indexed definition.
+    pub resolved_calls: BTreeSet<String>,
+    /// Called names with no indexed definition (builtins, externals, typos).
+    pub unresolved_calls: BTreeSet<String>,
+    /// The file can reach itself over import edges.
+    pub in_import_cycle: bool,
+}
+
+/// Diagnostics of edited files and their direct dependents, taken before the
+/// edit so they can be compared with the re-indexed state.
+#[derive(Debug, Clone, Default)]
+pub struct EditBaseline {
+    /// Files the agent wrote.
+    touched: BTreeSet<String>,
+    /// Snapshot per checked file; `None` for files not indexed yet.
+    before: BTreeMap<String, Option<FileDiagnostics>>,
+}
+
+impl EditBaseline {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.touched.is_empty()
+    }
+
+    /// Edited file paths, sorted.
+    pub fn touched(&self) -> impl Iterator<Item = &str> {
+        self.touched.iter().map(String::as_str)
+    }
+}
+
+impl RepositoryGraph {
+    /// Compute the diagnostics of an indexed file, or `None` when the file is
+    /// not in the graph.
+    pub fn file_diagnostics(&self, file_path: &str) -> Option<FileDiagnostics> {
+        let indices = self.file_to_nodes.get(file_path)?.clone();
+        let mut diagnostics = FileDiagnostics::default();
+        let lang = SupportedLanguage::from_extension(file_path)?;
+
+        for idx in indices {
+            let node = &self.graph[idx];
+            let Some(elem) = self.element_arena.get(&node.id).map(|e| e.clone()) else {
+                continue;
+            };
+            let Some(tree) = with_thread_parser(|parser| parser.parse(&elem.code, lang)) else {
+                continue;
+            };
+            match elem.element_type {
+                ElementType::File => {
+                    for import in extract_imports(&tree, &elem.code, lang) {
+                        if !self.import_resolves(&import, file_path) && self.looks_internal(&import)
+                        {
+                            diagnostics.missing_imports.insert(import.written_module());
+                        }
+                    }
+                }
+                ElementType::Function | ElementType::Method => {
+                    for call in extract_calls(&tree, &elem.code, lang) {
+                        if self.name_to_nodes.contains_key(&call.call_name) {
+                            diagnostics.resolved_calls.insert(call.call_name);
+                        } else {
+                            diagnostics.unresolved_calls.insert(call.call_name);
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        diagnostics.in_import_cycle = self.in_import_cycle(file_path);
+        Some(diagnostics)
+    }
+
+    /// Snapshot `files` and their direct dependents into `baseline` before
+    /// they are edited. Files already in the baseline keep their first
+    /// snapshot, so several edits in one step compare against the original.
+    pub fn record_edit_baseline(&self, baseline: &mut EditBaseline, files: &[String]) {
+        for file in files {
+            baseline.touched.insert(file.clone());
+            let dependents: Vec<String> = self
+                .get_dependents(file)
+                .unwrap_or_default()
+                .into_iter()
+                .map(|node| node.file_path.clone())
+                .take(MAX_DEPENDENTS_PER_FILE)
+                .collect();
+            for path in std::iter::once(file.clone()).chain(dependents) {
+                if !baseline.before.contains_key(&path) {
+                    let snapshot = self.file_diagnostics(&path);
+                    baseline.before.insert(path, snapshot);
+                }
+            }
+        }
+    }
+
+    /// Compare the current (re-indexed) state with `baseline` and describe
+    /// what got worse, one finding per line. Empty when nothing regressed.
+    pub fn edit_regressions(&self, baseline: &EditBaseline, repo_root: &str) -> Vec<String> {
+        let mut findings = Vec::new();
+        for (path, before) in &baseline.before {
+            let Some(after) = self.file_diagnostics(path) else {
+                continue;
+            };
+            let before = before.clone().unwrap_or_default();
+            let display = Path::new(path)
+                .strip_prefix(repo_root)
+                .map(|p| crate::utils::normalize_path(&p.to_string_lossy()))
+                .unwrap_or_else(|_| path.clone());
+
+            for module in after.missing_imports.difference(&before.missing_imports) {
+                findings.push(format!("{display} now imports missing module '{module}'"));
+            }
+            let broken: Vec<&String> = after
+                .unresolved_calls
+                .iter()
+                .filter(|name| before.resolved_calls.contains(*name))
+                .collect();
+            if !broken.is_empty() {
+                let names: Vec<&str> = broken.iter().map(|name| name.as_str()).collect();
+                findings.push(format!(
+                    "{} call{} in {display} no longer resolve{}: {}",
+                    broken.len(),
+                    if broken.len() == 1 { "" } else { "s" },
+                    if broken.len() == 1 { "s" } else { "" },
+                    names.join(", ")
+                ));
+            }
+            if after.in_import_cycle && !before.in_import_cycle {
+                findings.push(format!("{display} is now part of an import cycle"));
+            }
+        }
+        findings
+    }
+
+    fn import_resolves(&self, import: &ImportInfo, file_path: &str) -> bool {
+        ModuleResolver::new(&self.global_index)
+            .resolve_import(import, file_path)
+            .is_some()
+            || self.resolve_import_target_heuristic(import).is_some()
+    }
+
+    /// Whether an unresolved import names repo code rather than a stdlib or
+    /// third-party module: relative imports, imports under an indexed
+    /// top-level package, and near-misses of an indexed module name.
+    fn looks_internal(&self, import: &ImportInfo) -> bool {
+        if import.level > 0 || import.module.starts_with('.') {
+            return true;
+        }
+        let segments: Vec<&str> = import
+            .module
+            .split(['.', '/', ':'])
+            .filter(|s| !s.is_empty())
+            .collect();
+        let (Some(first), Some(last)) = (segments.first(), segments.last()) else {
+            return false;
+        };
+        self.global_index.module_map.iter().any(|entry| {
+            let module = entry.key();
+            let module_last = module.rsplit('.').next().unwrap_or(module);
+            (segments.len() > 1 && module.split('.').next() == Some(*first))
+                || (last.len() >= 4 && edit_distance(last, module_last) == 1)
+        })
+    }
+
+    /// Whether `file_path` can reach itself over import edges.
+    fn in_import_cycle(&self, file_path: &str) -> bool {
+        let mut queue: VecDeque<String> = VecDeque::from([file_path.to_string()]);
+        let mut seen: HashSet<String> = HashSet::new();
+        while let Some(current) = queue.pop_front() {
+            let Some(indices) = self.file_to_nodes.get(&current).map(|v| v.clone()) else {
+                continue;
+            };
+            for idx in indices {
+                if self.graph[idx].kind != NodeKind::File {
+                    continue;
+                }
+                for edge in self.graph.edges_directed(idx, Direction::Outgoing) {
+                    if edge.weight().kind != EdgeKind::Imports {
+                        continue;
+                    }
+                    let target = &self.graph[edge.target()].file_path;
+                    if target == file_path {
+                        return true;
+                    }
+                    if seen.insert(target.clone()) {
+                        queue.push_back(target.clone());
+                    }
+                }
+            }
+        }
+        false
+    }
+}
+
+/// Render findings as the message shown to the agent, or `None` when there
+/// is nothing to report.
+pub fn format_edit_findings(findings: &[String]) -> Option<String> {
+    if findings.is_empty() {
+        return None;
+    }
+    let mut out = String::from("Code graph check after your edits found structural problems:\n");
+    for finding in findings.iter().take(MAX_EDIT_FINDINGS) {
+        out.push_str(&format!("- {finding}\n"));
+    }
+    if findings.len() > MAX_EDIT_FINDINGS {
+        out.push_str(&format!(
+            "- ... and {} more\n",
+            findings.len() - MAX_EDIT_FINDINGS
+        ));
+    }
+    out.push_str("Fix these before continuing if they were not intended.");
+    Some(out)
+}
+
+/// Levenshtein distance between two short strings.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.iter().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = prev[j] + usize::from(ca != cb);
+            current[j + 1] = substitution.min(prev[j + 1] + 1).min(current[j] + 1);
+        }
+        prev = current;
+    }
+    prev[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indexer::{index_single_file, walk_and_index};
+
+    fn build(dir: &Path) -> (RepositoryGraph, String) {
+        let root = dir.canonicalize().unwrap().to_string_lossy().to_string();
+        let mut graph = RepositoryGraph::new();
+        graph.build_from_elements(&walk_and_index(&root, &Default::default()), &root);
+        (graph, root)
+    }
+
+    fn edit(graph: &mut RepositoryGraph, root: &str, relative: &str, code: &str) {
+        let path = format!("{root}/{relative}");
+        std::fs::write(&path, code).unwrap();
+        let elements = index_single_file(&path, root, &Default::default()).unwrap();
+        graph.update_file(&path, &elements, root);
+    }
+
+    #[test]
+    fn test_edit_regressions_report_missing_import_and_broken_calls() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("utils.py"),
+            "def helper():\n    return 1\n\ndef other():\n    return 2\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.path().join("a.py"),
+            "import os\nimport utils\n\ndef run():\n    return utils.helper()\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.path().join("b.py"),
+            "from utils import helper\n\ndef use():\n    return helper() + len([])\n",
+        )
+        .unwrap();
+        let (mut graph, root) = build(dir.path());
+
+        let touched = vec![format!("{root}/a.py"), format!("{root}/utils.py")];
+        let mut baseline = EditBaseline::new();
+        graph.record_edit_baseline(&mut baseline, &touched);
+        assert_eq!(baseline.touched().count(), 2);
+
+        // Unchanged state: nothing to report.
+        assert!(graph.edit_regressions(&baseline, &root).is_empty());
+
+        edit(
+            &mut graph,
+            &root,
+            "a.py",
+            "import os\nimport utilz\n\ndef run():\n    return utilz.helper()\n",
+        );
+        edit(
+            &mut graph,
+            &root,
+            "utils.py",
+            "def other():\n    return 2\n",
+        );
+
+        let findings = graph.edit_regressions(&baseline, &root);
+        assert!(
+            findings.contains(&"a.py now imports missing module 'utilz'".to_string()),
+            "{findings:?}"
+        );
+        assert!(
+            findings.contains(&"1 call in b.py no longer resolves: helper".to_string()),
+            "{findings:?}"
+        );
+        assert!(findings.iter().all(|f| !f.contains("'os'")), "{findings:?}");
+
+        let message = format_edit_findings(&findings).unwrap();
+        assert!(message.contains("utilz"));
+        assert!(format_edit_findings(&[]).is_none());
+    }
+
+    #[test]
+    fn test_load_verify_config() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(VerifyConfig::load(dir.path()).unwrap().verify_edits);
+        std::fs::create_dir_all(dir.path().join(".happy")).unwrap();
+        std::fs::write(
+            dir.path().join(".happy/agent.toml"),
+            "[agent]\nverify_edits = false\ngrounding = \"enforce\"\nrlm_timeout_secs = 30\n\n[agent.results]\nmax_results = 20\n",
+        )
+        .unwrap();
+        let config = VerifyConfig::load(dir.path()).unwrap();
+        assert!(!config.verify_edits);
+        assert_eq!(config.grounding, GroundingMode::Enforce);
+        assert_eq!(config.rlm_timeout_secs, 30);
+        assert_eq!(
+            config.results,
+            ResultBudget {
+                max_results: 20,
+                ..ResultBudget::default()
+            }
+        );
+    }
+}
@@ -0,0 +1,229 @@
+//! Digests of node lists too long to hand to the model whole.
+//!
+//! A digest keeps the total, the files and directories the results fall in
+//! with their counts, and a sample of entries: the most confident entry of
+//! each top file first, then the remaining entries in confidence order.
+//! Entries of equal confidence keep their input order, so callers list
+//! closer results first.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+/// When and how node lists are digested (`[agent.results]` in
+/// `.happy/agent.toml`).
+///
+/// ```toml
+/// [agent.results]
+/// max_results = 50
+/// sample_size = 10
+/// top_files = 15
+/// top_directories = 10
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct ResultBudget {
+    /// Longest list returned in full; longer ones are digested.
+    pub max_results: usize,
+    /// Full entries kept in a digest.
+    pub sample_size: usize,
+    /// Files listed with their counts; the rest are counted together.
+    pub top_files: usize,
+    /// Directories listed with their counts.
+    pub top_directories: usize,
+}
+
+impl Default for ResultBudget {
+    fn default() -> Self {
+        Self {
+            max_results: 50,
+            sample_size: 10,
+            top_files: 15,
+            top_directories: 10,
+        }
+    }
+}
+
+/// Results in one file or directory.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PathCount {
+    pub path: String,
+    pub count: usize,
+}
+
+/// Summary of a node list over the budget.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResultDigest {
+    pub total: usize,
+    /// Files with the most results, most first.
+    pub by_file: Vec<PathCount>,
+    /// Files left out of `by_file`.
+    pub other_files: usize,
+    /// Directories with the most results, most first.
+    pub by_directory: Vec<PathCount>,
+    /// Input indices of the sampled entries, in sample order.
+    pub sample: Vec<usize>,
+}
+
+impl ResultBudget {
+    /// Digest `total` entries when they are over the budget.
+    ///
+    /// `file_of` gives an entry's file relative to the repo root and
+    /// `confidence_of` its confidence, lower ranking first (0 for edges the
+    /// graph resolved, higher for guesses such as string references).
+    pub fn digest(
+        &self,
+        total: usize,
+        file_of: impl Fn(usize) -> String,
+        confidence_of: impl Fn(usize) -> u8,
+    ) -> Option<ResultDigest> {
+        if total <= self.max_results {
+            return None;
+        }
+        let files: Vec<String> = (0..total).map(file_of).collect();
+        let mut by_file = count_paths(files.iter().map(String::as_str));
+        let mut by_directory = count_paths(files.iter().map(|file| directory_of(file)));
+        let other_files = by_file.len().saturating_sub(self.top_files);
+        by_file.truncate(self.top_files);
+        by_directory.truncate(self.top_directories);
+
+        // Stable sort: entries of equal confidence stay in input order.
+        let mut ranked: Vec<usize> = (0..total).collect();
+        ranked.sort_by_key(|&idx| confidence_of(idx));
+        let mut sample = Vec::with_capacity(self.sample_size.min(total));
+        for file in &by_file {
+            if sample.len() == self.sample_size {
+                break;
+            }
+            if let Some(&idx) = ranked.iter().find(|&&idx| files[idx] == file.path) {
+                sample.push(idx);
+            }
+        }
+        for idx in ranked {
+            if sample.len() == self.sample_size {
+                break;
+            }
+            if !sample.contains(&idx) {
+                sample.push(idx);
+            }
+        }
+
+        Some(ResultDigest {
+            total,
+            by_file,
+            other_files,
+            by_directory,
+            sample,
+        })
+    }
+}
+
+/// Occurrences of each path, most first, then by path.
+fn count_paths<'a>(paths: impl Iterator<Item = &'a str>) -> Vec<PathCount> {
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for path in paths {
+        *counts.entry(path).or_default() += 1;
+    }
+    let mut counts: Vec<PathCount> = counts
+        .into_iter()
+        .map(|(path, count)| PathCount {
+            path: path.to_string(),
+            count,
+        })
+        .collect();
+    counts.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.path.cmp(&b.path)));
+    counts
+}
+
+fn directory_of(file: &str) -> &str {
+    match Path::new(file).parent().and_then(Path::to_str) {
+        Some("") | None => ".",
+        Some(dir) => dir,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn budget() -> ResultBudget {
+        ResultBudget {
+            max_results: 50,
+            sample_size: 4,
+            top_files: 2,
+            top_directories: 2,
+        }
+    }
+
+    #[test]
+    fn lists_within_budget_are_not_digested() {
+        let files = ["a.py"; 50];
+        assert_eq!(
+            budget().digest(files.len(), |i| files[i].to_string(), |_| 0),
+            None
+        );
+    }
+
+    #[test]
+    fn digest_counts_files_and_directories() {
+        let files: Vec<String> = (0..500)
+            .map(|i| match i % 10 {
+                0..=5 => "src/api/handlers.py".to_string(),
+                6..=8 => "src/api/routes.py".to_string(),
+                _ => format!("scripts/job_{i}.py"),
+            })
+            .collect();
+        let digest = budget()
+            .digest(files.len(), |i| files[i].clone(), |_| 0)
+            .unwrap();
+
+        assert_eq!(digest.total, 500);
+        assert_eq!(
+            digest.by_file,
+            vec![
+                PathCount {
+                    path: "src/api/handlers.py".to_string(),
+                    count: 300,
+                },
+                PathCount {
+                    path: "src/api/routes.py".to_string(),
+                    count: 150,
+                },
+            ]
+        );
+        assert_eq!(digest.other_files, 50);
+        assert_eq!(
+            digest.by_directory,
+            vec![
+                PathCount {
+                    path: "src/api".to_string(),
+                    count: 450,
+                },
+                PathCount {
+                    path: "scripts".to_string(),
+                    count: 50,
+                },
+            ]
+        );
+        // One entry of each top file, then the rest in input order.
+        assert_eq!(digest.sample, vec![0, 6, 1, 2]);
+    }
+
+    #[test]
+    fn sample_prefers_confident_entries() {
+        let files: Vec<String> = (0..100).map(|i| format!("m{}.py", i % 3)).collect();
+        // Only the last ten entries are confident.
+        let digest = budget()
+            .digest(files.len(), |i| files[i].clone(), |i| u8::from(i < 90))
+            .unwrap();
+
+        assert_eq!(digest.sample.len(), 4);
+        assert!(
+            digest.sample.iter().all(|&i| i >= 90),
+            "{:?}",
+            digest.sample
+        );
+    }
+}
@@ -0,0 +1,419 @@
+//! Export of the code graph for Graphviz, Gephi, and other tooling.
+//!
+//! Output is written to the caller's writer node by node, so exporting a large
+//! graph to a file does not build the whole document in memory. File paths are
+//! written relative to the repo root. When only some edge kinds are exported,
+//! nodes without such an edge are left out.
+
+use std::collections::{BTreeMap, HashSet};
+use std::io::{self, Write};
+use std::path::Path;
+use std::str::FromStr;
+
+use petgraph::stable_graph::{EdgeIndex, NodeIndex};
+use serde::Serialize;
+
+use super::RepositoryGraph;
+use super::types::{EdgeKind, GraphNode};
+
+/// Output format of [`RepositoryGraph::export`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// Graphviz, with one cluster per file.
+    Dot,
+    GraphMl,
+    /// `{"nodes": [...], "edges": [...]}`.
+    Json,
+}
+
+impl FromStr for ExportFormat {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "dot" | "graphviz" => Ok(Self::Dot),
+            "graphml" => Ok(Self::GraphMl),
+            "json" => Ok(Self::Json),
+            other => Err(format!(
+                "unknown export format '{other}', expected dot, graphml, or json"
+            )),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct JsonNode<'a> {
+    id: &'a str,
+    name: &'a str,
+    kind: String,
+    file: &'a str,
+    start_line: usize,
+    end_line: usize,
+}
+
+#[derive(Serialize)]
+struct JsonEdge<'a> {
+    source: &'a str,
+    target: &'a str,
+    kind: String,
+    count: u32,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    low_confidence: bool,
+}
+
+/// The nodes and edges selected for one export.
+struct Selection<'a> {
+    graph: &'a RepositoryGraph,
+    repo_root: &'a str,
+    nodes: Vec<NodeIndex>,
+    edges: Vec<EdgeIndex>,
+}
+
+impl RepositoryGraph {
+    /// Write the graph to `out` as `format`, keeping only edges of
+    /// `edge_kinds` (every edge when empty) and the nodes they connect.
+    pub fn export(
+        &self,
+        out: &mut impl Write,
+        format: ExportFormat,
+        edge_kinds: &[EdgeKind],
+        repo_root: &str,
+    ) -> io::Result<()> {
+        let selection = self.export_selection(edge_kinds, repo_root);
+        match format {
+            ExportFormat::Dot => selection.write_dot(out),
+            ExportFormat::GraphMl => selection.write_graphml(out),
+            ExportFormat::Json => selection.write_json(out),
+        }
+    }
+
+    fn export_selection<'a>(
+        &'a self,
+        edge_kinds: &[EdgeKind],
+        repo_root: &'a str,
+    ) -> Selection<'a> {
+        let edges: Vec<EdgeIndex> = self
+            .graph
+            .edge_indices()
+            .filter(|&edge| edge_kinds.is_empty() || edge_kinds.contains(&self.graph[edge].kind))
+            .collect();
+        let nodes = if edge_kinds.is_empty() {
+            self.graph.node_indices().collect()
+        } else {
+            let mut connected = HashSet::new();
+            for &edge in &edges {
+                if let Some((source, target)) = self.graph.edge_endpoints(edge) {
+                    connected.insert(source);
+                    connected.insert(target);
+                }
+            }
+            self.graph
+                .node_indices()
+                .filter(|idx| connected.contains(idx))
+                .collect()
+        };
+        Selection {
+            graph: self,
+            repo_root,
+            nodes,
+            edges,
+        }
+    }
+}
+
+impl Selection<'_> {
+    fn node(&self, idx: NodeIndex) -> &GraphNode {
+        &self.graph.graph[idx]
+    }
+
+    fn file_of<'n>(&self, node: &'n GraphNode) -> &'n str {
+        Path::new(&node.file_path)
+            .strip_prefix(self.repo_root)
+            .ok()
+            .and_then(Path::to_str)
+            .unwrap_or(&node.file_path)
+    }
+
+    fn endpoints(&self) -> impl Iterator<Item = (NodeIndex, NodeIndex, EdgeIndex)> + '_ {
+        self.edges.iter().filter_map(|&edge| {
+            let (source, target) = self.graph.graph.edge_endpoints(edge)?;
+            Some((source, target, edge))
+        })
+    }
+
+    fn write_dot(&self, out: &mut impl Write) -> io::Result<()> {
+        let mut by_file: BTreeMap<&str, Vec<NodeIndex>> = BTreeMap::new();
+        for &idx in &self.nodes {
+            by_file
+                .entry(self.file_of(self.node(idx)))
+                .or_default()
+                .push(idx);
+        }
+        writeln!(out, "digraph code_graph {{")?;
+        writeln!(out, "  rankdir=LR;")?;
+        writeln!(out, "  node [shape=box, fontsize=10];")?;
+        for (cluster, (file, nodes)) in by_file.iter().enumerate() {
+            writeln!(out, "  subgraph cluster_{cluster} {{")?;
+            writeln!(out, "    label=\"{}\";", dot_escape(file))?;
+            for &idx in nodes {
+                let node = self.node(idx);
+                writeln!(
+                    out,
+                    "    n{} [label=\"{}\", kind=\"{:?}\", tooltip=\"{}:{}-{}\"];",
+                    idx.index(),
+                    dot_escape(&node.name),
+                    node.kind,
+                    dot_escape(file),
+                    node.start_line,
+                    node.end_line
+                )?;
+            }
+            writeln!(out, "  }}")?;
+        }
+        for (source, target, edge) in self.endpoints() {
+            let edge = &self.graph.graph[edge];
+            writeln!(
+                out,
+                "  n{} -> n{} [label=\"{:?}\", weight={}];",
+                source.index(),
+                target.index(),
+                edge.kind,
+                edge.count
+            )?;
+        }
+        writeln!(out, "}}")
+    }
+
+    fn write_graphml(&self, out: &mut impl Write) -> io::Result<()> {
+        writeln!(out, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+        writeln!(
+            out,
+            r#"<graphml xmlns="http://graphml.graphdrawing.org/xmlns">"#
+        )?;
+        for (id, target, name, kind) in [
+            ("name", "node", "name", "string"),
+            ("kind", "node", "kind", "string"),
+            ("file", "node", "file", "string"),
+            ("start_line", "node", "start_line", "int"),
+            ("end_line", "node", "end_line", "int"),
+            ("edge_kind", "edge", "kind", "string"),
+            ("count", "edge", "count", "int"),
+        ] {
+            writeln!(
+                out,
+                r#"  <key id="{id}" for="{target}" attr.name="{name}" attr.type="{kind}"/>"#
+            )?;
+        }
+        writeln!(out, r#"  <graph id="code_graph" edgedefault="directed">"#)?;
+        for &idx in &self.nodes {
+            let node = self.node(idx);
+            writeln!(out, r#"    <node id="{}">"#, xml_escape(&node.id))?;
+            writeln!(
+                out,
+                r#"      <data key="name">{}</data>"#,
+                xml_escape(&node.name)
+            )?;
+            writeln!(out, r#"      <data key="kind">{:?}</data>"#, node.kind)?;
+            writeln!(
+                out,
+                r#"      <data key="file">{}</data>"#,
+                xml_escape(self.file_of(node))
+            )?;
+            writeln!(
+                out,
+                r#"      <data key="start_line">{}</data>"#,
+                node.start_line
+            )?;
+            writeln!(
+                out,
+                r#"      <data key="end_line">{}</data>"#,
+                node.end_line
+            )?;
+            writeln!(out, "    </node>")?;
+        }
+        for (source, target, edge) in self.endpoints() {
+            let edge = &self.graph.graph[edge];
+            writeln!(
+                out,
+                r#"    <edge source="{}" target="{}">"#,
+                xml_escape(&self.node(source).id),
+                xml_escape(&self.node(target).id)
+            )?;
+            writeln!(out, r#"      <data key="edge_kind">{:?}</data>"#, edge.kind)?;
+            writeln!(out, r#"      <data key="count">{}</data>"#, edge.count)?;
+            writeln!(out, "    </edge>")?;
+        }
+        writeln!(out, "  </graph>")?;
+        writeln!(out, "</graphml>")
+    }
+
+    fn write_json(&self, out: &mut impl Write) -> io::Result<()> {
+        write!(out, "{{\"nodes\":[")?;
+        for (i, &idx) in self.nodes.iter().enumerate() {
+            if i > 0 {
+                write!(out, ",")?;
+            }
+            let node = self.node(idx);
+            serde_json::to_writer(
+                &mut *out,
+                &JsonNode {
+                    id: &node.id,
+                    name: node.name.as_str(),
+                    kind: format!("{:?}", node.kind),
+                    file: self.file_of(node),
+                    start_line: node.start_line,
+                    end_line: node.end_line,
+                },
+            )?;
+        }
+        write!(out, "],\"edges\":[")?;
+        for (i, (source, target, edge)) in self.endpoints().enumerate() {
+            if i > 0 {
+                write!(out, ",")?;
+            }
+            let edge = &self.graph.graph[edge];
+            serde_json::to_writer(
+                &mut *out,
+                &JsonEdge {
+                    source: &self.node(source).id,
+                    target: &self.node(target).id,
+                    kind: format!("{:?}", edge.kind),
+                    count: edge.count,
+                    low_confidence: edge.low_confidence,
+                },
+            )?;
+        }
+        writeln!(out, "]}}")
+    }
+}
+
+fn dot_escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use serde_json::Value;
+
+    fn index(files: &[(&str, &str)]) -> (tempfile::TempDir, String, RepositoryGraph) {
+        let dir = tempfile::tempdir().unwrap();
+        for (name, code) in files {
+            std::fs::write(dir.path().join(name), code).unwrap();
+        }
+        let root = dir
+            .path()
+            .canonicalize()
+            .unwrap()
+            .to_string_lossy()
+            .to_string();
+        let mut repo = RepositoryGraph::new();
+        repo.build_from_elements(
+            &crate::indexer::walk_and_index(&root, &Default::default()),
+            &root,
+        );
+        (dir, root, repo)
+    }
+
+    fn export(
+        repo: &RepositoryGraph,
+        root: &str,
+        format: ExportFormat,
+        kinds: &[EdgeKind],
+    ) -> String {
+        let mut out = Vec::new();
+        repo.export(&mut out, format, kinds, root).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    const FILES: &[(&str, &str)] = &[
+        ("util.py", "def helper():\n    return 1\n"),
+        (
+            "app.py",
+            "import util\n\ndef main():\n    return util.helper()\n",
+        ),
+    ];
+
+    #[test]
+    fn json_export_filters_by_edge_kind() {
+        let (_dir, root, repo) = index(FILES);
+        let json: Value = serde_json::from_str(&export(
+            &repo,
+            &root,
+            ExportFormat::Json,
+            &[EdgeKind::Calls],
+        ))
+        .unwrap();
+
+        let names: Vec<&str> = json["nodes"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter_map(|node| node["name"].as_str())
+            .collect();
+        assert_eq!(names.len(), 2);
+        assert!(
+            names.contains(&"main") && names.contains(&"helper"),
+            "{names:?}"
+        );
+        let edges = json["edges"].as_array().unwrap();
+        assert_eq!(edges.len(), 1);
+        assert_eq!(edges[0]["kind"], "Calls");
+        let main = json["nodes"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|node| node["name"] == "main")
+            .unwrap();
+        assert_eq!(main["file"], "app.py");
+        assert_eq!(edges[0]["source"], main["id"]);
+    }
+
+    #[test]
+    fn dot_export_clusters_nodes_by_file() {
+        let (_dir, root, repo) = index(FILES);
+        let dot = export(&repo, &root, ExportFormat::Dot, &[]);
+
+        assert!(dot.starts_with("digraph code_graph {"), "{dot}");
+        assert_eq!(dot.matches("subgraph cluster_").count(), 2, "{dot}");
+        assert!(dot.contains("label=\"app.py\";"), "{dot}");
+        assert!(dot.contains("[label=\"Calls\""), "{dot}");
+        assert!(dot.contains("[label=\"Imports\""), "{dot}");
+        assert!(dot.trim_end().ends_with('}'));
+    }
+
+    #[test]
+    fn graphml_export_lists_nodes_and_edges() {
+        let (_dir, root, repo) = index(FILES);
+        let graphml = export(&repo, &root, ExportFormat::GraphMl, &[EdgeKind::Imports]);
+
+        assert!(
+            graphml.contains(r#"<data key="file">util.py</data>"#),
+            "{graphml}"
+        );
+        assert!(graphml.contains(r#"<data key="edge_kind">Imports</data>"#));
+        assert!(!graphml.contains(r#"<data key="edge_kind">Calls</data>"#));
+        assert_eq!(
+            graphml.matches("<node id=").count(),
+            repo.export_selection(&[EdgeKind::Imports], &root)
+                .nodes
+                .len()
+        );
+        assert!(graphml.trim_end().ends_with("</graphml>"));
+    }
+
+    #[test]
+    fn export_format_parses() {
+        assert_eq!("DOT".parse(), Ok(ExportFormat::Dot));
+        assert_eq!("graphml".parse(), Ok(ExportFormat::GraphMl));
+        assert!("svg".parse::<ExportFormat>().is_err());
+    }
+}
@@ -0,0 +1,76 @@
+use rayon::prelude::*;
+
+use super::extract_base_classes;
+use crate::indexer::{CONTENT_ONLY_KEY, CodeElement, ElementType};
+use crate::parser::calls::{CallInfo, extract_calls};
+use crate::parser::imports::{ImportInfo, extract_imports};
+use crate::parser::languages::SupportedLanguage;
+use crate::parser::strings::{StringLiteral, extract_string_literals};
+use crate::parser::with_thread_parser;
+
+/// What edge building reads from one element's source: the calls of a
+/// function or method, the imports of a file, the bases of a class, and
+/// string literals when string references are detected. Only the parts
+/// that apply to the element's type are filled in.
+#[derive(Debug, Default)]
+pub(crate) struct ElementFacts {
+    pub calls: Vec<CallInfo>,
+    pub imports: Vec<ImportInfo>,
+    pub bases: Vec<String>,
+    pub strings: Vec<StringLiteral>,
+}
+
+/// Parse each element once, in parallel, and extract its facts. The result
+/// lines up with `elements`, so edges applied from it in order are the
+/// same whatever the thread count.
+pub(crate) fn extract_facts(elements: &[CodeElement], string_refs: bool) -> Vec<ElementFacts> {
+    elements
+        .par_iter()
+        .map(|elem| element_facts(elem, string_refs))
+        .collect()
+}
+
+fn element_facts(elem: &CodeElement, string_refs: bool) -> ElementFacts {
+    let is_callable = matches!(
+        elem.element_type,
+        ElementType::Function | ElementType::Method
+    );
+    let is_file = elem.element_type == ElementType::File;
+    let is_class = matches!(
+        elem.element_type,
+        ElementType::Class | ElementType::Struct | ElementType::Interface
+    );
+    let wants_strings = string_refs && (is_file || is_callable);
+    if !(is_callable || is_file || is_class) || elem.metadata.contains_key(CONTENT_ONLY_KEY) {
+        return ElementFacts::default();
+    }
+    let Some(lang) = SupportedLanguage::from_extension(&elem.file_path) else {
+        return ElementFacts::default();
+    };
+    let Some(tree) = with_thread_parser(|parser| parser.parse(&elem.code, lang)) else {
+        return ElementFacts::default();
+    };
+
+    ElementFacts {
+        calls: if is_callable {
+            extract_calls(&tree, &elem.code, lang)
+        } else {
+            Vec::new()
+        },
+        imports: if is_file {
+            extract_imports(&tree, &elem.code, lang)
+        } else {
+            Vec::new()
+        },
+        bases: if is_class {
+            extract_base_classes(&tree, &elem.code, lang)
+        } else {
+            Vec::new()
+        },
+        strings: if wants_strings {
+            extract_string_literals(&tree, &elem.code)
+        } else {
+            Vec::new()
+        },
+    }
+}
@@ -0,0 +1,164 @@
+//! Limits for names defined many times over.
+//!
+//! Generated code can define the same name thousands of times (`descriptor`
+//! and `clone` in protobuf output). Resolving a call to such a name by
+//! scanning every definition makes graph builds quadratic, and the
+//! first-match fallback links every unresolved call to an arbitrary one.
+//! Definitions are therefore also indexed per directory and file, names
+//! with more than `max_call_candidates` definitions are resolved from those
+//! narrow slices, and names with more than `fallback_fanout_limit`
+//! definitions get no fallback edges at all.
+
+use std::collections::HashMap;
+
+use petgraph::stable_graph::NodeIndex;
+use serde::Serialize;
+
+use super::RepositoryGraph;
+
+/// Definitions of a name considered per call before it is treated as hot.
+pub const DEFAULT_MAX_CALL_CANDIDATES: usize = 50;
+
+/// Definitions of a name above which no first-match fallback edge is made.
+pub const DEFAULT_FALLBACK_FANOUT_LIMIT: usize = 200;
+
+/// Hot names listed in the indexing report.
+pub const HOT_NAMES_REPORTED: usize = 10;
+
+/// Definitions of one name, grouped by directory and then file.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct NameScopes {
+    dirs: HashMap<String, HashMap<String, Vec<NodeIndex>>>,
+}
+
+impl NameScopes {
+    pub(crate) fn insert(&mut self, file_path: &str, idx: NodeIndex) {
+        self.dirs
+            .entry(dir_of(file_path).to_string())
+            .or_default()
+            .entry(file_path.to_string())
+            .or_default()
+            .push(idx);
+    }
+
+    pub(crate) fn remove(&mut self, file_path: &str, idx: NodeIndex) {
+        let dir = dir_of(file_path);
+        let Some(files) = self.dirs.get_mut(dir) else {
+            return;
+        };
+        if let Some(nodes) = files.get_mut(file_path) {
+            nodes.retain(|&i| i != idx);
+            if nodes.is_empty() {
+                files.remove(file_path);
+            }
+        }
+        if files.is_empty() {
+            self.dirs.remove(dir);
+        }
+    }
+
+    /// Definitions in `file_path`.
+    pub(crate) fn in_file(&self, file_path: &str) -> &[NodeIndex] {
+        self.dirs
+            .get(dir_of(file_path))
+            .and_then(|files| files.get(file_path))
+            .map_or(&[], Vec::as_slice)
+    }
+
+    /// Definitions in the directory of `file_path`, when a single file
+    /// there defines the name.
+    pub(crate) fn sole_file_in_dir(&self, file_path: &str) -> &[NodeIndex] {
+        match self.dirs.get(dir_of(file_path)) {
+            Some(files) if files.len() == 1 => files.values().next().map_or(&[], Vec::as_slice),
+            _ => &[],
+        }
+    }
+}
+
+/// The directory part of an indexed path.
+fn dir_of(file_path: &str) -> &str {
+    file_path
+        .rfind(['/', '\\'])
+        .map_or("", |end| &file_path[..end])
+}
+
+/// Work done and edges withheld by call resolution since the last full build.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ResolutionCounters {
+    /// Candidate definitions looked at while resolving calls.
+    pub(crate) candidates_examined: usize,
+    /// Name -> fallback edges not created because the name is too common.
+    pub(crate) skipped_fallbacks: HashMap<String, usize>,
+}
+
+/// A name with more definitions than call resolution considers at once.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct HotName {
+    pub name: String,
+    pub definitions: usize,
+    /// Calls and imports left unlinked instead of guessing a definition.
+    pub skipped_fallbacks: usize,
+}
+
+impl RepositoryGraph {
+    /// Up to `limit` names with more than `max_call_candidates` definitions,
+    /// most defined first.
+    pub fn hot_names(&self, limit: usize) -> Vec<HotName> {
+        let mut hot: Vec<HotName> = self
+            .name_to_nodes
+            .iter()
+            .filter(|entry| entry.value().len() > self.max_call_candidates)
+            .map(|entry| HotName {
+                name: entry.key().clone(),
+                definitions: entry.value().len(),
+                skipped_fallbacks: self
+                    .resolution
+                    .skipped_fallbacks
+                    .get(entry.key())
+                    .copied()
+                    .unwrap_or(0),
+            })
+            .collect();
+        hot.sort_by(|a, b| {
+            b.definitions
+                .cmp(&a.definitions)
+                .then_with(|| a.name.cmp(&b.name))
+        });
+        hot.truncate(limit);
+        hot
+    }
+
+    /// Candidate definitions looked at by call resolution since the last
+    /// full build.
+    pub fn call_candidates_examined(&self) -> usize {
+        self.resolution.candidates_examined
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_name_scopes() {
+        let mut scopes = NameScopes::default();
+        scopes.insert("app/main.py", NodeIndex::new(1));
+        scopes.insert("app/main.py", NodeIndex::new(2));
+        scopes.insert("gen/a.py", NodeIndex::new(3));
+        scopes.insert("gen/b.py", NodeIndex::new(4));
+
+        assert_eq!(
+            scopes.in_file("app/main.py"),
+            &[NodeIndex::new(1), NodeIndex::new(2)]
+        );
+        assert_eq!(scopes.sole_file_in_dir("app/other.py").len(), 2);
+        // Two files in gen/ define the name, so neither is preferred.
+        assert!(scopes.sole_file_in_dir("gen/c.py").is_empty());
+        assert!(scopes.in_file("lib/x.py").is_empty());
+
+        scopes.remove("gen/b.py", NodeIndex::new(4));
+        assert_eq!(scopes.sole_file_in_dir("gen/c.py"), &[NodeIndex::new(3)]);
+        scopes.remove("gen/a.py", NodeIndex::new(3));
+        assert!(scopes.dirs.get("gen").is_none());
+    }
+}
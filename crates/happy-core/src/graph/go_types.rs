@@ -0,0 +1,281 @@
+//! Inheritance for Go, which has no syntax for it.
+//!
+//! A struct or interface that embeds another type gets an `Inherits` edge
+//! to it, and so does a struct whose methods, its own and those promoted
+//! from embedded structs, cover every method of an interface defined in the
+//! repository. Method sets are matched by name only, so an implementation
+//! with the wrong signature still counts. Interfaces that embed a type from
+//! outside the repository, such as `io.Reader`, have an unknown method set
+//! and are not matched.
+
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+use petgraph::stable_graph::NodeIndex;
+use petgraph::visit::EdgeRef;
+
+use super::types::EdgeKind;
+use super::{RepositoryGraph, add_semantic_edge};
+use crate::indexer::{CodeElement, EMBEDS_KEY, ElementType, METHODS_KEY, RECEIVER_KEY};
+
+/// A Go struct or interface.
+struct GoType<'a> {
+    elem: &'a CodeElement,
+    dir: &'a str,
+    embeds: Vec<&'a str>,
+    /// Methods declared on the type: in an interface body, or with the
+    /// struct as receiver.
+    methods: HashSet<&'a str>,
+}
+
+impl GoType<'_> {
+    fn is_interface(&self) -> bool {
+        self.elem.element_type == ElementType::Interface
+    }
+}
+
+struct GoTypes<'a> {
+    types: Vec<GoType<'a>>,
+    /// Type name -> indices into `types`.
+    by_name: HashMap<&'a str, Vec<usize>>,
+}
+
+impl<'a> GoTypes<'a> {
+    fn collect(elements: &'a [CodeElement]) -> Self {
+        let dir_of = |elem: &'a CodeElement| {
+            Path::new(&elem.file_path)
+                .parent()
+                .and_then(Path::to_str)
+                .unwrap_or("")
+        };
+        let mut types = Vec::new();
+        let mut by_name: HashMap<&str, Vec<usize>> = HashMap::new();
+        for elem in elements.iter().filter(|elem| {
+            elem.language == "go"
+                && matches!(
+                    elem.element_type,
+                    ElementType::Struct | ElementType::Interface
+                )
+        }) {
+            let list = |key: &str| -> Vec<&'a str> {
+                elem.metadata
+                    .get(key)
+                    .map(|value| value.split(',').filter(|s| !s.is_empty()).collect())
+                    .unwrap_or_default()
+            };
+            by_name.entry(&elem.name).or_default().push(types.len());
+            types.push(GoType {
+                elem,
+                dir: dir_of(elem),
+                embeds: list(EMBEDS_KEY),
+                methods: list(METHODS_KEY).into_iter().collect(),
+            });
+        }
+
+        let mut set = Self { types, by_name };
+        for elem in elements.iter().filter(|elem| elem.language == "go") {
+            let Some(receiver) = elem.metadata.get(RECEIVER_KEY) else {
+                continue;
+            };
+            if let Some(i) = set.resolve(receiver, dir_of(elem))
+                && !set.types[i].is_interface()
+            {
+                set.types[i].methods.insert(&elem.name);
+            }
+        }
+        set
+    }
+
+    /// The type `name` refers to from a file in `dir`: one in the same
+    /// directory for a bare name, or in a directory named after the package
+    /// for a qualified `pkg.Name`.
+    fn resolve(&self, name: &str, dir: &str) -> Option<usize> {
+        let (package, name) = match name.rsplit_once('.') {
+            Some((package, name)) => (Some(package), name),
+            None => (None, name),
+        };
+        self.by_name.get(name)?.iter().copied().find(|&i| {
+            let ty_dir = self.types[i].dir;
+            match package {
+                Some(package) => {
+                    Path::new(ty_dir).file_name().and_then(|n| n.to_str()) == Some(package)
+                }
+                None => ty_dir == dir,
+            }
+        })
+    }
+
+    /// Every method of type `i`, including those of the types it embeds.
+    /// `None` when an embedded interface is not in the repository.
+    fn method_set(&self, i: usize, seen: &mut HashSet<usize>) -> Option<HashSet<&'a str>> {
+        let ty = &self.types[i];
+        let mut methods = ty.methods.clone();
+        if !seen.insert(i) {
+            return Some(methods);
+        }
+        for embed in &ty.embeds {
+            match self.resolve(embed, ty.dir) {
+                Some(j) => methods.extend(self.method_set(j, seen)?),
+                // A struct may embed anything; only interfaces need every
+                // method accounted for.
+                None if ty.is_interface() => return None,
+                None => {}
+            }
+        }
+        Some(methods)
+    }
+}
+
+impl RepositoryGraph {
+    /// Link Go types to the types they embed and the interfaces they
+    /// satisfy. Safe to run again over all Go elements after an update: an
+    /// edge already present is left as is.
+    pub(super) fn build_go_type_edges(&mut self, elements: &[CodeElement]) {
+        let go = GoTypes::collect(elements);
+        if go.types.is_empty() {
+            return;
+        }
+        let node =
+            |graph: &Self, i: usize| graph.id_to_node.get(&go.types[i].elem.id).map(|idx| *idx);
+        let mut links: Vec<(usize, usize)> = Vec::new();
+
+        for (i, ty) in go.types.iter().enumerate() {
+            for embed in &ty.embeds {
+                if let Some(j) = go.resolve(embed, ty.dir)
+                    && j != i
+                {
+                    links.push((i, j));
+                }
+            }
+        }
+
+        let method_sets: Vec<Option<HashSet<&str>>> = (0..go.types.len())
+            .map(|i| go.method_set(i, &mut HashSet::new()))
+            .collect();
+        for (iface, required) in method_sets.iter().enumerate() {
+            let Some(required) = required.as_ref().filter(|methods| !methods.is_empty()) else {
+                continue;
+            };
+            if !go.types[iface].is_interface() {
+                continue;
+            }
+            for (strukt, methods) in method_sets.iter().enumerate() {
+                if !go.types[strukt].is_interface()
+                    && methods
+                        .as_ref()
+                        .is_some_and(|methods| methods.is_superset(required))
+                {
+                    links.push((strukt, iface));
+                }
+            }
+        }
+
+        for (from, to) in links {
+            let (Some(from), Some(to)) = (node(self, from), node(self, to)) else {
+                continue;
+            };
+            if !self.has_edge(from, to, EdgeKind::Inherits) {
+                add_semantic_edge(&mut self.graph, from, to, EdgeKind::Inherits);
+            }
+        }
+    }
+
+    pub(super) fn has_edge(&self, from: NodeIndex, to: NodeIndex, kind: EdgeKind) -> bool {
+        self.graph
+            .edges_connecting(from, to)
+            .any(|edge| edge.weight().kind == kind)
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "lang-go")]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn names(nodes: Vec<&super::super::types::GraphNode>) -> Vec<String> {
+        let mut names: Vec<String> = nodes.into_iter().map(|n| n.name.to_string()).collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn test_go_embedding_and_interface_satisfaction() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        std::fs::create_dir_all(root.join("animals")).unwrap();
+        std::fs::write(
+            root.join("animals/animals.go"),
+            r#"package animals
+
+import "io"
+
+// Speaker makes a sound.
+type Speaker interface {
+	Speak() string
+	Name() string
+}
+
+type Walker interface {
+	Speaker
+	Walk()
+}
+
+type Streamer interface {
+	io.Reader
+	Speak() string
+}
+
+type Base struct {
+	name string
+}
+
+func (b *Base) Name() string { return b.name }
+
+type Dog struct {
+	*Base
+	breed string
+}
+
+func (d Dog) Speak() string { return "woof" }
+
+type (
+	Cat struct {
+		Base
+	}
+	Rock struct{}
+)
+
+func (c *Cat) Speak() string { return "meow" }
+
+func (c *Cat) Walk() {}
+
+func (r Rock) Speak() string { return "" }
+"#,
+        )
+        .unwrap();
+        let root = root.to_string_lossy().to_string();
+        let elements = crate::indexer::walk_and_index(&root, &Default::default());
+        let dog = elements.iter().find(|e| e.name == "Dog").unwrap();
+        assert_eq!(dog.element_type, ElementType::Struct);
+        assert_eq!(
+            dog.metadata.get(EMBEDS_KEY).map(String::as_str),
+            Some("Base")
+        );
+        assert!(dog.code.starts_with("type Dog struct"));
+        let speaker = elements.iter().find(|e| e.name == "Speaker").unwrap();
+        assert_eq!(speaker.docstring.as_deref(), Some("Speaker makes a sound."));
+
+        let mut graph = RepositoryGraph::new();
+        graph.build_from_elements(&elements, &root);
+
+        assert_eq!(
+            names(graph.get_subclasses("Speaker")),
+            vec!["Cat", "Dog", "Walker"]
+        );
+        assert_eq!(names(graph.get_subclasses("Walker")), vec!["Cat"]);
+        assert_eq!(names(graph.get_subclasses("Base")), vec!["Cat", "Dog"]);
+        assert_eq!(names(graph.get_superclasses("Walker")), vec!["Speaker"]);
+        assert!(graph.get_subclasses("Streamer").is_empty());
+    }
+}
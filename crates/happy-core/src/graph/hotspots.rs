@@ -0,0 +1,330 @@
+//! Where a repository's code and connections concentrate.
+//!
+//! Counts elements and files per language, and ranks files by size and by
+//! the cross-file edges into and out of them, and functions by callers, so
+//! the most central modules can be found in one query.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use petgraph::Direction;
+use petgraph::visit::EdgeRef;
+use serde::Serialize;
+
+use super::RepositoryGraph;
+use super::types::{EdgeKind, NodeKind};
+use crate::indexer::ElementType;
+
+/// Entries listed per ranking by `happycode stats` and `repo_hotspots`.
+pub const HOTSPOTS_REPORTED: usize = 10;
+
+/// Indexed files and elements of one language.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LanguageCount {
+    pub language: String,
+    pub files: usize,
+    /// Every element, the file elements included.
+    pub elements: usize,
+}
+
+/// Indexed files and elements under one repository root of a workspace.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RootCount {
+    pub root: String,
+    pub files: usize,
+    /// Every element, the file elements included.
+    pub elements: usize,
+}
+
+/// A file and the count it is ranked by.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FileCount {
+    pub file_path: String,
+    pub count: usize,
+}
+
+/// A function or method and the distinct elements calling it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FunctionCallers {
+    pub id: String,
+    pub name: String,
+    pub file_path: String,
+    pub start_line: usize,
+    pub callers: usize,
+}
+
+/// The rankings of [`RepositoryGraph::hotspots`].
+#[derive(Debug, Clone, Serialize)]
+pub struct Hotspots {
+    pub languages: Vec<LanguageCount>,
+    /// Files defining the most elements.
+    pub largest_files: Vec<FileCount>,
+    /// Files with the most edges in from other files.
+    pub most_depended_upon: Vec<FileCount>,
+    /// Files with the most edges out to other files.
+    pub most_dependent: Vec<FileCount>,
+    /// Functions and methods with the most callers.
+    pub most_called: Vec<FunctionCallers>,
+}
+
+/// Whether an edge of `kind` makes its source depend on its target.
+/// `Defines` and `Contains` only describe structure, and
+/// `ReferencesByName` edges are too uncertain to rank by.
+fn is_dependency(kind: EdgeKind) -> bool {
+    matches!(
+        kind,
+        EdgeKind::Imports
+            | EdgeKind::Calls
+            | EdgeKind::Inherits
+            | EdgeKind::Implements
+            | EdgeKind::References
+    )
+}
+
+impl RepositoryGraph {
+    /// Files and elements per language, most elements first.
+    pub fn language_breakdown(&self) -> Vec<LanguageCount> {
+        let mut counts: BTreeMap<String, LanguageCount> = BTreeMap::new();
+        for entry in self.element_arena.iter() {
+            let elem = entry.value();
+            let count = counts
+                .entry(elem.language.clone())
+                .or_insert_with(|| LanguageCount {
+                    language: elem.language.clone(),
+                    files: 0,
+                    elements: 0,
+                });
+            count.elements += 1;
+            if elem.element_type == ElementType::File {
+                count.files += 1;
+            }
+        }
+        let mut languages: Vec<LanguageCount> = counts.into_values().collect();
+        languages.sort_by(|a, b| b.elements.cmp(&a.elements));
+        languages
+    }
+
+    /// Files and elements per repository root, in the order the roots were
+    /// indexed. Empty unless the graph was built from more than one root.
+    pub fn root_breakdown(&self) -> Vec<RootCount> {
+        let roots = self.global_index.roots();
+        if roots.len() < 2 {
+            return Vec::new();
+        }
+        let mut counts: Vec<RootCount> = roots
+            .into_iter()
+            .map(|root| RootCount {
+                root,
+                files: 0,
+                elements: 0,
+            })
+            .collect();
+        for entry in self.element_arena.iter() {
+            let elem = entry.value();
+            let Some(root) = self.global_index.root_of(&elem.file_path) else {
+                continue;
+            };
+            let Some(count) = counts.iter_mut().find(|count| count.root == root) else {
+                continue;
+            };
+            count.elements += 1;
+            if elem.element_type == ElementType::File {
+                count.files += 1;
+            }
+        }
+        counts
+    }
+
+    /// The `n` files defining the most elements, their file elements not
+    /// counted.
+    pub fn top_files_by_elements(&self, n: usize) -> Vec<FileCount> {
+        let counts = self.file_to_nodes.iter().map(|entry| {
+            let definitions = entry
+                .value()
+                .iter()
+                .filter(|&&idx| {
+                    self.graph
+                        .node_weight(idx)
+                        .is_some_and(|node| node.kind != NodeKind::File)
+                })
+                .count();
+            (entry.key().clone(), definitions)
+        });
+        top_files(counts, n)
+    }
+
+    /// The `n` files with the most dependency edges (imports, calls,
+    /// inheritance, references) crossing into them from other files for
+    /// [`Direction::Incoming`], or out of them for [`Direction::Outgoing`].
+    pub fn top_files_by_degree(&self, direction: Direction, n: usize) -> Vec<FileCount> {
+        let mut counts: HashMap<&str, usize> = HashMap::new();
+        for edge in self.graph.edge_references() {
+            if !is_dependency(edge.weight().kind) {
+                continue;
+            }
+            let (source, target) = (&self.graph[edge.source()], &self.graph[edge.target()]);
+            if source.file_path == target.file_path
+                || source.kind == NodeKind::Module
+                || target.kind == NodeKind::Module
+            {
+                continue;
+            }
+            let file = match direction {
+                Direction::Incoming => &target.file_path,
+                Direction::Outgoing => &source.file_path,
+            };
+            *counts.entry(file).or_default() += 1;
+        }
+        top_files(
+            counts
+                .into_iter()
+                .map(|(file, count)| (file.to_string(), count)),
+            n,
+        )
+    }
+
+    /// The `n` functions and methods called by the most distinct elements.
+    pub fn top_functions_by_callers(&self, n: usize) -> Vec<FunctionCallers> {
+        let mut ranked: Vec<FunctionCallers> = self
+            .graph
+            .node_indices()
+            .filter_map(|idx| {
+                let node = &self.graph[idx];
+                if !matches!(node.kind, NodeKind::Function | NodeKind::Method) {
+                    return None;
+                }
+                let callers = self
+                    .graph
+                    .edges_directed(idx, Direction::Incoming)
+                    .filter(|edge| edge.weight().kind == EdgeKind::Calls)
+                    .map(|edge| edge.source())
+                    .collect::<HashSet<_>>()
+                    .len();
+                (callers > 0).then(|| FunctionCallers {
+                    id: node.id.clone(),
+                    name: node.name.to_string(),
+                    file_path: node.file_path.clone(),
+                    start_line: node.start_line,
+                    callers,
+                })
+            })
+            .collect();
+        ranked.sort_by(|a, b| {
+            b.callers
+                .cmp(&a.callers)
+                .then_with(|| a.file_path.cmp(&b.file_path))
+                .then(a.start_line.cmp(&b.start_line))
+        });
+        ranked.truncate(n);
+        ranked
+    }
+
+    /// Every ranking, `n` entries each.
+    pub fn hotspots(&self, n: usize) -> Hotspots {
+        Hotspots {
+            languages: self.language_breakdown(),
+            largest_files: self.top_files_by_elements(n),
+            most_depended_upon: self.top_files_by_degree(Direction::Incoming, n),
+            most_dependent: self.top_files_by_degree(Direction::Outgoing, n),
+            most_called: self.top_functions_by_callers(n),
+        }
+    }
+}
+
+/// The `n` highest nonzero counts, ties broken by path.
+fn top_files(counts: impl Iterator<Item = (String, usize)>, n: usize) -> Vec<FileCount> {
+    let mut ranked: Vec<FileCount> = counts
+        .filter(|(_, count)| *count > 0)
+        .map(|(file_path, count)| FileCount { file_path, count })
+        .collect();
+    ranked.sort_by(|a, b| {
+        b.count
+            .cmp(&a.count)
+            .then_with(|| a.file_path.cmp(&b.file_path))
+    });
+    ranked.truncate(n);
+    ranked
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn index(files: &[(&str, &str)]) -> (String, RepositoryGraph) {
+        let dir = tempfile::tempdir().unwrap();
+        for (name, code) in files {
+            std::fs::write(dir.path().join(name), code).unwrap();
+        }
+        let root = dir
+            .path()
+            .canonicalize()
+            .unwrap()
+            .to_string_lossy()
+            .to_string();
+        let mut repo = RepositoryGraph::new();
+        repo.build_from_elements(
+            &crate::indexer::walk_and_index(&root, &Default::default()),
+            &root,
+        );
+        (root, repo)
+    }
+
+    fn names(root: &str, files: &[FileCount]) -> Vec<(String, usize)> {
+        files
+            .iter()
+            .map(|file| {
+                let name = file
+                    .file_path
+                    .trim_start_matches(root)
+                    .trim_start_matches('/');
+                (name.to_string(), file.count)
+            })
+            .collect()
+    }
+
+    #[test]
+    #[cfg(feature = "lang-rust")]
+    fn ranks_files_and_functions() {
+        let (root, repo) = index(&[
+            (
+                "core.py",
+                "def base():\n    return 1\n\ndef other():\n    return 2\n",
+            ),
+            (
+                "models.py",
+                "from core import base\n\ndef model():\n    return base()\n",
+            ),
+            (
+                "views.py",
+                "from core import base\nfrom models import model\n\ndef view():\n    return base() + model()\n",
+            ),
+            ("lib.rs", "fn helper() -> u32 {\n    1\n}\n"),
+        ]);
+
+        let hotspots = repo.hotspots(2);
+        assert_eq!(
+            names(&root, &hotspots.largest_files),
+            vec![("core.py".to_string(), 2), ("lib.rs".to_string(), 1)]
+        );
+        assert_eq!(
+            names(&root, &hotspots.most_depended_upon)[0].0,
+            "core.py".to_string()
+        );
+        assert_eq!(
+            names(&root, &hotspots.most_dependent)[0].0,
+            "views.py".to_string()
+        );
+        assert_eq!(hotspots.most_called[0].name, "base");
+        assert_eq!(hotspots.most_called[0].callers, 2);
+
+        let languages: Vec<(String, usize, usize)> = repo
+            .language_breakdown()
+            .into_iter()
+            .map(|lang| (lang.language, lang.files, lang.elements))
+            .collect();
+        assert_eq!(
+            languages,
+            vec![("python".to_string(), 3, 7), ("rust".to_string(), 1, 2)]
+        );
+    }
+}
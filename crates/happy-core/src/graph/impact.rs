@@ -0,0 +1,514 @@
+//! What a diff touches, transitively.
+//!
+//! The changed line ranges of `git diff --unified=0` are mapped to the
+//! innermost elements spanning them. From those, incoming `Calls` and
+//! `Imports` edges are followed breadth-first, so each impacted element is
+//! reported at its distance from the change: 0 for the changed elements
+//! themselves, 1 for their direct callers and importers, and so on. Files
+//! reached through imports are only followed further through imports.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::io;
+use std::path::Path;
+use std::process::Command;
+
+use petgraph::Direction;
+use petgraph::stable_graph::NodeIndex;
+use petgraph::visit::EdgeRef;
+use serde::Serialize;
+
+use super::RepositoryGraph;
+use super::types::{EdgeKind, NodeKind};
+
+/// Default number of hops followed from the changed elements.
+pub const DEFAULT_IMPACT_DEPTH: usize = 3;
+
+/// Default cap on the impacted elements reported.
+pub const DEFAULT_IMPACT_LIMIT: usize = 500;
+
+/// Directory names whose files are tests.
+const TEST_DIRS: &[&str] = &["test", "tests", "__tests__", "spec", "specs"];
+
+/// Lines `start..=end` (1-based) of a file's new version.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct LineRange {
+    pub start: usize,
+    pub end: usize,
+}
+
+/// One file of a diff.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FileChange {
+    /// Path relative to the directory the diff was taken in.
+    pub path: String,
+    /// Changed lines. A hunk that only deletes lines marks the line after
+    /// which they were.
+    pub ranges: Vec<LineRange>,
+    pub deleted: bool,
+}
+
+/// An element reached from the change.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ImpactedElement {
+    pub id: String,
+    pub name: String,
+    pub kind: NodeKind,
+    pub file_path: String,
+    pub start_line: usize,
+    pub end_line: usize,
+    /// The edge followed to reach it; `None` for the changed elements.
+    pub via: Option<EdgeKind>,
+    /// Name of the element it was reached from.
+    pub from: Option<String>,
+}
+
+/// The elements and files first reached at one distance from the change.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ImpactLevel {
+    pub distance: usize,
+    pub elements: Vec<ImpactedElement>,
+    /// Files not already listed at a smaller distance.
+    pub files: Vec<String>,
+}
+
+/// The result of [`RepositoryGraph::diff_impact`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DiffImpact {
+    /// Every file in the diff, indexed or not.
+    pub changed_files: Vec<String>,
+    pub levels: Vec<ImpactLevel>,
+    /// Impacted files that look like tests, nearest first.
+    pub tests: Vec<String>,
+    /// Whether the walk stopped at the element limit.
+    pub truncated: bool,
+}
+
+/// `git diff --unified=0` of the working tree in `repo_root` against `base`
+/// (`HEAD` by default), restricted to files under `repo_root` and with
+/// paths relative to it.
+pub fn git_diff(repo_root: &Path, base: Option<&str>) -> io::Result<String> {
+    let base = base.unwrap_or("HEAD");
+    if base.starts_with('-') {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("invalid base revision '{base}'"),
+        ));
+    }
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(repo_root)
+        .args([
+            "diff",
+            "--no-color",
+            "--no-ext-diff",
+            "--unified=0",
+            "--relative",
+            "--src-prefix=a/",
+            "--dst-prefix=b/",
+            base,
+            "--",
+        ])
+        .output()?;
+    if !output.status.success() {
+        return Err(io::Error::other(format!(
+            "git diff {base} failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// The files and changed lines of a unified diff with `a/` and `b/`
+/// prefixes. Files without hunks (renames, mode and binary changes) get no
+/// ranges.
+pub fn parse_unified_diff(diff: &str) -> Vec<FileChange> {
+    let mut changes: Vec<FileChange> = Vec::new();
+    let mut old_path: Option<String> = None;
+    for line in diff.lines() {
+        if line.starts_with("diff --git ") {
+            old_path = None;
+        } else if let Some(path) = line.strip_prefix("--- ") {
+            old_path = path.strip_prefix("a/").map(str::to_string);
+        } else if let Some(path) = line.strip_prefix("+++ ") {
+            let change = match path.strip_prefix("b/") {
+                Some(path) => FileChange {
+                    path: path.to_string(),
+                    ranges: Vec::new(),
+                    deleted: false,
+                },
+                None => match old_path.take() {
+                    Some(path) => FileChange {
+                        path,
+                        ranges: Vec::new(),
+                        deleted: true,
+                    },
+                    None => continue,
+                },
+            };
+            changes.push(change);
+        } else if let Some(path) = line.strip_prefix("rename to ") {
+            changes.push(FileChange {
+                path: path.to_string(),
+                ranges: Vec::new(),
+                deleted: false,
+            });
+        } else if line.starts_with("@@ ")
+            && let Some(change) = changes.last_mut()
+            && !change.deleted
+            && let Some(range) = hunk_range(line)
+        {
+            change.ranges.push(range);
+        }
+    }
+    // A renamed and edited file has both a `rename to` and a `+++` entry.
+    changes.dedup_by(|later, earlier| {
+        if later.path != earlier.path {
+            return false;
+        }
+        earlier.ranges.append(&mut later.ranges);
+        true
+    });
+    changes
+}
+
+/// The new-side lines of a `@@ -a,b +c,d @@` hunk header.
+fn hunk_range(header: &str) -> Option<LineRange> {
+    let new_side = header.split_whitespace().nth(2)?.strip_prefix('+')?;
+    let (start, count) = match new_side.split_once(',') {
+        Some((start, count)) => (start.parse::<usize>().ok()?, count.parse::<usize>().ok()?),
+        None => (new_side.parse::<usize>().ok()?, 1),
+    };
+    if count == 0 {
+        let line = start.max(1);
+        return Some(LineRange {
+            start: line,
+            end: line,
+        });
+    }
+    Some(LineRange {
+        start,
+        end: start + count - 1,
+    })
+}
+
+/// Whether `relative_path` looks like a test file: under a `tests`-style
+/// directory, or named like `test_x.py`, `x_test.go`, `x.test.ts`,
+/// `x.spec.js` or `XTest.java`.
+pub fn is_test_file(relative_path: &str) -> bool {
+    let path = relative_path.replace('\\', "/");
+    let mut parts: Vec<&str> = path.split('/').collect();
+    let Some(name) = parts.pop() else {
+        return false;
+    };
+    if parts.iter().any(|dir| TEST_DIRS.contains(dir)) {
+        return true;
+    }
+    let stem = name.split('.').next().unwrap_or(name);
+    stem.starts_with("test_")
+        || stem.ends_with("_test")
+        || stem.ends_with("Test")
+        || stem.ends_with("Tests")
+        || name.contains(".test.")
+        || name.contains(".spec.")
+}
+
+impl RepositoryGraph {
+    /// Elements and files impacted by `changes`, taken in `repo_root`:
+    /// the innermost elements spanning each changed range, the changed
+    /// files themselves, and whatever reaches them within `max_depth`
+    /// incoming call and import hops. Stops after `limit` elements.
+    pub fn diff_impact(
+        &self,
+        repo_root: &Path,
+        changes: &[FileChange],
+        max_depth: usize,
+        limit: usize,
+    ) -> DiffImpact {
+        let mut distance: HashMap<NodeIndex, usize> = HashMap::new();
+        let mut queue: VecDeque<(NodeIndex, Option<(EdgeKind, NodeIndex)>)> = VecDeque::new();
+        let mut changed_files = Vec::new();
+        for change in changes {
+            let file_path = repo_root.join(&change.path).to_string_lossy().into_owned();
+            if let Some(nodes) = self.file_to_nodes.get(&file_path) {
+                for idx in self.changed_nodes(nodes.value(), &change.ranges) {
+                    if distance.insert(idx, 0).is_none() {
+                        queue.push_back((idx, None));
+                    }
+                }
+            }
+            changed_files.push(file_path);
+        }
+
+        let mut levels: Vec<ImpactLevel> = Vec::new();
+        let mut listed_files: HashSet<String> = HashSet::new();
+        let mut reported = 0;
+        let mut truncated = false;
+        while let Some((idx, reached)) = queue.pop_front() {
+            let node = &self.graph[idx];
+            let hops = distance[&idx];
+            if levels.len() <= hops {
+                levels.push(ImpactLevel {
+                    distance: hops,
+                    elements: Vec::new(),
+                    files: Vec::new(),
+                });
+            }
+            let level = &mut levels[hops];
+            if listed_files.insert(node.file_path.clone()) {
+                level.files.push(node.file_path.clone());
+            }
+            if node.kind != NodeKind::File {
+                if reported == limit {
+                    truncated = true;
+                    break;
+                }
+                reported += 1;
+                level.elements.push(ImpactedElement {
+                    id: node.id.clone(),
+                    name: node.name.to_string(),
+                    kind: node.kind,
+                    file_path: node.file_path.clone(),
+                    start_line: node.start_line,
+                    end_line: node.end_line,
+                    via: reached.map(|(kind, _)| kind),
+                    from: reached.map(|(_, from)| self.graph[from].name.to_string()),
+                });
+            }
+            if hops == max_depth {
+                continue;
+            }
+            for edge in self.graph.edges_directed(idx, Direction::Incoming) {
+                let kind = edge.weight().kind;
+                let follow = match node.kind {
+                    NodeKind::File => kind == EdgeKind::Imports,
+                    _ => matches!(kind, EdgeKind::Calls | EdgeKind::Imports),
+                };
+                if !follow || distance.contains_key(&edge.source()) {
+                    continue;
+                }
+                distance.insert(edge.source(), hops + 1);
+                queue.push_back((edge.source(), Some((kind, idx))));
+            }
+        }
+
+        for level in &mut levels {
+            level.elements.sort_by(|a, b| {
+                a.file_path
+                    .cmp(&b.file_path)
+                    .then(a.start_line.cmp(&b.start_line))
+            });
+            level.files.sort();
+        }
+        let tests = levels
+            .iter()
+            .flat_map(|level| &level.files)
+            .filter(|file| {
+                let relative = Path::new(file)
+                    .strip_prefix(repo_root)
+                    .unwrap_or(Path::new(file));
+                is_test_file(&relative.to_string_lossy())
+            })
+            .cloned()
+            .collect();
+        DiffImpact {
+            changed_files,
+            levels,
+            tests,
+            truncated,
+        }
+    }
+
+    /// The file node of a changed file and, for each range, the innermost
+    /// elements overlapping it: those containing no other overlapping
+    /// element.
+    fn changed_nodes(&self, nodes: &[NodeIndex], ranges: &[LineRange]) -> Vec<NodeIndex> {
+        let mut changed: Vec<NodeIndex> = nodes
+            .iter()
+            .copied()
+            .filter(|&idx| self.graph[idx].kind == NodeKind::File)
+            .collect();
+        for range in ranges {
+            let overlapping: Vec<NodeIndex> = nodes
+                .iter()
+                .copied()
+                .filter(|&idx| {
+                    let node = &self.graph[idx];
+                    node.kind != NodeKind::File
+                        && node.start_line <= range.end
+                        && node.end_line >= range.start
+                })
+                .collect();
+            for &idx in &overlapping {
+                let outer = &self.graph[idx];
+                let contains_other = overlapping.iter().any(|&other| {
+                    let inner = &self.graph[other];
+                    other != idx
+                        && outer.start_line <= inner.start_line
+                        && inner.end_line <= outer.end_line
+                });
+                if !contains_other && !changed.contains(&idx) {
+                    changed.push(idx);
+                }
+            }
+        }
+        changed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    const DIFF: &str = "\
+diff --git a/core.py b/core.py
+index 1111111..2222222 100644
+--- a/core.py
++++ b/core.py
+@@ -5 +5,2 @@ def base():
+-    return 1
++    value = 2
++    return value
+diff --git a/old.py b/old.py
+deleted file mode 100644
+index 3333333..0000000
+--- a/old.py
++++ /dev/null
+@@ -1,2 +0,0 @@
+-def gone():
+-    pass
+diff --git a/a.py b/b.py
+similarity index 100%
+rename from a.py
+rename to b.py
+diff --git a/views.py b/views.py
+--- a/views.py
++++ b/views.py
+@@ -3,0 +4 @@ def view():
++    pass
+";
+
+    #[test]
+    fn test_parse_unified_diff() {
+        let changes = parse_unified_diff(DIFF);
+        let summary: Vec<(&str, Vec<(usize, usize)>, bool)> = changes
+            .iter()
+            .map(|change| {
+                (
+                    change.path.as_str(),
+                    change
+                        .ranges
+                        .iter()
+                        .map(|range| (range.start, range.end))
+                        .collect(),
+                    change.deleted,
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("core.py", vec![(5, 6)], false),
+                ("old.py", vec![], true),
+                ("b.py", vec![], false),
+                ("views.py", vec![(4, 4)], false),
+            ]
+        );
+        assert_eq!(
+            hunk_range("@@ -10,3 +9,0 @@"),
+            Some(LineRange { start: 9, end: 9 })
+        );
+    }
+
+    #[test]
+    fn test_is_test_file() {
+        for path in [
+            "tests/test_api.py",
+            "src/api_test.go",
+            "web/src/App.test.tsx",
+            "src/test/java/UserServiceTest.java",
+            "lib/__tests__/util.js",
+        ] {
+            assert!(is_test_file(path), "{path}");
+        }
+        for path in ["src/api.py", "src/contest.rs", "attestation/mod.rs"] {
+            assert!(!is_test_file(path), "{path}");
+        }
+    }
+
+    #[test]
+    fn test_diff_impact_groups_by_distance() {
+        let dir = tempfile::tempdir().unwrap();
+        let files = [
+            (
+                "core.py",
+                "def base():\n    return 1\n\ndef other():\n    return 2\n",
+            ),
+            (
+                "service.py",
+                "from core import base\n\ndef serve():\n    return base()\n",
+            ),
+            (
+                "api.py",
+                "from service import serve\n\ndef handle():\n    return serve()\n",
+            ),
+        ];
+        std::fs::create_dir(dir.path().join("tests")).unwrap();
+        std::fs::write(
+            dir.path().join("tests/test_api.py"),
+            "from api import handle\n\ndef test_handle():\n    assert handle() == 1\n",
+        )
+        .unwrap();
+        for (name, code) in files {
+            std::fs::write(dir.path().join(name), code).unwrap();
+        }
+        let root = dir.path().canonicalize().unwrap();
+        let mut graph = RepositoryGraph::new();
+        graph.build_from_elements(
+            &crate::indexer::walk_and_index(&root.to_string_lossy(), &Default::default()),
+            &root.to_string_lossy(),
+        );
+
+        let changes = vec![FileChange {
+            path: "core.py".to_string(),
+            ranges: vec![LineRange { start: 2, end: 2 }],
+            deleted: false,
+        }];
+        let impact = graph.diff_impact(&root, &changes, DEFAULT_IMPACT_DEPTH, DEFAULT_IMPACT_LIMIT);
+        let names: Vec<Vec<&str>> = impact
+            .levels
+            .iter()
+            .map(|level| {
+                level
+                    .elements
+                    .iter()
+                    .map(|elem| elem.name.as_str())
+                    .collect()
+            })
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                vec!["base"],
+                vec!["serve"],
+                vec!["handle"],
+                vec!["test_handle"]
+            ]
+        );
+        assert_eq!(impact.levels[1].elements[0].via, Some(EdgeKind::Calls));
+        assert_eq!(impact.levels[1].elements[0].from.as_deref(), Some("base"));
+        assert_eq!(
+            impact.tests,
+            vec![
+                root.join("tests/test_api.py")
+                    .to_string_lossy()
+                    .into_owned()
+            ]
+        );
+        assert!(!impact.truncated);
+
+        let shallow = graph.diff_impact(&root, &changes, 1, DEFAULT_IMPACT_LIMIT);
+        assert_eq!(shallow.levels.len(), 2);
+        assert!(shallow.tests.is_empty());
+    }
+}
@@ -0,0 +1,251 @@
+//! Files ordered so the modules the rest of the repo builds on come first.
+//!
+//! Files are layered over the import graph after collapsing import cycles:
+//! a file nothing imports is in layer 0, and every other file sits one layer
+//! below its deepest importer, so the files of a cycle share a layer. Deeper
+//! layers come first, putting core modules ahead of entry points and leaves.
+//! Within a layer, files with more direct importers come first, then by path.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::str::FromStr;
+use std::sync::{Arc, PoisonError};
+
+use petgraph::algo::tarjan_scc;
+use petgraph::graph::DiGraph;
+use serde::Serialize;
+
+use super::RepositoryGraph;
+use super::types::{EdgeKind, NodeKind};
+
+/// How files are listed by `list_indexed_files` and `happycode ls`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FileOrder {
+    /// By path.
+    #[default]
+    Alpha,
+    /// Most depended-on first, see [`RepositoryGraph::files_by_importance`].
+    Importance,
+}
+
+impl FromStr for FileOrder {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "alpha" => Ok(Self::Alpha),
+            "importance" => Ok(Self::Importance),
+            other => Err(format!(
+                "unknown order '{other}', expected alpha or importance"
+            )),
+        }
+    }
+}
+
+/// A file's place in the importance ordering.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FileImportance {
+    pub file_path: String,
+    /// Depth below the files nothing imports; higher is more foundational.
+    pub layer: usize,
+    /// Distinct files importing this one directly.
+    pub dependents: usize,
+}
+
+/// Importance ordering of the graph generation it was computed for.
+#[derive(Debug)]
+pub(crate) struct ImportanceCache {
+    generation: u64,
+    files: Arc<Vec<FileImportance>>,
+}
+
+impl RepositoryGraph {
+    /// Every indexed file, most depended-on first.
+    ///
+    /// Computed once per graph generation.
+    pub fn files_by_importance(&self) -> Arc<Vec<FileImportance>> {
+        let mut cache = self
+            .importance_cache
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        if let Some(cached) = cache.as_ref()
+            && cached.generation == self.generation
+        {
+            return Arc::clone(&cached.files);
+        }
+        let files = Arc::new(self.compute_file_importance());
+        *cache = Some(ImportanceCache {
+            generation: self.generation,
+            files: Arc::clone(&files),
+        });
+        files
+    }
+
+    fn compute_file_importance(&self) -> Vec<FileImportance> {
+        // file -> the files it imports, over every indexed file.
+        let mut imports: BTreeMap<String, BTreeSet<String>> = self
+            .file_to_nodes
+            .iter()
+            .map(|entry| (entry.key().clone(), BTreeSet::new()))
+            .collect();
+        for edge in self.graph.edge_indices() {
+            if self.graph[edge].kind != EdgeKind::Imports {
+                continue;
+            }
+            let Some((source, target)) = self.graph.edge_endpoints(edge) else {
+                continue;
+            };
+            let (source, target) = (&self.graph[source], &self.graph[target]);
+            if source.kind != NodeKind::File
+                || target.kind != NodeKind::File
+                || source.file_path == target.file_path
+            {
+                continue;
+            }
+            imports.entry(target.file_path.clone()).or_default();
+            imports
+                .entry(source.file_path.clone())
+                .or_default()
+                .insert(target.file_path.clone());
+        }
+
+        let mut files: DiGraph<&str, ()> = DiGraph::new();
+        let indices: BTreeMap<&str, _> = imports
+            .keys()
+            .map(|path| (path.as_str(), files.add_node(path.as_str())))
+            .collect();
+        let mut dependents: BTreeMap<&str, usize> = BTreeMap::new();
+        for (source, targets) in &imports {
+            for target in targets {
+                files.add_edge(indices[source.as_str()], indices[target.as_str()], ());
+                *dependents.entry(target.as_str()).or_default() += 1;
+            }
+        }
+
+        // Tarjan yields components imported files first; walk it backwards so
+        // every importer is layered before the files it imports.
+        let components = tarjan_scc(&files);
+        let mut component_of = vec![0; files.node_count()];
+        for (component, members) in components.iter().enumerate() {
+            for idx in members {
+                component_of[idx.index()] = component;
+            }
+        }
+        let mut layers = vec![0; components.len()];
+        for (component, members) in components.iter().enumerate().rev() {
+            for &idx in members {
+                for target in files.neighbors(idx) {
+                    let target = component_of[target.index()];
+                    if target != component {
+                        layers[target] = layers[target].max(layers[component] + 1);
+                    }
+                }
+            }
+        }
+
+        let mut ranked: Vec<FileImportance> = files
+            .node_indices()
+            .map(|idx| {
+                let file_path = files[idx];
+                FileImportance {
+                    file_path: file_path.to_string(),
+                    layer: layers[component_of[idx.index()]],
+                    dependents: dependents.get(file_path).copied().unwrap_or(0),
+                }
+            })
+            .collect();
+        ranked.sort_by(|a, b| {
+            b.layer
+                .cmp(&a.layer)
+                .then(b.dependents.cmp(&a.dependents))
+                .then_with(|| a.file_path.cmp(&b.file_path))
+        });
+        ranked
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn index(files: &[(&str, &str)]) -> (String, RepositoryGraph) {
+        let dir = tempfile::tempdir().unwrap();
+        for (name, code) in files {
+            std::fs::write(dir.path().join(name), code).unwrap();
+        }
+        let root = dir
+            .path()
+            .canonicalize()
+            .unwrap()
+            .to_string_lossy()
+            .to_string();
+        let mut repo = RepositoryGraph::new();
+        repo.build_from_elements(
+            &crate::indexer::walk_and_index(&root, &Default::default()),
+            &root,
+        );
+        (root, repo)
+    }
+
+    fn ranking(root: &str, repo: &RepositoryGraph) -> Vec<(String, usize, usize)> {
+        repo.files_by_importance()
+            .iter()
+            .map(|file| {
+                let name = file
+                    .file_path
+                    .trim_start_matches(root)
+                    .trim_start_matches('/');
+                (name.to_string(), file.layer, file.dependents)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn core_files_come_before_the_files_importing_them() {
+        let (root, repo) = index(&[
+            ("core.py", "def base():\n    return 1\n"),
+            ("models.py", "import core\n"),
+            ("views.py", "import core\nimport models\n"),
+            ("cli.py", "import views\n"),
+            ("script.py", "def standalone():\n    return 2\n"),
+        ]);
+
+        assert_eq!(
+            ranking(&root, &repo),
+            vec![
+                ("core.py".to_string(), 3, 2),
+                ("models.py".to_string(), 2, 1),
+                ("views.py".to_string(), 1, 1),
+                ("cli.py".to_string(), 0, 0),
+                ("script.py".to_string(), 0, 0),
+            ]
+        );
+    }
+
+    #[test]
+    fn import_cycles_share_a_layer() {
+        let (root, repo) = index(&[
+            ("a.py", "import b\n"),
+            ("b.py", "import a\nimport util\n"),
+            ("util.py", "def helper():\n    return 1\n"),
+            ("main.py", "import a\n"),
+        ]);
+
+        assert_eq!(
+            ranking(&root, &repo),
+            vec![
+                ("util.py".to_string(), 2, 1),
+                ("a.py".to_string(), 1, 2),
+                ("b.py".to_string(), 1, 1),
+                ("main.py".to_string(), 0, 0),
+            ]
+        );
+    }
+
+    #[test]
+    fn ordering_is_cached_per_generation() {
+        let (_, repo) = index(&[("core.py", "x = 1\n"), ("app.py", "import core\n")]);
+        let first = repo.files_by_importance();
+        assert!(Arc::ptr_eq(&first, &repo.files_by_importance()));
+    }
+}
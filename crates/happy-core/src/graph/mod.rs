@@ -1,17 +1,47 @@
+pub mod analysis;
+pub mod diagnostics;
+pub mod digest;
+pub mod export;
+mod facts;
+pub mod fanout;
+mod go_types;
+pub mod hotspots;
+pub mod impact;
+pub mod importance;
+mod modules;
+pub mod qualified;
 pub mod queries;
+pub mod renames;
+mod rust_impls;
+pub mod snippet;
+pub mod source;
 pub mod types;
 
+use std::collections::HashSet;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::Instant;
+
 use dashmap::DashMap;
-use petgraph::stable_graph::NodeIndex;
+use petgraph::stable_graph::{EdgeIndex, NodeIndex};
+use petgraph::visit::EdgeRef;
 use smol_str::SmolStr;
 
 use crate::global_index::GlobalIndex;
 use crate::global_index::module_resolver::ModuleResolver;
 use crate::global_index::symbol_resolver::SymbolResolver;
-use crate::indexer::{CodeElement, ElementType};
-use crate::parser::calls::extract_calls;
-use crate::parser::imports::extract_imports;
+use crate::indexer::{CodeElement, ElementType, IMPL_TRAIT_KEY, IndexConfig};
+use crate::parser::calls::{CallInfo, CallType};
 use crate::parser::languages::SupportedLanguage;
+use crate::parser::strings::string_ref_name;
+use crate::workspace::Workspace;
+use facts::{ElementFacts, extract_facts};
+use fanout::{
+    DEFAULT_FALLBACK_FANOUT_LIMIT, DEFAULT_MAX_CALL_CANDIDATES, HOT_NAMES_REPORTED, NameScopes,
+    ResolutionCounters,
+};
+use importance::ImportanceCache;
+use renames::{RenameMap, RenameRecord};
 use types::{CodeGraph, EdgeKind, GraphEdge, GraphNode, NodeKind};
 
 /// The main repository graph holding all code relationships.
@@ -21,14 +51,33 @@ pub struct RepositoryGraph {
     pub(crate) id_to_node: DashMap<String, NodeIndex>,
     /// name -> Vec<NodeIndex> (multiple elements can share a name)
     pub(crate) name_to_nodes: DashMap<String, Vec<NodeIndex>>,
+    /// name -> the same nodes by directory and file, for names too common to scan
+    name_scopes: DashMap<String, NameScopes>,
     /// file_path -> Vec<NodeIndex>
     pub(crate) file_to_nodes: DashMap<String, Vec<NodeIndex>>,
+    /// module path -> its module node, e.g. `src.app.services`
+    module_nodes: DashMap<String, NodeIndex>,
     /// Store elements for source code retrieval
     pub(crate) element_arena: DashMap<String, CodeElement>,
     /// file_path -> list of imported module/symbol names (for import-aware resolution)
     file_imports: DashMap<String, Vec<String>>,
     /// Global index for module/symbol resolution across the repo
     global_index: GlobalIndex,
+    /// Old element ID -> new ID for elements renamed by `update_file`
+    renames: RenameMap,
+    /// Bumped on every node/edge change, so callers can cache derived data
+    generation: u64,
+    /// Whether to build string-literal `ReferencesByName` edges, from
+    /// `[index] detect_string_refs` in the repo's `.happy/agent.toml`
+    detect_string_refs: bool,
+    /// `[index] max_call_candidates`
+    max_call_candidates: usize,
+    /// `[index] fallback_fanout_limit`
+    fallback_fanout_limit: usize,
+    /// Call resolution work and withheld fallback edges
+    resolution: ResolutionCounters,
+    /// `files_by_importance` of the last generation it was computed for
+    importance_cache: Mutex<Option<ImportanceCache>>,
 }
 
 impl RepositoryGraph {
@@ -37,10 +86,19 @@ impl RepositoryGraph {
             graph: CodeGraph::default(),
             id_to_node: DashMap::new(),
             name_to_nodes: DashMap::new(),
+            name_scopes: DashMap::new(),
             file_to_nodes: DashMap::new(),
+            module_nodes: DashMap::new(),
             element_arena: DashMap::new(),
             file_imports: DashMap::new(),
             global_index: GlobalIndex::new(),
+            renames: RenameMap::new(),
+            generation: 0,
+            detect_string_refs: false,
+            max_call_candidates: DEFAULT_MAX_CALL_CANDIDATES,
+            fallback_fanout_limit: DEFAULT_FALLBACK_FANOUT_LIMIT,
+            resolution: ResolutionCounters::default(),
+            importance_cache: Mutex::default(),
         }
     }
 
@@ -51,8 +109,13 @@ impl RepositoryGraph {
         let file_path = node.file_path.clone();
 
         let idx = self.graph.add_node(node);
+        self.generation += 1;
 
         self.id_to_node.insert(id, idx);
+        self.name_scopes
+            .entry(name.clone())
+            .or_default()
+            .insert(&file_path, idx);
         self.name_to_nodes.entry(name).or_default().push(idx);
         self.file_to_nodes.entry(file_path).or_default().push(idx);
 
@@ -62,14 +125,45 @@ impl RepositoryGraph {
     /// Add an edge between two nodes.
     pub fn add_edge(&mut self, from: NodeIndex, to: NodeIndex, edge: GraphEdge) {
         self.graph.add_edge(from, to, edge);
+        self.generation += 1;
     }
 
     /// Build the graph from a set of code elements.
     ///
     /// `repo_root` is used to compute module paths for the GlobalIndex (enables
-    /// proper import resolution for Python relative imports, Java packages, etc.).
+    /// proper import resolution for Python relative imports, Java packages, etc.)
+    /// and to read the repo's `[index]` config.
     /// Pass `""` if repo root is unknown — resolution will fall back to heuristics.
     pub fn build_from_elements(&mut self, elements: &[CodeElement], repo_root: &str) {
+        self.build_from_roots(elements, &[repo_root]);
+    }
+
+    /// Build the graph from the elements a [`Workspace`] loaded.
+    pub fn build_from_workspace(&mut self, elements: &[CodeElement], workspace: &Workspace) {
+        let roots = workspace.root_paths();
+        let roots: Vec<&str> = roots.iter().map(String::as_str).collect();
+        self.build_from_roots(elements, &roots);
+    }
+
+    /// Build the graph from the elements of several repository roots, the
+    /// primary root first. Imports resolve across roots; the `[index]` config is read
+    /// from the primary root.
+    pub fn build_from_roots(&mut self, elements: &[CodeElement], repo_roots: &[&str]) {
+        let started = Instant::now();
+        let repo_root = repo_roots.first().copied().unwrap_or("");
+        // Semantic edges are added straight to `graph`, so count the whole build
+        self.generation += 1;
+        self.resolution = ResolutionCounters::default();
+        if !repo_root.is_empty() {
+            let config = IndexConfig::load(Path::new(repo_root)).unwrap_or_else(|err| {
+                tracing::warn!(error = %err, "ignoring invalid [index] config in .happy/agent.toml");
+                IndexConfig::default()
+            });
+            self.detect_string_refs = config.detect_string_refs;
+            self.max_call_candidates = config.max_call_candidates;
+            self.fallback_fanout_limit = config.fallback_fanout_limit;
+        }
+
         // Phase 1: Add all elements as nodes
         for elem in elements {
             let node = GraphNode {
@@ -85,66 +179,132 @@ impl RepositoryGraph {
         }
 
         // Phase 1.5: Build GlobalIndex (file→module, module→file, symbol→elements)
-        self.global_index.build(elements, repo_root);
+        self.global_index.build_roots(elements, repo_roots);
+        tracing::debug!(
+            nodes = elements.len(),
+            duration_ms = started.elapsed().as_millis() as u64,
+            "graph phase: nodes and global index"
+        );
+
+        // Phase 2: Build "defines" edges (file -> its children) and
+        // "contains" edges (class -> its members)
+        self.build_structure_edges(elements);
+
+        // Phase 3: Build semantic edges from source code analysis. Each
+        // element is parsed once, in parallel; edges are then resolved and
+        // added in element order.
+        let phase = Instant::now();
+        let facts = extract_facts(elements, self.detect_string_refs);
+        tracing::debug!(
+            duration_ms = phase.elapsed().as_millis() as u64,
+            "graph phase: parse elements"
+        );
+        // Import edges first (populates file_imports for call resolution)
+        let phase = Instant::now();
+        self.build_import_edges(elements, &facts);
+        tracing::debug!(
+            duration_ms = phase.elapsed().as_millis() as u64,
+            "graph phase: import edges"
+        );
+        let phase = Instant::now();
+        self.build_call_edges(elements, &facts);
+        tracing::debug!(
+            duration_ms = phase.elapsed().as_millis() as u64,
+            "graph phase: call edges"
+        );
+        let phase = Instant::now();
+        self.build_inheritance_edges(elements, &facts);
+        self.build_go_type_edges(elements);
+        self.build_rust_impl_edges(elements);
+        tracing::debug!(
+            duration_ms = phase.elapsed().as_millis() as u64,
+            "graph phase: inheritance edges"
+        );
+        if self.detect_string_refs {
+            let phase = Instant::now();
+            self.build_string_ref_edges(elements, &facts);
+            tracing::debug!(
+                duration_ms = phase.elapsed().as_millis() as u64,
+                "graph phase: string reference edges"
+            );
+        }
+        // Phase 4: Group files into packages and modules
+        self.build_module_nodes();
+
+        tracing::info!(
+            nodes = self.graph.node_count(),
+            edges = self.graph.edge_count(),
+            duration_ms = started.elapsed().as_millis() as u64,
+            "built code graph"
+        );
+        let hot_names = self.hot_names(HOT_NAMES_REPORTED);
+        if !hot_names.is_empty() {
+            let summary: Vec<String> = hot_names
+                .iter()
+                .map(|hot| {
+                    format!(
+                        "{} ({} definitions, {} fallbacks skipped)",
+                        hot.name, hot.definitions, hot.skipped_fallbacks
+                    )
+                })
+                .collect();
+            tracing::info!(
+                names = %summary.join(", "),
+                candidates_examined = self.resolution.candidates_examined,
+                "names with pathological fan-out"
+            );
+        }
+    }
 
-        // Phase 2: Build "defines" edges (file -> its children)
+    /// Link each element to the file that defines it, and each method, field,
+    /// or nested class to the class it was extracted from.
+    fn build_structure_edges(&mut self, elements: &[CodeElement]) {
         for elem in elements {
             if elem.element_type == ElementType::File {
                 continue;
             }
-            if let Some(file_nodes) = self.file_to_nodes.get(&elem.file_path) {
-                let file_idx = file_nodes
-                    .iter()
-                    .find(|&&idx| self.graph[idx].kind == NodeKind::File);
-                if let (Some(&file_idx), Some(elem_idx)) = (file_idx, self.id_to_node.get(&elem.id))
-                {
-                    self.graph.add_edge(
-                        file_idx,
-                        *elem_idx,
-                        GraphEdge {
-                            kind: EdgeKind::Defines,
-                        },
-                    );
-                }
+            let Some(elem_idx) = self.id_to_node.get(&elem.id).map(|idx| *idx) else {
+                continue;
+            };
+            if let Some(file_idx) = self.file_node(&elem.file_path) {
+                self.graph
+                    .add_edge(file_idx, elem_idx, GraphEdge::new(EdgeKind::Defines));
+            }
+            if let Some(parent_idx) = elem
+                .parent_id
+                .as_ref()
+                .and_then(|parent_id| self.id_to_node.get(parent_id).map(|idx| *idx))
+            {
+                self.graph
+                    .add_edge(parent_idx, elem_idx, GraphEdge::new(EdgeKind::Contains));
             }
         }
-
-        // Phase 3: Build semantic edges from source code analysis
-        // Import edges first (populates file_imports for call resolution)
-        self.build_import_edges(elements);
-        self.build_call_edges(elements);
-        self.build_inheritance_edges(elements);
     }
 
     /// Build call edges using import-aware resolution.
     ///
+    /// Method calls on `self`, `this`, or `Self` resolve to a method of the
+    /// caller's class, and calls on a class or module name to a member of
+    /// it (see [`Self::resolve_method_call`]). Other calls, and method calls
+    /// whose receiver has no such member, fall through to:
+    ///
     /// Resolution strategy (in priority order):
     /// 1. Same-file match — prefer callee defined in the same file
-    /// 2. GlobalIndex SymbolResolver — use export_map + import context for precise resolution
+    /// 2. GlobalIndex SymbolResolver — the best-scored definition, when an
+    ///    import or shared package backs it
     /// 3. Import-aware heuristic — prefer callee from a file matching an import name
-    /// 4. Fallback — first match by name (least accurate)
-    fn build_call_edges(&mut self, elements: &[CodeElement]) {
-        let mut parser = crate::parser::Parser::new();
-
-        for elem in elements {
-            if elem.element_type != ElementType::Function
-                && elem.element_type != ElementType::Method
-            {
+    /// 4. Fallback — the best-scored (else first) definition, linked with a
+    ///    low-confidence edge when other definitions were visible
+    ///
+    /// Names with more than `max_call_candidates` definitions are resolved by
+    /// [`Self::resolve_hot_call_target`] instead.
+    fn build_call_edges(&mut self, elements: &[CodeElement], facts: &[ElementFacts]) {
+        for (elem, facts) in elements.iter().zip(facts) {
+            let calls = &facts.calls;
+            if calls.is_empty() {
                 continue;
             }
 
-            let lang = match SupportedLanguage::from_extension(&elem.file_path) {
-                Some(l) => l,
-                None => continue,
-            };
-
-            let tree = match parser.parse(&elem.code, lang) {
-                Some(t) => t,
-                None => continue,
-            };
-
-            let calls = extract_calls(&tree, &elem.code, lang);
-
             let caller_idx = match self.id_to_node.get(&elem.id) {
                 Some(idx) => *idx,
                 None => continue,
@@ -157,32 +317,86 @@ impl RepositoryGraph {
                 .map(|v| v.clone())
                 .unwrap_or_default();
 
-            for call in &calls {
+            for call in calls {
                 let callee_name = &call.call_name;
-                if let Some(callee_indices) = self.name_to_nodes.get(callee_name) {
-                    let best_idx = self.resolve_call_target(
-                        callee_name,
-                        &callee_indices,
-                        &elem.file_path,
-                        &imported_names,
-                    );
-
-                    if let Some(callee_idx) = best_idx {
-                        if callee_idx != caller_idx {
-                            self.graph.add_edge(
-                                caller_idx,
-                                callee_idx,
-                                GraphEdge {
-                                    kind: EdgeKind::Calls,
-                                },
-                            );
+                let method_idx =
+                    self.resolve_method_call(call, caller_idx, &elem.file_path, &imported_names);
+                let target = if let Some(idx) = method_idx {
+                    Some(CallTarget::confident(idx))
+                } else {
+                    match self.name_to_nodes.get(callee_name) {
+                        Some(callee_indices) if callee_indices.len() > self.max_call_candidates => {
+                            drop(callee_indices);
+                            self.resolve_hot_call_target(
+                                callee_name,
+                                &elem.file_path,
+                                &imported_names,
+                            )
+                        }
+                        Some(callee_indices) => {
+                            self.resolution.candidates_examined += callee_indices.len();
+                            self.resolve_call_target(
+                                callee_name,
+                                &callee_indices,
+                                &elem.file_path,
+                                &imported_names,
+                            )
                         }
+                        None => None,
                     }
+                };
+
+                if let Some(target) = target
+                    && target.idx != caller_idx
+                {
+                    add_call_edge(&mut self.graph, caller_idx, target);
                 }
             }
         }
     }
 
+    /// Resolve `receiver.method()` from its receiver: a method of the
+    /// caller's innermost class for `self`, `this`, `cls` or `Self`, else a
+    /// member of the class or module the receiver names. Among several
+    /// matches, one in the caller's file wins, then one from an imported
+    /// file. `None` when the receiver is anything else (a local variable, an
+    /// attribute chain) or has no such member.
+    fn resolve_method_call(
+        &self,
+        call: &CallInfo,
+        caller_idx: NodeIndex,
+        caller_file: &str,
+        imported_names: &[String],
+    ) -> Option<NodeIndex> {
+        if call.call_type != CallType::Attribute {
+            return None;
+        }
+        let receiver = call.base_object.as_deref()?;
+        let members = if matches!(receiver, "self" | "this" | "cls" | "Self") {
+            let class = self.enclosing_parent_index(&self.graph[caller_idx])?;
+            self.members_named(class, &call.call_name)
+        } else {
+            self.members_of(receiver, &call.call_name)
+        };
+        let visible: Vec<NodeIndex> = members
+            .into_iter()
+            .filter(|&idx| self.is_visible_from(idx, caller_file, imported_names))
+            .collect();
+
+        let same_file = visible
+            .iter()
+            .find(|&&idx| self.graph[idx].file_path == caller_file);
+        let from_import = || {
+            visible.iter().find(|&&idx| {
+                let file_path = &self.graph[idx].file_path;
+                imported_names
+                    .iter()
+                    .any(|imp| file_path.contains(imp.as_str()))
+            })
+        };
+        same_file.or_else(from_import).or(visible.first()).copied()
+    }
+
     /// Resolve a call target from a list of candidates using layered heuristics.
     fn resolve_call_target(
         &self,
@@ -190,36 +404,158 @@ impl RepositoryGraph {
         candidates: &[NodeIndex],
         caller_file: &str,
         imported_names: &[String],
-    ) -> Option<NodeIndex> {
+    ) -> Option<CallTarget> {
         // Priority 1: Same file
         let same_file = candidates
             .iter()
             .find(|&&idx| self.graph[idx].file_path == caller_file);
         if let Some(&idx) = same_file {
-            return Some(idx);
+            return Some(CallTarget::confident(idx));
         }
 
-        // Priority 2: Use SymbolResolver with import context
+        // Priority 2: The best-scored definition, if an import or the
+        // caller's package backs it
         let resolver = SymbolResolver::new(&self.global_index);
-        let resolved = resolver.resolve_in_context(callee_name, caller_file, imported_names);
-        if !resolved.is_empty() {
-            // Find the first resolved element that's in our candidates
-            for (_file_path, element_id) in &resolved {
-                if let Some(idx_ref) = self.id_to_node.get(element_id) {
-                    let idx = *idx_ref;
-                    if candidates.contains(&idx) {
-                        return Some(idx);
-                    }
-                }
+        let best_scored = resolver
+            .resolve_in_context(callee_name, caller_file, imported_names)
+            .into_iter()
+            .find_map(|scored| {
+                let idx = *self.id_to_node.get(&scored.element_id)?;
+                Some((idx, scored.is_confident()))
+            });
+        if let Some((idx, true)) = best_scored {
+            return Some(CallTarget::confident(idx));
+        }
+
+        // Only definitions the caller's file can see from here on
+        let visible: Vec<NodeIndex> = candidates
+            .iter()
+            .copied()
+            .filter(|&idx| self.is_visible_from(idx, caller_file, imported_names))
+            .collect();
+
+        // Priority 3: Heuristic — from an imported module (file path/name matching)
+        if !imported_names.is_empty() {
+            let from_import = visible.iter().find(|&&idx| {
+                let node = &self.graph[idx];
+                imported_names
+                    .iter()
+                    .any(|imp| node.file_path.contains(imp) || node.name.as_str() == imp.as_str())
+            });
+            if let Some(&idx) = from_import {
+                return Some(CallTarget::confident(idx));
             }
-            // If resolved but not in candidates, use the first resolved element directly
-            let (_file_path, element_id) = &resolved[0];
-            if let Some(idx_ref) = self.id_to_node.get(element_id) {
-                return Some(*idx_ref);
+        }
+
+        // Priority 4: Fallback to the best-scored, else the first, candidate;
+        // a guess unless nothing else could be meant
+        let idx = best_scored
+            .map(|(idx, _)| idx)
+            .or_else(|| visible.first().copied())?;
+        Some(if visible.len() == 1 {
+            CallTarget::confident(idx)
+        } else {
+            CallTarget::guessed(idx)
+        })
+    }
+
+    /// Whether code in `file_path` can reference the node's definition,
+    /// going by the visibility recorded when it was extracted. A private
+    /// definition the file imports by name, as in Python's
+    /// `from .x import _helper`, is visible too.
+    fn is_visible_from(&self, idx: NodeIndex, file_path: &str, imported_names: &[String]) -> bool {
+        let Some(elem) = self.element_arena.get(&self.graph[idx].id) else {
+            return true;
+        };
+        elem.is_visible_from(file_path) || self.is_imported_by_name(&elem, imported_names)
+    }
+
+    /// Whether `imported_names` holds both the element's name and the
+    /// module defining it.
+    fn is_imported_by_name(&self, elem: &CodeElement, imported_names: &[String]) -> bool {
+        if !imported_names.iter().any(|imp| *imp == elem.name) {
+            return false;
+        }
+        let Some(module) = self.global_index.file_to_module(&elem.file_path) else {
+            return false;
+        };
+        imported_names
+            .iter()
+            .any(|imp| *imp == module || module.ends_with(&format!(".{imp}")))
+    }
+
+    /// Resolve a call to a name with more than `max_call_candidates`
+    /// definitions without scanning all of them:
+    /// 1. Same file
+    /// 2. A module the caller's file imports, via the GlobalIndex
+    /// 3. The caller's directory, when one file there defines the name
+    /// 4. Import-aware heuristic over at most `max_call_candidates` definitions
+    /// 5. Fallback to the first of those, unless the name has more than
+    ///    `fallback_fanout_limit` definitions, linked with a low-confidence
+    ///    edge
+    fn resolve_hot_call_target(
+        &mut self,
+        callee_name: &str,
+        caller_file: &str,
+        imported_names: &[String],
+    ) -> Option<CallTarget> {
+        self.resolve_hot_call_scopes(callee_name, caller_file, imported_names)
+            .map(CallTarget::confident)
+            .or_else(|| self.fallback_target(callee_name).map(CallTarget::guessed))
+    }
+
+    /// Priorities 1-4 of [`Self::resolve_hot_call_target`].
+    fn resolve_hot_call_scopes(
+        &mut self,
+        callee_name: &str,
+        caller_file: &str,
+        imported_names: &[String],
+    ) -> Option<NodeIndex> {
+        let scopes = self.name_scopes.get(callee_name)?;
+
+        // Priority 1: Same file
+        let same_file = scopes.in_file(caller_file);
+        self.resolution.candidates_examined += same_file.len();
+        if let Some(&idx) = same_file.first() {
+            return Some(idx);
+        }
+
+        // Priority 2: Defined in an imported module
+        for imported in imported_names {
+            if let Some(file_path) = self.global_index.resolve_module(imported) {
+                let in_module = scopes.in_file(&file_path);
+                self.resolution.candidates_examined += in_module.len();
+                if let Some(&idx) = in_module
+                    .iter()
+                    .find(|&&idx| self.is_visible_from(idx, caller_file, imported_names))
+                {
+                    return Some(idx);
+                }
             }
         }
 
-        // Priority 3: Heuristic — from an imported module (file path/name matching)
+        // Priority 3: Same directory
+        let same_dir = scopes.sole_file_in_dir(caller_file);
+        self.resolution.candidates_examined += same_dir.len();
+        if let Some(&idx) = same_dir
+            .iter()
+            .find(|&&idx| self.is_visible_from(idx, caller_file, imported_names))
+        {
+            return Some(idx);
+        }
+        drop(scopes);
+
+        let candidates: Vec<NodeIndex> = self
+            .name_to_nodes
+            .get(callee_name)?
+            .iter()
+            .take(self.max_call_candidates)
+            .copied()
+            .filter(|&idx| self.is_visible_from(idx, caller_file, imported_names))
+            .collect();
+        self.resolution.candidates_examined += candidates.len();
+
+        // Priority 4: Heuristic — from an imported module (file path/name matching)
         if !imported_names.is_empty() {
             let from_import = candidates.iter().find(|&&idx| {
                 let node = &self.graph[idx];
@@ -231,9 +567,23 @@ impl RepositoryGraph {
                 return Some(idx);
             }
         }
+        None
+    }
 
-        // Priority 4: Fallback to first candidate
-        candidates.first().copied()
+    /// First definition of `name`, unless more than `fallback_fanout_limit`
+    /// share it, in which case the skip is counted and nothing is linked.
+    fn fallback_target(&mut self, name: &str) -> Option<NodeIndex> {
+        let indices = self.name_to_nodes.get(name)?;
+        if indices.len() > self.fallback_fanout_limit {
+            drop(indices);
+            *self
+                .resolution
+                .skipped_fallbacks
+                .entry(name.to_string())
+                .or_default() += 1;
+            return None;
+        }
+        indices.first().copied()
     }
 
     /// Build import edges between files, dispatched by language.
@@ -242,113 +592,130 @@ impl RepositoryGraph {
     /// (proper module-path-based resolution for Python, Java, etc.), falling back
     /// to heuristic name/path matching for other languages or when the GlobalIndex
     /// doesn't have a match.
-    fn build_import_edges(&mut self, elements: &[CodeElement]) {
-        let mut parser = crate::parser::Parser::new();
-
-        for elem in elements {
+    fn build_import_edges(&mut self, elements: &[CodeElement], facts: &[ElementFacts]) {
+        for (elem, facts) in elements.iter().zip(facts) {
             if elem.element_type != ElementType::File {
                 continue;
             }
-
-            let lang = match SupportedLanguage::from_extension(&elem.file_path) {
-                Some(l) => l,
-                None => continue,
-            };
-
-            let tree = match parser.parse(&elem.code, lang) {
-                Some(t) => t,
-                None => continue,
-            };
-
-            let imports = extract_imports(&tree, &elem.code, lang);
+            let imports = &facts.imports;
 
             let file_idx = match self.id_to_node.get(&elem.id) {
                 Some(idx) => *idx,
                 None => continue,
             };
 
-            // Collect imported names for this file (used by call resolution)
+            let resolver = ModuleResolver::new(&self.global_index);
+            let resolved_files: Vec<Option<String>> = imports
+                .iter()
+                .map(|import| resolver.resolve_import(import, &elem.file_path))
+                .collect();
+
+            // Collect imported names for this file (used by call resolution).
+            // Relative imports are recorded by the module they resolved to.
             let mut imported_names = Vec::new();
-            for import in &imports {
-                imported_names.push(import.module.clone());
+            for (import, resolved) in imports.iter().zip(&resolved_files) {
+                let resolved_module = resolved
+                    .as_ref()
+                    .filter(|_| import.level > 0)
+                    .and_then(|file_path| self.global_index.file_to_module(file_path));
+                imported_names.push(resolved_module.unwrap_or_else(|| import.module.clone()));
                 imported_names.extend(import.names.iter().cloned());
             }
             self.file_imports
                 .insert(elem.file_path.clone(), imported_names);
 
-            let module_resolver = ModuleResolver::new(&self.global_index);
-
-            for import in &imports {
+            for (import, resolved) in imports.iter().zip(resolved_files) {
                 // Strategy 1: Use ModuleResolver for proper module-path resolution
-                let resolved_via_index = module_resolver
-                    .resolve_import(import, &elem.file_path)
-                    .and_then(|file_path| {
-                        // Find the File node for this resolved path
-                        self.file_to_nodes.get(&file_path).and_then(|nodes| {
-                            nodes
-                                .iter()
-                                .find(|&&idx| self.graph[idx].kind == NodeKind::File)
-                                .copied()
-                        })
-                    });
+                let resolved_via_index = resolved
+                    .as_deref()
+                    .and_then(|file_path| self.file_node(file_path));
 
                 // Strategy 2: Fallback to heuristic name/path matching
                 let target_idx =
                     resolved_via_index.or_else(|| self.resolve_import_target_heuristic(import));
 
                 if let Some(target_idx) = target_idx {
-                    self.graph.add_edge(
-                        file_idx,
-                        target_idx,
-                        GraphEdge {
-                            kind: EdgeKind::Imports,
-                        },
-                    );
+                    add_semantic_edge(&mut self.graph, file_idx, target_idx, EdgeKind::Imports);
+                }
+
+                // A C# namespace spans any number of files, and `using` it
+                // imports them all.
+                if elem.language == "csharp" {
+                    for file_path in self.global_index.resolve_namespace(&import.module) {
+                        if let Some(idx) = self.file_node(&file_path)
+                            && idx != file_idx
+                            && Some(idx) != target_idx
+                        {
+                            add_semantic_edge(&mut self.graph, file_idx, idx, EdgeKind::Imports);
+                        }
+                    }
                 }
 
                 // Also link to individually imported names (via SymbolResolver)
-                let symbol_resolver = SymbolResolver::new(&self.global_index);
                 for name in &import.names {
                     if name == "*" {
                         continue;
                     }
 
-                    // Try SymbolResolver first (proper export_map lookup)
-                    let resolved = symbol_resolver.resolve(name);
-                    let linked = if !resolved.is_empty() {
+                    // `from pkg import submodule` names a module
+                    let submodule = ModuleResolver::new(&self.global_index)
+                        .resolve_imported_module(import, name, &elem.file_path)
+                        .and_then(|file_path| self.file_node(&file_path));
+
+                    // A symbol defined in the resolved module
+                    let in_module = resolved.as_ref().and_then(|file_path| {
+                        self.file_to_nodes.get(file_path).and_then(|nodes| {
+                            nodes
+                                .iter()
+                                .find(|&&idx| self.graph[idx].name.as_str() == name.as_str())
+                                .copied()
+                        })
+                    });
+
+                    // Try SymbolResolver next (proper export_map lookup)
+                    let linked = submodule.or(in_module).or_else(|| {
+                        let resolved = SymbolResolver::new(&self.global_index).resolve(name);
                         // Link to the first matching element by its ID
-                        let (_file_path, element_id) = &resolved[0];
+                        let (_file_path, element_id) = resolved.first()?;
                         self.id_to_node.get(element_id).map(|idx| *idx)
-                    } else {
-                        None
-                    };
+                    });
 
                     // Fallback to name_to_nodes
-                    let target = linked.or_else(|| {
-                        self.name_to_nodes
-                            .get(name)
-                            .and_then(|indices| indices.first().copied())
-                    });
+                    let target = match linked {
+                        Some(idx) => Some(idx),
+                        None => self.fallback_target(name),
+                    };
 
                     if let Some(target_idx) = target {
-                        self.graph.add_edge(
-                            file_idx,
-                            target_idx,
-                            GraphEdge {
-                                kind: EdgeKind::Imports,
-                            },
-                        );
+                        add_semantic_edge(&mut self.graph, file_idx, target_idx, EdgeKind::Imports);
                     }
                 }
             }
         }
     }
 
+    /// The File node of an indexed file.
+    fn file_node(&self, file_path: &str) -> Option<NodeIndex> {
+        self.file_to_nodes.get(file_path).and_then(|nodes| {
+            nodes
+                .iter()
+                .find(|&&idx| self.graph[idx].kind == NodeKind::File)
+                .copied()
+        })
+    }
+
     /// Heuristic import resolution (fallback when GlobalIndex doesn't resolve).
+    ///
+    /// Relative imports name a module by its position, so a same-named
+    /// module elsewhere in the repo is never a match for one.
     fn resolve_import_target_heuristic(
         &self,
         import: &crate::parser::imports::ImportInfo,
     ) -> Option<NodeIndex> {
+        if import.level > 0 {
+            return None;
+        }
+
         // Try direct module name match
         if let Some(target_indices) = self.name_to_nodes.get(&import.module) {
             if let Some(&idx) = target_indices.first() {
@@ -393,53 +760,189 @@ impl RepositoryGraph {
     }
 
     /// Build inheritance edges, dispatched by language.
-    fn build_inheritance_edges(&mut self, elements: &[CodeElement]) {
-        let mut parser = crate::parser::Parser::new();
-
-        for elem in elements {
-            if elem.element_type != ElementType::Class
-                && elem.element_type != ElementType::Struct
-                && elem.element_type != ElementType::Interface
-            {
+    fn build_inheritance_edges(&mut self, elements: &[CodeElement], facts: &[ElementFacts]) {
+        for (elem, facts) in elements.iter().zip(facts) {
+            if facts.bases.is_empty() {
                 continue;
             }
 
-            let lang = match SupportedLanguage::from_extension(&elem.file_path) {
-                Some(l) => l,
+            let class_idx = match self.id_to_node.get(&elem.id) {
+                Some(idx) => *idx,
                 None => continue,
             };
 
-            let tree = match parser.parse(&elem.code, lang) {
-                Some(t) => t,
-                None => continue,
-            };
+            for base_name in &facts.bases {
+                if let Some(base_idx) = self.fallback_target(base_name) {
+                    add_semantic_edge(&mut self.graph, class_idx, base_idx, EdgeKind::Inherits);
+                }
+            }
+        }
+    }
 
-            let class_idx = match self.id_to_node.get(&elem.id) {
+    /// Build low-confidence `ReferencesByName` edges from functions, methods,
+    /// and module-level code to the symbols and modules their string
+    /// literals spell out, e.g. `getattr(obj, "process_payment")` or
+    /// `import_module("app.tasks")`.
+    ///
+    /// Only literals that are entirely a name or dotted path count (see
+    /// [`string_ref_name`]), and a bare name must resolve to a single
+    /// definition or one in the same file.
+    fn build_string_ref_edges(&mut self, elements: &[CodeElement], facts: &[ElementFacts]) {
+        for (elem, facts) in elements.iter().zip(facts) {
+            if facts.strings.is_empty() {
+                continue;
+            }
+
+            let source_idx = match self.id_to_node.get(&elem.id) {
                 Some(idx) => *idx,
                 None => continue,
             };
 
-            let base_names = extract_base_classes(&tree, lang);
-
-            for base_name in &base_names {
-                if let Some(base_indices) = self.name_to_nodes.get(base_name) {
-                    if let Some(&base_idx) = base_indices.first() {
-                        self.graph.add_edge(
-                            class_idx,
-                            base_idx,
-                            GraphEdge {
-                                kind: EdgeKind::Inherits,
-                            },
-                        );
-                    }
+            // A file only claims the strings outside its definitions; those
+            // inside are attributed to the enclosing function.
+            let definitions: Vec<(usize, usize)> = if elem.element_type == ElementType::File {
+                self.file_to_nodes
+                    .get(&elem.file_path)
+                    .map(|nodes| {
+                        nodes
+                            .iter()
+                            .map(|&idx| &self.graph[idx])
+                            .filter(|node| {
+                                matches!(
+                                    node.kind,
+                                    NodeKind::Function | NodeKind::Method | NodeKind::Class
+                                )
+                            })
+                            .map(|node| (node.start_line, node.end_line))
+                            .collect()
+                    })
+                    .unwrap_or_default()
+            } else {
+                Vec::new()
+            };
+
+            for literal in &facts.strings {
+                let line = elem.start_line + literal.line;
+                if definitions
+                    .iter()
+                    .any(|&(start, end)| (start..=end).contains(&line))
+                {
+                    continue;
+                }
+                let Some(name) = string_ref_name(&literal.value) else {
+                    continue;
+                };
+                if let Some(target_idx) = self.resolve_string_ref(name, &elem.file_path)
+                    && target_idx != source_idx
+                {
+                    add_semantic_edge(
+                        &mut self.graph,
+                        source_idx,
+                        target_idx,
+                        EdgeKind::ReferencesByName,
+                    );
                 }
             }
         }
     }
 
+    /// The node a string names: the file of a module path, a definition in
+    /// the module a dotted path starts with, or a definition with that name
+    /// in `from_file` or nowhere else.
+    fn resolve_string_ref(&self, name: &str, from_file: &str) -> Option<NodeIndex> {
+        let file_node = |file_path: &str| {
+            self.file_to_nodes.get(file_path).and_then(|nodes| {
+                nodes
+                    .iter()
+                    .find(|&&idx| self.graph[idx].kind == NodeKind::File)
+                    .copied()
+            })
+        };
+        if let Some(file_path) = self.global_index.resolve_module(name) {
+            return file_node(&file_path);
+        }
+
+        let (module, symbol) = match name.rsplit_once('.') {
+            Some((module, symbol)) => (Some(module), symbol),
+            None => (None, name),
+        };
+        let candidates: Vec<NodeIndex> = self
+            .name_to_nodes
+            .get(symbol)?
+            .iter()
+            .copied()
+            .filter(|&idx| {
+                !matches!(
+                    self.graph[idx].kind,
+                    NodeKind::File | NodeKind::Module | NodeKind::Variable
+                )
+            })
+            .collect();
+        if let Some(module) = module {
+            let file_path = self.global_index.resolve_module(module)?;
+            return candidates
+                .into_iter()
+                .find(|&idx| self.graph[idx].file_path == file_path);
+        }
+        if let Some(&idx) = candidates
+            .iter()
+            .find(|&&idx| self.graph[idx].file_path == from_file)
+        {
+            return Some(idx);
+        }
+        match candidates.as_slice() {
+            [idx] => Some(*idx),
+            _ => None,
+        }
+    }
+
     /// Get the source code for an element by ID.
+    ///
+    /// An ID that was renamed away resolves to the renamed element.
     pub fn get_source(&self, element_id: &str) -> Option<String> {
-        self.element_arena.get(element_id).map(|e| e.code.clone())
+        let id = self.live_id(element_id)?;
+        self.element_arena.get(&id).map(|e| e.code.to_string())
+    }
+
+    /// Get a clone of an indexed element by ID, following renames.
+    pub fn get_element(&self, element_id: &str) -> Option<CodeElement> {
+        let id = self.live_id(element_id)?;
+        self.element_arena.get(&id).map(|e| e.clone())
+    }
+
+    /// `element_id` if it is indexed, else the live ID it was renamed to.
+    fn live_id(&self, element_id: &str) -> Option<String> {
+        if self.element_arena.contains_key(element_id) {
+            return Some(element_id.to_string());
+        }
+        self.resolve_renamed(element_id).map(|record| record.new_id)
+    }
+
+    /// The rename that replaced `element_id`, when the ID is no longer
+    /// indexed but was renamed to an element that still is.
+    pub fn resolve_renamed(&self, element_id: &str) -> Option<RenameRecord> {
+        if self.element_arena.contains_key(element_id) {
+            return None;
+        }
+        self.renames
+            .resolve(element_id)
+            .filter(|record| self.element_arena.contains_key(&record.new_id))
+    }
+
+    /// Up to `limit` renames detected by `update_file`, newest first.
+    pub fn recent_renames(&self, limit: usize) -> Vec<RenameRecord> {
+        self.renames.recent(limit)
+    }
+
+    /// The rename redirect map, for persisting alongside the index cache.
+    pub fn renames(&self) -> &RenameMap {
+        &self.renames
+    }
+
+    /// Replace the rename redirect map with one loaded from the cache.
+    pub fn restore_renames(&mut self, mut map: RenameMap) {
+        map.prune(renames::unix_now());
+        self.renames = map;
     }
 
     /// Get graph statistics.
@@ -449,7 +952,34 @@ impl RepositoryGraph {
             edge_count: self.graph.edge_count(),
             file_count: self.file_to_nodes.len(),
             element_count: self.element_arena.len(),
+            module_count: self.module_nodes.len(),
+            languages: self.language_breakdown(),
+            roots: self.root_breakdown(),
+        }
+    }
+
+    /// Secrets redacted at index time, summed per pattern over all files.
+    pub fn redaction_counts(&self) -> crate::redact::RedactionCounts {
+        let mut totals = crate::redact::RedactionCounts::new();
+        for entry in self.element_arena.iter() {
+            if entry.value().element_type != crate::indexer::ElementType::File {
+                continue;
+            }
+            for (name, count) in crate::redact::file_redactions(entry.value()) {
+                *totals.entry(name).or_default() += count;
+            }
         }
+        totals
+    }
+
+    /// Whether string literals naming symbols become `ReferencesByName` edges.
+    pub fn detects_string_refs(&self) -> bool {
+        self.detect_string_refs
+    }
+
+    /// Counter that changes whenever nodes or edges are added or removed.
+    pub fn generation(&self) -> u64 {
+        self.generation
     }
 
     /// Get all indexed file paths.
@@ -460,14 +990,40 @@ impl RepositoryGraph {
             .collect()
     }
 
+    /// Whether any indexed element has exactly this name.
+    pub fn has_symbol(&self, name: &str) -> bool {
+        self.name_to_nodes.get(name).is_some_and(|v| !v.is_empty())
+    }
+
     /// Resolve a module name to its file path using the global index.
     pub fn resolve_module(&self, module_name: &str) -> Option<String> {
         self.global_index.resolve_module(module_name)
     }
 
     /// Resolve a symbol name to matching (file_path, element_id) pairs.
+    ///
+    /// Exported definitions come first, then package-visible and private
+    /// ones, which call resolution only links from files that can see them.
+    /// A name with no matches that was recently renamed resolves to the
+    /// renamed element.
     pub fn resolve_symbol(&self, symbol_name: &str) -> Vec<(String, String)> {
-        self.global_index.resolve_symbol(symbol_name)
+        let mut matches = self.global_index.resolve_symbol(symbol_name);
+        if let Some(indices) = self.name_to_nodes.get(symbol_name) {
+            for &idx in indices.iter() {
+                let node = &self.graph[idx];
+                if node.kind != NodeKind::File && !matches.iter().any(|(_, id)| *id == node.id) {
+                    matches.push((node.file_path.clone(), node.id.clone()));
+                }
+            }
+        }
+        if !matches.is_empty() {
+            return matches;
+        }
+        self.renames
+            .find_by_old_name(symbol_name)
+            .and_then(|record| self.get_element(&record.old_id))
+            .map(|elem| vec![(elem.file_path, elem.id)])
+            .unwrap_or_default()
     }
 
     /// Get a clone of all indexed elements.
@@ -482,9 +1038,30 @@ impl RepositoryGraph {
         elements
     }
 
-    /// Get all element IDs associated with a file path.
+    /// Get all element IDs associated with a file path, resolved like
+    /// [`resolve_file`](Self::resolve_file). Empty when it matches no file or
+    /// several.
     /// Used by the file watcher to remove stale BM25 entries before re-indexing.
     pub fn element_ids_for_file(&self, file_path: &str) -> Vec<String> {
+        match self.resolve_file(file_path) {
+            Ok(file_path) => self.element_ids_for_key(&file_path),
+            Err(_) => Vec::new(),
+        }
+    }
+
+    /// Remove all nodes and edges associated with a file. Relative paths and
+    /// suffixes are resolved like [`resolve_file`](Self::resolve_file); an
+    /// ambiguous path removes nothing.
+    pub fn remove_file(&mut self, file_path: &str) {
+        match self.resolve_file(file_path) {
+            Ok(file_path) => self.remove_file_key(&file_path),
+            Err(_) => self.remove_file_key(file_path),
+        }
+        self.build_module_nodes();
+    }
+
+    /// Element IDs stored under the exact indexed path `file_path`.
+    fn element_ids_for_key(&self, file_path: &str) -> Vec<String> {
         self.file_to_nodes
             .get(file_path)
             .map(|nodes| {
@@ -496,19 +1073,22 @@ impl RepositoryGraph {
             .unwrap_or_default()
     }
 
-    /// Remove all nodes and edges associated with a file.
-    pub fn remove_file(&mut self, file_path: &str) {
+    fn remove_file_key(&mut self, file_path: &str) {
         if let Some((_, indices)) = self.file_to_nodes.remove(file_path) {
             for idx in indices {
                 let name = self.graph[idx].name.to_string();
                 if let Some(mut nodes) = self.name_to_nodes.get_mut(&name) {
                     nodes.retain(|&i| i != idx);
                 }
+                if let Some(mut scopes) = self.name_scopes.get_mut(&name) {
+                    scopes.remove(file_path, idx);
+                }
                 let id = self.graph[idx].id.clone();
                 self.id_to_node.remove(&id);
                 self.element_arena.remove(&id);
                 self.graph.remove_node(idx);
             }
+            self.generation += 1;
         }
         self.file_imports.remove(file_path);
     }
@@ -517,9 +1097,23 @@ impl RepositoryGraph {
     ///
     /// Removes all old data for `file_path`, then re-adds `new_elements`
     /// and rebuilds edges (defines, imports, calls, inheritance) for them.
+    /// Old elements that reappear under a new ID with the same structural
+    /// fingerprint are recorded as renames, so their old IDs keep resolving.
     pub fn update_file(&mut self, file_path: &str, new_elements: &[CodeElement], repo_root: &str) {
+        self.generation += 1;
+        // Phase 0: Keep the elements that are about to disappear
+        let new_ids: HashSet<&str> = new_elements.iter().map(|e| e.id.as_str()).collect();
+        // `file_path` is the exact indexed path here; a new file must not
+        // resolve to an indexed one by suffix.
+        let old_ids = self.element_ids_for_key(file_path);
+        let removed: Vec<CodeElement> = old_ids
+            .iter()
+            .filter(|id| !new_ids.contains(id.as_str()))
+            .filter_map(|id| self.element_arena.get(id).map(|e| e.clone()))
+            .collect();
+
         // Phase 1: Remove old data
-        self.remove_file(file_path);
+        self.remove_file_key(file_path);
         self.global_index.remove_file(file_path);
 
         // Phase 2: Add new nodes
@@ -540,29 +1134,65 @@ impl RepositoryGraph {
         self.global_index.build(new_elements, repo_root);
 
         // Phase 4: Rebuild edges for the changed file
-        self.build_import_edges(new_elements);
-        self.build_call_edges(new_elements);
-        self.build_inheritance_edges(new_elements);
+        let facts = extract_facts(new_elements, self.detect_string_refs);
+        self.build_import_edges(new_elements, &facts);
+        self.build_call_edges(new_elements, &facts);
+        self.build_inheritance_edges(new_elements, &facts);
+        if new_elements.iter().any(|elem| elem.language == "rust") {
+            // The type and trait of an impl may live in other files.
+            let impls: Vec<CodeElement> = self
+                .element_arena
+                .iter()
+                .filter(|entry| {
+                    let elem = entry.value();
+                    elem.language == "rust" && elem.metadata.contains_key(IMPL_TRAIT_KEY)
+                })
+                .map(|entry| entry.value().clone())
+                .collect();
+            self.build_rust_impl_edges(&impls);
+        }
+        if new_elements.iter().any(|elem| elem.language == "go") {
+            // Interface satisfaction spans files, so match every Go type.
+            let go_elements: Vec<CodeElement> = self
+                .element_arena
+                .iter()
+                .filter(|entry| entry.value().language == "go")
+                .map(|entry| entry.value().clone())
+                .collect();
+            self.build_go_type_edges(&go_elements);
+        }
+        if self.detect_string_refs {
+            self.build_string_ref_edges(new_elements, &facts);
+        }
 
-        // Phase 5: Rebuild defines edges
-        for elem in new_elements {
-            if elem.element_type == ElementType::File {
-                continue;
-            }
-            if let Some(file_nodes) = self.file_to_nodes.get(&elem.file_path) {
-                let file_idx = file_nodes
-                    .iter()
-                    .find(|&&idx| self.graph[idx].kind == NodeKind::File);
-                if let (Some(&file_idx), Some(elem_idx)) = (file_idx, self.id_to_node.get(&elem.id))
-                {
-                    self.graph.add_edge(
-                        file_idx,
-                        *elem_idx,
-                        GraphEdge {
-                            kind: EdgeKind::Defines,
-                        },
-                    );
-                }
+        // Phase 5: Rebuild defines and contains edges, and module nodes
+        self.build_structure_edges(new_elements);
+        self.build_module_nodes();
+
+        // Phase 6: Record renames
+        if !removed.is_empty() {
+            let old_ids: HashSet<&str> = old_ids.iter().map(String::as_str).collect();
+            let added: Vec<CodeElement> = new_elements
+                .iter()
+                .filter(|e| !old_ids.contains(e.id.as_str()))
+                .cloned()
+                .collect();
+            let now = renames::unix_now();
+            for (old, new) in renames::detect_renames(&removed, &added) {
+                tracing::debug!(
+                    file = %file_path,
+                    old_id = %old.id,
+                    new_id = %new.id,
+                    "detected renamed element"
+                );
+                self.renames.record(RenameRecord {
+                    old_id: old.id,
+                    new_id: new.id,
+                    old_name: old.name,
+                    new_name: new.name,
+                    file_path: file_path.to_string(),
+                    renamed_at: now,
+                });
             }
         }
     }
@@ -580,32 +1210,100 @@ pub struct GraphStats {
     pub edge_count: usize,
     pub file_count: usize,
     pub element_count: usize,
+    /// Python packages, Rust modules with submodules, and Java packages.
+    pub module_count: usize,
+    /// Files and elements per language, most elements first.
+    pub languages: Vec<hotspots::LanguageCount>,
+    /// Files and elements per repository root, when there is more than one.
+    pub roots: Vec<hotspots::RootCount>,
 }
 
-// ── Multi-language inheritance extraction ──────────────────────
+/// A call's resolved target. `low_confidence` when it was picked from
+/// same-named definitions without an import or shared package backing it.
+#[derive(Debug, Clone, Copy)]
+struct CallTarget {
+    idx: NodeIndex,
+    low_confidence: bool,
+}
 
-/// Extract base class/interface names from a class definition, dispatched by language.
-fn extract_base_classes(tree: &tree_sitter::Tree, language: SupportedLanguage) -> Vec<String> {
-    let root = tree.root_node();
-    let mut bases = Vec::new();
-    let mut cursor = root.walk();
-    for child in root.children(&mut cursor) {
-        collect_bases_recursive(&child, language, tree, &mut bases);
+impl CallTarget {
+    fn confident(idx: NodeIndex) -> Self {
+        Self {
+            idx,
+            low_confidence: false,
+        }
+    }
+
+    fn guessed(idx: NodeIndex) -> Self {
+        Self {
+            idx,
+            low_confidence: true,
+        }
+    }
+}
+
+/// Add a call edge to `target`. The edge stays low confidence only while
+/// every occurrence of the call was a guess.
+fn add_call_edge(graph: &mut CodeGraph, from: NodeIndex, target: CallTarget) {
+    let edge_idx = add_semantic_edge(graph, from, target.idx, EdgeKind::Calls);
+    let edge = &mut graph[edge_idx];
+    edge.low_confidence = if edge.count == 1 {
+        target.low_confidence
+    } else {
+        edge.low_confidence && target.low_confidence
+    };
+}
+
+/// Add a semantic edge, merging it into an existing edge of the same kind.
+///
+/// Calls/Imports/Inherits are kept unique per `(from, to, kind)`; a repeat
+/// occurrence bumps the existing edge's `count` instead of adding a parallel
+/// edge, so `edge_count` reflects distinct relationships.
+fn add_semantic_edge(
+    graph: &mut CodeGraph,
+    from: NodeIndex,
+    to: NodeIndex,
+    kind: EdgeKind,
+) -> EdgeIndex {
+    let existing = graph
+        .edges_connecting(from, to)
+        .find(|edge| edge.weight().kind == kind)
+        .map(|edge| edge.id());
+    match existing {
+        Some(edge_idx) => {
+            graph[edge_idx].count += 1;
+            edge_idx
+        }
+        None => graph.add_edge(from, to, GraphEdge::new(kind)),
+    }
+}
+
+// ── Multi-language inheritance extraction ──────────────────────
+
+/// Extract base class/interface names from a class definition, dispatched by language.
+fn extract_base_classes(
+    tree: &tree_sitter::Tree,
+    source: &str,
+    language: SupportedLanguage,
+) -> Vec<String> {
+    let root = tree.root_node();
+    let src = source.as_bytes();
+    let mut bases = Vec::new();
+    let mut cursor = root.walk();
+    for child in root.children(&mut cursor) {
+        collect_bases_recursive(&child, language, src, &mut bases);
     }
     // Also check root itself
-    collect_bases_recursive(&root, language, tree, &mut bases);
+    collect_bases_recursive(&root, language, src, &mut bases);
     bases
 }
 
 fn collect_bases_recursive(
     node: &tree_sitter::Node,
     language: SupportedLanguage,
-    tree: &tree_sitter::Tree,
+    src: &[u8],
     bases: &mut Vec<String>,
 ) {
-    let code_bytes = tree.root_node().utf8_text(&[]).unwrap_or_default();
-    let src = code_bytes.as_bytes();
-
     match language {
         SupportedLanguage::Python => {
             if node.kind() == "class_definition" {
@@ -641,15 +1339,8 @@ fn collect_bases_recursive(
             }
         }
         SupportedLanguage::Rust => {
-            if node.kind() == "impl_item" {
-                // impl Trait for Type — extract trait name
-                if let Some(trait_node) = node.child_by_field_name("trait") {
-                    let name = trait_node.utf8_text(src).unwrap_or_default().to_string();
-                    if !name.is_empty() {
-                        bases.push(name);
-                    }
-                }
-            }
+            // Trait impls link the implementing type, not the impl block,
+            // from element metadata in `rust_impls`.
         }
         SupportedLanguage::Cpp | SupportedLanguage::C => {
             if node.kind() == "class_specifier" || node.kind() == "struct_specifier" {
@@ -663,15 +1354,52 @@ fn collect_bases_recursive(
             }
         }
         SupportedLanguage::Go => {
-            // Go doesn't have class inheritance. Struct embedding is handled
-            // at the field level, not captured here.
+            // Go has no inheritance syntax; embedding and interface
+            // satisfaction are linked from element metadata in `go_types`.
         }
+        SupportedLanguage::CSharp => {
+            if matches!(
+                node.kind(),
+                "class_declaration"
+                    | "interface_declaration"
+                    | "struct_declaration"
+                    | "record_declaration"
+                    | "record_struct_declaration"
+            ) {
+                // class Repo : Base<User>, IRepository, System.IDisposable
+                let mut c = node.walk();
+                for child in node.children(&mut c) {
+                    if child.kind() == "base_list" {
+                        extract_csharp_type_list(&child, src, bases);
+                    }
+                }
+            }
+        }
+        SupportedLanguage::Swift => {
+            // class, struct, enum, actor, and extension declarations are all
+            // `class_declaration`s: `extension Repo: Store<User>, Foundation.Sendable`
+            if matches!(node.kind(), "class_declaration" | "protocol_declaration") {
+                let mut c = node.walk();
+                for child in node.children(&mut c) {
+                    if child.kind() == "inheritance_specifier" {
+                        let text = child.utf8_text(src).unwrap_or_default();
+                        let name = text.split('<').next().unwrap_or(text);
+                        let name = name.rsplit('.').next().unwrap_or(name).trim();
+                        if !name.is_empty() {
+                            bases.push(name.to_string());
+                        }
+                    }
+                }
+            }
+        }
+        // Not parsed.
+        SupportedLanguage::Kotlin => {}
     }
 
     // Recurse
     let mut cursor = node.walk();
     for child in node.children(&mut cursor) {
-        collect_bases_recursive(&child, language, tree, bases);
+        collect_bases_recursive(&child, language, src, bases);
     }
 }
 
@@ -761,6 +1489,28 @@ fn extract_java_type_list(node: &tree_sitter::Node, src: &[u8], bases: &mut Vec<
     }
 }
 
+/// Extract type names from a C# base list, without namespaces or type
+/// arguments: `Base<T>` is `Base`, `System.IDisposable` is `IDisposable`.
+fn extract_csharp_type_list(node: &tree_sitter::Node, src: &[u8], bases: &mut Vec<String>) {
+    let mut cursor = node.walk();
+    for child in node.named_children(&mut cursor) {
+        let ty = match child.kind() {
+            // record Admin(string Name) : User(Name)
+            "primary_constructor_base_type" => match child.named_child(0) {
+                Some(ty) => ty,
+                None => continue,
+            },
+            _ => child,
+        };
+        let text = ty.utf8_text(src).unwrap_or_default();
+        let name = text.split('<').next().unwrap_or(text);
+        let name = name.rsplit('.').next().unwrap_or(name).trim();
+        if !name.is_empty() && ty.kind() != "argument_list" {
+            bases.push(name.to_string());
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -783,10 +1533,11 @@ mod tests {
             language: "python".to_string(),
             start_line: 1,
             end_line: 5,
-            code: code.to_string(),
+            code: code.into(),
             signature: None,
             docstring: None,
             summary: None,
+            parent_id: None,
             metadata: HashMap::new(),
         }
     }
@@ -817,6 +1568,546 @@ mod tests {
         assert!(graph.get_source("func_foo").is_some());
     }
 
+    #[test]
+    fn test_private_helpers_resolve_within_their_file() {
+        let private = |id: &str, file: &str, code: &str| {
+            let mut elem = make_element(id, "_normalize", ElementType::Function, file, code);
+            elem.metadata
+                .insert(VISIBILITY_KEY.into(), Visibility::Private.as_str().into());
+            elem
+        };
+        let elements = vec![
+            make_element("file_a", "a.py", ElementType::File, "a.py", ""),
+            private(
+                "func_a_normalize",
+                "a.py",
+                "def _normalize(x):\n    return x\n",
+            ),
+            make_element(
+                "func_run",
+                "run",
+                ElementType::Function,
+                "a.py",
+                "def run(x):\n    return _normalize(x)\n",
+            ),
+            make_element("file_b", "b.py", ElementType::File, "b.py", ""),
+            private(
+                "func_b_normalize",
+                "b.py",
+                "def _normalize(x):\n    return x.strip()\n",
+            ),
+            make_element(
+                "func_clean",
+                "clean",
+                ElementType::Function,
+                "b.py",
+                "def clean(x):\n    return _normalize(x)\n",
+            ),
+            make_element("file_c", "c.py", ElementType::File, "c.py", ""),
+            make_element(
+                "func_go",
+                "go",
+                ElementType::Function,
+                "c.py",
+                "def go(x):\n    return _normalize(x)\n",
+            ),
+            make_element(
+                "file_d",
+                "d.py",
+                ElementType::File,
+                "d.py",
+                "from .a import _normalize\n",
+            ),
+            make_element(
+                "func_tidy",
+                "tidy",
+                ElementType::Function,
+                "d.py",
+                "def tidy(x):\n    return _normalize(x)\n",
+            ),
+        ];
+
+        let mut graph = RepositoryGraph::new();
+        graph.build_from_elements(&elements, "");
+
+        let callees = |name: &str| -> Vec<String> {
+            graph
+                .find_callees(name)
+                .into_iter()
+                .map(|n| n.id.clone())
+                .collect()
+        };
+        assert_eq!(callees("run"), vec!["func_a_normalize"]);
+        assert_eq!(callees("clean"), vec!["func_b_normalize"]);
+        // Neither helper is visible from c.py.
+        assert!(callees("go").is_empty());
+        // d.py imports a.py's helper by name.
+        assert_eq!(callees("tidy"), vec!["func_a_normalize"]);
+        // Both stay findable by name.
+        assert_eq!(graph.resolve_symbol("_normalize").len(), 2);
+    }
+
+    #[test]
+    fn test_method_calls_resolve_through_receiver() {
+        let at = |mut elem: CodeElement, start_line: usize, end_line: usize| {
+            elem.start_line = start_line;
+            elem.end_line = end_line;
+            elem
+        };
+        let method = |id: &str, name: &str, code: &str| {
+            make_element(id, name, ElementType::Method, "models.py", code)
+        };
+        let elements = vec![
+            make_element(
+                "file_models",
+                "models.py",
+                ElementType::File,
+                "models.py",
+                "",
+            ),
+            at(
+                make_element(
+                    "class_parser",
+                    "Parser",
+                    ElementType::Class,
+                    "models.py",
+                    "",
+                ),
+                1,
+                6,
+            ),
+            at(
+                method("parser_parse", "parse", "def parse(self):\n    pass\n"),
+                2,
+                3,
+            ),
+            at(
+                method(
+                    "parser_run",
+                    "run",
+                    "def run(self):\n    return self.parse()\n",
+                ),
+                5,
+                6,
+            ),
+            at(
+                make_element("class_lexer", "Lexer", ElementType::Class, "models.py", ""),
+                8,
+                13,
+            ),
+            at(
+                method("lexer_parse", "parse", "def parse(self):\n    pass\n"),
+                9,
+                10,
+            ),
+            at(
+                method(
+                    "lexer_tokenize",
+                    "tokenize",
+                    "def tokenize(self):\n    return self.parse()\n",
+                ),
+                12,
+                13,
+            ),
+            make_element("file_main", "main.py", ElementType::File, "main.py", ""),
+            make_element(
+                "func_main",
+                "main",
+                ElementType::Function,
+                "main.py",
+                "def main(source):\n    return Lexer.parse(source)\n",
+            ),
+            make_element(
+                "func_load",
+                "load",
+                ElementType::Function,
+                "main.py",
+                "def load(thing):\n    return thing.parse()\n",
+            ),
+        ];
+
+        let mut graph = RepositoryGraph::new();
+        graph.build_from_elements(&elements, "");
+
+        let callees = |name: &str| -> Vec<String> {
+            graph
+                .find_callees(name)
+                .into_iter()
+                .map(|n| n.id.clone())
+                .collect()
+        };
+        assert_eq!(callees("run"), vec!["parser_parse"]);
+        assert_eq!(callees("tokenize"), vec!["lexer_parse"]);
+        // A class name as the receiver picks that class's method.
+        assert_eq!(callees("main"), vec!["lexer_parse"]);
+        // An unknown receiver falls back to the name heuristics.
+        assert_eq!(callees("load"), vec!["parser_parse"]);
+    }
+
+    #[test]
+    fn test_relative_imports_pick_the_nearby_module() {
+        let views = "from ..utils import helper\nfrom . import sibling\n";
+        let elements = vec![
+            // The same module names under another package, indexed first.
+            make_element(
+                "b_utils",
+                "utils.py",
+                ElementType::File,
+                "pkg_b/utils.py",
+                "",
+            ),
+            make_element(
+                "b_helper",
+                "helper",
+                ElementType::Function,
+                "pkg_b/utils.py",
+                "def helper():\n    pass\n",
+            ),
+            make_element(
+                "b_sibling",
+                "sibling.py",
+                ElementType::File,
+                "pkg_b/api/sibling.py",
+                "",
+            ),
+            make_element(
+                "a_utils",
+                "utils.py",
+                ElementType::File,
+                "pkg_a/utils.py",
+                "",
+            ),
+            make_element(
+                "a_helper",
+                "helper",
+                ElementType::Function,
+                "pkg_a/utils.py",
+                "def helper():\n    pass\n",
+            ),
+            make_element(
+                "a_sibling",
+                "sibling.py",
+                ElementType::File,
+                "pkg_a/api/sibling.py",
+                "",
+            ),
+            make_element(
+                "a_views",
+                "views.py",
+                ElementType::File,
+                "pkg_a/api/views.py",
+                views,
+            ),
+            make_element(
+                "a_show",
+                "show",
+                ElementType::Function,
+                "pkg_a/api/views.py",
+                "def show():\n    return helper()\n",
+            ),
+        ];
+
+        let mut graph = RepositoryGraph::new();
+        graph.build_from_elements(&elements, "");
+
+        let mut deps: Vec<String> = graph
+            .get_dependencies("pkg_a/api/views.py")
+            .unwrap()
+            .into_iter()
+            .map(|n| n.id.clone())
+            .collect();
+        deps.sort();
+        assert_eq!(deps, vec!["a_helper", "a_sibling", "a_utils"]);
+
+        let callees: Vec<&str> = graph
+            .find_callees("show")
+            .into_iter()
+            .map(|n| n.id.as_str())
+            .collect();
+        assert_eq!(callees, vec!["a_helper"]);
+    }
+
+    #[test]
+    #[cfg(feature = "lang-rust")]
+    fn test_rust_use_paths_resolve_to_module_files() {
+        let file = |id: &str, path: &str, code: &str| {
+            make_element(id, path, ElementType::File, path, code)
+        };
+        let elements = vec![
+            // Another crate with the same module layout.
+            file("other_lib", "other/src/lib.rs", "mod parser;\n"),
+            file("other_calls", "other/src/parser/calls.rs", ""),
+            file("lib", "app/src/lib.rs", "mod parser;\nmod graph;\n"),
+            file("parser", "app/src/parser/mod.rs", "pub mod calls;\n"),
+            file("calls", "app/src/parser/calls.rs", ""),
+            make_element(
+                "extract_calls",
+                "extract_calls",
+                ElementType::Function,
+                "app/src/parser/calls.rs",
+                "pub fn extract_calls() {}\n",
+            ),
+            file(
+                "graph",
+                "app/src/graph.rs",
+                "mod queries;\nuse crate::parser::calls::extract_calls;\n",
+            ),
+            file(
+                "queries",
+                "app/src/graph/queries.rs",
+                "#[cfg(test)]\nmod tests {\n    use super::super::RepositoryGraph;\n}\n",
+            ),
+            file(
+                "other_main",
+                "other/src/main.rs",
+                "use app::parser::calls;\n",
+            ),
+        ];
+
+        let mut graph = RepositoryGraph::new();
+        graph.build_from_elements(&elements, "");
+
+        let deps = |file_path: &str| -> Vec<String> {
+            let mut ids: Vec<String> = graph
+                .get_dependencies(file_path)
+                .unwrap()
+                .into_iter()
+                .map(|n| n.id.clone())
+                .collect();
+            ids.sort();
+            ids
+        };
+        assert_eq!(deps("app/src/lib.rs"), vec!["graph", "parser"]);
+        assert_eq!(deps("app/src/parser/mod.rs"), vec!["calls"]);
+        assert_eq!(
+            deps("app/src/graph.rs"),
+            vec!["calls", "extract_calls", "queries"]
+        );
+        // `super::` inside `mod tests` is relative to the file's module.
+        assert_eq!(deps("app/src/graph/queries.rs"), vec!["graph"]);
+        // Another workspace crate, by name.
+        assert_eq!(deps("other/src/main.rs"), vec!["calls"]);
+    }
+
+    #[cfg(feature = "lang-typescript")]
+    #[test]
+    fn test_ts_aliases_and_index_files_resolve() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().to_string_lossy().into_owned();
+        std::fs::write(
+            dir.path().join("tsconfig.json"),
+            r#"{ "compilerOptions": { "baseUrl": "src", "paths": { "@app/*": ["app/*"] } } }"#,
+        )
+        .unwrap();
+        let file = |id: &str, rel: &str, code: &str| {
+            let path = format!("{root}/{rel}");
+            make_element(id, rel, ElementType::File, &path, code)
+        };
+        let elements = vec![
+            file(
+                "main",
+                "src/main.ts",
+                "import { Foo } from \"@app/services\";\nimport { Button } from \"./components\";\n",
+            ),
+            file("services", "src/app/services/index.ts", ""),
+            file("components", "src/components/index.tsx", ""),
+            // Would match a substring search for "services"
+            file("decoy", "src/legacy/services.ts", ""),
+        ];
+
+        let mut graph = RepositoryGraph::new();
+        graph.build_from_elements(&elements, &root);
+
+        let mut deps: Vec<String> = graph
+            .get_dependencies(&format!("{root}/src/main.ts"))
+            .unwrap()
+            .into_iter()
+            .map(|n| n.id.clone())
+            .collect();
+        deps.sort();
+        assert_eq!(deps, vec!["components", "services"]);
+    }
+
+    #[cfg(feature = "lang-csharp")]
+    #[test]
+    fn test_csharp_usings_resolve_to_namespace_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        let write = |rel: &str, code: &str| {
+            let path = root.join(rel);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, code).unwrap();
+        };
+        write(
+            "Models/User.cs",
+            "namespace My.Company.Models\n{\n    public class User {}\n\n    public interface IEntity {}\n}\n",
+        );
+        write(
+            "Models/Admin.cs",
+            "namespace My.Company.Models;\n\npublic class Admin : User, IEntity {}\n",
+        );
+        write(
+            "Services/Guard.cs",
+            "namespace My.Company.Services;\n\npublic static class Guard\n{\n    public static void NotNegative(int value) {}\n}\n",
+        );
+        write(
+            "Services/UserService.cs",
+            r#"using My.Company.Models;
+using static My.Company.Services.Guard;
+
+namespace My.Company.Services
+{
+    public interface IUserService
+    {
+        User Find(int id);
+    }
+
+    public class UserService : IUserService
+    {
+        public User Find(int id)
+        {
+            Guard.NotNegative(id);
+            return new User();
+        }
+    }
+}
+"#,
+        );
+        let root = root.to_string_lossy().to_string();
+        let elements = crate::indexer::walk_and_index(&root, &Default::default());
+        let mut graph = RepositoryGraph::new();
+        graph.build_from_elements(&elements, &root);
+
+        let mut deps: Vec<String> = graph
+            .get_dependencies(&format!("{root}/Services/UserService.cs"))
+            .unwrap()
+            .into_iter()
+            .map(|n| n.name.to_string())
+            .collect();
+        deps.sort();
+        assert_eq!(deps, vec!["Admin.cs", "Guard", "Guard.cs", "User.cs"]);
+
+        let subclasses = |name: &str| -> Vec<String> {
+            let mut names: Vec<String> = graph
+                .get_subclasses(name)
+                .into_iter()
+                .map(|n| n.name.to_string())
+                .collect();
+            names.sort();
+            names
+        };
+        assert_eq!(subclasses("User"), vec!["Admin"]);
+        assert_eq!(subclasses("IEntity"), vec!["Admin"]);
+        assert_eq!(subclasses("IUserService"), vec!["UserService"]);
+        let callers: Vec<&str> = graph
+            .find_callers("NotNegative")
+            .into_iter()
+            .map(|n| n.name.as_str())
+            .collect();
+        assert_eq!(callers, vec!["Find"]);
+    }
+
+    #[cfg(feature = "lang-swift")]
+    #[test]
+    fn test_swift_inheritance_and_calls() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        let write = |rel: &str, code: &str| {
+            let path = root.join(rel);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, code).unwrap();
+        };
+        write(
+            "Sources/Models/User.swift",
+            "public protocol Entity {}\n\npublic class User: Entity {}\n\npublic class Admin: User {}\n",
+        );
+        write(
+            "Sources/App/UserService.swift",
+            r#"import struct Models.User
+
+protocol Store {
+    func find(id: Int) -> User?
+}
+
+struct UserService: Store {
+    func find(id: Int) -> User? {
+        validate(id)
+        return nil
+    }
+}
+
+extension UserService: CustomStringConvertible {
+    var description: String { "users" }
+}
+
+func validate(_ id: Int) {}
+"#,
+        );
+        let root = root.to_string_lossy().to_string();
+        let elements = crate::indexer::walk_and_index(&root, &Default::default());
+        let mut graph = RepositoryGraph::new();
+        graph.build_from_elements(&elements, &root);
+
+        let deps: Vec<String> = graph
+            .get_dependencies(&format!("{root}/Sources/App/UserService.swift"))
+            .unwrap()
+            .into_iter()
+            .map(|n| n.name.to_string())
+            .collect();
+        assert!(deps.contains(&"User".to_string()), "{deps:?}");
+
+        let subclasses = |name: &str| -> Vec<String> {
+            let mut names: Vec<String> = graph
+                .get_subclasses(name)
+                .into_iter()
+                .map(|n| n.name.to_string())
+                .collect();
+            names.sort();
+            names
+        };
+        assert_eq!(subclasses("Entity"), vec!["User"]);
+        assert_eq!(subclasses("User"), vec!["Admin"]);
+        assert_eq!(subclasses("Store"), vec!["UserService"]);
+        let callers: Vec<&str> = graph
+            .find_callers("validate")
+            .into_iter()
+            .map(|n| n.name.as_str())
+            .collect();
+        assert_eq!(callers, vec!["find"]);
+    }
+
+    #[test]
+    fn test_content_only_files_get_no_semantic_edges() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        std::fs::write(root.join("Util.kt"), "fun helper() {}\n").unwrap();
+        std::fs::write(
+            root.join("Main.kt"),
+            "import com.example.Util\n\nfun main() {\n    helper()\n}\n",
+        )
+        .unwrap();
+        let root = root.to_string_lossy().to_string();
+        let elements = crate::indexer::walk_and_index(&root, &Default::default());
+        let mut graph = RepositoryGraph::new();
+        graph.build_from_elements(&elements, &root);
+
+        let kinds: Vec<NodeKind> = graph.graph.node_weights().map(|n| n.kind).collect();
+        assert_eq!(kinds.iter().filter(|k| **k == NodeKind::File).count(), 2);
+        assert_eq!(kinds.iter().filter(|k| **k == NodeKind::Chunk).count(), 2);
+        assert!(
+            graph
+                .graph
+                .edge_weights()
+                .all(|edge| edge.kind == EdgeKind::Defines)
+        );
+        assert!(
+            graph
+                .get_dependencies(&format!("{root}/Main.kt"))
+                .unwrap()
+                .is_empty()
+        );
+    }
+
     #[test]
     fn test_remove_file() {
         let elements = vec![
@@ -894,6 +2185,214 @@ mod tests {
         assert!(graph.stats().edge_count > 0, "Should have edges");
     }
 
+    #[test]
+    fn test_imported_definition_beats_distant_namesake() {
+        let main = "from app.util import helper\n\ndef run():\n    return helper()\n";
+        let page = "def render():\n    return helper()\n";
+        let elements = vec![
+            // An unimported namesake far away, indexed first.
+            make_element(
+                "vendor_file",
+                "helpers.py",
+                ElementType::File,
+                "vendor/deep/lib/helpers.py",
+                "def helper():\n    return 0\n",
+            ),
+            make_element(
+                "vendor_helper",
+                "helper",
+                ElementType::Function,
+                "vendor/deep/lib/helpers.py",
+                "def helper():\n    return 0\n",
+            ),
+            make_element(
+                "util_file",
+                "util.py",
+                ElementType::File,
+                "app/util.py",
+                "def helper():\n    return 1\n",
+            ),
+            make_element(
+                "app_helper",
+                "helper",
+                ElementType::Function,
+                "app/util.py",
+                "def helper():\n    return 1\n",
+            ),
+            make_element(
+                "main_file",
+                "main.py",
+                ElementType::File,
+                "app/main.py",
+                main,
+            ),
+            make_element(
+                "run",
+                "run",
+                ElementType::Function,
+                "app/main.py",
+                "def run():\n    return helper()\n",
+            ),
+            make_element(
+                "page_file",
+                "page.py",
+                ElementType::File,
+                "app/views/page.py",
+                page,
+            ),
+            make_element(
+                "render",
+                "render",
+                ElementType::Function,
+                "app/views/page.py",
+                page,
+            ),
+        ];
+
+        let mut graph = RepositoryGraph::new();
+        graph.build_from_elements(&elements, "");
+        let filter = queries::QueryFilter::default();
+
+        let ids = |nodes: Vec<&GraphNode>| -> Vec<String> {
+            nodes.into_iter().map(|n| n.id.clone()).collect()
+        };
+        assert_eq!(ids(graph.find_callees("run")), vec!["app_helper"]);
+        assert!(graph.low_confidence_callees("run", &filter).is_empty());
+
+        // Without an import the nearest definition is linked, as a guess.
+        assert_eq!(ids(graph.find_callees("render")), vec!["app_helper"]);
+        assert_eq!(
+            graph.low_confidence_callees("render", &filter),
+            HashSet::from(["app_helper".to_string()])
+        );
+        assert_eq!(
+            graph.low_confidence_callers("helper", &filter),
+            HashSet::from(["render".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_repeated_calls_share_one_edge() {
+        let elements = vec![
+            make_element(
+                "file_a",
+                "a.py",
+                ElementType::File,
+                "a.py",
+                "def helper():\n    pass\n\ndef caller():\n    helper()\n    helper()\n    helper()\n",
+            ),
+            make_element(
+                "func_helper",
+                "helper",
+                ElementType::Function,
+                "a.py",
+                "def helper():\n    pass\n",
+            ),
+            make_element(
+                "func_caller",
+                "caller",
+                ElementType::Function,
+                "a.py",
+                "def caller():\n    helper()\n    helper()\n    helper()\n",
+            ),
+        ];
+
+        let mut graph = RepositoryGraph::new();
+        graph.build_from_elements(&elements, "");
+
+        let caller_idx = *graph.id_to_node.get("func_caller").unwrap();
+        let helper_idx = *graph.id_to_node.get("func_helper").unwrap();
+        let call_edges: Vec<_> = graph
+            .graph
+            .edges_connecting(caller_idx, helper_idx)
+            .filter(|e| e.weight().kind == EdgeKind::Calls)
+            .collect();
+        assert_eq!(call_edges.len(), 1);
+        assert!(call_edges[0].weight().count >= 2);
+
+        let callers = graph.find_callers("helper");
+        assert_eq!(callers.len(), 1);
+        assert_eq!(callers[0].id, "func_caller");
+        assert_eq!(graph.find_callees("caller").len(), 1);
+    }
+
+    #[test]
+    fn test_pathological_fanout_is_bounded() {
+        const GENERATED: usize = 5000;
+        let mut elements = Vec::new();
+        for i in 0..GENERATED {
+            let file = format!("gen/f{i}_pb2.py");
+            elements.push(make_element(
+                &format!("gen_{i}_descriptor"),
+                "descriptor",
+                ElementType::Function,
+                &file,
+                "def descriptor():\n    pass\n",
+            ));
+            elements.push(make_element(
+                &format!("gen_{i}_build"),
+                &format!("build_{i}"),
+                ElementType::Function,
+                &file,
+                &format!("def build_{i}():\n    return descriptor()\n"),
+            ));
+        }
+        elements.extend([
+            make_element(
+                "app_descriptor",
+                "descriptor",
+                ElementType::Function,
+                "app/helpers.py",
+                "def descriptor():\n    pass\n",
+            ),
+            make_element(
+                "app_run",
+                "run",
+                ElementType::Function,
+                "app/main.py",
+                "def run():\n    return descriptor()\n",
+            ),
+            make_element(
+                "svc_handle",
+                "handle",
+                ElementType::Function,
+                "svc/api.py",
+                "def handle():\n    return descriptor()\n",
+            ),
+        ]);
+
+        let mut graph = RepositoryGraph::new();
+        graph.build_from_elements(&elements, "");
+
+        let callee_ids = |name: &str| -> Vec<String> {
+            graph
+                .find_callees(name)
+                .into_iter()
+                .map(|node| node.id.clone())
+                .collect()
+        };
+        assert_eq!(callee_ids("build_42"), vec!["gen_42_descriptor"]);
+        // The only definition in the caller's directory wins.
+        assert_eq!(callee_ids("run"), vec!["app_descriptor"]);
+        // Nothing narrows the 5001 definitions down, so no edge is guessed.
+        assert!(callee_ids("handle").is_empty());
+
+        // One same-file hit per generated caller, one same-directory hit for
+        // `run`, and one capped candidate list for `handle`.
+        assert_eq!(
+            graph.call_candidates_examined(),
+            GENERATED + 1 + DEFAULT_MAX_CALL_CANDIDATES
+        );
+        assert_eq!(
+            graph.hot_names(HOT_NAMES_REPORTED),
+            vec![fanout::HotName {
+                name: "descriptor".to_string(),
+                definitions: GENERATED + 1,
+                skipped_fallbacks: 1,
+            }]
+        );
+    }
+
     #[test]
     fn test_update_file() {
         // Build initial graph with two files
@@ -952,8 +2451,14 @@ mod tests {
         ];
         graph.update_file("a.py", &new_elements, "");
 
-        // Old element should be gone
-        assert!(graph.get_source("func_foo").is_none());
+        // Old element should be gone (its ID now redirects to the rename)
+        assert!(
+            !graph
+                .element_ids_for_file("a.py")
+                .iter()
+                .any(|id| id == "func_foo")
+        );
+        assert_eq!(graph.get_source("func_foo"), graph.get_source("func_baz"));
         // New element should be present
         assert!(graph.get_source("func_baz").is_some());
         // b.py should still be intact
@@ -1031,4 +2536,184 @@ mod tests {
         assert_eq!(snapshot[0].id, "a_file");
         assert_eq!(snapshot[1].id, "z_func");
     }
+
+    #[test]
+    fn test_update_file_records_renames() {
+        let load_body = "def load(path):\n    with open(path) as f:\n        return f.read()\n";
+        let elements = vec![
+            make_element("file_a", "a.py", ElementType::File, "a.py", ""),
+            make_element(
+                "func_load",
+                "load",
+                ElementType::Function,
+                "a.py",
+                load_body,
+            ),
+            make_element(
+                "func_gone",
+                "gone",
+                ElementType::Function,
+                "a.py",
+                "def gone():\n    return 1\n",
+            ),
+        ];
+        let mut graph = RepositoryGraph::new();
+        graph.build_from_elements(&elements, "");
+
+        // Rename load -> read_file, delete gone, add an unrelated function.
+        let new_elements = vec![
+            make_element("file_a", "a.py", ElementType::File, "a.py", ""),
+            make_element(
+                "func_read_file",
+                "read_file",
+                ElementType::Function,
+                "a.py",
+                &load_body.replace("load", "read_file"),
+            ),
+            make_element(
+                "func_fresh",
+                "fresh",
+                ElementType::Function,
+                "a.py",
+                "def fresh():\n    return 2\n",
+            ),
+        ];
+        graph.update_file("a.py", &new_elements, "");
+
+        let record = graph.resolve_renamed("func_load").unwrap();
+        assert_eq!(record.new_id, "func_read_file");
+        assert_eq!(record.old_name, "load");
+        assert_eq!(record.new_name, "read_file");
+        assert_eq!(
+            graph.get_source("func_load"),
+            graph.get_source("func_read_file")
+        );
+        assert_eq!(
+            graph.resolve_symbol("load"),
+            vec![("a.py".to_string(), "func_read_file".to_string())]
+        );
+        assert!(graph.resolve_renamed("func_read_file").is_none());
+
+        // A deleted element without a structural match gets no redirect.
+        assert!(graph.resolve_renamed("func_gone").is_none());
+        assert!(graph.get_source("func_gone").is_none());
+        assert_eq!(graph.recent_renames(10), vec![record]);
+    }
+
+    /// `files` Python modules in packages of 50, each importing the one
+    /// before it (the first imports the last), subclassing its class, and
+    /// calling its helper.
+    fn write_synthetic_repo(root: &Path, files: usize) {
+        for i in 0..files {
+            let dir = root.join(format!("pkg{}", i / 50));
+            std::fs::create_dir_all(&dir).unwrap();
+            let prev = (i + files - 1) % files;
+            let code = format!(
+                "from pkg{}.mod{prev} import Base{prev}, helper{prev}\n\n\
+                 class Base{i}(Base{prev}):\n    def run(self):\n        return self.step()\n\n\
+                 \x20   def step(self):\n        return helper{prev}()\n\n\
+                 def helper{i}():\n    return helper{prev}() + len(\"pkg{}.mod{prev}\")\n",
+                prev / 50,
+                prev / 50,
+            );
+            std::fs::write(dir.join(format!("mod{i}.py")), code).unwrap();
+        }
+    }
+
+    /// Build a graph on a rayon pool of `threads` threads and list its
+    /// edges in insertion order.
+    fn build_edges(
+        elements: &[CodeElement],
+        root: &str,
+        threads: usize,
+    ) -> Vec<(String, String, EdgeKind, u32)> {
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build()
+            .unwrap();
+        pool.install(|| {
+            let mut repo = RepositoryGraph::new();
+            repo.build_from_elements(elements, root);
+            repo.graph
+                .edge_indices()
+                .map(|edge| {
+                    let (from, to) = repo.graph.edge_endpoints(edge).unwrap();
+                    let weight = &repo.graph[edge];
+                    (
+                        repo.graph[from].id.clone(),
+                        repo.graph[to].id.clone(),
+                        weight.kind,
+                        weight.count,
+                    )
+                })
+                .collect()
+        })
+    }
+
+    #[test]
+    fn test_parallel_build_matches_sequential() {
+        let dir = tempfile::tempdir().unwrap();
+        write_synthetic_repo(dir.path(), 120);
+        std::fs::create_dir_all(dir.path().join(".happy")).unwrap();
+        std::fs::write(
+            dir.path().join(".happy/agent.toml"),
+            "[index]\ndetect_string_refs = true\n",
+        )
+        .unwrap();
+        let root = dir
+            .path()
+            .canonicalize()
+            .unwrap()
+            .to_string_lossy()
+            .to_string();
+        let elements = crate::indexer::walk_and_index(&root, &Default::default());
+
+        let sequential = build_edges(&elements, &root, 1);
+        let parallel = build_edges(&elements, &root, 4);
+        assert_eq!(parallel, sequential);
+        for kind in [
+            EdgeKind::Imports,
+            EdgeKind::Calls,
+            EdgeKind::Inherits,
+            EdgeKind::ReferencesByName,
+        ] {
+            assert!(
+                sequential.iter().any(|edge| edge.2 == kind),
+                "no {kind:?} edges"
+            );
+        }
+    }
+
+    /// Times the graph build over 5,000 files on one thread and on all of
+    /// them. Run with `cargo test --release -p happy-core bench_build -- --ignored --nocapture`.
+    #[test]
+    #[ignore]
+    fn bench_build_synthetic_repo() {
+        let dir = tempfile::tempdir().unwrap();
+        write_synthetic_repo(dir.path(), 5_000);
+        let root = dir
+            .path()
+            .canonicalize()
+            .unwrap()
+            .to_string_lossy()
+            .to_string();
+        let started = Instant::now();
+        let elements = crate::indexer::walk_and_index(&root, &Default::default());
+        let walk = started.elapsed();
+
+        let started = Instant::now();
+        let sequential = build_edges(&elements, &root, 1);
+        let one_thread = started.elapsed();
+        let started = Instant::now();
+        let parallel = build_edges(&elements, &root, rayon::current_num_threads());
+        let all_threads = started.elapsed();
+
+        assert_eq!(parallel, sequential);
+        eprintln!(
+            "{} elements, {} edges: walk {walk:?}, build on 1 thread {one_thread:?}, on {} threads {all_threads:?}",
+            elements.len(),
+            parallel.len(),
+            rayon::current_num_threads(),
+        );
+    }
 }
@@ -0,0 +1,466 @@
+//! Module nodes, so a package can be queried as a whole.
+//!
+//! A Python package is a directory with an `__init__.py`, named by its
+//! dotted module path (`src.app.services`) and holding the `.py` files
+//! directly in it. A Rust module with child modules is named by its path
+//! in the crate's module tree (`my_crate::graph`) and holds the file
+//! declaring it and the files of its leaf children; the crate root holds
+//! `lib.rs` or `main.rs`. A Java package is named by the `package`
+//! declaration of its files (`com.acme.billing`).
+//!
+//! Each module `Defines` its files and `Contains` its subpackages (Java
+//! packages are not nested). Imports from a file in one module to a file
+//! in another become an `Imports` edge between the modules, whose count is
+//! the number of distinct importing and imported file pairs. Module nodes
+//! are graph-only: they have no element, file entry, or symbol name, and
+//! are rebuilt whenever a file changes.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::Path;
+
+use petgraph::stable_graph::NodeIndex;
+use petgraph::visit::EdgeRef;
+use smol_str::SmolStr;
+
+use super::types::{EdgeKind, GraphEdge, GraphNode, NodeKind};
+use super::{RepositoryGraph, add_semantic_edge};
+use crate::global_index::rust_resolver::RustModule;
+use crate::indexer::{CONTENT_ONLY_KEY, CodeElement, ElementType};
+
+/// A module and the files directly in it.
+#[derive(Default)]
+struct ModuleInfo {
+    /// The directory holding the module's files.
+    dir: String,
+    files: Vec<String>,
+    parent: Option<String>,
+}
+
+impl RepositoryGraph {
+    /// All module nodes, sorted by name.
+    pub fn modules(&self) -> Vec<&GraphNode> {
+        let mut modules: Vec<&GraphNode> = self
+            .module_nodes
+            .iter()
+            .map(|entry| &self.graph[*entry.value()])
+            .collect();
+        modules.sort_by(|a, b| a.name.cmp(&b.name));
+        modules
+    }
+
+    /// The node of the module named `module_path`, e.g. `src.app.services`.
+    pub(crate) fn module_index(&self, module_path: &str) -> Option<NodeIndex> {
+        self.module_nodes.get(module_path).map(|idx| *idx)
+    }
+
+    /// Replace the module nodes with ones for the files indexed now.
+    pub(super) fn build_module_nodes(&mut self) {
+        let old: Vec<NodeIndex> = self
+            .module_nodes
+            .iter()
+            .map(|entry| *entry.value())
+            .collect();
+        self.module_nodes.clear();
+        for idx in old {
+            if let Some(node) = self.graph.remove_node(idx) {
+                self.id_to_node.remove(&node.id);
+            }
+        }
+        self.generation += 1;
+
+        let modules = self.collect_modules();
+        let mut module_of_file: HashMap<String, NodeIndex> = HashMap::new();
+        for (name, info) in &modules {
+            let node = GraphNode {
+                id: CodeElement::generate_id("module", &[name]),
+                kind: NodeKind::Module,
+                name: SmolStr::new(name),
+                file_path: info.dir.clone(),
+                start_line: 0,
+                end_line: 0,
+            };
+            let id = node.id.clone();
+            let idx = self.graph.add_node(node);
+            self.id_to_node.insert(id, idx);
+            self.module_nodes.insert(name.clone(), idx);
+            for file_path in &info.files {
+                module_of_file.insert(file_path.clone(), idx);
+                if let Some(file_idx) = self.file_node(file_path) {
+                    self.graph
+                        .add_edge(idx, file_idx, GraphEdge::new(EdgeKind::Defines));
+                }
+            }
+        }
+        for (name, info) in &modules {
+            if let Some(parent) = info.parent.as_deref().and_then(|p| self.module_index(p))
+                && let Some(idx) = self.module_index(name)
+            {
+                self.graph
+                    .add_edge(parent, idx, GraphEdge::new(EdgeKind::Contains));
+            }
+        }
+
+        // Imports between files, aggregated per module pair
+        let mut file_imports: BTreeSet<(NodeIndex, NodeIndex, &str, &str)> = BTreeSet::new();
+        for edge in self.graph.edge_references() {
+            if edge.weight().kind != EdgeKind::Imports {
+                continue;
+            }
+            let (source, target) = (&self.graph[edge.source()], &self.graph[edge.target()]);
+            if source.kind != NodeKind::File {
+                continue;
+            }
+            let (Some(&from), Some(&to)) = (
+                module_of_file.get(&source.file_path),
+                module_of_file.get(&target.file_path),
+            ) else {
+                continue;
+            };
+            if from != to {
+                file_imports.insert((from, to, &source.file_path, &target.file_path));
+            }
+        }
+        let pairs: Vec<(NodeIndex, NodeIndex)> = file_imports
+            .into_iter()
+            .map(|(from, to, _, _)| (from, to))
+            .collect();
+        for (from, to) in pairs {
+            add_semantic_edge(&mut self.graph, from, to, EdgeKind::Imports);
+        }
+    }
+
+    /// The modules of the indexed Python, Rust, and Java files, by name.
+    fn collect_modules(&self) -> BTreeMap<String, ModuleInfo> {
+        // file_path, and the package of a Java file
+        let mut files: Vec<(String, Option<String>)> = Vec::new();
+        for entry in self.file_to_nodes.iter() {
+            let Some(elem) = entry
+                .value()
+                .iter()
+                .find(|&&idx| self.graph[idx].kind == NodeKind::File)
+                .and_then(|&idx| self.element_arena.get(&self.graph[idx].id))
+            else {
+                continue;
+            };
+            if elem.element_type != ElementType::File
+                || elem.metadata.contains_key(CONTENT_ONLY_KEY)
+            {
+                continue;
+            }
+            let package = has_extension(&elem.file_path, "java")
+                .then(|| java_package(elem.code.as_str()))
+                .flatten();
+            files.push((elem.file_path.clone(), package));
+        }
+        files.sort();
+
+        let mut modules: BTreeMap<String, ModuleInfo> = BTreeMap::new();
+        let mut add = |name: String, dir: &str, file_path: &str, parent: Option<String>| {
+            let info = modules.entry(name).or_default();
+            if info.dir.is_empty() {
+                info.dir = dir.to_string();
+            }
+            if info.parent.is_none() {
+                info.parent = parent;
+            }
+            info.files.push(file_path.to_string());
+        };
+
+        // Python: directory -> package, for directories with an `__init__.py`
+        let packages: HashMap<&str, String> = files
+            .iter()
+            .filter(|(file_path, _)| {
+                Path::new(file_path).file_name() == Some("__init__.py".as_ref())
+            })
+            .filter_map(|(file_path, _)| {
+                let module = self.global_index.file_to_module(file_path)?;
+                Some((parent_dir(file_path), module))
+            })
+            .collect();
+
+        // Rust: the modules that have child modules
+        let rust_files: Vec<(&str, RustModule)> = files
+            .iter()
+            .filter(|(file_path, _)| has_extension(file_path, "rs"))
+            .filter_map(|(file_path, _)| {
+                let module = self.global_index.rust_modules.file_module(file_path)?;
+                Some((file_path.as_str(), module))
+            })
+            .collect();
+        let with_children: BTreeSet<(&str, &[String])> = rust_files
+            .iter()
+            .filter_map(|(_, module)| {
+                let (_, parent) = module.path.split_last()?;
+                Some((module.krate.as_str(), parent))
+            })
+            .collect();
+
+        for (file_path, package) in &files {
+            let dir = parent_dir(file_path);
+            if let Some(package) = package {
+                add(package.clone(), dir, file_path, None);
+            } else if has_extension(file_path, "py")
+                && let Some(name) = packages.get(dir)
+            {
+                let parent = packages.get(parent_dir(dir)).cloned();
+                add(name.clone(), dir, file_path, parent);
+            }
+        }
+        for (file_path, module) in &rust_files {
+            let krate = module.krate.as_str();
+            let declares = with_children.contains(&(krate, module.path.as_slice()));
+            let path = match module.path.split_last() {
+                Some((_, parent)) if !declares => parent,
+                _ => module.path.as_slice(),
+            };
+            let name = rust_module_name(krate, path);
+            let parent = path
+                .split_last()
+                .map(|(_, parent)| rust_module_name(krate, parent));
+            // A module's directory is the one holding its children, which
+            // for `foo.rs` is `foo/`, not the file's own.
+            let dir = if declares && !is_mod_file(file_path) {
+                Path::new(file_path)
+                    .with_extension("")
+                    .to_string_lossy()
+                    .to_string()
+            } else {
+                parent_dir(file_path).to_string()
+            };
+            add(name, &dir, file_path, parent);
+        }
+        modules
+    }
+}
+
+fn has_extension(file_path: &str, extension: &str) -> bool {
+    Path::new(file_path).extension() == Some(extension.as_ref())
+}
+
+fn parent_dir(path: &str) -> &str {
+    Path::new(path)
+        .parent()
+        .and_then(Path::to_str)
+        .unwrap_or("")
+}
+
+/// `krate::a::b`, as Rust `use` paths write it.
+fn rust_module_name(krate: &str, path: &[String]) -> String {
+    std::iter::once(krate)
+        .chain(path.iter().map(String::as_str))
+        .collect::<Vec<_>>()
+        .join("::")
+}
+
+/// Whether a Rust file declares the module of its directory.
+fn is_mod_file(file_path: &str) -> bool {
+    matches!(
+        Path::new(file_path)
+            .file_name()
+            .and_then(|name| name.to_str()),
+        Some("mod.rs" | "lib.rs" | "main.rs")
+    )
+}
+
+/// The package a Java file declares, e.g. `com.acme.billing` for
+/// `package com.acme.billing;`.
+fn java_package(source: &str) -> Option<String> {
+    source.lines().find_map(|line| {
+        let package = line.trim().strip_prefix("package ")?;
+        let package = package.trim_end().strip_suffix(';')?.trim();
+        (!package.is_empty()).then(|| package.to_string())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use petgraph::Direction;
+    use pretty_assertions::assert_eq;
+
+    fn write(root: &Path, files: &[(&str, &str)]) {
+        for (path, code) in files {
+            let path = root.join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, code).unwrap();
+        }
+    }
+
+    fn names(nodes: Vec<&GraphNode>) -> Vec<String> {
+        let mut names: Vec<String> = nodes.into_iter().map(|n| n.name.to_string()).collect();
+        names.sort();
+        names
+    }
+
+    /// Names of the nodes `module` links to by `kind` edges.
+    fn linked(graph: &RepositoryGraph, module: &str, kind: EdgeKind) -> Vec<String> {
+        let idx = graph.module_index(module).unwrap();
+        names(
+            graph
+                .graph
+                .edges_directed(idx, Direction::Outgoing)
+                .filter(|edge| edge.weight().kind == kind)
+                .map(|edge| &graph.graph[edge.target()])
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn test_python_packages() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        write(
+            &root,
+            &[
+                ("src/app/__init__.py", "\"\"\"The app.\"\"\"\n"),
+                (
+                    "src/app/api.py",
+                    "from src.app.services.billing import charge\n\n\ndef checkout(user):\n    return charge(user)\n",
+                ),
+                ("src/app/services/__init__.py", "\"\"\"Services.\"\"\"\n"),
+                (
+                    "src/app/services/billing.py",
+                    "from src.app.models.user import User\n\n\ndef charge(user):\n    return User()\n",
+                ),
+                (
+                    "src/app/services/refunds.py",
+                    "from src.app.models.user import User\n\n\ndef refund(user):\n    return User()\n",
+                ),
+                ("src/app/models/__init__.py", "\"\"\"Models.\"\"\"\n"),
+                ("src/app/models/user.py", "class User:\n    pass\n"),
+                // Not a package: no `__init__.py`
+                (
+                    "scripts/seed.py",
+                    "from src.app.models.user import User\n\nUser()\n",
+                ),
+            ],
+        );
+        let root = root.to_string_lossy().to_string();
+        let elements = crate::indexer::walk_and_index(&root, &Default::default());
+        let mut graph = RepositoryGraph::new();
+        graph.build_from_elements(&elements, &root);
+
+        assert_eq!(
+            names(graph.modules()),
+            vec!["src.app", "src.app.models", "src.app.services"]
+        );
+        assert_eq!(graph.stats().module_count, 3);
+        assert_eq!(
+            linked(&graph, "src.app.services", EdgeKind::Defines),
+            vec!["__init__.py", "billing.py", "refunds.py"]
+        );
+        assert_eq!(
+            linked(&graph, "src.app", EdgeKind::Contains),
+            vec!["src.app.models", "src.app.services"]
+        );
+        let services = graph.module_index("src.app.services").unwrap();
+        assert_eq!(
+            graph.graph[services].file_path,
+            format!("{root}/src/app/services")
+        );
+
+        let deps =
+            |graph: &RepositoryGraph, module: &str| names(graph.get_dependencies(module).unwrap());
+        assert_eq!(deps(&graph, "src.app.services"), vec!["src.app.models"]);
+        assert_eq!(deps(&graph, "src.app"), vec!["src.app.services"]);
+        assert_eq!(
+            names(graph.get_dependents("src.app.models").unwrap()),
+            vec!["src.app.services"]
+        );
+        // Both billing.py and refunds.py import user.py.
+        let models = graph.module_index("src.app.models").unwrap();
+        let edge = graph.graph.find_edge(services, models).unwrap();
+        assert_eq!(graph.graph[edge].count, 2);
+
+        // A new import across packages shows up at module level, and goes
+        // away with the file.
+        let audit = format!("{root}/src/app/services/audit.py");
+        write(
+            Path::new(&root),
+            &[(
+                "src/app/services/audit.py",
+                "from src.app.api import checkout\n",
+            )],
+        );
+        let new_elements: Vec<CodeElement> =
+            crate::indexer::walk_and_index(&root, &Default::default())
+                .into_iter()
+                .filter(|elem| elem.file_path == audit)
+                .collect();
+        graph.update_file(&audit, &new_elements, &root);
+        assert_eq!(
+            deps(&graph, "src.app.services"),
+            vec!["src.app", "src.app.models"]
+        );
+        graph.remove_file(&audit);
+        assert_eq!(deps(&graph, "src.app.services"), vec!["src.app.models"]);
+        assert_eq!(
+            linked(&graph, "src.app.services", EdgeKind::Defines),
+            vec!["__init__.py", "billing.py", "refunds.py"]
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "lang-rust")]
+    fn test_rust_module_tree() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        write(
+            &root,
+            &[
+                ("mycrate/src/lib.rs", "mod graph;\nmod parser;\nmod util;\n"),
+                ("mycrate/src/util.rs", "pub fn helper() {}\n"),
+                ("mycrate/src/parser/mod.rs", "pub mod calls;\n"),
+                ("mycrate/src/parser/calls.rs", "pub fn extract_calls() {}\n"),
+                (
+                    "mycrate/src/graph.rs",
+                    "mod queries;\nuse crate::parser::calls::extract_calls;\n",
+                ),
+                ("mycrate/src/graph/queries.rs", "use crate::util::helper;\n"),
+            ],
+        );
+        let root = root.to_string_lossy().to_string();
+        let elements = crate::indexer::walk_and_index(&root, &Default::default());
+        let mut graph = RepositoryGraph::new();
+        graph.build_from_elements(&elements, &root);
+
+        assert_eq!(
+            names(graph.modules()),
+            vec!["mycrate", "mycrate::graph", "mycrate::parser"]
+        );
+        // `graph.rs` declares a module whose files live in `graph/`.
+        assert_eq!(
+            linked(&graph, "mycrate::graph", EdgeKind::Defines),
+            vec!["graph.rs", "queries.rs"]
+        );
+        let graph_module = graph.module_index("mycrate::graph").unwrap();
+        assert_eq!(
+            graph.graph[graph_module].file_path,
+            format!("{root}/mycrate/src/graph")
+        );
+        assert_eq!(
+            linked(&graph, "mycrate::parser", EdgeKind::Defines),
+            vec!["calls.rs", "mod.rs"]
+        );
+        assert_eq!(
+            linked(&graph, "mycrate", EdgeKind::Defines),
+            vec!["lib.rs", "util.rs"]
+        );
+        assert_eq!(
+            linked(&graph, "mycrate", EdgeKind::Contains),
+            vec!["mycrate::graph", "mycrate::parser"]
+        );
+
+        assert_eq!(
+            names(graph.get_dependencies("mycrate::graph").unwrap()),
+            vec!["mycrate", "mycrate::parser"]
+        );
+        assert_eq!(
+            names(graph.get_dependents("mycrate::parser").unwrap()),
+            vec!["mycrate", "mycrate::graph"]
+        );
+        // File paths still resolve to files.
+        let deps = names(graph.get_dependencies("graph/queries.rs").unwrap());
+        assert!(deps.contains(&"util.rs".to_string()), "{deps:?}");
+    }
+}
@@ -0,0 +1,674 @@
+//! Qualified symbol names: `UserService.create`, `RepositoryGraph::update_file`,
+//! `app.services.create_user`.
+//!
+//! A symbol that is not an indexed name as written is split at its last `.`
+//! or `::`. The prefix is looked up as a class, struct or impl whose line
+//! range contains the member, then as a module whose file defines it. When
+//! the prefix names neither, the member is looked up as a bare name. Rust's
+//! `<Foo as Display>::fmt` picks the member of `Foo`'s `Display` impl.
+
+use std::collections::HashMap;
+
+use petgraph::stable_graph::NodeIndex;
+
+use super::RepositoryGraph;
+use super::types::{GraphNode, NodeKind};
+use crate::indexer::IMPL_TRAIT_KEY;
+
+/// Why a symbol passed to a graph query did not resolve to one element.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum SymbolLookupError {
+    #[error(
+        "'{input}' matches {} elements, pass a qualified name: {}",
+        candidates.len(),
+        candidates.join(", ")
+    )]
+    Ambiguous {
+        input: String,
+        /// A name for each match that tells it apart from the others, sorted.
+        candidates: Vec<String>,
+    },
+}
+
+/// Nodes a symbol resolved to.
+enum Lookup {
+    Found(Vec<NodeIndex>),
+    /// Members of same-named parents in different files.
+    Ambiguous(Vec<NodeIndex>),
+}
+
+impl RepositoryGraph {
+    /// Nodes named by `symbol`, a bare or qualified name. An ambiguous
+    /// qualified name yields every match.
+    pub(crate) fn find_nodes_by_name(&self, symbol: &str) -> Vec<NodeIndex> {
+        match self.lookup(symbol) {
+            Lookup::Found(nodes) | Lookup::Ambiguous(nodes) => nodes,
+        }
+    }
+
+    /// Check that a qualified `symbol` does not match members of several
+    /// same-named classes in different files.
+    pub fn check_symbol(&self, symbol: &str) -> Result<(), SymbolLookupError> {
+        match self.lookup(symbol) {
+            Lookup::Found(_) => Ok(()),
+            Lookup::Ambiguous(nodes) => Err(self.ambiguous(symbol, &nodes)),
+        }
+    }
+
+    /// The element an ID, bare name or qualified name refers to, `None` when
+    /// nothing matches. A name matching several elements is an error listing
+    /// a qualified name for each.
+    pub fn resolve_element_id(&self, symbol: &str) -> Result<Option<String>, SymbolLookupError> {
+        if self.live_id(symbol).is_some() {
+            return Ok(Some(symbol.to_string()));
+        }
+        let nodes = match self.lookup(symbol) {
+            Lookup::Found(nodes) | Lookup::Ambiguous(nodes) => nodes,
+        };
+        match nodes.as_slice() {
+            [] => Ok(None),
+            [idx] => Ok(Some(self.graph[*idx].id.clone())),
+            _ => Err(self.ambiguous(symbol, &nodes)),
+        }
+    }
+
+    /// `Parent.member` (`Parent::member` in Rust) for a node defined inside
+    /// a class, struct or impl; `None` for top-level elements.
+    pub fn qualified_name(&self, node: &GraphNode) -> Option<String> {
+        if matches!(node.kind, NodeKind::File | NodeKind::Module) {
+            return None;
+        }
+        let parent = self.enclosing_parent(node)?;
+        let sep = separator(&node.file_path);
+        Some(format!("{}{sep}{}", parent.name, node.name))
+    }
+
+    fn lookup(&self, symbol: &str) -> Lookup {
+        if let Some(nodes) = self.name_to_nodes.get(symbol)
+            && !nodes.is_empty()
+        {
+            return Lookup::Found(nodes.clone());
+        }
+        let Some((prefix, member)) = split_qualified(symbol) else {
+            return Lookup::Found(Vec::new());
+        };
+
+        // `<Type as Trait>::member`
+        if let Some((type_name, trait_name)) = split_trait_impl(prefix) {
+            let members = self
+                .parents_named(None, type_name)
+                .into_iter()
+                .flat_map(|parent| self.members_named(parent, member))
+                .filter(|&idx| self.implements(idx, trait_name))
+                .collect();
+            return Lookup::Found(members);
+        }
+
+        // `Parent.member`, or `module.Parent.member`
+        let (module, parent_name) = match split_qualified(prefix) {
+            Some((module, parent)) => (Some(module), parent),
+            None => (None, prefix),
+        };
+        let parents = self.parents_named(module, parent_name);
+        if !parents.is_empty() {
+            let mut members: Vec<NodeIndex> = Vec::new();
+            for &parent in &parents {
+                for idx in self.members_named(parent, member) {
+                    if !members.contains(&idx) {
+                        members.push(idx);
+                    }
+                }
+            }
+            let first_file = members.first().map(|&idx| &self.graph[idx].file_path);
+            let one_file = members
+                .iter()
+                .all(|&idx| Some(&self.graph[idx].file_path) == first_file);
+            return if one_file {
+                Lookup::Found(members)
+            } else {
+                Lookup::Ambiguous(members)
+            };
+        }
+
+        // `module.member`
+        if let Some(file_path) = self.module_file(prefix) {
+            return Lookup::Found(self.file_members_named(&file_path, member));
+        }
+
+        Lookup::Found(
+            self.name_to_nodes
+                .get(member)
+                .map(|nodes| nodes.clone())
+                .unwrap_or_default(),
+        )
+    }
+
+    /// Members named `member` of the classes, structs or impls `owner`
+    /// names, else of the module it names. Unlike a qualified lookup, an
+    /// `owner` naming neither yields nothing.
+    pub(super) fn members_of(&self, owner: &str, member: &str) -> Vec<NodeIndex> {
+        let (module, parent_name) = match split_qualified(owner) {
+            Some((module, parent)) => (Some(module), parent),
+            None => (None, owner),
+        };
+        let mut members: Vec<NodeIndex> = Vec::new();
+        for parent in self.parents_named(module, parent_name) {
+            for idx in self.members_named(parent, member) {
+                if !members.contains(&idx) {
+                    members.push(idx);
+                }
+            }
+        }
+        if members.is_empty()
+            && let Some(file_path) = self.module_file(owner)
+        {
+            members = self.file_members_named(&file_path, member);
+        }
+        members
+    }
+
+    /// Classes, structs or impls named `name`, in `module` when given.
+    fn parents_named(&self, module: Option<&str>, name: &str) -> Vec<NodeIndex> {
+        self.name_to_nodes
+            .get(name)
+            .map(|nodes| {
+                nodes
+                    .iter()
+                    .copied()
+                    .filter(|&idx| is_parent_kind(self.graph[idx].kind))
+                    .filter(|&idx| {
+                        module.is_none_or(|module| {
+                            self.file_in_module(&self.graph[idx].file_path, module)
+                        })
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Whether `idx` is a method in an impl of `trait_name` (a bare or path
+    /// name), going by the `impl_trait` the indexer recorded.
+    fn implements(&self, idx: NodeIndex, trait_name: &str) -> bool {
+        let trait_name = trait_name.rsplit("::").next().unwrap_or(trait_name);
+        self.element_arena
+            .get(&self.graph[idx].id)
+            .is_some_and(|elem| {
+                elem.metadata.get(IMPL_TRAIT_KEY).map(String::as_str) == Some(trait_name)
+            })
+    }
+
+    /// Elements named `member` anywhere in `file_path`.
+    fn file_members_named(&self, file_path: &str, member: &str) -> Vec<NodeIndex> {
+        self.file_to_nodes
+            .get(file_path)
+            .map(|nodes| {
+                nodes
+                    .iter()
+                    .copied()
+                    .filter(|&idx| self.graph[idx].name.as_str() == member)
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Elements named `member` inside `parent`'s line range.
+    pub(super) fn members_named(&self, parent: NodeIndex, member: &str) -> Vec<NodeIndex> {
+        let parent = &self.graph[parent];
+        let Some(nodes) = self.file_to_nodes.get(&parent.file_path) else {
+            return Vec::new();
+        };
+        nodes
+            .iter()
+            .copied()
+            .filter(|&idx| {
+                let node = &self.graph[idx];
+                node.id != parent.id
+                    && node.name.as_str() == member
+                    && node.start_line >= parent.start_line
+                    && node.end_line <= parent.end_line
+            })
+            .collect()
+    }
+
+    /// The innermost class, struct or impl containing `node`.
+    fn enclosing_parent(&self, node: &GraphNode) -> Option<&GraphNode> {
+        self.enclosing_parent_index(node)
+            .map(|idx| &self.graph[idx])
+    }
+
+    pub(super) fn enclosing_parent_index(&self, node: &GraphNode) -> Option<NodeIndex> {
+        self.file_to_nodes
+            .get(&node.file_path)?
+            .iter()
+            .copied()
+            .filter(|&idx| {
+                let candidate = &self.graph[idx];
+                is_parent_kind(candidate.kind)
+                    && candidate.id != node.id
+                    && candidate.start_line <= node.start_line
+                    && candidate.end_line >= node.end_line
+                    && (candidate.start_line, candidate.end_line)
+                        != (node.start_line, node.end_line)
+            })
+            .min_by_key(|&idx| self.graph[idx].end_line - self.graph[idx].start_line)
+    }
+
+    /// The file of a module written with `.` or `::` separators: an exact
+    /// module path, else the one module path ending with it.
+    fn module_file(&self, module: &str) -> Option<String> {
+        let wanted = normalize_module(module);
+        if let Some(file_path) = self.global_index.resolve_module(&wanted) {
+            return Some(file_path);
+        }
+        let mut matches = self
+            .global_index
+            .file_map
+            .iter()
+            .filter(|entry| module_ends_with(entry.value(), &wanted))
+            .map(|entry| entry.key().clone());
+        let file_path = matches.next()?;
+        matches.next().is_none().then_some(file_path)
+    }
+
+    fn file_in_module(&self, file_path: &str, module: &str) -> bool {
+        self.global_index
+            .file_to_module(file_path)
+            .is_some_and(|path| module_ends_with(&path, &normalize_module(module)))
+    }
+
+    fn ambiguous(&self, symbol: &str, nodes: &[NodeIndex]) -> SymbolLookupError {
+        let short: Vec<String> = nodes
+            .iter()
+            .map(|&idx| {
+                let node = &self.graph[idx];
+                self.qualified_name(node)
+                    .unwrap_or_else(|| node.name.to_string())
+            })
+            .collect();
+        let mut counts: HashMap<&str, usize> = HashMap::new();
+        for name in &short {
+            *counts.entry(name).or_default() += 1;
+        }
+        let mut candidates: Vec<String> = nodes
+            .iter()
+            .zip(&short)
+            .map(|(&idx, name)| {
+                let node = &self.graph[idx];
+                let name = if counts[name.as_str()] > 1
+                    && let Some(module) = self.global_index.file_to_module(&node.file_path)
+                {
+                    let sep = separator(&node.file_path);
+                    format!("{}{sep}{name}", display_module(&module, sep))
+                } else {
+                    name.clone()
+                };
+                format!("{name} ({}:{})", node.file_path, node.start_line)
+            })
+            .collect();
+        candidates.sort();
+        SymbolLookupError::Ambiguous {
+            input: symbol.to_string(),
+            candidates,
+        }
+    }
+}
+
+/// Split a qualified name at its last `.` or `::` into prefix and member.
+fn split_qualified(symbol: &str) -> Option<(&str, &str)> {
+    if symbol.contains(['/', '\\']) {
+        return None;
+    }
+    let (prefix, member) = match (symbol.rfind("::"), symbol.rfind('.')) {
+        (Some(colons), Some(dot)) if dot > colons => (&symbol[..dot], &symbol[dot + 1..]),
+        (Some(colons), _) => (&symbol[..colons], &symbol[colons + 2..]),
+        (None, Some(dot)) => (&symbol[..dot], &symbol[dot + 1..]),
+        (None, None) => return None,
+    };
+    (!prefix.is_empty() && !member.is_empty()).then_some((prefix, member))
+}
+
+/// `Foo` and `Display` for Rust's `<Foo as Display>`.
+fn split_trait_impl(prefix: &str) -> Option<(&str, &str)> {
+    let (type_name, trait_name) = prefix
+        .strip_prefix('<')?
+        .strip_suffix('>')?
+        .split_once(" as ")?;
+    Some((type_name.trim(), trait_name.trim()))
+}
+
+fn is_parent_kind(kind: NodeKind) -> bool {
+    matches!(
+        kind,
+        NodeKind::Class | NodeKind::Struct | NodeKind::Interface | NodeKind::Enum
+    )
+}
+
+/// `::` for Rust and C++, `.` elsewhere.
+fn separator(file_path: &str) -> &'static str {
+    let rust_like = [".rs", ".cpp", ".cc", ".hpp", ".h"]
+        .iter()
+        .any(|ext| file_path.ends_with(ext));
+    if rust_like { "::" } else { "." }
+}
+
+/// A module path in the GlobalIndex's dotted form, without Rust path roots.
+fn normalize_module(module: &str) -> String {
+    let dotted = module.replace("::", ".");
+    let mut rest = dotted.as_str();
+    for root in ["crate.", "self.", "super."] {
+        rest = rest.strip_prefix(root).unwrap_or(rest);
+    }
+    rest.to_string()
+}
+
+/// Whether an indexed module path is `wanted` or ends with `.wanted`,
+/// treating Rust's `foo/mod.rs` as `foo`.
+fn module_ends_with(module_path: &str, wanted: &str) -> bool {
+    let module_path = module_path.strip_suffix(".mod").unwrap_or(module_path);
+    module_path == wanted
+        || module_path
+            .strip_suffix(wanted)
+            .is_some_and(|head| head.ends_with('.'))
+}
+
+/// An indexed module path written with `sep`, e.g. `graph` for
+/// `src.graph.mod` in Rust.
+fn display_module(module_path: &str, sep: &str) -> String {
+    if sep == "." {
+        return module_path.to_string();
+    }
+    let module_path = module_path.strip_suffix(".mod").unwrap_or(module_path);
+    let module_path = module_path.strip_prefix("src.").unwrap_or(module_path);
+    module_path.replace('.', sep)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indexer::element::*;
+
+    fn element(
+        id: &str,
+        name: &str,
+        etype: ElementType,
+        file: &str,
+        lines: (usize, usize),
+    ) -> CodeElement {
+        CodeElement {
+            id: id.to_string(),
+            element_type: etype,
+            name: name.to_string(),
+            file_path: file.to_string(),
+            relative_path: file.to_string(),
+            language: "python".to_string(),
+            start_line: lines.0,
+            end_line: lines.1,
+            code: Default::default(),
+            signature: None,
+            docstring: None,
+            summary: None,
+            parent_id: None,
+            metadata: HashMap::new(),
+        }
+    }
+
+    fn graph(elements: &[CodeElement]) -> RepositoryGraph {
+        let mut graph = RepositoryGraph::new();
+        graph.build_from_elements(elements, "");
+        graph
+    }
+
+    fn ids(graph: &RepositoryGraph, symbol: &str) -> Vec<String> {
+        let mut ids: Vec<String> = graph
+            .find_nodes_by_name(symbol)
+            .into_iter()
+            .map(|idx| graph.graph[idx].id.clone())
+            .collect();
+        ids.sort();
+        ids
+    }
+
+    #[test]
+    fn test_split_qualified() {
+        assert_eq!(
+            split_qualified("UserService.create"),
+            Some(("UserService", "create"))
+        );
+        assert_eq!(
+            split_qualified("crate::graph::RepositoryGraph::update_file"),
+            Some(("crate::graph::RepositoryGraph", "update_file"))
+        );
+        assert_eq!(split_qualified("a::B.c"), Some(("a::B", "c")));
+        assert_eq!(split_qualified("create"), None);
+        assert_eq!(split_qualified("src/app.py"), None);
+        assert_eq!(split_qualified(".hidden"), None);
+    }
+
+    #[test]
+    fn test_python_dot_form() {
+        let graph = graph(&[
+            element(
+                "user_svc",
+                "UserService",
+                ElementType::Class,
+                "app/users.py",
+                (1, 20),
+            ),
+            element(
+                "user_create",
+                "create",
+                ElementType::Method,
+                "app/users.py",
+                (5, 9),
+            ),
+            element(
+                "order_svc",
+                "OrderService",
+                ElementType::Class,
+                "app/orders.py",
+                (1, 20),
+            ),
+            element(
+                "order_create",
+                "create",
+                ElementType::Method,
+                "app/orders.py",
+                (3, 8),
+            ),
+        ]);
+
+        assert_eq!(ids(&graph, "UserService.create"), vec!["user_create"]);
+        assert_eq!(ids(&graph, "create"), vec!["order_create", "user_create"]);
+        // The class exists but has no such member.
+        assert!(ids(&graph, "UserService.delete").is_empty());
+        // An unknown prefix falls back to the bare member name.
+        assert_eq!(ids(&graph, "self.create").len(), 2);
+
+        let node = &graph.graph[*graph.id_to_node.get("user_create").unwrap()];
+        assert_eq!(
+            graph.qualified_name(node).as_deref(),
+            Some("UserService.create")
+        );
+        let class = &graph.graph[*graph.id_to_node.get("user_svc").unwrap()];
+        assert_eq!(graph.qualified_name(class), None);
+
+        assert_eq!(
+            graph.resolve_element_id("UserService.create"),
+            Ok(Some("user_create".to_string()))
+        );
+        assert_eq!(
+            graph.resolve_element_id("user_create"),
+            Ok(Some("user_create".to_string()))
+        );
+        assert_eq!(graph.resolve_element_id("missing"), Ok(None));
+        let Err(SymbolLookupError::Ambiguous { candidates, .. }) =
+            graph.resolve_element_id("create")
+        else {
+            panic!("bare 'create' should be ambiguous");
+        };
+        assert_eq!(
+            candidates,
+            vec![
+                "OrderService.create (app/orders.py:3)",
+                "UserService.create (app/users.py:5)",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_rust_path_form() {
+        let rust = |id: &str, name: &str, etype: ElementType, lines: (usize, usize)| {
+            let mut elem = element(id, name, etype, "src/graph/mod.rs", lines);
+            elem.language = "rust".to_string();
+            elem
+        };
+        let graph = graph(&[
+            rust("file", "mod.rs", ElementType::File, (1, 100)),
+            rust(
+                "graph_struct",
+                "RepositoryGraph",
+                ElementType::Struct,
+                (1, 10),
+            ),
+            rust(
+                "graph_impl",
+                "RepositoryGraph",
+                ElementType::Class,
+                (12, 60),
+            ),
+            rust("graph_update", "update_file", ElementType::Method, (20, 40)),
+            rust(
+                "free_update",
+                "update_file",
+                ElementType::Function,
+                (70, 80),
+            ),
+        ]);
+
+        assert_eq!(
+            ids(&graph, "RepositoryGraph::update_file"),
+            vec!["graph_update"]
+        );
+        assert_eq!(
+            ids(&graph, "crate::graph::RepositoryGraph::update_file"),
+            vec!["graph_update"]
+        );
+        let node = &graph.graph[*graph.id_to_node.get("graph_update").unwrap()];
+        assert_eq!(
+            graph.qualified_name(node).as_deref(),
+            Some("RepositoryGraph::update_file")
+        );
+    }
+
+    #[test]
+    fn test_module_prefix() {
+        let graph = graph(&[
+            element(
+                "svc_file",
+                "services.py",
+                ElementType::File,
+                "app/services.py",
+                (1, 30),
+            ),
+            element(
+                "svc_create",
+                "create_user",
+                ElementType::Function,
+                "app/services.py",
+                (3, 8),
+            ),
+            element(
+                "cli_file",
+                "cli.py",
+                ElementType::File,
+                "app/cli.py",
+                (1, 30),
+            ),
+            element(
+                "cli_create",
+                "create_user",
+                ElementType::Function,
+                "app/cli.py",
+                (3, 8),
+            ),
+        ]);
+
+        assert_eq!(ids(&graph, "app.services.create_user"), vec!["svc_create"]);
+        assert_eq!(ids(&graph, "services.create_user"), vec!["svc_create"]);
+        assert_eq!(ids(&graph, "cli.create_user"), vec!["cli_create"]);
+        assert!(graph.check_symbol("services.create_user").is_ok());
+    }
+
+    #[test]
+    fn test_ambiguous_prefix() {
+        let graph = graph(&[
+            element(
+                "models_file",
+                "models.py",
+                ElementType::File,
+                "app/models.py",
+                (1, 30),
+            ),
+            element(
+                "app_user",
+                "User",
+                ElementType::Class,
+                "app/models.py",
+                (1, 20),
+            ),
+            element(
+                "app_save",
+                "save",
+                ElementType::Method,
+                "app/models.py",
+                (10, 12),
+            ),
+            element(
+                "billing_file",
+                "models.py",
+                ElementType::File,
+                "billing/models.py",
+                (1, 30),
+            ),
+            element(
+                "billing_user",
+                "User",
+                ElementType::Class,
+                "billing/models.py",
+                (1, 20),
+            ),
+            element(
+                "billing_save",
+                "save",
+                ElementType::Method,
+                "billing/models.py",
+                (4, 6),
+            ),
+        ]);
+
+        let err = graph.check_symbol("User.save").unwrap_err();
+        assert_eq!(
+            err,
+            SymbolLookupError::Ambiguous {
+                input: "User.save".to_string(),
+                candidates: vec![
+                    "app.models.User.save (app/models.py:10)".to_string(),
+                    "billing.models.User.save (billing/models.py:4)".to_string(),
+                ],
+            }
+        );
+        assert!(
+            err.to_string()
+                .starts_with("'User.save' matches 2 elements")
+        );
+        // Queries still see every match.
+        assert_eq!(ids(&graph, "User.save"), vec!["app_save", "billing_save"]);
+
+        // The suggested names resolve to one element each.
+        assert_eq!(
+            graph.resolve_element_id("billing.models.User.save"),
+            Ok(Some("billing_save".to_string()))
+        );
+        assert_eq!(ids(&graph, "app.models.User.save"), vec!["app_save"]);
+    }
+}
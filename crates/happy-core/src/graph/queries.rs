@@ -1,226 +1,1081 @@
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BTreeSet, BinaryHeap, HashMap, HashSet, VecDeque};
+use std::iter::Peekable;
+use std::path::Path;
+
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
 use petgraph::Direction;
-use petgraph::algo::astar;
+use petgraph::algo::tarjan_scc;
+use petgraph::graph::DiGraph;
 use petgraph::stable_graph::NodeIndex;
+use petgraph::visit::EdgeRef;
+use serde::Serialize;
 
 use super::RepositoryGraph;
-use super::types::{EdgeKind, GraphNode};
+use super::types::{EdgeKind, GraphNode, NodeKind};
+use crate::indexer::CodeElement;
+
+/// Default cap on the nodes returned by a transitive call query.
+pub const DEFAULT_TRANSITIVE_LIMIT: usize = 500;
+
+/// Default number of hops a transitive call query follows.
+pub const DEFAULT_TRANSITIVE_DEPTH: usize = 3;
+
+/// Default number of hops a related-element query follows.
+pub const DEFAULT_RELATED_HOPS: usize = 2;
+
+/// Node kinds searched for dead code when none are given.
+pub const DEFAULT_UNREFERENCED_KINDS: [NodeKind; 3] =
+    [NodeKind::Function, NodeKind::Method, NodeKind::Class];
+
+/// Nodes listed per group by [`RepositoryGraph::summarize_related`].
+pub const RELATED_GROUP_LIMIT: usize = 10;
+
+/// Default number of nodes returned by
+/// [`RepositoryGraph::get_related_ranked`].
+pub const RELATED_RANKED_LIMIT: usize = 10;
+
+/// Default cap on the edges of a path enumerated by
+/// [`RepositoryGraph::find_paths`].
+pub const DEFAULT_MAX_PATH_LEN: usize = 6;
+
+/// Partial paths [`RepositoryGraph::find_paths`] creates before it stops
+/// extending them, bounding its time and memory on dense graphs.
+const PATH_SEARCH_STATES: usize = 20_000;
+
+/// Share of a ranked related node's score that comes from its graph
+/// distance; the rest comes from lexical similarity.
+const RELATED_DISTANCE_WEIGHT: f64 = 0.7;
+
+/// Cost of the closest kind of hop, see [`related_hop_cost`].
+const MIN_HOP_COST: u32 = 2;
+
+/// Relationship groups reported by [`RepositoryGraph::summarize_related`]:
+/// the edge kind each collects and the direction it is followed in.
+const RELATED_GROUPS: &[(&str, EdgeKind, Direction)] = &[
+    ("callers", EdgeKind::Calls, Direction::Incoming),
+    ("callees", EdgeKind::Calls, Direction::Outgoing),
+    ("importers", EdgeKind::Imports, Direction::Incoming),
+    ("subclasses", EdgeKind::Inherits, Direction::Incoming),
+    ("superclasses", EdgeKind::Inherits, Direction::Outgoing),
+    ("defined_in", EdgeKind::Defines, Direction::Incoming),
+];
+
+/// Which edges a related-element query follows from each node.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RelatedDirection {
+    /// Incoming edges: callers, importers, subclasses, the defining file.
+    In,
+    /// Outgoing edges: callees, superclasses, defined members.
+    Out,
+    #[default]
+    Both,
+}
+
+impl RelatedDirection {
+    fn allows(self, direction: Direction) -> bool {
+        match self {
+            RelatedDirection::In => direction == Direction::Incoming,
+            RelatedDirection::Out => direction == Direction::Outgoing,
+            RelatedDirection::Both => true,
+        }
+    }
+}
+
+impl std::str::FromStr for RelatedDirection {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "in" | "incoming" => Ok(RelatedDirection::In),
+            "out" | "outgoing" => Ok(RelatedDirection::Out),
+            "both" => Ok(RelatedDirection::Both),
+            other => Err(format!(
+                "unknown direction '{other}', expected in, out, or both"
+            )),
+        }
+    }
+}
+
+/// One relationship group of a related-element summary.
+#[derive(Debug)]
+pub struct RelatedGroup<'a> {
+    /// `callers`, `callees`, `importers`, `subclasses`, `superclasses`, or
+    /// `defined_in`.
+    pub name: &'static str,
+    /// Number of distinct nodes in the group.
+    pub total: usize,
+    /// At most [`RELATED_GROUP_LIMIT`] nodes, most edges first, ties by
+    /// name then ID.
+    pub nodes: Vec<&'a GraphNode>,
+}
+
+/// A node related to a symbol, as ranked by
+/// [`RepositoryGraph::get_related_ranked`].
+#[derive(Debug)]
+pub struct RankedRelated<'a> {
+    pub node: &'a GraphNode,
+    /// In `0..=1`; higher is more closely related.
+    pub score: f64,
+    /// Edges between the symbol and the node on the cheapest path.
+    pub hops: usize,
+    /// Cosine similarity of the identifiers and docstrings of the symbol
+    /// and the node, in `0..=1`.
+    pub similarity: f64,
+    /// The cheapest path from the symbol to the node, both included.
+    pub path: Vec<&'a GraphNode>,
+    /// The kind of each edge along `path`.
+    pub via: Vec<EdgeKind>,
+}
+
+/// A path through the code graph, as found by
+/// [`RepositoryGraph::find_path`].
+#[derive(Debug)]
+pub struct GraphPath<'a> {
+    /// The nodes along the path, source and target included.
+    pub nodes: Vec<&'a GraphNode>,
+    /// The kind of each edge along `nodes`, one fewer than the nodes.
+    pub via: Vec<EdgeKind>,
+}
+
+impl GraphPath<'_> {
+    /// Number of edges along the path.
+    pub fn hops(&self) -> usize {
+        self.via.len()
+    }
+}
+
+impl std::fmt::Display for GraphPath<'_> {
+    /// Node names joined by their edges, as in
+    /// `handler --calls--> save --calls--> insert`.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, node) in self.nodes.iter().enumerate() {
+            if i > 0 {
+                let kind = format!("{:?}", self.via[i - 1]).to_ascii_lowercase();
+                write!(f, " --{kind}--> ")?;
+            }
+            write!(f, "{}", node.name)?;
+        }
+        Ok(())
+    }
+}
+
+/// Result of a transitive call query.
+#[derive(Debug, Default)]
+pub struct CallChain<'a> {
+    /// `levels[0]` holds direct callers (or callees), `levels[1]` those one
+    /// hop further, and so on. Each node appears once, at its nearest depth.
+    pub levels: Vec<Vec<&'a GraphNode>>,
+    /// Whether the node cap stopped the search early.
+    pub truncated: bool,
+}
+
+impl CallChain<'_> {
+    /// Total number of nodes across all levels.
+    pub fn len(&self) -> usize {
+        self.levels.iter().map(Vec::len).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.levels.is_empty()
+    }
+
+    /// Element IDs per level.
+    pub fn level_ids(&self) -> Vec<Vec<String>> {
+        self.levels
+            .iter()
+            .map(|level| level.iter().map(|n| n.id.clone()).collect())
+            .collect()
+    }
+}
+
+/// A gitignore-style glob over repo-relative paths: `*.py` matches at any
+/// depth, `src/api/*.ts` is anchored at the repo root, and a directory
+/// such as `src/api` matches everything under it.
+#[derive(Debug, Clone)]
+pub struct FileGlob {
+    matcher: Gitignore,
+}
+
+impl FileGlob {
+    pub fn new(pattern: &str) -> Result<Self, ignore::Error> {
+        let pattern = pattern.trim().trim_start_matches("./");
+        let mut builder = GitignoreBuilder::new("");
+        builder.add_line(None, pattern)?;
+        Ok(Self {
+            matcher: builder.build()?,
+        })
+    }
+
+    /// Whether a path relative to the repo root matches.
+    pub fn is_match(&self, relative_path: &str) -> bool {
+        let path = crate::utils::normalize_path(relative_path);
+        let path = Path::new(path.as_str());
+        // Absolute paths lie outside the matcher's root, which it rejects.
+        !path.has_root()
+            && self
+                .matcher
+                .matched_path_or_any_parents(path, false)
+                .is_ignore()
+    }
+}
+
+/// Narrows a caller or callee query. The default filter narrows nothing.
+#[derive(Debug, Clone, Default)]
+pub struct QueryFilter {
+    /// Only resolve the queried symbol to elements in matching files.
+    pub symbol_files: Option<FileGlob>,
+    /// Only resolve the queried symbol to elements of these kinds; any
+    /// kind when empty.
+    pub kinds: Vec<NodeKind>,
+    /// Only return results in matching files.
+    pub result_files: Option<FileGlob>,
+}
+
+/// Narrows a keyword search to some languages, element kinds, and files.
+/// The default filter narrows nothing.
+#[derive(Debug, Clone, Default)]
+pub struct SearchFilter {
+    /// Only return elements in these languages, e.g. `python` or `rust`;
+    /// any language when empty.
+    pub languages: Vec<String>,
+    /// Only return elements of these kinds; any kind when empty.
+    pub kinds: Vec<NodeKind>,
+    /// Only return elements in matching files.
+    pub files: Option<FileGlob>,
+}
+
+impl SearchFilter {
+    pub fn is_empty(&self) -> bool {
+        self.languages.is_empty() && self.kinds.is_empty() && self.files.is_none()
+    }
+
+    pub fn matches(&self, elem: &CodeElement) -> bool {
+        (self.languages.is_empty()
+            || self
+                .languages
+                .iter()
+                .any(|lang| lang.eq_ignore_ascii_case(&elem.language)))
+            && (self.kinds.is_empty() || self.kinds.contains(&NodeKind::from(elem.element_type)))
+            && self
+                .files
+                .as_ref()
+                .is_none_or(|glob| glob.is_match(&elem.relative_path))
+    }
+}
+
+/// One element of a file outline, with the elements nested inside it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct OutlineNode {
+    pub id: String,
+    pub name: String,
+    pub kind: NodeKind,
+    pub signature: Option<String>,
+    /// First line of the docstring.
+    pub doc: Option<String>,
+    pub start_line: usize,
+    pub end_line: usize,
+    /// Methods of a class, functions nested in a function, ..., in source
+    /// order.
+    pub children: Vec<OutlineNode>,
+}
+
+/// Why a file path passed to a graph query did not resolve to exactly one
+/// indexed file.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum FileLookupError {
+    #[error("no indexed file matches '{0}'")]
+    NotFound(String),
+    #[error(
+        "'{input}' matches {} indexed files, pass a longer path: {}",
+        candidates.len(),
+        candidates.join(", ")
+    )]
+    Ambiguous {
+        input: String,
+        /// Matching files, relative to the repo root where known, sorted.
+        candidates: Vec<String>,
+    },
+}
+
+/// Whether a path query follows an edge of `kind`: one of `edge_kinds`, or
+/// any kind but [`EdgeKind::Defines`] when that is empty.
+fn follows_path_edge(kind: EdgeKind, edge_kinds: &[EdgeKind]) -> bool {
+    if edge_kinds.is_empty() {
+        kind != EdgeKind::Defines
+    } else {
+        edge_kinds.contains(&kind)
+    }
+}
+
+/// Cost of following one edge of `kind` when ranking related nodes: calls
+/// and inheritance tie two elements most closely, imports and name-only
+/// references least.
+fn related_hop_cost(kind: EdgeKind) -> u32 {
+    match kind {
+        EdgeKind::Calls | EdgeKind::Inherits | EdgeKind::Implements => MIN_HOP_COST,
+        EdgeKind::Contains | EdgeKind::Defines | EdgeKind::References => 3,
+        EdgeKind::Imports => 4,
+        EdgeKind::ReferencesByName => 6,
+    }
+}
+
+/// Cosine similarity of two term-count vectors; 0 when either is empty.
+fn cosine(a: &HashMap<String, f64>, b: &HashMap<String, f64>) -> f64 {
+    let dot: f64 = a
+        .iter()
+        .filter_map(|(term, count)| b.get(term).map(|other| count * other))
+        .sum();
+    let norm = |v: &HashMap<String, f64>| v.values().map(|c| c * c).sum::<f64>().sqrt();
+    let norms = norm(a) * norm(b);
+    if norms == 0.0 { 0.0 } else { dot / norms }
+}
 
 impl RepositoryGraph {
     /// Find all callers of a symbol (nodes with Calls edges pointing to it).
+    ///
+    /// Each caller is returned once, no matter how many times it calls the symbol.
     pub fn find_callers(&self, symbol: &str) -> Vec<&GraphNode> {
-        let indices = self.find_nodes_by_name(symbol);
-        let mut callers = Vec::new();
-
-        for idx in indices {
-            for neighbor in self.graph.neighbors_directed(idx, Direction::Incoming) {
-                let edge_idx = self.graph.find_edge(neighbor, idx);
-                if let Some(eidx) = edge_idx {
-                    if self.graph[eidx].kind == EdgeKind::Calls {
-                        callers.push(&self.graph[neighbor]);
-                    }
-                }
-            }
-        }
+        self.find_callers_filtered(symbol, &QueryFilter::default())
+    }
 
-        callers
+    /// [`find_callers`](Self::find_callers) narrowed by `filter`: only
+    /// definitions of `symbol` it allows are queried and only callers in
+    /// its result files are returned.
+    pub fn find_callers_filtered(&self, symbol: &str, filter: &QueryFilter) -> Vec<&GraphNode> {
+        let indices = self.find_nodes_filtered(symbol, filter);
+        let callers = self.collect_neighbors(&indices, Direction::Incoming, EdgeKind::Calls);
+        self.retain_result_files(callers, filter)
     }
 
     /// Find all callees of a symbol (nodes it calls).
+    ///
+    /// Each callee is returned once, no matter how many times it is called.
     pub fn find_callees(&self, symbol: &str) -> Vec<&GraphNode> {
+        self.find_callees_filtered(symbol, &QueryFilter::default())
+    }
+
+    /// [`find_callees`](Self::find_callees) narrowed by `filter`.
+    pub fn find_callees_filtered(&self, symbol: &str, filter: &QueryFilter) -> Vec<&GraphNode> {
+        let indices = self.find_nodes_filtered(symbol, filter);
+        let callees = self.collect_neighbors(&indices, Direction::Outgoing, EdgeKind::Calls);
+        self.retain_result_files(callees, filter)
+    }
+
+    /// Ids of the [`find_callers_filtered`](Self::find_callers_filtered)
+    /// results whose calls to `symbol` were all low-confidence guesses.
+    pub fn low_confidence_callers(&self, symbol: &str, filter: &QueryFilter) -> HashSet<String> {
+        let indices = self.find_nodes_filtered(symbol, filter);
+        self.low_confidence_calls(&indices, Direction::Incoming)
+    }
+
+    /// Ids of the [`find_callees_filtered`](Self::find_callees_filtered)
+    /// results `symbol` was linked to only by low-confidence guesses.
+    pub fn low_confidence_callees(&self, symbol: &str, filter: &QueryFilter) -> HashSet<String> {
+        let indices = self.find_nodes_filtered(symbol, filter);
+        self.low_confidence_calls(&indices, Direction::Outgoing)
+    }
+
+    /// Find elements whose string literals name a symbol, such as
+    /// `getattr(obj, "process_payment")` (incoming `ReferencesByName` edges).
+    ///
+    /// Empty unless the graph was built with `[index] detect_string_refs`.
+    pub fn find_string_references(&self, symbol: &str) -> Vec<&GraphNode> {
         let indices = self.find_nodes_by_name(symbol);
-        let mut callees = Vec::new();
-
-        for idx in indices {
-            for neighbor in self.graph.neighbors_directed(idx, Direction::Outgoing) {
-                let edge_idx = self.graph.find_edge(idx, neighbor);
-                if let Some(eidx) = edge_idx {
-                    if self.graph[eidx].kind == EdgeKind::Calls {
-                        callees.push(&self.graph[neighbor]);
-                    }
-                }
-            }
-        }
+        self.collect_neighbors(&indices, Direction::Incoming, EdgeKind::ReferencesByName)
+    }
 
-        callees
+    /// Find callers of a symbol up to `max_depth` hops away along Calls
+    /// edges, e.g. the entry points that eventually reach it. Stops after
+    /// `limit` nodes.
+    pub fn find_callers_transitive(
+        &self,
+        symbol: &str,
+        max_depth: usize,
+        limit: usize,
+    ) -> CallChain<'_> {
+        self.expand_calls(symbol, Direction::Incoming, max_depth, limit)
     }
 
-    /// Get files that a given file depends on (via import edges).
-    pub fn get_dependencies(&self, file_path: &str) -> Vec<&GraphNode> {
-        let indices = self.find_nodes_by_file(file_path);
-        let mut deps = Vec::new();
+    /// Find callees of a symbol up to `max_depth` hops away along Calls
+    /// edges. Stops after `limit` nodes.
+    pub fn find_callees_transitive(
+        &self,
+        symbol: &str,
+        max_depth: usize,
+        limit: usize,
+    ) -> CallChain<'_> {
+        self.expand_calls(symbol, Direction::Outgoing, max_depth, limit)
+    }
 
-        for idx in indices {
-            if self.graph[idx].kind != super::types::NodeKind::File {
-                continue;
-            }
-            for neighbor in self.graph.neighbors_directed(idx, Direction::Outgoing) {
-                let edge_idx = self.graph.find_edge(idx, neighbor);
-                if let Some(eidx) = edge_idx {
-                    if self.graph[eidx].kind == EdgeKind::Imports {
-                        deps.push(&self.graph[neighbor]);
-                    }
-                }
-            }
-        }
+    /// Get files that a given file depends on (via import edges), or the
+    /// modules a module depends on.
+    ///
+    /// `file_path` is a module path such as `src.app.services` or
+    /// `my_crate::graph` (see [`modules`](Self::modules)), else resolved
+    /// with [`resolve_file`](Self::resolve_file).
+    pub fn get_dependencies(&self, file_path: &str) -> Result<Vec<&GraphNode>, FileLookupError> {
+        let indices = self.file_or_module_nodes(file_path)?;
+        Ok(self.collect_neighbors(&indices, Direction::Outgoing, EdgeKind::Imports))
+    }
 
-        deps
+    /// Get files that depend on a given file (reverse imports), or the
+    /// modules that depend on a module.
+    ///
+    /// `file_path` is resolved like [`get_dependencies`](Self::get_dependencies).
+    pub fn get_dependents(&self, file_path: &str) -> Result<Vec<&GraphNode>, FileLookupError> {
+        let indices = self.file_or_module_nodes(file_path)?;
+        Ok(self.collect_neighbors(&indices, Direction::Incoming, EdgeKind::Imports))
     }
 
-    /// Get files that depend on a given file (reverse imports).
-    pub fn get_dependents(&self, file_path: &str) -> Vec<&GraphNode> {
-        let indices = self.find_nodes_by_file(file_path);
-        let mut dependents = Vec::new();
+    /// The module node named `path`, else the File node it resolves to.
+    fn file_or_module_nodes(&self, path: &str) -> Result<Vec<NodeIndex>, FileLookupError> {
+        match self.module_index(path) {
+            Some(idx) => Ok(vec![idx]),
+            None => Ok(self.find_file_nodes(&self.resolve_file(path)?)),
+        }
+    }
 
-        for idx in indices {
-            if self.graph[idx].kind != super::types::NodeKind::File {
-                continue;
-            }
-            for neighbor in self.graph.neighbors_directed(idx, Direction::Incoming) {
-                let edge_idx = self.graph.find_edge(neighbor, idx);
-                if let Some(eidx) = edge_idx {
-                    if self.graph[eidx].kind == EdgeKind::Imports {
-                        dependents.push(&self.graph[neighbor]);
-                    }
-                }
+    /// Map a user-supplied file path to the key it is indexed under.
+    ///
+    /// Tries, in order: the exact indexed path; the path relative to the
+    /// repo root (`src/auth.py`, `./src/auth.py`); then a path suffix
+    /// (`auth.py`), which must match a single file. Absolute paths only
+    /// match exactly, so a new file is never mistaken for an indexed one.
+    pub fn resolve_file(&self, file_path: &str) -> Result<String, FileLookupError> {
+        if self.file_to_nodes.contains_key(file_path) {
+            return Ok(file_path.to_string());
+        }
+        let not_found = || FileLookupError::NotFound(file_path.to_string());
+        if Path::new(file_path).is_absolute() {
+            return Err(not_found());
+        }
+        let normalized = crate::utils::normalize_path(file_path.trim());
+        let wanted = normalized.trim_start_matches("./").trim_start_matches('/');
+        if wanted.is_empty() {
+            return Err(not_found());
+        }
+
+        let suffix = format!("/{wanted}");
+        let mut by_relative = Vec::new();
+        let mut by_suffix = Vec::new();
+        for entry in self.file_to_nodes.iter() {
+            let relative = entry.value().iter().find_map(|&idx| {
+                self.element_arena
+                    .get(&self.graph[idx].id)
+                    .map(|elem| crate::utils::normalize_path(&elem.relative_path))
+            });
+            let key = entry.key().clone();
+            if relative.as_deref() == Some(wanted) {
+                by_relative.push((key, relative));
+            } else if crate::utils::normalize_path(&key).ends_with(&suffix) {
+                by_suffix.push((key, relative));
             }
         }
 
-        dependents
+        let mut matches = if by_relative.is_empty() {
+            by_suffix
+        } else {
+            by_relative
+        };
+        match matches.len() {
+            0 => Err(not_found()),
+            1 => Ok(matches.remove(0).0),
+            _ => {
+                let mut candidates: Vec<String> = matches
+                    .into_iter()
+                    .map(|(key, relative)| relative.unwrap_or(key))
+                    .collect();
+                candidates.sort();
+                Err(FileLookupError::Ambiguous {
+                    input: file_path.to_string(),
+                    candidates,
+                })
+            }
+        }
     }
 
     /// Get subclasses of a class.
     pub fn get_subclasses(&self, class_name: &str) -> Vec<&GraphNode> {
         let indices = self.find_nodes_by_name(class_name);
-        let mut subclasses = Vec::new();
-
-        for idx in indices {
-            for neighbor in self.graph.neighbors_directed(idx, Direction::Incoming) {
-                let edge_idx = self.graph.find_edge(neighbor, idx);
-                if let Some(eidx) = edge_idx {
-                    if self.graph[eidx].kind == EdgeKind::Inherits {
-                        subclasses.push(&self.graph[neighbor]);
-                    }
-                }
-            }
-        }
-
-        subclasses
+        self.collect_neighbors(&indices, Direction::Incoming, EdgeKind::Inherits)
     }
 
     /// Get superclasses of a class.
     pub fn get_superclasses(&self, class_name: &str) -> Vec<&GraphNode> {
         let indices = self.find_nodes_by_name(class_name);
-        let mut superclasses = Vec::new();
-
-        for idx in indices {
-            for neighbor in self.graph.neighbors_directed(idx, Direction::Outgoing) {
-                let edge_idx = self.graph.find_edge(idx, neighbor);
-                if let Some(eidx) = edge_idx {
-                    if self.graph[eidx].kind == EdgeKind::Inherits {
-                        superclasses.push(&self.graph[neighbor]);
-                    }
-                }
-            }
-        }
+        self.collect_neighbors(&indices, Direction::Outgoing, EdgeKind::Inherits)
+    }
 
-        superclasses
+    /// The class a method, field, or nested class is defined in.
+    pub fn get_parent(&self, element_id: &str) -> Option<&GraphNode> {
+        let idx = *self.id_to_node.get(element_id)?;
+        self.graph
+            .edges_directed(idx, Direction::Incoming)
+            .find(|edge| edge.weight().kind == EdgeKind::Contains)
+            .map(|edge| &self.graph[edge.source()])
     }
 
-    /// Find shortest path between two elements, optionally filtering by edge type.
+    /// The methods, fields, and nested classes a class defines, in source
+    /// order.
+    pub fn get_children(&self, element_id: &str) -> Vec<&GraphNode> {
+        let Some(idx) = self.id_to_node.get(element_id).map(|idx| *idx) else {
+            return Vec::new();
+        };
+        let mut children: Vec<&GraphNode> = self
+            .graph
+            .edges_directed(idx, Direction::Outgoing)
+            .filter(|edge| edge.weight().kind == EdgeKind::Contains)
+            .map(|edge| &self.graph[edge.target()])
+            .collect();
+        children.sort_by_key(|node| (node.start_line, node.id.as_str()));
+        children
+    }
+
+    /// The shortest path from a definition of `source` to one of `target`,
+    /// following edges forward.
+    ///
+    /// Only edges of `edge_kinds` are followed. When it is empty, every
+    /// kind but [`EdgeKind::Defines`] is, so a path does not shortcut
+    /// through the file two symbols share. Among equally short paths the
+    /// one from the earliest-matched source wins.
     pub fn find_path(
         &self,
         source: &str,
         target: &str,
-        edge_filter: Option<EdgeKind>,
-    ) -> Option<Vec<String>> {
-        let source_indices = self.find_nodes_by_name(source);
-        let target_indices = self.find_nodes_by_name(target);
-
-        for &src in &source_indices {
-            for &tgt in &target_indices {
-                let result = astar(
-                    &self.graph,
-                    src,
-                    |n| n == tgt,
-                    |edge| {
-                        if let Some(filter) = edge_filter {
-                            if edge.weight().kind == filter {
-                                1
-                            } else {
-                                usize::MAX / 2
-                            }
-                        } else {
-                            1
-                        }
-                    },
-                    |_| 0,
-                );
-
-                if let Some((_cost, path)) = result {
-                    return Some(
-                        path.into_iter()
-                            .map(|idx| self.graph[idx].id.clone())
-                            .collect(),
-                    );
+        edge_kinds: &[EdgeKind],
+    ) -> Option<GraphPath<'_>> {
+        let targets: HashSet<NodeIndex> = self.find_nodes_by_name(target).into_iter().collect();
+        if targets.is_empty() {
+            return None;
+        }
+        // Breadth-first from every source at once: node -> the node and
+        // edge kind it was first reached from.
+        let sources = self.find_nodes_by_name(source);
+        let mut reached: HashMap<NodeIndex, Option<(NodeIndex, EdgeKind)>> =
+            sources.iter().map(|&idx| (idx, None)).collect();
+        let mut queue: VecDeque<NodeIndex> = sources.into_iter().collect();
+        while let Some(idx) = queue.pop_front() {
+            if targets.contains(&idx) {
+                let mut nodes = vec![&self.graph[idx]];
+                let mut via = Vec::new();
+                let mut current = idx;
+                while let Some(&Some((previous, kind))) = reached.get(&current) {
+                    nodes.push(&self.graph[previous]);
+                    via.push(kind);
+                    current = previous;
+                }
+                nodes.reverse();
+                via.reverse();
+                return Some(GraphPath { nodes, via });
+            }
+            for edge in self.graph.edges_directed(idx, Direction::Outgoing) {
+                let kind = edge.weight().kind;
+                if follows_path_edge(kind, edge_kinds) && !reached.contains_key(&edge.target()) {
+                    reached.insert(edge.target(), Some((idx, kind)));
+                    queue.push_back(edge.target());
                 }
             }
         }
-
         None
     }
 
-    /// Get related elements within a given number of hops.
+    /// Up to `k` distinct simple paths of at most `max_len` edges from a
+    /// definition of `source` to one of `target`, following the same edges
+    /// as [`find_path`](Self::find_path).
+    ///
+    /// Shorter paths come first. Paths of the same length are ordered by
+    /// the summed degree of the nodes between the ends, so a route through
+    /// a logger or other hub that everything touches ranks below one
+    /// through code specific to the two symbols. The search stops extending
+    /// paths after creating 20,000 of them and returns what it finds among
+    /// those.
+    pub fn find_paths(
+        &self,
+        source: &str,
+        target: &str,
+        edge_kinds: &[EdgeKind],
+        k: usize,
+        max_len: usize,
+    ) -> Vec<GraphPath<'_>> {
+        let targets: HashSet<NodeIndex> = self.find_nodes_by_name(target).into_iter().collect();
+        if targets.is_empty() || k == 0 {
+            return Vec::new();
+        }
+        let degree = |idx: NodeIndex| {
+            self.graph.edges_directed(idx, Direction::Outgoing).count()
+                + self.graph.edges_directed(idx, Direction::Incoming).count()
+        };
+
+        // Partial paths as (nodes, edge kinds), expanded cheapest first by
+        // (length, hub weight, insertion order). Extending a path never
+        // makes it cheaper, so complete paths come out in order.
+        let mut partial: Vec<(Vec<NodeIndex>, Vec<EdgeKind>)> = Vec::new();
+        let mut queue: BinaryHeap<Reverse<(usize, usize, usize)>> = BinaryHeap::new();
+        for idx in self.find_nodes_by_name(source) {
+            queue.push(Reverse((0, 0, partial.len())));
+            partial.push((vec![idx], Vec::new()));
+        }
+        let mut found = Vec::new();
+        while let Some(Reverse((len, weight, i))) = queue.pop() {
+            // Taken out, so an expanded path's memory is freed.
+            let (nodes, via) = std::mem::take(&mut partial[i]);
+            let last = nodes[nodes.len() - 1];
+            if targets.contains(&last) {
+                found.push(GraphPath {
+                    nodes: nodes.iter().map(|&idx| &self.graph[idx]).collect(),
+                    via,
+                });
+                if found.len() == k {
+                    break;
+                }
+                continue;
+            }
+            if len == max_len {
+                continue;
+            }
+            // Nodes already on the path keep it simple; each neighbor is
+            // taken once, through its first matching edge.
+            let mut seen: HashSet<NodeIndex> = nodes.iter().copied().collect();
+            let step_weight = if len == 0 { 0 } else { degree(last) };
+            for edge in self.graph.edges_directed(last, Direction::Outgoing) {
+                if partial.len() >= PATH_SEARCH_STATES {
+                    break;
+                }
+                let kind = edge.weight().kind;
+                if !follows_path_edge(kind, edge_kinds) || !seen.insert(edge.target()) {
+                    continue;
+                }
+                let mut next = (nodes.clone(), via.clone());
+                next.0.push(edge.target());
+                next.1.push(kind);
+                queue.push(Reverse((len + 1, weight + step_weight, partial.len())));
+                partial.push(next);
+            }
+        }
+        found
+    }
+
+    /// Get related elements within a given number of hops, following
+    /// every edge kind in both directions.
     pub fn get_related(&self, element_name: &str, max_hops: usize) -> Vec<&GraphNode> {
-        let indices = self.find_nodes_by_name(element_name);
-        let mut visited = std::collections::HashSet::new();
+        self.get_related_filtered(element_name, max_hops, RelatedDirection::Both, &[])
+    }
+
+    /// Get elements within `max_hops` of a symbol, following only edges in
+    /// `direction` and, unless `edge_kinds` is empty, of those kinds.
+    ///
+    /// Nodes are returned nearest first; the symbol itself is excluded.
+    pub fn get_related_filtered(
+        &self,
+        element_name: &str,
+        max_hops: usize,
+        direction: RelatedDirection,
+        edge_kinds: &[EdgeKind],
+    ) -> Vec<&GraphNode> {
+        let mut frontier = self.find_nodes_by_name(element_name);
+        let mut visited: HashSet<NodeIndex> = frontier.iter().copied().collect();
         let mut result = Vec::new();
-        let mut frontier: Vec<NodeIndex> = indices.clone();
 
         for _ in 0..max_hops {
             let mut next_frontier = Vec::new();
-            for idx in &frontier {
-                if !visited.insert(*idx) {
-                    continue;
-                }
-                // Both directions
-                for neighbor in self.graph.neighbors_directed(*idx, Direction::Outgoing) {
-                    if !visited.contains(&neighbor) {
+            for &idx in &frontier {
+                for neighbor in self.related_neighbors(idx, direction, edge_kinds) {
+                    if visited.insert(neighbor) {
                         next_frontier.push(neighbor);
+                        result.push(&self.graph[neighbor]);
                     }
                 }
-                for neighbor in self.graph.neighbors_directed(*idx, Direction::Incoming) {
-                    if !visited.contains(&neighbor) {
-                        next_frontier.push(neighbor);
+            }
+            if next_frontier.is_empty() {
+                break;
+            }
+            frontier = next_frontier;
+        }
+
+        result
+    }
+
+    /// Like [`get_related_filtered`](Self::get_related_filtered), ranked and
+    /// cut to the `limit` best.
+    ///
+    /// A node's score mixes its distance from the symbol, where a call or
+    /// inheritance hop counts as closer than an import or a name-only
+    /// reference (see [`related_hop_cost`]), with the lexical similarity of
+    /// the two elements' names, signatures, and docstrings. Each result
+    /// carries the path that connects it. Ties are broken by name then ID.
+    pub fn get_related_ranked(
+        &self,
+        element_name: &str,
+        max_hops: usize,
+        direction: RelatedDirection,
+        edge_kinds: &[EdgeKind],
+        limit: usize,
+    ) -> Vec<RankedRelated<'_>> {
+        let sources = self.find_nodes_by_name(element_name);
+        let mut query_terms: HashMap<String, f64> = HashMap::new();
+        for &idx in &sources {
+            for (term, count) in self.term_counts(idx) {
+                *query_terms.entry(term).or_default() += count;
+            }
+        }
+
+        // Cheapest paths within `max_hops`: node -> (cost, hops, the node
+        // and edge kind it was reached from).
+        let mut best: HashMap<NodeIndex, (u32, usize, Option<(NodeIndex, EdgeKind)>)> =
+            sources.iter().map(|&idx| (idx, (0, 0, None))).collect();
+        let mut queue: BinaryHeap<Reverse<(u32, usize, NodeIndex)>> =
+            sources.iter().map(|&idx| Reverse((0, 0, idx))).collect();
+        while let Some(Reverse((cost, hops, idx))) = queue.pop() {
+            if best.get(&idx).is_some_and(|&(known, _, _)| known < cost) || hops >= max_hops {
+                continue;
+            }
+            for dir in [Direction::Outgoing, Direction::Incoming] {
+                if !direction.allows(dir) {
+                    continue;
+                }
+                for edge in self.graph.edges_directed(idx, dir) {
+                    let kind = edge.weight().kind;
+                    if !edge_kinds.is_empty() && !edge_kinds.contains(&kind) {
+                        continue;
+                    }
+                    let neighbor = match dir {
+                        Direction::Outgoing => edge.target(),
+                        Direction::Incoming => edge.source(),
+                    };
+                    let next = cost + related_hop_cost(kind);
+                    if best
+                        .get(&neighbor)
+                        .is_none_or(|&(known, _, _)| next < known)
+                    {
+                        best.insert(neighbor, (next, hops + 1, Some((idx, kind))));
+                        queue.push(Reverse((next, hops + 1, neighbor)));
                     }
                 }
             }
-            frontier = next_frontier;
         }
 
-        // Add the final frontier
-        for idx in &frontier {
-            if visited.insert(*idx) {
-                // only new ones
+        let mut ranked: Vec<RankedRelated<'_>> = best
+            .iter()
+            .filter(|(_, (_, hops, _))| *hops > 0)
+            .map(|(&idx, &(cost, hops, _))| {
+                let similarity = cosine(&query_terms, &self.term_counts(idx));
+                let distance = f64::from(MIN_HOP_COST) / f64::from(cost);
+                let mut path = vec![&self.graph[idx]];
+                let mut via = Vec::new();
+                let mut current = idx;
+                while let Some(&(_, _, Some((previous, kind)))) = best.get(&current) {
+                    path.push(&self.graph[previous]);
+                    via.push(kind);
+                    current = previous;
+                }
+                path.reverse();
+                via.reverse();
+                RankedRelated {
+                    node: &self.graph[idx],
+                    score: RELATED_DISTANCE_WEIGHT * distance
+                        + (1.0 - RELATED_DISTANCE_WEIGHT) * similarity,
+                    hops,
+                    similarity,
+                    path,
+                    via,
+                }
+            })
+            .collect();
+        ranked.sort_by(|a, b| {
+            b.score
+                .total_cmp(&a.score)
+                .then_with(|| (&a.node.name, &a.node.id).cmp(&(&b.node.name, &b.node.id)))
+        });
+        ranked.truncate(limit);
+        ranked
+    }
+
+    /// How often each token occurs in a node's name, signature, and
+    /// docstring.
+    fn term_counts(&self, idx: NodeIndex) -> HashMap<String, f64> {
+        let node = &self.graph[idx];
+        let mut text = node.name.to_string();
+        if let Some(elem) = self.element_arena.get(&node.id) {
+            for part in [&elem.signature, &elem.docstring].into_iter().flatten() {
+                text.push(' ');
+                text.push_str(part);
             }
         }
+        let mut counts = HashMap::new();
+        for token in crate::utils::tokenize(&text) {
+            *counts.entry(token).or_default() += 1.0;
+        }
+        counts
+    }
 
-        // Collect all visited except the originals
-        for idx in visited {
-            if !indices.contains(&idx) {
-                result.push(&self.graph[idx]);
+    /// Group a symbol's direct relationships into callers, callees,
+    /// importers, subclasses, superclasses, and the file that defines it,
+    /// in a single pass over its edges.
+    ///
+    /// Groups excluded by `direction` or `edge_kinds` (empty means all) are
+    /// left out; included groups are returned even when empty, in the order
+    /// listed above.
+    pub fn summarize_related(
+        &self,
+        element_name: &str,
+        direction: RelatedDirection,
+        edge_kinds: &[EdgeKind],
+    ) -> Vec<RelatedGroup<'_>> {
+        let included: Vec<usize> = (0..RELATED_GROUPS.len())
+            .filter(|&i| {
+                let (_, kind, dir) = RELATED_GROUPS[i];
+                direction.allows(dir) && (edge_kinds.is_empty() || edge_kinds.contains(&kind))
+            })
+            .collect();
+        let mut counts: Vec<HashMap<NodeIndex, u32>> = vec![HashMap::new(); RELATED_GROUPS.len()];
+
+        for idx in self.find_nodes_by_name(element_name) {
+            for dir in [Direction::Incoming, Direction::Outgoing] {
+                for edge in self.graph.edges_directed(idx, dir) {
+                    let group = included.iter().copied().find(|&i| {
+                        let (_, kind, group_dir) = RELATED_GROUPS[i];
+                        kind == edge.weight().kind && group_dir == dir
+                    });
+                    let Some(group) = group else {
+                        continue;
+                    };
+                    let neighbor = match dir {
+                        Direction::Outgoing => edge.target(),
+                        Direction::Incoming => edge.source(),
+                    };
+                    *counts[group].entry(neighbor).or_default() += edge.weight().count;
+                }
             }
         }
 
+        included
+            .into_iter()
+            .map(|i| {
+                let mut nodes: Vec<(u32, &GraphNode)> = counts[i]
+                    .iter()
+                    .map(|(&idx, &count)| (count, &self.graph[idx]))
+                    .collect();
+                nodes.sort_by(|(a_count, a), (b_count, b)| {
+                    b_count
+                        .cmp(a_count)
+                        .then_with(|| (&a.name, &a.id).cmp(&(&b.name, &b.id)))
+                });
+                RelatedGroup {
+                    name: RELATED_GROUPS[i].0,
+                    total: nodes.len(),
+                    nodes: nodes
+                        .into_iter()
+                        .take(RELATED_GROUP_LIMIT)
+                        .map(|(_, node)| node)
+                        .collect(),
+                }
+            })
+            .collect()
+    }
+
+    /// Find likely dead code: elements of the given kinds with no incoming
+    /// Calls, Imports, Inherits, or ReferencesByName edges, defined in files
+    /// nothing imports.
+    ///
+    /// Obvious entry points are skipped: `main`, dunder methods such as
+    /// `__init__`, and tests named `test_*`, `*_test`, or `Test*`. Results are
+    /// sorted by file and line.
+    pub fn find_unreferenced(&self, kinds: &[NodeKind]) -> Vec<&GraphNode> {
+        self.find_unreferenced_except(kinds, &[])
+    }
+
+    /// Like [`find_unreferenced`](Self::find_unreferenced), also skipping
+    /// elements whose name equals an `allowlist` entry or whose file lies
+    /// under one (`scripts/`, `src/cli.py`).
+    pub fn find_unreferenced_except(
+        &self,
+        kinds: &[NodeKind],
+        allowlist: &[String],
+    ) -> Vec<&GraphNode> {
+        let is_referenced = |idx: NodeIndex| {
+            self.graph
+                .edges_directed(idx, Direction::Incoming)
+                .any(|edge| {
+                    matches!(
+                        edge.weight().kind,
+                        EdgeKind::Calls
+                            | EdgeKind::Imports
+                            | EdgeKind::Inherits
+                            | EdgeKind::ReferencesByName
+                    )
+                })
+        };
+        let mut result: Vec<&GraphNode> = self
+            .graph
+            .node_indices()
+            .filter(|&idx| kinds.contains(&self.graph[idx].kind))
+            .filter(|&idx| !is_referenced(idx))
+            .map(|idx| &self.graph[idx])
+            .filter(|node| !is_entry_point(&node.name))
+            .filter(|node| !allowlist.iter().any(|entry| allowlisted(node, entry)))
+            .filter(|node| {
+                !self
+                    .find_file_nodes(&node.file_path)
+                    .into_iter()
+                    .any(is_referenced)
+            })
+            .collect();
+        result.sort_by(|a, b| (&a.file_path, a.start_line).cmp(&(&b.file_path, b.start_line)));
         result
     }
 
+    /// Find circular imports: strongly connected components of the file-level
+    /// import graph (Imports edges between File nodes), plus files that
+    /// import themselves.
+    ///
+    /// Each cycle is a closed walk in import order, starting from the
+    /// lexicographically smallest path: every file imports the next one, and
+    /// the last imports the first. A component that is not a simple loop
+    /// (say `a` imports `b` and `c`, and both import `a`) lists a file more
+    /// than once. Cycles are sorted by their first path.
+    pub fn find_import_cycles(&self) -> Vec<Vec<String>> {
+        let mut imports: BTreeMap<&str, BTreeSet<&str>> = BTreeMap::new();
+        for edge in self.graph.edge_indices() {
+            if self.graph[edge].kind != EdgeKind::Imports {
+                continue;
+            }
+            let Some((source, target)) = self.graph.edge_endpoints(edge) else {
+                continue;
+            };
+            let (source, target) = (&self.graph[source], &self.graph[target]);
+            if source.kind != NodeKind::File || target.kind != NodeKind::File {
+                continue;
+            }
+            imports.entry(target.file_path.as_str()).or_default();
+            imports
+                .entry(source.file_path.as_str())
+                .or_default()
+                .insert(target.file_path.as_str());
+        }
+
+        let mut files: DiGraph<&str, ()> = DiGraph::new();
+        let indices: BTreeMap<&str, _> = imports
+            .keys()
+            .map(|&path| (path, files.add_node(path)))
+            .collect();
+        for (source, targets) in &imports {
+            for target in targets {
+                files.add_edge(indices[source], indices[target], ());
+            }
+        }
+
+        let mut cycles: Vec<Vec<String>> = tarjan_scc(&files)
+            .into_iter()
+            .filter(|scc| scc.len() > 1 || imports[files[scc[0]]].contains(files[scc[0]]))
+            .map(|scc| {
+                let members: BTreeSet<&str> = scc.iter().map(|&idx| files[idx]).collect();
+                order_cycle(&members, &imports)
+            })
+            .collect();
+        cycles.sort();
+        cycles
+    }
+
+    /// The structure of a file: the classes, functions, structs, enums and
+    /// interfaces it defines (its Defines edges), in source order, each
+    /// holding the elements whose line range it contains. Imports and
+    /// variables are left out.
+    ///
+    /// `file_path` is resolved with [`resolve_file`](Self::resolve_file).
+    pub fn file_outline(&self, file_path: &str) -> Result<Vec<OutlineNode>, FileLookupError> {
+        let mut nodes: Vec<OutlineNode> = self
+            .find_file_nodes(&self.resolve_file(file_path)?)
+            .into_iter()
+            .flat_map(|idx| self.graph.edges_directed(idx, Direction::Outgoing))
+            .filter(|edge| edge.weight().kind == EdgeKind::Defines)
+            .map(|edge| &self.graph[edge.target()])
+            .filter(|node| {
+                !matches!(
+                    node.kind,
+                    NodeKind::File | NodeKind::Module | NodeKind::Variable
+                )
+            })
+            .map(|node| {
+                let elem = self.element_arena.get(&node.id);
+                OutlineNode {
+                    id: node.id.clone(),
+                    name: node.name.to_string(),
+                    kind: node.kind,
+                    signature: elem.as_ref().and_then(|e| e.signature.clone()),
+                    doc: elem
+                        .as_ref()
+                        .and_then(|e| e.docstring.as_deref())
+                        .and_then(|doc| doc.lines().map(str::trim).find(|line| !line.is_empty()))
+                        .map(str::to_string),
+                    start_line: node.start_line,
+                    end_line: node.end_line,
+                    children: Vec::new(),
+                }
+            })
+            .collect();
+        // Enclosing elements sort before the elements they contain.
+        nodes.sort_by(|a, b| {
+            (a.start_line, Reverse(a.end_line), &a.id).cmp(&(
+                b.start_line,
+                Reverse(b.end_line),
+                &b.id,
+            ))
+        });
+        Ok(nest_outline(&mut nodes.into_iter().peekable(), usize::MAX))
+    }
+
     // --- internal helpers ---
 
-    fn find_nodes_by_name(&self, name: &str) -> Vec<NodeIndex> {
-        self.name_to_nodes
-            .get(name)
-            .map(|v| v.value().clone())
-            .unwrap_or_default()
+    /// Breadth-first expansion along Calls edges. Nodes already seen
+    /// (including the start nodes) are skipped, so cycles terminate.
+    fn expand_calls(
+        &self,
+        symbol: &str,
+        direction: Direction,
+        max_depth: usize,
+        limit: usize,
+    ) -> CallChain<'_> {
+        let mut frontier = self.find_nodes_by_name(symbol);
+        let mut visited: HashSet<NodeIndex> = frontier.iter().copied().collect();
+        let mut chain = CallChain::default();
+        let mut total = 0;
+
+        for _ in 0..max_depth {
+            let mut level = Vec::new();
+            let mut next_frontier = Vec::new();
+            'expand: for &idx in &frontier {
+                for edge in self.graph.edges_directed(idx, direction) {
+                    if edge.weight().kind != EdgeKind::Calls {
+                        continue;
+                    }
+                    let neighbor = match direction {
+                        Direction::Outgoing => edge.target(),
+                        Direction::Incoming => edge.source(),
+                    };
+                    if !visited.insert(neighbor) {
+                        continue;
+                    }
+                    if total == limit {
+                        chain.truncated = true;
+                        break 'expand;
+                    }
+                    total += 1;
+                    level.push(&self.graph[neighbor]);
+                    next_frontier.push(neighbor);
+                }
+            }
+            if level.is_empty() {
+                break;
+            }
+            chain.levels.push(level);
+            if chain.truncated {
+                break;
+            }
+            frontier = next_frontier;
+        }
+
+        chain
     }
 
     fn find_nodes_by_file(&self, file_path: &str) -> Vec<NodeIndex> {
@@ -229,6 +1084,230 @@ impl RepositoryGraph {
             .map(|v| v.value().clone())
             .unwrap_or_default()
     }
+
+    /// File nodes only — dependency queries start from the file itself.
+    fn find_file_nodes(&self, file_path: &str) -> Vec<NodeIndex> {
+        self.find_nodes_by_file(file_path)
+            .into_iter()
+            .filter(|&idx| self.graph[idx].kind == NodeKind::File)
+            .collect()
+    }
+
+    /// Neighbors of `idx` over edges allowed by `direction` and
+    /// `edge_kinds` (empty means all).
+    fn related_neighbors<'a>(
+        &'a self,
+        idx: NodeIndex,
+        direction: RelatedDirection,
+        edge_kinds: &'a [EdgeKind],
+    ) -> impl Iterator<Item = NodeIndex> + 'a {
+        [Direction::Outgoing, Direction::Incoming]
+            .into_iter()
+            .filter(move |&dir| direction.allows(dir))
+            .flat_map(move |dir| {
+                self.graph
+                    .edges_directed(idx, dir)
+                    .filter(move |edge| {
+                        edge_kinds.is_empty() || edge_kinds.contains(&edge.weight().kind)
+                    })
+                    .map(move |edge| match dir {
+                        Direction::Outgoing => edge.target(),
+                        Direction::Incoming => edge.source(),
+                    })
+            })
+    }
+
+    /// Nodes `symbol` names that are of a kind and in a file `filter`
+    /// allows.
+    fn find_nodes_filtered(&self, symbol: &str, filter: &QueryFilter) -> Vec<NodeIndex> {
+        let mut indices = self.find_nodes_by_name(symbol);
+        indices.retain(|&idx| {
+            let node = &self.graph[idx];
+            (filter.kinds.is_empty() || filter.kinds.contains(&node.kind))
+                && filter
+                    .symbol_files
+                    .as_ref()
+                    .is_none_or(|glob| self.in_files(node, glob))
+        });
+        indices
+    }
+
+    fn retain_result_files<'a>(
+        &self,
+        mut nodes: Vec<&'a GraphNode>,
+        filter: &QueryFilter,
+    ) -> Vec<&'a GraphNode> {
+        if let Some(glob) = &filter.result_files {
+            nodes.retain(|node| self.in_files(node, glob));
+        }
+        nodes
+    }
+
+    /// Whether the element `id` passes `filter`. Unknown IDs never do.
+    pub fn matches_search_filter(&self, id: &str, filter: &SearchFilter) -> bool {
+        self.element_arena
+            .get(id)
+            .is_some_and(|elem| filter.matches(&elem))
+    }
+
+    /// Whether `node`'s file, relative to the repo root, matches `glob`.
+    fn in_files(&self, node: &GraphNode, glob: &FileGlob) -> bool {
+        self.element_arena
+            .get(&node.id)
+            .is_some_and(|elem| glob.is_match(&elem.relative_path))
+    }
+
+    /// Collect unique neighbors of `indices` reachable over edges of `kind`
+    /// in the given direction, in first-seen order.
+    /// Call neighbors of `indices` in `direction` reached only over
+    /// low-confidence edges.
+    fn low_confidence_calls(&self, indices: &[NodeIndex], direction: Direction) -> HashSet<String> {
+        let mut confident = HashSet::new();
+        let mut guessed = HashSet::new();
+        for &idx in indices {
+            for edge in self.graph.edges_directed(idx, direction) {
+                if edge.weight().kind != EdgeKind::Calls {
+                    continue;
+                }
+                let neighbor = match direction {
+                    Direction::Outgoing => edge.target(),
+                    Direction::Incoming => edge.source(),
+                };
+                if edge.weight().low_confidence {
+                    guessed.insert(neighbor);
+                } else {
+                    confident.insert(neighbor);
+                }
+            }
+        }
+        guessed
+            .difference(&confident)
+            .map(|&idx| self.graph[idx].id.clone())
+            .collect()
+    }
+
+    fn collect_neighbors(
+        &self,
+        indices: &[NodeIndex],
+        direction: Direction,
+        kind: EdgeKind,
+    ) -> Vec<&GraphNode> {
+        let mut seen = HashSet::new();
+        let mut result = Vec::new();
+
+        for &idx in indices {
+            for edge in self.graph.edges_directed(idx, direction) {
+                if edge.weight().kind != kind {
+                    continue;
+                }
+                let neighbor = match direction {
+                    Direction::Outgoing => edge.target(),
+                    Direction::Incoming => edge.source(),
+                };
+                if seen.insert(neighbor) {
+                    result.push(&self.graph[neighbor]);
+                }
+            }
+        }
+
+        result
+    }
+}
+
+/// A closed walk over the imports of one strongly connected component that
+/// visits every member, starting from the smallest path: from the current
+/// file, go to the nearest unvisited member, then back to the start. The
+/// closing file is left off, as the last file imports the first.
+fn order_cycle(members: &BTreeSet<&str>, imports: &BTreeMap<&str, BTreeSet<&str>>) -> Vec<String> {
+    let Some(&start) = members.iter().next() else {
+        return Vec::new();
+    };
+    let mut walk = vec![start];
+    let mut visited = HashSet::from([start]);
+    let mut current = start;
+    while visited.len() < members.len() {
+        let Some(path) = import_path(members, imports, current, |file| !visited.contains(file))
+        else {
+            break;
+        };
+        visited.extend(path.iter().copied());
+        current = path[path.len() - 1];
+        walk.extend(path);
+    }
+    if let Some(back) = import_path(members, imports, current, |file| file == start) {
+        walk.extend(&back[..back.len() - 1]);
+    }
+    walk.into_iter().map(str::to_string).collect()
+}
+
+/// The shortest import path inside `members` from `from` to the nearest file
+/// matching `is_goal`, without `from` itself. Ties go to the smaller path.
+fn import_path<'a>(
+    members: &BTreeSet<&'a str>,
+    imports: &BTreeMap<&'a str, BTreeSet<&'a str>>,
+    from: &'a str,
+    is_goal: impl Fn(&str) -> bool,
+) -> Option<Vec<&'a str>> {
+    let mut parent: HashMap<&str, &str> = HashMap::new();
+    let mut queue = VecDeque::from([from]);
+    while let Some(file) = queue.pop_front() {
+        for &next in &imports[file] {
+            if !members.contains(next) {
+                continue;
+            }
+            if is_goal(next) {
+                let mut path = vec![next];
+                let mut step = file;
+                while step != from {
+                    path.push(step);
+                    step = parent[step];
+                }
+                path.reverse();
+                return Some(path);
+            }
+            if next != from && !parent.contains_key(next) {
+                parent.insert(next, file);
+                queue.push_back(next);
+            }
+        }
+    }
+    None
+}
+
+/// Take the nodes starting at or before line `end` from `nodes` (sorted by
+/// start line, enclosing nodes first), nesting each node's contents under it.
+fn nest_outline(
+    nodes: &mut Peekable<std::vec::IntoIter<OutlineNode>>,
+    end: usize,
+) -> Vec<OutlineNode> {
+    let mut level = Vec::new();
+    while let Some(mut node) = nodes.next_if(|node| node.start_line <= end) {
+        node.children = nest_outline(nodes, node.end_line);
+        level.push(node);
+    }
+    level
+}
+
+/// Names that are called by a runtime or test harness rather than by code.
+fn is_entry_point(name: &str) -> bool {
+    name == "main"
+        || (name.len() > 4 && name.starts_with("__") && name.ends_with("__"))
+        || name.starts_with("test_")
+        || name.ends_with("_test")
+        || name.starts_with("Test")
+}
+
+fn allowlisted(node: &GraphNode, entry: &str) -> bool {
+    let entry = entry.trim().trim_matches('/');
+    if entry.is_empty() {
+        return false;
+    }
+    let path = crate::utils::normalize_path(&node.file_path);
+    node.name == entry
+        || path == entry
+        || path.starts_with(&format!("{entry}/"))
+        || path.ends_with(&format!("/{entry}"))
+        || path.contains(&format!("/{entry}/"))
 }
 
 #[cfg(test)]
@@ -257,13 +1336,7 @@ mod tests {
             end_line: 5,
         });
 
-        repo.add_edge(
-            a,
-            b,
-            GraphEdge {
-                kind: EdgeKind::Calls,
-            },
-        );
+        repo.add_edge(a, b, GraphEdge::new(EdgeKind::Calls));
         repo
     }
 
@@ -286,10 +1359,118 @@ mod tests {
     #[test]
     fn test_find_path() {
         let repo = build_test_graph();
-        let path = repo.find_path("func_a", "func_b", None);
-        assert!(path.is_some());
-        let path = path.unwrap();
-        assert_eq!(path, vec!["func_a", "func_b"]);
+        let path = repo.find_path("func_a", "func_b", &[]).unwrap();
+        let ids: Vec<&str> = path.nodes.iter().map(|n| n.id.as_str()).collect();
+        assert_eq!(ids, vec!["func_a", "func_b"]);
+        assert_eq!(path.via, vec![EdgeKind::Calls]);
+        assert_eq!(path.hops(), 1);
+        assert!(repo.find_path("func_b", "func_a", &[]).is_none());
+        assert!(repo.find_path("func_a", "missing", &[]).is_none());
+    }
+
+    #[test]
+    fn test_find_path_edge_kinds() {
+        let mut repo = RepositoryGraph::new();
+        let mut node = |id: &str, kind: NodeKind, file_path: &str| {
+            repo.add_node(GraphNode {
+                id: id.into(),
+                kind,
+                name: SmolStr::new(id),
+                file_path: file_path.into(),
+                start_line: 1,
+                end_line: 5,
+            })
+        };
+        let handler = node("handler", NodeKind::Function, "api.py");
+        let save = node("save", NodeKind::Function, "service.py");
+        let insert = node("insert", NodeKind::Function, "db.py");
+        let api = node("api.py", NodeKind::File, "api.py");
+        let db = node("db.py", NodeKind::File, "db.py");
+        repo.add_edge(handler, save, GraphEdge::new(EdgeKind::Calls));
+        repo.add_edge(save, insert, GraphEdge::new(EdgeKind::Calls));
+        repo.add_edge(api, handler, GraphEdge::new(EdgeKind::Defines));
+        repo.add_edge(api, db, GraphEdge::new(EdgeKind::Imports));
+        repo.add_edge(db, insert, GraphEdge::new(EdgeKind::Defines));
+
+        let path = repo.find_path("handler", "insert", &[]).unwrap();
+        assert_eq!(
+            path.to_string(),
+            "handler --calls--> save --calls--> insert"
+        );
+        assert_eq!(path.hops(), 2);
+
+        // Defines edges are only followed when asked for.
+        assert!(repo.find_path("api.py", "insert", &[]).is_none());
+        let path = repo
+            .find_path("api.py", "insert", &[EdgeKind::Imports, EdgeKind::Defines])
+            .unwrap();
+        assert_eq!(
+            path.to_string(),
+            "api.py --imports--> db.py --defines--> insert"
+        );
+        assert!(
+            repo.find_path("handler", "insert", &[EdgeKind::Imports])
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_find_paths() {
+        let mut repo = RepositoryGraph::new();
+        let mut node = |id: &str| {
+            repo.add_node(GraphNode {
+                id: id.into(),
+                kind: NodeKind::Function,
+                name: SmolStr::new(id),
+                file_path: "app.py".into(),
+                start_line: 1,
+                end_line: 5,
+            })
+        };
+        let handler = node("handler");
+        let validate = node("validate");
+        let save = node("save");
+        let log = node("log");
+        let insert = node("insert");
+        let others: Vec<NodeIndex> = (0..5).map(|i| node(&format!("other_{i}"))).collect();
+        for (from, to) in [
+            (handler, log),
+            (handler, save),
+            (handler, validate),
+            (validate, save),
+            (save, insert),
+            (save, handler),
+            (log, insert),
+        ] {
+            repo.add_edge(from, to, GraphEdge::new(EdgeKind::Calls));
+        }
+        for other in others {
+            repo.add_edge(other, log, GraphEdge::new(EdgeKind::Calls));
+        }
+        let paths = |k: usize, max_len: usize| -> Vec<String> {
+            repo.find_paths("handler", "insert", &[], k, max_len)
+                .iter()
+                .map(ToString::to_string)
+                .collect()
+        };
+
+        // Of the two-hop routes, the one through the `log` hub comes last.
+        assert_eq!(
+            paths(3, DEFAULT_MAX_PATH_LEN),
+            vec![
+                "handler --calls--> save --calls--> insert",
+                "handler --calls--> log --calls--> insert",
+                "handler --calls--> validate --calls--> save --calls--> insert",
+            ]
+        );
+        assert_eq!(paths(10, DEFAULT_MAX_PATH_LEN).len(), 3);
+        assert_eq!(paths(1, DEFAULT_MAX_PATH_LEN).len(), 1);
+        assert_eq!(paths(10, 2).len(), 2);
+        assert!(paths(10, 1).is_empty());
+        assert!(
+            repo.find_paths("handler", "insert", &[EdgeKind::Imports], 3, 6)
+                .is_empty()
+        );
     }
 
     #[test]
@@ -298,5 +1479,642 @@ mod tests {
         let related = repo.get_related("func_a", 1);
         assert_eq!(related.len(), 1);
         assert_eq!(related[0].name.as_str(), "func_b");
+
+        assert!(
+            repo.get_related_filtered("func_a", 1, RelatedDirection::In, &[])
+                .is_empty()
+        );
+        assert!(
+            repo.get_related_filtered("func_a", 1, RelatedDirection::Both, &[EdgeKind::Imports])
+                .is_empty()
+        );
+        assert_eq!(
+            repo.get_related_filtered("func_b", 2, RelatedDirection::In, &[EdgeKind::Calls])
+                .len(),
+            1
+        );
+    }
+
+    /// `Session` in models.py: called by `open_db` (twice) and `reset`,
+    /// subclassed by `PooledSession`, imported by app.py, and calling
+    /// `connect`.
+    fn build_related_graph() -> RepositoryGraph {
+        let mut repo = RepositoryGraph::new();
+        let mut add = |name: &str, kind: NodeKind, file: &str| {
+            repo.add_node(GraphNode {
+                id: format!("{kind:?}_{name}").to_lowercase(),
+                kind,
+                name: SmolStr::new(name),
+                file_path: file.into(),
+                start_line: 1,
+                end_line: 5,
+            })
+        };
+        let models = add("models.py", NodeKind::File, "models.py");
+        let app = add("app.py", NodeKind::File, "app.py");
+        let session = add("Session", NodeKind::Class, "models.py");
+        let connect = add("connect", NodeKind::Function, "models.py");
+        let pooled = add("PooledSession", NodeKind::Class, "app.py");
+        let open_db = add("open_db", NodeKind::Function, "app.py");
+        let reset = add("reset", NodeKind::Function, "app.py");
+
+        for (file, elem) in [
+            (models, session),
+            (models, connect),
+            (app, pooled),
+            (app, open_db),
+            (app, reset),
+        ] {
+            repo.add_edge(file, elem, GraphEdge::new(EdgeKind::Defines));
+        }
+        repo.add_edge(app, session, GraphEdge::new(EdgeKind::Imports));
+        repo.add_edge(pooled, session, GraphEdge::new(EdgeKind::Inherits));
+        repo.add_edge(
+            open_db,
+            session,
+            GraphEdge {
+                kind: EdgeKind::Calls,
+                count: 2,
+                low_confidence: false,
+            },
+        );
+        repo.add_edge(reset, session, GraphEdge::new(EdgeKind::Calls));
+        repo.add_edge(session, connect, GraphEdge::new(EdgeKind::Calls));
+        repo
+    }
+
+    #[test]
+    fn test_get_related_ranked() {
+        let repo = build_related_graph();
+        let names = |ranked: &[RankedRelated<'_>]| -> Vec<String> {
+            ranked.iter().map(|r| r.node.name.to_string()).collect()
+        };
+
+        // Calls and inheritance rank above definitions and imports; the
+        // similarly named subclass ranks first.
+        let ranked = repo.get_related_ranked("Session", 2, RelatedDirection::Both, &[], 10);
+        assert_eq!(
+            names(&ranked),
+            vec![
+                "PooledSession",
+                "connect",
+                "open_db",
+                "reset",
+                "models.py",
+                "app.py"
+            ]
+        );
+        assert!(ranked[0].similarity > 0.0);
+        assert_eq!(ranked[1].similarity, 0.0);
+        assert!(ranked[0].score > ranked[1].score);
+        assert_eq!(ranked[4].via, vec![EdgeKind::Defines]);
+
+        let ranked = repo.get_related_ranked("connect", 2, RelatedDirection::In, &[], 3);
+        assert_eq!(
+            names(&ranked),
+            vec!["Session", "models.py", "PooledSession"]
+        );
+        let path: Vec<&str> = ranked[2].path.iter().map(|n| n.name.as_str()).collect();
+        assert_eq!(path, vec!["connect", "Session", "PooledSession"]);
+        assert_eq!(ranked[2].via, vec![EdgeKind::Calls, EdgeKind::Inherits]);
+        assert_eq!(ranked[2].hops, 2);
+    }
+
+    #[test]
+    fn test_summarize_related() {
+        let repo = build_related_graph();
+        let groups = |direction, kinds: &[EdgeKind]| -> Vec<(&'static str, usize, Vec<String>)> {
+            repo.summarize_related("Session", direction, kinds)
+                .into_iter()
+                .map(|group| {
+                    let names = group.nodes.iter().map(|n| n.name.to_string()).collect();
+                    (group.name, group.total, names)
+                })
+                .collect()
+        };
+        let strings =
+            |names: &[&str]| -> Vec<String> { names.iter().map(|n| n.to_string()).collect() };
+
+        assert_eq!(
+            groups(RelatedDirection::Both, &[]),
+            vec![
+                ("callers", 2, strings(&["open_db", "reset"])),
+                ("callees", 1, strings(&["connect"])),
+                ("importers", 1, strings(&["app.py"])),
+                ("subclasses", 1, strings(&["PooledSession"])),
+                ("superclasses", 0, strings(&[])),
+                ("defined_in", 1, strings(&["models.py"])),
+            ]
+        );
+
+        // Direction and kind filters drop whole groups.
+        let names = |direction, kinds: &[EdgeKind]| -> Vec<&'static str> {
+            groups(direction, kinds)
+                .into_iter()
+                .map(|(name, _, _)| name)
+                .collect()
+        };
+        assert_eq!(
+            names(RelatedDirection::In, &[]),
+            vec!["callers", "importers", "subclasses", "defined_in"]
+        );
+        assert_eq!(
+            names(RelatedDirection::Out, &[]),
+            vec!["callees", "superclasses"]
+        );
+        assert_eq!(
+            names(RelatedDirection::Both, &[EdgeKind::Calls]),
+            vec!["callers", "callees"]
+        );
+        assert_eq!(
+            names(
+                RelatedDirection::In,
+                &[EdgeKind::Inherits, EdgeKind::Imports]
+            ),
+            vec!["importers", "subclasses"]
+        );
+        assert!(
+            repo.summarize_related("missing", RelatedDirection::Both, &[])
+                .iter()
+                .all(|g| g.total == 0)
+        );
+    }
+
+    /// main -> handler -> service -> delete_user, with delete_user -> service
+    /// closing a cycle.
+    fn build_chain_graph() -> RepositoryGraph {
+        let mut repo = RepositoryGraph::new();
+        let names = ["main", "handler", "service", "delete_user"];
+        let nodes: Vec<NodeIndex> = names
+            .iter()
+            .map(|name| {
+                repo.add_node(GraphNode {
+                    id: format!("func_{name}"),
+                    kind: NodeKind::Function,
+                    name: SmolStr::new(name),
+                    file_path: "app.py".into(),
+                    start_line: 1,
+                    end_line: 5,
+                })
+            })
+            .collect();
+        for pair in nodes.windows(2) {
+            repo.add_edge(pair[0], pair[1], GraphEdge::new(EdgeKind::Calls));
+        }
+        repo.add_edge(nodes[3], nodes[2], GraphEdge::new(EdgeKind::Calls));
+        repo
+    }
+
+    fn level_names(chain: &CallChain<'_>) -> Vec<Vec<String>> {
+        chain
+            .levels
+            .iter()
+            .map(|level| level.iter().map(|n| n.name.to_string()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_find_callers_transitive() {
+        let repo = build_chain_graph();
+
+        let chain = repo.find_callers_transitive("delete_user", 10, DEFAULT_TRANSITIVE_LIMIT);
+        assert_eq!(
+            level_names(&chain),
+            vec![vec!["service"], vec!["handler"], vec!["main"]]
+        );
+        assert!(!chain.truncated);
+
+        let chain = repo.find_callers_transitive("delete_user", 2, DEFAULT_TRANSITIVE_LIMIT);
+        assert_eq!(level_names(&chain), vec![vec!["service"], vec!["handler"]]);
+
+        let chain = repo.find_callers_transitive("delete_user", 10, 2);
+        assert_eq!(chain.len(), 2);
+        assert!(chain.truncated);
+    }
+
+    #[test]
+    fn test_find_callees_transitive_stops_on_cycles() {
+        let repo = build_chain_graph();
+        let chain = repo.find_callees_transitive("main", 10, DEFAULT_TRANSITIVE_LIMIT);
+        assert_eq!(
+            level_names(&chain),
+            vec![vec!["handler"], vec!["service"], vec!["delete_user"]]
+        );
+        assert!(repo.find_callees_transitive("missing", 3, 10).is_empty());
+    }
+
+    #[test]
+    fn test_find_unreferenced() {
+        let mut repo = RepositoryGraph::new();
+        let mut add = |name: &str, kind: NodeKind, file: &str| {
+            repo.add_node(GraphNode {
+                id: format!("{file}::{name}"),
+                kind,
+                name: SmolStr::new(name),
+                file_path: file.into(),
+                start_line: 1,
+                end_line: 5,
+            })
+        };
+        let app = add("app.py", NodeKind::File, "app.py");
+        let lib = add("lib.py", NodeKind::File, "lib.py");
+        let main = add("main", NodeKind::Function, "app.py");
+        let used = add("used", NodeKind::Function, "app.py");
+        add("orphan", NodeKind::Function, "app.py");
+        add("Orphaned", NodeKind::Class, "app.py");
+        add("test_orphan", NodeKind::Function, "app.py");
+        add("__init__", NodeKind::Method, "app.py");
+        add("helper", NodeKind::Function, "scripts/tool.py");
+        add("imported_file_fn", NodeKind::Function, "lib.py");
+        repo.add_edge(main, used, GraphEdge::new(EdgeKind::Calls));
+        repo.add_edge(app, lib, GraphEdge::new(EdgeKind::Imports));
+
+        let kinds = [NodeKind::Function, NodeKind::Method, NodeKind::Class];
+        let names = |nodes: Vec<&GraphNode>| -> Vec<String> {
+            nodes.iter().map(|n| n.name.to_string()).collect()
+        };
+        assert_eq!(
+            names(repo.find_unreferenced(&kinds)),
+            vec!["orphan", "Orphaned", "helper"]
+        );
+        assert_eq!(
+            names(repo.find_unreferenced(&[NodeKind::Class])),
+            vec!["Orphaned"]
+        );
+        assert_eq!(
+            names(
+                repo.find_unreferenced_except(
+                    &kinds,
+                    &["scripts/".to_string(), "orphan".to_string()]
+                )
+            ),
+            vec!["Orphaned"]
+        );
+    }
+
+    #[test]
+    fn test_find_string_references() {
+        let dir = tempfile::tempdir().unwrap();
+        let files = [
+            (
+                "payments.py",
+                "def process_payment(order):\n    return order\n\ndef process(x):\n    return x\n",
+            ),
+            (
+                "dispatch.py",
+                "import importlib\n\ndef dispatch(obj):\n    handler = getattr(obj, \"process_payment\")\n    return handler()\n\ndef log(obj):\n    print(\"failed to process the order\")\n    return getattr(obj, \"process\")\n\ndef load_tasks():\n    return importlib.import_module(\"payments\")\n",
+            ),
+        ];
+        for (name, code) in files {
+            std::fs::write(dir.path().join(name), code).unwrap();
+        }
+        let root = dir
+            .path()
+            .canonicalize()
+            .unwrap()
+            .to_string_lossy()
+            .to_string();
+        let elements = crate::indexer::walk_and_index(&root, &Default::default());
+
+        // Off unless the repo opts in.
+        let mut repo = RepositoryGraph::new();
+        repo.build_from_elements(&elements, &root);
+        assert!(repo.find_string_references("process_payment").is_empty());
+
+        std::fs::create_dir_all(dir.path().join(".happy")).unwrap();
+        std::fs::write(
+            dir.path().join(".happy/agent.toml"),
+            "[index]\ndetect_string_refs = true\n",
+        )
+        .unwrap();
+        let mut repo = RepositoryGraph::new();
+        repo.build_from_elements(&elements, &root);
+        let names = |nodes: Vec<&GraphNode>| -> Vec<String> {
+            nodes.iter().map(|n| n.name.to_string()).collect()
+        };
+
+        assert_eq!(
+            names(repo.find_string_references("process_payment")),
+            vec!["dispatch"]
+        );
+        assert!(repo.find_callers("process_payment").is_empty());
+        // A common word spelled by a string is not a reference, even when a
+        // symbol has that name.
+        assert!(repo.find_string_references("process").is_empty());
+        assert_eq!(
+            repo.get_related_filtered(
+                "load_tasks",
+                1,
+                RelatedDirection::Out,
+                &[EdgeKind::ReferencesByName]
+            )
+            .iter()
+            .map(|n| (n.kind, n.file_path.clone()))
+            .collect::<Vec<_>>(),
+            vec![(NodeKind::File, format!("{root}/payments.py"))]
+        );
+        assert!(
+            !names(repo.find_unreferenced(&[NodeKind::Function]))
+                .contains(&"process_payment".to_string())
+        );
+    }
+
+    #[test]
+    fn test_file_outline() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("auth.py"),
+            "import os\n\nclass Session:\n    \"\"\"A login session.\n\n    Details.\n    \"\"\"\n\n    def open(self):\n        return 1\n\n    def close(self):\n        return 2\n\ndef login(user):\n    return Session()\n",
+        )
+        .unwrap();
+        let root = dir
+            .path()
+            .canonicalize()
+            .unwrap()
+            .to_string_lossy()
+            .to_string();
+        let mut repo = RepositoryGraph::new();
+        repo.build_from_elements(
+            &crate::indexer::walk_and_index(&root, &Default::default()),
+            &root,
+        );
+
+        let outline = repo.file_outline("auth.py").unwrap();
+        let shape: Vec<(&str, NodeKind, Vec<&str>)> = outline
+            .iter()
+            .map(|node| {
+                (
+                    node.name.as_str(),
+                    node.kind,
+                    node.children.iter().map(|c| c.name.as_str()).collect(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            shape,
+            vec![
+                ("Session", NodeKind::Class, vec!["open", "close"]),
+                ("login", NodeKind::Function, vec![]),
+            ]
+        );
+        let session = &outline[0];
+        assert_eq!((session.start_line, session.end_line), (3, 13));
+        assert_eq!(session.doc.as_deref(), Some("A login session."));
+        assert_eq!(session.signature.as_deref(), Some("class Session:"));
+        assert_eq!(
+            session.children[0].signature.as_deref(),
+            Some("def open(self):")
+        );
+        assert_eq!(
+            repo.file_outline("missing.py"),
+            Err(FileLookupError::NotFound("missing.py".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_parent_and_children() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("auth.py"),
+            "class Session:\n    class Token:\n        def refresh(self):\n            return 1\n\n    def open(self):\n        return 1\n\ndef login():\n    return Session()\n",
+        )
+        .unwrap();
+        let root = dir
+            .path()
+            .canonicalize()
+            .unwrap()
+            .to_string_lossy()
+            .to_string();
+        let mut repo = RepositoryGraph::new();
+        repo.build_from_elements(
+            &crate::indexer::walk_and_index(&root, &Default::default()),
+            &root,
+        );
+        let id = |name: &str| repo.graph[repo.find_nodes_by_name(name)[0]].id.clone();
+
+        let names = |nodes: Vec<&GraphNode>| -> Vec<String> {
+            nodes.iter().map(|n| n.name.to_string()).collect()
+        };
+        assert_eq!(
+            names(repo.get_children(&id("Session"))),
+            vec!["Token", "open"]
+        );
+        assert_eq!(names(repo.get_children(&id("Token"))), vec!["refresh"]);
+        assert_eq!(
+            repo.get_parent(&id("refresh")).map(|n| n.name.as_str()),
+            Some("Token")
+        );
+        assert_eq!(
+            repo.get_parent(&id("Token")).map(|n| n.name.as_str()),
+            Some("Session")
+        );
+        assert!(repo.get_parent(&id("login")).is_none());
+        assert!(repo.get_children("missing").is_empty());
+    }
+
+    #[test]
+    fn test_filtered_callers() {
+        let dir = tempfile::tempdir().unwrap();
+        let files = [
+            (
+                "api/routes.py",
+                "def handler():\n    return 1\n\ndef serve():\n    return handler()\n",
+            ),
+            (
+                "web/app.py",
+                "class App:\n    def handler(self):\n        return 1\n\n    def run(self):\n        return self.handler()\n",
+            ),
+            (
+                "tests/test_routes.py",
+                "from api.routes import handler\n\ndef test_handler():\n    return handler()\n",
+            ),
+        ];
+        for (name, code) in files {
+            let path = dir.path().join(name);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, code).unwrap();
+        }
+        let root = dir
+            .path()
+            .canonicalize()
+            .unwrap()
+            .to_string_lossy()
+            .to_string();
+        let mut repo = RepositoryGraph::new();
+        repo.build_from_elements(
+            &crate::indexer::walk_and_index(&root, &Default::default()),
+            &root,
+        );
+        let glob = |pattern: &str| Some(FileGlob::new(pattern).unwrap());
+        let callers = |filter: QueryFilter| -> Vec<String> {
+            let mut names: Vec<String> = repo
+                .find_callers_filtered("handler", &filter)
+                .iter()
+                .map(|n| n.name.to_string())
+                .collect();
+            names.sort();
+            names
+        };
+
+        assert_eq!(
+            callers(QueryFilter::default()),
+            vec!["run", "serve", "test_handler"]
+        );
+        let methods = QueryFilter {
+            kinds: vec![NodeKind::Method],
+            ..Default::default()
+        };
+        assert_eq!(callers(methods), vec!["run"]);
+        let in_api = QueryFilter {
+            symbol_files: glob("api"),
+            ..Default::default()
+        };
+        assert_eq!(callers(in_api), vec!["serve", "test_handler"]);
+        let from_tests = QueryFilter {
+            symbol_files: glob("api/*.py"),
+            result_files: glob("tests/"),
+            ..Default::default()
+        };
+        assert_eq!(callers(from_tests), vec!["test_handler"]);
+
+        let py = FileGlob::new("*.py").unwrap();
+        assert!(py.is_match("web/app.py"));
+        assert!(!py.is_match("web/app.ts"));
+        assert!(!py.is_match("/abs/app.py"));
+    }
+
+    #[test]
+    fn test_find_import_cycles() {
+        let dir = tempfile::tempdir().unwrap();
+        let files = [
+            ("a.py", "import b\n\ndef run():\n    return b.step()\n"),
+            ("b.py", "import c\n\ndef step():\n    return c.finish()\n"),
+            ("c.py", "import a\n\ndef finish():\n    return 1\n"),
+            ("d.py", "import a\n"),
+        ];
+        for (name, code) in files {
+            std::fs::write(dir.path().join(name), code).unwrap();
+        }
+        let root = dir
+            .path()
+            .canonicalize()
+            .unwrap()
+            .to_string_lossy()
+            .to_string();
+        let mut repo = RepositoryGraph::new();
+        repo.build_from_elements(
+            &crate::indexer::walk_and_index(&root, &Default::default()),
+            &root,
+        );
+
+        let cycles = repo.find_import_cycles();
+        assert_eq!(
+            cycles,
+            vec![vec![
+                format!("{root}/a.py"),
+                format!("{root}/b.py"),
+                format!("{root}/c.py"),
+            ]]
+        );
+        assert!(RepositoryGraph::new().find_import_cycles().is_empty());
+    }
+
+    #[test]
+    fn test_order_cycle_walks_import_edges() {
+        let imports: BTreeMap<&str, BTreeSet<&str>> = BTreeMap::from([
+            ("a", BTreeSet::from(["b", "c"])),
+            ("b", BTreeSet::from(["a"])),
+            ("c", BTreeSet::from(["a"])),
+        ]);
+        let members = BTreeSet::from(["a", "b", "c"]);
+        let walk = order_cycle(&members, &imports);
+        assert_eq!(walk, vec!["a", "b", "a", "c"]);
+        // Every step, including the closing one, is an import.
+        for (from, to) in walk.iter().zip(walk.iter().cycle().skip(1)) {
+            assert!(
+                imports[from.as_str()].contains(to.as_str()),
+                "{from} -> {to}"
+            );
+        }
+
+        let imports: BTreeMap<&str, BTreeSet<&str>> =
+            BTreeMap::from([("a", BTreeSet::from(["a"]))]);
+        assert_eq!(order_cycle(&BTreeSet::from(["a"]), &imports), vec!["a"]);
+    }
+
+    #[test]
+    fn test_resolve_file_paths() {
+        let dir = tempfile::tempdir().unwrap();
+        let files = [
+            (
+                "app.py",
+                "import auth\n\ndef run():\n    return auth.login()\n",
+            ),
+            ("auth.py", "def login():\n    return 1\n"),
+            ("lib/x/util.py", "def x_helper():\n    return 1\n"),
+            ("lib/y/util.py", "def y_helper():\n    return 2\n"),
+        ];
+        for (name, code) in files {
+            let path = dir.path().join(name);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, code).unwrap();
+        }
+        let root = dir
+            .path()
+            .canonicalize()
+            .unwrap()
+            .to_string_lossy()
+            .to_string();
+        let mut repo = RepositoryGraph::new();
+        repo.build_from_elements(
+            &crate::indexer::walk_and_index(&root, &Default::default()),
+            &root,
+        );
+        let app = format!("{root}/app.py");
+        let auth = format!("{root}/auth.py");
+        let files_of = |nodes: Vec<&GraphNode>| -> Vec<String> {
+            nodes.iter().map(|n| n.file_path.clone()).collect()
+        };
+
+        // Absolute, relative, and `./`-prefixed inputs all find the same file.
+        assert_eq!(
+            files_of(repo.get_dependencies(&app).unwrap()),
+            vec![auth.clone()]
+        );
+        assert_eq!(
+            files_of(repo.get_dependencies("app.py").unwrap()),
+            vec![auth.clone()]
+        );
+        assert_eq!(
+            files_of(repo.get_dependents("./auth.py").unwrap()),
+            vec![app.clone()]
+        );
+
+        // A unique suffix wins; an ambiguous one lists the candidates.
+        assert_eq!(
+            repo.resolve_file("x/util.py"),
+            Ok(format!("{root}/lib/x/util.py"))
+        );
+        assert_eq!(
+            repo.get_dependencies("util.py").unwrap_err(),
+            FileLookupError::Ambiguous {
+                input: "util.py".to_string(),
+                candidates: vec!["lib/x/util.py".to_string(), "lib/y/util.py".to_string()],
+            }
+        );
+        assert_eq!(
+            repo.get_dependents("/elsewhere/app.py").unwrap_err(),
+            FileLookupError::NotFound("/elsewhere/app.py".to_string())
+        );
+
+        assert!(!repo.element_ids_for_file("lib/x/util.py").is_empty());
+        assert!(repo.element_ids_for_file("util.py").is_empty());
+        repo.remove_file("util.py");
+        assert_eq!(repo.file_paths().len(), 4);
+        repo.remove_file("lib/y/util.py");
+        assert_eq!(
+            repo.resolve_file("util.py"),
+            Ok(format!("{root}/lib/x/util.py"))
+        );
     }
 }
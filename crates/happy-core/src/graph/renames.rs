@@ -0,0 +1,227 @@
+use std::collections::{HashMap, VecDeque};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::indexer::{CodeElement, ElementType};
+
+/// Maximum number of rename records kept.
+pub const MAX_RENAMES: usize = 1024;
+
+/// Rename records older than this (in seconds) are pruned.
+pub const MAX_RENAME_AGE_SECS: u64 = 30 * 24 * 60 * 60;
+
+/// A tombstone left when an element was renamed by an incremental update.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RenameRecord {
+    pub old_id: String,
+    pub new_id: String,
+    pub old_name: String,
+    pub new_name: String,
+    pub file_path: String,
+    /// Unix timestamp (seconds) of the update that detected the rename.
+    pub renamed_at: u64,
+}
+
+/// Bounded, age-limited redirect map from old element IDs to new ones.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RenameMap {
+    /// Oldest first.
+    records: VecDeque<RenameRecord>,
+}
+
+impl RenameMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+
+    /// Record a rename, then prune by age and size.
+    pub fn record(&mut self, record: RenameRecord) {
+        let now = record.renamed_at;
+        self.records.retain(|r| r.old_id != record.old_id);
+        self.records.push_back(record);
+        self.prune(now);
+    }
+
+    /// Drop records older than `MAX_RENAME_AGE_SECS` relative to `now`, then
+    /// the oldest ones beyond `MAX_RENAMES`.
+    pub fn prune(&mut self, now: u64) {
+        self.records
+            .retain(|r| now.saturating_sub(r.renamed_at) <= MAX_RENAME_AGE_SECS);
+        while self.records.len() > MAX_RENAMES {
+            self.records.pop_front();
+        }
+    }
+
+    /// Follow renames starting from `old_id` to the newest ID.
+    ///
+    /// The returned record spans the whole chain: `old_*` from the first
+    /// rename, `new_*` from the last. `None` when `old_id` was never renamed.
+    pub fn resolve(&self, old_id: &str) -> Option<RenameRecord> {
+        let mut current = self.latest_from(old_id)?.clone();
+        for _ in 0..self.records.len() {
+            match self.latest_from(&current.new_id) {
+                Some(next) if next.new_id != old_id => {
+                    current.new_id = next.new_id.clone();
+                    current.new_name = next.new_name.clone();
+                    current.file_path = next.file_path.clone();
+                    current.renamed_at = next.renamed_at;
+                }
+                _ => break,
+            }
+        }
+        Some(current)
+    }
+
+    /// The newest rename whose old name was `name`.
+    pub fn find_by_old_name(&self, name: &str) -> Option<&RenameRecord> {
+        self.records.iter().rev().find(|r| r.old_name == name)
+    }
+
+    /// Up to `limit` records, newest first.
+    pub fn recent(&self, limit: usize) -> Vec<RenameRecord> {
+        self.records.iter().rev().take(limit).cloned().collect()
+    }
+
+    fn latest_from(&self, old_id: &str) -> Option<&RenameRecord> {
+        self.records.iter().rev().find(|r| r.old_id == old_id)
+    }
+}
+
+/// Current Unix time in seconds.
+pub(crate) fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Structural fingerprint of an element: its kind plus a hash of its code
+/// with the element's own name masked out and whitespace collapsed, so a
+/// pure rename keeps the fingerprint.
+fn fingerprint(elem: &CodeElement) -> (ElementType, blake3::Hash) {
+    let mut normalized = String::with_capacity(elem.code.len());
+    for token in elem.code.split_whitespace() {
+        let masked = mask_identifier(token, &elem.name);
+        normalized.push_str(&masked);
+        normalized.push(' ');
+    }
+    (elem.element_type, blake3::hash(normalized.as_bytes()))
+}
+
+/// Replace whole-identifier occurrences of `name` in `token`.
+fn mask_identifier(token: &str, name: &str) -> String {
+    if name.is_empty() {
+        return token.to_string();
+    }
+    let is_ident = |c: char| c.is_alphanumeric() || c == '_';
+    let mut out = String::with_capacity(token.len());
+    let mut last = 0;
+    for (pos, _) in token.match_indices(name) {
+        let before = token[..pos].chars().next_back();
+        let after = token[pos + name.len()..].chars().next();
+        if before.is_some_and(is_ident) || after.is_some_and(is_ident) {
+            continue;
+        }
+        out.push_str(&token[last..pos]);
+        out.push('\u{0}');
+        last = pos + name.len();
+    }
+    out.push_str(&token[last..]);
+    out
+}
+
+/// Pair removed elements with added ones that have the same fingerprint.
+/// A pair may keep its name when only the ID changed (e.g. a method of a
+/// renamed class).
+///
+/// Only unambiguous pairs count: a fingerprint must occur exactly once among
+/// the removed elements and once among the added ones. File elements never
+/// match.
+pub(crate) fn detect_renames(
+    removed: &[CodeElement],
+    added: &[CodeElement],
+) -> Vec<(CodeElement, CodeElement)> {
+    fn unique_by_fingerprint(
+        elements: &[CodeElement],
+    ) -> HashMap<(ElementType, blake3::Hash), Option<&CodeElement>> {
+        let mut map: HashMap<_, Option<&CodeElement>> = HashMap::new();
+        for elem in elements {
+            if elem.element_type == ElementType::File {
+                continue;
+            }
+            map.entry(fingerprint(elem))
+                .and_modify(|slot| *slot = None)
+                .or_insert(Some(elem));
+        }
+        map
+    }
+
+    let added = unique_by_fingerprint(added);
+    let mut pairs: Vec<(CodeElement, CodeElement)> = unique_by_fingerprint(removed)
+        .into_iter()
+        .filter_map(|(key, old)| {
+            let new = (*added.get(&key)?)?;
+            Some((old?.clone(), new.clone()))
+        })
+        .collect();
+    pairs.sort_by(|a, b| a.0.id.cmp(&b.0.id));
+    pairs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(old: &str, new: &str, at: u64) -> RenameRecord {
+        RenameRecord {
+            old_id: format!("id_{old}"),
+            new_id: format!("id_{new}"),
+            old_name: old.to_string(),
+            new_name: new.to_string(),
+            file_path: "a.py".to_string(),
+            renamed_at: at,
+        }
+    }
+
+    #[test]
+    fn test_resolve_follows_chains_and_prunes() {
+        let mut map = RenameMap::new();
+        map.record(record("a", "b", 100));
+        map.record(record("b", "c", 200));
+
+        let resolved = map.resolve("id_a").unwrap();
+        assert_eq!(resolved.old_name, "a");
+        assert_eq!(resolved.new_id, "id_c");
+        assert_eq!(resolved.new_name, "c");
+        assert!(map.resolve("id_c").is_none());
+
+        map.prune(200 + MAX_RENAME_AGE_SECS);
+        assert_eq!(map.len(), 1);
+        assert_eq!(map.recent(10)[0].old_name, "b");
+
+        for i in 0..MAX_RENAMES + 5 {
+            map.record(record(&format!("f{i}"), &format!("g{i}"), 300));
+        }
+        assert_eq!(map.len(), MAX_RENAMES);
+        assert!(map.resolve("id_f0").is_none());
+    }
+
+    #[test]
+    fn test_mask_identifier() {
+        assert_eq!(mask_identifier("load(x):", "load"), "\u{0}(x):");
+        assert_eq!(mask_identifier("loader(x)", "load"), "loader(x)");
+        assert_eq!(
+            mask_identifier("self.load.load", "load"),
+            "self.\u{0}.\u{0}"
+        );
+    }
+}
@@ -0,0 +1,229 @@
+//! Inheritance for Rust, from `impl Trait for Type` blocks.
+//!
+//! The indexer records the type and trait of each impl block in element
+//! metadata. The type's struct or enum gets an `Inherits` edge to the
+//! trait, so `get_subclasses("Trait")` lists implementing types rather than
+//! impl blocks. A type defined outside the repository, such as `Vec<Foo>`,
+//! has no node of its own and the impl block stands in for it. Traits
+//! outside the repository, such as `Display`, are not linked.
+
+use petgraph::stable_graph::NodeIndex;
+
+use super::types::{EdgeKind, NodeKind};
+use super::{RepositoryGraph, add_semantic_edge};
+use crate::indexer::{CodeElement, ElementType, IMPL_TRAIT_KEY, IMPL_TYPE_KEY};
+
+impl RepositoryGraph {
+    /// Link the types of trait impls among `elements` to their traits.
+    /// Safe to run again over all Rust impls after an update: an edge
+    /// already present is left as is.
+    pub(super) fn build_rust_impl_edges(&mut self, elements: &[CodeElement]) {
+        for elem in elements {
+            if elem.language != "rust" || elem.element_type != ElementType::Class {
+                continue;
+            }
+            let (Some(impl_type), Some(impl_trait)) = (
+                elem.metadata.get(IMPL_TYPE_KEY),
+                elem.metadata.get(IMPL_TRAIT_KEY),
+            ) else {
+                continue;
+            };
+            let Some(impl_idx) = self.id_to_node.get(&elem.id).map(|idx| *idx) else {
+                continue;
+            };
+            let Some(trait_idx) =
+                self.rust_definition(impl_trait, &elem.file_path, &[NodeKind::Interface])
+            else {
+                continue;
+            };
+            let type_idx = self
+                .rust_definition(
+                    impl_type,
+                    &elem.file_path,
+                    &[NodeKind::Struct, NodeKind::Enum],
+                )
+                .unwrap_or(impl_idx);
+            if type_idx != trait_idx && !self.has_edge(type_idx, trait_idx, EdgeKind::Inherits) {
+                add_semantic_edge(&mut self.graph, type_idx, trait_idx, EdgeKind::Inherits);
+            }
+        }
+    }
+
+    /// A Rust definition named `name` of one of `kinds`, preferring one in
+    /// `file_path`.
+    fn rust_definition(
+        &self,
+        name: &str,
+        file_path: &str,
+        kinds: &[NodeKind],
+    ) -> Option<NodeIndex> {
+        let nodes = self.name_to_nodes.get(name)?;
+        let mut candidates = nodes.iter().copied().filter(|&idx| {
+            let node = &self.graph[idx];
+            kinds.contains(&node.kind) && node.file_path.ends_with(".rs")
+        });
+        let first = candidates.next()?;
+        Some(
+            std::iter::once(first)
+                .chain(candidates)
+                .find(|&idx| self.graph[idx].file_path == file_path)
+                .unwrap_or(first),
+        )
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "lang-rust")]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn names(nodes: Vec<&super::super::types::GraphNode>) -> Vec<String> {
+        let mut names: Vec<String> = nodes.into_iter().map(|n| n.name.to_string()).collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn test_rust_trait_impls() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        std::fs::create_dir_all(root.join("src")).unwrap();
+        std::fs::write(
+            root.join("src/shapes.rs"),
+            r#"use std::fmt;
+
+pub trait Shape {
+    fn area(&self) -> f64;
+
+    fn describe(&self) -> String {
+        format!("area {}", self.area())
+    }
+}
+
+pub struct Square {
+    side: f64,
+}
+
+impl Square {
+    pub fn new(side: f64) -> Self {
+        Self { side }
+    }
+
+    pub fn area(&self) -> f64 {
+        self.side * self.side
+    }
+}
+
+impl Shape for Square {
+    fn area(&self) -> f64 {
+        Square::area(self)
+    }
+}
+
+impl fmt::Display for Square {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "square {}", self.side)
+    }
+}
+
+pub enum Blob<T> {
+    Empty,
+    Full(T),
+}
+
+impl<T: Clone> crate::shapes::Shape for Blob<T> {
+    fn area(&self) -> f64 {
+        0.0
+    }
+}
+
+impl<'a> Shape for &'a [f64] {
+    fn area(&self) -> f64 {
+        self.iter().sum()
+    }
+}
+"#,
+        )
+        .unwrap();
+        let root = root.to_string_lossy().to_string();
+        let elements = crate::indexer::walk_and_index(&root, &Default::default());
+
+        let shape = elements.iter().find(|e| e.name == "Shape").unwrap();
+        assert_eq!(shape.element_type, ElementType::Interface);
+        let impls: Vec<(&str, Option<&str>)> = elements
+            .iter()
+            .filter(|e| e.element_type == ElementType::Class)
+            .map(|e| {
+                (
+                    e.name.as_str(),
+                    e.metadata.get(IMPL_TRAIT_KEY).map(String::as_str),
+                )
+            })
+            .collect();
+        assert_eq!(
+            impls,
+            vec![
+                ("Square", None),
+                ("Square", Some("Shape")),
+                ("Square", Some("Display")),
+                ("Blob", Some("Shape")),
+                ("[f64]", Some("Shape")),
+            ]
+        );
+        let areas: Vec<(Option<&str>, Option<&str>)> = elements
+            .iter()
+            .filter(|e| e.name == "area" && e.element_type == ElementType::Method)
+            .filter(|e| e.metadata.contains_key(IMPL_TYPE_KEY))
+            .map(|e| {
+                (
+                    e.metadata.get(IMPL_TYPE_KEY).map(String::as_str),
+                    e.metadata.get(IMPL_TRAIT_KEY).map(String::as_str),
+                )
+            })
+            .collect();
+        assert_eq!(
+            areas,
+            vec![
+                (Some("Square"), None),
+                (Some("Square"), Some("Shape")),
+                (Some("Blob"), Some("Shape")),
+                (Some("[f64]"), Some("Shape")),
+            ]
+        );
+        let mut ids: Vec<&str> = elements.iter().map(|e| e.id.as_str()).collect();
+        ids.sort();
+        ids.dedup();
+        assert_eq!(ids.len(), elements.len());
+
+        let mut graph = RepositoryGraph::new();
+        graph.build_from_elements(&elements, &root);
+
+        assert_eq!(
+            names(graph.get_subclasses("Shape")),
+            vec!["Blob", "Square", "[f64]"]
+        );
+        assert_eq!(names(graph.get_superclasses("Square")), vec!["Shape"]);
+        assert_eq!(graph.find_nodes_by_name("Square::area").len(), 2);
+        assert_eq!(graph.find_nodes_by_name("Blob::area").len(), 1);
+        let trait_of = |symbol: &str| -> Vec<Option<String>> {
+            graph
+                .find_nodes_by_name(symbol)
+                .into_iter()
+                .map(|idx| {
+                    let elem = graph.element_arena.get(&graph.graph[idx].id).unwrap();
+                    elem.metadata.get(IMPL_TRAIT_KEY).cloned()
+                })
+                .collect()
+        };
+        assert_eq!(
+            trait_of("<Square as Shape>::area"),
+            vec![Some("Shape".to_string())]
+        );
+        assert_eq!(
+            trait_of("<Square as fmt::Display>::fmt"),
+            vec![Some("Display".to_string())]
+        );
+        assert!(trait_of("<Blob as Display>::fmt").is_empty());
+    }
+}
@@ -0,0 +1,160 @@
+use std::cmp::Reverse;
+
+use serde::Serialize;
+
+use super::RepositoryGraph;
+
+/// Most lines in a snippet.
+pub const SNIPPET_MAX_LINES: usize = 5;
+
+/// Fewest lines a snippet is trimmed down to.
+const SNIPPET_MIN_LINES: usize = 2;
+
+/// Lines of an element scanned for query terms; bounds the work spent on
+/// whole-file elements.
+const MAX_SCAN_LINES: usize = 2_000;
+
+/// Characters kept per snippet line.
+const MAX_LINE_CHARS: usize = 160;
+
+/// A few lines of a search hit around its densest cluster of query terms.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Snippet {
+    /// File line number of the first snippet line.
+    pub line: usize,
+    pub text: String,
+    /// Query terms found in the element's code, in query order.
+    pub matched_terms: Vec<String>,
+}
+
+impl Snippet {
+    /// Pick the window of up to [`SNIPPET_MAX_LINES`] lines of `code` (which
+    /// starts at file line `start_line`) that matches the most distinct query
+    /// terms, then the most occurrences, then the most centered matches, and
+    /// trim blank edge lines. Code without a matching line yields its first
+    /// lines.
+    pub fn extract(code: &str, start_line: usize, query: &str) -> Self {
+        let mut terms: Vec<String> = Vec::new();
+        for term in crate::utils::tokenize(query) {
+            if !terms.contains(&term) {
+                terms.push(term);
+            }
+        }
+        let lines: Vec<&str> = code.lines().take(MAX_SCAN_LINES).collect();
+        // For each line, how often each query term occurs in it.
+        let hits: Vec<Vec<usize>> = lines
+            .iter()
+            .map(|line| {
+                let tokens = crate::utils::tokenize(line);
+                terms
+                    .iter()
+                    .map(|term| tokens.iter().filter(|token| *token == term).count())
+                    .collect()
+            })
+            .collect();
+
+        let window = SNIPPET_MAX_LINES.min(lines.len());
+        let matches = |line: &Vec<usize>| line.iter().any(|&count| count > 0);
+        let mut best = (0, (0, 0, Reverse(usize::MAX)));
+        for start in 0..=lines.len() - window {
+            let window_hits = &hits[start..start + window];
+            let distinct = (0..terms.len())
+                .filter(|&t| window_hits.iter().any(|line| line[t] > 0))
+                .count();
+            let occurrences: usize = window_hits.iter().flatten().sum();
+            // Among equally good windows, prefer the one that centers the
+            // matches.
+            let leading = window_hits.iter().take_while(|line| !matches(line)).count();
+            let trailing = window_hits
+                .iter()
+                .rev()
+                .take_while(|line| !matches(line))
+                .count();
+            let key = (distinct, occurrences, Reverse(leading.abs_diff(trailing)));
+            if key > best.1 {
+                best = (start, key);
+            }
+        }
+
+        let (mut first, mut end) = (best.0, best.0 + window);
+        while end - first > SNIPPET_MIN_LINES && lines[first].trim().is_empty() {
+            first += 1;
+        }
+        while end - first > SNIPPET_MIN_LINES && lines[end - 1].trim().is_empty() {
+            end -= 1;
+        }
+        let text = lines[first..end]
+            .iter()
+            .map(|line| truncate_line(line))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let matched_terms = terms
+            .iter()
+            .enumerate()
+            .filter(|&(t, _)| hits.iter().any(|line| line[t] > 0))
+            .map(|(_, term)| term.clone())
+            .collect();
+        Self {
+            line: start_line + first,
+            text,
+            matched_terms,
+        }
+    }
+}
+
+impl RepositoryGraph {
+    /// The snippet of `element_id` that best matches `query`, without
+    /// copying the element's code.
+    pub fn search_snippet(&self, element_id: &str, query: &str) -> Option<Snippet> {
+        let id = self.live_id(element_id)?;
+        let elem = self.element_arena.get(&id)?;
+        Some(Snippet::extract(&elem.code, elem.start_line, query))
+    }
+}
+
+fn truncate_line(line: &str) -> String {
+    match line.char_indices().nth(MAX_LINE_CHARS) {
+        Some((cut, _)) => format!("{}…", &line[..cut]),
+        None => line.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snippet_picks_densest_window() {
+        let code = "def handle(request):\n    \"\"\"Entry point.\"\"\"\n    log(request)\n\n    x = 1\n    y = 2\n    z = 3\n    token = parse_token(request.headers)\n    user = verify_token(token)\n    return user\n";
+        let snippet = Snippet::extract(code, 10, "verify token");
+        assert_eq!(snippet.matched_terms, vec!["verify", "token"]);
+        assert_eq!(snippet.line, 15);
+        assert_eq!(
+            snippet.text,
+            "    y = 2\n    z = 3\n    token = parse_token(request.headers)\n    user = verify_token(token)\n    return user"
+        );
+    }
+
+    #[test]
+    fn test_snippet_without_matches_shows_head() {
+        let code = "def a():\n\n    pass\n\n\n\n\n";
+        let snippet = Snippet::extract(code, 1, "nothing here");
+        assert!(snippet.matched_terms.is_empty());
+        assert_eq!(snippet.line, 1);
+        assert_eq!(snippet.text, "def a():\n\n    pass");
+    }
+
+    #[test]
+    fn test_snippet_bounds_huge_elements() {
+        let mut code = "x = 1\n".repeat(MAX_SCAN_LINES);
+        code.push_str("needle = 1\n");
+        code.push_str(&format!("{}\n", "a".repeat(10_000)));
+        let snippet = Snippet::extract(&code, 1, "needle");
+        assert!(snippet.matched_terms.is_empty());
+
+        let long = format!("needle = '{}'\n", "a".repeat(10_000));
+        let snippet = Snippet::extract(&long, 1, "needle");
+        assert_eq!(snippet.matched_terms, vec!["needle"]);
+        assert_eq!(snippet.text.chars().count(), MAX_LINE_CHARS + 1);
+    }
+}
@@ -0,0 +1,378 @@
+use super::RepositoryGraph;
+use super::types::NodeKind;
+use crate::indexer::{CodeElement, ElementType};
+
+/// Default size limit, in bytes, for element source handed to the model.
+pub const DEFAULT_SOURCE_BUDGET: usize = 16 * 1024;
+
+/// Room kept free for an elision marker line.
+const MARKER_RESERVE: usize = 96;
+
+impl RepositoryGraph {
+    /// Get the source of an element, shortened to at most `budget` bytes.
+    ///
+    /// Elements that fit are returned verbatim. An oversized class-like
+    /// element becomes an outline: its header and docstring, the signature of
+    /// every member, and full bodies only for members matching `focus` (a
+    /// member name or search term). Any other oversized element keeps its
+    /// head and tail, cut at statement boundaries around an elision marker.
+    pub fn get_source_within_budget(
+        &self,
+        element_id: &str,
+        budget: usize,
+        focus: Option<&str>,
+    ) -> Option<String> {
+        let elem = self.get_element(element_id)?;
+        if elem.code.len() <= budget {
+            return Some(elem.code.into());
+        }
+        let class_like = matches!(
+            elem.element_type,
+            ElementType::Class | ElementType::Struct | ElementType::Interface | ElementType::Enum
+        );
+        let members = if class_like {
+            self.class_members(&elem)
+        } else {
+            Vec::new()
+        };
+        if members.is_empty() {
+            return Some(truncate_head_tail(&elem.code, elem.start_line, budget));
+        }
+        Some(class_outline(&elem, &members, budget, focus))
+    }
+
+    /// Functions and methods the file defines inside `class`'s line range,
+    /// excluding functions nested in another member, in source order.
+    fn class_members(&self, class: &CodeElement) -> Vec<CodeElement> {
+        let Some(nodes) = self.file_to_nodes.get(&class.file_path) else {
+            return Vec::new();
+        };
+        let mut members: Vec<CodeElement> = nodes
+            .iter()
+            .map(|&idx| &self.graph[idx])
+            .filter(|node| matches!(node.kind, NodeKind::Method | NodeKind::Function))
+            .filter(|node| {
+                node.id != class.id
+                    && node.start_line > class.start_line
+                    && node.end_line <= class.end_line
+            })
+            .filter_map(|node| self.element_arena.get(&node.id).map(|e| e.clone()))
+            .collect();
+        members.sort_by_key(|m| (m.start_line, std::cmp::Reverse(m.end_line)));
+
+        let mut top_level: Vec<CodeElement> = Vec::new();
+        for member in members {
+            if top_level
+                .last()
+                .is_some_and(|prev| member.end_line <= prev.end_line)
+            {
+                continue;
+            }
+            top_level.push(member);
+        }
+        top_level
+    }
+}
+
+/// How well a member matches the focus term: exact name, name substring,
+/// then body substring. `None` means no match.
+fn focus_rank(member: &CodeElement, focus: &str) -> Option<u8> {
+    let focus = focus.trim().to_lowercase();
+    if focus.is_empty() {
+        return None;
+    }
+    let name = member.name.to_lowercase();
+    if name == focus {
+        Some(0)
+    } else if name.contains(&focus) {
+        Some(1)
+    } else if member.code.to_lowercase().contains(&focus) {
+        Some(2)
+    } else {
+        None
+    }
+}
+
+fn class_outline(
+    class: &CodeElement,
+    members: &[CodeElement],
+    budget: usize,
+    focus: Option<&str>,
+) -> String {
+    let class_lines: Vec<&str> = class.code.lines().collect();
+    // Leading whitespace of a member's first line, which the element's own
+    // code (starting at the definition keyword) does not include.
+    let indent_of = |member: &CodeElement| -> String {
+        class_lines
+            .get(member.start_line - class.start_line)
+            .map(|line| line[..line.len() - line.trim_start().len()].to_string())
+            .unwrap_or_default()
+    };
+    let signature_line = |member: &CodeElement| -> String {
+        let signature = member
+            .signature
+            .clone()
+            .or_else(|| member.code.lines().next().map(str::to_string))
+            .unwrap_or_else(|| member.name.clone());
+        format!(
+            "{}{}  ... [lines {}-{}]",
+            indent_of(member),
+            signature.trim(),
+            member.start_line,
+            member.end_line
+        )
+    };
+
+    let footer = format!(
+        "... [{} is {} lines; member bodies are elided. Call get_code_source again with \
+         focus=\"<member name>\" to see a member's full body] ...",
+        class.name,
+        class_lines.len()
+    );
+
+    // Header: everything before the first member (signature, docstring, fields).
+    let header_len = (members[0].start_line - class.start_line).max(1);
+    let mut header = class_lines[..header_len.min(class_lines.len())].join("\n");
+    if let Some(doc) = class.docstring.as_deref()
+        && !header.contains(doc.trim())
+    {
+        header = format!("{doc}\n{header}");
+    }
+    let header = truncate_head_tail(&header, class.start_line, budget / 4);
+
+    let mut pieces: Vec<String> = members.iter().map(signature_line).collect();
+    let fixed = header.len() + footer.len() + 2;
+    let mut used = fixed + pieces.iter().map(|p| p.len() + 1).sum::<usize>();
+
+    // Too many members to list: keep as many signatures as fit.
+    let mut listed = pieces.len();
+    while used > budget && listed > 0 {
+        listed -= 1;
+        used -= pieces[listed].len() + 1;
+    }
+    if listed < pieces.len() {
+        let more = format!(
+            "... [{} more members not listed] ...",
+            pieces.len() - listed
+        );
+        pieces.truncate(listed);
+        while listed > 0 && used + more.len() + 1 > budget {
+            listed -= 1;
+            used -= pieces[listed].len() + 1;
+            pieces.truncate(listed);
+        }
+        used += more.len() + 1;
+        pieces.push(more);
+    }
+
+    // Swap focused signatures for full bodies, best matches first.
+    if let Some(focus) = focus {
+        let mut focused: Vec<(u8, usize)> = members[..listed]
+            .iter()
+            .enumerate()
+            .filter_map(|(i, m)| focus_rank(m, focus).map(|rank| (rank, i)))
+            .collect();
+        focused.sort();
+        for (_, i) in focused {
+            let member = &members[i];
+            let indent = indent_of(member);
+            let body = format!("{indent}{}", member.code);
+            let room = budget.saturating_sub(used) + pieces[i].len();
+            let body = if body.len() <= room {
+                body
+            } else if room > MARKER_RESERVE * 2 {
+                truncate_head_tail(&body, member.start_line, room)
+            } else {
+                continue;
+            };
+            used = used + body.len() - pieces[i].len();
+            pieces[i] = body;
+        }
+    }
+
+    let mut out = header;
+    for piece in pieces {
+        out.push('\n');
+        out.push_str(&piece);
+    }
+    out.push('\n');
+    out.push_str(&footer);
+    out
+}
+
+/// Keep the head and tail of `code` within `budget` bytes, cutting at lines
+/// that start a statement at the body's outermost indentation and replacing
+/// the middle with an elision marker. `start_line` is the 1-based line of the
+/// first line of `code`, used to label the elided range. The result never
+/// exceeds `budget`; a budget too small for the marker gets a plain cut.
+pub fn truncate_head_tail(code: &str, start_line: usize, budget: usize) -> String {
+    if code.len() <= budget {
+        return code.to_string();
+    }
+    let lines: Vec<&str> = code.lines().collect();
+    let indent = |line: &str| line.len() - line.trim_start().len();
+    let body_indent = lines
+        .iter()
+        .skip(1)
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| indent(line))
+        .min()
+        .unwrap_or(0);
+    // Lines that are blank or nested deeper than the body continue a statement.
+    let continues = |line: &str| line.trim().is_empty() || indent(line) > body_indent;
+
+    let available = budget.saturating_sub(MARKER_RESERVE + body_indent);
+    let head_budget = available * 2 / 3;
+    let tail_budget = available - head_budget;
+
+    let mut head_end = 0;
+    let mut used = 0;
+    while head_end < lines.len() && used + lines[head_end].len() + 1 <= head_budget {
+        used += lines[head_end].len() + 1;
+        head_end += 1;
+    }
+    let mut tail_start = lines.len();
+    used = 0;
+    while tail_start > head_end && used + lines[tail_start - 1].len() + 1 <= tail_budget {
+        used += lines[tail_start - 1].len() + 1;
+        tail_start -= 1;
+    }
+
+    // Pull the cuts back to statement boundaries.
+    while head_end > 1 && head_end < lines.len() && continues(lines[head_end]) {
+        head_end -= 1;
+    }
+    while tail_start < lines.len() && continues(lines[tail_start]) {
+        tail_start += 1;
+    }
+    if tail_start <= head_end {
+        tail_start = head_end.min(lines.len());
+    }
+
+    let marker = format!(
+        "{}... [{} lines elided: lines {}-{}] ...",
+        " ".repeat(body_indent),
+        tail_start - head_end,
+        start_line + head_end,
+        start_line + tail_start - 1
+    );
+    let mut out: Vec<&str> = lines[..head_end].to_vec();
+    out.push(&marker);
+    out.extend_from_slice(&lines[tail_start..]);
+    let mut out = out.join("\n");
+    if out.len() > budget {
+        let mut end = budget;
+        while !out.is_char_boundary(end) {
+            end -= 1;
+        }
+        out.truncate(end);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indexer::walk_and_index;
+
+    fn build(files: &[(&str, &str)]) -> (tempfile::TempDir, RepositoryGraph, Vec<CodeElement>) {
+        let dir = tempfile::tempdir().unwrap();
+        for (name, code) in files {
+            std::fs::write(dir.path().join(name), code).unwrap();
+        }
+        let root = dir.path().to_string_lossy().to_string();
+        let elements = walk_and_index(&root, &Default::default());
+        let mut graph = RepositoryGraph::new();
+        graph.build_from_elements(&elements, &root);
+        (dir, graph, elements)
+    }
+
+    fn big_class(methods: usize) -> String {
+        let mut code = String::from("class Big:\n    \"\"\"A very large class.\"\"\"\n\n");
+        for i in 0..methods {
+            code.push_str(&format!("    def method_{i}(self, value):\n"));
+            for j in 0..20 {
+                code.push_str(&format!(
+                    "        value = value + {j}  # step {j} of method {i}\n"
+                ));
+            }
+            code.push_str("        return value\n\n");
+        }
+        code
+    }
+
+    #[test]
+    fn test_class_outline_lists_members_and_focus_body() {
+        let (_dir, graph, elements) = build(&[("big.py", &big_class(60))]);
+        let class = elements.iter().find(|e| e.name == "Big").unwrap();
+        let focused = elements.iter().find(|e| e.name == "method_42").unwrap();
+        assert!(class.code.len() > 40_000);
+
+        let budget = 8 * 1024;
+        let out = graph
+            .get_source_within_budget(&class.id, budget, Some("method_42"))
+            .unwrap();
+
+        assert!(out.len() <= budget, "{} > {budget}", out.len());
+        assert!(out.starts_with("class Big:"));
+        assert!(out.contains("A very large class."));
+        for i in 0..60 {
+            assert!(
+                out.contains(&format!("def method_{i}(self, value):")),
+                "missing method_{i}"
+            );
+        }
+        assert!(
+            out.contains(focused.code.as_str()),
+            "focused body not intact"
+        );
+        assert!(!out.contains("step 0 of method 41"));
+        assert!(out.contains("focus=\"<member name>\""));
+    }
+
+    #[test]
+    fn test_head_tail_never_exceeds_budget() {
+        let code = big_class(10);
+        for budget in [0, 1, 10, 50, 96, 120, 200, 1000] {
+            let out = truncate_head_tail(&code, 1, budget);
+            assert!(out.len() <= budget, "budget {budget}: {} bytes", out.len());
+        }
+        let minified = format!("é{}", "x".repeat(5000));
+        assert!(truncate_head_tail(&minified, 1, 300).len() <= 300);
+    }
+
+    #[test]
+    fn test_function_head_tail_cuts_at_line_boundaries() {
+        let mut code = String::from("def long_function(x):\n");
+        for i in 0..400 {
+            code.push_str(&format!("    x = x * {i} + 1\n"));
+        }
+        code.push_str("    return x\n");
+        let (_dir, graph, elements) = build(&[("long.py", &code)]);
+        let func = elements.iter().find(|e| e.name == "long_function").unwrap();
+
+        let out = graph
+            .get_source_within_budget(&func.id, 1024, None)
+            .unwrap();
+        assert!(out.len() <= 1024);
+        assert!(out.starts_with("def long_function(x):"));
+        assert!(out.ends_with("    return x"));
+        let original: Vec<&str> = func.code.lines().collect();
+        let mut markers = 0;
+        for line in out.lines() {
+            if line.contains("lines elided") {
+                markers += 1;
+            } else {
+                assert!(original.contains(&line), "cut mid-line: {line:?}");
+            }
+        }
+        assert_eq!(markers, 1);
+
+        let small = elements.iter().find(|e| e.name == "long.py").unwrap();
+        assert!(
+            graph
+                .get_source_within_budget(&small.id, usize::MAX, None)
+                .is_some_and(|s| s == small.code)
+        );
+    }
+}
@@ -11,9 +11,12 @@ pub enum NodeKind {
     Function,
     Method,
     Variable,
+    Constant,
+    TypeAlias,
     Interface,
     Struct,
     Enum,
+    Chunk,
 }
 
 /// A node in the repository graph.
@@ -40,14 +43,114 @@ pub enum EdgeKind {
     Defines,
     /// Element A references element B
     References,
+    /// A string literal in element A names element B, as in
+    /// `getattr(obj, "process_payment")`. Matched by name only, so these
+    /// edges are low confidence and left out of caller queries by default.
+    ReferencesByName,
     /// Class A implements interface B
     Implements,
+    /// Class A declares method, field, or nested class B
+    Contains,
 }
 
 /// An edge in the repository graph.
+///
+/// Semantic edges (calls, imports, inheritance) are deduplicated per
+/// `(from, to, kind)`; `count` records how many times the relationship
+/// occurs in source, e.g. a function calling the same helper three times.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GraphEdge {
     pub kind: EdgeKind,
+    #[serde(default = "default_edge_count")]
+    pub count: u32,
+    /// A call linked to the best-scoring of several same-named definitions
+    /// without an import or shared package to back it. Cleared once any
+    /// occurrence of the call resolves with confidence.
+    #[serde(default)]
+    pub low_confidence: bool,
+}
+
+impl GraphEdge {
+    /// Create an edge of the given kind with a count of 1.
+    pub fn new(kind: EdgeKind) -> Self {
+        Self {
+            kind,
+            count: 1,
+            low_confidence: false,
+        }
+    }
+}
+
+impl std::str::FromStr for EdgeKind {
+    type Err = String;
+
+    /// Parse a lowercase edge kind name such as `calls` or `imports`.
+    fn from_str(kind: &str) -> Result<Self, Self::Err> {
+        match kind.trim().to_ascii_lowercase().as_str() {
+            "calls" | "call" => Ok(EdgeKind::Calls),
+            "imports" | "import" => Ok(EdgeKind::Imports),
+            "inherits" | "inherit" => Ok(EdgeKind::Inherits),
+            "defines" | "define" => Ok(EdgeKind::Defines),
+            "references" | "reference" => Ok(EdgeKind::References),
+            "references_by_name" | "dynamic" => Ok(EdgeKind::ReferencesByName),
+            "implements" | "implement" => Ok(EdgeKind::Implements),
+            "contains" | "contain" => Ok(EdgeKind::Contains),
+            other => Err(format!("unknown edge kind '{other}'")),
+        }
+    }
+}
+
+impl NodeKind {
+    /// The lowercase name of the kind, as [`FromStr`](std::str::FromStr)
+    /// parses it, e.g. `type_alias`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            NodeKind::File => "file",
+            NodeKind::Module => "module",
+            NodeKind::Class => "class",
+            NodeKind::Function => "function",
+            NodeKind::Method => "method",
+            NodeKind::Variable => "variable",
+            NodeKind::Constant => "constant",
+            NodeKind::TypeAlias => "type_alias",
+            NodeKind::Interface => "interface",
+            NodeKind::Struct => "struct",
+            NodeKind::Enum => "enum",
+            NodeKind::Chunk => "chunk",
+        }
+    }
+}
+
+impl std::fmt::Display for NodeKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for NodeKind {
+    type Err = String;
+
+    /// Parse a lowercase code element kind such as `function` or `classes`;
+    /// files and modules are not accepted.
+    fn from_str(kind: &str) -> Result<Self, Self::Err> {
+        match kind.trim().to_ascii_lowercase().as_str() {
+            "function" | "functions" => Ok(NodeKind::Function),
+            "method" | "methods" => Ok(NodeKind::Method),
+            "class" | "classes" => Ok(NodeKind::Class),
+            "struct" | "structs" => Ok(NodeKind::Struct),
+            "interface" | "interfaces" => Ok(NodeKind::Interface),
+            "enum" | "enums" => Ok(NodeKind::Enum),
+            "constant" | "constants" => Ok(NodeKind::Constant),
+            "variable" | "variables" => Ok(NodeKind::Variable),
+            "type_alias" | "type_aliases" => Ok(NodeKind::TypeAlias),
+            "chunk" | "chunks" => Ok(NodeKind::Chunk),
+            other => Err(format!("unknown element kind '{other}'")),
+        }
+    }
+}
+
+fn default_edge_count() -> u32 {
+    1
 }
 
 /// The core graph type used throughout the application.
@@ -63,9 +166,36 @@ impl From<crate::indexer::ElementType> for NodeKind {
             crate::indexer::ElementType::Module => NodeKind::Module,
             crate::indexer::ElementType::Import => NodeKind::Module,
             crate::indexer::ElementType::Variable => NodeKind::Variable,
+            crate::indexer::ElementType::Constant => NodeKind::Constant,
+            crate::indexer::ElementType::TypeAlias => NodeKind::TypeAlias,
             crate::indexer::ElementType::Interface => NodeKind::Interface,
             crate::indexer::ElementType::Struct => NodeKind::Struct,
             crate::indexer::ElementType::Enum => NodeKind::Enum,
+            crate::indexer::ElementType::Chunk => NodeKind::Chunk,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_node_kind_names_parse_back() {
+        for kind in [
+            NodeKind::Class,
+            NodeKind::Function,
+            NodeKind::Method,
+            NodeKind::Variable,
+            NodeKind::Constant,
+            NodeKind::TypeAlias,
+            NodeKind::Interface,
+            NodeKind::Struct,
+            NodeKind::Enum,
+            NodeKind::Chunk,
+        ] {
+            assert_eq!(kind.to_string().parse::<NodeKind>(), Ok(kind));
         }
+        assert_eq!(NodeKind::TypeAlias.to_string(), "type_alias");
     }
 }
@@ -0,0 +1,205 @@
+//! Grounding check for code-relationship claims in agent answers.
+//!
+//! An answer that says "`main` calls `helper`" without the session ever
+//! querying the graph about either symbol is a guess. Detection is kept
+//! conservative so it does not nag: only explicit "A calls B", "A inherits
+//! from B" (or "extends", "subclasses") and "A imports B" phrasings count,
+//! and both names must resolve to indexed symbols.
+
+use std::collections::{BTreeSet, HashSet};
+use std::sync::LazyLock;
+
+use regex::Regex;
+use serde::Deserialize;
+
+use crate::graph::RepositoryGraph;
+use crate::graph::types::EdgeKind;
+
+/// What the agent does with ungrounded claims in a final answer
+/// (`[agent] grounding` in `.happy/agent.toml`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GroundingMode {
+    #[default]
+    Off,
+    /// Show a caveat listing the unverified symbols.
+    Warn,
+    /// Send one follow-up turn asking the model to verify the claims.
+    Enforce,
+}
+
+/// An explicit "A calls B"-style statement found in an answer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RelationshipClaim {
+    /// Indexed name of the subject, e.g. `helper` for `utils.helper()`.
+    pub source: String,
+    pub relation: EdgeKind,
+    /// Indexed name of the object.
+    pub target: String,
+    /// The claim as written in the answer.
+    pub text: String,
+}
+
+static CLAIM: LazyLock<Regex> = LazyLock::new(|| {
+    let name = r"`?\b([A-Za-z_]\w*(?:(?:\.|::)[A-Za-z_]\w*)*)(?:\(\))?`?";
+    Regex::new(&format!(
+        r"{name}\s+(calls|inherits from|inherits|extends|subclasses|imports)\s+{name}"
+    ))
+    .expect("claim pattern compiles")
+});
+
+/// Relationship claims in `text` whose subject and object are both indexed
+/// symbols, in order of appearance, without duplicates.
+pub fn find_claims(text: &str, graph: &RepositoryGraph) -> Vec<RelationshipClaim> {
+    let mut claims: Vec<RelationshipClaim> = Vec::new();
+    for caps in CLAIM.captures_iter(text) {
+        let (Some(source), Some(target)) =
+            (indexed_name(graph, &caps[1]), indexed_name(graph, &caps[3]))
+        else {
+            continue;
+        };
+        let relation = match &caps[2] {
+            "calls" => EdgeKind::Calls,
+            "imports" => EdgeKind::Imports,
+            _ => EdgeKind::Inherits,
+        };
+        let claim = RelationshipClaim {
+            source: source.to_string(),
+            relation,
+            target: target.to_string(),
+            text: caps[0].to_string(),
+        };
+        if !claims
+            .iter()
+            .any(|c| (&c.source, c.relation, &c.target) == (&claim.source, relation, &claim.target))
+        {
+            claims.push(claim);
+        }
+    }
+    claims
+}
+
+/// Claims in `text` where neither symbol is in `queried`, the names that
+/// graph queries in the session touched.
+pub fn unverified_claims(
+    text: &str,
+    graph: &RepositoryGraph,
+    queried: &HashSet<String>,
+) -> Vec<RelationshipClaim> {
+    find_claims(text, graph)
+        .into_iter()
+        .filter(|claim| !queried.contains(&claim.source) && !queried.contains(&claim.target))
+        .collect()
+}
+
+/// The symbols named by `claims`, sorted and deduplicated.
+pub fn claim_symbols(claims: &[RelationshipClaim]) -> Vec<String> {
+    let symbols: BTreeSet<&str> = claims
+        .iter()
+        .flat_map(|claim| [claim.source.as_str(), claim.target.as_str()])
+        .collect();
+    symbols.into_iter().map(str::to_string).collect()
+}
+
+/// Instructions for the follow-up turn sent in [`GroundingMode::Enforce`].
+pub fn verification_request(claims: &[RelationshipClaim]) -> String {
+    let mut message = String::from(
+        "Before finalizing, verify these code relationship claims from your answer with the code graph tools (find_callers, find_callees, get_superclasses, get_dependencies). No graph query in this session touched the symbols involved:\n",
+    );
+    for claim in claims {
+        message.push_str(&format!("- {}\n", claim.text));
+    }
+    message.push_str("Correct any claim the graph does not support, then give your final answer.");
+    message
+}
+
+/// The caveat shown in [`GroundingMode::Warn`].
+pub fn caveat(claims: &[RelationshipClaim]) -> String {
+    let symbols: Vec<String> = claim_symbols(claims)
+        .iter()
+        .map(|symbol| format!("`{symbol}`"))
+        .collect();
+    format!(
+        "Unverified code relationships: the answer describes how {} relate, but no code graph query checked them.",
+        symbols.join(", ")
+    )
+}
+
+/// The indexed name `name` refers to: the name itself, or its last `.` or
+/// `::` segment (`utils.helper` -> `helper`).
+fn indexed_name<'a>(graph: &RepositoryGraph, name: &'a str) -> Option<&'a str> {
+    let last = name.rsplit(['.', ':']).next().unwrap_or(name);
+    [name, last].into_iter().find(|n| graph.has_symbol(n))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn build_fixture() -> (tempfile::TempDir, RepositoryGraph) {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("app.py"),
+            "from auth import login, Base\n\nclass Admin(Base):\n    pass\n\ndef run():\n    return login()\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.path().join("auth.py"),
+            "class Base:\n    pass\n\ndef login():\n    return 1\n",
+        )
+        .unwrap();
+        let root = dir
+            .path()
+            .canonicalize()
+            .unwrap()
+            .to_string_lossy()
+            .to_string();
+        let mut repo = RepositoryGraph::new();
+        repo.build_from_elements(
+            &crate::indexer::walk_and_index(&root, &Default::default()),
+            &root,
+        );
+        (dir, repo)
+    }
+
+    #[test]
+    fn test_find_claims_is_conservative() {
+        let (_dir, repo) = build_fixture();
+        let answer = "`run()` calls `auth.login()`, and Admin inherits from Base. \
+                      This calls for care; run calls print; login is fast. \
+                      run calls login again.";
+        let claims = find_claims(answer, &repo);
+        assert_eq!(
+            claims
+                .iter()
+                .map(|c| (c.source.as_str(), c.relation, c.target.as_str()))
+                .collect::<Vec<_>>(),
+            vec![
+                ("run", EdgeKind::Calls, "login"),
+                ("Admin", EdgeKind::Inherits, "Base"),
+            ]
+        );
+        assert_eq!(claims[0].text, "`run()` calls `auth.login()`");
+    }
+
+    #[test]
+    fn test_unverified_claims_skip_queried_symbols() {
+        let (_dir, repo) = build_fixture();
+        let answer = "run calls login. Admin extends Base.";
+
+        let claims = unverified_claims(answer, &repo, &HashSet::new());
+        assert_eq!(
+            claim_symbols(&claims),
+            vec!["Admin", "Base", "login", "run"]
+        );
+
+        let queried: HashSet<String> = ["login".to_string()].into_iter().collect();
+        let claims = unverified_claims(answer, &repo, &queried);
+        assert_eq!(claim_symbols(&claims), vec!["Admin", "Base"]);
+        assert!(verification_request(&claims).contains("- Admin extends Base\n"));
+        assert_eq!(
+            caveat(&claims),
+            "Unverified code relationships: the answer describes how `Admin`, `Base` relate, but no code graph query checked them."
+        );
+    }
+}
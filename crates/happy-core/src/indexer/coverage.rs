@@ -0,0 +1,485 @@
+use std::borrow::Cow;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use ignore::WalkBuilder;
+use ignore::gitignore::GitignoreBuilder;
+use serde::Serialize;
+
+use super::excludes::Excludes;
+use super::options::IndexOptions;
+use super::walker::{HAPPYIGNORE_FILENAME, index_walk_builder};
+use crate::parser::languages::SupportedLanguage;
+
+/// Limits for the on-disk literal search run when a symbol query comes back empty.
+#[derive(Debug, Clone, Copy)]
+pub struct CoverageBudget {
+    /// Stop after scanning this many files.
+    pub max_files: usize,
+    /// Files larger than this are not read.
+    pub max_file_bytes: u64,
+    /// Stop after reading this many bytes in total.
+    pub max_total_bytes: u64,
+    /// Stop listing the files the index walk visits after this many; files
+    /// past it are left unexplained rather than guessed at.
+    pub max_walked_files: usize,
+    /// Stop after collecting this many matches.
+    pub max_matches: usize,
+}
+
+impl Default for CoverageBudget {
+    fn default() -> Self {
+        Self {
+            max_files: 5_000,
+            max_file_bytes: 1024 * 1024,
+            max_total_bytes: 64 * 1024 * 1024,
+            max_walked_files: 50_000,
+            max_matches: 10,
+        }
+    }
+}
+
+/// Why a file on disk is not part of the index.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SkipReason {
+    /// Matched a pattern in the repo's `.happyignore`.
+    Happyignore,
+    /// Matched `.gitignore`, a global gitignore, or `.git/info/exclude`.
+    Gitignore,
+    /// Inside a default-excluded dependency or build directory, or matched
+    /// `[index] extra_excludes`.
+    IndexExclude,
+    /// The file or one of its parent directories is hidden (dot-prefixed).
+    Hidden,
+    /// No tree-sitter grammar is registered for the file extension, or its
+    /// language feature is disabled in this build.
+    UnsupportedExtension,
+    /// The file could not be read, e.g. for lack of permission.
+    Unreadable,
+    /// The file could not be decoded as UTF-8.
+    NotUtf8,
+    /// The parser produced no syntax tree for the file.
+    ParseFailed,
+    /// Larger than `[index] max_file_size`.
+    TooLarge,
+    /// Has a line longer than `[index] max_line_length`, as minified code does.
+    Minified,
+    /// Marked generated (`DO NOT EDIT`, `@generated`): indexed as a file, but
+    /// its definitions are not.
+    Generated,
+}
+
+impl SkipReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Happyignore => "excluded by .happyignore",
+            Self::Gitignore => "excluded by .gitignore",
+            Self::IndexExclude => "excluded as a dependency or build directory",
+            Self::Hidden => "inside a hidden file or directory",
+            Self::UnsupportedExtension => "unsupported file extension",
+            Self::Unreadable => "unreadable",
+            Self::NotUtf8 => "not valid UTF-8",
+            Self::ParseFailed => "failed to parse",
+            Self::TooLarge => "larger than the maximum file size",
+            Self::Minified => "minified",
+            Self::Generated => "a generated file",
+        }
+    }
+}
+
+/// A literal occurrence of a queried symbol in a file the index skipped.
+#[derive(Debug, Clone, Serialize)]
+pub struct UnindexedMatch {
+    /// Path relative to the repo root, with `/` separators.
+    pub relative_path: String,
+    /// 1-based line of the first occurrence.
+    pub line: usize,
+    pub reason: SkipReason,
+}
+
+impl UnindexedMatch {
+    /// A one-line suggestion for getting this file into the index.
+    pub fn hint(&self) -> String {
+        match self.reason {
+            SkipReason::Happyignore => format!(
+                "remove the pattern matching '{}' from {HAPPYIGNORE_FILENAME} to index it",
+                self.relative_path
+            ),
+            SkipReason::Gitignore => format!(
+                "add '!{}' to {HAPPYIGNORE_FILENAME} to index it despite .gitignore",
+                self.relative_path
+            ),
+            SkipReason::IndexExclude => format!(
+                "add \"{}\" to [index] unexclude in .happy/agent.toml to index it",
+                self.relative_path
+            ),
+            SkipReason::Hidden => {
+                "hidden paths are never indexed; move the code out of the dot-directory to index it"
+                    .to_string()
+            }
+            SkipReason::UnsupportedExtension => {
+                let ext = Path::new(&self.relative_path)
+                    .extension()
+                    .map(|e| format!(".{}", e.to_string_lossy()))
+                    .unwrap_or_else(|| "extension-less".to_string());
+                if let Some(feature) = SupportedLanguage::from_extension_any(&self.relative_path)
+                    .and_then(|lang| lang.feature())
+                {
+                    return format!(
+                        "{ext} files are not parsed because happy-core was built without the \
+                         `{feature}` feature; rebuild with it (or `lang-all`) to index them"
+                    );
+                }
+                let supported: Vec<String> = SupportedLanguage::ALL
+                    .into_iter()
+                    .filter(SupportedLanguage::is_enabled)
+                    .flat_map(|lang| lang.extensions())
+                    .map(|ext| format!(".{ext}"))
+                    .collect();
+                format!(
+                    "{ext} files are not parsed; only {} files are indexed",
+                    supported.join(", ")
+                )
+            }
+            SkipReason::Unreadable => {
+                "check that the file exists and its permissions allow reading it".to_string()
+            }
+            SkipReason::NotUtf8 => {
+                "re-encode the file as UTF-8, or set [index] lossy_utf8 = true in \
+                 .happy/agent.toml (or pass --lossy-utf8) to index it with invalid bytes replaced"
+                    .to_string()
+            }
+            SkipReason::ParseFailed => {
+                "the parser gave up on the file; check it for unusually deep nesting or size"
+                    .to_string()
+            }
+            SkipReason::TooLarge => {
+                "raise [index] max_file_size in .happy/agent.toml (or pass --max-file-size) to \
+                 index it"
+                    .to_string()
+            }
+            SkipReason::Minified => {
+                "raise [index] max_line_length in .happy/agent.toml to index it".to_string()
+            }
+            SkipReason::Generated => {
+                "set [index] include_generated = true in .happy/agent.toml (or pass \
+                 --include-generated) to index its definitions"
+                    .to_string()
+            }
+        }
+    }
+
+    /// Human-readable explanation naming the file and reason.
+    pub fn describe(&self, symbol: &str) -> String {
+        format!(
+            "'{symbol}' appears in {}:{}, which is not indexed ({}); {}",
+            self.relative_path,
+            self.line,
+            self.reason.as_str(),
+            self.hint()
+        )
+    }
+}
+
+/// Explain an empty symbol query by searching the repo's files on disk.
+///
+/// Returns the unindexed files that literally contain `symbol` as a whole
+/// word. The result is empty if the identifier is not found at all, or if it
+/// also appears in an indexed file (the symbol is visible to the index, just
+/// not as a definition), so callers only warn when the index is genuinely
+/// missing the file.
+pub fn explain_missing_symbol(repo_path: &str, symbol: &str) -> Vec<UnindexedMatch> {
+    explain_missing_symbol_with_budget(repo_path, symbol, &CoverageBudget::default())
+}
+
+/// Like [`explain_missing_symbol`], with explicit search limits.
+pub fn explain_missing_symbol_with_budget(
+    repo_path: &str,
+    symbol: &str,
+    budget: &CoverageBudget,
+) -> Vec<UnindexedMatch> {
+    let Some(identifier) = last_identifier(symbol) else {
+        return Vec::new();
+    };
+    let repo_root = Path::new(repo_path)
+        .canonicalize()
+        .unwrap_or_else(|_| PathBuf::from(repo_path));
+
+    // Everything the indexer's walk visits, before the extension filter.
+    let excludes = Excludes::load(&repo_root);
+    let options = IndexOptions::load(&repo_root);
+    let walked: HashSet<PathBuf> = index_walk_builder(&repo_root, Arc::new(excludes.clone()), None)
+        .build()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_some_and(|ft| ft.is_file()))
+        .map(|entry| entry.into_path())
+        .take(budget.max_walked_files)
+        .collect();
+    // A file missing from a truncated list may still be indexed.
+    let walked_all = walked.len() < budget.max_walked_files;
+
+    let happyignore = {
+        let mut builder = GitignoreBuilder::new(&repo_root);
+        builder.add(repo_root.join(HAPPYIGNORE_FILENAME));
+        builder.build().ok()
+    };
+
+    let mut matches = Vec::new();
+    let mut scanned = 0usize;
+    let mut read_bytes = 0u64;
+    let raw_walk = WalkBuilder::new(&repo_root)
+        .standard_filters(false)
+        .filter_entry(|entry| entry.file_name() != ".git")
+        .build();
+
+    for entry in raw_walk.filter_map(|entry| entry.ok()) {
+        if !entry.file_type().is_some_and(|ft| ft.is_file()) {
+            continue;
+        }
+        if scanned >= budget.max_files
+            || read_bytes >= budget.max_total_bytes
+            || matches.len() >= budget.max_matches
+        {
+            break;
+        }
+        let Ok(len) = entry.metadata().map(|meta| meta.len()) else {
+            continue;
+        };
+        if len > budget.max_file_bytes {
+            continue;
+        }
+        if !walked_all && !walked.contains(entry.path()) {
+            continue;
+        }
+        scanned += 1;
+        read_bytes += len;
+
+        let path = entry.path();
+        let Ok(bytes) = std::fs::read(path) else {
+            continue;
+        };
+        let Some(offset) = find_word(&bytes, identifier.as_bytes()) else {
+            continue;
+        };
+
+        let relative = path.strip_prefix(&repo_root).unwrap_or(path);
+        let Some(reason) = skip_reason(
+            path,
+            relative,
+            &bytes,
+            &walked,
+            happyignore.as_ref(),
+            &excludes,
+            &options,
+        ) else {
+            // Found in an indexed file: the index can see it, nothing to explain.
+            return Vec::new();
+        };
+        matches.push(UnindexedMatch {
+            relative_path: crate::utils::normalize_path(&relative.to_string_lossy()),
+            line: bytes[..offset].iter().filter(|&&b| b == b'\n').count() + 1,
+            reason,
+        });
+    }
+
+    matches
+}
+
+/// Classify why `path` is not indexed, or `None` if the indexer would read it.
+fn skip_reason(
+    path: &Path,
+    relative: &Path,
+    bytes: &[u8],
+    walked: &HashSet<PathBuf>,
+    happyignore: Option<&ignore::gitignore::Gitignore>,
+    excludes: &Excludes,
+    options: &IndexOptions,
+) -> Option<SkipReason> {
+    if !walked.contains(path) {
+        if happyignore.is_some_and(|gi| gi.matched_path_or_any_parents(relative, false).is_ignore())
+        {
+            return Some(SkipReason::Happyignore);
+        }
+        if excludes.is_excluded(relative, false) {
+            return Some(SkipReason::IndexExclude);
+        }
+        let hidden = relative
+            .components()
+            .any(|c| c.as_os_str().to_string_lossy().starts_with('.'));
+        return Some(if hidden {
+            SkipReason::Hidden
+        } else {
+            SkipReason::Gitignore
+        });
+    }
+    if SupportedLanguage::from_extension(&path.to_string_lossy()).is_none() {
+        return Some(SkipReason::UnsupportedExtension);
+    }
+    let code = match std::str::from_utf8(bytes) {
+        Ok(code) => Cow::Borrowed(code),
+        Err(_) if options.lossy_utf8 => String::from_utf8_lossy(bytes),
+        Err(_) => return Some(SkipReason::NotUtf8),
+    };
+    options.content_limit(&code)
+}
+
+/// The trailing identifier of a possibly qualified name (`a.b`, `a::b`).
+fn last_identifier(symbol: &str) -> Option<&str> {
+    let ident = symbol.rsplit(['.', ':']).next()?.trim();
+    let valid = !ident.is_empty()
+        && ident
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '_' || c == '$');
+    valid.then_some(ident)
+}
+
+/// Byte offset of the first whole-word occurrence of `needle`.
+fn find_word(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    if needle.is_empty() || haystack.len() < needle.len() {
+        return None;
+    }
+    let is_word = |b: u8| b.is_ascii_alphanumeric() || b == b'_';
+    (0..=haystack.len() - needle.len()).find(|&i| {
+        haystack[i..].starts_with(needle)
+            && (i == 0 || !is_word(haystack[i - 1]))
+            && haystack.get(i + needle.len()).is_none_or(|&b| !is_word(b))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(root: &Path, relative: &str, contents: &str) {
+        let path = root.join(relative);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, contents).unwrap();
+    }
+
+    #[test]
+    fn test_explains_symbol_in_happyignored_file() {
+        let dir = tempfile::tempdir().unwrap();
+        write(dir.path(), ".happyignore", "vendor/\n");
+        write(dir.path(), "main.py", "def main():\n    pass\n");
+        write(
+            dir.path(),
+            "vendor/helpers.py",
+            "import os\n\ndef vendored_helper():\n    return os.getcwd()\n",
+        );
+
+        let root = dir.path().to_string_lossy().to_string();
+        let elements = crate::indexer::walk_and_index(&root, &Default::default());
+        assert!(elements.iter().all(|e| e.name != "vendored_helper"));
+
+        let matches = explain_missing_symbol(&root, "vendored_helper");
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].relative_path, "vendor/helpers.py");
+        assert_eq!(matches[0].line, 3);
+        assert_eq!(matches[0].reason, SkipReason::Happyignore);
+        let text = matches[0].describe("vendored_helper");
+        assert!(text.contains("vendor/helpers.py"), "{text}");
+        assert!(text.contains("excluded by .happyignore"), "{text}");
+    }
+
+    #[test]
+    fn test_explains_symbol_in_default_excluded_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        write(dir.path(), "main.py", "def main():\n    pass\n");
+        write(
+            dir.path(),
+            "node_modules/lib/index.js",
+            "function bundledHelper() {}\n",
+        );
+
+        let root = dir.path().to_string_lossy().to_string();
+        let matches = explain_missing_symbol(&root, "bundledHelper");
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].reason, SkipReason::IndexExclude);
+        assert!(matches[0].hint().contains("[index] unexclude"));
+    }
+
+    #[test]
+    fn test_unsupported_extension_and_indexed_hits() {
+        let dir = tempfile::tempdir().unwrap();
+        write(dir.path(), "app.rb", "def ruby_only\nend\n");
+        write(
+            dir.path(),
+            "main.py",
+            "def main():\n    return shared_name\n",
+        );
+        write(dir.path(), "notes.txt", "shared_name\n");
+
+        let root = dir.path().to_string_lossy().to_string();
+        let matches = explain_missing_symbol(&root, "ruby_only");
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].reason, SkipReason::UnsupportedExtension);
+
+        // Also present in an indexed file, so there is nothing to explain.
+        assert!(explain_missing_symbol(&root, "shared_name").is_empty());
+        assert!(explain_missing_symbol(&root, "nowhere_at_all").is_empty());
+    }
+
+    #[test]
+    fn test_budget_caps_bytes_read() {
+        let dir = tempfile::tempdir().unwrap();
+        write(dir.path(), "a.rb", "def ruby_only\nend\n");
+        write(dir.path(), "b.rb", "ruby_only\n");
+
+        let root = dir.path().to_string_lossy().to_string();
+        assert_eq!(explain_missing_symbol(&root, "ruby_only").len(), 2);
+        let budget = CoverageBudget {
+            max_total_bytes: 1,
+            ..CoverageBudget::default()
+        };
+        let matches = explain_missing_symbol_with_budget(&root, "ruby_only", &budget);
+        assert_eq!(matches.len(), 1);
+        let hint = matches[0].hint();
+        assert!(
+            hint.starts_with(".rb files are not parsed; only ") && hint.contains(".kt"),
+            "{hint}"
+        );
+    }
+
+    #[test]
+    fn test_explains_symbol_in_generated_file() {
+        let dir = tempfile::tempdir().unwrap();
+        write(dir.path(), "main.py", "def main():\n    pass\n");
+        write(
+            dir.path(),
+            "api_pb2.py",
+            "# Generated by the protocol buffer compiler.  DO NOT EDIT!\n\ndef proto_helper():\n    pass\n",
+        );
+
+        let root = dir.path().to_string_lossy().to_string();
+        let matches = explain_missing_symbol(&root, "proto_helper");
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].reason, SkipReason::Generated);
+        assert!(matches[0].hint().contains("include_generated"));
+    }
+
+    /// Built by the minimal-feature CI job (`--no-default-features --features
+    /// lang-python`): files of a compiled-out language are skipped, not parsed.
+    #[test]
+    #[cfg(all(feature = "lang-python", not(feature = "lang-go")))]
+    fn test_disabled_language_is_reported_unsupported() {
+        let dir = tempfile::tempdir().unwrap();
+        write(dir.path(), "main.py", "def main():\n    return 1\n");
+        write(
+            dir.path(),
+            "server.go",
+            "package main\n\nfunc serveForever() {}\n",
+        );
+
+        let root = dir.path().to_string_lossy().to_string();
+        let elements = crate::indexer::walk_and_index(&root, &Default::default());
+        assert!(elements.iter().all(|e| e.language == "python"));
+        assert!(elements.iter().any(|e| e.name == "main"));
+
+        let matches = explain_missing_symbol(&root, "serveForever");
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].reason, SkipReason::UnsupportedExtension);
+        assert!(matches[0].hint().contains("`lang-go` feature"));
+    }
+}
@@ -1,5 +1,8 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use super::source_text::SourceText;
 
 /// The type of a code element extracted from source.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -14,6 +17,14 @@ pub enum ElementType {
     Interface,
     Struct,
     Enum,
+    // Cached elements store the variant index, so new variants go last.
+    /// Module-level `const`/`static`/`static final` values and Python
+    /// `UPPER_CASE` assignments.
+    Constant,
+    TypeAlias,
+    /// A run of lines from a file indexed without parsing (see
+    /// [`IndexingMode::ContentOnly`](crate::parser::languages::IndexingMode)).
+    Chunk,
 }
 
 impl ElementType {
@@ -26,13 +37,91 @@ impl ElementType {
             Self::Module => "module",
             Self::Import => "import",
             Self::Variable => "variable",
+            Self::Constant => "constant",
+            Self::TypeAlias => "type_alias",
             Self::Interface => "interface",
             Self::Struct => "struct",
             Self::Enum => "enum",
+            Self::Chunk => "chunk",
         }
     }
 }
 
+/// Element metadata key holding a definition's [`Visibility`].
+pub const VISIBILITY_KEY: &str = "visibility";
+
+/// Element metadata key holding the receiver type of a Go method, e.g.
+/// `Dog` for `func (d *Dog) Speak()`.
+pub const RECEIVER_KEY: &str = "receiver";
+
+/// Element metadata key holding the comma-separated types a Go struct or
+/// interface embeds, e.g. `Base,io.Reader`.
+pub const EMBEDS_KEY: &str = "embeds";
+
+/// Element metadata key holding the comma-separated method names a Go
+/// interface declares.
+pub const METHODS_KEY: &str = "methods";
+
+/// Element metadata key holding the type a Rust `impl` block, and each
+/// method in it, is for: `Foo` for `impl<T> Display for Foo<T>`.
+pub const IMPL_TYPE_KEY: &str = "impl_type";
+
+/// Element metadata key holding the trait a Rust `impl` block, and each
+/// method in it, implements: `Display` for `impl fmt::Display for Foo`.
+pub const IMPL_TRAIT_KEY: &str = "impl_trait";
+
+/// Element metadata key holding the comma-separated namespaces a C# file
+/// declares, e.g. `My.Company.Services`.
+pub const NAMESPACES_KEY: &str = "namespaces";
+
+/// Element metadata key marking the file and chunk elements of a file
+/// indexed without parsing. Graph edges are not built from them.
+pub const CONTENT_ONLY_KEY: &str = "content_only";
+
+/// Where a definition can be referenced from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Visibility {
+    /// Exported: `pub`, `public`, capitalized Go names, and anything the
+    /// language has no marker for.
+    Public,
+    /// Visible to subclasses (Java/TypeScript `protected`).
+    Protected,
+    /// Visible within its directory: Java package-private and lowercase Go
+    /// names.
+    Package,
+    /// Visible only within its file: Java/TypeScript `private`, `#private`
+    /// JS members, Python `_names`, C `static`. Non-`pub` Rust items are
+    /// also visible from their module's submodules.
+    Private,
+}
+
+impl Visibility {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Public => "public",
+            Self::Protected => "protected",
+            Self::Package => "package",
+            Self::Private => "private",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "public" => Some(Self::Public),
+            "protected" => Some(Self::Protected),
+            "package" => Some(Self::Package),
+            "private" => Some(Self::Private),
+            _ => None,
+        }
+    }
+
+    /// Whether other files can reference the definition regardless of
+    /// where they are.
+    pub fn is_exported(&self) -> bool {
+        matches!(self, Self::Public | Self::Protected)
+    }
+}
+
 /// A code element extracted from source code.
 /// Mirrors FastCode's CodeElement dataclass.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -45,10 +134,14 @@ pub struct CodeElement {
     pub language: String,
     pub start_line: usize,
     pub end_line: usize,
-    pub code: String,
+    /// Source text, sharing its file's buffer (see [`SourceText`]).
+    pub code: SourceText,
     pub signature: Option<String>,
     pub docstring: Option<String>,
     pub summary: Option<String>,
+    /// ID of the class-like element a method, field, or nested class is
+    /// defined in.
+    pub parent_id: Option<String>,
     pub metadata: HashMap<String, String>,
 }
 
@@ -57,6 +150,43 @@ impl CodeElement {
     pub fn generate_id(type_: &str, parts: &[&str]) -> String {
         crate::utils::generate_element_id(type_, parts)
     }
+
+    /// The visibility recorded at extraction, under [`VISIBILITY_KEY`].
+    /// Elements without one, such as those cached by older builds, are
+    /// public.
+    pub fn visibility(&self) -> Visibility {
+        self.metadata
+            .get(VISIBILITY_KEY)
+            .and_then(|v| Visibility::parse(v))
+            .unwrap_or(Visibility::Public)
+    }
+
+    /// Whether code in `file_path` can reference this element.
+    pub fn is_visible_from(&self, file_path: &str) -> bool {
+        match self.visibility() {
+            Visibility::Public | Visibility::Protected => true,
+            Visibility::Package => {
+                Path::new(&self.file_path).parent() == Path::new(file_path).parent()
+            }
+            Visibility::Private => {
+                self.file_path == file_path
+                    || (self.language == "rust"
+                        && Path::new(file_path).starts_with(rust_module_dir(&self.file_path)))
+            }
+        }
+    }
+}
+
+/// The directory holding a Rust file's submodules: its own directory for
+/// `mod.rs`, `lib.rs` and `main.rs`, else the sibling directory named after
+/// the file.
+fn rust_module_dir(file_path: &str) -> PathBuf {
+    let path = Path::new(file_path);
+    let dir = path.parent().unwrap_or(Path::new(""));
+    match path.file_stem().and_then(|stem| stem.to_str()) {
+        Some("mod" | "lib" | "main") | None => dir.to_path_buf(),
+        Some(stem) => dir.join(stem),
+    }
 }
 
 #[cfg(test)]
@@ -77,4 +207,65 @@ mod tests {
         let id2 = CodeElement::generate_id("function", &["src/main.py", "MyClass", "process"]);
         assert_eq!(id, id2);
     }
+
+    #[test]
+    fn test_visibility_scopes() {
+        let mut elem = CodeElement {
+            id: "function_helper".into(),
+            element_type: ElementType::Function,
+            name: "helper".into(),
+            file_path: "/repo/pkg/a.go".into(),
+            relative_path: "pkg/a.go".into(),
+            language: "go".into(),
+            start_line: 1,
+            end_line: 1,
+            code: "func helper() {}".into(),
+            signature: None,
+            docstring: None,
+            summary: None,
+            parent_id: None,
+            metadata: HashMap::new(),
+        };
+        assert_eq!(elem.visibility(), Visibility::Public);
+        assert!(elem.is_visible_from("/repo/other/b.go"));
+
+        elem.metadata
+            .insert(VISIBILITY_KEY.into(), Visibility::Package.as_str().into());
+        assert!(elem.is_visible_from("/repo/pkg/b.go"));
+        assert!(!elem.is_visible_from("/repo/other/b.go"));
+
+        elem.metadata
+            .insert(VISIBILITY_KEY.into(), Visibility::Private.as_str().into());
+        assert!(elem.is_visible_from("/repo/pkg/a.go"));
+        assert!(!elem.is_visible_from("/repo/pkg/b.go"));
+    }
+
+    #[test]
+    fn test_rust_private_items_reach_submodules() {
+        let mut elem = CodeElement {
+            id: "function_write_store".into(),
+            element_type: ElementType::Function,
+            name: "write_store".into(),
+            file_path: "/repo/src/store/mod.rs".into(),
+            relative_path: "src/store/mod.rs".into(),
+            language: "rust".into(),
+            start_line: 1,
+            end_line: 1,
+            code: "fn write_store() {}".into(),
+            signature: None,
+            docstring: None,
+            summary: None,
+            parent_id: None,
+            metadata: HashMap::from([(VISIBILITY_KEY.into(), Visibility::Private.as_str().into())]),
+        };
+        assert!(elem.is_visible_from("/repo/src/store/mod.rs"));
+        assert!(elem.is_visible_from("/repo/src/store/cache.rs"));
+        assert!(elem.is_visible_from("/repo/src/store/cache/meta.rs"));
+        assert!(!elem.is_visible_from("/repo/src/graph/mod.rs"));
+
+        elem.file_path = "/repo/src/graph.rs".into();
+        assert!(elem.is_visible_from("/repo/src/graph/queries.rs"));
+        assert!(!elem.is_visible_from("/repo/src/store/mod.rs"));
+        assert!(!elem.is_visible_from("/repo/src/graphs.rs"));
+    }
 }
@@ -0,0 +1,294 @@
+use std::ffi::OsStr;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use serde::Deserialize;
+
+use super::options::{DEFAULT_MAX_FILE_SIZE, DEFAULT_MAX_LINE_LENGTH, IndexOptions};
+use crate::agent_toml::AgentToml;
+use crate::graph::fanout::{DEFAULT_FALLBACK_FANOUT_LIMIT, DEFAULT_MAX_CALL_CANDIDATES};
+use crate::parser::languages::SupportedLanguage;
+
+/// Dependency, virtualenv, and build output directories that are skipped
+/// wherever they appear, even in repos without a `.gitignore`.
+pub const DEFAULT_EXCLUDED_DIRS: &[&str] = &[
+    // JavaScript / TypeScript
+    "node_modules",
+    "bower_components",
+    "dist",
+    ".next",
+    ".nuxt",
+    // Python
+    "venv",
+    ".venv",
+    "__pycache__",
+    ".tox",
+    ".nox",
+    ".mypy_cache",
+    ".pytest_cache",
+    "site-packages",
+    // Rust, Java, C/C++
+    "target",
+    "build",
+    "cmake-build-debug",
+    "cmake-build-release",
+    ".gradle",
+    // Go, PHP, Ruby
+    "vendor",
+];
+
+/// The `[index]` table of `.happy/agent.toml`.
+///
+/// ```toml
+/// [index]
+/// use_default_excludes = true
+/// extra_excludes = ["generated/", "*.pb.go"]
+/// unexclude = ["vendor/github.com/acme/sdk"]
+/// detect_string_refs = false
+/// max_call_candidates = 50
+/// fallback_fanout_limit = 200
+/// max_file_size = 2097152
+/// max_line_length = 1000
+/// include_generated = false
+/// lossy_utf8 = false
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct IndexConfig {
+    /// Skip [`DEFAULT_EXCLUDED_DIRS`].
+    pub use_default_excludes: bool,
+    /// More gitignore-style patterns to skip, relative to the repo root.
+    pub extra_excludes: Vec<String>,
+    /// Paths (relative to the repo root) or directory names that are indexed
+    /// even though a default or extra exclude matches them.
+    pub unexclude: Vec<String>,
+    /// Link elements to the symbols and modules their string literals name
+    /// (`getattr(obj, "process_payment")`, `import_module("app.tasks")`)
+    /// with low-confidence `ReferencesByName` edges.
+    pub detect_string_refs: bool,
+    /// Definitions of a name that call resolution considers per call. Calls
+    /// to names defined more often are resolved from the caller's file,
+    /// imports and directory only.
+    pub max_call_candidates: usize,
+    /// Names defined more often than this get no first-match fallback edges.
+    pub fallback_fanout_limit: usize,
+    /// See [`IndexOptions::max_file_size`].
+    pub max_file_size: u64,
+    /// See [`IndexOptions::max_line_length`].
+    pub max_line_length: usize,
+    /// See [`IndexOptions::include_generated`].
+    pub include_generated: bool,
+    /// See [`IndexOptions::lossy_utf8`].
+    pub lossy_utf8: bool,
+}
+
+impl Default for IndexConfig {
+    fn default() -> Self {
+        Self {
+            use_default_excludes: true,
+            extra_excludes: Vec::new(),
+            unexclude: Vec::new(),
+            detect_string_refs: false,
+            max_call_candidates: DEFAULT_MAX_CALL_CANDIDATES,
+            fallback_fanout_limit: DEFAULT_FALLBACK_FANOUT_LIMIT,
+            max_file_size: DEFAULT_MAX_FILE_SIZE,
+            max_line_length: DEFAULT_MAX_LINE_LENGTH,
+            include_generated: false,
+            lossy_utf8: false,
+        }
+    }
+}
+
+impl IndexConfig {
+    /// Load `[index]` from `<repo_root>/.happy/agent.toml`.
+    ///
+    /// Returns the default (default excludes on, nothing extra) when the file
+    /// or table is missing.
+    pub fn load(repo_root: &Path) -> io::Result<Self> {
+        Ok(AgentToml::load(repo_root)?
+            .section("index")?
+            .unwrap_or_default())
+    }
+
+    /// The file limits this config sets for the indexer.
+    pub fn index_options(&self) -> IndexOptions {
+        IndexOptions {
+            max_file_size: self.max_file_size,
+            max_line_length: self.max_line_length,
+            include_generated: self.include_generated,
+            lossy_utf8: self.lossy_utf8,
+        }
+    }
+}
+
+/// Exclusion rules for one repo, applied by both the indexing walk and the
+/// file watcher.
+#[derive(Debug, Clone)]
+pub struct Excludes {
+    root: PathBuf,
+    /// Directory names skipped at any depth.
+    dirs: Vec<String>,
+    extra: Option<Gitignore>,
+    /// Normalized relative paths, without trailing `/`.
+    unexclude: Vec<String>,
+}
+
+impl Excludes {
+    pub fn new(repo_root: &Path, config: &IndexConfig) -> Self {
+        let unexclude: Vec<String> = config
+            .unexclude
+            .iter()
+            .map(|path| crate::utils::normalize_path(path.trim()))
+            .map(|path| path.trim_matches('/').to_string())
+            .filter(|path| !path.is_empty())
+            .collect();
+        let dirs = if config.use_default_excludes {
+            DEFAULT_EXCLUDED_DIRS
+                .iter()
+                .filter(|name| !unexclude.iter().any(|u| u == *name))
+                .map(|name| name.to_string())
+                .collect()
+        } else {
+            Vec::new()
+        };
+        let extra = (!config.extra_excludes.is_empty())
+            .then(|| {
+                let mut builder = GitignoreBuilder::new(repo_root);
+                for pattern in &config.extra_excludes {
+                    if let Err(err) = builder.add_line(None, pattern) {
+                        tracing::warn!(pattern = %pattern, error = %err, "ignoring invalid extra exclude");
+                    }
+                }
+                builder.build().ok()
+            })
+            .flatten();
+        Self {
+            root: repo_root.to_path_buf(),
+            dirs,
+            extra,
+            unexclude,
+        }
+    }
+
+    /// Build the rules from the repo's `[index]` config, falling back to the
+    /// defaults when the config cannot be read.
+    pub fn load(repo_root: &Path) -> Self {
+        let config = IndexConfig::load(repo_root).unwrap_or_else(|err| {
+            tracing::warn!(error = %err, "ignoring invalid [index] config in .happy/agent.toml");
+            IndexConfig::default()
+        });
+        Self::new(repo_root, &config)
+    }
+
+    /// Whether `path` (absolute under the repo root, or relative to it) is
+    /// excluded. Directories are matched by their own name too; files only by
+    /// their parent directories and the extra patterns.
+    pub fn is_excluded(&self, path: &Path, is_dir: bool) -> bool {
+        let relative = path.strip_prefix(&self.root).unwrap_or(path);
+        if relative.is_absolute() || relative.as_os_str().is_empty() {
+            return false;
+        }
+        let normalized = crate::utils::normalize_path(&relative.to_string_lossy());
+        let unexcluded = self.unexclude.iter().any(|u| {
+            normalized == *u
+                || normalized.starts_with(&format!("{u}/"))
+                // Keep walking into the parents of an unexcluded path.
+                || (is_dir && u.starts_with(&format!("{normalized}/")))
+        });
+        if unexcluded {
+            return false;
+        }
+
+        let mut components: Vec<_> = relative.components().collect();
+        if !is_dir {
+            components.pop();
+        }
+        if components
+            .iter()
+            .any(|c| self.dirs.iter().any(|dir| c.as_os_str() == dir.as_str()))
+        {
+            return true;
+        }
+        self.extra
+            .as_ref()
+            .is_some_and(|gi| gi.matched_path_or_any_parents(relative, is_dir).is_ignore())
+    }
+
+    /// Whether directories named `name` are skipped at any depth.
+    pub fn is_excluded_dir_name(&self, name: &OsStr) -> bool {
+        self.dirs.iter().any(|dir| name == dir.as_str())
+    }
+
+    /// Whether a changed file should be (re)indexed: a supported language
+    /// outside every excluded directory.
+    pub fn is_indexable_file(&self, path: &Path) -> bool {
+        SupportedLanguage::from_extension(&path.to_string_lossy()).is_some()
+            && !self.is_excluded(path, false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_and_configured_excludes() {
+        let root = Path::new("/repo");
+        let excludes = Excludes::new(root, &IndexConfig::default());
+        assert!(excludes.is_excluded(Path::new("/repo/web/node_modules"), true));
+        assert!(excludes.is_excluded(Path::new("web/node_modules/react/index.js"), false));
+        assert!(excludes.is_excluded(Path::new("venv/lib/site.py"), false));
+        assert!(!excludes.is_excluded(Path::new("src/build.rs"), false));
+        assert!(!excludes.is_indexable_file(Path::new("/repo/target/debug/build.rs")));
+        assert!(excludes.is_indexable_file(Path::new("/repo/src/main.py")));
+
+        let config = IndexConfig {
+            use_default_excludes: true,
+            extra_excludes: vec!["generated/".to_string(), "*_pb2.py".to_string()],
+            unexclude: vec!["vendor/acme/".to_string(), "build".to_string()],
+            ..IndexConfig::default()
+        };
+        let excludes = Excludes::new(root, &config);
+        assert!(excludes.is_excluded(Path::new("api/generated"), true));
+        assert!(excludes.is_excluded(Path::new("api/user_pb2.py"), false));
+        assert!(!excludes.is_excluded(Path::new("vendor"), true));
+        assert!(!excludes.is_excluded(Path::new("vendor/acme/sdk.go"), false));
+        assert!(excludes.is_excluded(Path::new("vendor/other"), true));
+        assert!(!excludes.is_excluded(Path::new("tools/build/gen.py"), false));
+
+        let off = IndexConfig {
+            use_default_excludes: false,
+            ..IndexConfig::default()
+        };
+        let excludes = Excludes::new(root, &off);
+        assert!(!excludes.is_excluded(Path::new("node_modules/a.js"), false));
+    }
+
+    #[test]
+    fn test_load_index_config() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(
+            IndexConfig::load(dir.path()).unwrap(),
+            IndexConfig::default()
+        );
+        std::fs::create_dir_all(dir.path().join(".happy")).unwrap();
+        std::fs::write(
+            dir.path().join(".happy/agent.toml"),
+            "[index]\nunexclude = [\"vendor\"]\nmax_call_candidates = 20\ninclude_generated = true\n",
+        )
+        .unwrap();
+        let config = IndexConfig::load(dir.path()).unwrap();
+        assert!(config.use_default_excludes);
+        assert_eq!(config.unexclude, vec!["vendor".to_string()]);
+        assert_eq!(config.max_call_candidates, 20);
+        assert_eq!(config.fallback_fanout_limit, DEFAULT_FALLBACK_FANOUT_LIMIT);
+        assert_eq!(
+            IndexOptions::load(dir.path()),
+            IndexOptions {
+                include_generated: true,
+                ..IndexOptions::default()
+            }
+        );
+    }
+}
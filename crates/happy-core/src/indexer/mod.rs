@@ -1,5 +1,19 @@
+pub mod coverage;
 pub mod element;
+pub mod excludes;
+pub mod options;
+pub mod source_text;
 pub mod walker;
 
-pub use element::{CodeElement, ElementType};
-pub use walker::{index_single_file, walk_and_index};
+pub use coverage::{SkipReason, UnindexedMatch, explain_missing_symbol};
+pub use element::{
+    CONTENT_ONLY_KEY, CodeElement, EMBEDS_KEY, ElementType, IMPL_TRAIT_KEY, IMPL_TYPE_KEY,
+    METHODS_KEY, NAMESPACES_KEY, RECEIVER_KEY, VISIBILITY_KEY, Visibility,
+};
+pub use excludes::{DEFAULT_EXCLUDED_DIRS, Excludes, IndexConfig};
+pub use options::IndexOptions;
+pub use source_text::{SourceText, share_file_contents};
+pub use walker::{
+    HAPPYIGNORE_FILENAME, IndexSummary, index_single_file, list_source_files, walk_and_index,
+    walk_and_index_with_progress, walk_and_index_with_summary,
+};
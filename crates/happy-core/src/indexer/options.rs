@@ -0,0 +1,144 @@
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use super::coverage::SkipReason;
+use super::excludes::IndexConfig;
+
+/// Default [`IndexOptions::max_file_size`]: 2 MiB.
+pub const DEFAULT_MAX_FILE_SIZE: u64 = 2 * 1024 * 1024;
+
+/// Default [`IndexOptions::max_line_length`]. Hand-written code rarely comes
+/// close; minified bundles put whole modules on one line.
+pub const DEFAULT_MAX_LINE_LENGTH: usize = 1_000;
+
+/// Header comments that mark a file as generated.
+const GENERATED_MARKERS: &[&str] = &["DO NOT EDIT", "@generated"];
+
+/// Leading lines searched for [`GENERATED_MARKERS`].
+const GENERATED_HEADER_LINES: usize = 20;
+
+/// Limits on which files the indexer parses.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IndexOptions {
+    /// Files larger than this many bytes are skipped without being read.
+    pub max_file_size: u64,
+    /// Files with a line longer than this many bytes are taken to be
+    /// minified and skipped.
+    pub max_line_length: usize,
+    /// Extract definitions from generated files (a `DO NOT EDIT` or
+    /// `@generated` header). Otherwise only their file element is indexed.
+    pub include_generated: bool,
+    /// Index files that are not valid UTF-8 with the invalid bytes replaced
+    /// by U+FFFD. Otherwise they are skipped.
+    pub lossy_utf8: bool,
+}
+
+impl Default for IndexOptions {
+    fn default() -> Self {
+        Self {
+            max_file_size: DEFAULT_MAX_FILE_SIZE,
+            max_line_length: DEFAULT_MAX_LINE_LENGTH,
+            include_generated: false,
+            lossy_utf8: false,
+        }
+    }
+}
+
+impl IndexOptions {
+    /// The options in the repo's `[index]` config, falling back to the
+    /// defaults when the config cannot be read.
+    pub fn load(repo_root: &Path) -> Self {
+        IndexConfig::load(repo_root)
+            .map(|config| config.index_options())
+            .unwrap_or_else(|err| {
+                tracing::warn!(error = %err, "ignoring invalid [index] config in .happy/agent.toml");
+                Self::default()
+            })
+    }
+
+    /// Why a file of `size` bytes is skipped before it is read.
+    pub fn size_limit(&self, size: u64) -> Option<SkipReason> {
+        (size > self.max_file_size).then_some(SkipReason::TooLarge)
+    }
+
+    /// The text of a file's `bytes`, decoded lossily when `lossy_utf8` is
+    /// set. [`SkipReason::NotUtf8`] otherwise when they are not UTF-8.
+    pub fn decode(&self, bytes: Vec<u8>) -> Result<String, SkipReason> {
+        match String::from_utf8(bytes) {
+            Ok(code) => Ok(code),
+            Err(err) if self.lossy_utf8 => Ok(String::from_utf8_lossy(err.as_bytes()).into_owned()),
+            Err(_) => Err(SkipReason::NotUtf8),
+        }
+    }
+
+    /// Why `code` is skipped ([`SkipReason::TooLarge`],
+    /// [`SkipReason::Minified`]) or indexed without its definitions
+    /// ([`SkipReason::Generated`]).
+    pub fn content_limit(&self, code: &str) -> Option<SkipReason> {
+        if let Some(reason) = self.size_limit(code.len() as u64) {
+            return Some(reason);
+        }
+        if code.lines().any(|line| line.len() > self.max_line_length) {
+            return Some(SkipReason::Minified);
+        }
+        if !self.include_generated && is_generated(code) {
+            return Some(SkipReason::Generated);
+        }
+        None
+    }
+}
+
+/// Whether the first lines of `code` carry a generated-file marker.
+fn is_generated(code: &str) -> bool {
+    code.lines()
+        .take(GENERATED_HEADER_LINES)
+        .any(|line| GENERATED_MARKERS.iter().any(|marker| line.contains(marker)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_content_limits() {
+        let options = IndexOptions::default();
+        assert_eq!(options.content_limit("def run():\n    pass\n"), None);
+        assert_eq!(
+            options.size_limit(DEFAULT_MAX_FILE_SIZE + 1),
+            Some(SkipReason::TooLarge)
+        );
+
+        let minified = format!("var a={};\n", "1+".repeat(DEFAULT_MAX_LINE_LENGTH));
+        assert_eq!(options.content_limit(&minified), Some(SkipReason::Minified));
+
+        let proto = "// Code generated by protoc-gen-go. DO NOT EDIT.\npackage api\n";
+        assert_eq!(options.content_limit(proto), Some(SkipReason::Generated));
+        let late = format!("{}# @generated\n", "x = 1\n".repeat(GENERATED_HEADER_LINES));
+        assert_eq!(options.content_limit(&late), None);
+
+        let include = IndexOptions {
+            include_generated: true,
+            ..IndexOptions::default()
+        };
+        assert_eq!(include.content_limit(proto), None);
+    }
+
+    #[test]
+    fn test_decode() {
+        let latin1 = b"name = 'caf\xe9'\n".to_vec();
+        assert_eq!(
+            IndexOptions::default().decode(latin1.clone()),
+            Err(SkipReason::NotUtf8)
+        );
+        let lossy = IndexOptions {
+            lossy_utf8: true,
+            ..IndexOptions::default()
+        };
+        assert_eq!(
+            lossy.decode(latin1).as_deref(),
+            Ok("name = 'caf\u{fffd}'\n")
+        );
+        assert_eq!(lossy.decode(b"ok".to_vec()).as_deref(), Ok("ok"));
+    }
+}
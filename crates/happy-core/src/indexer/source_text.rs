@@ -0,0 +1,273 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::ops::{Deref, Range};
+use std::sync::Arc;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use super::{CodeElement, ElementType};
+
+/// The source of a code element: a byte range of a buffer shared with the
+/// other elements of its file.
+///
+/// A file's contents are read into one buffer, and the file element and
+/// every definition in it point into that buffer, so indexing a file keeps
+/// one copy of it however many elements it yields, and cloning an element
+/// does not copy its code. Dereferences to `str`; serializes as a string.
+#[derive(Clone)]
+pub struct SourceText {
+    buffer: Arc<str>,
+    start: usize,
+    end: usize,
+}
+
+impl SourceText {
+    /// The text in a buffer of its own.
+    pub fn new(text: impl Into<Arc<str>>) -> Self {
+        let buffer = text.into();
+        let end = buffer.len();
+        Self {
+            buffer,
+            start: 0,
+            end,
+        }
+    }
+
+    /// `range` of this text (in bytes, relative to it), sharing its buffer.
+    /// `None` when the range is out of bounds or not on character
+    /// boundaries.
+    pub fn slice(&self, range: Range<usize>) -> Option<Self> {
+        self.as_str().get(range.clone())?;
+        Some(Self {
+            buffer: Arc::clone(&self.buffer),
+            start: self.start + range.start,
+            end: self.start + range.end,
+        })
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.buffer[self.start..self.end]
+    }
+
+    /// The whole buffer this text is part of.
+    pub fn buffer(&self) -> &Arc<str> {
+        &self.buffer
+    }
+
+    /// Byte range of this text within [`buffer`](Self::buffer).
+    pub fn buffer_range(&self) -> Range<usize> {
+        self.start..self.end
+    }
+
+    /// `range` of `buffer`, or `None` when it is out of bounds or not on
+    /// character boundaries.
+    pub fn from_buffer(buffer: Arc<str>, range: Range<usize>) -> Option<Self> {
+        buffer.get(range.clone())?;
+        Some(Self {
+            buffer,
+            start: range.start,
+            end: range.end,
+        })
+    }
+}
+
+impl Default for SourceText {
+    fn default() -> Self {
+        Self::new("")
+    }
+}
+
+impl Deref for SourceText {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl AsRef<str> for SourceText {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl fmt::Debug for SourceText {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl fmt::Display for SourceText {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self.as_str(), f)
+    }
+}
+
+impl From<String> for SourceText {
+    fn from(text: String) -> Self {
+        Self::new(text)
+    }
+}
+
+impl From<&str> for SourceText {
+    fn from(text: &str) -> Self {
+        Self::new(text)
+    }
+}
+
+impl From<SourceText> for String {
+    fn from(text: SourceText) -> Self {
+        text.as_str().to_string()
+    }
+}
+
+impl PartialEq for SourceText {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+impl Eq for SourceText {}
+
+impl PartialEq<str> for SourceText {
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl PartialEq<&str> for SourceText {
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+impl PartialEq<String> for SourceText {
+    fn eq(&self, other: &String) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl PartialEq<SourceText> for String {
+    fn eq(&self, other: &SourceText) -> bool {
+        self == other.as_str()
+    }
+}
+
+impl PartialEq<SourceText> for &str {
+    fn eq(&self, other: &SourceText) -> bool {
+        *self == other.as_str()
+    }
+}
+
+impl Serialize for SourceText {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for SourceText {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer).map(Self::from)
+    }
+}
+
+/// Point each element's code into its file element's buffer where the two
+/// hold the same text, so elements loaded or built separately share one
+/// copy of each file again.
+pub fn share_file_contents(elements: &mut [CodeElement]) {
+    let files: HashMap<String, SourceText> = elements
+        .iter()
+        .filter(|elem| elem.element_type == ElementType::File)
+        .map(|elem| (elem.file_path.clone(), elem.code.clone()))
+        .collect();
+    for elem in elements {
+        if elem.element_type == ElementType::File {
+            continue;
+        }
+        let Some(file) = files.get(&elem.file_path) else {
+            continue;
+        };
+        if Arc::ptr_eq(file.buffer(), elem.code.buffer()) {
+            continue;
+        }
+        // Definitions start on their start line, so search from there.
+        let line_start = file
+            .split_inclusive('\n')
+            .take(elem.start_line.saturating_sub(1))
+            .map(str::len)
+            .sum::<usize>();
+        let Some(offset) = file
+            .get(line_start..)
+            .and_then(|rest| rest.find(elem.code.as_str()))
+        else {
+            continue;
+        };
+        let start = line_start + offset;
+        if let Some(shared) = file.slice(start..start + elem.code.len()) {
+            elem.code = shared;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_slices_share_the_buffer() {
+        let file = SourceText::from("def a():\n    pass\n\ndef b():\n    pass\n");
+        let b = file.slice(19..36).unwrap();
+        assert_eq!(b, "def b():\n    pass");
+        assert!(Arc::ptr_eq(file.buffer(), b.buffer()));
+        assert_eq!(b.buffer_range(), 19..36);
+        assert_eq!(b.slice(0..7).unwrap(), "def b()");
+        assert_eq!(b.slice(0..7).unwrap().buffer_range(), 19..26);
+        assert!(b.slice(0..100).is_none());
+
+        let text = SourceText::from("é");
+        assert!(text.slice(0..1).is_none());
+    }
+
+    #[test]
+    fn test_serializes_as_a_string() {
+        let file = SourceText::from("x = 1\ny = 2\n");
+        let y = file.slice(6..11).unwrap();
+        assert_eq!(serde_json::to_string(&y).unwrap(), "\"y = 2\"");
+        let back: SourceText = serde_json::from_str("\"y = 2\"").unwrap();
+        assert_eq!(back, y);
+    }
+
+    #[test]
+    fn test_share_file_contents() {
+        let code = "def a():\n    pass\n\ndef b():\n    pass\n";
+        let element = |element_type: ElementType, start_line: usize, code: &str| CodeElement {
+            id: format!("{}_{start_line}", element_type.as_str()),
+            element_type,
+            name: String::new(),
+            file_path: "/repo/m.py".into(),
+            relative_path: "m.py".into(),
+            language: "python".into(),
+            start_line,
+            end_line: start_line + 1,
+            code: code.into(),
+            signature: None,
+            docstring: None,
+            summary: None,
+            parent_id: None,
+            metadata: HashMap::new(),
+        };
+        let mut elements = vec![
+            element(ElementType::File, 1, code),
+            element(ElementType::Function, 1, "def a():\n    pass"),
+            element(ElementType::Function, 4, "def b():\n    pass"),
+            element(ElementType::Function, 4, "def gone():\n    pass"),
+        ];
+        share_file_contents(&mut elements);
+
+        let file = elements[0].code.buffer().clone();
+        assert!(Arc::ptr_eq(elements[1].code.buffer(), &file));
+        assert_eq!(elements[2].code.buffer_range(), 19..36);
+        assert_eq!(elements[2].code, "def b():\n    pass");
+        assert!(!Arc::ptr_eq(elements[3].code.buffer(), &file));
+    }
+}
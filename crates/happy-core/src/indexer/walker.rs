@@ -2,86 +2,359 @@ use ignore::WalkBuilder;
 use rayon::prelude::*;
 use std::path::{Path, PathBuf};
 
-use super::element::{CodeElement, ElementType};
-use crate::parser::Parser;
-use crate::parser::languages::SupportedLanguage;
+use super::coverage::SkipReason;
+use super::element::{
+    CONTENT_ONLY_KEY, CodeElement, EMBEDS_KEY, ElementType, IMPL_TRAIT_KEY, IMPL_TYPE_KEY,
+    METHODS_KEY, NAMESPACES_KEY, RECEIVER_KEY, VISIBILITY_KEY, Visibility,
+};
+use super::excludes::Excludes;
+use super::options::IndexOptions;
+use super::source_text::SourceText;
+use crate::parser::languages::{IndexingMode, SupportedLanguage};
+use crate::parser::with_thread_parser;
+use crate::redact::Redactor;
 use std::collections::HashMap;
-use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+/// Source lines per extracted element, used to size element vectors up
+/// front. Typical code lands between 8 and 20.
+const LINES_PER_ELEMENT: usize = 12;
+
+/// Lines per chunk element of a file indexed without parsing.
+const CHUNK_LINES: usize = 40;
+
+/// Per-repo ignore file, using gitignore syntax. Patterns here take precedence
+/// over `.gitignore`, so `!path` can force-include a git-ignored file.
+pub const HAPPYIGNORE_FILENAME: &str = ".happyignore";
+
+/// The walk configuration used for indexing: skips hidden entries, the
+/// repo's [`Excludes`], and honors `.gitignore`, git excludes, and
+/// `.happyignore`. Entries pruned by the excludes are pushed to `excluded`.
+pub(crate) fn index_walk_builder(
+    repo_root: &Path,
+    excludes: Arc<Excludes>,
+    excluded: Option<Arc<Mutex<Vec<PathBuf>>>>,
+) -> WalkBuilder {
+    let mut builder = WalkBuilder::new(repo_root);
+    builder
+        .hidden(true)
+        .git_ignore(true)
+        .git_global(true)
+        .git_exclude(true)
+        .add_custom_ignore_filename(HAPPYIGNORE_FILENAME)
+        .filter_entry(move |entry| {
+            if entry.depth() == 0 {
+                return true;
+            }
+            let is_dir = entry.file_type().is_some_and(|ft| ft.is_dir());
+            if !excludes.is_excluded(entry.path(), is_dir) {
+                return true;
+            }
+            if let Some(excluded) = &excluded
+                && let Ok(mut paths) = excluded.lock()
+            {
+                paths.push(entry.path().to_path_buf());
+            }
+            false
+        });
+    builder
+}
+
+/// Counts from one full indexing walk.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IndexSummary {
+    /// Source files found, including those skipped.
+    pub files: usize,
+    pub elements: usize,
+    /// Supported-language files skipped by the default or configured excludes.
+    pub excluded_files: usize,
+    /// Excluded files and directories, relative to the repo root.
+    pub excluded_paths: Vec<String>,
+    /// Source files skipped (unreadable, not UTF-8, unparsable, or over
+    /// the [`IndexOptions`] limits) or indexed without definitions when
+    /// generated, relative to the repo root and sorted.
+    pub skipped_paths: Vec<(String, SkipReason)>,
+}
+
+/// Walk a repository and extract code elements from all supported files
+/// within the `options` limits.
+pub fn walk_and_index(repo_path: &str, options: &IndexOptions) -> Vec<CodeElement> {
+    walk_and_index_with_summary(repo_path, options).0
+}
+
+/// Like [`walk_and_index`], also reporting what the excludes and limits
+/// skipped.
+pub fn walk_and_index_with_summary(
+    repo_path: &str,
+    options: &IndexOptions,
+) -> (Vec<CodeElement>, IndexSummary) {
+    walk_and_index_with_progress(repo_path, options, &|_, _| {})
+}
 
-/// Walk a repository and extract code elements from all supported files.
-pub fn walk_and_index(repo_path: &str) -> Vec<CodeElement> {
+/// Like [`walk_and_index_with_summary`], calling `progress(done, total)` as
+/// each source file is processed. Files are parsed in parallel, so calls
+/// come from several threads.
+pub fn walk_and_index_with_progress(
+    repo_path: &str,
+    options: &IndexOptions,
+    progress: &(dyn Fn(usize, usize) + Sync),
+) -> (Vec<CodeElement>, IndexSummary) {
+    let started = Instant::now();
     let repo_root = Path::new(repo_path)
         .canonicalize()
         .unwrap_or_else(|_| PathBuf::from(repo_path));
     let repo_root_str = repo_root.to_string_lossy().to_string();
+    let excludes = Arc::new(Excludes::load(&repo_root));
+    let excluded = Arc::new(Mutex::new(Vec::new()));
+    let redactor = Redactor::load(&repo_root);
 
     // Collect all file paths first
-    let files: Vec<PathBuf> = WalkBuilder::new(&repo_root)
+    let files = source_files(&repo_root, excludes.clone(), Some(excluded.clone()));
+    let mut excluded = std::mem::take(&mut *excluded.lock().unwrap_or_else(|e| e.into_inner()));
+    excluded.sort();
+    let excluded_files = excluded
+        .iter()
+        .map(|path| count_source_files(path, &excludes))
+        .sum();
+    tracing::debug!(
+        files = files.len(),
+        excluded_files,
+        duration_ms = started.elapsed().as_millis() as u64,
+        "walked repository"
+    );
+
+    // Process files in parallel with rayon
+    let elements: Mutex<Vec<CodeElement>> = Mutex::new(Vec::new());
+    let skipped: Mutex<Vec<(String, SkipReason)>> = Mutex::new(Vec::new());
+    let done = AtomicUsize::new(0);
+
+    files.par_iter().for_each(|path| {
+        let relative = path
+            .strip_prefix(&repo_root)
+            .map(|p| p.to_string_lossy().to_string())
+            .unwrap_or_else(|_| path.to_string_lossy().to_string());
+        if let Some(file) = index_file(path, &relative, &repo_root_str, options, &redactor) {
+            tracing::trace!(
+                file = %relative,
+                elements = file.elements.len(),
+                "parsed file"
+            );
+            if let Some(reason) = file.limit
+                && let Ok(mut skipped) = skipped.lock()
+            {
+                skipped.push((crate::utils::normalize_path(&relative), reason));
+            }
+            if let Ok(mut elems) = elements.lock() {
+                elems.extend(file.elements);
+            }
+        }
+        progress(done.fetch_add(1, Ordering::Relaxed) + 1, files.len());
+    });
+
+    let elements = elements.into_inner().unwrap_or_default();
+    let mut skipped = skipped.into_inner().unwrap_or_default();
+    skipped.sort_by(|a, b| a.0.cmp(&b.0));
+    tracing::info!(
+        files = files.len(),
+        elements = elements.len(),
+        excluded_files,
+        skipped_files = skipped.len(),
+        duration_ms = started.elapsed().as_millis() as u64,
+        "indexed repository"
+    );
+    let summary = IndexSummary {
+        files: files.len(),
+        elements: elements.len(),
+        excluded_files,
+        excluded_paths: excluded
+            .iter()
+            .map(|path| {
+                let relative = path.strip_prefix(&repo_root).unwrap_or(path);
+                crate::utils::normalize_path(&relative.to_string_lossy())
+            })
+            .collect(),
+        skipped_paths: skipped,
+    };
+    (elements, summary)
+}
+
+/// Supported-language files the index walk visits.
+fn source_files(
+    repo_root: &Path,
+    excludes: Arc<Excludes>,
+    excluded: Option<Arc<Mutex<Vec<PathBuf>>>>,
+) -> Vec<PathBuf> {
+    index_walk_builder(repo_root, excludes, excluded)
+        .build()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_some_and(|ft| ft.is_file()))
+        .filter(|entry| {
+            SupportedLanguage::from_extension(&entry.path().to_string_lossy()).is_some()
+        })
+        .map(|entry| entry.into_path())
+        .collect()
+}
+
+/// Repo-relative paths of the files [`walk_and_index`] would parse, without
+/// parsing them.
+pub fn list_source_files(repo_path: &str) -> Vec<String> {
+    let repo_root = Path::new(repo_path)
+        .canonicalize()
+        .unwrap_or_else(|_| PathBuf::from(repo_path));
+    let excludes = Arc::new(Excludes::load(&repo_root));
+    let mut files: Vec<String> = source_files(&repo_root, excludes, None)
+        .into_iter()
+        .map(|path| {
+            path.strip_prefix(&repo_root)
+                .map(|p| p.to_string_lossy().to_string())
+                .unwrap_or_else(|_| path.to_string_lossy().to_string())
+        })
+        .collect();
+    files.sort();
+    files
+}
+
+/// Supported-language files at or below `path`, an entry the excludes
+/// pruned from the index walk. The walk below it applies the same rules as
+/// the index walk (hidden entries, ignore files, and directories excluded
+/// by name, such as a `node_modules` nested in another), so only files the
+/// excludes actually kept out are counted and large trees stay cheap to
+/// count.
+fn count_source_files(path: &Path, excludes: &Arc<Excludes>) -> usize {
+    let excludes = excludes.clone();
+    WalkBuilder::new(path)
         .hidden(true)
         .git_ignore(true)
         .git_global(true)
         .git_exclude(true)
+        .add_custom_ignore_filename(HAPPYIGNORE_FILENAME)
+        .filter_entry(move |entry| {
+            entry.depth() == 0
+                || !(entry.file_type().is_some_and(|ft| ft.is_dir())
+                    && excludes.is_excluded_dir_name(entry.file_name()))
+        })
         .build()
         .filter_map(|entry| entry.ok())
         .filter(|entry| entry.file_type().is_some_and(|ft| ft.is_file()))
         .filter(|entry| {
             SupportedLanguage::from_extension(&entry.path().to_string_lossy()).is_some()
         })
-        .map(|entry| entry.into_path())
-        .collect();
-
-    // Process files in parallel with rayon
-    let elements: Mutex<Vec<CodeElement>> = Mutex::new(Vec::new());
-
-    files.par_iter().for_each(|path| {
-        let path_str = path.to_string_lossy().to_string();
-        if let Ok(code) = std::fs::read_to_string(path) {
-            let mut parser = Parser::new();
-            if let Some((lang, tree)) = parser.parse_file(&path_str, &code) {
-                let relative = path
-                    .strip_prefix(&repo_root)
-                    .map(|p| p.to_string_lossy().to_string())
-                    .unwrap_or_else(|_| path_str.clone());
-
-                let file_elements = extract_elements_from_tree(
-                    &tree,
-                    &code,
-                    &path_str,
-                    &relative,
-                    lang,
-                    &repo_root_str,
-                );
-
-                if let Ok(mut elems) = elements.lock() {
-                    elems.extend(file_elements);
-                }
-            }
-        }
-    });
-
-    elements.into_inner().unwrap_or_default()
+        .count()
 }
 
 /// Index a single file and return its code elements.
 /// Used for incremental re-indexing when a file changes during a session.
-pub fn index_single_file(file_path: &str, repo_root: &str) -> Option<Vec<CodeElement>> {
+/// `None` when the file is skipped: unreadable, unparsable, or over the
+/// `options` limits.
+pub fn index_single_file(
+    file_path: &str,
+    repo_root: &str,
+    options: &IndexOptions,
+) -> Option<Vec<CodeElement>> {
     let path = Path::new(file_path);
     let root = Path::new(repo_root);
-
-    let lang = SupportedLanguage::from_extension(&path.to_string_lossy())?;
-    let code = std::fs::read_to_string(path).ok()?;
-
-    let mut parser = Parser::new();
-    let tree = parser.parse(&code, lang)?;
-
     let relative = path
         .strip_prefix(root)
         .map(|p| p.to_string_lossy().to_string())
         .unwrap_or_else(|_| file_path.to_string());
 
-    Some(extract_elements_from_tree(
-        &tree, &code, file_path, &relative, lang, repo_root,
-    ))
+    let file = index_file(path, &relative, repo_root, options, &Redactor::load(root))?;
+    match file.limit {
+        None | Some(SkipReason::Generated) => Some(file.elements),
+        Some(_) => None,
+    }
+}
+
+/// The elements of one file, and why it was skipped or limited, if it was.
+struct IndexedFile {
+    elements: Vec<CodeElement>,
+    limit: Option<SkipReason>,
+}
+
+impl IndexedFile {
+    /// A file left out of the index, logged as skipped.
+    fn skipped(relative: &str, bytes: u64, reason: SkipReason) -> Self {
+        match reason {
+            SkipReason::TooLarge | SkipReason::Minified => {
+                tracing::info!(file = %relative, bytes, reason = reason.as_str(), "skipped file");
+            }
+            _ => tracing::warn!(file = %relative, bytes, reason = reason.as_str(), "skipped file"),
+        }
+        Self {
+            elements: Vec::new(),
+            limit: Some(reason),
+        }
+    }
+}
+
+/// Read and parse one source file within the `options` limits. Files over
+/// them, or that cannot be read, decoded, or parsed, yield no elements, and
+/// generated files only their file element. Files of a content-only
+/// language are chunked instead of parsed. `None` for unsupported files.
+fn index_file(
+    path: &Path,
+    relative: &str,
+    repo_root: &str,
+    options: &IndexOptions,
+    redactor: &Redactor,
+) -> Option<IndexedFile> {
+    let path_str = path.to_string_lossy();
+    let lang = SupportedLanguage::from_extension(&path_str)?;
+    let size = match std::fs::metadata(path) {
+        Ok(metadata) => metadata.len(),
+        Err(err) => {
+            tracing::debug!(file = %relative, error = %err, "cannot stat file");
+            return Some(IndexedFile::skipped(relative, 0, SkipReason::Unreadable));
+        }
+    };
+    // Checked before reading, so huge bundles are never read at all.
+    if let Some(limit) = options.size_limit(size) {
+        return Some(IndexedFile::skipped(relative, size, limit));
+    }
+    let bytes = match std::fs::read(path) {
+        Ok(bytes) => bytes,
+        Err(err) => {
+            tracing::debug!(file = %relative, error = %err, "cannot read file");
+            return Some(IndexedFile::skipped(relative, size, SkipReason::Unreadable));
+        }
+    };
+    let code = match options.decode(bytes) {
+        Ok(code) => code,
+        Err(reason) => return Some(IndexedFile::skipped(relative, size, reason)),
+    };
+    let limit = options.content_limit(&code);
+    let mut elements = match limit {
+        None if lang.indexing_mode() == IndexingMode::ContentOnly => {
+            extract_content_elements(&code, &path_str, relative, lang)
+        }
+        None => {
+            // One parser per rayon worker, reused across its files.
+            let Some(tree) = with_thread_parser(|parser| parser.parse(&code, lang)) else {
+                return Some(IndexedFile::skipped(
+                    relative,
+                    size,
+                    SkipReason::ParseFailed,
+                ));
+            };
+            extract_elements_from_tree(&tree, &code, &path_str, relative, lang, repo_root)
+        }
+        Some(SkipReason::Generated) => {
+            tracing::debug!(file = %relative, "indexing generated file without its definitions");
+            let line_count = code.lines().count();
+            vec![file_element(
+                SourceText::from(code),
+                &path_str,
+                relative,
+                lang,
+                line_count,
+            )]
+        }
+        Some(limit) => return Some(IndexedFile::skipped(relative, size, limit)),
+    };
+    redactor.redact_elements(&mut elements);
+    Some(IndexedFile { elements, limit })
 }
 
 /// Extract code elements from a parsed tree-sitter AST.
@@ -93,37 +366,35 @@ pub fn extract_elements_from_tree(
     language: SupportedLanguage,
     _repo_root: &str,
 ) -> Vec<CodeElement> {
-    let mut elements = Vec::new();
+    let line_count = code.lines().count();
+    let mut elements = Vec::with_capacity(1 + line_count / LINES_PER_ELEMENT);
     let code_bytes = code.as_bytes();
     let lang_str = language.name().to_string();
-
-    // File-level element
-    let file_id = CodeElement::generate_id("file", &[relative_path]);
-    elements.push(CodeElement {
-        id: file_id,
-        element_type: ElementType::File,
-        name: Path::new(file_path)
-            .file_name()
-            .map(|f| f.to_string_lossy().to_string())
-            .unwrap_or_default(),
-        file_path: file_path.to_string(),
-        relative_path: relative_path.to_string(),
-        language: lang_str.clone(),
-        start_line: 1,
-        end_line: code.lines().count(),
-        code: code.to_string(),
-        signature: None,
-        docstring: None,
-        summary: None,
-        metadata: HashMap::new(),
-    });
+    // One copy of the file, shared by every element extracted from it.
+    let source = SourceText::from(code);
+    let root = tree.root_node();
+    let mut file = file_element(
+        source.clone(),
+        file_path,
+        relative_path,
+        language,
+        line_count,
+    );
+    if language == SupportedLanguage::CSharp {
+        let mut namespaces = Vec::new();
+        csharp_namespaces(&root, code_bytes, "", &mut namespaces);
+        if !namespaces.is_empty() {
+            file.metadata
+                .insert(NAMESPACES_KEY.to_string(), namespaces.join(","));
+        }
+    }
+    elements.push(file);
 
     // Walk the AST for class/function definitions
-    let root = tree.root_node();
     collect_definitions(
         &root,
         code_bytes,
-        code,
+        &source,
         file_path,
         relative_path,
         &lang_str,
@@ -135,22 +406,107 @@ pub fn extract_elements_from_tree(
     elements
 }
 
+/// Elements of a file indexed without parsing: the file, and a chunk for
+/// every [`CHUNK_LINES`] lines of it, so search hits can point into it.
+fn extract_content_elements(
+    code: &str,
+    file_path: &str,
+    relative_path: &str,
+    language: SupportedLanguage,
+) -> Vec<CodeElement> {
+    let lines: Vec<&str> = code.split_inclusive('\n').collect();
+    let mut elements = Vec::with_capacity(1 + lines.len().div_ceil(CHUNK_LINES));
+    let source = SourceText::from(code);
+    let file = file_element(
+        source.clone(),
+        file_path,
+        relative_path,
+        language,
+        lines.len(),
+    );
+    let mut offset = 0;
+    for (i, chunk) in lines.chunks(CHUNK_LINES).enumerate() {
+        let range = offset..offset + chunk.iter().map(|line| line.len()).sum::<usize>();
+        offset = range.end;
+        if code[range.clone()].trim().is_empty() {
+            continue;
+        }
+        let start_line = i * CHUNK_LINES + 1;
+        let end_line = start_line + chunk.len() - 1;
+        elements.push(CodeElement {
+            id: CodeElement::generate_id("chunk", &[relative_path, &start_line.to_string()]),
+            element_type: ElementType::Chunk,
+            name: format!("{}:{start_line}-{end_line}", file.name),
+            file_path: file_path.to_string(),
+            relative_path: relative_path.to_string(),
+            language: language.name().to_string(),
+            start_line,
+            end_line,
+            code: source.slice(range).unwrap_or_default(),
+            signature: None,
+            docstring: None,
+            summary: None,
+            parent_id: None,
+            metadata: HashMap::from([(CONTENT_ONLY_KEY.to_string(), "true".to_string())]),
+        });
+    }
+    elements.insert(0, file);
+    elements
+}
+
+/// The element for a whole file, flagged with [`CONTENT_ONLY_KEY`] for a
+/// language indexed without parsing.
+fn file_element(
+    source: SourceText,
+    file_path: &str,
+    relative_path: &str,
+    language: SupportedLanguage,
+    line_count: usize,
+) -> CodeElement {
+    CodeElement {
+        id: CodeElement::generate_id("file", &[relative_path]),
+        element_type: ElementType::File,
+        name: Path::new(file_path)
+            .file_name()
+            .map(|f| f.to_string_lossy().to_string())
+            .unwrap_or_default(),
+        file_path: file_path.to_string(),
+        relative_path: relative_path.to_string(),
+        language: language.name().to_string(),
+        start_line: 1,
+        end_line: line_count,
+        code: source,
+        signature: None,
+        docstring: None,
+        summary: None,
+        parent_id: None,
+        metadata: match language.indexing_mode() {
+            IndexingMode::Parsed => HashMap::new(),
+            IndexingMode::ContentOnly => {
+                HashMap::from([(CONTENT_ONLY_KEY.to_string(), "true".to_string())])
+            }
+        },
+    }
+}
+
 /// Check if a tree-sitter node kind represents a function/method definition.
 fn is_function_kind(kind: &str) -> bool {
     matches!(
         kind,
         // Python
         "function_definition" |
-        // JS/TS
+        // JS/TS/Swift
         "function_declaration" |
         // JS/TS class methods
         "method_definition" |
         // Rust
         "function_item" |
-        // Go/Java methods
+        // Go/Java/C# methods
         "method_declaration" |
-        // Java constructors
-        "constructor_declaration"
+        // Java/C# constructors
+        "constructor_declaration" |
+        // Swift initializers and protocol requirements
+        "init_declaration" | "protocol_function_declaration"
     )
 }
 
@@ -158,23 +514,29 @@ fn is_function_kind(kind: &str) -> bool {
 fn is_class_like_kind(kind: &str) -> bool {
     matches!(
         kind,
-        // Python/JS/TS/Java
+        // Python/JS/TS/Java/C#, and Swift classes, structs, enums, and extensions
         "class_definition" | "class_declaration" |
         // Rust
-        "struct_item" | "enum_item" | "impl_item" |
+        "struct_item" | "enum_item" | "impl_item" | "trait_item" |
         // C/C++
         "struct_specifier" | "enum_specifier" |
-        // TS/Java
-        "interface_declaration" | "enum_declaration"
+        // TS/Java/C#
+        "interface_declaration" | "enum_declaration" |
+        // C#, and Java records
+        "struct_declaration" | "record_declaration" | "record_struct_declaration" |
+        // Swift
+        "protocol_declaration"
     )
 }
 
 /// Determine the ElementType for a class-like node kind.
 fn classify_class_kind(kind: &str) -> ElementType {
     match kind {
-        "struct_item" | "struct_specifier" => ElementType::Struct,
+        "struct_item" | "struct_specifier" | "struct_declaration" | "record_struct_declaration" => {
+            ElementType::Struct
+        }
         "enum_item" | "enum_specifier" | "enum_declaration" => ElementType::Enum,
-        "interface_declaration" => ElementType::Interface,
+        "interface_declaration" | "trait_item" | "protocol_declaration" => ElementType::Interface,
         "impl_item" => ElementType::Class,
         _ => ElementType::Class,
     }
@@ -192,16 +554,14 @@ fn extract_name(node: &tree_sitter::Node, code_bytes: &[u8]) -> Option<String> {
         );
     }
 
-    // Rust impl_item uses "type" field for the implemented type
+    // Swift initializers are all named `init`
+    if node.kind() == "init_declaration" {
+        return Some("init".to_string());
+    }
+
+    // Rust impl_item is named after the type it is for
     if node.kind() == "impl_item" {
-        if let Some(type_node) = node.child_by_field_name("type") {
-            return Some(
-                type_node
-                    .utf8_text(code_bytes)
-                    .unwrap_or_default()
-                    .to_string(),
-            );
-        }
+        return rust_impl_target(node, code_bytes).map(|(ty, _)| ty);
     }
 
     // C/C++ function_definition uses "declarator" field
@@ -246,16 +606,25 @@ fn extract_declarator_name(node: &tree_sitter::Node, code_bytes: &[u8]) -> Optio
     }
 }
 
+/// The class-like element definitions are nested in.
+#[derive(Clone, Copy)]
+struct ParentClass<'a> {
+    name: &'a str,
+    id: &'a str,
+    /// For a Rust `impl` block, the type and trait it is for.
+    rust_impl: Option<(&'a str, Option<&'a str>)>,
+}
+
 fn collect_definitions(
     node: &tree_sitter::Node,
     code_bytes: &[u8],
-    code: &str,
+    source: &SourceText,
     file_path: &str,
     relative_path: &str,
     language: &str,
     lang_enum: SupportedLanguage,
     elements: &mut Vec<CodeElement>,
-    parent_class: Option<&str>,
+    parent_class: Option<ParentClass>,
 ) {
     let kind = node.kind();
 
@@ -269,16 +638,31 @@ fn collect_definitions(
 
             let start_line = node.start_position().row + 1;
             let end_line = node.end_position().row + 1;
-            let node_code = node.utf8_text(code_bytes).unwrap_or_default().to_string();
+            let node_code = source.slice(node.byte_range()).unwrap_or_default();
             let signature = node_code.lines().next().map(|s| s.to_string());
             let docstring = extract_docstring(node, code_bytes, lang_enum);
+            let visibility =
+                extract_visibility(node, code_bytes, lang_enum, &name, parent_class.is_some());
 
             let id_parts: Vec<&str> = if let Some(cls) = parent_class {
-                vec![relative_path, cls, &name]
+                vec![relative_path, cls.name, &name]
             } else {
                 vec![relative_path, &name]
             };
             let id = CodeElement::generate_id(element_type.as_str(), &id_parts);
+            let mut metadata =
+                HashMap::from([(VISIBILITY_KEY.to_string(), visibility.as_str().to_string())]);
+            if lang_enum == SupportedLanguage::Go
+                && let Some(receiver) = go_receiver_type(node, code_bytes)
+            {
+                metadata.insert(RECEIVER_KEY.to_string(), receiver);
+            }
+            if let Some((impl_type, impl_trait)) = parent_class.and_then(|cls| cls.rust_impl) {
+                metadata.insert(IMPL_TYPE_KEY.to_string(), impl_type.to_string());
+                if let Some(impl_trait) = impl_trait {
+                    metadata.insert(IMPL_TRAIT_KEY.to_string(), impl_trait.to_string());
+                }
+            }
 
             elements.push(CodeElement {
                 id,
@@ -293,23 +677,69 @@ fn collect_definitions(
                 signature,
                 docstring,
                 summary: None,
-                metadata: HashMap::new(),
+                parent_id: parent_class.map(|cls| cls.id.to_string()),
+                metadata,
             });
         }
     } else if is_class_like_kind(kind) {
-        if let Some(name) = extract_name(node, code_bytes) {
-            let element_type = classify_class_kind(kind);
+        if let Some(mut name) = extract_name(node, code_bytes) {
+            let swift_kind = (lang_enum == SupportedLanguage::Swift)
+                .then(|| node.child_by_field_name("declaration_kind"))
+                .flatten()
+                .and_then(|kind| kind.utf8_text(code_bytes).ok());
+            let element_type = match swift_kind {
+                Some("struct") => ElementType::Struct,
+                Some("enum") => ElementType::Enum,
+                _ => classify_class_kind(kind),
+            };
+            if swift_kind == Some("extension") {
+                // `extension Array<Int>` extends `Array`.
+                name = name
+                    .split('<')
+                    .next()
+                    .unwrap_or_default()
+                    .trim()
+                    .to_string();
+            }
 
             let start_line = node.start_position().row + 1;
             let end_line = node.end_position().row + 1;
-            let node_code = node.utf8_text(code_bytes).unwrap_or_default().to_string();
+            let node_code = source.slice(node.byte_range()).unwrap_or_default();
             let signature = node_code.lines().next().map(|s| s.to_string());
             let docstring = extract_docstring(node, code_bytes, lang_enum);
-
-            let id = CodeElement::generate_id(element_type.as_str(), &[relative_path, &name]);
+            let visibility =
+                extract_visibility(node, code_bytes, lang_enum, &name, parent_class.is_some());
+
+            let rust_impl = (kind == "impl_item")
+                .then(|| rust_impl_target(node, code_bytes))
+                .flatten();
+            let impl_trait = rust_impl.as_ref().and_then(|(_, t)| t.as_deref());
+            // A type can have an inherent impl and several trait impls in
+            // one file; trait impls and their methods are keyed like Rust's
+            // `<Foo as Display>` so their ids stay apart.
+            let key = match impl_trait {
+                Some(impl_trait) => format!("<{name} as {impl_trait}>"),
+                // Likewise a Swift type and its extensions.
+                None if swift_kind == Some("extension") => {
+                    match swift_inherited_types(node, code_bytes).as_slice() {
+                        [] => format!("extension {name}"),
+                        types => format!("extension {name}: {}", types.join(", ")),
+                    }
+                }
+                None => name.clone(),
+            };
+            let id = CodeElement::generate_id(element_type.as_str(), &[relative_path, &key]);
+            let mut metadata =
+                HashMap::from([(VISIBILITY_KEY.to_string(), visibility.as_str().to_string())]);
+            if let Some((impl_type, impl_trait)) = &rust_impl {
+                metadata.insert(IMPL_TYPE_KEY.to_string(), impl_type.clone());
+                if let Some(impl_trait) = impl_trait {
+                    metadata.insert(IMPL_TRAIT_KEY.to_string(), impl_trait.clone());
+                }
+            }
 
             elements.push(CodeElement {
-                id,
+                id: id.clone(),
                 element_type,
                 name: name.clone(),
                 file_path: file_path.to_string(),
@@ -321,7 +751,8 @@ fn collect_definitions(
                 signature,
                 docstring,
                 summary: None,
-                metadata: HashMap::new(),
+                parent_id: parent_class.map(|cls| cls.id.to_string()),
+                metadata,
             });
 
             // Recurse into class/struct/impl body to find methods
@@ -330,34 +761,625 @@ fn collect_definitions(
                 collect_definitions(
                     &child,
                     code_bytes,
-                    code,
+                    source,
                     file_path,
                     relative_path,
                     language,
                     lang_enum,
                     elements,
-                    Some(&name),
+                    Some(ParentClass {
+                        name: &key,
+                        id: &id,
+                        rust_impl: rust_impl
+                            .as_ref()
+                            .map(|(ty, tr)| (ty.as_str(), tr.as_deref())),
+                    }),
                 );
             }
-            return;
+            return;
+        }
+    }
+
+    if lang_enum == SupportedLanguage::Go
+        && let Some((name, element_type, code_node)) = go_type_definition(node, code_bytes)
+    {
+        let node_code = source.slice(code_node.byte_range()).unwrap_or_default();
+        let mut metadata = HashMap::from([(
+            VISIBILITY_KEY.to_string(),
+            extract_visibility(node, code_bytes, lang_enum, &name, false)
+                .as_str()
+                .to_string(),
+        )]);
+        if let Some(body) = node.child_by_field_name("type") {
+            let (embeds, methods) = go_type_members(&body, code_bytes);
+            if !embeds.is_empty() {
+                metadata.insert(EMBEDS_KEY.to_string(), embeds.join(","));
+            }
+            if element_type == ElementType::Interface {
+                metadata.insert(METHODS_KEY.to_string(), methods.join(","));
+            }
+        }
+        elements.push(CodeElement {
+            id: CodeElement::generate_id(element_type.as_str(), &[relative_path, &name]),
+            element_type,
+            name,
+            file_path: file_path.to_string(),
+            relative_path: relative_path.to_string(),
+            language: language.to_string(),
+            start_line: code_node.start_position().row + 1,
+            end_line: code_node.end_position().row + 1,
+            signature: node_code.lines().next().map(|s| s.to_string()),
+            code: node_code,
+            docstring: extract_docstring(&code_node, code_bytes, lang_enum),
+            summary: None,
+            parent_id: None,
+            metadata,
+        });
+        return;
+    }
+
+    for (name, element_type, code_node) in extract_variables(node, code_bytes, lang_enum) {
+        let start_line = code_node.start_position().row + 1;
+        let end_line = code_node.end_position().row + 1;
+        let node_code = source.slice(code_node.byte_range()).unwrap_or_default();
+        let signature = node_code.lines().next().map(|s| s.to_string());
+        let docstring = extract_docstring(&code_node, code_bytes, lang_enum);
+        let visibility =
+            extract_visibility(node, code_bytes, lang_enum, &name, parent_class.is_some());
+
+        let id_parts: Vec<&str> = if let Some(cls) = parent_class {
+            vec![relative_path, cls.name, &name]
+        } else {
+            vec![relative_path, &name]
+        };
+        let id = CodeElement::generate_id(element_type.as_str(), &id_parts);
+        // A reassignment is not a second definition.
+        if elements.iter().any(|e| e.id == id) {
+            continue;
+        }
+
+        elements.push(CodeElement {
+            id,
+            element_type,
+            name,
+            file_path: file_path.to_string(),
+            relative_path: relative_path.to_string(),
+            language: language.to_string(),
+            start_line,
+            end_line,
+            code: node_code,
+            signature,
+            docstring,
+            summary: None,
+            parent_id: parent_class.map(|cls| cls.id.to_string()),
+            metadata: HashMap::from([(
+                VISIBILITY_KEY.to_string(),
+                visibility.as_str().to_string(),
+            )]),
+        });
+    }
+
+    // Recurse into children
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        collect_definitions(
+            &child,
+            code_bytes,
+            source,
+            file_path,
+            relative_path,
+            language,
+            lang_enum,
+            elements,
+            parent_class,
+        );
+    }
+}
+
+/// The namespaces declared under `node`, nested ones joined to their
+/// parent's: `namespace A { namespace B.C {} }` declares `A` and `A.B.C`.
+fn csharp_namespaces(
+    node: &tree_sitter::Node,
+    code_bytes: &[u8],
+    outer: &str,
+    namespaces: &mut Vec<String>,
+) {
+    let mut cursor = node.walk();
+    for child in node.named_children(&mut cursor) {
+        match child.kind() {
+            "namespace_declaration" | "file_scoped_namespace_declaration" => {
+                let Some(name) = child
+                    .child_by_field_name("name")
+                    .and_then(|n| n.utf8_text(code_bytes).ok())
+                else {
+                    continue;
+                };
+                let name: String = name.split_whitespace().collect();
+                let name = if outer.is_empty() {
+                    name
+                } else {
+                    format!("{outer}.{name}")
+                };
+                if !namespaces.contains(&name) {
+                    namespaces.push(name.clone());
+                }
+                csharp_namespaces(&child, code_bytes, &name, namespaces);
+            }
+            "declaration_list" => csharp_namespaces(&child, code_bytes, outer, namespaces),
+            _ => {}
+        }
+    }
+}
+
+/// The type a Rust `impl` block is for and the trait it implements, both
+/// without paths, references, or generic arguments: `impl<T> fmt::Display
+/// for &Foo<T>` is (`Foo`, `Display`).
+fn rust_impl_target(
+    node: &tree_sitter::Node,
+    code_bytes: &[u8],
+) -> Option<(String, Option<String>)> {
+    let type_name = |field: &str| {
+        node.child_by_field_name(field)
+            .and_then(|n| n.utf8_text(code_bytes).ok())
+            .map(rust_type_name)
+            .filter(|name| !name.is_empty())
+    };
+    Some((type_name("type")?, type_name("trait")))
+}
+
+/// `Foo` for `&'a mut crate::model::Foo<T>` or `dyn Foo + Send`.
+fn rust_type_name(text: &str) -> String {
+    let mut text = text.trim();
+    loop {
+        let stripped = text
+            .trim_start_matches('&')
+            .trim_start_matches("mut ")
+            .trim_start_matches("dyn ")
+            .trim_start();
+        let stripped = match stripped.strip_prefix('\'') {
+            Some(rest) => rest
+                .split_once(char::is_whitespace)
+                .map_or("", |(_, rest)| rest)
+                .trim_start(),
+            None => stripped,
+        };
+        if stripped == text {
+            break;
+        }
+        text = stripped;
+    }
+    let path = text.split(['<', ' ', '+']).next().unwrap_or(text);
+    path.rsplit("::").next().unwrap_or(path).to_string()
+}
+
+/// A Go `type Name struct {...}` or `type Name interface {...}` spec, as
+/// (name, type, node whose source is the element's). Like `const` and `var`,
+/// a spec alone in its declaration takes the `type` keyword and doc comment
+/// with it. Other named types are not elements.
+fn go_type_definition<'t>(
+    node: &tree_sitter::Node<'t>,
+    code_bytes: &[u8],
+) -> Option<(String, ElementType, tree_sitter::Node<'t>)> {
+    if node.kind() != "type_spec" {
+        return None;
+    }
+    let element_type = match node.child_by_field_name("type")?.kind() {
+        "struct_type" => ElementType::Struct,
+        "interface_type" => ElementType::Interface,
+        _ => return None,
+    };
+    let name = node
+        .child_by_field_name("name")?
+        .utf8_text(code_bytes)
+        .ok()?
+        .to_string();
+    let code_node = node
+        .parent()
+        .filter(|decl| decl.kind() == "type_declaration" && decl.named_child_count() == 1)
+        .unwrap_or(*node);
+    Some((name, element_type, code_node))
+}
+
+/// The embedded types and method names of a Go struct or interface body.
+/// Embedded types keep their package qualifier and drop pointers and type
+/// arguments: `*pkg.Base[T]` is `pkg.Base`.
+fn go_type_members(body: &tree_sitter::Node, code_bytes: &[u8]) -> (Vec<String>, Vec<String>) {
+    let mut embeds = Vec::new();
+    let mut methods = Vec::new();
+    let mut push_type = |node: tree_sitter::Node| {
+        let text = node.utf8_text(code_bytes).unwrap_or_default();
+        let name = text.trim_start_matches('*');
+        let name = name.split('[').next().unwrap_or(name).trim();
+        if !name.is_empty() {
+            embeds.push(name.to_string());
+        }
+    };
+    let mut stack = vec![*body];
+    while let Some(node) = stack.pop() {
+        match node.kind() {
+            // An embedded field has a type but no name.
+            "field_declaration" => {
+                if node.child_by_field_name("name").is_none()
+                    && let Some(ty) = node.child_by_field_name("type")
+                {
+                    push_type(ty);
+                }
+            }
+            "method_elem" | "method_spec" => {
+                if let Some(name) = node.child_by_field_name("name") {
+                    methods.push(name.utf8_text(code_bytes).unwrap_or_default().to_string());
+                }
+            }
+            // Embedded interfaces; unions like `~int | ~string` are
+            // constraints, not embeddings.
+            "type_elem" | "constraint_elem" | "interface_type_name" => {
+                let named: Vec<tree_sitter::Node> = {
+                    let mut cursor = node.walk();
+                    node.named_children(&mut cursor).collect()
+                };
+                match named.as_slice() {
+                    [ty] if matches!(ty.kind(), "type_identifier" | "qualified_type") => {
+                        push_type(*ty)
+                    }
+                    [] if node.kind() == "interface_type_name" => push_type(node),
+                    _ => {}
+                }
+            }
+            _ => {
+                let mut cursor = node.walk();
+                let children: Vec<tree_sitter::Node> = node.named_children(&mut cursor).collect();
+                stack.extend(children.into_iter().rev());
+            }
+        }
+    }
+    (embeds, methods)
+}
+
+/// The type a Go method is declared on: `Dog` for `func (d *Dog[T]) Speak()`.
+fn go_receiver_type(node: &tree_sitter::Node, code_bytes: &[u8]) -> Option<String> {
+    let receiver = node.child_by_field_name("receiver")?;
+    let mut cursor = receiver.walk();
+    let param = receiver
+        .named_children(&mut cursor)
+        .find(|child| child.kind() == "parameter_declaration")?;
+    let text = param
+        .child_by_field_name("type")?
+        .utf8_text(code_bytes)
+        .ok()?;
+    let name = text.trim_start_matches('*');
+    let name = name.split('[').next().unwrap_or(name).trim();
+    (!name.is_empty()).then(|| name.to_string())
+}
+
+/// Constants, variables, and type aliases a module-level declaration or a
+/// class field defines, as (name, type, node whose source is the element's).
+/// Locals are skipped: Python and Go only count statements directly in the
+/// file, JS/TS only exported ones, Rust and Java anything outside a body of
+/// code.
+fn extract_variables<'t>(
+    node: &tree_sitter::Node<'t>,
+    code_bytes: &[u8],
+    language: SupportedLanguage,
+) -> Vec<(String, ElementType, tree_sitter::Node<'t>)> {
+    let text = |n: tree_sitter::Node| n.utf8_text(code_bytes).unwrap_or_default().to_string();
+    let parent_kind = node.parent().map(|p| p.kind()).unwrap_or_default();
+    let mut found = Vec::new();
+    match (language, node.kind()) {
+        (SupportedLanguage::Python, "expression_statement") if parent_kind == "module" => {
+            if let Some(assignment) = node.child(0).filter(|c| c.kind() == "assignment")
+                && let Some(left) = assignment
+                    .child_by_field_name("left")
+                    .filter(|l| l.kind() == "identifier")
+            {
+                let name = text(left);
+                let element_type = if is_constant_name(&name) {
+                    ElementType::Constant
+                } else {
+                    ElementType::Variable
+                };
+                found.push((name, element_type, *node));
+            }
+        }
+        (SupportedLanguage::Rust, "const_item" | "static_item" | "type_item")
+            if !in_code_body(node) =>
+        {
+            if let Some(name) = node.child_by_field_name("name") {
+                let element_type = match node.kind() {
+                    "type_item" => ElementType::TypeAlias,
+                    _ if has_child_kind(node, "mutable_specifier") => ElementType::Variable,
+                    _ => ElementType::Constant,
+                };
+                found.push((text(name), element_type, *node));
+            }
+        }
+        (SupportedLanguage::Go, "const_declaration" | "var_declaration")
+            if parent_kind == "source_file" =>
+        {
+            let element_type = if node.kind() == "const_declaration" {
+                ElementType::Constant
+            } else {
+                ElementType::Variable
+            };
+            // `var ( ... )` groups its specs in a `var_spec_list`.
+            let mut specs = Vec::new();
+            let mut cursor = node.walk();
+            for child in node.named_children(&mut cursor) {
+                if child.kind() == "var_spec_list" {
+                    let mut inner = child.walk();
+                    specs.extend(child.named_children(&mut inner));
+                } else {
+                    specs.push(child);
+                }
+            }
+            specs.retain(|spec| matches!(spec.kind(), "const_spec" | "var_spec"));
+            let single = specs.len() == 1;
+            for spec in specs {
+                let code_node = if single { *node } else { spec };
+                let mut cursor = spec.walk();
+                for name in spec.children_by_field_name("name", &mut cursor) {
+                    found.push((text(name), element_type, code_node));
+                }
+            }
+        }
+        (
+            SupportedLanguage::JavaScript | SupportedLanguage::TypeScript | SupportedLanguage::Tsx,
+            "lexical_declaration" | "variable_declaration" | "type_alias_declaration",
+        ) if parent_kind == "export_statement" => {
+            let Some(export) = node.parent() else {
+                return found;
+            };
+            if node.kind() == "type_alias_declaration" {
+                if let Some(name) = node.child_by_field_name("name") {
+                    found.push((text(name), ElementType::TypeAlias, export));
+                }
+                return found;
+            }
+            let is_const = node.child(0).is_some_and(|k| k.kind() == "const");
+            let mut cursor = node.walk();
+            for declarator in node.named_children(&mut cursor) {
+                let Some(name) = declarator
+                    .child_by_field_name("name")
+                    .filter(|n| n.kind() == "identifier")
+                else {
+                    continue;
+                };
+                let is_function = declarator.child_by_field_name("value").is_some_and(|v| {
+                    matches!(
+                        v.kind(),
+                        "arrow_function" | "function_expression" | "function"
+                    )
+                });
+                let element_type = if is_function {
+                    ElementType::Function
+                } else if is_const {
+                    ElementType::Constant
+                } else {
+                    ElementType::Variable
+                };
+                found.push((text(name), element_type, export));
+            }
+        }
+        (SupportedLanguage::CSharp, "field_declaration" | "property_declaration")
+            if parent_kind == "declaration_list" =>
+        {
+            let modifiers = csharp_modifiers(node, code_bytes);
+            let has = |word: &str| modifiers.contains(&word);
+            let element_type = if has("const") || (has("static") && has("readonly")) {
+                ElementType::Constant
+            } else {
+                ElementType::Variable
+            };
+            if node.kind() == "property_declaration" {
+                if let Some(name) = node.child_by_field_name("name") {
+                    found.push((text(name), element_type, *node));
+                }
+                return found;
+            }
+            let Some(declaration) = child_of_kind(node, "variable_declaration") else {
+                return found;
+            };
+            let mut cursor = declaration.walk();
+            for declarator in declaration.named_children(&mut cursor) {
+                if declarator.kind() != "variable_declarator" {
+                    continue;
+                }
+                let name = declarator
+                    .child_by_field_name("name")
+                    .or_else(|| child_of_kind(&declarator, "identifier"));
+                if let Some(name) = name {
+                    found.push((text(name), element_type, *node));
+                }
+            }
+        }
+        (SupportedLanguage::Java, "field_declaration") if !in_code_body(node) => {
+            let modifiers = child_of_kind(node, "modifiers")
+                .map(|m| m.utf8_text(code_bytes).unwrap_or_default())
+                .unwrap_or_default();
+            let has = |word: &str| modifiers.split_whitespace().any(|m| m == word);
+            let in_interface = parent_kind == "interface_body";
+            let element_type = if in_interface || (has("static") && has("final")) {
+                ElementType::Constant
+            } else {
+                ElementType::Variable
+            };
+            let mut cursor = node.walk();
+            for declarator in node.children_by_field_name("declarator", &mut cursor) {
+                if let Some(name) = declarator.child_by_field_name("name") {
+                    found.push((text(name), element_type, *node));
+                }
+            }
+        }
+        _ => {}
+    }
+    found
+}
+
+/// `MAX_RETRIES`, `API_V2`: all caps, with at least one letter.
+fn is_constant_name(name: &str) -> bool {
+    name.chars().any(|c| c.is_ascii_uppercase())
+        && name
+            .chars()
+            .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_')
+}
+
+/// Whether `node` sits inside a function or closure body.
+fn in_code_body(node: &tree_sitter::Node) -> bool {
+    let mut ancestor = node.parent();
+    while let Some(current) = ancestor {
+        let kind = current.kind();
+        if is_function_kind(kind) || matches!(kind, "closure_expression" | "lambda_expression") {
+            return true;
+        }
+        ancestor = current.parent();
+    }
+    false
+}
+
+/// Determine who can reference a definition, from its modifiers or, where
+/// the language has none, its name.
+fn extract_visibility(
+    node: &tree_sitter::Node,
+    code_bytes: &[u8],
+    language: SupportedLanguage,
+    name: &str,
+    in_class: bool,
+) -> Visibility {
+    match language {
+        SupportedLanguage::Python => {
+            if name.starts_with('_') && !(name.starts_with("__") && name.ends_with("__")) {
+                Visibility::Private
+            } else {
+                Visibility::Public
+            }
+        }
+        SupportedLanguage::Go => {
+            if name.starts_with(|c: char| c.is_uppercase()) {
+                Visibility::Public
+            } else {
+                Visibility::Package
+            }
+        }
+        SupportedLanguage::Rust => {
+            // Trait items and trait impl methods are as visible as the trait.
+            let in_trait = node
+                .parent()
+                .and_then(|body| body.parent())
+                .is_some_and(|owner| {
+                    owner.kind() == "trait_item"
+                        || (owner.kind() == "impl_item"
+                            && owner.child_by_field_name("trait").is_some())
+                });
+            if in_trait || has_child_kind(node, "visibility_modifier") {
+                Visibility::Public
+            } else {
+                Visibility::Private
+            }
+        }
+        SupportedLanguage::Java => {
+            let in_interface = node
+                .parent()
+                .is_some_and(|body| body.kind() == "interface_body");
+            let modifiers = child_of_kind(node, "modifiers")
+                .map(|m| m.utf8_text(code_bytes).unwrap_or_default())
+                .unwrap_or_default();
+            let has = |word: &str| modifiers.split_whitespace().any(|m| m == word);
+            if in_interface || has("public") {
+                Visibility::Public
+            } else if has("protected") {
+                Visibility::Protected
+            } else if has("private") {
+                Visibility::Private
+            } else {
+                Visibility::Package
+            }
+        }
+        SupportedLanguage::CSharp => {
+            let in_interface = node
+                .parent()
+                .and_then(|body| body.parent())
+                .is_some_and(|owner| owner.kind() == "interface_declaration");
+            let modifiers = csharp_modifiers(node, code_bytes);
+            let has = |word: &str| modifiers.contains(&word);
+            // `internal` reaches the whole assembly, usually the whole
+            // project, and is the default for top-level types.
+            if in_interface || has("public") || has("internal") {
+                Visibility::Public
+            } else if has("protected") {
+                Visibility::Protected
+            } else if has("private") || in_class {
+                Visibility::Private
+            } else {
+                Visibility::Public
+            }
+        }
+        SupportedLanguage::Swift => {
+            // `internal`, the default, reaches the whole module.
+            let modifiers = child_of_kind(node, "modifiers")
+                .map(|m| m.utf8_text(code_bytes).unwrap_or_default())
+                .unwrap_or_default();
+            if modifiers
+                .split_whitespace()
+                .any(|m| m == "private" || m == "fileprivate")
+            {
+                Visibility::Private
+            } else {
+                Visibility::Public
+            }
+        }
+        SupportedLanguage::JavaScript | SupportedLanguage::TypeScript | SupportedLanguage::Tsx => {
+            let accessibility = child_of_kind(node, "accessibility_modifier")
+                .map(|m| m.utf8_text(code_bytes).unwrap_or_default());
+            match accessibility {
+                Some("private") => Visibility::Private,
+                Some("protected") => Visibility::Protected,
+                _ if name.starts_with('#') => Visibility::Private,
+                _ => Visibility::Public,
+            }
+        }
+        SupportedLanguage::C | SupportedLanguage::Cpp => {
+            // `static` on a class member is not about linkage.
+            let is_static = !in_class
+                && child_of_kind(node, "storage_class_specifier")
+                    .is_some_and(|s| s.utf8_text(code_bytes).unwrap_or_default() == "static");
+            if is_static {
+                Visibility::Private
+            } else {
+                Visibility::Public
+            }
         }
+        // Not parsed, so there are no definitions to mark.
+        SupportedLanguage::Kotlin => Visibility::Public,
     }
+}
 
-    // Recurse into children
+/// The types a Swift class, extension, or protocol declaration lists after
+/// `:`, e.g. `["Codable", "Equatable"]`.
+fn swift_inherited_types(node: &tree_sitter::Node, code_bytes: &[u8]) -> Vec<String> {
     let mut cursor = node.walk();
-    for child in node.children(&mut cursor) {
-        collect_definitions(
-            &child,
-            code_bytes,
-            code,
-            file_path,
-            relative_path,
-            language,
-            lang_enum,
-            elements,
-            parent_class,
-        );
-    }
+    node.children(&mut cursor)
+        .filter(|child| child.kind() == "inheritance_specifier")
+        .filter_map(|child| child.utf8_text(code_bytes).ok())
+        .map(|text| text.trim().to_string())
+        .collect()
+}
+
+/// The modifier keywords of a C# declaration, e.g. `["public", "static"]`.
+fn csharp_modifiers<'a>(node: &tree_sitter::Node, code_bytes: &'a [u8]) -> Vec<&'a str> {
+    let mut cursor = node.walk();
+    node.children(&mut cursor)
+        .filter(|child| child.kind() == "modifier")
+        .filter_map(|child| child.utf8_text(code_bytes).ok())
+        .collect()
+}
+
+fn child_of_kind<'t>(node: &tree_sitter::Node<'t>, kind: &str) -> Option<tree_sitter::Node<'t>> {
+    let mut cursor = node.walk();
+    node.children(&mut cursor)
+        .find(|child| child.kind() == kind)
+}
+
+fn has_child_kind(node: &tree_sitter::Node, kind: &str) -> bool {
+    child_of_kind(node, kind).is_some()
 }
 
 /// Try to extract a docstring from a function/class body.
@@ -369,6 +1391,10 @@ fn extract_docstring(
     match language {
         SupportedLanguage::Python => extract_python_docstring(node, code_bytes),
         SupportedLanguage::Rust => extract_rust_doc_comment(node, code_bytes),
+        SupportedLanguage::CSharp => extract_csharp_doc_comment(node, code_bytes)
+            .or_else(|| extract_comment_doc(node, code_bytes)),
+        SupportedLanguage::Swift => extract_swift_doc_comment(node, code_bytes)
+            .or_else(|| extract_comment_doc(node, code_bytes)),
         _ => extract_comment_doc(node, code_bytes),
     }
 }
@@ -427,12 +1453,66 @@ fn extract_rust_doc_comment(node: &tree_sitter::Node, code_bytes: &[u8]) -> Opti
     Some(doc_lines.join("\n"))
 }
 
-/// Generic comment-based doc extraction for JS/TS/Java/Go/C++.
+/// The `///` comment lines directly preceding an item, in order and
+/// without the slashes.
+fn triple_slash_lines<'a>(node: &tree_sitter::Node, code_bytes: &'a [u8]) -> Vec<&'a str> {
+    let mut doc_lines = Vec::new();
+    let mut prev = node.prev_sibling();
+    while let Some(sibling) = prev {
+        let text = sibling.utf8_text(code_bytes).unwrap_or_default();
+        match text.strip_prefix("///") {
+            Some(content) if sibling.kind() == "comment" => doc_lines.push(content),
+            _ => break,
+        }
+        prev = sibling.prev_sibling();
+    }
+    doc_lines.reverse();
+    doc_lines
+}
+
+/// Swift doc comments: `///` lines preceding the item.
+fn extract_swift_doc_comment(node: &tree_sitter::Node, code_bytes: &[u8]) -> Option<String> {
+    let doc = triple_slash_lines(node, code_bytes)
+        .iter()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n");
+    (!doc.is_empty()).then_some(doc)
+}
+
+/// C# XML doc comments: `///` lines preceding the item, without their tags.
+fn extract_csharp_doc_comment(node: &tree_sitter::Node, code_bytes: &[u8]) -> Option<String> {
+    let doc = triple_slash_lines(node, code_bytes)
+        .iter()
+        .map(|line| strip_xml_tags(line).trim().to_string())
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n");
+    (!doc.is_empty()).then_some(doc)
+}
+
+/// `Gets the user.` for `<summary>Gets the user.</summary>`.
+fn strip_xml_tags(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut in_tag = false;
+    for c in text.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' if in_tag => in_tag = false,
+            _ if !in_tag => out.push(c),
+            _ => {}
+        }
+    }
+    out
+}
+
+/// Generic comment-based doc extraction for JS/TS/Java/Go/C++/Swift.
 fn extract_comment_doc(node: &tree_sitter::Node, code_bytes: &[u8]) -> Option<String> {
     let mut prev = node.prev_sibling();
     while let Some(sibling) = prev {
         match sibling.kind() {
-            "comment" | "block_comment" => {
+            "comment" | "block_comment" | "multiline_comment" => {
                 let text = sibling.utf8_text(code_bytes).unwrap_or_default();
                 if text.starts_with("/**") || text.starts_with("/*") {
                     let cleaned = text
@@ -515,6 +1595,7 @@ def foo():
     }
 
     #[test]
+    #[cfg(feature = "lang-rust")]
     fn test_extract_rust_functions() {
         let code = r#"
 /// Documentation for greet.
@@ -595,6 +1676,7 @@ enum Color {
     }
 
     #[test]
+    #[cfg(feature = "lang-javascript")]
     fn test_extract_javascript_elements() {
         let code = r#"
 function processData(data) {
@@ -636,6 +1718,7 @@ class DataService {
     }
 
     #[test]
+    #[cfg(feature = "lang-typescript")]
     fn test_extract_typescript_elements() {
         let code = r#"
 interface UserService {
@@ -696,6 +1779,7 @@ class UserManager {
     }
 
     #[test]
+    #[cfg(feature = "lang-go")]
     fn test_extract_go_elements() {
         let code = r#"
 package main
@@ -724,6 +1808,7 @@ func Hello(name string) string {
     }
 
     #[test]
+    #[cfg(feature = "lang-java")]
     fn test_extract_java_elements() {
         let code = r#"
 public class UserService {
@@ -765,6 +1850,480 @@ public class UserService {
         );
     }
 
+    #[test]
+    #[cfg(feature = "lang-csharp")]
+    fn test_extract_csharp_elements() {
+        let code = r#"
+using System;
+
+namespace My.Company.Services
+{
+    /// <summary>Looks users up.</summary>
+    public interface IUserService
+    {
+        User Find(int id);
+    }
+
+    [Serializable]
+    public class UserService : IUserService
+    {
+        public UserService() {}
+
+        public User Find(int id) => new User(id);
+    }
+
+    public struct Point { public int X; }
+
+    public enum Status { Active, Inactive }
+
+    public record User(int Id);
+
+    namespace Internal
+    {
+        static class Helpers {}
+    }
+}
+"#;
+        let mut parser = Parser::new();
+        let tree = parser.parse(code, SupportedLanguage::CSharp).unwrap();
+        let elements = extract_elements_from_tree(
+            &tree,
+            code,
+            "UserService.cs",
+            "UserService.cs",
+            SupportedLanguage::CSharp,
+            "/repo",
+        );
+
+        let found: Vec<(&str, ElementType)> = elements
+            .iter()
+            .filter(|e| e.element_type != ElementType::Variable)
+            .map(|e| (e.name.as_str(), e.element_type))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("UserService.cs", ElementType::File),
+                ("IUserService", ElementType::Interface),
+                ("Find", ElementType::Method),
+                ("UserService", ElementType::Class),
+                ("UserService", ElementType::Method),
+                ("Find", ElementType::Method),
+                ("Point", ElementType::Struct),
+                ("Status", ElementType::Enum),
+                ("User", ElementType::Class),
+                ("Helpers", ElementType::Class),
+            ]
+        );
+        assert_eq!(
+            elements[0].metadata.get(NAMESPACES_KEY).map(String::as_str),
+            Some("My.Company.Services,My.Company.Services.Internal")
+        );
+        let iface = elements.iter().find(|e| e.name == "IUserService").unwrap();
+        assert_eq!(iface.docstring.as_deref(), Some("Looks users up."));
+        let find = elements.iter().rfind(|e| e.name == "Find").unwrap();
+        let class = elements.iter().find(|e| e.name == "UserService").unwrap();
+        assert_eq!(find.parent_id.as_ref(), Some(&class.id));
+    }
+
+    #[test]
+    #[cfg(feature = "lang-swift")]
+    fn test_extract_swift_elements() {
+        let code = r#"
+import Foundation
+
+/// Looks users up.
+protocol UserStore {
+    func find(id: Int) -> User?
+}
+
+class UserService: UserStore {
+    private let cache: [Int: User] = [:]
+
+    init() {}
+
+    func find(id: Int) -> User? {
+        return cache[id]
+    }
+
+    fileprivate func evict() {}
+}
+
+struct User {
+    let id: Int
+}
+
+enum Status {
+    case active, inactive
+}
+
+extension UserService: CustomStringConvertible {
+    var description: String { "users" }
+
+    func reset() {}
+}
+
+func makeService() -> UserService {
+    UserService()
+}
+"#;
+        let mut parser = Parser::new();
+        let tree = parser.parse(code, SupportedLanguage::Swift).unwrap();
+        let elements = extract_elements_from_tree(
+            &tree,
+            code,
+            "UserService.swift",
+            "UserService.swift",
+            SupportedLanguage::Swift,
+            "/repo",
+        );
+
+        let found: Vec<(&str, ElementType)> = elements
+            .iter()
+            .map(|e| (e.name.as_str(), e.element_type))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("UserService.swift", ElementType::File),
+                ("UserStore", ElementType::Interface),
+                ("find", ElementType::Method),
+                ("UserService", ElementType::Class),
+                ("init", ElementType::Method),
+                ("find", ElementType::Method),
+                ("evict", ElementType::Method),
+                ("User", ElementType::Struct),
+                ("Status", ElementType::Enum),
+                ("UserService", ElementType::Class),
+                ("reset", ElementType::Method),
+                ("makeService", ElementType::Function),
+            ]
+        );
+        let store = elements.iter().find(|e| e.name == "UserStore").unwrap();
+        assert_eq!(store.docstring.as_deref(), Some("Looks users up."));
+        let evict = elements.iter().find(|e| e.name == "evict").unwrap();
+        assert_eq!(evict.visibility(), Visibility::Private);
+        let reset = elements.iter().find(|e| e.name == "reset").unwrap();
+        let extension = elements.iter().rfind(|e| e.name == "UserService").unwrap();
+        assert_eq!(reset.parent_id.as_ref(), Some(&extension.id));
+        let mut ids: Vec<&str> = elements.iter().map(|e| e.id.as_str()).collect();
+        ids.sort();
+        ids.dedup();
+        assert_eq!(ids.len(), elements.len());
+    }
+
+    fn variables(
+        code: &str,
+        file_name: &str,
+        language: SupportedLanguage,
+    ) -> Vec<(String, ElementType)> {
+        let mut parser = Parser::new();
+        let tree = parser.parse(code, language).unwrap();
+        extract_elements_from_tree(&tree, code, file_name, file_name, language, "/repo")
+            .into_iter()
+            .filter(|e| {
+                matches!(
+                    e.element_type,
+                    ElementType::Constant | ElementType::Variable | ElementType::TypeAlias
+                )
+            })
+            .map(|e| (e.name, e.element_type))
+            .collect()
+    }
+
+    #[test]
+    fn test_python_module_level_assignments() {
+        let code = r#"
+MAX_RETRIES = 3
+registry = {}
+MAX_RETRIES = 5
+a, b = 1, 2
+
+def run():
+    LOCAL = 1
+    return LOCAL
+"#;
+        assert_eq!(
+            variables(code, "config.py", SupportedLanguage::Python),
+            vec![
+                ("MAX_RETRIES".to_string(), ElementType::Constant),
+                ("registry".to_string(), ElementType::Variable),
+            ]
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "lang-rust")]
+    fn test_rust_consts_statics_and_type_aliases() {
+        let code = r#"
+/// Attempts before giving up.
+pub const MAX_RETRIES: u32 = 3;
+static mut COUNTER: u32 = 0;
+pub type Result<T> = std::result::Result<T, Error>;
+
+fn run() {
+    const LOCAL: u32 = 1;
+}
+"#;
+        assert_eq!(
+            variables(code, "lib.rs", SupportedLanguage::Rust),
+            vec![
+                ("MAX_RETRIES".to_string(), ElementType::Constant),
+                ("COUNTER".to_string(), ElementType::Variable),
+                ("Result".to_string(), ElementType::TypeAlias),
+            ]
+        );
+        let vis = visibilities(code, "lib.rs", SupportedLanguage::Rust);
+        assert_eq!(vis["MAX_RETRIES"], Visibility::Public);
+        assert_eq!(vis["COUNTER"], Visibility::Private);
+    }
+
+    #[test]
+    #[cfg(feature = "lang-typescript")]
+    fn test_typescript_exported_declarations() {
+        let code = r#"
+export const router = createRouter();
+export let current = 0;
+export type UserId = string;
+export const handler = async () => {
+    const local = 1;
+};
+const internal = 1;
+"#;
+        let mut parser = Parser::new();
+        let tree = parser.parse(code, SupportedLanguage::TypeScript).unwrap();
+        let elements = extract_elements_from_tree(
+            &tree,
+            code,
+            "routes.ts",
+            "routes.ts",
+            SupportedLanguage::TypeScript,
+            "/repo",
+        );
+        let kinds: Vec<(&str, ElementType)> = elements
+            .iter()
+            .filter(|e| e.element_type != ElementType::File)
+            .map(|e| (e.name.as_str(), e.element_type))
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                ("router", ElementType::Constant),
+                ("current", ElementType::Variable),
+                ("UserId", ElementType::TypeAlias),
+                ("handler", ElementType::Function),
+            ]
+        );
+        let router = elements.iter().find(|e| e.name == "router").unwrap();
+        assert_eq!(router.code, "export const router = createRouter();");
+    }
+
+    #[test]
+    #[cfg(feature = "lang-go")]
+    fn test_go_consts_and_vars() {
+        let code = r#"
+package main
+
+const Timeout = 30
+
+var (
+    cache = map[string]int{}
+    Debug bool
+)
+
+func run() {
+    var local = 1
+}
+"#;
+        assert_eq!(
+            variables(code, "main.go", SupportedLanguage::Go),
+            vec![
+                ("Timeout".to_string(), ElementType::Constant),
+                ("cache".to_string(), ElementType::Variable),
+                ("Debug".to_string(), ElementType::Variable),
+            ]
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "lang-java")]
+    fn test_java_fields() {
+        let code = r#"
+public class UserService {
+    public static final int MAX_USERS = 100;
+    private String name;
+
+    void reset() {
+        int local = 0;
+    }
+}
+"#;
+        assert_eq!(
+            variables(code, "UserService.java", SupportedLanguage::Java),
+            vec![
+                ("MAX_USERS".to_string(), ElementType::Constant),
+                ("name".to_string(), ElementType::Variable),
+            ]
+        );
+        let vis = visibilities(code, "UserService.java", SupportedLanguage::Java);
+        assert_eq!(vis["name"], Visibility::Private);
+    }
+
+    #[test]
+    #[cfg(feature = "lang-csharp")]
+    fn test_csharp_fields_and_properties() {
+        let code = r#"
+public class UserService
+{
+    public const int MaxUsers = 100;
+    private static readonly TimeSpan Timeout = TimeSpan.FromSeconds(5);
+    private readonly IRepository _repository, _fallback;
+    public string Name { get; set; }
+
+    void Reset()
+    {
+        int local = 0;
+    }
+}
+"#;
+        assert_eq!(
+            variables(code, "UserService.cs", SupportedLanguage::CSharp),
+            vec![
+                ("MaxUsers".to_string(), ElementType::Constant),
+                ("Timeout".to_string(), ElementType::Constant),
+                ("_repository".to_string(), ElementType::Variable),
+                ("_fallback".to_string(), ElementType::Variable),
+                ("Name".to_string(), ElementType::Variable),
+            ]
+        );
+    }
+
+    fn visibilities(
+        code: &str,
+        file_name: &str,
+        language: SupportedLanguage,
+    ) -> HashMap<String, Visibility> {
+        let mut parser = Parser::new();
+        let tree = parser.parse(code, language).unwrap();
+        extract_elements_from_tree(&tree, code, file_name, file_name, language, "/repo")
+            .into_iter()
+            .filter(|e| e.element_type != ElementType::File)
+            .map(|e| (e.name.clone(), e.visibility()))
+            .collect()
+    }
+
+    #[test]
+    fn test_python_visibility() {
+        let code = r#"
+def _helper():
+    pass
+
+def run():
+    pass
+
+class Service:
+    def __init__(self):
+        pass
+"#;
+        let vis = visibilities(code, "test.py", SupportedLanguage::Python);
+        assert_eq!(vis["_helper"], Visibility::Private);
+        assert_eq!(vis["run"], Visibility::Public);
+        assert_eq!(vis["__init__"], Visibility::Public);
+    }
+
+    #[test]
+    #[cfg(feature = "lang-rust")]
+    fn test_rust_visibility() {
+        let code = r#"
+fn helper() {}
+
+pub fn run() {}
+
+pub(crate) struct Config;
+
+impl Config {
+    fn load() -> Self { Config }
+}
+
+impl Default for Config {
+    fn default() -> Self { Config }
+}
+"#;
+        let vis = visibilities(code, "lib.rs", SupportedLanguage::Rust);
+        assert_eq!(vis["helper"], Visibility::Private);
+        assert_eq!(vis["run"], Visibility::Public);
+        assert_eq!(vis["Config"], Visibility::Public);
+        assert_eq!(vis["load"], Visibility::Private);
+        assert_eq!(vis["default"], Visibility::Public);
+    }
+
+    #[test]
+    #[cfg(feature = "lang-java")]
+    fn test_java_visibility() {
+        let code = r#"
+public class UserService {
+    public void process() {}
+    protected void validate() {}
+    private int calculate() { return 0; }
+    void reset() {}
+}
+"#;
+        let vis = visibilities(code, "UserService.java", SupportedLanguage::Java);
+        assert_eq!(vis["UserService"], Visibility::Public);
+        assert_eq!(vis["process"], Visibility::Public);
+        assert_eq!(vis["validate"], Visibility::Protected);
+        assert_eq!(vis["calculate"], Visibility::Private);
+        assert_eq!(vis["reset"], Visibility::Package);
+    }
+
+    #[test]
+    #[cfg(feature = "lang-go")]
+    fn test_go_visibility() {
+        let code = r#"
+package main
+
+func Hello() {}
+
+func helper() {}
+"#;
+        let vis = visibilities(code, "main.go", SupportedLanguage::Go);
+        assert_eq!(vis["Hello"], Visibility::Public);
+        assert_eq!(vis["helper"], Visibility::Package);
+    }
+
+    #[test]
+    #[cfg(feature = "lang-csharp")]
+    fn test_csharp_visibility() {
+        let code = r#"
+namespace Shop
+{
+    class Cart
+    {
+        public void Add() {}
+        protected internal void Merge() {}
+        protected void Validate() {}
+        internal void Sync() {}
+        private void Log() {}
+        void Reset() {}
+    }
+
+    public interface ICart
+    {
+        void Clear();
+    }
+}
+"#;
+        let vis = visibilities(code, "Cart.cs", SupportedLanguage::CSharp);
+        assert_eq!(vis["Cart"], Visibility::Public);
+        assert_eq!(vis["Add"], Visibility::Public);
+        assert_eq!(vis["Merge"], Visibility::Public);
+        assert_eq!(vis["Validate"], Visibility::Protected);
+        assert_eq!(vis["Sync"], Visibility::Public);
+        assert_eq!(vis["Log"], Visibility::Private);
+        assert_eq!(vis["Reset"], Visibility::Private);
+        assert_eq!(vis["Clear"], Visibility::Public);
+    }
+
     #[test]
     fn test_index_single_file() {
         use std::io::Write;
@@ -778,7 +2337,7 @@ public class UserService {
 
         let file_str = file_path.to_string_lossy().to_string();
         let repo_root = dir.path().to_string_lossy().to_string();
-        let elements = index_single_file(&file_str, &repo_root).unwrap();
+        let elements = index_single_file(&file_str, &repo_root, &Default::default()).unwrap();
 
         // Should have a File element + a Function element
         assert!(
@@ -798,4 +2357,184 @@ public class UserService {
         assert!(types.contains(&ElementType::File));
         assert!(types.contains(&ElementType::Function));
     }
+
+    #[test]
+    fn test_content_only_files_are_chunked() {
+        let dir = tempfile::tempdir().unwrap();
+        let file_path = dir.path().join("Main.kt");
+        let mut code = String::from("fun main() {\n");
+        for i in 0..CHUNK_LINES {
+            code.push_str(&format!("    println({i})\n"));
+        }
+        code.push_str("}\n");
+        std::fs::write(&file_path, &code).unwrap();
+
+        let elements = index_single_file(
+            &file_path.to_string_lossy(),
+            &dir.path().to_string_lossy(),
+            &Default::default(),
+        )
+        .unwrap();
+        let found: Vec<(&str, ElementType, usize, usize)> = elements
+            .iter()
+            .map(|e| (e.name.as_str(), e.element_type, e.start_line, e.end_line))
+            .collect();
+        let last = CHUNK_LINES + 2;
+        let second = format!("Main.kt:{}-{last}", CHUNK_LINES + 1);
+        assert_eq!(
+            found,
+            vec![
+                ("Main.kt", ElementType::File, 1, last),
+                ("Main.kt:1-40", ElementType::Chunk, 1, CHUNK_LINES),
+                (second.as_str(), ElementType::Chunk, CHUNK_LINES + 1, last),
+            ]
+        );
+        assert!(elements[1].code.starts_with("fun main() {\n"));
+        assert_eq!(elements[2].code.as_str(), "    println(39)\n}\n");
+        assert!(
+            elements
+                .iter()
+                .all(|e| { e.language == "kotlin" && e.metadata.contains_key(CONTENT_ONLY_KEY) })
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "lang-javascript")]
+    fn test_default_excludes_without_gitignore() {
+        let dir = tempfile::tempdir().unwrap();
+        let write = |relative: &str, contents: &str| {
+            let path = dir.path().join(relative);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, contents).unwrap();
+        };
+        write("app.py", "def app():\n    pass\n");
+        write("node_modules/left-pad/index.js", "function leftPad() {}\n");
+        write(
+            "web/node_modules/react/index.js",
+            "function createElement() {}\n",
+        );
+        write("venv/lib/site.py", "def site_helper():\n    pass\n");
+        // Nested excluded and hidden directories are not counted again.
+        write(
+            "node_modules/left-pad/node_modules/dep/index.js",
+            "function dep() {}\n",
+        );
+        write("venv/.cache/stale.py", "def stale():\n    pass\n");
+
+        let root = dir.path().to_string_lossy().to_string();
+        let (elements, summary) = walk_and_index_with_summary(&root, &Default::default());
+        assert!(
+            elements
+                .iter()
+                .all(|e| !e.relative_path.contains("node_modules")
+                    && !e.relative_path.starts_with("venv/")),
+            "{:?}",
+            elements
+                .iter()
+                .map(|e| &e.relative_path)
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(summary.files, 1);
+        assert_eq!(summary.excluded_files, 3);
+        assert_eq!(
+            summary.excluded_paths,
+            vec!["node_modules", "venv", "web/node_modules"]
+        );
+
+        write(
+            ".happy/agent.toml",
+            "[index]\nunexclude = [\"node_modules/left-pad\"]\n",
+        );
+        let elements = walk_and_index(&root, &Default::default());
+        assert!(elements.iter().any(|e| e.name == "leftPad"));
+        assert!(elements.iter().all(|e| e.name != "createElement"));
+        assert!(elements.iter().all(|e| e.name != "site_helper"));
+    }
+
+    #[test]
+    #[cfg(feature = "lang-javascript")]
+    fn test_large_minified_and_generated_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let write = |relative: &str, contents: &str| {
+            std::fs::write(dir.path().join(relative), contents).unwrap();
+        };
+        write("app.py", "def app():\n    pass\n");
+        write(
+            "bundle.js",
+            &format!("function bundled() {{}}\n{}", "// padding\n".repeat(300)),
+        );
+        write(
+            "vendor.min.js",
+            &format!("function minified(){{{}}}\n", "a();".repeat(300)),
+        );
+        write(
+            "api_pb2.py",
+            "# Generated by the protocol buffer compiler.  DO NOT EDIT!\ndef proto():\n    pass\n",
+        );
+
+        let root = dir.path().to_string_lossy().to_string();
+        let options = IndexOptions {
+            max_file_size: 2048,
+            ..IndexOptions::default()
+        };
+        let (elements, summary) = walk_and_index_with_summary(&root, &options);
+        let names: Vec<&str> = elements.iter().map(|e| e.name.as_str()).collect();
+        assert!(names.contains(&"app"));
+        assert!(names.contains(&"api_pb2.py"));
+        assert!(!names.contains(&"proto"));
+        assert!(
+            elements
+                .iter()
+                .all(|e| e.relative_path != "bundle.js" && e.relative_path != "vendor.min.js"),
+            "{names:?}"
+        );
+        assert_eq!(
+            summary.skipped_paths,
+            vec![
+                ("api_pb2.py".to_string(), SkipReason::Generated),
+                ("bundle.js".to_string(), SkipReason::TooLarge),
+                ("vendor.min.js".to_string(), SkipReason::Minified),
+            ]
+        );
+
+        let pb2 = dir.path().join("api_pb2.py").to_string_lossy().to_string();
+        assert_eq!(index_single_file(&pb2, &root, &options).unwrap().len(), 1);
+        let include = IndexOptions {
+            include_generated: true,
+            ..options.clone()
+        };
+        let elements = index_single_file(&pb2, &root, &include).unwrap();
+        assert!(elements.iter().any(|e| e.name == "proto"));
+        let bundle = dir.path().join("bundle.js").to_string_lossy().to_string();
+        assert!(index_single_file(&bundle, &root, &options).is_none());
+    }
+
+    #[test]
+    fn test_skips_non_utf8_files_unless_lossy() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("ok.py"), "def ok():\n    pass\n").unwrap();
+        std::fs::write(
+            dir.path().join("latin1.py"),
+            b"# caf\xe9\ndef latin():\n    pass\n",
+        )
+        .unwrap();
+        let root = dir.path().to_string_lossy().to_string();
+
+        let (elements, summary) = walk_and_index_with_summary(&root, &IndexOptions::default());
+        assert!(elements.iter().any(|e| e.name == "ok"));
+        assert!(!elements.iter().any(|e| e.name == "latin"));
+        assert_eq!(summary.files, 2);
+        assert_eq!(
+            summary.skipped_paths,
+            vec![("latin1.py".to_string(), SkipReason::NotUtf8)]
+        );
+
+        let lossy = IndexOptions {
+            lossy_utf8: true,
+            ..IndexOptions::default()
+        };
+        let (elements, summary) = walk_and_index_with_summary(&root, &lossy);
+        assert!(elements.iter().any(|e| e.name == "latin"));
+        assert!(summary.skipped_paths.is_empty());
+    }
 }
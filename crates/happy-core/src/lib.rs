@@ -1,11 +1,22 @@
+pub mod agent_toml;
+pub mod bookmarks;
+pub mod events;
 pub mod global_index;
 pub mod graph;
+pub mod grounding;
 pub mod indexer;
+pub mod logging;
 pub mod parser;
+pub mod permissions;
+pub mod redact;
 pub mod store;
+pub mod summary;
+pub mod tool_examples;
+pub mod tool_spec;
 pub mod utils;
 pub mod vector;
 pub mod watcher;
+pub mod workspace;
 
 #[cfg(feature = "python")]
 mod py;
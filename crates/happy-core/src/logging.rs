@@ -0,0 +1,281 @@
+use std::io;
+use std::path::{Component, Path, PathBuf};
+
+use serde::Deserialize;
+
+use crate::agent_toml::AgentToml;
+
+/// Default JSON log file, relative to the repo root.
+pub const DEFAULT_LOG_FILE: &str = ".happy/logs/happy.log";
+
+/// The `[logging]` table of `.happy/agent.toml`.
+///
+/// ```toml
+/// [logging]
+/// filters = "happy_core::graph=debug,happy_core::indexer=info"
+/// file = ".happy/logs/happy.log"
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct LoggingConfig {
+    /// Comma-separated `target=level` directives (or a bare level).
+    pub filters: Option<String>,
+    /// JSON log file, relative to the repo root and inside its `.happy`
+    /// directory. A cloned repo's config must not make the agent append to
+    /// files elsewhere on disk.
+    pub file: Option<PathBuf>,
+}
+
+/// Log levels accepted in filter directives.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogLevel {
+    Off,
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl LogLevel {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Off => "off",
+            Self::Error => "error",
+            Self::Warn => "warn",
+            Self::Info => "info",
+            Self::Debug => "debug",
+            Self::Trace => "trace",
+        }
+    }
+
+    fn parse(s: &str) -> Option<Self> {
+        Some(match s.to_ascii_lowercase().as_str() {
+            "off" => Self::Off,
+            "error" => Self::Error,
+            "warn" | "warning" => Self::Warn,
+            "info" => Self::Info,
+            "debug" => Self::Debug,
+            "trace" => Self::Trace,
+            _ => return None,
+        })
+    }
+}
+
+/// One `target=level` directive. `target` is `None` for a bare level, which
+/// applies to every module.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogFilter {
+    pub target: Option<String>,
+    pub level: LogLevel,
+}
+
+impl LogFilter {
+    /// Render in `RUST_LOG` / `EnvFilter` syntax.
+    pub fn directive(&self) -> String {
+        match &self.target {
+            Some(target) => format!("{target}={}", self.level.as_str()),
+            None => self.level.as_str().to_string(),
+        }
+    }
+}
+
+/// Parse a comma-separated filter string such as
+/// `"happy_core::graph=debug,happy_core::indexer=info"`.
+///
+/// Empty entries are ignored. Errors name the offending directive.
+pub fn parse_filters(filters: &str) -> Result<Vec<LogFilter>, String> {
+    filters
+        .split(',')
+        .map(str::trim)
+        .filter(|directive| !directive.is_empty())
+        .map(|directive| {
+            let (target, level) = match directive.split_once('=') {
+                Some((target, level)) => (Some(target.trim()), level.trim()),
+                None => (None, directive),
+            };
+            let valid_target = target.is_none_or(|t| {
+                !t.is_empty()
+                    && t.split("::").all(|part| {
+                        !part.is_empty()
+                            && part.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+                    })
+            });
+            match (valid_target, LogLevel::parse(level)) {
+                (true, Some(level)) => Ok(LogFilter {
+                    target: target.map(str::to_string),
+                    level,
+                }),
+                _ => Err(format!("invalid log filter directive '{directive}'")),
+            }
+        })
+        .collect()
+}
+
+impl LoggingConfig {
+    /// Load `[logging]` from `<repo_root>/.happy/agent.toml`.
+    ///
+    /// Returns the default (no filters, no file) when the file or table is
+    /// missing.
+    pub fn load(repo_root: &Path) -> io::Result<Self> {
+        let section = AgentToml::load(repo_root)?.section("logging")?;
+        let config = section.unwrap_or_default();
+        if let Some(filters) = &config.filters {
+            parse_filters(filters).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        }
+        if let Some(file) = &config.file
+            && !is_under_happy_dir(file)
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "log file {} must be a relative path inside .happy/",
+                    file.display()
+                ),
+            ));
+        }
+        Ok(config)
+    }
+
+    /// Build the full directive string: `base`, then the `-v` verbosity for
+    /// `happy_core` (1 = debug, 2+ = trace), then the configured filters, so
+    /// more specific config entries refine the defaults.
+    pub fn directives(&self, base: &str, verbosity: u8) -> Result<String, String> {
+        let mut directives: Vec<String> = parse_filters(base)?
+            .iter()
+            .map(LogFilter::directive)
+            .collect();
+        match verbosity {
+            0 => {}
+            1 => directives.push("happy_core=debug".to_string()),
+            _ => directives.push("happy_core=trace".to_string()),
+        }
+        if let Some(filters) = &self.filters {
+            directives.extend(parse_filters(filters)?.iter().map(LogFilter::directive));
+        }
+        Ok(directives.join(","))
+    }
+
+    /// The JSON log file to write, resolved against `repo_root`.
+    ///
+    /// `None` when no file is configured, or when it would land outside
+    /// `<repo_root>/.happy/`, including through a symlinked directory.
+    pub fn log_file(&self, repo_root: &Path) -> Option<PathBuf> {
+        let file = self.file.as_ref()?;
+        if !is_under_happy_dir(file) {
+            return None;
+        }
+        let mut dir = repo_root.to_path_buf();
+        for component in file.parent()?.components() {
+            dir.push(component);
+            if std::fs::symlink_metadata(&dir).is_ok_and(|meta| meta.file_type().is_symlink()) {
+                return None;
+            }
+        }
+        Some(repo_root.join(file))
+    }
+}
+
+/// Whether `file` is a plain relative path below `.happy/`, with no `..`.
+fn is_under_happy_dir(file: &Path) -> bool {
+    let mut components = file.components();
+    components.next() == Some(Component::Normal(".happy".as_ref()))
+        && components.clone().next().is_some()
+        && components.all(|component| matches!(component, Component::Normal(_)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_filters() {
+        let filters =
+            parse_filters("happy_core::graph=debug, happy_core::indexer=INFO,warn").unwrap();
+        assert_eq!(
+            filters,
+            vec![
+                LogFilter {
+                    target: Some("happy_core::graph".to_string()),
+                    level: LogLevel::Debug,
+                },
+                LogFilter {
+                    target: Some("happy_core::indexer".to_string()),
+                    level: LogLevel::Info,
+                },
+                LogFilter {
+                    target: None,
+                    level: LogLevel::Warn,
+                },
+            ]
+        );
+
+        assert!(parse_filters("").unwrap().is_empty());
+        assert!(parse_filters("happy_core=loud").is_err());
+        assert!(parse_filters("happy core=debug").is_err());
+        assert!(parse_filters("=debug").is_err());
+    }
+
+    #[test]
+    fn test_load_and_directives() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join(".happy")).unwrap();
+        std::fs::write(
+            dir.path().join(".happy/agent.toml"),
+            "[logging]\nfilters = \"happy_core::graph=debug\"\nfile = \".happy/logs/happy.log\"\n",
+        )
+        .unwrap();
+
+        let config = LoggingConfig::load(dir.path()).unwrap();
+        assert_eq!(
+            config.directives("codex_core=info", 1).unwrap(),
+            "codex_core=info,happy_core=debug,happy_core::graph=debug"
+        );
+        assert_eq!(
+            config.log_file(dir.path()),
+            Some(dir.path().join(DEFAULT_LOG_FILE))
+        );
+
+        let missing = tempfile::tempdir().unwrap();
+        assert_eq!(
+            LoggingConfig::load(missing.path()).unwrap(),
+            LoggingConfig::default()
+        );
+    }
+
+    #[test]
+    fn test_log_file_stays_inside_happy_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join(".happy")).unwrap();
+        for file in [
+            "/tmp/happy.log",
+            "../happy.log",
+            ".happy/../../happy.log",
+            "logs/happy.log",
+            ".happy",
+        ] {
+            std::fs::write(
+                dir.path().join(".happy/agent.toml"),
+                format!("[logging]\nfile = \"{file}\"\n"),
+            )
+            .unwrap();
+            assert!(LoggingConfig::load(dir.path()).is_err(), "{file}");
+            let config = LoggingConfig {
+                file: Some(PathBuf::from(file)),
+                ..LoggingConfig::default()
+            };
+            assert_eq!(config.log_file(dir.path()), None, "{file}");
+        }
+
+        #[cfg(unix)]
+        {
+            let outside = tempfile::tempdir().unwrap();
+            std::os::unix::fs::symlink(outside.path(), dir.path().join(".happy/logs")).unwrap();
+            let config = LoggingConfig {
+                file: Some(PathBuf::from(DEFAULT_LOG_FILE)),
+                ..LoggingConfig::default()
+            };
+            assert_eq!(config.log_file(dir.path()), None);
+        }
+    }
+}
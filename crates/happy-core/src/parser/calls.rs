@@ -127,8 +127,9 @@ fn collect_scopes(node: &Node, code_bytes: &[u8], scopes: &mut Vec<ScopeInfo>) {
         | "function_declaration"    // JS/TS, Go
         | "function_item"           // Rust
         | "method_definition"       // JS/TS class methods
-        | "method_declaration"      // Java, Go
-        | "constructor_declaration" // Java
+        | "method_declaration"      // Java, Go, C#
+        | "constructor_declaration" // Java, C#
+        | "protocol_function_declaration" // Swift
         => {
             if let Some(name_node) = node.child_by_field_name("name") {
                 let name = name_node.utf8_text(code_bytes).unwrap_or_default().to_string();
@@ -142,12 +143,15 @@ fn collect_scopes(node: &Node, code_bytes: &[u8], scopes: &mut Vec<ScopeInfo>) {
         }
         // Class/type scopes (all languages)
         "class_definition"        // Python
-        | "class_declaration"     // JS/TS, Java
+        | "class_declaration"     // JS/TS, Java, C#
         | "struct_item"           // Rust
         | "impl_item"             // Rust
         | "enum_item"             // Rust
-        | "interface_declaration" // Java, TS
-        | "enum_declaration"      // Java
+        | "interface_declaration" // Java, TS, C#
+        | "enum_declaration"      // Java, C#
+        | "struct_declaration"    // C#
+        | "record_declaration"    // C#, Java
+        | "protocol_declaration"  // Swift
         => {
             // Try "name" field first, then "type" (Rust impl_item uses "type")
             let name_node = node.child_by_field_name("name")
@@ -181,7 +185,8 @@ fn extract_calls_recursive(
     let is_call = match language {
         SupportedLanguage::Python => node.kind() == "call",
         SupportedLanguage::Java => node.kind() == "method_invocation",
-        _ => node.kind() == "call_expression", // JS/TS/Rust/Go/C/C++
+        SupportedLanguage::CSharp => node.kind() == "invocation_expression",
+        _ => node.kind() == "call_expression", // JS/TS/Rust/Go/C/C++/Swift
     };
 
     if is_call {
@@ -206,22 +211,28 @@ fn process_call_node(
 ) -> Option<CallInfo> {
     let code_bytes = code.as_bytes();
 
-    // Java's method_invocation has no `function` field — extract directly from node
-    let (call_name, base_object, call_type) = if language == SupportedLanguage::Java {
-        extract_java_call(node, code_bytes)?
-    } else {
-        let function_node = node.child_by_field_name("function")?;
-        match language {
-            SupportedLanguage::Python => extract_python_call(&function_node, code_bytes)?,
-            SupportedLanguage::JavaScript
-            | SupportedLanguage::TypeScript
-            | SupportedLanguage::Tsx => extract_js_ts_call(&function_node, code_bytes)?,
-            SupportedLanguage::Rust => extract_rust_call(&function_node, code_bytes)?,
-            SupportedLanguage::Go => extract_go_call(&function_node, code_bytes)?,
-            SupportedLanguage::C | SupportedLanguage::Cpp => {
-                extract_c_cpp_call(&function_node, code_bytes)?
+    // Java's method_invocation and Swift's call_expression have no `function`
+    // field — extract directly from node
+    let (call_name, base_object, call_type) = match language {
+        SupportedLanguage::Java => extract_java_call(node, code_bytes)?,
+        SupportedLanguage::Swift => extract_swift_call(node, code_bytes)?,
+        _ => {
+            let function_node = node.child_by_field_name("function")?;
+            match language {
+                SupportedLanguage::Python => extract_python_call(&function_node, code_bytes)?,
+                SupportedLanguage::JavaScript
+                | SupportedLanguage::TypeScript
+                | SupportedLanguage::Tsx => extract_js_ts_call(&function_node, code_bytes)?,
+                SupportedLanguage::Rust => extract_rust_call(&function_node, code_bytes)?,
+                SupportedLanguage::Go => extract_go_call(&function_node, code_bytes)?,
+                SupportedLanguage::C | SupportedLanguage::Cpp => {
+                    extract_c_cpp_call(&function_node, code_bytes)?
+                }
+                SupportedLanguage::CSharp => extract_csharp_call(&function_node, code_bytes)?,
+                // Not parsed.
+                SupportedLanguage::Kotlin => return None,
+                SupportedLanguage::Java | SupportedLanguage::Swift => unreachable!(),
             }
-            SupportedLanguage::Java => unreachable!(),
         }
     };
 
@@ -357,6 +368,56 @@ fn extract_java_call(node: &Node, code_bytes: &[u8]) -> Option<(String, Option<S
     Some((name, base_object, call_type))
 }
 
+/// C#: `invocation_expression` → `function` field → `identifier` |
+/// `generic_name` | `member_access_expression`
+fn extract_csharp_call(
+    function_node: &Node,
+    code_bytes: &[u8],
+) -> Option<(String, Option<String>, CallType)> {
+    // `Map<User>` is called as `Map`
+    let name_of = |node: Node| -> Option<String> {
+        let node = match node.kind() {
+            "generic_name" => node.named_child(0)?,
+            _ => node,
+        };
+        Some(node.utf8_text(code_bytes).ok()?.to_string())
+    };
+    match function_node.kind() {
+        "identifier" | "generic_name" => Some((name_of(*function_node)?, None, CallType::Simple)),
+        "member_access_expression" => {
+            let expression_node = function_node.child_by_field_name("expression")?;
+            let name_node = function_node.child_by_field_name("name")?;
+            let base = expression_node.utf8_text(code_bytes).ok()?.to_string();
+            Some((name_of(name_node)?, Some(base), CallType::Attribute))
+        }
+        _ => None,
+    }
+}
+
+/// Swift: `call_expression` → callee as first child → `simple_identifier` | `navigation_expression`
+fn extract_swift_call(
+    node: &Node,
+    code_bytes: &[u8],
+) -> Option<(String, Option<String>, CallType)> {
+    let callee = node.named_child(0)?;
+    match callee.kind() {
+        "simple_identifier" => {
+            let name = callee.utf8_text(code_bytes).ok()?.to_string();
+            Some((name, None, CallType::Simple))
+        }
+        "navigation_expression" => {
+            let target_node = callee.child_by_field_name("target")?;
+            let name_node = callee
+                .child_by_field_name("suffix")?
+                .child_by_field_name("suffix")?;
+            let base = target_node.utf8_text(code_bytes).ok()?.to_string();
+            let name = name_node.utf8_text(code_bytes).ok()?.to_string();
+            Some((name, Some(base), CallType::Attribute))
+        }
+        _ => None,
+    }
+}
+
 /// C/C++: `call_expression` → `function` field → `identifier` | `field_expression` | `qualified_identifier`
 fn extract_c_cpp_call(
     function_node: &Node,
@@ -491,6 +552,7 @@ z = custom_func()
     // ── JavaScript tests ──────────────────────────────────────
 
     #[test]
+    #[cfg(feature = "lang-javascript")]
     fn test_extract_js_calls() {
         let code = r#"
 function main() {
@@ -529,6 +591,7 @@ function main() {
     // ── TypeScript tests ──────────────────────────────────────
 
     #[test]
+    #[cfg(feature = "lang-typescript")]
     fn test_extract_ts_calls() {
         let code = r#"
 async function fetchData() {
@@ -560,6 +623,7 @@ async function fetchData() {
     // ── Rust tests ────────────────────────────────────────────
 
     #[test]
+    #[cfg(feature = "lang-rust")]
     fn test_extract_rust_calls() {
         let code = r#"
 fn main() {
@@ -597,6 +661,7 @@ fn main() {
     // ── Go tests ──────────────────────────────────────────────
 
     #[test]
+    #[cfg(feature = "lang-go")]
     fn test_extract_go_calls() {
         let code = r#"
 package main
@@ -631,6 +696,7 @@ func main() {
     // ── Java tests ────────────────────────────────────────────
 
     #[test]
+    #[cfg(feature = "lang-java")]
     fn test_extract_java_calls() {
         let code = r#"
 public class Main {
@@ -666,9 +732,85 @@ public class Main {
         assert_eq!(add_call.base_object.as_deref(), Some("list"));
     }
 
+    // ── C# tests ──────────────────────────────────────────────
+
+    #[test]
+    #[cfg(feature = "lang-csharp")]
+    fn test_extract_csharp_calls() {
+        let code = r#"
+namespace Shop
+{
+    public class OrderService
+    {
+        public void Place(Order order)
+        {
+            Validate(order);
+            _repository.Save(order);
+            var items = order.Lines.Select<Line>(l => l);
+            Console.WriteLine("placed");
+        }
+    }
+}
+"#;
+        let mut parser = Parser::new();
+        let tree = parser.parse(code, SupportedLanguage::CSharp).unwrap();
+        let calls = extract_calls(&tree, code, SupportedLanguage::CSharp);
+
+        let found: Vec<(&str, Option<&str>)> = calls
+            .iter()
+            .map(|c| (c.call_name.as_str(), c.base_object.as_deref()))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("Validate", None),
+                ("Save", Some("_repository")),
+                ("Select", Some("order.Lines")),
+                ("WriteLine", Some("Console")),
+            ]
+        );
+        assert_eq!(calls[0].scope_id.as_deref(), Some("function::Place"));
+    }
+
+    // ── Swift tests ───────────────────────────────────────────
+
+    #[test]
+    #[cfg(feature = "lang-swift")]
+    fn test_extract_swift_calls() {
+        let code = r#"
+class OrderService {
+    func place(order: Order) {
+        validate(order)
+        repository.save(order)
+        let total = order.lines.map { $0.price }
+        print("placed")
+    }
+}
+"#;
+        let mut parser = Parser::new();
+        let tree = parser.parse(code, SupportedLanguage::Swift).unwrap();
+        let calls = extract_calls(&tree, code, SupportedLanguage::Swift);
+
+        let found: Vec<(&str, Option<&str>)> = calls
+            .iter()
+            .map(|c| (c.call_name.as_str(), c.base_object.as_deref()))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("validate", None),
+                ("save", Some("repository")),
+                ("map", Some("order.lines")),
+                ("print", None),
+            ]
+        );
+        assert_eq!(calls[0].scope_id.as_deref(), Some("function::place"));
+    }
+
     // ── C++ tests ─────────────────────────────────────────────
 
     #[test]
+    #[cfg(feature = "lang-cpp")]
     fn test_extract_cpp_calls() {
         let code = r#"
 #include <vector>
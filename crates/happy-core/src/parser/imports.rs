@@ -11,6 +11,14 @@ pub struct ImportInfo {
     pub end_line: usize,
 }
 
+impl ImportInfo {
+    /// The module as written, with a relative import's leading dots
+    /// (`..models`, `.`).
+    pub fn written_module(&self) -> String {
+        format!("{}{}", ".".repeat(self.level as usize), self.module)
+    }
+}
+
 /// Extract import statements from a parsed AST, dispatching by language.
 pub fn extract_imports(tree: &Tree, code: &str, language: SupportedLanguage) -> Vec<ImportInfo> {
     let root = tree.root_node();
@@ -26,6 +34,10 @@ pub fn extract_imports(tree: &Tree, code: &str, language: SupportedLanguage) ->
         SupportedLanguage::Cpp | SupportedLanguage::C => {
             collect_c_cpp_imports(&root, code, &mut imports)
         }
+        SupportedLanguage::CSharp => collect_csharp_imports(&root, code, &mut imports),
+        SupportedLanguage::Swift => collect_swift_imports(&root, code, &mut imports),
+        // Not parsed.
+        SupportedLanguage::Kotlin => {}
     }
     imports
 }
@@ -93,6 +105,9 @@ fn collect_python_imports(node: &Node, code: &str, imports: &mut Vec<ImportInfo>
                 if !seen_import_keyword {
                     match kind {
                         "relative_import" => {
+                            // `module_name` includes the dots; keep only the
+                            // dotted name after them, if any.
+                            module.clear();
                             let mut rc = child.walk();
                             for rchild in child.children(&mut rc) {
                                 match rchild.kind() {
@@ -271,6 +286,17 @@ fn collect_js_import_names(node: &Node, code_bytes: &[u8], names: &mut Vec<Strin
 // ── Rust ───────────────────────────────────────────────────────
 
 fn collect_rust_imports(node: &Node, code: &str, imports: &mut Vec<ImportInfo>) {
+    collect_rust_imports_in(node, code, &mut Vec::new(), imports);
+}
+
+/// `inline_mods` is the path of the inline `mod name { ... }` blocks being
+/// walked, so paths written inside them can be made relative to the file.
+fn collect_rust_imports_in(
+    node: &Node,
+    code: &str,
+    inline_mods: &mut Vec<String>,
+    imports: &mut Vec<ImportInfo>,
+) {
     let code_bytes = code.as_bytes();
 
     match node.kind() {
@@ -281,7 +307,7 @@ fn collect_rust_imports(node: &Node, code: &str, imports: &mut Vec<ImportInfo>)
             let path = extract_rust_use_path(node, code_bytes, &mut names);
 
             imports.push(ImportInfo {
-                module: path,
+                module: relative_to_file(&path, inline_mods),
                 names,
                 level: 0,
                 start_line: node.start_position().row + 1,
@@ -296,25 +322,73 @@ fn collect_rust_imports(node: &Node, code: &str, imports: &mut Vec<ImportInfo>)
                     .unwrap_or_default()
                     .to_string();
                 // Only count `mod foo;` (no body), not `mod foo { ... }`
-                let has_body = node.child_by_field_name("body").is_some();
-                if !has_body {
-                    imports.push(ImportInfo {
-                        module: name.clone(),
-                        names: vec![name],
-                        level: 0,
-                        start_line: node.start_position().row + 1,
-                        end_line: node.end_position().row + 1,
-                    });
+                match node.child_by_field_name("body") {
+                    None => {
+                        let module = if inline_mods.is_empty() {
+                            name.clone()
+                        } else {
+                            format!("{}::{name}", inline_mods.join("::"))
+                        };
+                        imports.push(ImportInfo {
+                            module,
+                            names: vec![name],
+                            level: 0,
+                            start_line: node.start_position().row + 1,
+                            end_line: node.end_position().row + 1,
+                        });
+                    }
+                    Some(body) => {
+                        inline_mods.push(name);
+                        collect_rust_imports_in(&body, code, inline_mods, imports);
+                        inline_mods.pop();
+                    }
                 }
             }
         }
         _ => {
             let mut cursor = node.walk();
             for child in node.children(&mut cursor) {
-                collect_rust_imports(&child, code, imports);
+                collect_rust_imports_in(&child, code, inline_mods, imports);
+            }
+        }
+    }
+}
+
+/// Rewrite a `self::`/`super::` path written inside inline `mod` blocks so
+/// it is relative to the file's module: `super::Config` inside
+/// `mod tests { ... }` becomes `self::Config`.
+fn relative_to_file(path: &str, inline_mods: &[String]) -> String {
+    if inline_mods.is_empty() {
+        return path.to_string();
+    }
+    let mut scope: Vec<&str> = inline_mods.iter().map(String::as_str).collect();
+    let mut file_supers = 0;
+    let mut segments = path.split("::").peekable();
+    let mut relative = false;
+    while let Some(&segment) = segments.peek() {
+        match segment {
+            "self" => {}
+            "super" => {
+                if scope.pop().is_none() {
+                    file_supers += 1;
+                }
             }
+            _ => break,
         }
+        relative = true;
+        segments.next();
+    }
+    if !relative {
+        return path.to_string();
     }
+    let mut parts = if file_supers > 0 {
+        vec!["super"; file_supers]
+    } else {
+        vec!["self"]
+    };
+    parts.extend(scope);
+    parts.extend(segments);
+    parts.join("::")
 }
 
 fn extract_rust_use_path(node: &Node, code_bytes: &[u8], names: &mut Vec<String>) -> String {
@@ -325,7 +399,8 @@ fn extract_rust_use_path(node: &Node, code_bytes: &[u8], names: &mut Vec<String>
         match node.kind() {
             "scoped_identifier" | "scoped_use_list" => {
                 if let Some(path) = node.child_by_field_name("path") {
-                    walk_use(&path, code_bytes, parts, names);
+                    // Segments before the last one are modules, not imported names
+                    walk_use(&path, code_bytes, parts, &mut Vec::new());
                 }
                 if let Some(name) = node.child_by_field_name("name") {
                     let text = name.utf8_text(code_bytes).unwrap_or_default().to_string();
@@ -362,8 +437,10 @@ fn extract_rust_use_path(node: &Node, code_bytes: &[u8], names: &mut Vec<String>
                 }
             }
             "use_wildcard" => {
-                if let Some(path) = node.child_by_field_name("path") {
-                    walk_use(&path, code_bytes, parts, names);
+                // `path::*`; the path is not a named field
+                let mut cursor = node.walk();
+                for child in node.named_children(&mut cursor) {
+                    walk_use(&child, code_bytes, parts, &mut Vec::new());
                 }
                 names.push("*".to_string());
             }
@@ -520,6 +597,103 @@ fn collect_java_imports(node: &Node, code: &str, imports: &mut Vec<ImportInfo>)
     }
 }
 
+// ── C# ─────────────────────────────────────────────────────────
+
+fn collect_csharp_imports(node: &Node, code: &str, imports: &mut Vec<ImportInfo>) {
+    let code_bytes = code.as_bytes();
+
+    match node.kind() {
+        // using System.Text;
+        // global using static System.Math;
+        // using Json = Newtonsoft.Json.JsonConvert;
+        "using_directive" => {
+            let full_text = node.utf8_text(code_bytes).unwrap_or_default();
+            let mut rest = full_text.trim().trim_end_matches(';').trim();
+            let mut is_static = false;
+            while let Some((word, tail)) = rest.split_once(char::is_whitespace) {
+                match word {
+                    "global" | "using" | "unsafe" => {}
+                    "static" => is_static = true,
+                    _ => break,
+                }
+                rest = tail.trim_start();
+            }
+            let (alias, target) = match rest.split_once('=') {
+                Some((alias, target)) => (Some(alias.trim()), target.trim()),
+                None => (None, rest),
+            };
+            let module: String = target
+                .split('<')
+                .next()
+                .unwrap_or(target)
+                .split_whitespace()
+                .collect();
+            // A namespace brings in everything it declares; a static or
+            // aliased type is the one name imported.
+            let names = if is_static || alias.is_some() {
+                vec![module.rsplit('.').next().unwrap_or(&module).to_string()]
+            } else {
+                vec![]
+            };
+            imports.push(ImportInfo {
+                module,
+                names,
+                level: 0,
+                start_line: node.start_position().row + 1,
+                end_line: node.end_position().row + 1,
+            });
+        }
+        _ => {
+            let mut cursor = node.walk();
+            for child in node.children(&mut cursor) {
+                collect_csharp_imports(&child, code, imports);
+            }
+        }
+    }
+}
+
+// ── Swift ──────────────────────────────────────────────────────
+
+fn collect_swift_imports(node: &Node, code: &str, imports: &mut Vec<ImportInfo>) {
+    let code_bytes = code.as_bytes();
+
+    match node.kind() {
+        // import Foundation
+        // @testable import MyApp
+        // import struct Foundation.Date
+        "import_declaration" => {
+            let full_text = node.utf8_text(code_bytes).unwrap_or_default();
+            let mut words = full_text
+                .split_whitespace()
+                .skip_while(|word| *word != "import")
+                .skip(1);
+            // A module brings in everything it declares; `import struct`
+            // and the like name the one declaration imported.
+            let (module, names) = match (words.next(), words.next()) {
+                (Some(_kind), Some(module)) => (
+                    module,
+                    vec![module.rsplit('.').next().unwrap_or(module).to_string()],
+                ),
+                (Some(module), None) => (module, vec![]),
+                _ => return,
+            };
+            imports.push(ImportInfo {
+                module: module.to_string(),
+                names,
+                level: 0,
+                start_line: node.start_position().row + 1,
+                end_line: node.end_position().row + 1,
+            });
+        }
+        _ => {
+            let mut cursor = node.walk();
+            for child in node.children(&mut cursor) {
+                collect_swift_imports(&child, code, imports);
+            }
+        }
+    }
+}
+
 // ── C / C++ ────────────────────────────────────────────────────
 
 fn collect_c_cpp_imports(node: &Node, code: &str, imports: &mut Vec<ImportInfo>) {
@@ -591,13 +765,20 @@ mod tests {
         let mut parser = Parser::new();
         let tree = parser.parse(code, SupportedLanguage::Python).unwrap();
         let imports = extract_imports(&tree, code, SupportedLanguage::Python);
-        assert!(imports.len() >= 1);
-        assert!(imports.iter().any(|i| i.level > 0));
+        assert_eq!(imports.len(), 2);
+        assert_eq!((imports[0].module.as_str(), imports[0].level), ("", 1));
+        assert_eq!(imports[0].names, vec!["utils"]);
+        assert_eq!(
+            (imports[1].module.as_str(), imports[1].level),
+            ("models", 2)
+        );
+        assert_eq!(imports[1].names, vec!["User"]);
     }
 
     // ── JavaScript tests ───────────────────────────────────────
 
     #[test]
+    #[cfg(feature = "lang-javascript")]
     fn test_js_named_import() {
         let code = r#"import { foo, bar } from "module-name";"#;
         let mut parser = Parser::new();
@@ -610,6 +791,7 @@ mod tests {
     }
 
     #[test]
+    #[cfg(feature = "lang-javascript")]
     fn test_js_default_import() {
         let code = r#"import React from "react";"#;
         let mut parser = Parser::new();
@@ -620,6 +802,7 @@ mod tests {
     }
 
     #[test]
+    #[cfg(feature = "lang-typescript")]
     fn test_ts_import() {
         let code = r#"import { Component } from "@angular/core";
 import * as path from "path";
@@ -635,6 +818,7 @@ import * as path from "path";
     // ── Rust tests ─────────────────────────────────────────────
 
     #[test]
+    #[cfg(feature = "lang-rust")]
     fn test_rust_use_simple() {
         let code = "use std::collections::HashMap;\n";
         let mut parser = Parser::new();
@@ -645,6 +829,7 @@ import * as path from "path";
     }
 
     #[test]
+    #[cfg(feature = "lang-rust")]
     fn test_rust_mod_decl() {
         let code = "mod parser;\nmod graph;\n";
         let mut parser = Parser::new();
@@ -655,9 +840,50 @@ import * as path from "path";
         assert_eq!(imports[1].module, "graph");
     }
 
+    #[test]
+    #[cfg(feature = "lang-rust")]
+    fn test_rust_use_names_and_nested_modules() {
+        let code = r#"
+use crate::parser::calls::extract_calls;
+use crate::parser::{calls, imports};
+
+mod tests {
+    use super::*;
+    use super::super::graph::RepositoryGraph;
+
+    mod fixtures {
+        use super::helpers;
+    }
+}
+"#;
+        let mut parser = Parser::new();
+        let tree = parser.parse(code, SupportedLanguage::Rust).unwrap();
+        let imports = extract_imports(&tree, code, SupportedLanguage::Rust);
+        let found: Vec<(&str, Vec<&str>)> = imports
+            .iter()
+            .map(|i| {
+                (
+                    i.module.as_str(),
+                    i.names.iter().map(String::as_str).collect(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("crate::parser::calls::extract_calls", vec!["extract_calls"]),
+                ("crate::parser", vec!["calls", "imports"]),
+                ("self", vec!["*"]),
+                ("super::graph::RepositoryGraph", vec!["RepositoryGraph"]),
+                ("self::tests::helpers", vec!["helpers"]),
+            ]
+        );
+    }
+
     // ── Go tests ───────────────────────────────────────────────
 
     #[test]
+    #[cfg(feature = "lang-go")]
     fn test_go_single_import() {
         let code = r#"package main
 
@@ -674,6 +900,7 @@ import "fmt"
     }
 
     #[test]
+    #[cfg(feature = "lang-go")]
     fn test_go_grouped_import() {
         let code = r#"package main
 
@@ -693,6 +920,7 @@ import (
     // ── Java tests ─────────────────────────────────────────────
 
     #[test]
+    #[cfg(feature = "lang-java")]
     fn test_java_import() {
         let code = r#"
 import java.util.HashMap;
@@ -712,9 +940,90 @@ public class Main {}
         assert!(modules.contains(&"java.util.*"), "modules: {:?}", modules);
     }
 
+    // ── C# tests ───────────────────────────────────────────────
+
+    #[test]
+    #[cfg(feature = "lang-csharp")]
+    fn test_csharp_using() {
+        let code = r#"
+global using System.Linq;
+using System.Collections.Generic;
+using static System.Math;
+using Json = Newtonsoft.Json.JsonConvert;
+using Lookup = System.Collections.Generic.Dictionary<string, int>;
+
+namespace My.Company.Services
+{
+    using My.Company.Models;
+
+    public class UserService {}
+}
+"#;
+        let mut parser = Parser::new();
+        let tree = parser.parse(code, SupportedLanguage::CSharp).unwrap();
+        let imports = extract_imports(&tree, code, SupportedLanguage::CSharp);
+        let found: Vec<(&str, Vec<&str>)> = imports
+            .iter()
+            .map(|i| {
+                (
+                    i.module.as_str(),
+                    i.names.iter().map(String::as_str).collect(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("System.Linq", vec![]),
+                ("System.Collections.Generic", vec![]),
+                ("System.Math", vec!["Math"]),
+                ("Newtonsoft.Json.JsonConvert", vec!["JsonConvert"]),
+                ("System.Collections.Generic.Dictionary", vec!["Dictionary"]),
+                ("My.Company.Models", vec![]),
+            ]
+        );
+        assert_eq!(imports[5].start_line, 11);
+    }
+
+    // ── Swift tests ────────────────────────────────────────────
+
+    #[test]
+    #[cfg(feature = "lang-swift")]
+    fn test_swift_import() {
+        let code = r#"
+import Foundation
+@testable import MyApp
+import struct Models.User
+
+class UserService {}
+"#;
+        let mut parser = Parser::new();
+        let tree = parser.parse(code, SupportedLanguage::Swift).unwrap();
+        let imports = extract_imports(&tree, code, SupportedLanguage::Swift);
+        let found: Vec<(&str, Vec<&str>)> = imports
+            .iter()
+            .map(|i| {
+                (
+                    i.module.as_str(),
+                    i.names.iter().map(String::as_str).collect(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("Foundation", vec![]),
+                ("MyApp", vec![]),
+                ("Models.User", vec!["User"]),
+            ]
+        );
+        assert_eq!(imports[1].start_line, 3);
+    }
+
     // ── C/C++ tests ────────────────────────────────────────────
 
     #[test]
+    #[cfg(feature = "lang-cpp")]
     fn test_cpp_include() {
         let code = r#"
 #include <vector>
@@ -731,6 +1040,7 @@ int main() { return 0; }
     }
 
     #[test]
+    #[cfg(feature = "lang-c")]
     fn test_c_include() {
         let code = r#"
 #include <stdio.h>
@@ -2,6 +2,16 @@ use std::path::Path;
 use tree_sitter::Language;
 
 /// Supported programming languages for parsing.
+///
+/// Every variant always exists, but each grammar is behind a cargo feature
+/// (`lang-python`, `lang-rust`, ...; `lang-all` is the default). Languages
+/// whose feature is disabled are never returned by [`from_extension`], and
+/// their files are skipped like any other unsupported extension.
+///
+/// Languages indexed [`IndexingMode::ContentOnly`] have no grammar and need
+/// no feature.
+///
+/// [`from_extension`]: SupportedLanguage::from_extension
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum SupportedLanguage {
     Python,
@@ -13,38 +23,143 @@ pub enum SupportedLanguage {
     Java,
     Cpp,
     C,
+    CSharp,
+    Swift,
+    Kotlin,
+}
+
+/// How the files of a language are indexed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum IndexingMode {
+    /// Parsed with the language's grammar into definitions, with calls,
+    /// imports, and bases read for graph edges.
+    Parsed,
+    /// Not parsed: the file and fixed runs of its lines are indexed for
+    /// search, and no graph edges are built from them.
+    ContentOnly,
 }
 
 impl SupportedLanguage {
-    /// Detect language from file extension.
+    pub const ALL: [Self; 12] = [
+        Self::Python,
+        Self::JavaScript,
+        Self::TypeScript,
+        Self::Tsx,
+        Self::Rust,
+        Self::Go,
+        Self::Java,
+        Self::Cpp,
+        Self::C,
+        Self::CSharp,
+        Self::Swift,
+        Self::Kotlin,
+    ];
+
+    /// Detect language from file extension, among the languages compiled in.
     pub fn from_extension(path: &str) -> Option<Self> {
+        Self::from_extension_any(path).filter(Self::is_enabled)
+    }
+
+    /// Detect language from file extension, including languages whose
+    /// grammar feature is disabled in this build.
+    pub fn from_extension_any(path: &str) -> Option<Self> {
         let ext = Path::new(path).extension()?.to_str()?;
-        match ext {
-            "py" | "pyi" => Some(Self::Python),
-            "js" | "mjs" | "cjs" => Some(Self::JavaScript),
-            "ts" | "mts" | "cts" => Some(Self::TypeScript),
-            "tsx" | "jsx" => Some(Self::Tsx),
-            "rs" => Some(Self::Rust),
-            "go" => Some(Self::Go),
-            "java" => Some(Self::Java),
-            "cpp" | "cc" | "cxx" | "hpp" | "hxx" | "h" => Some(Self::Cpp),
-            "c" => Some(Self::C),
-            _ => None,
+        Self::ALL
+            .into_iter()
+            .find(|lang| lang.extensions().contains(&ext))
+    }
+
+    /// The file extensions of this language, without the dot.
+    pub fn extensions(&self) -> &'static [&'static str] {
+        match self {
+            Self::Python => &["py", "pyi"],
+            Self::JavaScript => &["js", "mjs", "cjs"],
+            Self::TypeScript => &["ts", "mts", "cts"],
+            Self::Tsx => &["tsx", "jsx"],
+            Self::Rust => &["rs"],
+            Self::Go => &["go"],
+            Self::Java => &["java"],
+            Self::Cpp => &["cpp", "cc", "cxx", "hpp", "hxx", "h"],
+            Self::C => &["c"],
+            Self::CSharp => &["cs"],
+            Self::Swift => &["swift"],
+            Self::Kotlin => &["kt", "kts"],
+        }
+    }
+
+    /// The cargo feature that compiles in this language's grammar, or
+    /// `None` for a language indexed without one.
+    pub fn feature(&self) -> Option<&'static str> {
+        match self {
+            Self::Python => Some("lang-python"),
+            Self::JavaScript => Some("lang-javascript"),
+            Self::TypeScript | Self::Tsx => Some("lang-typescript"),
+            Self::Rust => Some("lang-rust"),
+            Self::Go => Some("lang-go"),
+            Self::Java => Some("lang-java"),
+            Self::Cpp => Some("lang-cpp"),
+            Self::C => Some("lang-c"),
+            Self::CSharp => Some("lang-csharp"),
+            Self::Swift => Some("lang-swift"),
+            Self::Kotlin => None,
+        }
+    }
+
+    /// Whether files in this language are parsed or only indexed for
+    /// search.
+    pub fn indexing_mode(&self) -> IndexingMode {
+        match self {
+            Self::Kotlin => IndexingMode::ContentOnly,
+            _ => IndexingMode::Parsed,
         }
     }
 
-    /// Get the tree-sitter Language grammar for this language.
-    pub fn grammar(&self) -> Language {
+    /// Whether this language's grammar is compiled in. Always true for a
+    /// language indexed without one.
+    pub fn is_enabled(&self) -> bool {
         match self {
-            Self::Python => tree_sitter_python::LANGUAGE.into(),
-            Self::JavaScript => tree_sitter_javascript::LANGUAGE.into(),
-            Self::TypeScript => tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into(),
-            Self::Tsx => tree_sitter_typescript::LANGUAGE_TSX.into(),
-            Self::Rust => tree_sitter_rust::LANGUAGE.into(),
-            Self::Go => tree_sitter_go::LANGUAGE.into(),
-            Self::Java => tree_sitter_java::LANGUAGE.into(),
-            Self::Cpp => tree_sitter_cpp::LANGUAGE.into(),
-            Self::C => tree_sitter_c::LANGUAGE.into(),
+            Self::Python => cfg!(feature = "lang-python"),
+            Self::JavaScript => cfg!(feature = "lang-javascript"),
+            Self::TypeScript | Self::Tsx => cfg!(feature = "lang-typescript"),
+            Self::Rust => cfg!(feature = "lang-rust"),
+            Self::Go => cfg!(feature = "lang-go"),
+            Self::Java => cfg!(feature = "lang-java"),
+            Self::Cpp => cfg!(feature = "lang-cpp"),
+            Self::C => cfg!(feature = "lang-c"),
+            Self::CSharp => cfg!(feature = "lang-csharp"),
+            Self::Swift => cfg!(feature = "lang-swift"),
+            Self::Kotlin => true,
+        }
+    }
+
+    /// Get the tree-sitter Language grammar for this language, or `None`
+    /// when its feature is disabled or it has none.
+    pub fn grammar(&self) -> Option<Language> {
+        match self {
+            #[cfg(feature = "lang-python")]
+            Self::Python => Some(tree_sitter_python::LANGUAGE.into()),
+            #[cfg(feature = "lang-javascript")]
+            Self::JavaScript => Some(tree_sitter_javascript::LANGUAGE.into()),
+            #[cfg(feature = "lang-typescript")]
+            Self::TypeScript => Some(tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into()),
+            #[cfg(feature = "lang-typescript")]
+            Self::Tsx => Some(tree_sitter_typescript::LANGUAGE_TSX.into()),
+            #[cfg(feature = "lang-rust")]
+            Self::Rust => Some(tree_sitter_rust::LANGUAGE.into()),
+            #[cfg(feature = "lang-go")]
+            Self::Go => Some(tree_sitter_go::LANGUAGE.into()),
+            #[cfg(feature = "lang-java")]
+            Self::Java => Some(tree_sitter_java::LANGUAGE.into()),
+            #[cfg(feature = "lang-cpp")]
+            Self::Cpp => Some(tree_sitter_cpp::LANGUAGE.into()),
+            #[cfg(feature = "lang-c")]
+            Self::C => Some(tree_sitter_c::LANGUAGE.into()),
+            #[cfg(feature = "lang-csharp")]
+            Self::CSharp => Some(tree_sitter_c_sharp::LANGUAGE.into()),
+            #[cfg(feature = "lang-swift")]
+            Self::Swift => Some(tree_sitter_swift::LANGUAGE.into()),
+            #[allow(unreachable_patterns)]
+            _ => None,
         }
     }
 
@@ -60,6 +175,9 @@ impl SupportedLanguage {
             Self::Java => "java",
             Self::Cpp => "cpp",
             Self::C => "c",
+            Self::CSharp => "csharp",
+            Self::Swift => "swift",
+            Self::Kotlin => "kotlin",
         }
     }
 }
@@ -77,34 +195,56 @@ mod tests {
     #[test]
     fn test_from_extension() {
         assert_eq!(
-            SupportedLanguage::from_extension("foo.py"),
+            SupportedLanguage::from_extension_any("foo.py"),
             Some(SupportedLanguage::Python)
         );
         assert_eq!(
-            SupportedLanguage::from_extension("bar.ts"),
+            SupportedLanguage::from_extension_any("bar.ts"),
             Some(SupportedLanguage::TypeScript)
         );
         assert_eq!(
-            SupportedLanguage::from_extension("baz.rs"),
+            SupportedLanguage::from_extension_any("baz.rs"),
             Some(SupportedLanguage::Rust)
         );
-        assert_eq!(SupportedLanguage::from_extension("qux.txt"), None);
+        assert_eq!(
+            SupportedLanguage::from_extension_any("Program.cs"),
+            Some(SupportedLanguage::CSharp)
+        );
+        assert_eq!(
+            SupportedLanguage::from_extension_any("App.swift"),
+            Some(SupportedLanguage::Swift)
+        );
+        assert_eq!(
+            SupportedLanguage::from_extension("build.gradle.kts"),
+            Some(SupportedLanguage::Kotlin)
+        );
+        assert_eq!(SupportedLanguage::from_extension_any("qux.txt"), None);
+        for (path, lang) in [
+            ("foo.py", SupportedLanguage::Python),
+            ("baz.rs", SupportedLanguage::Rust),
+        ] {
+            let expected = lang.is_enabled().then_some(lang);
+            assert_eq!(SupportedLanguage::from_extension(path), expected);
+        }
     }
 
     #[test]
     fn test_grammar_loads() {
-        // Verify all grammars can be loaded
-        for lang in [
-            SupportedLanguage::Python,
-            SupportedLanguage::JavaScript,
-            SupportedLanguage::TypeScript,
-            SupportedLanguage::Rust,
-            SupportedLanguage::Go,
-            SupportedLanguage::Java,
-            SupportedLanguage::Cpp,
-            SupportedLanguage::C,
-        ] {
-            let _grammar = lang.grammar();
+        // Grammars load exactly for the parsed languages compiled in.
+        for lang in SupportedLanguage::ALL {
+            let parsed = lang.indexing_mode() == IndexingMode::Parsed;
+            assert_eq!(
+                lang.grammar().is_some(),
+                parsed && lang.is_enabled(),
+                "{lang}"
+            );
+            assert_eq!(lang.feature().is_some(), parsed, "{lang}");
         }
     }
+
+    #[test]
+    #[cfg(feature = "lang-all")]
+    fn test_lang_all_enables_every_language() {
+        assert!(SupportedLanguage::ALL.iter().all(|lang| lang.is_enabled()));
+    }
 }
@@ -1,9 +1,12 @@
 pub mod calls;
 pub mod imports;
 pub mod languages;
+pub mod strings;
 
 use languages::SupportedLanguage;
+use std::cell::RefCell;
 use std::collections::HashMap;
+use std::collections::hash_map::Entry;
 use tree_sitter::{Parser as TsParser, Tree};
 
 /// Thread-safe parser that caches language instances.
@@ -18,14 +21,18 @@ impl Parser {
         }
     }
 
-    /// Parse source code for a given language.
+    /// Parse source code for a given language. Returns `None` when the
+    /// language's grammar feature is disabled.
     pub fn parse(&mut self, code: &str, language: SupportedLanguage) -> Option<Tree> {
-        let parser = self.parsers.entry(language).or_insert_with(|| {
-            let mut p = TsParser::new();
-            p.set_language(&language.grammar())
-                .expect("Failed to set language");
-            p
-        });
+        let parser = match self.parsers.entry(language) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => {
+                let mut p = TsParser::new();
+                p.set_language(&language.grammar()?)
+                    .expect("Failed to set language");
+                entry.insert(p)
+            }
+        };
         parser.parse(code.as_bytes(), None)
     }
 
@@ -43,6 +50,23 @@ impl Default for Parser {
     }
 }
 
+thread_local! {
+    static THREAD_PARSER: RefCell<Parser> = RefCell::new(Parser::new());
+}
+
+/// Run `f` with this thread's shared [`Parser`].
+///
+/// Indexing parses every file and then every element again while edges are
+/// built; reusing one parser per thread (and per language within it) saves
+/// creating a tree-sitter parser each time. A nested call, which would find
+/// the shared parser busy, gets a fresh one.
+pub fn with_thread_parser<R>(f: impl FnOnce(&mut Parser) -> R) -> R {
+    THREAD_PARSER.with(|cell| match cell.try_borrow_mut() {
+        Ok(mut parser) => f(&mut parser),
+        Err(_) => f(&mut Parser::new()),
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -72,4 +96,19 @@ mod tests {
         let result = parser.parse_file("test.xyz", "x = 1");
         assert!(result.is_none());
     }
+
+    #[test]
+    fn test_thread_parser_is_reused_and_nests() {
+        with_thread_parser(|outer| {
+            assert!(outer.parse("x = 1", SupportedLanguage::Python).is_some());
+            // The shared parser is busy here, so this gets a fresh one.
+            with_thread_parser(|inner| {
+                assert!(inner.parsers.is_empty());
+                assert!(inner.parse("y = 2", SupportedLanguage::Python).is_some());
+            });
+        });
+        with_thread_parser(|parser| {
+            assert!(parser.parsers.contains_key(&SupportedLanguage::Python));
+        });
+    }
 }
@@ -0,0 +1,149 @@
+use tree_sitter::{Node, Tree};
+
+/// Shortest name a string literal must spell to count as a reference.
+pub const MIN_STRING_REF_LEN: usize = 4;
+
+/// Words that are also plausible symbol names but far more often plain
+/// text: a string spelling one of these is never treated as a reference.
+const COMMON_WORDS: &[&str] = &[
+    "action", "admin", "call", "check", "class", "clear", "close", "config", "content", "context",
+    "count", "create", "data", "date", "debug", "default", "delete", "done", "error", "event",
+    "exit", "false", "fetch", "field", "file", "filter", "format", "handle", "handler", "head",
+    "help", "index", "info", "init", "input", "items", "json", "keys", "label", "list", "load",
+    "main", "message", "method", "model", "module", "name", "none", "null", "object", "open",
+    "options", "output", "page", "parse", "patch", "path", "post", "print", "process", "read",
+    "render", "reset", "result", "return", "save", "self", "send", "setup", "size", "start",
+    "state", "status", "stop", "string", "test", "text", "time", "title", "true", "type", "update",
+    "user", "value", "values", "view", "warn", "warning", "write",
+];
+
+/// A string literal found in source code.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StringLiteral {
+    /// The literal's contents without quotes or prefixes (`r`, `b`, `f`).
+    pub value: String,
+    /// Line within the parsed code, starting at 0.
+    pub line: usize,
+}
+
+/// Extract every string literal from a tree-sitter AST, in source order.
+/// The node kinds cover all supported grammars.
+pub fn extract_string_literals(tree: &Tree, code: &str) -> Vec<StringLiteral> {
+    let mut literals = Vec::new();
+    collect_literals(&tree.root_node(), code.as_bytes(), &mut literals);
+    literals
+}
+
+fn collect_literals(node: &Node, code_bytes: &[u8], literals: &mut Vec<StringLiteral>) {
+    if matches!(
+        node.kind(),
+        "string"
+            | "string_literal"
+            | "raw_string_literal"
+            | "interpreted_string_literal"
+            | "template_string"
+    ) {
+        if let Ok(text) = node.utf8_text(code_bytes) {
+            literals.push(StringLiteral {
+                value: unquote(text).to_string(),
+                line: node.start_position().row,
+            });
+        }
+        return;
+    }
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        collect_literals(&child, code_bytes, literals);
+    }
+}
+
+/// Strip a literal's prefix (`r`, `b`, `f`, `u8`) and quotes.
+fn unquote(text: &str) -> &str {
+    text.trim_start_matches(|c: char| c.is_ascii_alphanumeric())
+        .trim_matches(|c| matches!(c, '"' | '\'' | '`' | '#'))
+}
+
+/// The name a string literal refers to, when the whole literal is an
+/// identifier or a dotted path (`process_payment`, `app.views.handler`)
+/// whose last segment is at least [`MIN_STRING_REF_LEN`] characters and not
+/// a common word. Prose, paths with spaces, and format strings yield `None`.
+pub fn string_ref_name(value: &str) -> Option<&str> {
+    let mut segments = value.split('.');
+    let valid = segments.clone().all(|segment| {
+        let mut chars = segment.chars();
+        chars
+            .next()
+            .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+            && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+    });
+    let last = segments.next_back()?;
+    if !valid || last.len() < MIN_STRING_REF_LEN {
+        return None;
+    }
+    if COMMON_WORDS.contains(&last.to_ascii_lowercase().as_str()) {
+        return None;
+    }
+    Some(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::Parser;
+    use crate::parser::languages::SupportedLanguage;
+
+    #[test]
+    fn test_extract_string_literals() {
+        let code =
+            "def f(obj):\n    getattr(obj, \"process_payment\")()\n    return f'{obj}' + r'raw'\n";
+        let mut parser = Parser::new();
+        let tree = parser.parse(code, SupportedLanguage::Python).unwrap();
+        let literals = extract_string_literals(&tree, code);
+        assert_eq!(
+            literals,
+            vec![
+                StringLiteral {
+                    value: "process_payment".to_string(),
+                    line: 1,
+                },
+                StringLiteral {
+                    value: "{obj}".to_string(),
+                    line: 2,
+                },
+                StringLiteral {
+                    value: "raw".to_string(),
+                    line: 2,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_extract_js_string_literals() {
+        let code = "emitter.on(\"user_created\", onUserCreated);\nconst t = `tpl`;\n";
+        let mut parser = Parser::new();
+        let tree = parser.parse(code, SupportedLanguage::JavaScript).unwrap();
+        let values: Vec<String> = extract_string_literals(&tree, code)
+            .into_iter()
+            .map(|literal| literal.value)
+            .collect();
+        assert_eq!(values, vec!["user_created", "tpl"]);
+    }
+
+    #[test]
+    fn test_string_ref_name() {
+        assert_eq!(string_ref_name("process_payment"), Some("process_payment"));
+        assert_eq!(
+            string_ref_name("app.views.handle_login"),
+            Some("app.views.handle_login")
+        );
+        assert_eq!(string_ref_name("process"), None);
+        assert_eq!(string_ref_name("Process"), None);
+        assert_eq!(string_ref_name("app.views.handler"), None);
+        assert_eq!(string_ref_name("foo"), None);
+        assert_eq!(string_ref_name("failed to process payment"), None);
+        assert_eq!(string_ref_name("app..tasks"), None);
+        assert_eq!(string_ref_name("{obj}"), None);
+        assert_eq!(string_ref_name(""), None);
+    }
+}
@@ -0,0 +1,124 @@
+//! Approvals remembered for a repo, kept under `CODEX_HOME`.
+//!
+//! The TUI writes the repo's file when an approval prompt is answered with
+//! "always in this repo" and loads it at startup. The files live outside the
+//! repo on purpose: a cloned repo must not be able to pre-approve its own
+//! commands. Directories are stored relative to the repo root.
+
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+/// Directory under `CODEX_HOME` holding one permissions file per repo.
+pub const PERMISSIONS_DIR: &str = "permissions";
+
+/// The permissions file for the repo at `repo_root`:
+/// `<codex_home>/permissions/<dir name>-<hash of the root path>.toml`.
+pub fn permissions_path(codex_home: &Path, repo_root: &Path) -> PathBuf {
+    let root = std::fs::canonicalize(repo_root).unwrap_or_else(|_| repo_root.to_path_buf());
+    let hash = blake3::hash(root.as_os_str().as_encoded_bytes()).to_hex();
+    let name = root
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or("root");
+    codex_home
+        .join(PERMISSIONS_DIR)
+        .join(format!("{name}-{}.toml", &hash[..16]))
+}
+
+/// The contents of a repo's permissions file.
+///
+/// ```toml
+/// commands = ["cargo test", "npm run lint"]
+/// write_dirs = ["src", "docs/api"]
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RepoPermissions {
+    /// Command prefixes as shell words; `cargo test` allows every command
+    /// that starts with those two words.
+    pub commands: Vec<String>,
+    /// Directories, relative to the repo root, that edits may write under.
+    pub write_dirs: Vec<PathBuf>,
+}
+
+impl RepoPermissions {
+    /// Load the permissions file at `path`, or nothing when it is missing.
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let text = match std::fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(err) => return Err(err),
+        };
+        toml::from_str(&text).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    /// Write the file at `path`, or remove it once the last rule is gone.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if self.is_empty() {
+            return match std::fs::remove_file(path) {
+                Err(err) if err.kind() != io::ErrorKind::NotFound => Err(err),
+                _ => Ok(()),
+            };
+        }
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let text =
+            toml::to_string(self).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        std::fs::write(path, text)
+    }
+
+    pub fn len(&self) -> usize {
+        self.commands.len() + self.write_dirs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_permissions_round_trip() {
+        let home = tempfile::tempdir().unwrap();
+        let path = permissions_path(home.path(), Path::new("/work/repo"));
+        assert_eq!(
+            RepoPermissions::load(&path).unwrap(),
+            RepoPermissions::default()
+        );
+
+        let permissions = RepoPermissions {
+            commands: vec!["cargo test".to_string()],
+            write_dirs: vec![PathBuf::from("src")],
+        };
+        permissions.save(&path).unwrap();
+        assert_eq!(RepoPermissions::load(&path).unwrap(), permissions);
+
+        RepoPermissions::default().save(&path).unwrap();
+        assert!(!path.exists());
+
+        std::fs::write(&path, "commands = 1\n").unwrap();
+        let err = RepoPermissions::load(&path).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_permissions_are_kept_per_repo_outside_it() {
+        let home = Path::new("/home/me/.codex");
+        let path = permissions_path(home, Path::new("/work/repo"));
+        assert!(path.starts_with(home.join(PERMISSIONS_DIR)));
+        assert!(
+            path.file_name()
+                .unwrap()
+                .to_string_lossy()
+                .starts_with("repo-")
+        );
+        assert_ne!(path, permissions_path(home, Path::new("/other/repo")));
+        assert_eq!(path, permissions_path(home, Path::new("/work/repo")));
+    }
+}
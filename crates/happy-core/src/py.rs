@@ -11,11 +11,24 @@ use crate::global_index::GlobalIndex;
 #[cfg(feature = "python")]
 use crate::graph::RepositoryGraph;
 #[cfg(feature = "python")]
+use crate::graph::export::ExportFormat;
+#[cfg(feature = "python")]
+use crate::graph::queries::{
+    DEFAULT_MAX_PATH_LEN, DEFAULT_TRANSITIVE_LIMIT, FileLookupError, OutlineNode,
+    RELATED_RANKED_LIMIT,
+};
+#[cfg(feature = "python")]
+use crate::graph::types::{EdgeKind, GraphNode, NodeKind};
+#[cfg(feature = "python")]
 use crate::indexer;
 #[cfg(feature = "python")]
 use crate::store;
 #[cfg(feature = "python")]
-use crate::vector::{BM25Index, VectorIndex};
+use crate::tool_spec::GetRelatedArgs;
+#[cfg(feature = "python")]
+use crate::vector::embedder::embed_elements;
+#[cfg(feature = "python")]
+use crate::vector::{BM25Index, Embedder, HashingEmbedder, VectorIndex};
 
 #[cfg(feature = "python")]
 #[pyclass]
@@ -24,6 +37,7 @@ pub struct HappyRepo {
     global_index: GlobalIndex,
     bm25: BM25Index,
     vector: Option<VectorIndex>,
+    embedder: HashingEmbedder,
     repo_path: String,
 }
 
@@ -40,20 +54,22 @@ impl HappyRepo {
 
         let mut bm25 = BM25Index::new();
         for elem in elements {
-            let text = format!(
-                "{} {} {}",
-                elem.name,
-                elem.code,
-                elem.docstring.as_deref().unwrap_or("")
-            );
-            bm25.add_document(&elem.id, &text);
+            bm25.add_element(elem);
         }
 
+        let embedder = HashingEmbedder::default();
+        let mut vector = VectorIndex::new(embedder.dimension());
+        let vector = match embed_elements(&embedder, elements, &mut vector) {
+            Ok(_) => Some(vector),
+            Err(_) => None,
+        };
+
         Self {
             graph,
             global_index,
             bm25,
-            vector: None,
+            vector,
+            embedder,
             repo_path: path.to_string(),
         }
     }
@@ -64,18 +80,19 @@ impl HappyRepo {
 impl HappyRepo {
     #[new]
     fn new(path: &str) -> PyResult<Self> {
-        let elements = indexer::walk_and_index(path);
+        let elements = indexer::walk_and_index(path, &indexer::IndexOptions::load(Path::new(path)));
         Ok(Self::build_from_elements(path, &elements))
     }
 
     #[staticmethod]
     fn from_elements_file(elements_file: &str, path: &str) -> PyResult<Self> {
-        let elements = store::load_elements(Path::new(elements_file)).map_err(|err| {
-            pyo3::exceptions::PyRuntimeError::new_err(format!(
-                "failed to load elements snapshot '{}': {err}",
-                elements_file
-            ))
-        })?;
+        let elements =
+            store::load_elements(Path::new(elements_file), Path::new(path)).map_err(|err| {
+                pyo3::exceptions::PyRuntimeError::new_err(format!(
+                    "failed to load elements snapshot '{}': {err}",
+                    elements_file
+                ))
+            })?;
         Ok(Self::build_from_elements(path, &elements))
     }
 
@@ -95,20 +112,38 @@ impl HappyRepo {
             .collect()
     }
 
-    fn get_dependencies(&self, file: &str) -> Vec<String> {
+    #[pyo3(signature = (symbol, max_depth=3))]
+    fn find_callers_transitive(&self, symbol: &str, max_depth: usize) -> Vec<Vec<String>> {
         self.graph
-            .get_dependencies(file)
-            .into_iter()
-            .map(|n| n.id.clone())
-            .collect()
+            .find_callers_transitive(symbol, max_depth, DEFAULT_TRANSITIVE_LIMIT)
+            .level_ids()
     }
 
-    fn get_dependents(&self, file: &str) -> Vec<String> {
+    #[pyo3(signature = (symbol, max_depth=3))]
+    fn find_callees_transitive(&self, symbol: &str, max_depth: usize) -> Vec<Vec<String>> {
         self.graph
-            .get_dependents(file)
-            .into_iter()
-            .map(|n| n.id.clone())
-            .collect()
+            .find_callees_transitive(symbol, max_depth, DEFAULT_TRANSITIVE_LIMIT)
+            .level_ids()
+    }
+
+    fn get_dependencies(&self, file: &str) -> PyResult<Vec<String>> {
+        let nodes = self
+            .graph
+            .get_dependencies(file)
+            .map_err(file_lookup_error)?;
+        Ok(nodes.into_iter().map(|n| n.id.clone()).collect())
+    }
+
+    fn get_dependents(&self, file: &str) -> PyResult<Vec<String>> {
+        let nodes = self.graph.get_dependents(file).map_err(file_lookup_error)?;
+        Ok(nodes.into_iter().map(|n| n.id.clone()).collect())
+    }
+
+    /// The elements `file` defines as nested dicts: `id`, `name`, `kind`,
+    /// `signature`, `doc`, `start_line`, `end_line` and `children`.
+    fn file_outline<'py>(&self, py: Python<'py>, file: &str) -> PyResult<Vec<Bound<'py, PyDict>>> {
+        let outline = self.graph.file_outline(file).map_err(file_lookup_error)?;
+        outline.iter().map(|node| outline_dict(py, node)).collect()
     }
 
     fn get_subclasses(&self, class_name: &str) -> Vec<String> {
@@ -127,22 +162,114 @@ impl HappyRepo {
             .collect()
     }
 
-    fn find_path(&self, source: &str, target: &str) -> Option<Vec<String>> {
-        self.graph.find_path(source, target, None)
-    }
-
-    fn get_related(&self, element: &str, max_hops: usize) -> Vec<String> {
+    #[pyo3(signature = (allowlist=Vec::new()))]
+    fn find_dead_code(&self, allowlist: Vec<String>) -> Vec<String> {
         self.graph
-            .get_related(element, max_hops)
+            .find_unreferenced_except(
+                &[NodeKind::Function, NodeKind::Method, NodeKind::Class],
+                &allowlist,
+            )
             .into_iter()
             .map(|n| n.id.clone())
             .collect()
     }
 
+    fn find_import_cycles(&self) -> Vec<Vec<String>> {
+        self.graph.find_import_cycles()
+    }
+
+    #[pyo3(signature = (source, target, edge_kinds=Vec::new()))]
+    fn find_path(
+        &self,
+        source: &str,
+        target: &str,
+        edge_kinds: Vec<String>,
+    ) -> PyResult<Option<Vec<String>>> {
+        let kinds = parse_edge_kinds(&edge_kinds)?;
+        Ok(self
+            .graph
+            .find_path(source, target, &kinds)
+            .map(|path| path.nodes.iter().map(|n| n.id.clone()).collect()))
+    }
+
+    /// Element IDs along up to `k` distinct paths of at most `max_len`
+    /// edges, shortest first and avoiding hub nodes among equally short
+    /// ones.
+    #[pyo3(signature = (source, target, k, max_len=DEFAULT_MAX_PATH_LEN, edge_kinds=Vec::new()))]
+    fn find_paths(
+        &self,
+        source: &str,
+        target: &str,
+        k: usize,
+        max_len: usize,
+        edge_kinds: Vec<String>,
+    ) -> PyResult<Vec<Vec<String>>> {
+        let kinds = parse_edge_kinds(&edge_kinds)?;
+        Ok(self
+            .graph
+            .find_paths(source, target, &kinds, k, max_len)
+            .iter()
+            .map(|path| path.nodes.iter().map(|n| n.id.clone()).collect())
+            .collect())
+    }
+
+    #[pyo3(signature = (element, max_hops, direction=None, edge_kinds=Vec::new()))]
+    fn get_related(
+        &self,
+        element: &str,
+        max_hops: usize,
+        direction: Option<String>,
+        edge_kinds: Vec<String>,
+    ) -> PyResult<Vec<String>> {
+        let args = related_args(element, max_hops, direction, edge_kinds);
+        let direction = args.related_direction().map_err(value_error)?;
+        let kinds = args.edge_kinds().map_err(value_error)?;
+        Ok(self
+            .graph
+            .get_related_filtered(element, max_hops, direction, &kinds)
+            .into_iter()
+            .map(|n| n.id.clone())
+            .collect())
+    }
+
+    /// Direct relationships of `element` grouped by kind:
+    /// `{group: {"total": n, "ids": [...]}}`.
+    #[pyo3(signature = (element, direction=None, edge_kinds=Vec::new()))]
+    fn summarize_related<'py>(
+        &self,
+        py: Python<'py>,
+        element: &str,
+        direction: Option<String>,
+        edge_kinds: Vec<String>,
+    ) -> PyResult<Bound<'py, PyDict>> {
+        let args = related_args(element, 1, direction, edge_kinds);
+        let direction = args.related_direction().map_err(value_error)?;
+        let kinds = args.edge_kinds().map_err(value_error)?;
+        let dict = PyDict::new(py);
+        for group in self.graph.summarize_related(element, direction, &kinds) {
+            let entry = PyDict::new(py);
+            entry.set_item("total", group.total)?;
+            let ids: Vec<&str> = group.nodes.iter().map(|n| n.id.as_str()).collect();
+            entry.set_item("ids", ids)?;
+            dict.set_item(group.name, entry)?;
+        }
+        Ok(dict)
+    }
+
     fn search(&self, query: &str, k: usize) -> Vec<(String, f64)> {
         self.bm25.search(query, k)
     }
 
+    #[pyo3(signature = (query, k, alpha=0.5))]
+    fn hybrid_search(&self, query: &str, k: usize, alpha: f64) -> Vec<(String, f64)> {
+        match &self.vector {
+            Some(vi) => {
+                crate::vector::hybrid_search(&self.bm25, vi, &self.embedder, query, k, alpha)
+            }
+            None => self.bm25.search(query, k),
+        }
+    }
+
     fn add_embeddings(&mut self, ids: Vec<String>, vectors: Vec<Vec<f32>>) {
         if vectors.is_empty() {
             return;
@@ -171,6 +298,7 @@ impl HappyRepo {
         dict.set_item("edges", gs.edge_count)?;
         dict.set_item("files", gs.file_count)?;
         dict.set_item("elements", gs.element_count)?;
+        dict.set_item("modules", gs.module_count)?;
         dict.set_item("bm25_docs", self.bm25.len())?;
         dict.set_item("has_vectors", self.vector.is_some())?;
         Ok(dict)
@@ -197,12 +325,372 @@ impl HappyRepo {
     }
 
     fn resolve_symbol(&self, symbol_name: &str) -> Vec<(String, String)> {
-        self.global_index.resolve_symbol(symbol_name)
+        self.graph.resolve_symbol(symbol_name)
+    }
+
+    /// Export the graph as `dot`, `graphml`, or `json`, keeping only
+    /// `edge_kinds` when given. Written to `output` when set, otherwise
+    /// returned as a string.
+    #[pyo3(signature = (format, edge_kinds=Vec::new(), output=None))]
+    fn export(
+        &self,
+        format: &str,
+        edge_kinds: Vec<String>,
+        output: Option<String>,
+    ) -> PyResult<Option<String>> {
+        let format: ExportFormat = format.parse().map_err(value_error)?;
+        let kinds = parse_edge_kinds(&edge_kinds)?;
+        let io_error = |err: std::io::Error| pyo3::exceptions::PyIOError::new_err(err.to_string());
+        match output {
+            Some(output) => {
+                let file = std::fs::File::create(&output).map_err(io_error)?;
+                let mut out = std::io::BufWriter::new(file);
+                self.graph
+                    .export(&mut out, format, &kinds, &self.repo_path)
+                    .and_then(|()| std::io::Write::flush(&mut out))
+                    .map_err(io_error)?;
+                Ok(None)
+            }
+            None => {
+                let mut out = Vec::new();
+                self.graph
+                    .export(&mut out, format, &kinds, &self.repo_path)
+                    .map_err(io_error)?;
+                Ok(Some(String::from_utf8_lossy(&out).into_owned()))
+            }
+        }
+    }
+}
+
+/// The code graph of one repository, queried by name and returning plain
+/// dicts. Indexing and snapshot I/O release the GIL.
+#[cfg(feature = "python")]
+#[pyclass(name = "RepositoryGraph")]
+pub struct PyRepositoryGraph {
+    graph: RepositoryGraph,
+    elements: Vec<crate::indexer::CodeElement>,
+    repo_path: String,
+}
+
+#[cfg(feature = "python")]
+impl PyRepositoryGraph {
+    fn from_elements(path: &str, elements: Vec<crate::indexer::CodeElement>) -> Self {
+        let mut graph = RepositoryGraph::new();
+        graph.build_from_elements(&elements, path);
+        Self {
+            graph,
+            elements,
+            repo_path: path.to_string(),
+        }
+    }
+
+    /// `KeyError` when `symbol` names nothing indexed, `ValueError` when it
+    /// is a qualified name matching members of several classes.
+    fn require_symbol(&self, symbol: &str) -> PyResult<()> {
+        self.graph
+            .check_symbol(symbol)
+            .map_err(|err| value_error(err.to_string()))?;
+        if self.graph.find_nodes_by_name(symbol).is_empty() {
+            return Err(missing_symbol(symbol));
+        }
+        Ok(())
+    }
+
+    fn node_dicts<'py>(
+        &self,
+        py: Python<'py>,
+        nodes: &[&GraphNode],
+    ) -> PyResult<Vec<Bound<'py, PyDict>>> {
+        nodes
+            .iter()
+            .map(|node| {
+                let dict = PyDict::new(py);
+                dict.set_item("id", &node.id)?;
+                dict.set_item("name", node.name.as_str())?;
+                dict.set_item("kind", format!("{:?}", node.kind))?;
+                dict.set_item("file_path", &node.file_path)?;
+                dict.set_item("start_line", node.start_line)?;
+                dict.set_item("end_line", node.end_line)?;
+                dict.set_item("qualified_name", self.graph.qualified_name(node))?;
+                Ok(dict)
+            })
+            .collect()
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl PyRepositoryGraph {
+    /// Index the repository at `path`.
+    #[staticmethod]
+    fn build(py: Python<'_>, path: &str) -> Self {
+        py.allow_threads(|| {
+            Self::from_elements(
+                path,
+                indexer::walk_and_index(path, &indexer::IndexOptions::load(Path::new(path))),
+            )
+        })
+    }
+
+    /// Load an elements snapshot written by `save_elements` or `happycode`.
+    #[staticmethod]
+    fn load_elements(py: Python<'_>, elements_file: &str, path: &str) -> PyResult<Self> {
+        py.allow_threads(|| -> std::io::Result<Self> {
+            let elements = store::load_elements(Path::new(elements_file), Path::new(path))?;
+            Ok(Self::from_elements(path, elements))
+        })
+        .map_err(|err| {
+            pyo3::exceptions::PyIOError::new_err(format!(
+                "failed to load elements snapshot '{elements_file}': {err}"
+            ))
+        })
+    }
+
+    /// Write the indexed elements to `elements_file`.
+    fn save_elements(&self, py: Python<'_>, elements_file: &str) -> PyResult<()> {
+        py.allow_threads(|| {
+            store::save_elements(
+                &self.elements,
+                Path::new(&self.repo_path),
+                Path::new(elements_file),
+            )
+        })
+        .map_err(|err| pyo3::exceptions::PyIOError::new_err(err.to_string()))
+    }
+
+    fn find_callers<'py>(
+        &self,
+        py: Python<'py>,
+        symbol: &str,
+    ) -> PyResult<Vec<Bound<'py, PyDict>>> {
+        self.require_symbol(symbol)?;
+        self.node_dicts(py, &self.graph.find_callers(symbol))
+    }
+
+    fn find_callees<'py>(
+        &self,
+        py: Python<'py>,
+        symbol: &str,
+    ) -> PyResult<Vec<Bound<'py, PyDict>>> {
+        self.require_symbol(symbol)?;
+        self.node_dicts(py, &self.graph.find_callees(symbol))
+    }
+
+    fn get_dependencies<'py>(
+        &self,
+        py: Python<'py>,
+        file_path: &str,
+    ) -> PyResult<Vec<Bound<'py, PyDict>>> {
+        let nodes = self
+            .graph
+            .get_dependencies(file_path)
+            .map_err(file_lookup_error)?;
+        self.node_dicts(py, &nodes)
+    }
+
+    #[pyo3(signature = (symbol, max_hops=2, direction=None, edge_kinds=Vec::new()))]
+    fn get_related<'py>(
+        &self,
+        py: Python<'py>,
+        symbol: &str,
+        max_hops: usize,
+        direction: Option<String>,
+        edge_kinds: Vec<String>,
+    ) -> PyResult<Vec<Bound<'py, PyDict>>> {
+        self.require_symbol(symbol)?;
+        let args = related_args(symbol, max_hops, direction, edge_kinds);
+        let direction = args.related_direction().map_err(value_error)?;
+        let kinds = args.edge_kinds().map_err(value_error)?;
+        let nodes = self
+            .graph
+            .get_related_filtered(symbol, max_hops, direction, &kinds);
+        self.node_dicts(py, &nodes)
+    }
+
+    /// Element IDs along the shortest path, `None` when the two symbols are
+    /// not connected. Only `edge_kinds` are followed when given, otherwise
+    /// every kind but `defines`.
+    #[pyo3(signature = (source, target, edge_kinds=Vec::new()))]
+    fn find_path(
+        &self,
+        source: &str,
+        target: &str,
+        edge_kinds: Vec<String>,
+    ) -> PyResult<Option<Vec<String>>> {
+        self.require_symbol(source)?;
+        self.require_symbol(target)?;
+        let kinds = parse_edge_kinds(&edge_kinds)?;
+        Ok(self
+            .graph
+            .find_path(source, target, &kinds)
+            .map(|path| path.nodes.iter().map(|n| n.id.clone()).collect()))
     }
+
+    /// Element IDs along up to `k` distinct paths of at most `max_len`
+    /// edges, shortest first and avoiding hub nodes among equally short
+    /// ones.
+    #[pyo3(signature = (source, target, k, max_len=DEFAULT_MAX_PATH_LEN, edge_kinds=Vec::new()))]
+    fn find_paths(
+        &self,
+        source: &str,
+        target: &str,
+        k: usize,
+        max_len: usize,
+        edge_kinds: Vec<String>,
+    ) -> PyResult<Vec<Vec<String>>> {
+        self.require_symbol(source)?;
+        self.require_symbol(target)?;
+        let kinds = parse_edge_kinds(&edge_kinds)?;
+        Ok(self
+            .graph
+            .find_paths(source, target, &kinds, k, max_len)
+            .iter()
+            .map(|path| path.nodes.iter().map(|n| n.id.clone()).collect())
+            .collect())
+    }
+
+    /// Source of the element an ID, name, or qualified name refers to.
+    fn get_source(&self, symbol: &str) -> PyResult<String> {
+        let id = self
+            .graph
+            .resolve_element_id(symbol)
+            .map_err(|err| value_error(err.to_string()))?
+            .ok_or_else(|| missing_symbol(symbol))?;
+        self.graph
+            .get_source(&id)
+            .ok_or_else(|| missing_symbol(symbol))
+    }
+
+    fn stats<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let dict = PyDict::new(py);
+        let gs = self.graph.stats();
+        dict.set_item("nodes", gs.node_count)?;
+        dict.set_item("edges", gs.edge_count)?;
+        dict.set_item("files", gs.file_count)?;
+        dict.set_item("elements", gs.element_count)?;
+        dict.set_item("modules", gs.module_count)?;
+        dict.set_item("generation", self.graph.generation())?;
+        Ok(dict)
+    }
+
+    #[getter]
+    fn path(&self) -> &str {
+        &self.repo_path
+    }
+}
+
+/// BM25 keyword index over a graph's elements.
+#[cfg(feature = "python")]
+#[pyclass(name = "BM25Index")]
+pub struct PyBM25Index {
+    index: BM25Index,
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl PyBM25Index {
+    #[new]
+    fn new(py: Python<'_>, graph: PyRef<'_, PyRepositoryGraph>) -> Self {
+        let elements = &graph.elements;
+        let index = py.allow_threads(|| {
+            let mut index = BM25Index::new();
+            for elem in elements {
+                index.add_element(elem);
+            }
+            index
+        });
+        Self { index }
+    }
+
+    /// The `k` best matches as `{"id", "score"}` dicts, best first.
+    fn search<'py>(
+        &self,
+        py: Python<'py>,
+        query: &str,
+        k: usize,
+    ) -> PyResult<Vec<Bound<'py, PyDict>>> {
+        self.index
+            .search(query, k)
+            .into_iter()
+            .map(|(id, score)| {
+                let dict = PyDict::new(py);
+                dict.set_item("id", id)?;
+                dict.set_item("score", score)?;
+                Ok(dict)
+            })
+            .collect()
+    }
+
+    fn __len__(&self) -> usize {
+        self.index.len()
+    }
+}
+
+#[cfg(feature = "python")]
+fn parse_edge_kinds(kinds: &[String]) -> PyResult<Vec<EdgeKind>> {
+    kinds
+        .iter()
+        .map(|kind| kind.parse())
+        .collect::<Result<Vec<EdgeKind>, _>>()
+        .map_err(value_error)
+}
+
+#[cfg(feature = "python")]
+fn related_args(
+    element: &str,
+    max_hops: usize,
+    direction: Option<String>,
+    edge_kinds: Vec<String>,
+) -> GetRelatedArgs {
+    GetRelatedArgs {
+        symbol: element.to_string(),
+        max_hops,
+        direction,
+        edge_kinds,
+        summarize: false,
+        ranked: false,
+        limit: RELATED_RANKED_LIMIT,
+    }
+}
+
+#[cfg(feature = "python")]
+fn outline_dict<'py>(py: Python<'py>, node: &OutlineNode) -> PyResult<Bound<'py, PyDict>> {
+    let dict = PyDict::new(py);
+    dict.set_item("id", &node.id)?;
+    dict.set_item("name", &node.name)?;
+    dict.set_item("kind", format!("{:?}", node.kind))?;
+    dict.set_item("signature", &node.signature)?;
+    dict.set_item("doc", &node.doc)?;
+    dict.set_item("start_line", node.start_line)?;
+    dict.set_item("end_line", node.end_line)?;
+    let children = node
+        .children
+        .iter()
+        .map(|child| outline_dict(py, child))
+        .collect::<PyResult<Vec<_>>>()?;
+    dict.set_item("children", children)?;
+    Ok(dict)
+}
+
+#[cfg(feature = "python")]
+fn value_error(message: String) -> PyErr {
+    pyo3::exceptions::PyValueError::new_err(message)
+}
+
+#[cfg(feature = "python")]
+fn missing_symbol(symbol: &str) -> PyErr {
+    pyo3::exceptions::PyKeyError::new_err(format!("symbol '{symbol}' not found in index"))
+}
+
+#[cfg(feature = "python")]
+fn file_lookup_error(err: FileLookupError) -> PyErr {
+    pyo3::exceptions::PyValueError::new_err(err.to_string())
 }
 
 #[cfg(feature = "python")]
 pub fn register(m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_class::<HappyRepo>()?;
+    m.add_class::<PyRepositoryGraph>()?;
+    m.add_class::<PyBM25Index>()?;
     Ok(())
 }
@@ -0,0 +1,138 @@
+//! Advisory lock serializing cache writes across happycode processes.
+//!
+//! A chat session's watcher and `happycode index` (or two sessions on the
+//! same repo) can save the cache at the same time. Each file is replaced
+//! atomically, but interleaved saves would leave the elements of one run
+//! next to the metadata of another. Writers hold [`CacheLock`] for the
+//! whole save; readers never take it and instead check the generation each
+//! file was written with.
+
+use std::fs::{self, File, OpenOptions, TryLockError};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Lock file, relative to the repo root.
+pub const LOCK_FILE: &str = ".happy/index.lock";
+
+/// How long a writer waits for another process to finish its save.
+pub const LOCK_WAIT: Duration = Duration::from_secs(5);
+
+/// Pause between attempts while the lock is held elsewhere.
+const RETRY_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Why [`CacheLock::acquire`] gave up.
+#[derive(Debug, thiserror::Error)]
+pub enum LockError {
+    #[error("another happycode process is updating the index ({}); skipping", describe_holder(*holder))]
+    Busy {
+        /// Process ID recorded by the holder, when it could be read.
+        holder: Option<u32>,
+    },
+    #[error("failed to lock {}: {source}", path.display())]
+    Io { path: PathBuf, source: io::Error },
+}
+
+fn describe_holder(holder: Option<u32>) -> String {
+    match holder {
+        Some(pid) => format!("pid {pid}"),
+        None => "pid unknown".to_string(),
+    }
+}
+
+/// Exclusive right to write the cache under a repo's `.happy/`, released
+/// when dropped or when the process exits.
+#[derive(Debug)]
+pub struct CacheLock {
+    _file: File,
+}
+
+impl CacheLock {
+    /// Lock the cache of the repo at `repo_root`, waiting up to `wait` for
+    /// another holder to let go.
+    pub fn acquire(repo_root: &Path, wait: Duration) -> Result<Self, LockError> {
+        let path = repo_root.join(LOCK_FILE);
+        let io_error = |source| LockError::Io {
+            path: path.clone(),
+            source,
+        };
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(io_error)?;
+        }
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&path)
+            .map_err(io_error)?;
+
+        let started = Instant::now();
+        let mut logged = false;
+        loop {
+            match file.try_lock() {
+                Ok(()) => break,
+                Err(TryLockError::WouldBlock) => {
+                    let holder = read_holder(&path);
+                    if started.elapsed() >= wait {
+                        return Err(LockError::Busy { holder });
+                    }
+                    if !logged {
+                        tracing::info!(
+                            holder = %describe_holder(holder),
+                            "another happycode process is updating the index; retrying"
+                        );
+                        logged = true;
+                    }
+                    thread::sleep(RETRY_INTERVAL.min(wait.saturating_sub(started.elapsed())));
+                }
+                Err(TryLockError::Error(err)) => return Err(io_error(err)),
+            }
+        }
+
+        // Record who holds the lock for processes that find it busy.
+        file.set_len(0).map_err(io_error)?;
+        write!(file, "{}", std::process::id()).map_err(io_error)?;
+        Ok(Self { _file: file })
+    }
+}
+
+fn read_holder(path: &Path) -> Option<u32> {
+    fs::read_to_string(path).ok()?.trim().parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_second_writer_waits_then_gives_up() {
+        let dir = tempfile::tempdir().unwrap();
+        let lock = CacheLock::acquire(dir.path(), Duration::ZERO).unwrap();
+
+        let started = Instant::now();
+        let err = CacheLock::acquire(dir.path(), Duration::from_millis(120)).unwrap_err();
+        assert!(started.elapsed() >= Duration::from_millis(120));
+        let LockError::Busy { holder } = &err else {
+            panic!("expected a busy lock, got {err:?}");
+        };
+        assert_eq!(*holder, Some(std::process::id()));
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "another happycode process is updating the index (pid {}); skipping",
+                std::process::id()
+            )
+        );
+
+        // A waiting writer gets the lock once the holder lets go.
+        let waiter = {
+            let root = dir.path().to_path_buf();
+            thread::spawn(move || CacheLock::acquire(&root, Duration::from_secs(5)).is_ok())
+        };
+        thread::sleep(Duration::from_millis(100));
+        drop(lock);
+        assert!(waiter.join().unwrap());
+    }
+}
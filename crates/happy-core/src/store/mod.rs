@@ -1,123 +1,597 @@
+pub mod cache;
+pub mod lock;
+
+use std::collections::HashMap;
 use std::fs;
 use std::io;
 use std::path::Path;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Instant;
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
 
-use crate::vector::BM25Index;
+use crate::graph::renames::RenameMap;
+use crate::indexer::{CodeElement, ElementType, SourceText, share_file_contents};
+use crate::vector::{BM25Index, VectorIndex};
 
 /// Version header for serialized data format.
-const FORMAT_VERSION: u32 = 1;
+///
+/// v2 records the repo root alongside cached elements so the cache can be
+/// rebased when the repo moves; v1 files are still read. v3 stores weighted
+/// term frequencies in BM25 indexes and v4 splits identifiers into terms;
+/// older BM25 files must be rebuilt. v5 stamps cached elements and metadata
+/// with the generation of the save that wrote them. v6 records each
+/// element's enclosing class; older elements load without one. v7 stores
+/// each file's contents once, with element code as ranges into them. v8
+/// records the index options in cache metadata; older metadata is rebuilt.
+/// v9 compresses payloads and checksums them in the header. v10 adds
+/// `lossy_utf8` to the recorded index options; older metadata is rebuilt.
+/// v11 records the redaction settings in cache metadata; older metadata is
+/// rebuilt. v12 records the embedding model and the hash of each vector's
+/// source text; older vector files are embedded again.
+const FORMAT_VERSION: u32 = 12;
+
+/// Oldest format version a BM25 index can be read from.
+const MIN_BM25_FORMAT_VERSION: u32 = 4;
+
+/// Oldest format version that can still be read.
+const MIN_FORMAT_VERSION: u32 = 1;
+
+/// Store format versions this build writes and reads, reported to graph RPC
+/// clients checking compatibility.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct StoreFormat {
+    /// Version written by `save_*`.
+    pub version: u32,
+    /// Oldest version still read.
+    pub min_readable: u32,
+    /// Oldest version a BM25 index is read from.
+    pub min_bm25: u32,
+}
 
-/// Header written at the start of serialized files.
+pub const STORE_FORMAT: StoreFormat = StoreFormat {
+    version: FORMAT_VERSION,
+    min_readable: MIN_FORMAT_VERSION,
+    min_bm25: MIN_BM25_FORMAT_VERSION,
+};
+
+/// Header written at the start of serialized files. From v9 it is followed
+/// by a [`PayloadInfo`] within the header length.
 #[derive(serde::Serialize, serde::Deserialize)]
 struct StoreHeader {
     version: u32,
     kind: String,
 }
 
-/// Save a BM25 index to disk.
-pub fn save_bm25(index: &BM25Index, path: &Path) -> io::Result<()> {
-    let header = StoreHeader {
-        version: FORMAT_VERSION,
-        kind: "bm25".to_string(),
-    };
+/// How the payload after the header is encoded, and its checksum.
+#[derive(Debug, Serialize, Deserialize)]
+struct PayloadInfo {
+    compression: Compression,
+    /// blake3 hash of the payload as written.
+    checksum: [u8; 32],
+}
 
-    let header_bytes =
-        bincode::serialize(&header).map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
-    let data_bytes =
-        bincode::serialize(index).map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+enum Compression {
+    None,
+    Zstd,
+}
 
-    let mut output = Vec::new();
-    output.extend_from_slice(&(header_bytes.len() as u32).to_le_bytes());
-    output.extend_from_slice(&header_bytes);
-    output.extend_from_slice(&data_bytes);
+/// Payloads smaller than this are written uncompressed.
+const MIN_COMPRESSED_BYTES: usize = 4 * 1024;
 
-    // Atomic write: write to temp file, then rename
-    let temp_path = path.with_extension("tmp");
-    fs::write(&temp_path, &output)?;
-    fs::rename(&temp_path, path)?;
+/// zstd level for payloads; the default level, fast enough to save on
+/// every cache write.
+const ZSTD_LEVEL: i32 = 3;
 
-    Ok(())
+/// A store file that exists but cannot be trusted: truncated, failing its
+/// checksum, or not decompressible. See [`is_corrupt`].
+#[derive(Debug, thiserror::Error)]
+#[error("store file is corrupt: {0}")]
+struct CorruptStore(String);
+
+fn corrupt(reason: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, CorruptStore(reason.into()))
+}
+
+/// Whether a `load_*` error means the file is damaged, as opposed to
+/// missing or written by an incompatible version.
+pub fn is_corrupt(err: &io::Error) -> bool {
+    err.get_ref()
+        .is_some_and(|inner| inner.is::<CorruptStore>())
+}
+
+/// First format version whose element snapshots carry a generation.
+const GENERATION_FORMAT_VERSION: u32 = 5;
+
+/// First format version whose elements carry a `parent_id`.
+const PARENT_FORMAT_VERSION: u32 = 6;
+
+/// First format version whose element code is stored as source ranges.
+const SOURCES_FORMAT_VERSION: u32 = 7;
+
+/// First format version whose cache metadata records every current index
+/// option and the redaction fingerprint.
+const META_FORMAT_VERSION: u32 = 11;
+
+/// First format version whose header carries a [`PayloadInfo`].
+const CHECKSUM_FORMAT_VERSION: u32 = 9;
+
+/// First format version whose vector indexes record their model and text
+/// hashes.
+const VECTORS_FORMAT_VERSION: u32 = 12;
+
+/// Distinguishes temp files written concurrently by one process.
+static TEMP_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// Cached code elements together with the repo root they were indexed
+/// under. Each distinct source buffer is stored once in `sources`.
+#[derive(Serialize)]
+struct ElementsSnapshotRef<'a> {
+    repo_root: &'a str,
+    generation: u64,
+    sources: Vec<&'a str>,
+    elements: Vec<StoredElementRef<'a>>,
+}
+
+#[derive(Deserialize)]
+struct StoredSnapshot {
+    repo_root: String,
+    generation: u64,
+    sources: Vec<String>,
+    elements: Vec<StoredElement>,
+}
+
+/// Where an element's code lies in a snapshot's `sources`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CodeRange {
+    source: u32,
+    start: usize,
+    end: usize,
+}
+
+/// A [`CodeElement`] as written from v7, its code a [`CodeRange`].
+#[derive(Serialize)]
+struct StoredElementRef<'a> {
+    id: &'a str,
+    element_type: ElementType,
+    name: &'a str,
+    file_path: &'a str,
+    relative_path: &'a str,
+    language: &'a str,
+    start_line: usize,
+    end_line: usize,
+    code: CodeRange,
+    signature: &'a Option<String>,
+    docstring: &'a Option<String>,
+    summary: &'a Option<String>,
+    parent_id: &'a Option<String>,
+    metadata: &'a HashMap<String, String>,
+}
+
+#[derive(Deserialize)]
+struct StoredElement {
+    id: String,
+    element_type: ElementType,
+    name: String,
+    file_path: String,
+    relative_path: String,
+    language: String,
+    start_line: usize,
+    end_line: usize,
+    code: CodeRange,
+    signature: Option<String>,
+    docstring: Option<String>,
+    summary: Option<String>,
+    parent_id: Option<String>,
+    metadata: HashMap<String, String>,
+}
+
+impl StoredSnapshot {
+    /// Rebuild the elements, pointing their code into shared buffers.
+    fn into_snapshot(self) -> bincode::Result<ElementsSnapshot> {
+        let sources: Vec<Arc<str>> = self.sources.into_iter().map(Arc::from).collect();
+        let elements = self
+            .elements
+            .into_iter()
+            .map(|elem| -> bincode::Result<CodeElement> {
+                let range = &elem.code;
+                let code = sources
+                    .get(range.source as usize)
+                    .and_then(|buffer| {
+                        SourceText::from_buffer(Arc::clone(buffer), range.start..range.end)
+                    })
+                    .ok_or_else(|| {
+                        bincode::ErrorKind::Custom(format!("invalid code range for {}", elem.id))
+                    })?;
+                Ok(CodeElement {
+                    id: elem.id,
+                    element_type: elem.element_type,
+                    name: elem.name,
+                    file_path: elem.file_path,
+                    relative_path: elem.relative_path,
+                    language: elem.language,
+                    start_line: elem.start_line,
+                    end_line: elem.end_line,
+                    code,
+                    signature: elem.signature,
+                    docstring: elem.docstring,
+                    summary: elem.summary,
+                    parent_id: elem.parent_id,
+                    metadata: elem.metadata,
+                })
+            })
+            .collect::<bincode::Result<_>>()?;
+        Ok(ElementsSnapshot {
+            repo_root: self.repo_root,
+            generation: self.generation,
+            elements,
+        })
+    }
+}
+
+#[derive(Deserialize)]
+struct ElementsSnapshot<E = CodeElement> {
+    repo_root: String,
+    generation: u64,
+    elements: Vec<E>,
+}
+
+/// Layout of element snapshots before v5.
+#[derive(Deserialize)]
+struct ElementsSnapshotV2<E> {
+    repo_root: String,
+    elements: Vec<E>,
+}
+
+/// Layout of a [`CodeElement`] before v6.
+#[derive(Deserialize)]
+#[cfg_attr(test, derive(Serialize))]
+struct CodeElementV5 {
+    id: String,
+    element_type: crate::indexer::ElementType,
+    name: String,
+    file_path: String,
+    relative_path: String,
+    language: String,
+    start_line: usize,
+    end_line: usize,
+    code: String,
+    signature: Option<String>,
+    docstring: Option<String>,
+    summary: Option<String>,
+    metadata: std::collections::HashMap<String, String>,
+}
+
+impl From<CodeElementV5> for CodeElement {
+    fn from(elem: CodeElementV5) -> Self {
+        CodeElement {
+            id: elem.id,
+            element_type: elem.element_type,
+            name: elem.name,
+            file_path: elem.file_path,
+            relative_path: elem.relative_path,
+            language: elem.language,
+            start_line: elem.start_line,
+            end_line: elem.end_line,
+            code: elem.code.into(),
+            signature: elem.signature,
+            docstring: elem.docstring,
+            summary: elem.summary,
+            parent_id: None,
+            metadata: elem.metadata,
+        }
+    }
+}
+
+fn upgrade(elements: Vec<CodeElementV5>) -> Vec<CodeElement> {
+    elements.into_iter().map(CodeElement::from).collect()
+}
+
+/// Elements saved before v7 carry a copy of their code each; point it back
+/// into their file's contents.
+fn share_sources(mut snapshot: ElementsSnapshot) -> ElementsSnapshot {
+    share_file_contents(&mut snapshot.elements);
+    snapshot
+}
+
+/// Save a BM25 index to disk.
+pub fn save_bm25(index: &BM25Index, path: &Path) -> io::Result<()> {
+    write_store("bm25", index, path)
 }
 
 /// Load a BM25 index from disk.
 pub fn load_bm25(path: &Path) -> io::Result<BM25Index> {
-    let data = fs::read(path)?;
+    read_store_with(path, "bm25", |version, bytes| {
+        if version < MIN_BM25_FORMAT_VERSION {
+            return Err(Box::new(bincode::ErrorKind::Custom(format!(
+                "BM25 index format v{version} is out of date; rebuild it"
+            ))));
+        }
+        bincode::deserialize(bytes)
+    })
+}
 
-    if data.len() < 4 {
-        return Err(io::Error::new(io::ErrorKind::InvalidData, "file too small"));
+/// Save code elements indexed under `repo_root` to disk.
+pub fn save_elements(elements: &[CodeElement], repo_root: &Path, path: &Path) -> io::Result<()> {
+    save_elements_as(elements, repo_root, 0, path)
+}
+
+/// Like [`save_elements`], stamped with the cache `generation` being written.
+pub(crate) fn save_elements_as(
+    elements: &[CodeElement],
+    repo_root: &Path,
+    generation: u64,
+    path: &Path,
+) -> io::Result<()> {
+    let repo_root = repo_root.to_string_lossy();
+    // Buffers by address: elements of one file share theirs.
+    let mut source_ids: HashMap<*const u8, u32> = HashMap::new();
+    let mut sources = Vec::new();
+    let elements = elements
+        .iter()
+        .map(|elem| {
+            let buffer = elem.code.buffer();
+            let source = *source_ids
+                .entry(Arc::as_ptr(buffer).cast::<u8>())
+                .or_insert_with(|| {
+                    sources.push(&**buffer);
+                    (sources.len() - 1) as u32
+                });
+            let range = elem.code.buffer_range();
+            StoredElementRef {
+                id: &elem.id,
+                element_type: elem.element_type,
+                name: &elem.name,
+                file_path: &elem.file_path,
+                relative_path: &elem.relative_path,
+                language: &elem.language,
+                start_line: elem.start_line,
+                end_line: elem.end_line,
+                code: CodeRange {
+                    source,
+                    start: range.start,
+                    end: range.end,
+                },
+                signature: &elem.signature,
+                docstring: &elem.docstring,
+                summary: &elem.summary,
+                parent_id: &elem.parent_id,
+                metadata: &elem.metadata,
+            }
+        })
+        .collect();
+    let snapshot = ElementsSnapshotRef {
+        repo_root: &repo_root,
+        generation,
+        sources,
+        elements,
+    };
+    write_store("elements", &snapshot, path)
+}
+
+/// Load code elements from disk for the repo at `repo_root`.
+///
+/// If the cache was written for a different root (the repo was moved, or
+/// the cache was built elsewhere), each element's `file_path` is rebuilt
+/// from its `relative_path` under `repo_root`. v1 files carry no root and
+/// are always rebased.
+pub fn load_elements(path: &Path, repo_root: &Path) -> io::Result<Vec<CodeElement>> {
+    load_elements_with_generation(path, repo_root).map(|(elements, _)| elements)
+}
+
+/// Like [`load_elements`], also returning the generation the elements were
+/// saved with (0 for files older than v5).
+pub(crate) fn load_elements_with_generation(
+    path: &Path,
+    repo_root: &Path,
+) -> io::Result<(Vec<CodeElement>, u64)> {
+    let snapshot = read_store_with(path, "elements", |version, bytes| match version {
+        1 => bincode::deserialize(bytes).map(|elements| {
+            share_sources(ElementsSnapshot {
+                repo_root: String::new(),
+                generation: 0,
+                elements: upgrade(elements),
+            })
+        }),
+        SOURCES_FORMAT_VERSION.. => {
+            bincode::deserialize::<StoredSnapshot>(bytes).and_then(StoredSnapshot::into_snapshot)
+        }
+        PARENT_FORMAT_VERSION => bincode::deserialize::<ElementsSnapshot>(bytes).map(share_sources),
+        GENERATION_FORMAT_VERSION => bincode::deserialize::<ElementsSnapshot<CodeElementV5>>(bytes)
+            .map(|snapshot| {
+                share_sources(ElementsSnapshot {
+                    repo_root: snapshot.repo_root,
+                    generation: snapshot.generation,
+                    elements: upgrade(snapshot.elements),
+                })
+            }),
+        _ => bincode::deserialize::<ElementsSnapshotV2<CodeElementV5>>(bytes).map(|snapshot| {
+            share_sources(ElementsSnapshot {
+                repo_root: snapshot.repo_root,
+                generation: 0,
+                elements: upgrade(snapshot.elements),
+            })
+        }),
+    })?;
+    let mut elements = snapshot.elements;
+    if snapshot.repo_root != repo_root.to_string_lossy() {
+        rebase_elements(&mut elements, repo_root);
+        tracing::info!(
+            from = %snapshot.repo_root,
+            to = %repo_root.display(),
+            elements = elements.len(),
+            "rebased cached elements onto repo root"
+        );
     }
+    Ok((elements, snapshot.generation))
+}
 
-    let header_len = u32::from_le_bytes([data[0], data[1], data[2], data[3]]) as usize;
-    if data.len() < 4 + header_len {
-        return Err(io::Error::new(
-            io::ErrorKind::InvalidData,
-            "truncated header",
-        ));
+/// Point every element's `file_path` at its `relative_path` under `repo_root`.
+///
+/// Elements without a usable relative path (files indexed from outside the
+/// root) keep their absolute path.
+fn rebase_elements(elements: &mut [CodeElement], repo_root: &Path) {
+    for elem in elements {
+        let relative = Path::new(&elem.relative_path);
+        if elem.relative_path.is_empty() || relative.is_absolute() {
+            continue;
+        }
+        elem.file_path = repo_root.join(relative).to_string_lossy().to_string();
     }
+}
 
-    let header: StoreHeader = bincode::deserialize(&data[4..4 + header_len])
-        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+/// Save a vector index (embeddings) to disk.
+pub fn save_vectors(index: &VectorIndex, path: &Path) -> io::Result<()> {
+    write_store("vectors", index, path)
+}
 
-    if header.version != FORMAT_VERSION {
-        return Err(io::Error::new(
-            io::ErrorKind::InvalidData,
-            format!("unsupported format version: {}", header.version),
-        ));
-    }
+/// Load a vector index (embeddings) from disk.
+pub fn load_vectors(path: &Path) -> io::Result<VectorIndex> {
+    read_store_with(path, "vectors", |version, bytes| {
+        if version < VECTORS_FORMAT_VERSION {
+            return Err(Box::new(bincode::ErrorKind::Custom(format!(
+                "vector index format v{version} is out of date; embed again"
+            ))));
+        }
+        bincode::deserialize(bytes)
+    })
+}
 
-    let index: BM25Index = bincode::deserialize(&data[4 + header_len..])
-        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+/// Save the rename redirect map to disk.
+pub fn save_renames(renames: &RenameMap, path: &Path) -> io::Result<()> {
+    write_store("renames", renames, path)
+}
 
-    Ok(index)
+/// Load a rename redirect map from disk.
+pub fn load_renames(path: &Path) -> io::Result<RenameMap> {
+    read_store(path, "renames")
 }
 
-/// Save code elements to disk.
-pub fn save_elements(elements: &[crate::indexer::CodeElement], path: &Path) -> io::Result<()> {
+/// Serialize `value` behind a length-prefixed header and write it atomically.
+/// Payloads of [`MIN_COMPRESSED_BYTES`] or more are zstd-compressed.
+fn write_store<T: Serialize + ?Sized>(kind: &str, value: &T, path: &Path) -> io::Result<()> {
+    let started = Instant::now();
     let header = StoreHeader {
         version: FORMAT_VERSION,
-        kind: "elements".to_string(),
+        kind: kind.to_string(),
     };
 
-    let header_bytes =
-        bincode::serialize(&header).map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
-    let data_bytes =
-        bincode::serialize(elements).map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+    let mut data_bytes = bincode::serialize(value).map_err(io::Error::other)?;
+    let mut compression = Compression::None;
+    if data_bytes.len() >= MIN_COMPRESSED_BYTES {
+        data_bytes = zstd::encode_all(data_bytes.as_slice(), ZSTD_LEVEL)?;
+        compression = Compression::Zstd;
+    }
+    let info = PayloadInfo {
+        compression,
+        checksum: blake3::hash(&data_bytes).into(),
+    };
+    let header_bytes = bincode::serialize(&(&header, &info)).map_err(io::Error::other)?;
 
     let mut output = Vec::new();
     output.extend_from_slice(&(header_bytes.len() as u32).to_le_bytes());
     output.extend_from_slice(&header_bytes);
     output.extend_from_slice(&data_bytes);
 
-    let temp_path = path.with_extension("tmp");
-    fs::write(&temp_path, &output)?;
-    fs::rename(&temp_path, path)?;
+    // Atomic write: write to temp file, then rename. The temp name is unique
+    // per process and call so concurrent writers never share one.
+    let temp_path = path.with_extension(format!(
+        "{}.{}.tmp",
+        std::process::id(),
+        TEMP_COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+    if let Err(err) = fs::write(&temp_path, &output).and_then(|()| fs::rename(&temp_path, path)) {
+        let _ = fs::remove_file(&temp_path);
+        return Err(err);
+    }
+    tracing::debug!(
+        kind,
+        file = %path.display(),
+        bytes = output.len(),
+        duration_ms = started.elapsed().as_millis() as u64,
+        "saved store"
+    );
 
     Ok(())
 }
 
-/// Load code elements from disk.
-pub fn load_elements(path: &Path) -> io::Result<Vec<crate::indexer::CodeElement>> {
+/// Read a file written by `write_store`, checking version and kind.
+fn read_store<T: DeserializeOwned>(path: &Path, kind: &str) -> io::Result<T> {
+    read_store_with(path, kind, |_, bytes| bincode::deserialize(bytes))
+}
+
+/// Like `read_store`, with `decode` given the file's format version so
+/// payloads whose layout changed can be migrated.
+///
+/// From v9 the payload is checked against its checksum and decompressed
+/// before `decode` sees it, so a damaged file fails here with an error
+/// [`is_corrupt`] recognizes instead of deep in deserialization.
+fn read_store_with<T>(
+    path: &Path,
+    kind: &str,
+    decode: impl FnOnce(u32, &[u8]) -> bincode::Result<T>,
+) -> io::Result<T> {
+    let started = Instant::now();
     let data = fs::read(path)?;
 
     if data.len() < 4 {
-        return Err(io::Error::new(io::ErrorKind::InvalidData, "file too small"));
+        return Err(corrupt("file too small"));
     }
 
     let header_len = u32::from_le_bytes([data[0], data[1], data[2], data[3]]) as usize;
     if data.len() < 4 + header_len {
+        return Err(corrupt("truncated header"));
+    }
+
+    let header_bytes = &data[4..4 + header_len];
+    let header: StoreHeader =
+        bincode::deserialize(header_bytes).map_err(|e| corrupt(e.to_string()))?;
+
+    if !(MIN_FORMAT_VERSION..=FORMAT_VERSION).contains(&header.version) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("unsupported format version: {}", header.version),
+        ));
+    }
+    if header.kind != kind {
         return Err(io::Error::new(
             io::ErrorKind::InvalidData,
-            "truncated header",
+            format!("expected '{kind}' data, found '{}'", header.kind),
         ));
     }
 
-    let _header: StoreHeader = bincode::deserialize(&data[4..4 + header_len])
-        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    let payload = &data[4 + header_len..];
+    let decompressed;
+    let payload = if header.version >= CHECKSUM_FORMAT_VERSION {
+        let (_, info): (StoreHeader, PayloadInfo) =
+            bincode::deserialize(header_bytes).map_err(|e| corrupt(e.to_string()))?;
+        if blake3::hash(payload) != blake3::Hash::from(info.checksum) {
+            return Err(corrupt("checksum mismatch"));
+        }
+        match info.compression {
+            Compression::None => payload,
+            Compression::Zstd => {
+                decompressed = zstd::decode_all(payload).map_err(|e| corrupt(e.to_string()))?;
+                decompressed.as_slice()
+            }
+        }
+    } else {
+        payload
+    };
 
-    let elements: Vec<crate::indexer::CodeElement> = bincode::deserialize(&data[4 + header_len..])
+    let value = decode(header.version, payload)
         .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
-
-    Ok(elements)
+    tracing::debug!(
+        kind,
+        file = %path.display(),
+        bytes = data.len(),
+        duration_ms = started.elapsed().as_millis() as u64,
+        "loaded store"
+    );
+    Ok(value)
 }
 
 #[cfg(test)]
@@ -139,5 +613,366 @@ mod tests {
         assert_eq!(loaded.len(), 2);
         let results = loaded.search("hello", 5);
         assert!(!results.is_empty());
+
+        // Indexes written by an older tokenizer have to be rebuilt.
+        let mut bytes = fs::read(&path).unwrap();
+        let header = bincode::serialize(&StoreHeader {
+            version: 3,
+            kind: "bm25".to_string(),
+        })
+        .unwrap();
+        bytes.splice(4..4 + header.len(), header);
+        fs::write(&path, bytes).unwrap();
+        assert!(load_bm25(&path).is_err());
+    }
+
+    /// The header and payload info of a v9 store file.
+    fn payload_info(path: &Path) -> (StoreHeader, PayloadInfo) {
+        let bytes = fs::read(path).unwrap();
+        let header_len = u32::from_le_bytes(bytes[..4].try_into().unwrap()) as usize;
+        bincode::deserialize(&bytes[4..4 + header_len]).unwrap()
+    }
+
+    #[test]
+    fn test_large_payloads_are_compressed() {
+        let mut index = BM25Index::new();
+        for i in 0..500 {
+            index.add_document(&format!("doc{i}"), "def handle_request(session, payload)");
+        }
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("bm25.bin");
+        save_bm25(&index, &path).unwrap();
+
+        let (header, info) = payload_info(&path);
+        assert_eq!(header.version, FORMAT_VERSION);
+        assert_eq!(info.compression, Compression::Zstd);
+        let raw = bincode::serialize(&index).unwrap().len();
+        assert!(fs::metadata(&path).unwrap().len() < raw as u64);
+        assert_eq!(load_bm25(&path).unwrap().len(), 500);
+
+        // Small payloads are not worth compressing.
+        let renames = dir.path().join("renames.bin");
+        save_renames(&RenameMap::new(), &renames).unwrap();
+        assert_eq!(payload_info(&renames).1.compression, Compression::None);
+        assert!(load_renames(&renames).is_ok());
+    }
+
+    #[test]
+    fn test_corrupt_files_are_detected() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        let elements = index_fixture(&root);
+        let path = root.join("elements.bin");
+        save_elements(&elements, &root, &path).unwrap();
+        let bytes = fs::read(&path).unwrap();
+
+        // Truncated mid-payload.
+        fs::write(&path, &bytes[..bytes.len() - 10]).unwrap();
+        let err = load_elements(&path, &root).unwrap_err();
+        assert!(is_corrupt(&err), "{err}");
+
+        // One flipped byte in the payload.
+        let mut flipped = bytes.clone();
+        *flipped.last_mut().unwrap() ^= 0xff;
+        fs::write(&path, &flipped).unwrap();
+        let err = load_elements(&path, &root).unwrap_err();
+        assert!(is_corrupt(&err), "{err}");
+
+        // Truncated inside the header.
+        fs::write(&path, &bytes[..6]).unwrap();
+        assert!(is_corrupt(&load_elements(&path, &root).unwrap_err()));
+
+        fs::write(&path, &bytes).unwrap();
+        assert_eq!(load_elements(&path, &root).unwrap().len(), elements.len());
+    }
+
+    #[test]
+    fn test_unknown_versions_are_rejected_but_not_corrupt() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("renames.bin");
+        save_renames(&RenameMap::new(), &path).unwrap();
+
+        let (_, info) = payload_info(&path);
+        let header = bincode::serialize(&(
+            StoreHeader {
+                version: FORMAT_VERSION + 1,
+                kind: "renames".to_string(),
+            },
+            info,
+        ))
+        .unwrap();
+        let bytes = fs::read(&path).unwrap();
+        let header_len = u32::from_le_bytes(bytes[..4].try_into().unwrap()) as usize;
+        let mut future = (header.len() as u32).to_le_bytes().to_vec();
+        future.extend_from_slice(&header);
+        future.extend_from_slice(&bytes[4 + header_len..]);
+        fs::write(&path, future).unwrap();
+
+        let err = load_renames(&path).unwrap_err();
+        assert!(
+            err.to_string().contains("unsupported format version"),
+            "{err}"
+        );
+        assert!(!is_corrupt(&err));
+    }
+
+    #[test]
+    fn test_vectors_roundtrip() {
+        let mut index = VectorIndex::for_model(2, "nomic-embed-text");
+        index.insert_embedded("a", vec![1.0, 0.0], "load config");
+        index.insert("b", vec![0.0, 1.0]);
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("vectors.bin");
+
+        save_vectors(&index, &path).unwrap();
+        let loaded = load_vectors(&path).unwrap();
+
+        assert_eq!(loaded.len(), 2);
+        assert_eq!(loaded.dimension(), 2);
+        assert_eq!(loaded.model(), "nomic-embed-text");
+        assert!(loaded.is_current("a", "load config"));
+        assert_eq!(loaded.search(&[0.0, 1.0], 1, 0.0)[0].0, "b");
+        // Reading a file as the wrong kind is rejected.
+        assert!(load_bm25(&path).is_err());
+    }
+
+    #[test]
+    fn test_renames_roundtrip() {
+        let mut renames = RenameMap::new();
+        renames.record(crate::graph::renames::RenameRecord {
+            old_id: "func_load".to_string(),
+            new_id: "func_read_file".to_string(),
+            old_name: "load".to_string(),
+            new_name: "read_file".to_string(),
+            file_path: "a.py".to_string(),
+            renamed_at: 1,
+        });
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("renames.bin");
+
+        save_renames(&renames, &path).unwrap();
+        let loaded = load_renames(&path).unwrap();
+        assert_eq!(loaded.recent(1), renames.recent(1));
+    }
+
+    fn index_fixture(dir: &Path) -> Vec<CodeElement> {
+        std::fs::create_dir_all(dir.join("pkg")).unwrap();
+        std::fs::write(
+            dir.join("app.py"),
+            "from pkg.util import helper\n\ndef run():\n    return helper()\n",
+        )
+        .unwrap();
+        std::fs::write(dir.join("pkg/util.py"), "def helper():\n    return 1\n").unwrap();
+        crate::indexer::walk_and_index(&dir.to_string_lossy(), &Default::default())
+    }
+
+    #[test]
+    fn test_elements_rebased_after_repo_moves() {
+        let original = tempfile::tempdir().unwrap();
+        let original_root = original.path().canonicalize().unwrap();
+        let elements = index_fixture(&original_root);
+        let cache = original_root.join("elements.bin");
+        save_elements(&elements, &original_root, &cache).unwrap();
+
+        // Loading in place leaves paths untouched.
+        let loaded = load_elements(&cache, &original_root).unwrap();
+        assert_eq!(loaded.len(), elements.len());
+        assert!(
+            loaded
+                .iter()
+                .zip(&elements)
+                .all(|(a, b)| a.file_path == b.file_path)
+        );
+
+        let moved = tempfile::tempdir().unwrap();
+        let moved_root = moved.path().canonicalize().unwrap();
+        let loaded = load_elements(&cache, &moved_root).unwrap();
+        for elem in &loaded {
+            assert_eq!(
+                Path::new(&elem.file_path),
+                moved_root.join(&elem.relative_path),
+                "{}",
+                elem.name
+            );
+        }
+
+        let root = moved_root.to_string_lossy().to_string();
+        let mut graph = crate::graph::RepositoryGraph::new();
+        graph.build_from_elements(&loaded, &root);
+        let app = format!("{root}/app.py");
+        assert!(graph.file_paths().contains(&app));
+        let deps: Vec<String> = graph
+            .get_dependencies("app.py")
+            .unwrap()
+            .iter()
+            .map(|n| n.file_path.clone())
+            .collect();
+        assert_eq!(deps, vec![format!("{root}/pkg/util.py")]);
+    }
+
+    #[test]
+    fn test_v1_elements_are_migrated() {
+        let original = tempfile::tempdir().unwrap();
+        let elements = index_fixture(original.path());
+
+        // v1 wrote the bare element list behind a version 1 header.
+        let header = bincode::serialize(&StoreHeader {
+            version: 1,
+            kind: "elements".to_string(),
+        })
+        .unwrap();
+        let mut bytes = (header.len() as u32).to_le_bytes().to_vec();
+        bytes.extend_from_slice(&header);
+        bytes.extend_from_slice(&bincode::serialize(&v5_layout(&elements)).unwrap());
+        let dir = tempfile::tempdir().unwrap();
+        let cache = dir.path().join("elements.bin");
+        fs::write(&cache, bytes).unwrap();
+
+        let loaded = load_elements(&cache, dir.path()).unwrap();
+        assert_eq!(loaded.len(), elements.len());
+        assert!(
+            loaded
+                .iter()
+                .all(|elem| Path::new(&elem.file_path) == dir.path().join(&elem.relative_path))
+        );
+    }
+
+    fn v5_layout(elements: &[CodeElement]) -> Vec<CodeElementV5> {
+        elements
+            .iter()
+            .cloned()
+            .map(|elem| CodeElementV5 {
+                id: elem.id,
+                element_type: elem.element_type,
+                name: elem.name,
+                file_path: elem.file_path,
+                relative_path: elem.relative_path,
+                language: elem.language,
+                start_line: elem.start_line,
+                end_line: elem.end_line,
+                code: elem.code.into(),
+                signature: elem.signature,
+                docstring: elem.docstring,
+                summary: elem.summary,
+                metadata: elem.metadata,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_v5_elements_load_without_parents() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        let elements = index_fixture(&root);
+
+        let header = bincode::serialize(&StoreHeader {
+            version: GENERATION_FORMAT_VERSION,
+            kind: "elements".to_string(),
+        })
+        .unwrap();
+        #[derive(Serialize)]
+        struct SnapshotV5 {
+            repo_root: String,
+            generation: u64,
+            elements: Vec<CodeElementV5>,
+        }
+        let snapshot = SnapshotV5 {
+            repo_root: root.to_string_lossy().to_string(),
+            generation: 7,
+            elements: v5_layout(&elements),
+        };
+        let mut bytes = (header.len() as u32).to_le_bytes().to_vec();
+        bytes.extend_from_slice(&header);
+        bytes.extend_from_slice(&bincode::serialize(&snapshot).unwrap());
+        let cache = root.join("elements.bin");
+        fs::write(&cache, bytes).unwrap();
+
+        let (loaded, generation) = load_elements_with_generation(&cache, &root).unwrap();
+        assert_eq!(generation, 7);
+        assert_eq!(loaded.len(), elements.len());
+        assert!(loaded.iter().all(|elem| elem.parent_id.is_none()));
+    }
+
+    #[test]
+    fn test_parent_ids_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        std::fs::write(
+            root.join("service.py"),
+            "class Service:\n    def run(self):\n        return 1\n",
+        )
+        .unwrap();
+        let elements = crate::indexer::walk_and_index(&root.to_string_lossy(), &Default::default());
+        let cache = root.join("elements.bin");
+        save_elements(&elements, &root, &cache).unwrap();
+
+        let loaded = load_elements(&cache, &root).unwrap();
+        let class = loaded.iter().find(|e| e.name == "Service").unwrap();
+        let run = loaded.iter().find(|e| e.name == "run").unwrap();
+        assert_eq!(run.parent_id.as_deref(), Some(class.id.as_str()));
+    }
+
+    #[test]
+    fn test_element_sources_are_stored_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        let source = "def alpha():\n    return 1\n\ndef beta():\n    return alpha()\n";
+        std::fs::write(root.join("mod.py"), source).unwrap();
+        let elements = crate::indexer::walk_and_index(&root.to_string_lossy(), &Default::default());
+        let cache = root.join("elements.bin");
+        save_elements(&elements, &root, &cache).unwrap();
+
+        let loaded = load_elements(&cache, &root).unwrap();
+        assert_eq!(loaded.len(), elements.len());
+        let file = loaded
+            .iter()
+            .find(|e| e.element_type == ElementType::File)
+            .unwrap();
+        assert_eq!(file.code, source);
+        for (elem, original) in loaded.iter().zip(&elements) {
+            assert_eq!(elem.code, original.code);
+            assert!(Arc::ptr_eq(elem.code.buffer(), file.code.buffer()));
+        }
+    }
+
+    #[test]
+    fn test_v6_elements_share_file_contents() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        let elements = index_fixture(&root);
+
+        // v6 wrote each element with its own copy of its code.
+        let header = bincode::serialize(&StoreHeader {
+            version: PARENT_FORMAT_VERSION,
+            kind: "elements".to_string(),
+        })
+        .unwrap();
+        #[derive(Serialize)]
+        struct SnapshotV6<'a> {
+            repo_root: String,
+            generation: u64,
+            elements: &'a [CodeElement],
+        }
+        let snapshot = SnapshotV6 {
+            repo_root: root.to_string_lossy().to_string(),
+            generation: 3,
+            elements: &elements,
+        };
+        let mut bytes = (header.len() as u32).to_le_bytes().to_vec();
+        bytes.extend_from_slice(&header);
+        bytes.extend_from_slice(&bincode::serialize(&snapshot).unwrap());
+        let cache = root.join("elements.bin");
+        fs::write(&cache, bytes).unwrap();
+
+        let loaded = load_elements(&cache, &root).unwrap();
+        let util = loaded
+            .iter()
+            .find(|e| e.element_type == ElementType::File && e.relative_path == "pkg/util.py")
+            .unwrap();
+        let helper = loaded.iter().find(|e| e.name == "helper").unwrap();
+        assert!(helper.code.starts_with("def helper():"));
+        assert!(Arc::ptr_eq(helper.code.buffer(), util.code.buffer()));
     }
 }
@@ -0,0 +1,280 @@
+use std::io;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use serde::Deserialize;
+
+use super::{Summarizer, SummaryError};
+use crate::agent_toml::AgentToml;
+use crate::indexer::CodeElement;
+
+const SYSTEM_PROMPT: &str = "You summarize source code for a code search index. For each \
+numbered element, write one sentence of at most 25 words saying what it does and why a \
+developer would look for it. Use domain terms, not the identifier names. Reply with only a \
+JSON array of strings, one per element, in order.";
+
+/// The `[summaries]` table of `.happy/agent.toml`: how hard to drive the
+/// model. Which model, and the credentials to reach it, come from the
+/// user's own Codex config, never from the repository.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct SummaryConfig {
+    /// Elements per request.
+    pub batch_size: usize,
+    /// Requests started per minute; `0` for no limit.
+    pub requests_per_minute: u32,
+    /// Retries when a reply does not hold one summary per element.
+    pub max_retries: u32,
+    /// Initial retry delay; doubles on every attempt.
+    pub retry_backoff_ms: u64,
+    /// Code sent per element, in characters; longer code is cut.
+    pub max_code_chars: usize,
+}
+
+impl Default for SummaryConfig {
+    fn default() -> Self {
+        Self {
+            batch_size: 20,
+            requests_per_minute: 30,
+            max_retries: 3,
+            retry_backoff_ms: 1000,
+            max_code_chars: 2000,
+        }
+    }
+}
+
+impl SummaryConfig {
+    /// Load `[summaries]` from `<repo_root>/.happy/agent.toml`.
+    ///
+    /// Returns the defaults when the file or table is missing.
+    pub fn load(repo_root: &Path) -> io::Result<Self> {
+        Ok(AgentToml::load(repo_root)?
+            .section("summaries")?
+            .unwrap_or_default())
+    }
+}
+
+/// A chat model that answers one prompt at a time.
+///
+/// Implementations block until the reply is complete, so call them off the
+/// async runtime, and retry transport failures themselves.
+pub trait ChatModel: Send + Sync {
+    /// The model's reply to `prompt` under the system `instructions`.
+    fn complete(&self, instructions: &str, prompt: &str) -> Result<String, SummaryError>;
+}
+
+/// Summaries from a [`ChatModel`], one request per batch.
+///
+/// Requests are spaced to stay under `requests_per_minute`, and a reply that
+/// lost count of the elements is asked for again with backoff.
+pub struct LlmSummarizer<M> {
+    config: SummaryConfig,
+    model: M,
+    last_request: Mutex<Option<Instant>>,
+}
+
+impl<M: ChatModel> LlmSummarizer<M> {
+    pub fn new(config: SummaryConfig, model: M) -> Self {
+        Self {
+            config,
+            model,
+            last_request: Mutex::new(None),
+        }
+    }
+
+    pub fn config(&self) -> &SummaryConfig {
+        &self.config
+    }
+
+    /// Sleep until the next request fits the rate limit.
+    fn wait_for_slot(&self) {
+        if self.config.requests_per_minute == 0 {
+            return;
+        }
+        let interval = Duration::from_secs(60) / self.config.requests_per_minute;
+        let mut last = self
+            .last_request
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        if let Some(previous) = *last {
+            let elapsed = previous.elapsed();
+            if elapsed < interval {
+                std::thread::sleep(interval - elapsed);
+            }
+        }
+        *last = Some(Instant::now());
+    }
+
+    fn request_with_retry(
+        &self,
+        prompt: &str,
+        expected: usize,
+    ) -> Result<Vec<String>, SummaryError> {
+        let mut delay = Duration::from_millis(self.config.retry_backoff_ms);
+        let mut attempt = 0;
+        loop {
+            self.wait_for_slot();
+            let reply = self.model.complete(SYSTEM_PROMPT, prompt)?;
+            // A reply that lost count is worth asking again.
+            match parse_summaries(&reply, expected) {
+                Ok(summaries) => return Ok(summaries),
+                Err(message) if attempt < self.config.max_retries => {
+                    tracing::warn!(
+                        attempt = attempt + 1,
+                        delay_ms = delay.as_millis() as u64,
+                        error = %message,
+                        "unusable summary reply, retrying"
+                    );
+                    std::thread::sleep(delay);
+                    delay *= 2;
+                    attempt += 1;
+                }
+                Err(message) => return Err(SummaryError::Backend(message)),
+            }
+        }
+    }
+}
+
+impl<M: ChatModel> Summarizer for LlmSummarizer<M> {
+    fn summarize(&self, elements: &[&CodeElement]) -> Result<Vec<Option<String>>, SummaryError> {
+        if elements.is_empty() {
+            return Ok(Vec::new());
+        }
+        let prompt = batch_prompt(elements, self.config.max_code_chars);
+        let summaries = self.request_with_retry(&prompt, elements.len())?;
+        Ok(summaries
+            .into_iter()
+            .map(|s| Some(s).filter(|s| !s.trim().is_empty()))
+            .collect())
+    }
+
+    fn batch_size(&self) -> usize {
+        self.config.batch_size.max(1)
+    }
+}
+
+/// The user message for a batch: each element numbered, with its kind,
+/// location, docstring, and (cut) code.
+fn batch_prompt(elements: &[&CodeElement], max_code_chars: usize) -> String {
+    let mut prompt = String::new();
+    for (i, elem) in elements.iter().enumerate() {
+        let code = match elem.code.char_indices().nth(max_code_chars) {
+            Some((cut, _)) => format!("{}\n...", &elem.code[..cut]),
+            None => elem.code.to_string(),
+        };
+        prompt.push_str(&format!(
+            "[{}] {} {} in {}\n",
+            i + 1,
+            elem.element_type.as_str(),
+            elem.name,
+            elem.relative_path
+        ));
+        if let Some(doc) = elem.docstring.as_deref().filter(|d| !d.is_empty()) {
+            prompt.push_str(&format!("Docstring: {doc}\n"));
+        }
+        prompt.push_str(&format!("```{}\n{code}\n```\n\n", elem.language));
+    }
+    prompt
+}
+
+/// The JSON array of strings in `content`, which models sometimes wrap
+/// in a code fence or prose.
+fn parse_summaries(content: &str, expected: usize) -> Result<Vec<String>, String> {
+    let start = content.find('[').ok_or("reply has no JSON array")?;
+    let end = content.rfind(']').ok_or("reply has no JSON array")?;
+    let summaries: Vec<String> = serde_json::from_str(content.get(start..=end).unwrap_or(""))
+        .map_err(|e| format!("reply is not a JSON array of strings: {e}"))?;
+    if summaries.len() != expected {
+        return Err(format!(
+            "reply has {} summaries for {expected} elements",
+            summaries.len()
+        ));
+    }
+    Ok(summaries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indexer::ElementType;
+    use crate::summary::tests::element;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_parse_summaries() {
+        let reply = "Here you go:\n```json\n[\"Checks a token.\", \"\"]\n```";
+        assert_eq!(
+            parse_summaries(reply, 2).unwrap(),
+            vec!["Checks a token.".to_string(), String::new()]
+        );
+        assert!(parse_summaries(reply, 3).is_err());
+        assert!(parse_summaries("no idea", 1).is_err());
+    }
+
+    #[test]
+    fn test_load_config() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(
+            SummaryConfig::load(dir.path()).unwrap(),
+            SummaryConfig::default()
+        );
+        std::fs::create_dir_all(dir.path().join(".happy")).unwrap();
+        std::fs::write(
+            dir.path().join(".happy/agent.toml"),
+            "[summaries]\nrequests_per_minute = 0\n",
+        )
+        .unwrap();
+        let config = SummaryConfig::load(dir.path()).unwrap();
+        assert_eq!(config.requests_per_minute, 0);
+        assert_eq!(config.batch_size, 20);
+
+        // The repository cannot point summaries at a server of its choosing.
+        std::fs::write(
+            dir.path().join(".happy/agent.toml"),
+            "[summaries]\nendpoint = \"http://attacker.example\"\n",
+        )
+        .unwrap();
+        assert_eq!(
+            SummaryConfig::load(dir.path()).unwrap(),
+            SummaryConfig::default()
+        );
+    }
+
+    /// Replies in order, recording each prompt.
+    struct ScriptedModel {
+        replies: Mutex<Vec<&'static str>>,
+        prompts: Mutex<Vec<String>>,
+    }
+
+    impl ChatModel for ScriptedModel {
+        fn complete(&self, instructions: &str, prompt: &str) -> Result<String, SummaryError> {
+            assert_eq!(instructions, SYSTEM_PROMPT);
+            self.prompts.lock().unwrap().push(prompt.to_string());
+            Ok(self.replies.lock().unwrap().remove(0).to_string())
+        }
+    }
+
+    #[test]
+    fn test_retries_a_reply_that_lost_count() {
+        let model = ScriptedModel {
+            replies: Mutex::new(vec!["[\"One.\"]", "[\"Checks a token.\", \"\"]"]),
+            prompts: Mutex::new(Vec::new()),
+        };
+        let config = SummaryConfig {
+            requests_per_minute: 0,
+            retry_backoff_ms: 0,
+            ..SummaryConfig::default()
+        };
+        let summarizer = LlmSummarizer::new(config, model);
+        let check = element(ElementType::Function, "check_token", "", None);
+        let main = element(ElementType::Function, "main", "", None);
+        assert_eq!(
+            summarizer.summarize(&[&check, &main]).unwrap(),
+            vec![Some("Checks a token.".to_string()), None]
+        );
+        let prompts = summarizer.model.prompts.lock().unwrap();
+        assert_eq!(prompts.len(), 2);
+        assert!(prompts[0].contains("check_token"));
+    }
+}
@@ -0,0 +1,360 @@
+//! One-line summaries of code elements, kept in [`CodeElement::summary`].
+//!
+//! A [`Summarizer`] writes them in batches: [`HeuristicSummarizer`] from
+//! the docstring and signature, [`LlmSummarizer`] by asking a chat model.
+//! Summaries are saved with the element cache, searched by BM25, and
+//! preferred over the signature when embedding, so a query like "where do
+//! we validate JWTs" finds code that never spells it out.
+
+pub mod llm;
+
+pub use llm::{ChatModel, LlmSummarizer, SummaryConfig};
+
+use std::path::Path;
+
+use crate::indexer::{CodeElement, ElementType, IndexOptions};
+use crate::store::cache::{LoadOptions, LoadedIndex};
+
+/// Summaries longer than this are cut at a character boundary.
+pub const MAX_SUMMARY_CHARS: usize = 200;
+
+/// Errors produced while summarizing.
+#[derive(Debug, thiserror::Error)]
+pub enum SummaryError {
+    #[error("summary backend failed: {0}")]
+    Backend(String),
+    #[error("summary backend returned {got} summaries for {expected} elements")]
+    CountMismatch { expected: usize, got: usize },
+}
+
+/// Writes summaries for code elements.
+///
+/// Implementations return exactly one entry per element, in input order;
+/// `None` leaves that element without a summary.
+pub trait Summarizer: Send + Sync {
+    fn summarize(&self, elements: &[&CodeElement]) -> Result<Vec<Option<String>>, SummaryError>;
+
+    /// Elements per call to [`summarize`](Self::summarize).
+    fn batch_size(&self) -> usize {
+        64
+    }
+}
+
+/// Progress reported after each batch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SummaryProgress {
+    pub done: usize,
+    pub total: usize,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SummaryReport {
+    /// Elements given a summary.
+    pub summarized: usize,
+    /// Elements the summarizer had nothing to say about.
+    pub empty: usize,
+    /// Elements skipped because they already had a summary.
+    pub skipped_existing: usize,
+}
+
+/// Whether an element gets a summary: files and definitions, but not
+/// imports, variables, or constants.
+pub fn is_summarizable(elem: &CodeElement) -> bool {
+    matches!(
+        elem.element_type,
+        ElementType::File
+            | ElementType::Class
+            | ElementType::Function
+            | ElementType::Method
+            | ElementType::Interface
+            | ElementType::Struct
+            | ElementType::Enum
+            | ElementType::TypeAlias
+    )
+}
+
+/// Summarize `elements` in place, in batches of the summarizer's
+/// [`batch_size`](Summarizer::batch_size).
+///
+/// With `only_missing`, elements that already have a summary are left
+/// alone; otherwise they are rewritten, and cleared when the summarizer
+/// returns nothing. `progress` is called after each batch. A failed batch
+/// aborts the run; summaries from earlier batches stay in `elements`.
+pub fn summarize_in_batches(
+    summarizer: &dyn Summarizer,
+    elements: &mut [CodeElement],
+    only_missing: bool,
+    progress: &mut dyn FnMut(SummaryProgress),
+) -> Result<SummaryReport, SummaryError> {
+    let mut report = SummaryReport::default();
+    let mut pending = Vec::new();
+    for (i, elem) in elements.iter().enumerate() {
+        if !is_summarizable(elem) {
+            continue;
+        }
+        if only_missing && elem.summary.as_deref().is_some_and(|s| !s.is_empty()) {
+            report.skipped_existing += 1;
+            continue;
+        }
+        pending.push(i);
+    }
+
+    let total = pending.len();
+    let mut done = 0;
+    for batch in pending.chunks(summarizer.batch_size().max(1)) {
+        let inputs: Vec<&CodeElement> = batch.iter().map(|&i| &elements[i]).collect();
+        let summaries = summarizer.summarize(&inputs)?;
+        if summaries.len() != batch.len() {
+            return Err(SummaryError::CountMismatch {
+                expected: batch.len(),
+                got: summaries.len(),
+            });
+        }
+        for (&i, summary) in batch.iter().zip(summaries) {
+            let summary = summary.map(|s| clean_summary(&s)).filter(|s| !s.is_empty());
+            if summary.is_some() {
+                report.summarized += 1;
+            } else {
+                report.empty += 1;
+            }
+            elements[i].summary = summary;
+        }
+        done += batch.len();
+        progress(SummaryProgress { done, total });
+    }
+    Ok(report)
+}
+
+/// Summarize the cached elements of the repo at `repo_root` (indexing it
+/// first if the cache is stale, see
+/// [`load_or_index`](crate::store::cache::load_or_index)) and
+/// write them back to the cache. Summaries from batches that finished
+/// before a failure are saved too.
+pub fn summarize_cache(
+    repo_root: &Path,
+    use_cache: bool,
+    options: &IndexOptions,
+    summarizer: &dyn Summarizer,
+    only_missing: bool,
+    progress: &mut dyn FnMut(SummaryProgress),
+) -> Result<SummaryReport, SummaryError> {
+    let LoadedIndex {
+        mut elements, meta, ..
+    } = crate::store::cache::load_or_index(
+        repo_root,
+        &LoadOptions {
+            use_cache,
+            ..LoadOptions::new(options)
+        },
+    );
+    let result = summarize_in_batches(summarizer, &mut elements, only_missing, progress);
+    if !crate::store::cache::store_elements(repo_root, &elements, &meta) {
+        tracing::warn!(repo = %repo_root.display(), "summaries were not saved");
+    }
+    result
+}
+
+/// Collapse whitespace onto one line and cap the length.
+fn clean_summary(text: &str) -> String {
+    let line = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    match line.char_indices().nth(MAX_SUMMARY_CHARS) {
+        Some((cut, _)) => format!("{}...", line[..cut].trim_end()),
+        None => line,
+    }
+}
+
+/// Summaries from what the code already says: the first line of the
+/// docstring after the signature, or a file's leading comment.
+///
+/// Elements without documentation get no summary, leaving them for a
+/// model when the index is summarized again with `only_missing`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HeuristicSummarizer;
+
+impl HeuristicSummarizer {
+    pub fn summary(elem: &CodeElement) -> Option<String> {
+        if elem.element_type == ElementType::File {
+            return leading_comment(&elem.code);
+        }
+        let doc = elem.docstring.as_deref().and_then(first_sentence)?;
+        let signature = elem
+            .signature
+            .as_deref()
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .unwrap_or(&elem.name);
+        Some(format!("{signature} — {doc}"))
+    }
+}
+
+impl Summarizer for HeuristicSummarizer {
+    fn summarize(&self, elements: &[&CodeElement]) -> Result<Vec<Option<String>>, SummaryError> {
+        Ok(elements.iter().map(|elem| Self::summary(elem)).collect())
+    }
+
+    fn batch_size(&self) -> usize {
+        1024
+    }
+}
+
+/// The first non-empty line of `doc`, skipping reST/Javadoc tags.
+fn first_sentence(doc: &str) -> Option<String> {
+    doc.lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty() && !line.starts_with('@') && !line.starts_with(':'))
+        .map(str::to_string)
+}
+
+/// The module docstring or first comment of a file, skipping a shebang,
+/// blank lines, and license headers.
+fn leading_comment(code: &str) -> Option<String> {
+    let mut lines = code.lines().map(str::trim).peekable();
+    while lines
+        .peek()
+        .is_some_and(|line| line.is_empty() || line.starts_with("#!"))
+    {
+        lines.next();
+    }
+    let first = lines.next()?;
+    for quote in ["\"\"\"", "'''"] {
+        if let Some(rest) = first.strip_prefix(quote) {
+            let rest = rest.trim_end_matches(quote).trim();
+            if !rest.is_empty() {
+                return Some(rest.to_string());
+            }
+            return lines
+                .find(|line| !line.is_empty())
+                .filter(|line| !line.starts_with(quote))
+                .map(str::to_string);
+        }
+    }
+    let comment_text = |line: &str| -> Option<String> {
+        let text = ["//!", "///", "//", "/**", "/*", "*", "#"]
+            .iter()
+            .find_map(|marker| line.strip_prefix(marker))?;
+        Some(text.trim().trim_end_matches("*/").trim().to_string())
+    };
+    std::iter::once(first)
+        .chain(lines)
+        .map_while(comment_text)
+        .find(|text| {
+            let lower = text.to_ascii_lowercase();
+            !text.is_empty()
+                && !lower.contains("copyright")
+                && !lower.contains("license")
+                && !lower.starts_with("spdx")
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use std::collections::HashMap;
+
+    pub(super) fn element(
+        kind: ElementType,
+        name: &str,
+        code: &str,
+        docstring: Option<&str>,
+    ) -> CodeElement {
+        CodeElement {
+            id: format!("{}_{name}", kind.as_str()),
+            element_type: kind,
+            name: name.into(),
+            file_path: "/repo/auth.py".into(),
+            relative_path: "auth.py".into(),
+            language: "python".into(),
+            start_line: 1,
+            end_line: 3,
+            code: code.into(),
+            signature: (kind != ElementType::File).then(|| format!("def {name}(token)")),
+            docstring: docstring.map(str::to_string),
+            summary: None,
+            parent_id: None,
+            metadata: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn test_heuristic_summaries() {
+        let func = element(
+            ElementType::Function,
+            "verify",
+            "",
+            Some("\nCheck a JWT's signature and expiry.\n\n:param token: the JWT"),
+        );
+        assert_eq!(
+            HeuristicSummarizer::summary(&func).as_deref(),
+            Some("def verify(token) — Check a JWT's signature and expiry.")
+        );
+        let bare = element(ElementType::Function, "helper", "", None);
+        assert_eq!(HeuristicSummarizer::summary(&bare), None);
+
+        let file = |code: &str| {
+            HeuristicSummarizer::summary(&element(ElementType::File, "auth.py", code, None))
+        };
+        assert_eq!(
+            file("#!/usr/bin/env python\n\"\"\"Token auth helpers.\"\"\"\n").as_deref(),
+            Some("Token auth helpers.")
+        );
+        assert_eq!(
+            file("// Copyright 2024 Example\n// Routes for the billing API.\n").as_deref(),
+            Some("Routes for the billing API.")
+        );
+        assert_eq!(
+            file("//! Session tokens.\n//!\n//! More.\n").as_deref(),
+            Some("Session tokens.")
+        );
+        assert_eq!(file("import os\n"), None);
+    }
+
+    struct Fixed(&'static str);
+
+    impl Summarizer for Fixed {
+        fn summarize(
+            &self,
+            elements: &[&CodeElement],
+        ) -> Result<Vec<Option<String>>, SummaryError> {
+            Ok(elements.iter().map(|_| Some(self.0.to_string())).collect())
+        }
+
+        fn batch_size(&self) -> usize {
+            2
+        }
+    }
+
+    #[test]
+    fn test_summarize_only_missing() {
+        let mut elements = vec![
+            element(ElementType::Function, "a", "", None),
+            element(ElementType::Import, "os", "", None),
+            element(ElementType::Function, "b", "", None),
+            element(ElementType::Function, "c", "", None),
+        ];
+        elements[2].summary = Some("kept".into());
+
+        let mut batches = Vec::new();
+        let report = summarize_in_batches(&Fixed("new\n  line"), &mut elements, true, &mut |p| {
+            batches.push(p.done)
+        })
+        .unwrap();
+        assert_eq!(
+            report,
+            SummaryReport {
+                summarized: 2,
+                empty: 0,
+                skipped_existing: 1,
+            }
+        );
+        assert_eq!(batches, vec![2]);
+        let summaries: Vec<Option<&str>> = elements.iter().map(|e| e.summary.as_deref()).collect();
+        assert_eq!(
+            summaries,
+            vec![Some("new line"), None, Some("kept"), Some("new line")]
+        );
+
+        summarize_in_batches(&Fixed("redo"), &mut elements, false, &mut |_| {}).unwrap();
+        assert_eq!(elements[2].summary.as_deref(), Some("redo"));
+    }
+}
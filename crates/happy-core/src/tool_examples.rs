@@ -0,0 +1,267 @@
+//! Example invocations appended to the code graph tool descriptions.
+//!
+//! A model picks better arguments when a description shows a call that works
+//! against the repo it is looking at. Examples are sampled from the index:
+//! the symbol with the most callers, the class with the most subclasses, a
+//! real element ID, and the first indexed file. Sampling is deterministic, so
+//! the same index always renders the same descriptions; before an index is
+//! ready the generic examples from [`ToolExamples::fallback`] are used.
+//! Sessions sample once and keep those examples, so the tool list does not
+//! change between requests and the prompt prefix stays cacheable.
+
+use std::collections::HashSet;
+use std::path::Path;
+
+use petgraph::Direction;
+use petgraph::stable_graph::NodeIndex;
+use petgraph::visit::EdgeRef;
+use serde_json::{Map, Value};
+
+use crate::graph::RepositoryGraph;
+use crate::graph::types::{EdgeKind, NodeKind};
+use crate::tool_spec::ToolSpec;
+use crate::utils::relative_to;
+
+/// Longest rendered example arguments; longer ones use the generic example.
+pub const MAX_EXAMPLE_LEN: usize = 160;
+
+/// Argument values used to render one example per tool.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ToolExamples {
+    /// Graph generation the values were sampled from, `None` for the fallback.
+    pub generation: Option<u64>,
+    symbol: String,
+    class: String,
+    element_id: String,
+    path: (String, String),
+    file_path: String,
+}
+
+impl ToolExamples {
+    /// Generic examples for when no index is available.
+    pub fn fallback() -> Self {
+        Self {
+            generation: None,
+            symbol: "parse_config".to_string(),
+            class: "BaseHandler".to_string(),
+            element_id: "function_0123456789abcdef".to_string(),
+            path: ("main".to_string(), "parse_config".to_string()),
+            file_path: "src/main.py".to_string(),
+        }
+    }
+
+    /// Sample example values from an indexed graph.
+    ///
+    /// Values the graph has nothing for (no classes, no calls) keep their
+    /// generic fallback.
+    pub fn sample(graph: &RepositoryGraph, repo_root: &str) -> Self {
+        let mut examples = Self::fallback();
+        examples.generation = Some(graph.generation());
+
+        let callable = [NodeKind::Function, NodeKind::Method];
+        if let Some(idx) = most_referenced(graph, EdgeKind::Calls, &callable) {
+            let node = &graph.graph[idx];
+            examples.symbol = node.name.to_string();
+            examples.element_id = node.id.clone();
+            let caller = graph
+                .graph
+                .edges_directed(idx, Direction::Incoming)
+                .filter(|edge| edge.weight().kind == EdgeKind::Calls)
+                .map(|edge| &graph.graph[edge.source()])
+                .min_by(|a, b| (&a.name, &a.id).cmp(&(&b.name, &b.id)));
+            if let Some(caller) = caller {
+                examples.path = (caller.name.to_string(), node.name.to_string());
+            }
+        }
+
+        let classes = [NodeKind::Class, NodeKind::Interface, NodeKind::Struct];
+        if let Some(idx) = most_referenced(graph, EdgeKind::Inherits, &classes) {
+            examples.class = graph.graph[idx].name.to_string();
+        }
+
+        let first_file = graph
+            .file_to_nodes
+            .iter()
+            .map(|entry| relative_to(Path::new(repo_root), entry.key()))
+            .min();
+        if let Some(file_path) = first_file {
+            examples.file_path = file_path;
+        }
+        examples
+    }
+
+    /// The tool description followed by an example invocation, if the tool
+    /// takes arguments.
+    pub fn describe(&self, tool: &ToolSpec) -> String {
+        match self
+            .example(tool)
+            .or_else(|| Self::fallback().example(tool))
+        {
+            Some(example) => format!("{}\nExample: {example}", tool.description),
+            None => tool.description.to_string(),
+        }
+    }
+
+    /// Example arguments for `tool` as a JSON object string.
+    ///
+    /// Only required parameters are filled in. Returns `None` for tools
+    /// without required parameters and for examples over [`MAX_EXAMPLE_LEN`].
+    pub fn example(&self, tool: &ToolSpec) -> Option<String> {
+        let mut args = Map::new();
+        for param in tool.params.iter().filter(|p| p.required) {
+            let value = match (tool.name, param.name) {
+                ("get_code_source", "symbol") => self.element_id.clone(),
+                ("get_subclasses" | "get_superclasses", "symbol") => self.class.clone(),
+                ("add_bookmark", "note") => "Start reading here".to_string(),
+                ("rlm_analyze", "query") => format!("How is {} used?", self.symbol),
+                (_, "symbol" | "query") => self.symbol.clone(),
+                (_, "file_path") => self.file_path.clone(),
+                (_, "source") => self.path.0.clone(),
+                (_, "target") => self.path.1.clone(),
+                _ => return None,
+            };
+            args.insert(param.name.to_string(), Value::String(value));
+        }
+        if args.is_empty() {
+            return None;
+        }
+        let rendered = Value::Object(args).to_string();
+        (rendered.len() <= MAX_EXAMPLE_LEN).then_some(rendered)
+    }
+}
+
+/// The node of one of `kinds` with the most distinct incoming `edge_kind`
+/// neighbours. Ties go to the smallest name, then ID.
+fn most_referenced(
+    graph: &RepositoryGraph,
+    edge_kind: EdgeKind,
+    kinds: &[NodeKind],
+) -> Option<NodeIndex> {
+    graph
+        .graph
+        .node_indices()
+        .filter(|&idx| kinds.contains(&graph.graph[idx].kind))
+        .map(|idx| {
+            let sources: HashSet<NodeIndex> = graph
+                .graph
+                .edges_directed(idx, Direction::Incoming)
+                .filter(|edge| edge.weight().kind == edge_kind)
+                .map(|edge| edge.source())
+                .collect();
+            (idx, sources.len())
+        })
+        .min_by(|(a, a_count), (b, b_count)| {
+            let (a, b) = (&graph.graph[*a], &graph.graph[*b]);
+            b_count
+                .cmp(a_count)
+                .then_with(|| (&a.name, &a.id).cmp(&(&b.name, &b.id)))
+        })
+        .map(|(idx, _)| idx)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tool_spec::{CODE_GRAPH_TOOLS, find_tool};
+    use serde_json::json;
+
+    fn build_fixture(root: &str) -> RepositoryGraph {
+        let mut repo = RepositoryGraph::new();
+        repo.build_from_elements(
+            &crate::indexer::walk_and_index(root, &Default::default()),
+            root,
+        );
+        repo
+    }
+
+    fn fixture_root(dir: &tempfile::TempDir) -> String {
+        let files = [
+            (
+                "app.py",
+                "from auth import login, Base\n\nclass Admin(Base):\n    pass\n\ndef run():\n    return login()\n\ndef retry():\n    return login()\n",
+            ),
+            (
+                "auth.py",
+                "class Base:\n    pass\n\ndef login():\n    return 1\n",
+            ),
+            (
+                "cli.py",
+                "from auth import login\n\ndef main():\n    return login()\n",
+            ),
+        ];
+        for (name, code) in files {
+            std::fs::write(dir.path().join(name), code).unwrap();
+        }
+        dir.path()
+            .canonicalize()
+            .unwrap()
+            .to_string_lossy()
+            .to_string()
+    }
+
+    fn example_args(examples: &ToolExamples, tool: &str) -> Value {
+        let rendered = examples.example(find_tool(tool).unwrap()).unwrap();
+        serde_json::from_str(&rendered).unwrap()
+    }
+
+    #[test]
+    fn test_examples_reference_indexed_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = fixture_root(&dir);
+        let repo = build_fixture(&root);
+        let examples = ToolExamples::sample(&repo, &root);
+        assert_eq!(examples.generation, Some(repo.generation()));
+
+        let symbol = example_args(&examples, "find_callers")["symbol"].clone();
+        assert_eq!(symbol, "login");
+        assert_eq!(repo.find_callers("login").len(), 3);
+
+        let id = example_args(&examples, "get_code_source")["symbol"].clone();
+        assert!(repo.element_arena.contains_key(id.as_str().unwrap()));
+
+        let class = example_args(&examples, "get_subclasses")["symbol"].clone();
+        assert_eq!(class, "Base");
+
+        let file = example_args(&examples, "get_dependencies")["file_path"].clone();
+        assert_eq!(file, "app.py");
+        assert!(repo.resolve_file(file.as_str().unwrap()).is_ok());
+
+        let path = example_args(&examples, "find_code_path");
+        assert_eq!(path, json!({ "source": "main", "target": "login" }));
+
+        let description = examples.describe(find_tool("find_callers").unwrap());
+        assert!(description.ends_with(r#"Example: {"symbol":"login"}"#));
+    }
+
+    #[test]
+    fn test_examples_are_deterministic() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = fixture_root(&dir);
+        let first = ToolExamples::sample(&build_fixture(&root), &root);
+        let second = ToolExamples::sample(&build_fixture(&root), &root);
+        for tool in CODE_GRAPH_TOOLS {
+            assert_eq!(first.describe(tool), second.describe(tool), "{}", tool.name);
+        }
+    }
+
+    #[test]
+    fn test_fallback_examples() {
+        let fallback = ToolExamples::fallback();
+        assert_eq!(fallback.generation, None);
+        for tool in CODE_GRAPH_TOOLS {
+            let description = fallback.describe(tool);
+            assert!(description.starts_with(tool.description), "{}", tool.name);
+            assert_eq!(
+                description.contains("\nExample: "),
+                !tool.required_params().is_empty(),
+                "{}",
+                tool.name
+            );
+        }
+
+        // An empty index has nothing to sample, so every value stays generic.
+        let empty = ToolExamples::sample(&RepositoryGraph::new(), "");
+        let tool = find_tool("get_dependencies").unwrap();
+        assert_eq!(empty.describe(tool), fallback.describe(tool));
+    }
+}
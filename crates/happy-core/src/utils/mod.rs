@@ -5,6 +5,14 @@ pub fn normalize_path(path: &str) -> String {
     path.replace('\\', "/").trim_end_matches('/').to_string()
 }
 
+/// `path` relative to `root` for display, or unchanged when it lies outside.
+pub fn relative_to(root: &Path, path: &str) -> String {
+    Path::new(path)
+        .strip_prefix(root)
+        .map(|p| p.to_string_lossy().to_string())
+        .unwrap_or_else(|_| path.to_string())
+}
+
 /// Convert a file path to a dotted module path (Python-style).
 /// e.g., "src/app/services/auth.py" -> "src.app.services.auth"
 pub fn file_path_to_module_path(file_path: &str, repo_root: &str) -> Option<String> {
@@ -22,6 +30,9 @@ pub fn file_path_to_module_path(file_path: &str, repo_root: &str) -> Option<Stri
         .or_else(|| relative_str.strip_suffix(".rs"))
         .or_else(|| relative_str.strip_suffix(".go"))
         .or_else(|| relative_str.strip_suffix(".java"))
+        .or_else(|| relative_str.strip_suffix(".cs"))
+        .or_else(|| relative_str.strip_suffix(".swift"))
+        .or_else(|| relative_str.strip_suffix(".kt"))
         .unwrap_or(relative_str);
 
     // Convert path separators to dots
@@ -48,12 +59,60 @@ pub fn generate_element_id(type_: &str, parts: &[&str]) -> String {
     format!("{}_{}", type_, &hex[..16])
 }
 
-/// Simple whitespace tokenizer for BM25.
+/// Code-aware tokenizer for BM25, dropping single-character tokens.
+///
+/// See [`tokenize_with`].
 pub fn tokenize(text: &str) -> Vec<String> {
-    text.to_lowercase()
-        .split_whitespace()
-        .map(|s| s.to_string())
-        .collect()
+    tokenize_with(text, true)
+}
+
+/// Split `text` into lowercased tokens at whitespace and punctuation (`::`,
+/// `.`, `/`, brackets, ...). Identifiers made of several words yield each
+/// word followed by the whole identifier, so `resolve_symbol` becomes
+/// `resolve`, `symbol`, `resolve_symbol` and `BM25Index` becomes `bm25`,
+/// `index`, `bm25index`.
+pub fn tokenize_with(text: &str, drop_single_chars: bool) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut push = |token: String| {
+        if !drop_single_chars || token.chars().nth(1).is_some() {
+            tokens.push(token);
+        }
+    };
+    for word in text
+        .split(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .filter(|w| !w.is_empty())
+    {
+        let parts = split_identifier(word);
+        let compound = parts.len() > 1;
+        for part in parts {
+            push(part);
+        }
+        if compound {
+            push(word.to_lowercase());
+        }
+    }
+    tokens
+}
+
+/// Lowercased words of a snake_case or camelCase identifier
+/// (`parseConfig`, `parse_config` -> `parse`, `config`).
+pub fn split_identifier(word: &str) -> Vec<String> {
+    let mut parts = Vec::new();
+    for chunk in word.split('_').filter(|c| !c.is_empty()) {
+        let mut current = String::new();
+        let mut prev_lower = false;
+        for c in chunk.chars() {
+            if c.is_uppercase() && prev_lower && !current.is_empty() {
+                parts.push(std::mem::take(&mut current));
+            }
+            prev_lower = c.is_lowercase() || c.is_ascii_digit();
+            current.extend(c.to_lowercase());
+        }
+        if !current.is_empty() {
+            parts.push(current);
+        }
+    }
+    parts
 }
 
 #[cfg(test)]
@@ -72,6 +131,14 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_relative_to() {
+        let root = Path::new("/repo");
+        assert_eq!(relative_to(root, "/repo/src/app.py"), "src/app.py");
+        assert_eq!(relative_to(root, "/other/app.py"), "/other/app.py");
+        assert_eq!(relative_to(Path::new(""), "src/app.py"), "src/app.py");
+    }
+
     #[test]
     fn test_generate_element_id() {
         let id = generate_element_id("function", &["src/main.py", "MyClass", "process"]);
@@ -83,5 +150,25 @@ mod tests {
     fn test_tokenize() {
         let tokens = tokenize("Hello World FOO");
         assert_eq!(tokens, vec!["hello", "world", "foo"]);
+
+        assert_eq!(
+            tokenize("resolve_symbol"),
+            vec!["resolve", "symbol", "resolve_symbol"]
+        );
+        assert_eq!(tokenize("BM25Index"), vec!["bm25", "index", "bm25index"]);
+        assert_eq!(
+            tokenize("graph::queries.find_path(a, src/main.rs)"),
+            vec![
+                "graph",
+                "queries",
+                "find",
+                "path",
+                "find_path",
+                "src",
+                "main",
+                "rs"
+            ]
+        );
+        assert_eq!(tokenize_with("x = y.z", false), vec!["x", "y", "z"]);
     }
 }
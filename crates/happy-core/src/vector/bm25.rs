@@ -1,15 +1,48 @@
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+
+use crate::indexer::CodeElement;
+
+/// Per-field weights applied by [`BM25Index::add_element`].
+///
+/// A token in a boosted field counts as `boost` occurrences, so a match on
+/// an element's name outranks the same words appearing once in a body.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FieldBoosts {
+    pub name: f64,
+    pub signature: f64,
+    pub docstring: f64,
+    pub summary: f64,
+    pub code: f64,
+}
+
+impl Default for FieldBoosts {
+    fn default() -> Self {
+        Self {
+            name: 3.0,
+            signature: 2.0,
+            docstring: 1.5,
+            summary: 1.5,
+            code: 1.0,
+        }
+    }
+}
+
+/// Default BM25 term frequency saturation.
+pub const DEFAULT_K1: f64 = 1.5;
+/// Default BM25 document length normalization.
+pub const DEFAULT_B: f64 = 0.75;
 
 /// BM25 keyword search index.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BM25Index {
-    /// Document ID -> tokenized terms
-    documents: HashMap<String, Vec<String>>,
+    /// Document ID -> weighted term frequencies
+    documents: HashMap<String, HashMap<String, f64>>,
     /// Term -> document IDs containing it
     inverted_index: HashMap<String, Vec<String>>,
-    /// Document ID -> document length
-    doc_lengths: HashMap<String, usize>,
+    /// Document ID -> weighted document length
+    doc_lengths: HashMap<String, f64>,
     /// Average document length
     avg_doc_len: f64,
     /// Total number of documents
@@ -17,81 +50,160 @@ pub struct BM25Index {
     /// BM25 parameters
     k1: f64,
     b: f64,
+    /// Field weights used by `add_element`
+    boosts: FieldBoosts,
 }
 
 impl BM25Index {
     pub fn new() -> Self {
+        Self::with_boosts(FieldBoosts::default())
+    }
+
+    /// An empty index that weights element fields with `boosts`.
+    pub fn with_boosts(boosts: FieldBoosts) -> Self {
+        Self::with_parameters(DEFAULT_K1, DEFAULT_B, boosts)
+    }
+
+    /// An empty index with explicit BM25 `k1` (term frequency saturation)
+    /// and `b` (length normalization) parameters.
+    pub fn with_parameters(k1: f64, b: f64, boosts: FieldBoosts) -> Self {
         Self {
             documents: HashMap::new(),
             inverted_index: HashMap::new(),
             doc_lengths: HashMap::new(),
             avg_doc_len: 0.0,
             num_docs: 0,
-            k1: 1.5,
-            b: 0.75,
+            k1,
+            b,
+            boosts,
         }
     }
 
     /// Add a document to the index.
     pub fn add_document(&mut self, doc_id: &str, text: &str) {
-        let tokens = crate::utils::tokenize(text);
-        let doc_len = tokens.len();
+        self.add_document_fields(doc_id, &[("text", text, 1.0)]);
+    }
+
+    /// Add a document made of `(field, text, boost)` parts.
+    ///
+    /// Each token counts `boost` times towards its term frequency and the
+    /// document length. The field name is descriptive only; all fields share
+    /// one term space. Re-adding an existing ID replaces the document.
+    pub fn add_document_fields(&mut self, doc_id: &str, fields: &[(&str, &str, f64)]) {
+        self.remove_document(doc_id);
 
-        for token in &tokens {
+        let mut terms: HashMap<String, f64> = HashMap::new();
+        for (_field, text, boost) in fields {
+            for token in crate::utils::tokenize(text) {
+                *terms.entry(token).or_insert(0.0) += boost;
+            }
+        }
+        let doc_len: f64 = terms.values().sum();
+
+        for token in terms.keys() {
             self.inverted_index
                 .entry(token.clone())
                 .or_default()
                 .push(doc_id.to_string());
         }
 
-        self.documents.insert(doc_id.to_string(), tokens);
+        self.documents.insert(doc_id.to_string(), terms);
         self.doc_lengths.insert(doc_id.to_string(), doc_len);
         self.num_docs += 1;
+        self.update_avg_doc_len();
+    }
 
-        // Recalculate average document length
-        let total_len: usize = self.doc_lengths.values().sum();
-        self.avg_doc_len = total_len as f64 / self.num_docs as f64;
+    /// Add a code element, weighting its name, signature, docstring,
+    /// summary, and code by the index's [`FieldBoosts`].
+    pub fn add_element(&mut self, elem: &CodeElement) {
+        let boosts = self.boosts;
+        self.add_document_fields(
+            &elem.id,
+            &[
+                ("name", &elem.name, boosts.name),
+                (
+                    "signature",
+                    elem.signature.as_deref().unwrap_or(""),
+                    boosts.signature,
+                ),
+                (
+                    "docstring",
+                    elem.docstring.as_deref().unwrap_or(""),
+                    boosts.docstring,
+                ),
+                (
+                    "summary",
+                    elem.summary.as_deref().unwrap_or(""),
+                    boosts.summary,
+                ),
+                ("code", &elem.code, boosts.code),
+            ],
+        );
     }
 
     /// Search the index with a query string.
     pub fn search(&self, query: &str, k: usize) -> Vec<(String, f64)> {
+        self.search_where(query, k, |_| true)
+    }
+
+    /// Search only documents whose ID `allow` accepts. Rejected documents
+    /// are skipped while scoring, so the top `k` are all allowed ones and
+    /// nothing is over-fetched.
+    pub fn search_where(
+        &self,
+        query: &str,
+        k: usize,
+        allow: impl Fn(&str) -> bool,
+    ) -> Vec<(String, f64)> {
         let query_tokens = crate::utils::tokenize(query);
         let mut scores: HashMap<String, f64> = HashMap::new();
+        let mut rejected: HashSet<&str> = HashSet::new();
 
         for token in &query_tokens {
             if let Some(doc_ids) = self.inverted_index.get(token) {
                 let df = doc_ids.len() as f64;
                 let idf = ((self.num_docs as f64 - df + 0.5) / (df + 0.5) + 1.0).ln();
 
-                // Count term frequency per document
-                let mut tf_map: HashMap<&str, usize> = HashMap::new();
                 for doc_id in doc_ids {
-                    *tf_map.entry(doc_id).or_insert(0) += 1;
-                }
-
-                for (doc_id, tf) in tf_map {
-                    let doc_len = *self.doc_lengths.get(doc_id).unwrap_or(&1) as f64;
-                    let tf = tf as f64;
+                    if rejected.contains(doc_id.as_str()) {
+                        continue;
+                    }
+                    if !scores.contains_key(doc_id) && !allow(doc_id) {
+                        rejected.insert(doc_id);
+                        continue;
+                    }
+                    let tf = self
+                        .documents
+                        .get(doc_id)
+                        .and_then(|terms| terms.get(token))
+                        .copied()
+                        .unwrap_or(0.0);
+                    let doc_len = self.doc_lengths.get(doc_id).copied().unwrap_or(1.0);
                     let numerator = tf * (self.k1 + 1.0);
                     let denominator =
                         tf + self.k1 * (1.0 - self.b + self.b * doc_len / self.avg_doc_len);
                     let score = idf * numerator / denominator;
-                    *scores.entry(doc_id.to_string()).or_insert(0.0) += score;
+                    *scores.entry(doc_id.clone()).or_insert(0.0) += score;
                 }
             }
         }
 
         let mut results: Vec<(String, f64)> = scores.into_iter().collect();
-        results.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+        // Break ties by ID so equal scores rank the same way on every run.
+        results.sort_by(|a, b| {
+            b.1.partial_cmp(&a.1)
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then_with(|| a.0.cmp(&b.0))
+        });
         results.truncate(k);
         results
     }
 
     /// Remove a document from the index.
     pub fn remove_document(&mut self, doc_id: &str) {
-        if let Some(tokens) = self.documents.remove(doc_id) {
+        if let Some(terms) = self.documents.remove(doc_id) {
             // Remove from inverted index
-            for token in &tokens {
+            for token in terms.keys() {
                 if let Some(doc_ids) = self.inverted_index.get_mut(token) {
                     doc_ids.retain(|id| id != doc_id);
                     if doc_ids.is_empty() {
@@ -102,17 +214,18 @@ impl BM25Index {
 
             self.doc_lengths.remove(doc_id);
             self.num_docs = self.num_docs.saturating_sub(1);
-
-            // Recalculate average document length
-            if self.num_docs > 0 {
-                let total_len: usize = self.doc_lengths.values().sum();
-                self.avg_doc_len = total_len as f64 / self.num_docs as f64;
-            } else {
-                self.avg_doc_len = 0.0;
-            }
+            self.update_avg_doc_len();
         }
     }
 
+    fn update_avg_doc_len(&mut self) {
+        self.avg_doc_len = if self.num_docs > 0 {
+            self.doc_lengths.values().sum::<f64>() / self.num_docs as f64
+        } else {
+            0.0
+        };
+    }
+
     pub fn len(&self) -> usize {
         self.num_docs
     }
@@ -164,6 +277,19 @@ mod tests {
         assert_eq!(results[0].0, "high");
     }
 
+    #[test]
+    fn test_search_where() {
+        let mut index = BM25Index::new();
+        index.add_document("py_login", "login login login");
+        index.add_document("rs_login", "login handler");
+        index.add_document("rs_logout", "logout login");
+
+        let results = index.search_where("login", 1, |id| id.starts_with("rs_"));
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].0, "rs_login");
+        assert!(index.search_where("login", 10, |_| false).is_empty());
+    }
+
     #[test]
     fn test_bm25_remove_document() {
         let mut index = BM25Index::new();
@@ -187,4 +313,75 @@ mod tests {
         index.remove_document("nonexistent");
         assert_eq!(index.len(), 1);
     }
+
+    fn element(name: &str, code: &str, docstring: Option<&str>) -> CodeElement {
+        CodeElement {
+            id: format!("function_{name}"),
+            element_type: crate::indexer::ElementType::Function,
+            name: name.to_string(),
+            file_path: "/repo/app.py".to_string(),
+            relative_path: "app.py".to_string(),
+            language: "python".to_string(),
+            start_line: 1,
+            end_line: code.lines().count(),
+            code: code.into(),
+            signature: code.lines().next().map(str::to_string),
+            docstring: docstring.map(str::to_string),
+            summary: None,
+            parent_id: None,
+            metadata: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn test_exact_name_match_ranks_first() {
+        let mut index = BM25Index::new();
+        index.add_element(&element(
+            "parse_file",
+            "def parse_file(path):\n    return Parser().run(open(path).read())",
+            None,
+        ));
+        index.add_element(&element(
+            "cleanup_tmp",
+            "def cleanup_tmp(tmp):\n    # parse file leftovers before removing them\n    shutil.rmtree(tmp)",
+            Some("Remove the temp dir."),
+        ));
+        index.add_element(&element(
+            "render",
+            "def render(widget):\n    return widget.draw()",
+            None,
+        ));
+
+        for query in ["parse_file", "parse file"] {
+            let results = index.search(query, 10);
+            assert_eq!(results[0].0, "function_parse_file", "{query}");
+        }
+    }
+
+    #[test]
+    fn test_identifier_parts_match() {
+        let mut index = BM25Index::new();
+        index.add_document("bm25", "pub struct BM25Index { documents: HashMap }");
+        index.add_document("resolve", "fn resolve_symbol(name: &str)");
+        index.add_document("other", "fn render(widget: Widget)");
+
+        assert_eq!(index.search("bm25", 10)[0].0, "bm25");
+        assert_eq!(index.search("resolve symbol", 10)[0].0, "resolve");
+        assert_eq!(index.search("resolve_symbol", 10)[0].0, "resolve");
+    }
+
+    #[test]
+    fn test_field_boosts() {
+        let mut index = BM25Index::new();
+        index.add_document_fields("title", &[("name", "widget", 3.0), ("code", "draw", 1.0)]);
+        index.add_document_fields("body", &[("name", "render", 3.0), ("code", "widget", 1.0)]);
+        assert_eq!(index.search("widget", 10)[0].0, "title");
+
+        // Re-adding a document replaces it.
+        index.add_document_fields("title", &[("name", "draw", 1.0)]);
+        assert_eq!(index.len(), 2);
+        let results = index.search("widget", 10);
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].0, "body");
+    }
 }
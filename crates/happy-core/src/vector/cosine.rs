@@ -1,22 +1,40 @@
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
 /// Brute-force vector similarity search using cosine distance.
 /// O(n) per query — suitable for small-to-medium corpora (< 100k elements).
 /// For larger corpora, swap in an ANN library (e.g. `hnsw_rs`, `usearch`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct VectorIndex {
     /// Stored vectors (element_id -> vector)
     vectors: HashMap<String, Vec<f32>>,
     dimension: usize,
+    /// Model the vectors came from; empty when unknown.
+    model: String,
+    /// blake3 hash of the text each vector was embedded from, for vectors
+    /// inserted with [`insert_embedded`](Self::insert_embedded).
+    text_hashes: HashMap<String, [u8; 32]>,
 }
 
 impl VectorIndex {
     pub fn new(dimension: usize) -> Self {
+        Self::for_model(dimension, "")
+    }
+
+    /// An empty index for vectors from `model`.
+    pub fn for_model(dimension: usize, model: &str) -> Self {
         Self {
             vectors: HashMap::new(),
             dimension,
+            model: model.to_string(),
+            text_hashes: HashMap::new(),
         }
     }
 
+    pub fn model(&self) -> &str {
+        &self.model
+    }
+
     /// Add vectors with their element IDs.
     pub fn add(&mut self, ids: &[String], vectors: &[Vec<f32>]) {
         for (id, vec) in ids.iter().zip(vectors.iter()) {
@@ -26,6 +44,64 @@ impl VectorIndex {
         }
     }
 
+    /// Insert or replace a single vector. Returns false if the dimension doesn't match.
+    pub fn insert(&mut self, id: &str, vector: Vec<f32>) -> bool {
+        if vector.len() != self.dimension {
+            return false;
+        }
+        self.vectors.insert(id.to_string(), vector);
+        self.text_hashes.remove(id);
+        true
+    }
+
+    /// Like [`insert`](Self::insert), remembering the `text` the vector was
+    /// embedded from so [`is_current`](Self::is_current) can tell when it
+    /// changes.
+    pub fn insert_embedded(&mut self, id: &str, vector: Vec<f32>, text: &str) -> bool {
+        if !self.insert(id, vector) {
+            return false;
+        }
+        self.text_hashes
+            .insert(id.to_string(), blake3::hash(text.as_bytes()).into());
+        true
+    }
+
+    /// Whether `id` has a vector embedded from exactly `text`.
+    pub fn is_current(&self, id: &str, text: &str) -> bool {
+        self.text_hashes
+            .get(id)
+            .is_some_and(|hash| *hash == <[u8; 32]>::from(blake3::hash(text.as_bytes())))
+    }
+
+    /// Remove the vector for an element ID, if present.
+    pub fn remove(&mut self, id: &str) -> bool {
+        self.text_hashes.remove(id);
+        self.vectors.remove(id).is_some()
+    }
+
+    pub fn contains(&self, id: &str) -> bool {
+        self.vectors.contains_key(id)
+    }
+
+    /// All element IDs that currently have a vector.
+    pub fn ids(&self) -> Vec<String> {
+        self.vectors.keys().cloned().collect()
+    }
+
+    /// Move all vectors from `other` into this index, replacing existing IDs.
+    /// Vectors whose dimension doesn't match are dropped.
+    pub fn extend(&mut self, other: VectorIndex) {
+        for (id, vector) in other.vectors {
+            if vector.len() == self.dimension {
+                self.text_hashes.remove(&id);
+                if let Some(hash) = other.text_hashes.get(&id) {
+                    self.text_hashes.insert(id.clone(), *hash);
+                }
+                self.vectors.insert(id, vector);
+            }
+        }
+    }
+
     /// Search for the k nearest neighbors of a query vector using cosine similarity.
     pub fn search(&self, query: &[f32], k: usize, min_score: f32) -> Vec<(String, f32)> {
         if query.len() != self.dimension || self.vectors.is_empty() {
@@ -106,6 +182,23 @@ mod tests {
         assert_eq!(results[0].0, "a");
     }
 
+    #[test]
+    fn test_is_current_tracks_embedded_text() {
+        let mut index = VectorIndex::for_model(2, "text-embedding-3-small");
+        assert_eq!(index.model(), "text-embedding-3-small");
+        index.insert_embedded("a", vec![1.0, 0.0], "parse config");
+        assert!(index.is_current("a", "parse config"));
+        assert!(!index.is_current("a", "parse config file"));
+
+        // A vector inserted without its text is never current.
+        index.insert("a", vec![0.0, 1.0]);
+        assert!(index.contains("a"));
+        assert!(!index.is_current("a", "parse config"));
+        index.insert_embedded("a", vec![1.0, 0.0], "parse config");
+        index.remove("a");
+        assert!(!index.is_current("a", "parse config"));
+    }
+
     #[test]
     fn test_vector_index_empty() {
         let index = VectorIndex::new(3);
@@ -0,0 +1,238 @@
+use crate::indexer::CodeElement;
+use crate::vector::VectorIndex;
+
+/// Errors produced while computing embeddings.
+#[derive(Debug, thiserror::Error)]
+pub enum EmbedError {
+    #[error("embedding backend failed: {0}")]
+    Backend(String),
+    #[error("embedding backend returned {got} vectors for {expected} inputs")]
+    CountMismatch { expected: usize, got: usize },
+    #[error("embedding dimension mismatch: expected {expected}, got {got}")]
+    DimensionMismatch { expected: usize, got: usize },
+}
+
+/// Turns text into fixed-size vectors for the `VectorIndex`.
+///
+/// Implementations must return exactly one vector of `dimension()` floats
+/// per input text, in input order.
+pub trait Embedder: Send + Sync {
+    fn dimension(&self) -> usize;
+
+    fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>, EmbedError>;
+
+    /// Embed a single text (used for queries).
+    fn embed_one(&self, text: &str) -> Result<Vec<f32>, EmbedError> {
+        let mut vectors = self.embed(&[text.to_string()])?;
+        vectors.pop().ok_or(EmbedError::CountMismatch {
+            expected: 1,
+            got: 0,
+        })
+    }
+}
+
+/// Local, dependency-free embedder using the hashing trick.
+///
+/// Each identifier-like token is hashed into one of `dimension` buckets with a
+/// hash-derived sign, and the result is L2-normalized. This gives a cheap
+/// bag-of-words projection that works offline; swap in a model-backed
+/// embedder for real semantic similarity.
+#[derive(Debug, Clone)]
+pub struct HashingEmbedder {
+    dimension: usize,
+}
+
+impl HashingEmbedder {
+    pub const DEFAULT_DIMENSION: usize = 256;
+
+    pub fn new(dimension: usize) -> Self {
+        Self {
+            dimension: dimension.max(1),
+        }
+    }
+
+    fn embed_text(&self, text: &str) -> Vec<f32> {
+        let mut vector = vec![0.0f32; self.dimension];
+        for token in hash_tokens(text) {
+            let hash = blake3::hash(token.as_bytes());
+            let bytes = hash.as_bytes();
+            let bucket = u64::from_le_bytes([
+                bytes[0], bytes[1], bytes[2], bytes[3], bytes[4], bytes[5], bytes[6], bytes[7],
+            ]) % self.dimension as u64;
+            let sign = if bytes[8] & 1 == 0 { 1.0 } else { -1.0 };
+            vector[bucket as usize] += sign;
+        }
+        normalize(&mut vector);
+        vector
+    }
+}
+
+impl Default for HashingEmbedder {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_DIMENSION)
+    }
+}
+
+impl Embedder for HashingEmbedder {
+    fn dimension(&self) -> usize {
+        self.dimension
+    }
+
+    fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>, EmbedError> {
+        Ok(texts.iter().map(|t| self.embed_text(t)).collect())
+    }
+}
+
+type EmbedFn = dyn Fn(&[String]) -> Result<Vec<Vec<f32>>, EmbedError> + Send + Sync;
+
+/// Embedder backed by a caller-supplied function.
+///
+/// This is the plug-in point for remote backends (an HTTP embedding server,
+/// a Python model, ...): the transport lives with the caller, while the
+/// result is validated here against the declared dimension.
+pub struct CallbackEmbedder {
+    dimension: usize,
+    callback: Box<EmbedFn>,
+}
+
+impl CallbackEmbedder {
+    pub fn new<F>(dimension: usize, callback: F) -> Self
+    where
+        F: Fn(&[String]) -> Result<Vec<Vec<f32>>, EmbedError> + Send + Sync + 'static,
+    {
+        Self {
+            dimension,
+            callback: Box::new(callback),
+        }
+    }
+}
+
+impl Embedder for CallbackEmbedder {
+    fn dimension(&self) -> usize {
+        self.dimension
+    }
+
+    fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>, EmbedError> {
+        let vectors = (self.callback)(texts)?;
+        if vectors.len() != texts.len() {
+            return Err(EmbedError::CountMismatch {
+                expected: texts.len(),
+                got: vectors.len(),
+            });
+        }
+        if let Some(bad) = vectors.iter().find(|v| v.len() != self.dimension) {
+            return Err(EmbedError::DimensionMismatch {
+                expected: self.dimension,
+                got: bad.len(),
+            });
+        }
+        Ok(vectors)
+    }
+}
+
+/// Text used to embed a code element: name, signature, docstring, then code.
+pub fn element_embedding_text(elem: &CodeElement) -> String {
+    let mut text = elem.name.clone();
+    for part in [elem.signature.as_deref(), elem.docstring.as_deref()]
+        .into_iter()
+        .flatten()
+    {
+        text.push(' ');
+        text.push_str(part);
+    }
+    text.push(' ');
+    text.push_str(&elem.code);
+    text
+}
+
+/// Embed `elements` and insert the vectors into `index`.
+///
+/// Returns the number of vectors added.
+pub fn embed_elements(
+    embedder: &dyn Embedder,
+    elements: &[CodeElement],
+    index: &mut VectorIndex,
+) -> Result<usize, EmbedError> {
+    let texts: Vec<String> = elements.iter().map(element_embedding_text).collect();
+    let vectors = embedder.embed(&texts)?;
+    let mut added = 0;
+    for (elem, vector) in elements.iter().zip(vectors) {
+        if index.insert(&elem.id, vector) {
+            added += 1;
+        }
+    }
+    Ok(added)
+}
+
+/// Lowercased alphanumeric/underscore runs, plus the parts of snake_case and
+/// camelCase identifiers so `parseConfig` and `parse_config` share buckets.
+fn hash_tokens(text: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    for word in text
+        .split(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .filter(|w| !w.is_empty())
+    {
+        tokens.push(word.to_lowercase());
+        let parts = crate::utils::split_identifier(word);
+        if parts.len() > 1 {
+            tokens.extend(parts);
+        }
+    }
+    tokens
+}
+
+fn normalize(vector: &mut [f32]) {
+    let norm = vector.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm > 0.0 {
+        for x in vector.iter_mut() {
+            *x /= norm;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hashing_embedder_is_deterministic_and_normalized() {
+        let embedder = HashingEmbedder::new(64);
+        let a = embedder.embed_one("parse the config file").unwrap();
+        let b = embedder.embed_one("parse the config file").unwrap();
+        assert_eq!(a.len(), 64);
+        assert_eq!(a, b);
+        let norm: f32 = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+        assert!((norm - 1.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_hashing_embedder_identifier_parts_overlap() {
+        let embedder = HashingEmbedder::default();
+        let mut index = VectorIndex::new(embedder.dimension());
+        index.insert(
+            "camel",
+            embedder.embed_one("def parseConfig(): pass").unwrap(),
+        );
+        index.insert(
+            "other",
+            embedder.embed_one("def render_widget(): pass").unwrap(),
+        );
+
+        let query = embedder.embed_one("parse config").unwrap();
+        let results = index.search(&query, 2, 0.0);
+        assert_eq!(results[0].0, "camel");
+    }
+
+    #[test]
+    fn test_callback_embedder_validates_dimension() {
+        let embedder = CallbackEmbedder::new(3, |texts| Ok(vec![vec![1.0, 0.0]; texts.len()]));
+        let err = embedder.embed(&["x".to_string()]).unwrap_err();
+        assert!(matches!(
+            err,
+            EmbedError::DimensionMismatch {
+                expected: 3,
+                got: 2
+            }
+        ));
+    }
+}
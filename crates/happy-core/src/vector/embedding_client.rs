@@ -0,0 +1,507 @@
+use std::io;
+use std::path::Path;
+use std::time::Duration;
+
+use serde::Deserialize;
+use serde_json::{Value, json};
+
+use super::embedder::{EmbedError, Embedder};
+use super::pipeline::EmbeddingBudget;
+use crate::agent_toml::AgentToml;
+
+/// Environment variable that overrides `embeddings.api_key`.
+pub const EMBEDDINGS_API_KEY_ENV: &str = "HAPPY_EMBEDDINGS_API_KEY";
+
+/// Wire format spoken by the embedding server.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EmbeddingApi {
+    /// OpenAI-compatible `POST /v1/embeddings` (`{"model", "input"}` -> `data[].embedding`).
+    #[default]
+    OpenAi,
+    /// Hugging Face text-embeddings-inference `POST /embed` (`{"inputs"}` -> `[[f32]]`).
+    Tei,
+    /// Ollama `POST /api/embed` (`{"model", "input"}` -> `embeddings`).
+    Ollama,
+}
+
+impl EmbeddingApi {
+    fn path(&self) -> &'static str {
+        match self {
+            Self::OpenAi => "/v1/embeddings",
+            Self::Tei => "/embed",
+            Self::Ollama => "/api/embed",
+        }
+    }
+}
+
+/// Where embeddings are requested: the `[embeddings]` table of the user's
+/// `config.toml`. The server receives the repository's code and the user's
+/// key, so a repository cannot choose it.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct EmbeddingEndpoint {
+    /// Base URL of the embedding server, e.g. `http://localhost:11434`.
+    pub endpoint: String,
+    pub model: String,
+    pub api_key: Option<String>,
+    pub api: EmbeddingApi,
+}
+
+/// The `[embeddings]` table of `.happy/agent.toml`, tuning how a repository
+/// is embedded, together with the user's [`EmbeddingEndpoint`].
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct EmbeddingConfig {
+    /// From [`EmbeddingEndpoint`]; ignored in `.happy/agent.toml`.
+    #[serde(skip)]
+    pub endpoint: String,
+    #[serde(skip)]
+    pub model: String,
+    #[serde(skip)]
+    pub api_key: Option<String>,
+    #[serde(skip)]
+    pub api: EmbeddingApi,
+    /// Texts per request.
+    pub batch_size: usize,
+    /// Vector size. Probed from the server with one request when unset.
+    pub dimensions: Option<usize>,
+    /// Retries for transient failures (connection errors, 429, 5xx).
+    pub max_retries: u32,
+    /// Initial retry delay; doubles on every attempt.
+    pub retry_backoff_ms: u64,
+    pub timeout_secs: u64,
+    /// Stop after embedding this many elements.
+    pub max_elements: Option<usize>,
+    /// Skip elements whose text is estimated above this many tokens.
+    pub max_tokens_per_element: usize,
+    /// Embed full element code instead of signature + docstring.
+    pub include_code: bool,
+}
+
+impl Default for EmbeddingConfig {
+    fn default() -> Self {
+        Self {
+            endpoint: String::new(),
+            model: String::new(),
+            api_key: None,
+            api: EmbeddingApi::default(),
+            batch_size: 64,
+            dimensions: None,
+            max_retries: 3,
+            retry_backoff_ms: 500,
+            timeout_secs: 60,
+            max_elements: None,
+            max_tokens_per_element: 512,
+            include_code: false,
+        }
+    }
+}
+
+impl EmbeddingConfig {
+    /// Load `[embeddings]` from `<repo_root>/.happy/agent.toml` for the
+    /// user's `endpoint`. An `endpoint`, `model`, `api_key`, or `api` in the
+    /// repository's table is ignored.
+    ///
+    /// Returns `Ok(None)` when the user configured no endpoint.
+    pub fn load(repo_root: &Path, endpoint: &EmbeddingEndpoint) -> io::Result<Option<Self>> {
+        if endpoint.endpoint.is_empty() {
+            return Ok(None);
+        }
+        let mut config: Self = AgentToml::load(repo_root)?
+            .section("embeddings")?
+            .unwrap_or_default();
+        config.endpoint = endpoint.endpoint.clone();
+        config.model = endpoint.model.clone();
+        config.api_key = endpoint.api_key.clone();
+        config.api = endpoint.api;
+        if let Ok(key) = std::env::var(EMBEDDINGS_API_KEY_ENV)
+            && !key.is_empty()
+        {
+            config.api_key = Some(key);
+        }
+        Ok(Some(config))
+    }
+
+    /// Budget limits derived from this config.
+    pub fn budget(&self) -> EmbeddingBudget {
+        EmbeddingBudget {
+            batch_size: self.batch_size.max(1),
+            max_elements: self.max_elements,
+            max_tokens_per_element: self.max_tokens_per_element,
+            include_code: self.include_code,
+        }
+    }
+}
+
+/// HTTP client for a user-supplied embedding server.
+pub struct EmbeddingClient {
+    config: EmbeddingConfig,
+    url: String,
+    dimension: usize,
+    http: reqwest::blocking::Client,
+}
+
+impl EmbeddingClient {
+    /// Build a client. Probes the server once if `dimensions` isn't configured.
+    ///
+    /// Uses a blocking HTTP client, so call this off the async runtime.
+    pub fn new(config: EmbeddingConfig) -> Result<Self, EmbedError> {
+        let http = reqwest::blocking::Client::builder()
+            .timeout(Duration::from_secs(config.timeout_secs.max(1)))
+            .build()
+            .map_err(|e| EmbedError::Backend(e.to_string()))?;
+        let base = config.endpoint.trim_end_matches('/');
+        let path = config.api.path();
+        let url = if base.ends_with(path) {
+            base.to_string()
+        } else {
+            format!("{base}{path}")
+        };
+
+        let mut client = Self {
+            dimension: config.dimensions.unwrap_or(0),
+            config,
+            url,
+            http,
+        };
+        if client.dimension == 0 {
+            let probe = client.request_with_retry(&["dimension probe".to_string()])?;
+            client.dimension = probe.first().map(Vec::len).unwrap_or(0);
+            if client.dimension == 0 {
+                return Err(EmbedError::Backend(
+                    "embedding server returned an empty vector".to_string(),
+                ));
+            }
+        }
+        Ok(client)
+    }
+
+    pub fn config(&self) -> &EmbeddingConfig {
+        &self.config
+    }
+
+    fn request_with_retry(&self, texts: &[String]) -> Result<Vec<Vec<f32>>, EmbedError> {
+        let mut delay = Duration::from_millis(self.config.retry_backoff_ms);
+        let mut attempt = 0;
+        loop {
+            match self.request(texts) {
+                Ok(vectors) => return Ok(vectors),
+                Err(RequestError::Transient(message)) if attempt < self.config.max_retries => {
+                    tracing::warn!(
+                        attempt = attempt + 1,
+                        delay_ms = delay.as_millis() as u64,
+                        error = %message,
+                        "embedding request failed, retrying"
+                    );
+                    std::thread::sleep(delay);
+                    delay *= 2;
+                    attempt += 1;
+                }
+                Err(RequestError::Transient(message)) | Err(RequestError::Fatal(message)) => {
+                    return Err(EmbedError::Backend(message));
+                }
+            }
+        }
+    }
+
+    fn request(&self, texts: &[String]) -> Result<Vec<Vec<f32>>, RequestError> {
+        let body = match self.config.api {
+            EmbeddingApi::OpenAi | EmbeddingApi::Ollama => {
+                json!({ "model": self.config.model, "input": texts })
+            }
+            EmbeddingApi::Tei => json!({ "inputs": texts }),
+        };
+        let mut request = self.http.post(&self.url).json(&body);
+        if let Some(key) = &self.config.api_key {
+            request = request.bearer_auth(key);
+        }
+
+        let response = request
+            .send()
+            .map_err(|e| RequestError::Transient(e.to_string()))?;
+        let status = response.status();
+        if !status.is_success() {
+            let text = response.text().unwrap_or_default();
+            let message = format!("embedding server returned {status}: {text}");
+            return Err(if status.is_server_error() || status.as_u16() == 429 {
+                RequestError::Transient(message)
+            } else {
+                RequestError::Fatal(message)
+            });
+        }
+        let value: Value = response
+            .json()
+            .map_err(|e| RequestError::Fatal(format!("invalid embedding response: {e}")))?;
+        parse_response(self.config.api, value).map_err(RequestError::Fatal)
+    }
+}
+
+impl Embedder for EmbeddingClient {
+    fn dimension(&self) -> usize {
+        self.dimension
+    }
+
+    fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>, EmbedError> {
+        let mut vectors = Vec::with_capacity(texts.len());
+        for chunk in texts.chunks(self.config.batch_size.max(1)) {
+            let batch = self.request_with_retry(chunk)?;
+            if batch.len() != chunk.len() {
+                return Err(EmbedError::CountMismatch {
+                    expected: chunk.len(),
+                    got: batch.len(),
+                });
+            }
+            if let Some(bad) = batch.iter().find(|v| v.len() != self.dimension) {
+                return Err(EmbedError::DimensionMismatch {
+                    expected: self.dimension,
+                    got: bad.len(),
+                });
+            }
+            vectors.extend(batch);
+        }
+        Ok(vectors)
+    }
+}
+
+enum RequestError {
+    Transient(String),
+    Fatal(String),
+}
+
+fn parse_response(api: EmbeddingApi, value: Value) -> Result<Vec<Vec<f32>>, String> {
+    let rows: Vec<(usize, Value)> = match api {
+        EmbeddingApi::OpenAi => value
+            .get("data")
+            .and_then(Value::as_array)
+            .ok_or("response has no 'data' array")?
+            .iter()
+            .enumerate()
+            .map(|(pos, item)| {
+                let index = item
+                    .get("index")
+                    .and_then(Value::as_u64)
+                    .map(|i| i as usize)
+                    .unwrap_or(pos);
+                (index, item.get("embedding").cloned().unwrap_or(Value::Null))
+            })
+            .collect(),
+        EmbeddingApi::Tei => value
+            .as_array()
+            .ok_or("response is not an array")?
+            .iter()
+            .cloned()
+            .enumerate()
+            .collect(),
+        EmbeddingApi::Ollama => value
+            .get("embeddings")
+            .and_then(Value::as_array)
+            .ok_or("response has no 'embeddings' array")?
+            .iter()
+            .cloned()
+            .enumerate()
+            .collect(),
+    };
+
+    let mut rows = rows;
+    rows.sort_by_key(|(index, _)| *index);
+    rows.into_iter()
+        .map(|(_, row)| {
+            serde_json::from_value::<Vec<f32>>(row).map_err(|e| format!("invalid embedding: {e}"))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indexer::{CodeElement, ElementType};
+    use crate::vector::embedder::HashingEmbedder;
+    use crate::vector::pipeline::{embed_in_batches, update_file_vectors};
+    use crate::vector::{BM25Index, VectorIndex, hybrid_search};
+    use std::collections::HashMap;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::sync::{Arc, Mutex};
+
+    const DIM: usize = 64;
+
+    /// Minimal OpenAI-compatible embedding server. Vectors come from a
+    /// `HashingEmbedder` so similarity is meaningful. The first `fail_first`
+    /// requests get a 500. Records the batch size of every request.
+    fn spawn_mock_server(fail_first: usize) -> (String, Arc<Mutex<Vec<usize>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let batches = Arc::new(Mutex::new(Vec::new()));
+        let recorded = batches.clone();
+
+        std::thread::spawn(move || {
+            let embedder = HashingEmbedder::new(DIM);
+            let mut failures_left = fail_first;
+            for stream in listener.incoming() {
+                let Ok(mut stream) = stream else { break };
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut content_length = 0;
+                loop {
+                    let mut line = String::new();
+                    if reader.read_line(&mut line).unwrap_or(0) == 0 || line == "\r\n" {
+                        break;
+                    }
+                    let lower = line.to_ascii_lowercase();
+                    if let Some(value) = lower.strip_prefix("content-length:") {
+                        content_length = value.trim().parse().unwrap_or(0);
+                    }
+                }
+                let mut body = vec![0u8; content_length];
+                reader.read_exact(&mut body).unwrap();
+
+                let (status, payload) = if failures_left > 0 {
+                    failures_left -= 1;
+                    (
+                        "500 Internal Server Error",
+                        "{\"error\":\"overloaded\"}".to_string(),
+                    )
+                } else {
+                    let request: Value = serde_json::from_slice(&body).unwrap();
+                    let inputs: Vec<String> =
+                        serde_json::from_value(request["input"].clone()).unwrap();
+                    recorded.lock().unwrap().push(inputs.len());
+                    let vectors = embedder.embed(&inputs).unwrap();
+                    let data: Vec<Value> = vectors
+                        .into_iter()
+                        .enumerate()
+                        .map(|(index, embedding)| json!({ "index": index, "embedding": embedding }))
+                        .collect();
+                    ("200 OK", json!({ "data": data }).to_string())
+                };
+                let _ = write!(
+                    stream,
+                    "HTTP/1.1 {status}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{payload}",
+                    payload.len()
+                );
+            }
+        });
+
+        (format!("http://{addr}"), batches)
+    }
+
+    fn config(endpoint: String, batch_size: usize) -> EmbeddingConfig {
+        EmbeddingConfig {
+            endpoint,
+            model: "mock".to_string(),
+            batch_size,
+            dimensions: Some(DIM),
+            retry_backoff_ms: 1,
+            ..EmbeddingConfig::default()
+        }
+    }
+
+    fn function(id: &str, name: &str, doc: &str) -> CodeElement {
+        CodeElement {
+            id: id.to_string(),
+            element_type: ElementType::Function,
+            name: name.to_string(),
+            file_path: "src/lib.py".to_string(),
+            relative_path: "src/lib.py".to_string(),
+            language: "python".to_string(),
+            start_line: 1,
+            end_line: 2,
+            code: format!("def {name}():\n    pass").into(),
+            signature: Some(format!("def {name}():")),
+            docstring: Some(doc.to_string()),
+            summary: None,
+            parent_id: None,
+            metadata: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn test_endpoint_comes_from_the_user() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join(".happy")).unwrap();
+        std::fs::write(
+            dir.path().join(".happy/agent.toml"),
+            "[embeddings]\nendpoint = \"http://attacker.example\"\nmodel = \"theirs\"\nbatch_size = 8\n",
+        )
+        .unwrap();
+        let none = EmbeddingEndpoint::default();
+        assert_eq!(EmbeddingConfig::load(dir.path(), &none).unwrap(), None);
+
+        let user = EmbeddingEndpoint {
+            endpoint: "http://localhost:11434".to_string(),
+            model: "nomic-embed-text".to_string(),
+            api_key: None,
+            api: EmbeddingApi::Ollama,
+        };
+        let config = EmbeddingConfig::load(dir.path(), &user).unwrap().unwrap();
+        assert_eq!(config.endpoint, "http://localhost:11434");
+        assert_eq!(config.model, "nomic-embed-text");
+        assert_eq!(config.api, EmbeddingApi::Ollama);
+        assert_eq!(config.batch_size, 8);
+    }
+
+    #[test]
+    fn test_embeds_in_configured_batches() {
+        let (endpoint, batches) = spawn_mock_server(0);
+        let client = EmbeddingClient::new(config(endpoint, 2)).unwrap();
+        let elements: Vec<CodeElement> = (0..5)
+            .map(|i| function(&format!("f{i}"), &format!("func_{i}"), "does things"))
+            .collect();
+
+        let mut index = VectorIndex::new(client.dimension());
+        let mut progress = Vec::new();
+        let report = embed_in_batches(
+            &client,
+            &elements,
+            &mut index,
+            &client.config().budget(),
+            &mut |p| progress.push(p.embedded),
+        )
+        .unwrap();
+
+        assert_eq!(report.embedded, 5);
+        assert_eq!(index.len(), 5);
+        assert_eq!(*batches.lock().unwrap(), vec![2, 2, 1]);
+        assert_eq!(progress, vec![2, 4, 5]);
+    }
+
+    #[test]
+    fn test_retries_transient_server_error() {
+        let (endpoint, batches) = spawn_mock_server(1);
+        let client = EmbeddingClient::new(config(endpoint, 8)).unwrap();
+        let vectors = client.embed(&["hello world".to_string()]).unwrap();
+        assert_eq!(vectors.len(), 1);
+        assert_eq!(vectors[0].len(), DIM);
+        assert_eq!(*batches.lock().unwrap(), vec![1]);
+    }
+
+    #[test]
+    fn test_incremental_update_feeds_hybrid_search() {
+        let (endpoint, batches) = spawn_mock_server(0);
+        let client = EmbeddingClient::new(config(endpoint, 16)).unwrap();
+        let budget = client.config().budget();
+
+        let old = function("old", "load_settings", "read the settings file");
+        let mut bm25 = BM25Index::new();
+        bm25.add_document(&old.id, &old.name);
+        let mut index = VectorIndex::new(client.dimension());
+        embed_in_batches(&client, &[old], &mut index, &budget, &mut |_| {}).unwrap();
+
+        // The file changes: `load_settings` is replaced by `render_chart`.
+        let new = function("new", "render_chart", "draw a bar chart widget");
+        bm25.remove_document("old");
+        bm25.add_document(&new.id, &new.name);
+        let report =
+            update_file_vectors(&client, &mut index, &["old".to_string()], &[new], &budget)
+                .unwrap();
+
+        assert_eq!(report.embedded, 1);
+        assert!(!index.contains("old"));
+        assert!(index.contains("new"));
+        // Only the changed element went over the wire the second time.
+        assert_eq!(*batches.lock().unwrap(), vec![1, 1]);
+
+        let results = hybrid_search(&bm25, &index, &client, "bar chart widget", 3, 1.0);
+        assert_eq!(results[0].0, "new");
+    }
+}
@@ -0,0 +1,515 @@
+//! Relevance evaluation for code search.
+//!
+//! A fixtures directory holds a `repo/` to index and a `judgments.json`
+//! pairing queries with the elements a good search returns for them. Each
+//! judged element is named by kind, name, and repo-relative path, so
+//! judgments survive re-indexing. Grades are 2 for what the query asks for
+//! and 1 for related elements:
+//!
+//! ```json
+//! {
+//!   "queries": [
+//!     {
+//!       "query": "charge a credit card",
+//!       "category": "natural_language",
+//!       "relevant": [
+//!         { "kind": "method", "name": "charge_card", "path": "shop/payments.py", "grade": 2 }
+//!       ]
+//!     }
+//!   ],
+//!   "floors": { "keyword": { "ndcg_at_10": 0.85, "mrr": 0.9, "recall_at_20": 0.95 } }
+//! }
+//! ```
+//!
+//! `floors` records the lowest acceptable mean metrics per search mode for
+//! the default parameters; the fixture test fails when a change to
+//! tokenization, boosts, or scoring drops below them.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use crate::indexer::CodeElement;
+use crate::utils::normalize_path;
+
+use super::bm25::{DEFAULT_B, DEFAULT_K1, FieldBoosts};
+use super::embedder::{EmbedError, Embedder, HashingEmbedder};
+use super::pipeline::{EmbeddingBudget, embed_in_batches};
+use super::{BM25Index, VectorIndex, hybrid_search};
+
+/// Judgments file inside a fixtures directory.
+pub const JUDGMENTS_FILE: &str = "judgments.json";
+/// Directory inside a fixtures directory holding the repo to index.
+pub const REPO_DIR: &str = "repo";
+/// Results scored by nDCG.
+pub const NDCG_CUTOFF: usize = 10;
+/// Results searched for each query, and scored by recall.
+pub const RECALL_CUTOFF: usize = 20;
+
+/// Errors loading fixtures or evaluating judgments against them.
+#[derive(Debug, thiserror::Error)]
+pub enum EvalError {
+    #[error("failed to read {path}: {source}")]
+    Read {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[error("invalid judgments in {path}: {source}")]
+    Parse {
+        path: PathBuf,
+        source: serde_json::Error,
+    },
+    #[error("judged {} '{}' in {} is not in the index", .0.kind, .0.name, .0.path)]
+    UnknownElement(ElementKey),
+    #[error(transparent)]
+    Embed(#[from] EmbedError),
+}
+
+/// The search pipelines that can be evaluated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SearchMode {
+    /// BM25 alone, as used by `search_code`.
+    Keyword,
+    /// BM25 blended with the local hashing embedder, as used by
+    /// `semantic_search` before a remote embedder is configured.
+    Hybrid,
+}
+
+impl SearchMode {
+    pub const ALL: [SearchMode; 2] = [SearchMode::Keyword, SearchMode::Hybrid];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Keyword => "keyword",
+            Self::Hybrid => "hybrid",
+        }
+    }
+}
+
+/// Stable identity of an indexed element.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct ElementKey {
+    /// Element type as in [`crate::indexer::ElementType::as_str`].
+    pub kind: String,
+    pub name: String,
+    /// Path relative to the repo root, with `/` separators.
+    pub path: String,
+}
+
+impl ElementKey {
+    pub fn of(elem: &CodeElement) -> Self {
+        Self {
+            kind: elem.element_type.as_str().to_string(),
+            name: elem.name.clone(),
+            path: normalize_path(&elem.relative_path),
+        }
+    }
+}
+
+/// An element judged relevant to a query.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Judgment {
+    #[serde(flatten)]
+    pub element: ElementKey,
+    #[serde(default = "default_grade")]
+    pub grade: u32,
+}
+
+fn default_grade() -> u32 {
+    1
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JudgedQuery {
+    pub query: String,
+    /// Free-form grouping such as `identifier` or `natural_language`.
+    #[serde(default)]
+    pub category: String,
+    pub relevant: Vec<Judgment>,
+}
+
+/// The contents of a judgments file.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Judgments {
+    pub queries: Vec<JudgedQuery>,
+    /// Lowest acceptable mean metrics per mode with default parameters.
+    #[serde(default)]
+    pub floors: BTreeMap<SearchMode, Metrics>,
+}
+
+impl Judgments {
+    pub fn load(path: &Path) -> Result<Self, EvalError> {
+        let text = std::fs::read_to_string(path).map_err(|source| EvalError::Read {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        serde_json::from_str(&text).map_err(|source| EvalError::Parse {
+            path: path.to_path_buf(),
+            source,
+        })
+    }
+}
+
+/// Ranking quality of one query, or the mean over many.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct Metrics {
+    pub ndcg_at_10: f64,
+    pub mrr: f64,
+    pub recall_at_20: f64,
+}
+
+impl Metrics {
+    /// `self - other`, metric by metric.
+    pub fn delta(&self, other: &Metrics) -> Metrics {
+        Metrics {
+            ndcg_at_10: self.ndcg_at_10 - other.ndcg_at_10,
+            mrr: self.mrr - other.mrr,
+            recall_at_20: self.recall_at_20 - other.recall_at_20,
+        }
+    }
+
+    /// Names and values of the metrics below `floor`.
+    pub fn below(&self, floor: &Metrics) -> Vec<(&'static str, f64, f64)> {
+        [
+            ("nDCG@10", self.ndcg_at_10, floor.ndcg_at_10),
+            ("MRR", self.mrr, floor.mrr),
+            ("recall@20", self.recall_at_20, floor.recall_at_20),
+        ]
+        .into_iter()
+        .filter(|(_, value, floor)| value < floor)
+        .collect()
+    }
+
+    fn mean(all: &[Metrics]) -> Metrics {
+        if all.is_empty() {
+            return Metrics::default();
+        }
+        let n = all.len() as f64;
+        Metrics {
+            ndcg_at_10: all.iter().map(|m| m.ndcg_at_10).sum::<f64>() / n,
+            mrr: all.iter().map(|m| m.mrr).sum::<f64>() / n,
+            recall_at_20: all.iter().map(|m| m.recall_at_20).sum::<f64>() / n,
+        }
+    }
+}
+
+/// Search parameters under evaluation. Missing fields in a parameter file
+/// keep the defaults the tools use.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SearchParams {
+    pub k1: f64,
+    pub b: f64,
+    pub boosts: FieldBoosts,
+    /// Weight of the vector score in hybrid mode.
+    pub alpha: f64,
+}
+
+impl Default for SearchParams {
+    fn default() -> Self {
+        Self {
+            k1: DEFAULT_K1,
+            b: DEFAULT_B,
+            boosts: FieldBoosts::default(),
+            alpha: 0.5,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueryReport {
+    pub query: String,
+    pub category: String,
+    pub metrics: Metrics,
+}
+
+/// Metrics of one mode over every judged query.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EvalReport {
+    pub mode: SearchMode,
+    pub queries: Vec<QueryReport>,
+    pub mean: Metrics,
+}
+
+/// Per-query change between two reports of the same judgments.
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueryDelta {
+    pub query: String,
+    pub baseline: Metrics,
+    pub candidate: Metrics,
+}
+
+impl QueryDelta {
+    pub fn delta(&self) -> Metrics {
+        self.candidate.delta(&self.baseline)
+    }
+}
+
+/// Read `<dir>/judgments.json` and index `<dir>/repo`.
+pub fn load_fixtures(dir: &Path) -> Result<(Vec<CodeElement>, Judgments), EvalError> {
+    let judgments = Judgments::load(&dir.join(JUDGMENTS_FILE))?;
+    let repo = dir.join(REPO_DIR);
+    if !repo.is_dir() {
+        return Err(EvalError::Read {
+            path: repo,
+            source: std::io::Error::new(std::io::ErrorKind::NotFound, "not a directory"),
+        });
+    }
+    let mut elements = crate::indexer::walk_and_index(&repo.to_string_lossy(), &Default::default());
+    elements.sort_by(|a, b| a.id.cmp(&b.id));
+    Ok((elements, judgments))
+}
+
+/// Run every judged query through `mode` with `params` and score the
+/// results. Fails if a judgment names an element that is not in `elements`,
+/// so stale judgments don't silently count as misses.
+pub fn evaluate(
+    elements: &[CodeElement],
+    judgments: &Judgments,
+    params: &SearchParams,
+    mode: SearchMode,
+) -> Result<EvalReport, EvalError> {
+    let keys: HashMap<&str, ElementKey> = elements
+        .iter()
+        .map(|elem| (elem.id.as_str(), ElementKey::of(elem)))
+        .collect();
+    let known: HashSet<&ElementKey> = keys.values().collect();
+    for judgment in judgments.queries.iter().flat_map(|q| &q.relevant) {
+        if !known.contains(&judgment.element) {
+            return Err(EvalError::UnknownElement(judgment.element.clone()));
+        }
+    }
+
+    let mut bm25 = BM25Index::with_parameters(params.k1, params.b, params.boosts);
+    for elem in elements {
+        bm25.add_element(elem);
+    }
+    let embedder = HashingEmbedder::default();
+    let mut vectors = VectorIndex::new(embedder.dimension());
+    if mode == SearchMode::Hybrid {
+        embed_in_batches(
+            &embedder,
+            elements,
+            &mut vectors,
+            &EmbeddingBudget::local(),
+            &mut |_| {},
+        )?;
+    }
+
+    let mut queries = Vec::new();
+    for judged in &judgments.queries {
+        let results = match mode {
+            SearchMode::Keyword => bm25.search(&judged.query, RECALL_CUTOFF),
+            SearchMode::Hybrid => hybrid_search(
+                &bm25,
+                &vectors,
+                &embedder,
+                &judged.query,
+                RECALL_CUTOFF,
+                params.alpha,
+            ),
+        };
+        let grades_by_key: HashMap<&ElementKey, u32> = judged
+            .relevant
+            .iter()
+            .map(|j| (&j.element, j.grade))
+            .collect();
+        // Elements sharing a key (overloads) count once, at their best rank.
+        let mut seen = HashSet::new();
+        let grades: Vec<u32> = results
+            .iter()
+            .filter_map(|(id, _)| keys.get(id.as_str()))
+            .filter(|key| seen.insert(*key))
+            .map(|key| grades_by_key.get(key).copied().unwrap_or(0))
+            .collect();
+        let mut ideal: Vec<u32> = grades_by_key.values().copied().collect();
+        ideal.sort_unstable_by(|a, b| b.cmp(a));
+        queries.push(QueryReport {
+            query: judged.query.clone(),
+            category: judged.category.clone(),
+            metrics: Metrics {
+                ndcg_at_10: ndcg_at(&grades, &ideal, NDCG_CUTOFF),
+                mrr: reciprocal_rank(&grades),
+                recall_at_20: recall_at(&grades, grades_by_key.len(), RECALL_CUTOFF),
+            },
+        });
+    }
+    let mean = Metrics::mean(&queries.iter().map(|q| q.metrics).collect::<Vec<_>>());
+    Ok(EvalReport {
+        mode,
+        queries,
+        mean,
+    })
+}
+
+/// Pair up the queries of two reports over the same judgments.
+pub fn compare(baseline: &EvalReport, candidate: &EvalReport) -> Vec<QueryDelta> {
+    baseline
+        .queries
+        .iter()
+        .zip(&candidate.queries)
+        .map(|(base, cand)| QueryDelta {
+            query: base.query.clone(),
+            baseline: base.metrics,
+            candidate: cand.metrics,
+        })
+        .collect()
+}
+
+/// Normalized discounted cumulative gain of the first `k` results.
+///
+/// `grades` are the relevance grades of the results in rank order (0 for
+/// unjudged results) and `ideal` the grades of every judged element, best
+/// first. Gain is `2^grade - 1`, discounted by `log2(rank + 1)`.
+pub fn ndcg_at(grades: &[u32], ideal: &[u32], k: usize) -> f64 {
+    let ideal_dcg = dcg_at(ideal, k);
+    if ideal_dcg == 0.0 {
+        return 0.0;
+    }
+    dcg_at(grades, k) / ideal_dcg
+}
+
+fn dcg_at(grades: &[u32], k: usize) -> f64 {
+    grades
+        .iter()
+        .take(k)
+        .enumerate()
+        .map(|(rank, &grade)| (2f64.powi(grade as i32) - 1.0) / (rank as f64 + 2.0).log2())
+        .sum()
+}
+
+/// `1 / rank` of the first relevant result, or 0 if there is none.
+pub fn reciprocal_rank(grades: &[u32]) -> f64 {
+    grades
+        .iter()
+        .position(|&grade| grade > 0)
+        .map_or(0.0, |rank| 1.0 / (rank as f64 + 1.0))
+}
+
+/// Share of the `relevant` judged elements found in the first `k` results.
+pub fn recall_at(grades: &[u32], relevant: usize, k: usize) -> f64 {
+    if relevant == 0 {
+        return 0.0;
+    }
+    let found = grades.iter().take(k).filter(|&&grade| grade > 0).count();
+    found as f64 / relevant as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!(
+            (actual - expected).abs() < 1e-9,
+            "expected {expected}, got {actual}"
+        );
+    }
+
+    #[test]
+    fn test_ndcg_at() {
+        // DCG = 3/log2(3) + 1/log2(4); IDCG = 3/log2(2) + 1/log2(3).
+        assert_close(ndcg_at(&[0, 2, 1], &[2, 1], 10), 0.6590018048024133);
+        assert_close(ndcg_at(&[2, 1], &[2, 1], 10), 1.0);
+        assert_close(ndcg_at(&[0, 0, 2], &[2], 2), 0.0);
+        assert_close(ndcg_at(&[1], &[], 10), 0.0);
+    }
+
+    #[test]
+    fn test_reciprocal_rank_and_recall() {
+        assert_close(reciprocal_rank(&[0, 0, 1]), 1.0 / 3.0);
+        assert_close(reciprocal_rank(&[2, 0]), 1.0);
+        assert_close(reciprocal_rank(&[]), 0.0);
+
+        assert_close(recall_at(&[1, 0, 2, 0], 3, 2), 1.0 / 3.0);
+        assert_close(recall_at(&[1, 0, 2, 0], 3, 20), 2.0 / 3.0);
+        assert_close(recall_at(&[1], 0, 20), 0.0);
+    }
+
+    #[test]
+    fn test_metrics_below_floor_and_delta() {
+        let metrics = Metrics {
+            ndcg_at_10: 0.8,
+            mrr: 0.9,
+            recall_at_20: 1.0,
+        };
+        let floor = Metrics {
+            ndcg_at_10: 0.85,
+            mrr: 0.9,
+            recall_at_20: 0.95,
+        };
+        assert_eq!(metrics.below(&floor), vec![("nDCG@10", 0.8, 0.85)]);
+        let delta = metrics.delta(&floor);
+        assert_close(delta.ndcg_at_10, -0.05);
+        assert_close(delta.mrr, 0.0);
+        assert_close(delta.recall_at_20, 0.05);
+    }
+
+    fn fixtures_dir() -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/search_eval")
+    }
+
+    #[test]
+    fn test_fixture_relevance_stays_above_floors() {
+        let (elements, judgments) = load_fixtures(&fixtures_dir()).unwrap();
+        assert!(!judgments.floors.is_empty());
+        for (mode, floor) in &judgments.floors {
+            let report = evaluate(&elements, &judgments, &SearchParams::default(), *mode).unwrap();
+            let below = report.mean.below(floor);
+            assert!(
+                below.is_empty(),
+                "{} search fell below its floors: {below:?}\n{:#?}",
+                mode.as_str(),
+                report.queries
+            );
+        }
+    }
+
+    #[test]
+    fn test_compare_reports_per_query_deltas() {
+        let (elements, judgments) = load_fixtures(&fixtures_dir()).unwrap();
+        let baseline = evaluate(
+            &elements,
+            &judgments,
+            &SearchParams::default(),
+            SearchMode::Keyword,
+        )
+        .unwrap();
+        // Scoring code alone loses the name and docstring signal, which
+        // reorders some natural-language queries.
+        let code_only = SearchParams {
+            boosts: FieldBoosts {
+                name: 0.0,
+                signature: 0.0,
+                docstring: 0.0,
+                summary: 0.0,
+                code: 1.0,
+            },
+            ..SearchParams::default()
+        };
+        let candidate = evaluate(&elements, &judgments, &code_only, SearchMode::Keyword).unwrap();
+
+        let deltas = compare(&baseline, &baseline);
+        assert_eq!(deltas.len(), judgments.queries.len());
+        assert!(deltas.iter().all(|d| d.delta() == Metrics::default()));
+
+        let deltas = compare(&baseline, &candidate);
+        assert_eq!(deltas[0].query, judgments.queries[0].query);
+        assert!(deltas.iter().any(|d| d.delta() != Metrics::default()));
+    }
+
+    #[test]
+    fn test_unknown_judged_element_is_an_error() {
+        let (elements, mut judgments) = load_fixtures(&fixtures_dir()).unwrap();
+        judgments.queries[0].relevant[0].element.name = "no_such_function".to_string();
+        let err = evaluate(
+            &elements,
+            &judgments,
+            &SearchParams::default(),
+            SearchMode::Keyword,
+        )
+        .unwrap_err();
+        assert!(matches!(err, EvalError::UnknownElement(key) if key.name == "no_such_function"));
+    }
+}
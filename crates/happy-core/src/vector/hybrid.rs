@@ -0,0 +1,143 @@
+use std::collections::HashMap;
+
+use super::embedder::Embedder;
+use super::{BM25Index, VectorIndex};
+
+/// How many candidates each retriever contributes per requested result.
+const CANDIDATE_MULTIPLIER: usize = 4;
+const MIN_CANDIDATES: usize = 20;
+
+/// Merge BM25 keyword scores with cosine similarity from the vector index.
+///
+/// Both score lists are min-max normalized to `[0, 1]` and blended as
+/// `alpha * semantic + (1 - alpha) * keyword`, so `alpha = 0.0` is pure BM25
+/// and `alpha = 1.0` is pure vector search. Falls back to plain BM25 when the
+/// vector index is empty or the query can't be embedded.
+pub fn hybrid_search(
+    bm25: &BM25Index,
+    vectors: &VectorIndex,
+    embedder: &dyn Embedder,
+    query: &str,
+    k: usize,
+    alpha: f64,
+) -> Vec<(String, f64)> {
+    let alpha = alpha.clamp(0.0, 1.0);
+    let pool = (k * CANDIDATE_MULTIPLIER).max(MIN_CANDIDATES);
+
+    let query_vector = if vectors.is_empty() || vectors.dimension() != embedder.dimension() {
+        None
+    } else {
+        embedder.embed_one(query).ok()
+    };
+    let Some(query_vector) = query_vector else {
+        return bm25.search(query, k);
+    };
+
+    let keyword = normalize_scores(bm25.search(query, pool));
+    let semantic = normalize_scores(
+        vectors
+            .search(&query_vector, pool, 0.0)
+            .into_iter()
+            .map(|(id, score)| (id, f64::from(score)))
+            .collect(),
+    );
+
+    let mut merged: HashMap<String, f64> = HashMap::new();
+    for (id, score) in keyword {
+        *merged.entry(id).or_insert(0.0) += (1.0 - alpha) * score;
+    }
+    for (id, score) in semantic {
+        *merged.entry(id).or_insert(0.0) += alpha * score;
+    }
+
+    let mut results: Vec<(String, f64)> = merged.into_iter().collect();
+    results.sort_by(|a, b| {
+        b.1.partial_cmp(&a.1)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then_with(|| a.0.cmp(&b.0))
+    });
+    results.truncate(k);
+    results
+}
+
+/// Min-max normalize scores to `[0, 1]`. A single (or constant) score maps to 1.
+fn normalize_scores(scores: Vec<(String, f64)>) -> Vec<(String, f64)> {
+    let max = scores.iter().map(|(_, s)| *s).fold(f64::MIN, f64::max);
+    let min = scores.iter().map(|(_, s)| *s).fold(f64::MAX, f64::min);
+    let range = max - min;
+    scores
+        .into_iter()
+        .map(|(id, s)| {
+            let norm = if range > f64::EPSILON {
+                (s - min) / range
+            } else {
+                1.0
+            };
+            (id, norm)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vector::embedder::{CallbackEmbedder, HashingEmbedder};
+
+    fn build() -> (BM25Index, VectorIndex, HashingEmbedder) {
+        let embedder = HashingEmbedder::new(128);
+        let docs = [
+            ("load", "def load_config(path): read settings from disk"),
+            ("save", "def save_config(path): write settings to disk"),
+            ("render", "def render_widget(): draw the button"),
+        ];
+        let mut bm25 = BM25Index::new();
+        let mut vectors = VectorIndex::new(embedder.dimension());
+        for (id, text) in docs {
+            bm25.add_document(id, text);
+            vectors.insert(id, embedder.embed_one(text).unwrap());
+        }
+        (bm25, vectors, embedder)
+    }
+
+    #[test]
+    fn test_hybrid_alpha_zero_matches_bm25_order() {
+        let (bm25, vectors, embedder) = build();
+        let hybrid = hybrid_search(&bm25, &vectors, &embedder, "button", 3, 0.0);
+        assert_eq!(hybrid[0].0, "render");
+    }
+
+    #[test]
+    fn test_hybrid_finds_semantic_only_match() {
+        let (bm25, vectors, hashing) = build();
+        // BM25 has no token of "preferences read"; an embedder that knows
+        // preferences are settings still reaches the config loader.
+        let embedder = CallbackEmbedder::new(hashing.dimension(), move |texts| {
+            let texts: Vec<String> = texts
+                .iter()
+                .map(|text| text.replace("preferences", "settings"))
+                .collect();
+            hashing.embed(&texts)
+        });
+        assert!(bm25.search("preferences read", 3).is_empty());
+        let hybrid = hybrid_search(&bm25, &vectors, &embedder, "preferences read", 3, 0.5);
+        assert_eq!(hybrid[0].0, "load");
+    }
+
+    #[test]
+    fn test_hybrid_matches_identifier_parts() {
+        let (bm25, vectors, embedder) = build();
+        // Both scorers split "load_config" into words, so "config load"
+        // reaches it either way.
+        assert_eq!(bm25.search("config load", 3)[0].0, "load");
+        let hybrid = hybrid_search(&bm25, &vectors, &embedder, "config load", 3, 0.5);
+        assert_eq!(hybrid[0].0, "load");
+    }
+
+    #[test]
+    fn test_hybrid_falls_back_without_vectors() {
+        let (bm25, _vectors, embedder) = build();
+        let empty = VectorIndex::new(embedder.dimension());
+        let hybrid = hybrid_search(&bm25, &empty, &embedder, "button", 3, 0.5);
+        assert_eq!(hybrid, bm25.search("button", 3));
+    }
+}
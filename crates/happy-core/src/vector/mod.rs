@@ -1,5 +1,14 @@
 pub mod bm25;
 pub mod cosine;
+pub mod embedder;
+pub mod embedding_client;
+pub mod eval;
+pub mod hybrid;
+pub mod pipeline;
 
 pub use bm25::BM25Index;
 pub use cosine::VectorIndex;
+pub use embedder::{CallbackEmbedder, EmbedError, Embedder, HashingEmbedder};
+pub use embedding_client::{EmbeddingApi, EmbeddingClient, EmbeddingConfig, EmbeddingEndpoint};
+pub use hybrid::hybrid_search;
+pub use pipeline::{EmbedProgress, EmbedReport, EmbeddingBudget};
@@ -0,0 +1,297 @@
+use std::collections::HashSet;
+use std::path::Path;
+
+use crate::indexer::{CodeElement, ElementType};
+use crate::vector::VectorIndex;
+
+use super::embedder::{EmbedError, Embedder, element_embedding_text};
+use super::embedding_client::EmbeddingClient;
+
+/// Limits applied when embedding elements.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EmbeddingBudget {
+    /// Texts sent per `Embedder::embed` call.
+    pub batch_size: usize,
+    /// Stop after embedding this many elements.
+    pub max_elements: Option<usize>,
+    /// Skip elements whose text is estimated above this many tokens.
+    pub max_tokens_per_element: usize,
+    /// Embed full element code instead of the compact summary text.
+    pub include_code: bool,
+}
+
+impl EmbeddingBudget {
+    /// Budget for the local hashing embedder: embedding is cheap, so include
+    /// code and don't cap anything.
+    pub fn local() -> Self {
+        Self {
+            batch_size: 1024,
+            max_elements: None,
+            max_tokens_per_element: usize::MAX,
+            include_code: true,
+        }
+    }
+}
+
+/// Progress reported after every batch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EmbedProgress {
+    pub embedded: usize,
+    pub total: usize,
+}
+
+/// Outcome of an embedding run.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EmbedReport {
+    pub embedded: usize,
+    /// Elements left out because `max_elements` was reached.
+    pub skipped_budget: usize,
+    /// Elements left out because their text exceeded `max_tokens_per_element`.
+    pub skipped_too_large: usize,
+}
+
+/// Text sent to a remote embedder: the summary if one exists, otherwise name,
+/// signature, and docstring. Full code only when `include_code` is set.
+pub fn element_summary_text(elem: &CodeElement, include_code: bool) -> String {
+    if include_code {
+        return element_embedding_text(elem);
+    }
+    if let Some(summary) = elem.summary.as_deref().filter(|s| !s.is_empty()) {
+        return format!("{} {summary}", elem.name);
+    }
+    let mut text = elem.name.clone();
+    for part in [elem.signature.as_deref(), elem.docstring.as_deref()]
+        .into_iter()
+        .flatten()
+    {
+        text.push(' ');
+        text.push_str(part);
+    }
+    text
+}
+
+/// Whether [`embed_in_batches`] would give `elem` a new vector in `index`:
+/// it has none, or the one it has was embedded from different text, because
+/// the element changed since or the text was never recorded.
+pub fn needs_embedding(index: &VectorIndex, elem: &CodeElement, include_code: bool) -> bool {
+    elem.element_type != ElementType::File
+        && !index.is_current(&elem.id, &element_summary_text(elem, include_code))
+}
+
+/// Rough token count (~4 bytes per token), good enough for budgeting.
+pub fn estimate_tokens(text: &str) -> usize {
+    text.len().div_ceil(4)
+}
+
+/// Embed `elements` in batches and insert the vectors into `index`.
+///
+/// File elements are skipped (their text is the whole file). `progress` is
+/// called after each batch. A failed batch aborts the run; vectors from
+/// earlier batches stay in `index`.
+pub fn embed_in_batches(
+    embedder: &dyn Embedder,
+    elements: &[CodeElement],
+    index: &mut VectorIndex,
+    budget: &EmbeddingBudget,
+    progress: &mut dyn FnMut(EmbedProgress),
+) -> Result<EmbedReport, EmbedError> {
+    let mut report = EmbedReport::default();
+    let mut pending: Vec<(&str, String)> = Vec::new();
+
+    for elem in elements {
+        if elem.element_type == ElementType::File {
+            continue;
+        }
+        let text = element_summary_text(elem, budget.include_code);
+        if estimate_tokens(&text) > budget.max_tokens_per_element {
+            report.skipped_too_large += 1;
+            continue;
+        }
+        if budget.max_elements.is_some_and(|max| pending.len() >= max) {
+            report.skipped_budget += 1;
+            continue;
+        }
+        pending.push((elem.id.as_str(), text));
+    }
+
+    let total = pending.len();
+    for batch in pending.chunks(budget.batch_size.max(1)) {
+        let texts: Vec<String> = batch.iter().map(|(_, text)| text.clone()).collect();
+        let vectors = embedder.embed(&texts)?;
+        for ((id, text), vector) in batch.iter().zip(vectors) {
+            if index.insert_embedded(id, vector, text) {
+                report.embedded += 1;
+            }
+        }
+        progress(EmbedProgress {
+            embedded: report.embedded,
+            total,
+        });
+    }
+
+    Ok(report)
+}
+
+/// What [`refresh_cached_vectors`] left behind.
+#[derive(Debug)]
+pub struct VectorRefresh {
+    pub vectors: VectorIndex,
+    /// Elements whose cached vector was still current.
+    pub reused: usize,
+    pub report: EmbedReport,
+    /// Why embedding stopped early. Vectors embedded before it are kept
+    /// and saved.
+    pub error: Option<EmbedError>,
+}
+
+/// Bring the vectors cached at `path` (`.happy/vectors.bin`) up to date with
+/// `elements`: drop those of elements that are gone, embed the new and
+/// changed ones with `client`, and save the result. A cache from another
+/// model or of another dimension is started over.
+pub fn refresh_cached_vectors(
+    client: &EmbeddingClient,
+    elements: &[CodeElement],
+    path: &Path,
+    progress: &mut dyn FnMut(EmbedProgress),
+) -> VectorRefresh {
+    let model = client.config().model.as_str();
+    let mut vectors = match crate::store::load_vectors(path) {
+        Ok(vectors) if vectors.dimension() == client.dimension() && vectors.model() == model => {
+            vectors
+        }
+        _ => VectorIndex::for_model(client.dimension(), model),
+    };
+
+    let live: HashSet<&str> = elements.iter().map(|elem| elem.id.as_str()).collect();
+    for id in vectors.ids() {
+        if !live.contains(id.as_str()) {
+            vectors.remove(&id);
+        }
+    }
+    let budget = client.config().budget();
+    // Elements edited since the last run keep their IDs, so compare what
+    // would be embedded with what was.
+    let stale: Vec<CodeElement> = elements
+        .iter()
+        .filter(|elem| needs_embedding(&vectors, elem, budget.include_code))
+        .cloned()
+        .collect();
+    let candidates = elements
+        .iter()
+        .filter(|elem| elem.element_type != ElementType::File)
+        .count();
+
+    let (report, error) = match embed_in_batches(client, &stale, &mut vectors, &budget, progress) {
+        Ok(report) => (report, None),
+        Err(err) => (EmbedReport::default(), Some(err)),
+    };
+
+    if let Some(parent) = path.parent() {
+        let _ = std::fs::create_dir_all(parent);
+    }
+    if let Err(err) = crate::store::save_vectors(&vectors, path) {
+        tracing::warn!(error = %err, path = %path.display(), "failed to persist embeddings");
+    }
+    VectorRefresh {
+        vectors,
+        reused: candidates - stale.len(),
+        report,
+        error,
+    }
+}
+
+/// Apply a file change to `index`: drop vectors for `removed_ids`, then embed
+/// only `new_elements`.
+pub fn update_file_vectors(
+    embedder: &dyn Embedder,
+    index: &mut VectorIndex,
+    removed_ids: &[String],
+    new_elements: &[CodeElement],
+    budget: &EmbeddingBudget,
+) -> Result<EmbedReport, EmbedError> {
+    for id in removed_ids {
+        index.remove(id);
+    }
+    embed_in_batches(embedder, new_elements, index, budget, &mut |_| {})
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vector::embedder::HashingEmbedder;
+    use std::collections::HashMap;
+
+    fn element(id: &str, element_type: ElementType, code: &str) -> CodeElement {
+        CodeElement {
+            id: id.to_string(),
+            element_type,
+            name: id.to_string(),
+            file_path: "a.py".to_string(),
+            relative_path: "a.py".to_string(),
+            language: "python".to_string(),
+            start_line: 1,
+            end_line: 1,
+            code: code.into(),
+            signature: Some(format!("def {id}():")),
+            docstring: None,
+            summary: None,
+            parent_id: None,
+            metadata: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn test_budget_limits_and_file_skip() {
+        let embedder = HashingEmbedder::new(32);
+        let elements = vec![
+            element("file", ElementType::File, "whole file"),
+            element("a", ElementType::Function, "pass"),
+            element("b", ElementType::Function, "pass"),
+            element("c", ElementType::Function, "pass"),
+            element("huge", ElementType::Function, &"x = 1\n".repeat(1000)),
+        ];
+        let budget = EmbeddingBudget {
+            batch_size: 2,
+            max_elements: Some(2),
+            max_tokens_per_element: 200,
+            include_code: true,
+        };
+
+        let mut index = VectorIndex::new(32);
+        let report =
+            embed_in_batches(&embedder, &elements, &mut index, &budget, &mut |_| {}).unwrap();
+
+        assert_eq!(report.embedded, 2);
+        assert_eq!(report.skipped_budget, 1);
+        assert_eq!(report.skipped_too_large, 1);
+        assert!(!index.contains("file"));
+        assert!(index.contains("a") && index.contains("b"));
+    }
+
+    #[test]
+    fn test_needs_embedding_after_a_change() {
+        let embedder = HashingEmbedder::new(32);
+        let budget = EmbeddingBudget::local();
+        let mut elements = vec![
+            element("file", ElementType::File, "whole file"),
+            element("a", ElementType::Function, "pass"),
+        ];
+        let mut index = VectorIndex::new(32);
+        embed_in_batches(&embedder, &elements, &mut index, &budget, &mut |_| {}).unwrap();
+        assert!(
+            !elements
+                .iter()
+                .any(|elem| needs_embedding(&index, elem, budget.include_code))
+        );
+
+        elements[1].docstring = Some("Now documented.".to_string());
+        assert!(needs_embedding(&index, &elements[1], budget.include_code));
+    }
+
+    #[test]
+    fn test_summary_text_excludes_code_by_default() {
+        let elem = element("parse", ElementType::Function, "secret_body_token()");
+        assert!(!element_summary_text(&elem, false).contains("secret_body_token"));
+        assert!(element_summary_text(&elem, true).contains("secret_body_token"));
+    }
+}
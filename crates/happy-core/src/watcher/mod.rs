@@ -1,54 +1,291 @@
-use std::path::Path;
-use std::sync::mpsc;
-use std::time::Duration;
+use std::collections::HashMap;
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, mpsc};
+use std::time::{Duration, Instant};
 
-use notify::{Config, RecommendedWatcher, RecursiveMode, Watcher};
+use notify::event::{ModifyKind, RenameMode};
+use notify::{Config, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use serde::{Deserialize, Serialize};
+
+use crate::agent_toml::AgentToml;
+use crate::bookmarks::Bookmarks;
+
+mod poll;
+
+pub use poll::PollScanner;
+
+/// Directories whose changes never affect the index (VCS data, our own cache).
+const IGNORED_DIRS: &[&str] = &[".git", ".happy"];
+
+/// Default time between polling scans.
+pub const DEFAULT_POLL_INTERVAL_MS: u64 = 2_000;
+
+/// Default number of changed files above which a batch triggers a full
+/// rebuild instead of per-file updates (e.g. a branch switch).
+pub const DEFAULT_REBUILD_THRESHOLD: usize = 200;
+
+/// Shortest accepted polling interval.
+const MIN_POLL_INTERVAL_MS: u64 = 100;
+
+/// How long `auto` waits for the native backend to report its probe file.
+const PROBE_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Probe file written under `.happy/` to check that native events arrive.
+const PROBE_FILE: &str = "watch-probe";
+
+/// How long a rename source waits for its destination before it is reported
+/// as removed.
+const RENAME_PAIR_TIMEOUT: Duration = Duration::from_millis(500);
 
 /// Events emitted by the file watcher.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum WatchEvent {
     Modified(String),
     Created(String),
     Removed(String),
+    Renamed { from: String, to: String },
 }
 
+/// How a [`FileWatcher`] learns about changes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WatchBackend {
+    /// Native events when they are delivered for the watched directory,
+    /// polling otherwise or once the native watcher fails.
+    #[default]
+    Auto,
+    /// OS notifications (inotify, FSEvents, ReadDirectoryChangesW).
+    Native,
+    /// Periodic scans of the repo.
+    Poll,
+}
+
+impl fmt::Display for WatchBackend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            WatchBackend::Auto => "auto",
+            WatchBackend::Native => "native",
+            WatchBackend::Poll => "poll",
+        })
+    }
+}
+
+/// `[watch]` in `.happy/agent.toml`:
+///
+/// ```toml
+/// [watch]
+/// backend = "auto"  # or "native", "poll"
+/// poll_interval_ms = 2000
+/// rebuild_threshold = 200
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct WatchConfig {
+    pub backend: WatchBackend,
+    pub poll_interval_ms: u64,
+    /// Batches touching more files than this rebuild the whole index.
+    pub rebuild_threshold: usize,
+}
+
+impl Default for WatchConfig {
+    fn default() -> Self {
+        Self {
+            backend: WatchBackend::Auto,
+            poll_interval_ms: DEFAULT_POLL_INTERVAL_MS,
+            rebuild_threshold: DEFAULT_REBUILD_THRESHOLD,
+        }
+    }
+}
+
+impl WatchConfig {
+    /// Load `[watch]` from `<repo_root>/.happy/agent.toml`.
+    ///
+    /// Returns the default (`auto`, 2s polling) when the file or table is
+    /// missing.
+    pub fn load(repo_root: &Path) -> io::Result<Self> {
+        Ok(AgentToml::load(repo_root)?
+            .section("watch")?
+            .unwrap_or_default())
+    }
+
+    pub fn poll_interval(&self) -> Duration {
+        Duration::from_millis(self.poll_interval_ms.max(MIN_POLL_INTERVAL_MS))
+    }
+}
+
+/// The backend a watcher is using and why it was chosen.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct WatchStatus {
+    /// The backend asked for in `[watch]`.
+    pub configured: WatchBackend,
+    /// `native` or `poll`; never `auto`.
+    pub active: WatchBackend,
+    pub poll_interval_ms: u64,
+    /// Why `active` was chosen, e.g. the result of the startup probe.
+    pub reason: String,
+}
+
+impl WatchStatus {
+    /// The status a watcher started with `config` begins with, without
+    /// starting one. For `auto` that is native events, pending the probe.
+    pub fn from_config(config: &WatchConfig) -> Self {
+        let (active, reason) = match config.backend {
+            WatchBackend::Poll => (WatchBackend::Poll, "configured"),
+            WatchBackend::Native => (WatchBackend::Native, "configured"),
+            WatchBackend::Auto => (
+                WatchBackend::Native,
+                "not started; polling if the startup probe sees no native event",
+            ),
+        };
+        Self {
+            configured: config.backend,
+            active,
+            poll_interval_ms: config.poll_interval().as_millis() as u64,
+            reason: reason.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for WatchStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.active {
+            WatchBackend::Poll => write!(f, "polling every {}ms", self.poll_interval_ms)?,
+            active => write!(f, "{active} events")?,
+        }
+        write!(f, " ({}: {})", self.configured, self.reason)
+    }
+}
+
+/// Watcher status shared with the watcher's threads, which update it when
+/// `auto` falls back to polling.
+pub type SharedWatchStatus = Arc<Mutex<WatchStatus>>;
+
+/// The native watcher, while it is in use. Polling takes it out and drops
+/// it when it starts.
+type NativeSlot = Arc<Mutex<Option<RecommendedWatcher>>>;
+
 /// Watch a directory for file changes.
 pub struct FileWatcher {
-    _watcher: RecommendedWatcher,
+    native: NativeSlot,
     receiver: mpsc::Receiver<WatchEvent>,
+    status: SharedWatchStatus,
+    /// Tells the polling thread, if any, to exit.
+    stop: Arc<AtomicBool>,
 }
 
 impl FileWatcher {
-    /// Start watching a directory for changes.
+    /// Start watching a directory for changes with the native backend.
     pub fn new(path: &str) -> Result<Self, notify::Error> {
+        Self::with_config(
+            path,
+            &WatchConfig {
+                backend: WatchBackend::Native,
+                ..WatchConfig::default()
+            },
+        )
+    }
+
+    /// Start watching a directory with the backend chosen by `config`.
+    ///
+    /// With [`WatchBackend::Auto`] a probe file is written under `.happy/`
+    /// and the native backend is kept only if it reports the probe within a
+    /// second; otherwise, and whenever the native watcher later reports an
+    /// error, the directory is polled instead. Either way the same
+    /// [`WatchEvent`]s are emitted.
+    pub fn with_config(path: &str, config: &WatchConfig) -> Result<Self, notify::Error> {
         let (tx, rx) = mpsc::channel();
+        let root = PathBuf::from(path);
+        let stop = Arc::new(AtomicBool::new(false));
+        let status = Arc::new(Mutex::new(WatchStatus {
+            configured: config.backend,
+            active: WatchBackend::Native,
+            poll_interval_ms: config.poll_interval().as_millis() as u64,
+            reason: "configured".to_string(),
+        }));
+        let native = NativeSlot::default();
+        let poller = Poller {
+            root: root.clone(),
+            interval: config.poll_interval(),
+            tx: tx.clone(),
+            status: status.clone(),
+            stop: stop.clone(),
+            native: native.clone(),
+        };
 
-        let mut watcher = RecommendedWatcher::new(
-            move |res: Result<notify::Event, notify::Error>| {
-                if let Ok(event) = res {
-                    for path in event.paths {
-                        let path_str = path.to_string_lossy().to_string();
-                        let watch_event = match event.kind {
-                            notify::EventKind::Create(_) => WatchEvent::Created(path_str),
-                            notify::EventKind::Modify(_) => WatchEvent::Modified(path_str),
-                            notify::EventKind::Remove(_) => WatchEvent::Removed(path_str),
-                            _ => continue,
-                        };
-                        let _ = tx.send(watch_event);
+        let watcher = match config.backend {
+            WatchBackend::Native => Some(native_watcher(&root, tx, stop.clone(), None, None)?),
+            WatchBackend::Poll => {
+                poller.start("configured".to_string());
+                None
+            }
+            WatchBackend::Auto => {
+                let probe_seen = Arc::new(AtomicBool::new(false));
+                match native_watcher(
+                    &root,
+                    tx,
+                    stop.clone(),
+                    Some(probe_seen.clone()),
+                    Some(poller.clone()),
+                ) {
+                    Ok(watcher) => match probe_native(&root, &probe_seen) {
+                        Some(true) => {
+                            lock_status(&status).reason = "probe event received".to_string();
+                            Some(watcher)
+                        }
+                        Some(false) => {
+                            drop(watcher);
+                            poller.start(format!(
+                                "no native event for the probe file within {}ms",
+                                PROBE_TIMEOUT.as_millis()
+                            ));
+                            None
+                        }
+                        None => {
+                            lock_status(&status).reason =
+                                "probe file could not be written, assuming native events work"
+                                    .to_string();
+                            Some(watcher)
+                        }
+                    },
+                    Err(err) => {
+                        poller.start(format!("native watcher failed to start: {err}"));
+                        None
                     }
                 }
-            },
-            Config::default(),
-        )?;
-
-        watcher.watch(Path::new(path), RecursiveMode::Recursive)?;
+            }
+        };
+        {
+            // A native error may already have started polling, which empties
+            // this slot; don't put the watcher back then.
+            let status = lock_status(&status);
+            if status.active == WatchBackend::Native {
+                *lock_native(&native) = watcher;
+            }
+            tracing::info!(status = %status, "file watcher started");
+        }
 
         Ok(Self {
-            _watcher: watcher,
+            native,
             receiver: rx,
+            status,
+            stop,
         })
     }
 
+    /// The backend in use and why; updated if `auto` falls back to polling.
+    pub fn status(&self) -> WatchStatus {
+        lock_status(&self.status).clone()
+    }
+
+    /// A handle that keeps reflecting this watcher's status, for health
+    /// reporting elsewhere.
+    pub fn status_handle(&self) -> SharedWatchStatus {
+        self.status.clone()
+    }
+
     /// Try to receive the next event (non-blocking).
     pub fn try_recv(&self) -> Option<WatchEvent> {
         self.receiver.try_recv().ok()
@@ -58,4 +295,631 @@ impl FileWatcher {
     pub fn recv_timeout(&self, timeout: Duration) -> Option<WatchEvent> {
         self.receiver.recv_timeout(timeout).ok()
     }
+
+    /// Block until an event arrives, keep collecting until no new event has
+    /// arrived for `debounce`, and return the batch coalesced per path (see
+    /// [`coalesce_events`]).
+    ///
+    /// A continuous stream of events is cut off after `debounce * 10` so
+    /// callers still make progress. Returns an empty batch only once the
+    /// watcher has shut down.
+    pub fn recv_batch(&self, debounce: Duration) -> Vec<WatchEvent> {
+        let Ok(first) = self.receiver.recv() else {
+            return Vec::new();
+        };
+        let deadline = Instant::now() + debounce * 10;
+        let mut events = vec![first];
+        while Instant::now() < deadline {
+            match self.receiver.recv_timeout(debounce) {
+                Ok(event) => events.push(event),
+                Err(_) => break,
+            }
+        }
+        coalesce_events(events)
+    }
+}
+
+impl Drop for FileWatcher {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        // The watcher's callback holds a clone of the slot, so it has to be
+        // emptied explicitly.
+        drop(lock_native(&self.native).take());
+    }
+}
+
+fn lock_status(status: &Mutex<WatchStatus>) -> MutexGuard<'_, WatchStatus> {
+    status
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Start the native backend on `root`. Sets `probe_seen` when the probe
+/// file is reported, and starts `fallback` if the backend reports an error.
+///
+/// A helper thread, stopped by `stop`, reports rename sources whose
+/// destination never arrives as removals after [`RENAME_PAIR_TIMEOUT`].
+fn native_watcher(
+    root: &Path,
+    tx: mpsc::Sender<WatchEvent>,
+    stop: Arc<AtomicBool>,
+    probe_seen: Option<Arc<AtomicBool>>,
+    fallback: Option<Poller>,
+) -> Result<RecommendedWatcher, notify::Error> {
+    let translator = Arc::new(Mutex::new(EventTranslator::new(root)));
+    let probe_path = Path::new(".happy").join(PROBE_FILE);
+
+    let callback_translator = translator.clone();
+    let callback_tx = tx.clone();
+    let mut watcher = RecommendedWatcher::new(
+        move |res: Result<notify::Event, notify::Error>| match res {
+            Ok(event) => {
+                if let Some(seen) = &probe_seen
+                    && event.paths.iter().any(|p| p.ends_with(&probe_path))
+                {
+                    seen.store(true, Ordering::Relaxed);
+                }
+                let events = lock_translator(&callback_translator).translate(event);
+                for watch_event in events {
+                    tracing::trace!(event = ?watch_event, "file watcher event");
+                    let _ = callback_tx.send(watch_event);
+                }
+            }
+            Err(err) => {
+                tracing::warn!(error = %err, "native file watcher error");
+                if let Some(poller) = &fallback {
+                    poller.start(format!("native watcher error: {err}"));
+                }
+            }
+        },
+        Config::default(),
+    )?;
+
+    watcher.watch(root, RecursiveMode::Recursive)?;
+
+    std::thread::spawn(move || {
+        loop {
+            std::thread::sleep(RENAME_PAIR_TIMEOUT / 2);
+            if stop.load(Ordering::Relaxed) {
+                return;
+            }
+            let expired = lock_translator(&translator).expire(Instant::now());
+            if let Some(event) = expired {
+                tracing::trace!(event = ?event, "file watcher event");
+                if tx.send(event).is_err() {
+                    return;
+                }
+            }
+        }
+    });
+    Ok(watcher)
+}
+
+fn lock_native(
+    native: &Mutex<Option<RecommendedWatcher>>,
+) -> MutexGuard<'_, Option<RecommendedWatcher>> {
+    native
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+fn lock_translator(translator: &Mutex<EventTranslator>) -> MutexGuard<'_, EventTranslator> {
+    translator
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Write a probe file under `<root>/.happy/` and wait for the native backend
+/// to report it. `None` if the probe could not be written.
+fn probe_native(root: &Path, seen: &AtomicBool) -> Option<bool> {
+    let dir = root.join(".happy");
+    let probe = dir.join(PROBE_FILE);
+    std::fs::create_dir_all(&dir).ok()?;
+    std::fs::write(&probe, b"probe").ok()?;
+
+    let deadline = Instant::now() + PROBE_TIMEOUT;
+    while !seen.load(Ordering::Relaxed) && Instant::now() < deadline {
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    let _ = std::fs::remove_file(&probe);
+    Some(seen.load(Ordering::Relaxed))
+}
+
+/// Everything needed to start polling, possibly from the native watcher's
+/// error callback.
+#[derive(Clone)]
+struct Poller {
+    root: PathBuf,
+    interval: Duration,
+    tx: mpsc::Sender<WatchEvent>,
+    status: SharedWatchStatus,
+    stop: Arc<AtomicBool>,
+    /// The native watcher being replaced, dropped by the polling thread:
+    /// the watcher's own callback, which may start polling, can't drop it.
+    native: NativeSlot,
+}
+
+impl Poller {
+    /// Switch the status to polling and start the polling thread, unless it
+    /// is already running.
+    fn start(&self, reason: String) {
+        {
+            let mut status = lock_status(&self.status);
+            if status.active == WatchBackend::Poll {
+                return;
+            }
+            status.active = WatchBackend::Poll;
+            status.reason = reason;
+            tracing::info!(status = %status, "file watcher polling");
+        }
+
+        // Take the baseline now so changes made right after this call are seen.
+        let mut scanner = PollScanner::new(&self.root);
+        let poller = self.clone();
+        std::thread::spawn(move || {
+            drop(lock_native(&poller.native).take());
+            loop {
+                std::thread::sleep(poller.interval);
+                if poller.stop.load(Ordering::Relaxed) {
+                    return;
+                }
+                for event in scanner.tick() {
+                    tracing::trace!(event = ?event, "file watcher poll event");
+                    if poller.tx.send(event).is_err() {
+                        return;
+                    }
+                }
+            }
+        });
+    }
+}
+
+/// Per-path state while coalescing a batch.
+#[derive(Clone)]
+enum PathChange {
+    Created,
+    Modified,
+    Removed,
+    RenamedFrom(String),
+}
+
+/// Collapse a burst of events into at most one event per path, in order of
+/// first appearance:
+///
+/// - repeated `Modified`, and `Created` followed by `Modified`, collapse into
+///   the first event;
+/// - `Modified` after `Removed` is dropped, while `Created` after `Removed`
+///   (an editor's delete-and-replace save) becomes `Modified`;
+/// - a rename of a path created in the same batch becomes `Created` at the
+///   destination, and chained renames collapse into one `Renamed`.
+pub fn coalesce_events(events: impl IntoIterator<Item = WatchEvent>) -> Vec<WatchEvent> {
+    let mut order: Vec<Option<(String, PathChange)>> = Vec::new();
+    let mut slots: HashMap<String, usize> = HashMap::new();
+
+    fn set(
+        order: &mut Vec<Option<(String, PathChange)>>,
+        slots: &mut HashMap<String, usize>,
+        path: String,
+        change: PathChange,
+    ) {
+        match slots.get(&path) {
+            Some(&slot) => order[slot] = Some((path, change)),
+            None => {
+                slots.insert(path.clone(), order.len());
+                order.push(Some((path, change)));
+            }
+        }
+    }
+
+    fn take(
+        order: &mut [Option<(String, PathChange)>],
+        slots: &mut HashMap<String, usize>,
+        path: &str,
+    ) -> Option<PathChange> {
+        let slot = slots.remove(path)?;
+        order[slot].take().map(|(_, change)| change)
+    }
+
+    for event in events {
+        match event {
+            WatchEvent::Created(path) => {
+                let current = slots.get(&path).and_then(|&i| order[i].as_ref());
+                let next = match current.map(|(_, change)| change) {
+                    None => PathChange::Created,
+                    Some(PathChange::Removed) => PathChange::Modified,
+                    Some(change) => change.clone(),
+                };
+                set(&mut order, &mut slots, path, next);
+            }
+            WatchEvent::Modified(path) => {
+                if !slots.contains_key(&path) {
+                    set(&mut order, &mut slots, path, PathChange::Modified);
+                }
+            }
+            WatchEvent::Removed(path) => match take(&mut order, &mut slots, &path) {
+                Some(PathChange::RenamedFrom(from)) => {
+                    set(&mut order, &mut slots, from, PathChange::Removed)
+                }
+                _ => set(&mut order, &mut slots, path, PathChange::Removed),
+            },
+            WatchEvent::Renamed { from, to } => {
+                let next = match take(&mut order, &mut slots, &from) {
+                    Some(PathChange::Created) => PathChange::Created,
+                    Some(PathChange::RenamedFrom(original)) => PathChange::RenamedFrom(original),
+                    _ => PathChange::RenamedFrom(from),
+                };
+                take(&mut order, &mut slots, &to);
+                set(&mut order, &mut slots, to, next);
+            }
+        }
+    }
+
+    order
+        .into_iter()
+        .flatten()
+        .map(|(path, change)| match change {
+            PathChange::Created => WatchEvent::Created(path),
+            PathChange::Modified => WatchEvent::Modified(path),
+            PathChange::Removed => WatchEvent::Removed(path),
+            PathChange::RenamedFrom(from) => WatchEvent::Renamed { from, to: path },
+        })
+        .collect()
+}
+
+/// Maps raw `notify` events to `WatchEvent`s.
+///
+/// Backends report a rename either as one `Both` event with `[from, to]` or as
+/// a `From` event followed by a `To` event (paired by tracker ID when the
+/// backend provides one). An unpaired `From` becomes `Removed` once the next
+/// event arrives or [`RENAME_PAIR_TIMEOUT`] passes (see
+/// [`EventTranslator::expire`]), and an unpaired `To` becomes `Created`.
+struct EventTranslator {
+    root: PathBuf,
+    /// Rename source path, its tracker ID, and when it arrived.
+    pending_from: Option<(String, Option<usize>, Instant)>,
+}
+
+impl EventTranslator {
+    fn new(root: &Path) -> Self {
+        Self {
+            root: root.to_path_buf(),
+            pending_from: None,
+        }
+    }
+
+    fn translate(&mut self, event: notify::Event) -> Vec<WatchEvent> {
+        let mut out = Vec::new();
+        let tracker = event.attrs.tracker();
+        let paths: Vec<(String, bool)> = event
+            .paths
+            .iter()
+            .map(|p| (p.to_string_lossy().to_string(), is_ignored(&self.root, p)))
+            .collect();
+        let mut paths = paths.into_iter();
+
+        match event.kind {
+            EventKind::Modify(ModifyKind::Name(RenameMode::From)) => {
+                self.flush(&mut out);
+                if let Some((path, false)) = paths.next() {
+                    self.pending_from = Some((path, tracker, Instant::now()));
+                }
+            }
+            EventKind::Modify(ModifyKind::Name(RenameMode::To)) => {
+                let from = match self.pending_from.take() {
+                    Some((from, from_tracker, _))
+                        if from_tracker.is_none()
+                            || tracker.is_none()
+                            || from_tracker == tracker =>
+                    {
+                        Some(from)
+                    }
+                    other => {
+                        self.pending_from = other;
+                        self.flush(&mut out);
+                        None
+                    }
+                };
+                if let Some((to, false)) = paths.next() {
+                    out.push(match from {
+                        Some(from) => WatchEvent::Renamed { from, to },
+                        None => WatchEvent::Created(to),
+                    });
+                } else if let Some(from) = from {
+                    // Moved into an ignored directory: gone as far as we care.
+                    out.push(WatchEvent::Removed(from));
+                }
+            }
+            EventKind::Modify(ModifyKind::Name(RenameMode::Both)) => {
+                self.flush(&mut out);
+                match (paths.next(), paths.next()) {
+                    (Some((from, false)), Some((to, false))) => {
+                        out.push(WatchEvent::Renamed { from, to })
+                    }
+                    (Some((from, false)), _) => out.push(WatchEvent::Removed(from)),
+                    (_, Some((to, false))) => out.push(WatchEvent::Created(to)),
+                    _ => {}
+                }
+            }
+            EventKind::Modify(ModifyKind::Name(_)) => {
+                // Unknown rename half: decide by whether the path still exists.
+                self.flush(&mut out);
+                for (path, ignored) in paths {
+                    if ignored {
+                        continue;
+                    }
+                    out.push(if Path::new(&path).exists() {
+                        WatchEvent::Created(path)
+                    } else {
+                        WatchEvent::Removed(path)
+                    });
+                }
+            }
+            kind => {
+                self.flush(&mut out);
+                for (path, ignored) in paths {
+                    if ignored {
+                        continue;
+                    }
+                    out.push(match kind {
+                        EventKind::Create(_) => WatchEvent::Created(path),
+                        EventKind::Modify(_) => WatchEvent::Modified(path),
+                        EventKind::Remove(_) => WatchEvent::Removed(path),
+                        _ => continue,
+                    });
+                }
+            }
+        }
+
+        out
+    }
+
+    /// Emit a rename source that never got its destination as a removal.
+    fn flush(&mut self, out: &mut Vec<WatchEvent>) {
+        if let Some((from, _, _)) = self.pending_from.take() {
+            out.push(WatchEvent::Removed(from));
+        }
+    }
+
+    /// Report the pending rename source as removed once it has waited
+    /// [`RENAME_PAIR_TIMEOUT`] for its destination by `now`.
+    fn expire(&mut self, now: Instant) -> Option<WatchEvent> {
+        match &self.pending_from {
+            Some((_, _, since)) if now.duration_since(*since) >= RENAME_PAIR_TIMEOUT => self
+                .pending_from
+                .take()
+                .map(|(from, _, _)| WatchEvent::Removed(from)),
+            _ => None,
+        }
+    }
+}
+
+/// Whether `path` lies under `.git/` or `.happy/` inside `root`. Only the
+/// part below the root is checked, so a repo that itself sits under such a
+/// directory is still watched. `.happy/bookmarks.json` is still reported,
+/// since sessions cache its notes.
+fn is_ignored(root: &Path, path: &Path) -> bool {
+    if path == Bookmarks::path_in(root) {
+        return false;
+    }
+    path.strip_prefix(root)
+        .unwrap_or(path)
+        .components()
+        .any(|c| IGNORED_DIRS.iter().any(|dir| c.as_os_str() == *dir))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use notify::event::{CreateKind, DataChange};
+    use std::path::PathBuf;
+
+    fn event(kind: EventKind, paths: &[&str]) -> notify::Event {
+        let mut event = notify::Event::new(kind);
+        event.paths = paths.iter().map(PathBuf::from).collect();
+        event
+    }
+
+    fn rename(mode: RenameMode, paths: &[&str]) -> notify::Event {
+        event(EventKind::Modify(ModifyKind::Name(mode)), paths)
+    }
+
+    #[test]
+    fn test_pairs_from_and_to_into_rename() {
+        let mut translator = EventTranslator::new(Path::new("/repo"));
+        assert!(
+            translator
+                .translate(rename(RenameMode::From, &["/repo/foo.py"]))
+                .is_empty()
+        );
+        assert_eq!(
+            translator.translate(rename(RenameMode::To, &["/repo/bar.py"])),
+            vec![WatchEvent::Renamed {
+                from: "/repo/foo.py".to_string(),
+                to: "/repo/bar.py".to_string(),
+            }]
+        );
+
+        assert_eq!(
+            translator.translate(rename(RenameMode::Both, &["/repo/a.py", "/repo/b.py"])),
+            vec![WatchEvent::Renamed {
+                from: "/repo/a.py".to_string(),
+                to: "/repo/b.py".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_unpaired_rename_halves_fall_back() {
+        let mut translator = EventTranslator::new(Path::new("/repo"));
+        translator.translate(rename(RenameMode::From, &["/repo/gone.py"]));
+        let modify = event(
+            EventKind::Modify(ModifyKind::Data(DataChange::Content)),
+            &["/repo/other.py"],
+        );
+        assert_eq!(
+            translator.translate(modify),
+            vec![
+                WatchEvent::Removed("/repo/gone.py".to_string()),
+                WatchEvent::Modified("/repo/other.py".to_string()),
+            ]
+        );
+        assert_eq!(
+            translator.translate(rename(RenameMode::To, &["/repo/new.py"])),
+            vec![WatchEvent::Created("/repo/new.py".to_string())]
+        );
+    }
+
+    fn modified(path: &str) -> WatchEvent {
+        WatchEvent::Modified(path.to_string())
+    }
+
+    #[test]
+    fn test_coalesce_rules() {
+        let created = WatchEvent::Created("new.py".to_string());
+        let removed = WatchEvent::Removed("gone.py".to_string());
+        let events = vec![
+            created.clone(),
+            modified("new.py"),
+            modified("a.py"),
+            removed.clone(),
+            modified("gone.py"),
+            WatchEvent::Removed("saved.py".to_string()),
+            WatchEvent::Created("saved.py".to_string()),
+            modified("a.py"),
+        ];
+        assert_eq!(
+            coalesce_events(events),
+            vec![created, modified("a.py"), removed, modified("saved.py")]
+        );
+
+        let renames = vec![
+            WatchEvent::Renamed {
+                from: "a.py".to_string(),
+                to: "b.py".to_string(),
+            },
+            WatchEvent::Renamed {
+                from: "b.py".to_string(),
+                to: "c.py".to_string(),
+            },
+            modified("c.py"),
+        ];
+        assert_eq!(
+            coalesce_events(renames),
+            vec![WatchEvent::Renamed {
+                from: "a.py".to_string(),
+                to: "c.py".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_recv_batch_collapses_save_storm() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        let file = root.join("main.py");
+        std::fs::write(&file, "x = 0\n").unwrap();
+
+        let watcher = FileWatcher::new(&root.to_string_lossy()).unwrap();
+        for i in 1..=10 {
+            std::fs::write(&file, format!("x = {i}\n")).unwrap();
+        }
+
+        let batch = watcher.recv_batch(Duration::from_millis(300));
+        assert_eq!(batch, vec![modified(&file.to_string_lossy())]);
+    }
+
+    #[test]
+    fn test_poll_backend_emits_watch_events() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        let file = root.join("main.py");
+        std::fs::write(&file, "x = 0\n").unwrap();
+
+        let config = WatchConfig {
+            backend: WatchBackend::Poll,
+            poll_interval_ms: 100,
+            ..WatchConfig::default()
+        };
+        let watcher = FileWatcher::with_config(&root.to_string_lossy(), &config).unwrap();
+        let status = watcher.status();
+        assert_eq!(status.active, WatchBackend::Poll);
+        assert_eq!(status.poll_interval_ms, 100);
+
+        std::fs::write(&file, "x = 10\n").unwrap();
+        let batch = watcher.recv_batch(Duration::from_millis(300));
+        assert_eq!(batch, vec![modified(&file.to_string_lossy())]);
+    }
+
+    #[test]
+    fn test_watch_config() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(
+            WatchConfig::load(dir.path()).unwrap(),
+            WatchConfig::default()
+        );
+
+        std::fs::create_dir_all(dir.path().join(".happy")).unwrap();
+        std::fs::write(
+            dir.path().join(".happy/agent.toml"),
+            "[watch]\nbackend = \"poll\"\npoll_interval_ms = 5\nrebuild_threshold = 50\n",
+        )
+        .unwrap();
+        let config = WatchConfig::load(dir.path()).unwrap();
+        assert_eq!(config.backend, WatchBackend::Poll);
+        assert_eq!(config.rebuild_threshold, 50);
+        assert_eq!(config.poll_interval(), Duration::from_millis(100));
+
+        let status = WatchStatus::from_config(&config);
+        assert_eq!(status.active, WatchBackend::Poll);
+        assert_eq!(status.poll_interval_ms, 100);
+        // Nothing is started, so no probe file is written.
+        assert!(!dir.path().join(".happy").join(PROBE_FILE).exists());
+    }
+
+    #[test]
+    fn test_ignores_git_and_happy_dirs() {
+        let mut translator = EventTranslator::new(Path::new("/repo"));
+        let create = event(
+            EventKind::Create(CreateKind::File),
+            &[
+                "/repo/.happy/cache.bin",
+                "/repo/.git/index",
+                "/repo/src/x.py",
+                "/repo/.happy/bookmarks.json",
+            ],
+        );
+        assert_eq!(
+            translator.translate(create),
+            vec![
+                WatchEvent::Created("/repo/src/x.py".to_string()),
+                WatchEvent::Created("/repo/.happy/bookmarks.json".to_string()),
+            ]
+        );
+
+        // Only the part below the root counts.
+        let mut translator = EventTranslator::new(Path::new("/home/me/.happy/repo"));
+        let create = event(
+            EventKind::Create(CreateKind::File),
+            &["/home/me/.happy/repo/src/x.py"],
+        );
+        assert_eq!(
+            translator.translate(create),
+            vec![WatchEvent::Created(
+                "/home/me/.happy/repo/src/x.py".to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn test_unpaired_rename_from_expires() {
+        let mut translator = EventTranslator::new(Path::new("/repo"));
+        translator.translate(rename(RenameMode::From, &["/repo/gone.py"]));
+        let now = Instant::now();
+        assert_eq!(translator.expire(now), None);
+        assert_eq!(
+            translator.expire(now + RENAME_PAIR_TIMEOUT),
+            Some(WatchEvent::Removed("/repo/gone.py".to_string()))
+        );
+        assert_eq!(translator.expire(now + RENAME_PAIR_TIMEOUT * 2), None);
+    }
 }
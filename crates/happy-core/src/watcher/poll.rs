@@ -0,0 +1,110 @@
+//! Polling fallback for filesystems that never deliver native events (NFS,
+//! SSHFS, some Docker bind mounts).
+//!
+//! Each tick walks the repo with the same ignore-aware walk the indexer uses
+//! and compares file stamps (modification time and size) against the
+//! previous scan, so unchanged files are never read.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use super::WatchEvent;
+use crate::bookmarks::Bookmarks;
+use crate::store::cache::FileStamp;
+
+/// Detects changes under a directory by diffing successive scans.
+pub struct PollScanner {
+    root: PathBuf,
+    stamps: BTreeMap<String, FileStamp>,
+}
+
+impl PollScanner {
+    /// Start from the current state of `root`; only later changes are
+    /// reported.
+    pub fn new(root: &Path) -> Self {
+        Self {
+            root: root.to_path_buf(),
+            stamps: scan(root),
+        }
+    }
+
+    /// Rescan and report what changed since the previous scan: creations
+    /// and modifications, then removals, each sorted by path. A tick over an
+    /// unchanged tree returns nothing.
+    pub fn tick(&mut self) -> Vec<WatchEvent> {
+        let current = scan(&self.root);
+        let mut events = Vec::new();
+        for (path, stamp) in &current {
+            match self.stamps.get(path) {
+                None => events.push(WatchEvent::Created(path.clone())),
+                Some(previous) if previous != stamp => {
+                    events.push(WatchEvent::Modified(path.clone()))
+                }
+                Some(_) => {}
+            }
+        }
+        for path in self.stamps.keys() {
+            if !current.contains_key(path) {
+                events.push(WatchEvent::Removed(path.clone()));
+            }
+        }
+        self.stamps = current;
+        events
+    }
+}
+
+/// Stamps of the source files under `root` and of its bookmarks file, keyed
+/// by path.
+fn scan(root: &Path) -> BTreeMap<String, FileStamp> {
+    crate::indexer::list_source_files(&root.to_string_lossy())
+        .into_iter()
+        .map(|relative| root.join(relative))
+        .chain(std::iter::once(Bookmarks::path_in(root)))
+        .filter_map(|path| {
+            let stamp = FileStamp::of(&path)?;
+            Some((path.to_string_lossy().to_string(), stamp))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_poll_scanner_reports_changes_between_ticks() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        let path = |name: &str| root.join(name).to_string_lossy().to_string();
+        std::fs::write(root.join("app.py"), "def run():\n    return 1\n").unwrap();
+        std::fs::write(root.join("old.py"), "x = 1\n").unwrap();
+
+        let mut scanner = PollScanner::new(&root);
+        assert!(scanner.tick().is_empty());
+
+        std::fs::write(root.join("new.py"), "def fresh():\n    pass\n").unwrap();
+        std::fs::write(root.join("app.py"), "def run():\n    return 100\n").unwrap();
+        std::fs::remove_file(root.join("old.py")).unwrap();
+        // Non-source files and our own cache never produce events.
+        std::fs::write(root.join("notes.bin"), [0u8; 4]).unwrap();
+        std::fs::create_dir_all(root.join(".happy")).unwrap();
+        std::fs::write(root.join(".happy/meta.py"), "x = 2\n").unwrap();
+
+        assert_eq!(
+            scanner.tick(),
+            vec![
+                WatchEvent::Modified(path("app.py")),
+                WatchEvent::Created(path("new.py")),
+                WatchEvent::Removed(path("old.py")),
+            ]
+        );
+        assert!(scanner.tick().is_empty());
+
+        // Bookmarks are reported so sessions can drop their cached notes.
+        std::fs::write(root.join(".happy/bookmarks.json"), "{}").unwrap();
+        assert_eq!(
+            scanner.tick(),
+            vec![WatchEvent::Created(path(".happy/bookmarks.json"))]
+        );
+    }
+}
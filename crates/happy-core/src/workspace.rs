@@ -0,0 +1,385 @@
+//! Several repositories indexed as one graph.
+//!
+//! A workspace is a primary repository plus the roots listed in its
+//! `.happy/workspace.toml` (or passed on the command line):
+//!
+//! ```toml
+//! roots = ["../api", "../shared"]
+//! ```
+//!
+//! Paths are relative to the primary repository. Each root keeps its own
+//! cache under its `.happy/`; their elements are merged on load, and the
+//! graph resolves imports from one root into another. Elements of roots
+//! other than the primary are namespaced by root name: their ids are
+//! rehashed and their relative paths prefixed with `<name>/`, so files at
+//! the same path in two roots stay distinct.
+
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::indexer::{CodeElement, IndexOptions, IndexSummary};
+use crate::redact::PathConfinement;
+use crate::store::cache::{CacheStatus, LoadOptions};
+
+/// Roots indexed alongside the repository, relative to it.
+pub const WORKSPACE_CONFIG: &str = ".happy/workspace.toml";
+
+/// One repository of a workspace.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct WorkspaceRoot {
+    /// The directory name, made unique within the workspace.
+    pub name: String,
+    /// Path to the repository.
+    pub path: PathBuf,
+}
+
+/// The repositories indexed together, the primary one first.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Workspace {
+    roots: Vec<WorkspaceRoot>,
+}
+
+#[derive(Deserialize)]
+struct WorkspaceToml {
+    #[serde(default)]
+    roots: Vec<PathBuf>,
+}
+
+impl Workspace {
+    /// A workspace of just `repo_root`, which should be canonical like the
+    /// roots added to it.
+    pub fn single(repo_root: &Path) -> Self {
+        let mut workspace = Self { roots: Vec::new() };
+        workspace.push(repo_root.to_path_buf());
+        workspace
+    }
+
+    /// `repo_root` and the roots in its [`WORKSPACE_CONFIG`], or just
+    /// `repo_root` when there is none. A listed root that does not exist
+    /// is an error.
+    pub fn load(repo_root: &Path) -> io::Result<Self> {
+        let workspace = Self::single(repo_root);
+        let path = repo_root.join(WORKSPACE_CONFIG);
+        let text = match std::fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(workspace),
+            Err(err) => return Err(err),
+        };
+        let parsed: WorkspaceToml =
+            toml::from_str(&text).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        let roots: Vec<PathBuf> = parsed
+            .roots
+            .iter()
+            .map(|root| repo_root.join(root))
+            .collect();
+        workspace.with_roots(&roots)
+    }
+
+    /// This workspace with `roots` added after its own. Roots already in
+    /// it are skipped.
+    pub fn with_roots(mut self, roots: &[PathBuf]) -> io::Result<Self> {
+        for root in roots {
+            let path = root.canonicalize().map_err(|err| {
+                io::Error::new(
+                    err.kind(),
+                    format!("workspace root {}: {err}", root.display()),
+                )
+            })?;
+            if !path.is_dir() {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("workspace root {} is not a directory", root.display()),
+                ));
+            }
+            self.push(path);
+        }
+        Ok(self)
+    }
+
+    /// This workspace without the roots `confinement` keeps tools from
+    /// reading, relative to the primary root. A repository's own
+    /// [`WORKSPACE_CONFIG`] cannot reach outside it this way; dropped roots
+    /// are logged.
+    pub fn confined(mut self, confinement: &PathConfinement) -> Self {
+        let primary = self.roots[0].path.clone();
+        self.roots.retain(|root| {
+            if root.path == primary || confinement.check_path(&primary, &root.path).is_ok() {
+                return true;
+            }
+            tracing::warn!(
+                root = %root.path.display(),
+                "skipping workspace root outside the repository; add it to [security] extra_roots in $CODEX_HOME/config.toml to index it"
+            );
+            false
+        });
+        self
+    }
+
+    fn push(&mut self, path: PathBuf) {
+        if self.roots.iter().any(|root| root.path == path) {
+            return;
+        }
+        let base = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "root".to_string());
+        let mut name = base.clone();
+        let mut suffix = 2;
+        while self.roots.iter().any(|root| root.name == name) {
+            name = format!("{base}-{suffix}");
+            suffix += 1;
+        }
+        self.roots.push(WorkspaceRoot { name, path });
+    }
+
+    /// Every root, the primary one first.
+    pub fn roots(&self) -> &[WorkspaceRoot] {
+        &self.roots
+    }
+
+    pub fn primary(&self) -> &WorkspaceRoot {
+        &self.roots[0]
+    }
+
+    /// Whether there is more than the primary root.
+    pub fn is_multi_root(&self) -> bool {
+        self.roots.len() > 1
+    }
+
+    /// The root paths as strings, the primary one first.
+    pub fn root_paths(&self) -> Vec<String> {
+        self.roots
+            .iter()
+            .map(|root| root.path.to_string_lossy().into_owned())
+            .collect()
+    }
+
+    /// The root holding `path`: the deepest one, when roots are nested.
+    pub fn root_of(&self, path: &Path) -> Option<&WorkspaceRoot> {
+        self.roots
+            .iter()
+            .filter(|root| path.starts_with(&root.path))
+            .max_by_key(|root| root.path.as_os_str().len())
+    }
+
+    /// Every root's elements, walked without caches and the roots other
+    /// than the primary namespaced.
+    pub fn index(&self, options: &IndexOptions) -> Vec<CodeElement> {
+        let mut elements = Vec::new();
+        for (i, root) in self.roots.iter().enumerate() {
+            let mut root_elements =
+                crate::indexer::walk_and_index(&root.path.to_string_lossy(), options);
+            if i > 0 {
+                namespace_elements(&mut root_elements, &root.name);
+            }
+            elements.extend(root_elements);
+        }
+        elements
+    }
+
+    /// Every root's elements, each root loaded from its own cache (see
+    /// [`load_or_index`](crate::store::cache::load_or_index)) and the others
+    /// namespaced. With `write_cache` off, no root's cache is written.
+    pub fn load_or_index(&self, options: &LoadOptions<'_>) -> LoadedWorkspace {
+        let mut loaded = LoadedWorkspace {
+            elements: Vec::new(),
+            statuses: Vec::with_capacity(self.roots.len()),
+            summaries: Vec::with_capacity(self.roots.len()),
+        };
+        for (i, root) in self.roots.iter().enumerate() {
+            let mut root_loaded = crate::store::cache::load_or_index(&root.path, options);
+            if i > 0 {
+                namespace_elements(&mut root_loaded.elements, &root.name);
+            }
+            loaded.elements.extend(root_loaded.elements);
+            loaded.statuses.push(root_loaded.status);
+            loaded.summaries.push(root_loaded.summary);
+        }
+        loaded
+    }
+}
+
+/// What [`Workspace::load_or_index`] loaded.
+#[derive(Debug, Clone)]
+pub struct LoadedWorkspace {
+    /// Every root's elements, the roots other than the primary namespaced.
+    pub elements: Vec<CodeElement>,
+    /// Each root's cache status, in root order.
+    pub statuses: Vec<CacheStatus>,
+    /// The [`IndexSummary`] of each root indexed from scratch, in root order.
+    pub summaries: Vec<Option<IndexSummary>>,
+}
+
+/// Give the elements of the root named `root_name` ids and relative paths
+/// of their own, so they cannot clash with another root's.
+pub fn namespace_elements(elements: &mut [CodeElement], root_name: &str) {
+    let namespaced = |id: &str| {
+        let kind = id.rsplit_once('_').map_or(id, |(kind, _)| kind);
+        CodeElement::generate_id(kind, &[root_name, id])
+    };
+    for elem in elements {
+        elem.id = namespaced(&elem.id);
+        elem.parent_id = elem.parent_id.as_deref().map(namespaced);
+        elem.relative_path = format!("{root_name}/{}", elem.relative_path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::RepositoryGraph;
+    use crate::graph::types::EdgeKind;
+    use pretty_assertions::assert_eq;
+
+    fn write(root: &Path, files: &[(&str, &str)]) {
+        for (name, text) in files {
+            let path = root.join(name);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, text).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_load_names_and_dedupes_roots() {
+        let dir = tempfile::tempdir().unwrap();
+        let base = dir.path().canonicalize().unwrap();
+        for root in ["app", "libs/shared", "vendor/shared"] {
+            std::fs::create_dir_all(base.join(root)).unwrap();
+        }
+        let app = base.join("app");
+        assert!(!Workspace::load(&app).unwrap().is_multi_root());
+
+        write(
+            &app,
+            &[(
+                WORKSPACE_CONFIG,
+                "roots = [\"../libs/shared\", \"../vendor/shared\", \"../libs/shared\"]\n",
+            )],
+        );
+        let workspace = Workspace::load(&app).unwrap();
+        let names: Vec<&str> = workspace
+            .roots()
+            .iter()
+            .map(|root| root.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["app", "shared", "shared-2"]);
+        assert_eq!(
+            workspace
+                .root_of(&base.join("vendor/shared/x.py"))
+                .unwrap()
+                .name,
+            "shared-2"
+        );
+
+        write(&app, &[(WORKSPACE_CONFIG, "roots = [\"../missing\"]\n")]);
+        assert!(Workspace::load(&app).is_err());
+    }
+
+    #[test]
+    fn test_confined_drops_roots_outside_the_repo() {
+        let dir = tempfile::tempdir().unwrap();
+        let base = dir.path().canonicalize().unwrap();
+        for root in ["app/vendored", "libs/shared", "home"] {
+            std::fs::create_dir_all(base.join(root)).unwrap();
+        }
+        let app = base.join("app");
+        write(
+            &app,
+            &[(
+                WORKSPACE_CONFIG,
+                "roots = [\"vendored\", \"../libs/shared\", \"../home\"]\n",
+            )],
+        );
+        let names = |workspace: &Workspace| -> Vec<String> {
+            workspace
+                .roots()
+                .iter()
+                .map(|root| root.name.clone())
+                .collect()
+        };
+
+        let workspace = Workspace::load(&app).unwrap();
+        assert_eq!(
+            names(&workspace.clone().confined(&PathConfinement::default())),
+            vec!["app", "vendored"]
+        );
+        let shared = PathConfinement {
+            extra_roots: vec![PathBuf::from("../libs")],
+            ..PathConfinement::default()
+        };
+        assert_eq!(
+            names(&workspace.confined(&shared)),
+            vec!["app", "vendored", "shared"]
+        );
+    }
+
+    #[test]
+    fn test_imports_resolve_across_roots() {
+        let dir = tempfile::tempdir().unwrap();
+        let base = dir.path().canonicalize().unwrap();
+        let (app, shared) = (base.join("app"), base.join("shared"));
+        write(
+            &app,
+            &[
+                (
+                    "main.py",
+                    "from billing.invoice import total\n\ndef run():\n    return total()\n",
+                ),
+                ("billing/util.py", "def total():\n    return 0\n"),
+            ],
+        );
+        write(
+            &shared,
+            &[("billing/invoice.py", "def total():\n    return 1\n")],
+        );
+        let workspace = Workspace::single(&app)
+            .with_roots(std::slice::from_ref(&shared))
+            .unwrap();
+        let options = IndexOptions::default();
+        let LoadedWorkspace {
+            elements, statuses, ..
+        } = workspace.load_or_index(&LoadOptions {
+            use_cache: false,
+            ..LoadOptions::new(&options)
+        });
+        assert_eq!(statuses.len(), 2);
+        let invoice = elements
+            .iter()
+            .find(|elem| elem.file_path.ends_with("invoice.py") && elem.name == "total")
+            .unwrap();
+        assert_eq!(invoice.relative_path, "shared/billing/invoice.py");
+
+        let mut graph = RepositoryGraph::new();
+        graph.build_from_workspace(&elements, &workspace);
+
+        let callees: Vec<String> = graph
+            .find_callees("run")
+            .into_iter()
+            .map(|node| node.file_path.clone())
+            .collect();
+        assert_eq!(
+            callees,
+            vec![
+                shared
+                    .join("billing/invoice.py")
+                    .to_string_lossy()
+                    .into_owned()
+            ]
+        );
+        let imports = graph
+            .graph
+            .edge_indices()
+            .filter(|&edge| graph.graph[edge].kind == EdgeKind::Imports)
+            .count();
+        assert!(imports > 0);
+
+        let files: Vec<usize> = graph
+            .root_breakdown()
+            .into_iter()
+            .map(|root| root.files)
+            .collect();
+        assert_eq!(files, vec![2, 1]);
+    }
+}
@@ -0,0 +1 @@
+"""A small storefront used to measure code search relevance."""
@@ -0,0 +1,33 @@
+import hashlib
+import hmac
+import os
+import secrets
+
+
+def hash_password(password, salt=None):
+    """Derive a salted PBKDF2 hash so plain text passwords are never stored."""
+    salt = salt or os.urandom(16)
+    digest = hashlib.pbkdf2_hmac("sha256", password.encode(), salt, 200_000)
+    return salt, digest
+
+
+def verify_password(password, salt, expected):
+    """Check a login attempt against the stored salted hash."""
+    _, digest = hash_password(password, salt)
+    return hmac.compare_digest(digest, expected)
+
+
+def create_session_token():
+    """Random URL-safe token identifying a logged in user."""
+    return secrets.token_urlsafe(32)
+
+
+class SessionStore:
+    """Keeps active session tokens per user in memory."""
+
+    def __init__(self):
+        self.tokens = {}
+
+    def invalidate_user_sessions(self, user_id):
+        """Log a user out everywhere by dropping all of their tokens."""
+        self.tokens.pop(user_id, None)
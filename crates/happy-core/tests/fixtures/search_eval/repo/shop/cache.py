@@ -0,0 +1,27 @@
+from collections import OrderedDict
+
+
+class LruCache:
+    """Bounded mapping that forgets the least recently used entry first."""
+
+    def __init__(self, capacity):
+        self.capacity = capacity
+        self.entries = OrderedDict()
+
+    def get(self, key):
+        """Return a cached value and mark it as recently used."""
+        if key not in self.entries:
+            return None
+        self.entries.move_to_end(key)
+        return self.entries[key]
+
+    def put(self, key, value):
+        """Store a value, evicting the oldest entry when the cache is full."""
+        self.entries[key] = value
+        self.entries.move_to_end(key)
+        if len(self.entries) > self.capacity:
+            self.evict_oldest()
+
+    def evict_oldest(self):
+        """Drop the least recently used entry."""
+        self.entries.popitem(last=False)
@@ -0,0 +1,26 @@
+import json
+import os
+
+
+class ConfigError(Exception):
+    """Raised when the settings file is missing or malformed."""
+
+
+def load_config(path):
+    """Read the JSON settings file at path and apply environment overrides."""
+    if not os.path.exists(path):
+        raise ConfigError(f"missing settings file: {path}")
+    with open(path) as handle:
+        settings = json.load(handle)
+    settings.update(parse_env_overrides(os.environ))
+    return settings
+
+
+def parse_env_overrides(env):
+    """Collect SHOP_* environment variables as lowercase setting names."""
+    prefix = "SHOP_"
+    return {
+        key[len(prefix):].lower(): value
+        for key, value in env.items()
+        if key.startswith(prefix)
+    }
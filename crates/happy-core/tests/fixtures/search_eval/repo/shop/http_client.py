@@ -0,0 +1,38 @@
+import json
+import time
+import urllib.request
+
+
+def parseHttpHeaders(raw):
+    """Split raw response header lines into a dict keyed by lowercase name."""
+    headers = {}
+    for line in raw.splitlines():
+        if ":" in line:
+            name, value = line.split(":", 1)
+            headers[name.strip().lower()] = value.strip()
+    return headers
+
+
+class HttpClient:
+    """Minimal JSON client for the inventory service."""
+
+    def __init__(self, base_url, attempts=3):
+        self.base_url = base_url
+        self.attempts = attempts
+
+    def get_json(self, endpoint):
+        """Fetch an endpoint and decode the JSON body."""
+        with urllib.request.urlopen(self.base_url + endpoint) as response:
+            return json.loads(response.read())
+
+    def retry_with_backoff(self, request):
+        """Retry a failed request, doubling the delay after every attempt."""
+        delay = 0.5
+        for attempt in range(self.attempts):
+            try:
+                return request()
+            except OSError:
+                if attempt == self.attempts - 1:
+                    raise
+                time.sleep(delay)
+                delay *= 2
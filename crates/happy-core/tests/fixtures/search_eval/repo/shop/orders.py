@@ -0,0 +1,28 @@
+DISCOUNT_CODES = {"WELCOME10": 0.10, "VIP25": 0.25}
+
+
+def calculate_order_total(order):
+    """Sum item prices times quantities, in cents, after any discount."""
+    subtotal = sum(item["price_cents"] * item["quantity"] for item in order["items"])
+    return round(subtotal * (1 - order.get("discount", 0.0)))
+
+
+def apply_discount_code(order, code):
+    """Attach a percentage discount to the order if the promo code is known."""
+    rate = DISCOUNT_CODES.get(code.upper())
+    if rate is None:
+        return False
+    order["discount"] = rate
+    return True
+
+
+class OrderRepository:
+    """Stores orders in the database."""
+
+    def __init__(self, db):
+        self.db = db
+
+    def find_by_customer(self, customer_id):
+        """All orders placed by one customer, newest first."""
+        rows = self.db.query("SELECT * FROM orders WHERE customer_id = ?", customer_id)
+        return sorted(rows, key=lambda row: row["created_at"], reverse=True)
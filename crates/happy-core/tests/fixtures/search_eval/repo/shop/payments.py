@@ -0,0 +1,34 @@
+from shop.orders import calculate_order_total
+
+
+class PaymentDeclined(Exception):
+    """The card issuer refused the charge."""
+
+
+class PaymentProcessor:
+    """Talks to the card gateway to charge and refund customers."""
+
+    def __init__(self, gateway):
+        self.gateway = gateway
+
+    def charge_card(self, card, amount_cents):
+        """Charge a credit card and return the gateway transaction id."""
+        response = self.gateway.post("/charges", {"card": card, "amount": amount_cents})
+        if response["status"] != "approved":
+            raise PaymentDeclined(response["reason"])
+        return response["transaction_id"]
+
+    def refund_payment(self, transaction_id, amount_cents=None):
+        """Give money back for an earlier charge, fully or partially."""
+        body = {"transaction": transaction_id}
+        if amount_cents is not None:
+            body["amount"] = amount_cents
+        return self.gateway.post("/refunds", body)
+
+
+def process_payment(processor, order, card):
+    """Charge the card for the order total and mark the order as paid."""
+    transaction_id = processor.charge_card(card, calculate_order_total(order))
+    order["paid"] = True
+    order["transaction_id"] = transaction_id
+    return order
@@ -0,0 +1,21 @@
+import re
+
+TAG_PATTERN = re.compile(r"<[^>]+>")
+
+
+def slugify_title(title):
+    """Turn a product title into a lowercase URL slug."""
+    return re.sub(r"[^a-z0-9]+", "-", title.lower()).strip("-")
+
+
+def strip_html_tags(text):
+    """Remove markup from a product description, keeping only the text."""
+    return TAG_PATTERN.sub("", text)
+
+
+def truncate_words(text, limit):
+    """Shorten text to at most limit words, adding an ellipsis when cut."""
+    words = text.split()
+    if len(words) <= limit:
+        return text
+    return " ".join(words[:limit]) + "..."
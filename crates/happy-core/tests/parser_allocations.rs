@@ -0,0 +1,114 @@
+//! Allocation counts of parsing and element extraction over the search
+//! fixture repo, measured with a counting global allocator. Lives in its own
+//! test binary so no other test allocates while a count is taken.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use happy_core::indexer::walker::extract_elements_from_tree;
+use happy_core::indexer::{CodeElement, walk_and_index};
+use happy_core::parser::languages::SupportedLanguage;
+use happy_core::parser::{Parser, with_thread_parser};
+use tree_sitter::Tree;
+
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        unsafe { System.realloc(ptr, layout, new_size) }
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+/// Allocations each file saves by reusing the thread's parser instead of
+/// building one: at least the parser's per-language table.
+const MIN_SAVED_PER_FILE: usize = 1;
+
+fn fixture_files() -> (PathBuf, Vec<(String, String, String)>) {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures/search_eval/repo")
+        .canonicalize()
+        .unwrap();
+    let mut files: Vec<(String, String, String)> = std::fs::read_dir(root.join("shop"))
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .map(|path| {
+            let relative = path
+                .strip_prefix(&root)
+                .unwrap()
+                .to_string_lossy()
+                .to_string();
+            let code = std::fs::read_to_string(&path).unwrap();
+            (path.to_string_lossy().to_string(), relative, code)
+        })
+        .collect();
+    files.sort();
+    (root, files)
+}
+
+fn extract(
+    files: &[(String, String, String)],
+    parse: impl Fn(&str, &str) -> Option<(SupportedLanguage, Tree)>,
+    root: &str,
+) -> (Vec<CodeElement>, usize) {
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    let mut elements = Vec::new();
+    for (path, relative, code) in files {
+        let (lang, tree) = parse(path, code).unwrap();
+        elements.extend(extract_elements_from_tree(
+            &tree, code, path, relative, lang, root,
+        ));
+    }
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed) - before;
+    (elements, allocations)
+}
+
+fn ids(elements: &[CodeElement]) -> Vec<(String, String, usize, usize)> {
+    let mut ids: Vec<_> = elements
+        .iter()
+        .map(|e| (e.id.clone(), e.name.clone(), e.start_line, e.end_line))
+        .collect();
+    ids.sort();
+    ids
+}
+
+#[test]
+fn test_thread_parser_reuse_cuts_allocations() {
+    let (root, files) = fixture_files();
+    let root = root.to_string_lossy().to_string();
+    let fresh = |path: &str, code: &str| Parser::new().parse_file(path, code);
+    let pooled =
+        |path: &str, code: &str| with_thread_parser(|parser| parser.parse_file(path, code));
+
+    // Warm the thread's parser so only steady-state reuse is measured.
+    extract(&files, pooled, &root);
+    let (fresh_elements, fresh_allocations) = extract(&files, fresh, &root);
+    let (pooled_elements, pooled_allocations) = extract(&files, pooled, &root);
+
+    assert_eq!(ids(&pooled_elements), ids(&fresh_elements));
+    assert!(
+        pooled_allocations + MIN_SAVED_PER_FILE * files.len() <= fresh_allocations,
+        "pooled parsing allocated {pooled_allocations} times, a fresh parser per file {fresh_allocations}"
+    );
+
+    // The parallel walk extracts exactly the same elements.
+    assert_eq!(
+        ids(&walk_and_index(&root, &Default::default())),
+        ids(&fresh_elements)
+    );
+}
@@ -272,6 +272,10 @@ pub enum Op {
     /// Request the list of available custom prompts.
     ListCustomPrompts,
 
+    /// Rebuild the code graph of the session's working directory from
+    /// scratch. Reply is delivered via `EventMsg::CodeGraphUpdated`.
+    ReindexCodeGraph,
+
     /// Request the list of skills for the provided `cwd` values or the session default.
     ListSkills {
         /// Working directories to scope repo skills discovery.
@@ -1033,6 +1037,14 @@ pub enum EventMsg {
     /// Notification that skill data may have been updated and clients may want to reload.
     SkillsUpdateAvailable,
 
+    /// The code graph was re-indexed in the background, after file edits or
+    /// on request.
+    CodeGraphUpdated(CodeGraphUpdatedEvent),
+
+    /// A tool call finished; lists the files it read or returned, for
+    /// clients that surface recently touched files.
+    ToolCallResult(ToolCallResultEvent),
+
     PlanUpdate(UpdatePlanArgs),
 
     TurnAborted(TurnAbortedEvent),
@@ -2287,6 +2299,29 @@ pub struct BackgroundEventEvent {
     pub message: String,
 }
 
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
+pub struct CodeGraphUpdatedEvent {
+    /// Files re-indexed or dropped from the graph. Empty for a full rebuild.
+    pub files: Vec<String>,
+    /// Whether the whole repository was indexed again.
+    pub full_rebuild: bool,
+    /// Indexed files after the update.
+    pub file_count: usize,
+    /// Indexed code elements after the update.
+    pub element_count: usize,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
+pub struct ToolCallResultEvent {
+    /// Identifier of the tool call, as sent by the model.
+    pub call_id: String,
+    pub tool_name: String,
+    /// Absolute paths of the files the call read or whose code elements it
+    /// returned.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub touched_files: Vec<PathBuf>,
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
 pub struct DeprecationNoticeEvent {
     /// Concise summary of what is deprecated.
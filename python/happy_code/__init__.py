@@ -1,17 +1,19 @@
 """happycode - Rust-native AI coding agent with high-performance code indexing."""
 
 try:
-    from .happy_core import HappyRepo
+    from .happy_core import BM25Index, HappyRepo, RepositoryGraph
 except ImportError:
     try:
         # Backward-compatible fallback for environments still loading the
         # extension module from the old package path.
-        from happy_faster_code.happy_core import HappyRepo
+        from happy_faster_code.happy_core import BM25Index, HappyRepo, RepositoryGraph
     except ImportError:
         HappyRepo = None
+        RepositoryGraph = None
+        BM25Index = None
 
 from .config import load_config
 from .launch import main as launch_main
 from .orchestrator import run as rlm_run
 
-__all__ = ["HappyRepo", "rlm_run", "load_config", "launch_main"]
+__all__ = ["HappyRepo", "RepositoryGraph", "BM25Index", "rlm_run", "load_config", "launch_main"]
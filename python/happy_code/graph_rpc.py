@@ -7,6 +7,43 @@ import socket
 import threading
 from typing import Any
 
+# What the orchestrator needs from the server; checked on the first request of
+# each connection so an older happycode fails up front rather than mid-analysis.
+REQUIRED_CAPABILITIES: dict[str, Any] = {
+    "protocol_version": 1,
+    "methods": [
+        "find_callers",
+        "find_callees",
+        "find_callers_transitive",
+        "find_callees_transitive",
+        "get_dependencies",
+        "get_dependents",
+        "file_outline",
+        "get_subclasses",
+        "get_superclasses",
+        "find_dead_code",
+        "find_import_cycles",
+        "find_path",
+        "get_related",
+        "search",
+        "hybrid_search",
+        "get_source",
+        "recent_renames",
+        "file_tree",
+        "stats",
+        "resolve_symbol",
+        "resolve_module",
+    ],
+}
+
+
+class GraphRpcIncompatible(RuntimeError):
+    """The server lacks capabilities the client asked for in ``min_capabilities``."""
+
+    def __init__(self, message: str, missing: dict[str, Any]) -> None:
+        super().__init__(message)
+        self.missing = missing
+
 
 class GraphRpcRepo:
     """HappyRepo-compatible proxy that queries the live Rust graph over localhost RPC."""
@@ -18,11 +55,14 @@ class GraphRpcRepo:
         *,
         path: str | None = None,
         timeout_seconds: float = 30.0,
+        min_capabilities: dict[str, Any] | None = None,
     ) -> None:
         host, port = endpoint.rsplit(":", 1)
         self._address = (host, int(port))
         self._token = token
         self._timeout_seconds = timeout_seconds
+        self._min_capabilities = min_capabilities
+        self._negotiated = False
         self._socket: socket.socket | None = None
         self._reader = None
         self._writer = None
@@ -51,6 +91,7 @@ class GraphRpcRepo:
         if self._socket is not None:
             return
         sock = socket.create_connection(self._address, timeout=self._timeout_seconds)
+        self._negotiated = False
         self._socket = sock
         self._reader = sock.makefile("r", encoding="utf-8")
         self._writer = sock.makefile("w", encoding="utf-8")
@@ -63,6 +104,9 @@ class GraphRpcRepo:
         }
         with self._lock:
             self._ensure_connection()
+            if self._min_capabilities is not None and not self._negotiated:
+                payload["min_capabilities"] = self._min_capabilities
+                self._negotiated = True
             assert self._writer is not None
             assert self._reader is not None
             self._writer.write(json.dumps(payload))
@@ -73,40 +117,120 @@ class GraphRpcRepo:
         if not response_line:
             raise RuntimeError("graph RPC connection closed")
         response = json.loads(response_line)
+        if "incompatible" in response:
+            raise GraphRpcIncompatible(
+                response.get("error", "incompatible graph RPC server"),
+                response["incompatible"],
+            )
         if not response.get("ok"):
             raise RuntimeError(response.get("error", "graph RPC error"))
         return response.get("result")
 
+    def capabilities(self) -> dict:
+        return self._request("capabilities")
+
     def find_callers(self, symbol: str) -> list[str]:
         return self._request("find_callers", {"symbol": symbol})
 
     def find_callees(self, symbol: str) -> list[str]:
         return self._request("find_callees", {"symbol": symbol})
 
+    def find_callers_transitive(self, symbol: str, max_depth: int = 3) -> list[list[str]]:
+        return self._request("find_callers_transitive", {"symbol": symbol, "max_depth": max_depth})
+
+    def find_callees_transitive(self, symbol: str, max_depth: int = 3) -> list[list[str]]:
+        return self._request("find_callees_transitive", {"symbol": symbol, "max_depth": max_depth})
+
     def get_dependencies(self, file_path: str) -> list[str]:
         return self._request("get_dependencies", {"file_path": file_path})
 
     def get_dependents(self, file_path: str) -> list[str]:
         return self._request("get_dependents", {"file_path": file_path})
 
+    def file_outline(self, file_path: str) -> list[dict]:
+        return self._request("file_outline", {"file_path": file_path})
+
     def get_subclasses(self, class_name: str) -> list[str]:
-        return self._request("get_subclasses", {"class_name": class_name})
+        return self._request("get_subclasses", {"symbol": class_name})
 
     def get_superclasses(self, class_name: str) -> list[str]:
-        return self._request("get_superclasses", {"class_name": class_name})
+        return self._request("get_superclasses", {"symbol": class_name})
 
-    def find_path(self, source: str, target: str) -> list[str] | None:
-        return self._request("find_path", {"source": source, "target": target})
+    def find_dead_code(self, allowlist: list[str] | None = None) -> list[str]:
+        params = {"allowlist": ",".join(allowlist)} if allowlist else {}
+        return self._request("find_dead_code", params)
 
-    def get_related(self, element: str, max_hops: int) -> list[str]:
-        return self._request("get_related", {"element": element, "max_hops": max_hops})
+    def find_import_cycles(self) -> list[list[str]]:
+        return self._request("find_import_cycles")
+
+    def find_path(
+        self, source: str, target: str, edge_kinds: list[str] | None = None
+    ) -> list[str] | None:
+        params = {"source": source, "target": target}
+        if edge_kinds:
+            params["edge_kinds"] = ",".join(edge_kinds)
+        return self._request("find_path", params)
+
+    def find_paths(
+        self,
+        source: str,
+        target: str,
+        k: int,
+        max_len: int = 6,
+        edge_kinds: list[str] | None = None,
+    ) -> list[list[str]]:
+        params = {"source": source, "target": target, "k": k, "max_len": max_len}
+        if edge_kinds:
+            params["edge_kinds"] = ",".join(edge_kinds)
+        return self._request("find_path", params)
+
+    def get_related(
+        self,
+        element: str,
+        max_hops: int,
+        direction: str | None = None,
+        edge_kinds: list[str] | None = None,
+    ) -> list[str]:
+        params = {"symbol": element, "max_hops": max_hops}
+        if direction:
+            params["direction"] = direction
+        if edge_kinds:
+            params["edge_kinds"] = ",".join(edge_kinds)
+        return self._request("get_related", params)
+
+    def summarize_related(
+        self,
+        element: str,
+        direction: str | None = None,
+        edge_kinds: list[str] | None = None,
+    ) -> dict[str, dict]:
+        params = {"symbol": element, "summarize": True}
+        if direction:
+            params["direction"] = direction
+        if edge_kinds:
+            params["edge_kinds"] = ",".join(edge_kinds)
+        return self._request("get_related", params)
 
     def search(self, query: str, k: int) -> list[tuple[str, float]]:
-        result = self._request("search", {"query": query, "k": k}) or []
+        result = self._request("search", {"query": query, "limit": k}) or []
         return [(row[0], float(row[1])) for row in result]
 
-    def get_source(self, element_id: str) -> str | None:
-        return self._request("get_source", {"element_id": element_id})
+    def hybrid_search(self, query: str, k: int, alpha: float = 0.5) -> list[tuple[str, float]]:
+        result = self._request("hybrid_search", {"query": query, "limit": k, "alpha": alpha}) or []
+        return [(row[0], float(row[1])) for row in result]
+
+    def get_source(self, element_id: str, focus: str | None = None) -> str | None:
+        params = {"symbol": element_id}
+        if focus:
+            params["focus"] = focus
+        result = self._request("get_source", params)
+        if isinstance(result, dict):
+            # The element was renamed; the server annotates it with `renamed_from`.
+            return result.get("source")
+        return result
+
+    def recent_renames(self, limit: int = 10) -> list[dict]:
+        return self._request("recent_renames", {"limit": limit}) or []
 
     def file_tree(self) -> list[str]:
         return self._request("file_tree")
@@ -120,3 +244,33 @@ class GraphRpcRepo:
 
     def resolve_module(self, module_name: str) -> str | None:
         return self._request("resolve_module", {"module_name": module_name})
+
+
+class ProgressReporter:
+    """Sends ``progress`` notifications to the Rust host over graph RPC.
+
+    Uses its own connection so reports never interleave with graph queries.
+    Failures are swallowed: progress is advisory and must not break analysis.
+    """
+
+    def __init__(self, endpoint: str, token: str, *, timeout_seconds: float = 5.0) -> None:
+        self._rpc = GraphRpcRepo(endpoint, token, timeout_seconds=timeout_seconds)
+
+    def __call__(
+        self,
+        stage: str,
+        percent: float | None = None,
+        query: str | None = None,
+    ) -> None:
+        params: dict[str, Any] = {"stage": stage}
+        if percent is not None:
+            params["percent"] = percent
+        if query:
+            params["query"] = query
+        try:
+            self._rpc._request("progress", params)
+        except Exception:
+            pass
+
+    def close(self) -> None:
+        self._rpc.close()
@@ -3,6 +3,9 @@
 import argparse
 import json
 import sys
+from collections.abc import Callable
+
+ProgressCallback = Callable[[str, float | None, str | None], None]
 
 
 def run(
@@ -15,6 +18,7 @@ def run(
     graph_rpc_endpoint: str | None = None,
     graph_rpc_token: str | None = None,
     volt_conversation_id: str | None = None,
+    progress: ProgressCallback | None = None,
 ) -> str:
     """Run the RLM agent with HappyRepo tools against a repository.
 
@@ -29,6 +33,8 @@ def run(
         graph_rpc_endpoint: Optional host:port for local graph RPC.
         graph_rpc_token: Auth token for local graph RPC.
         volt_conversation_id: Optional conversation/thread id override for Volt lookup scope.
+        progress: Optional ``progress(stage, percent, query)`` callback, called
+            as the run moves between stages and for each delegated sub-query.
 
     Returns:
         The agent's final response string.
@@ -41,6 +47,12 @@ def run(
     from .volt_memory import build_volt_memory_hooks
     from .worker import build_delegate
 
+    def report(stage: str, percent: float | None = None, sub_query: str | None = None) -> None:
+        if progress is not None:
+            progress(stage, percent, sub_query)
+
+    report("loading repository", 0)
+
     # Load config (TOML + env vars), allow model override
     config = load_config(path)
     if volt_conversation_id:
@@ -65,15 +77,17 @@ def run(
     #   2. Serialized elements snapshot (fast reconstruction)
     #   3. Filesystem re-index from path (fallback)
     if graph_rpc_endpoint and graph_rpc_token:
-        from .graph_rpc import GraphRpcRepo
+        from .graph_rpc import REQUIRED_CAPABILITIES, GraphRpcRepo
 
         try:
             repo = GraphRpcRepo(
                 graph_rpc_endpoint,
                 graph_rpc_token,
                 path=path,
+                min_capabilities=REQUIRED_CAPABILITIES,
             )
-            # Verify RPC reachability once up front so we can fallback cleanly.
+            # Verify RPC reachability and capabilities once up front so we can
+            # fallback cleanly.
             repo.stats()
         except Exception:
             if elements_file:
@@ -84,6 +98,7 @@ def run(
         repo = HappyRepo.from_elements_file(elements_file, path)
     else:
         repo = HappyRepo(path)
+    report("building tools", 10)
     memory_context, recall_memory = build_volt_memory_hooks(config, query)
     namespace = build_rlm_namespace(repo, path, recall_memory=recall_memory)
     system_prompt = build_system_prompt(repo, memory_context=memory_context)
@@ -96,6 +111,7 @@ def run(
         worker_model,
         recall_memory=recall_memory,
         memory_context=memory_context,
+        progress=progress,
     )
     namespace["delegate"] = delegate_fn
     # Backward-compatible alias used in existing prompts/docs.
@@ -107,6 +123,7 @@ def run(
         backend_kwargs={"model_name": litellm_model},
     )
 
+    report("analyzing", 20)
     result = agent.completion(
         prompt=query,
         system_prompt=system_prompt,
@@ -115,6 +132,7 @@ def run(
         verbose=verbose,
     )
 
+    report("done", 100)
     return result.response
 
 
@@ -150,6 +168,11 @@ def main():
         default=None,
         help="Auth token for --graph-rpc-endpoint.",
     )
+    parser.add_argument(
+        "--progress-rpc",
+        action="store_true",
+        help="Report progress to the host over --graph-rpc-endpoint.",
+    )
     parser.add_argument(
         "--volt-conversation-id",
         default=None,
@@ -158,6 +181,12 @@ def main():
 
     args = parser.parse_args()
 
+    progress = None
+    if args.progress_rpc and args.graph_rpc_endpoint and args.graph_rpc_token:
+        from .graph_rpc import ProgressReporter
+
+        progress = ProgressReporter(args.graph_rpc_endpoint, args.graph_rpc_token)
+
     response = run(
         path=args.path,
         query=args.query,
@@ -168,7 +197,10 @@ def main():
         graph_rpc_endpoint=args.graph_rpc_endpoint,
         graph_rpc_token=args.graph_rpc_token,
         volt_conversation_id=args.volt_conversation_id,
+        progress=progress,
     )
+    if progress is not None:
+        progress.close()
 
     if args.json:
         print(json.dumps({"query": args.query, "response": response}))
@@ -87,11 +87,29 @@ Methods:
   repo.find_callees(symbol: str) -> list[str]
       Find all functions/methods called by the given symbol. Returns element IDs.
 
+  repo.find_callers_transitive(symbol: str, max_depth: int = 3) -> list[list[str]]
+      Callers of callers up to max_depth hops. Returns element IDs grouped by
+      depth (index 0 = direct callers).
+
+  repo.find_callees_transitive(symbol: str, max_depth: int = 3) -> list[list[str]]
+      Callees of callees up to max_depth hops, grouped by depth.
+
   repo.get_dependencies(file_path: str) -> list[str]
       Get all files imported by the given file. Returns element IDs.
 
   repo.get_dependents(file_path: str) -> list[str]
       Get all files that import the given file. Returns element IDs.
+      Both accept an absolute path, a repo-relative path, or a unique path
+      suffix; an unknown or ambiguous path raises an error naming the
+      candidates. Given a module path instead ("src.app.services",
+      "my_crate::graph", a Java package), they return the modules it
+      imports or is imported by.
+
+  repo.file_outline(file_path: str) -> list[dict]
+      The classes, functions, structs, and enums the file defines, in source
+      order. Each dict has id, name, kind, signature, doc (first docstring
+      line), start_line, end_line, and children (e.g. a class's methods).
+      Paths resolve like get_dependencies.
 
   repo.get_subclasses(class_name: str) -> list[str]
       Find all classes that inherit from the given class. Returns element IDs.
@@ -99,12 +117,37 @@ Methods:
   repo.get_superclasses(class_name: str) -> list[str]
       Find all parent classes of the given class. Returns element IDs.
 
-  repo.find_path(source: str, target: str) -> list[str] | None
+  repo.find_dead_code(allowlist: list[str] | None = None) -> list[str]
+      Functions, methods, and classes nothing calls, imports, or inherits
+      from, in files nothing imports (entry points and tests skipped).
+      Returns element IDs sorted by file and line.
+
+  repo.find_import_cycles() -> list[list[str]]
+      Circular imports. Each cycle is a list of file paths in import order,
+      starting from the smallest path.
+
+  repo.find_path(source: str, target: str,
+                 edge_kinds: list[str] | None = None) -> list[str] | None
       Find shortest path between two symbols in the graph. Returns list of
-      element IDs or None if no path exists.
+      element IDs or None if no path exists. edge_kinds limits the edges
+      followed, e.g. ["calls"]; by default every kind but "defines" is.
+
+  repo.find_paths(source: str, target: str, k: int, max_len: int = 6,
+                  edge_kinds: list[str] | None = None) -> list[list[str]]
+      Up to k distinct paths of at most max_len edges, shortest first.
+      Among equally short paths, those through widely used helpers such as
+      loggers come last.
 
-  repo.get_related(element: str, max_hops: int) -> list[str]
+  repo.get_related(element: str, max_hops: int, direction: str | None = None,
+                   edge_kinds: list[str] | None = None) -> list[str]
       Find all elements within N hops of the given symbol. Returns element IDs.
+      direction is "in", "out", or "both" (default); edge_kinds limits the
+      edges followed, e.g. ["calls", "inherits"].
+
+  repo.summarize_related(element: str, direction: str | None = None,
+                         edge_kinds: list[str] | None = None) -> dict
+      Direct relationships grouped as callers, callees, importers, subclasses,
+      superclasses, and defined_in, each {"total": n, "ids": [top 10 IDs]}.
 
   repo.search(query: str, k: int) -> list[tuple[str, float]]
       BM25 keyword search across all indexed elements. Returns (element_id, score).
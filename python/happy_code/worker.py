@@ -8,6 +8,7 @@ def build_delegate(
     *,
     recall_memory=None,
     memory_context: str | None = None,
+    progress=None,
 ):
     """Build a delegate function that runs sub-queries on a worker model.
 
@@ -18,6 +19,8 @@ def build_delegate(
         repo: HappyRepo instance (shared with the parent agent).
         repo_path: Path to the repository.
         worker_model: LiteLLM model string for the worker.
+        progress: Optional ``progress(stage, percent, query)`` callback told
+            about each sub-query.
 
     Returns:
         A callable ``delegate(prompt: str) -> str``.
@@ -27,6 +30,9 @@ def build_delegate(
         """Delegate a sub-query to a worker model with full repo access."""
         from rlm import RLM
 
+        if progress is not None:
+            progress("delegating", None, prompt)
+
         from .rlm_tools import build_rlm_namespace, build_system_prompt
 
         namespace = build_rlm_namespace(
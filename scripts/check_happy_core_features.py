@@ -0,0 +1,54 @@
+#!/usr/bin/env python3
+"""Build happy-core under each grammar feature subset so no `lang-*` combination rots.
+
+Every `lang-*` feature is checked on its own and with no languages at all. The
+test suite runs with only `lang-python`, which also compiles the tests gated on
+a language being disabled.
+"""
+
+from __future__ import annotations
+
+import re
+import subprocess
+import sys
+from pathlib import Path
+
+REPO_ROOT = Path(__file__).resolve().parents[1]
+MANIFEST = REPO_ROOT / "crates" / "happy-core" / "Cargo.toml"
+MINIMAL_TEST_FEATURES = "lang-python"
+
+
+def load_lang_features() -> list[str]:
+    text = MANIFEST.read_text(encoding="utf-8")
+    return sorted(set(re.findall(r'^(lang-[a-z]+) = \["dep:', text, re.MULTILINE)))
+
+
+def cargo(*args: str) -> bool:
+    cmd = ["cargo", *args, "--manifest-path", str(MANIFEST), "--no-default-features"]
+    print("+", " ".join(cmd), flush=True)
+    return subprocess.run(cmd, cwd=REPO_ROOT).returncode == 0
+
+
+def main() -> int:
+    features = load_lang_features()
+    if not features:
+        print("No lang-* features found in happy-core/Cargo.toml", file=sys.stderr)
+        return 1
+
+    failed: list[str] = []
+    for feature in ["", *features]:
+        extra = ["--features", feature] if feature else []
+        if not cargo("check", "--all-targets", *extra):
+            failed.append(f"check {feature or '(no languages)'}")
+    if not cargo("test", "--features", MINIMAL_TEST_FEATURES):
+        failed.append(f"test {MINIMAL_TEST_FEATURES}")
+
+    if failed:
+        print("Failed:", ", ".join(failed), file=sys.stderr)
+        return 1
+    print(f"OK: happy-core builds with each of {len(features)} language features alone")
+    return 0
+
+
+if __name__ == "__main__":
+    raise SystemExit(main())
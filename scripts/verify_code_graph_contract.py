@@ -1,5 +1,5 @@
 #!/usr/bin/env python3
-"""Verify tool contract JSON stays in sync with Rust code graph tool registration."""
+"""Verify tool contract JSON stays in sync with the canonical Rust code graph tool specs."""
 
 from __future__ import annotations
 
@@ -9,18 +9,16 @@ import sys
 from pathlib import Path
 
 REPO_ROOT = Path(__file__).resolve().parents[1]
-RUST_FILE = REPO_ROOT / "core" / "src" / "tools" / "handlers" / "code_graph.rs"
+RUST_FILE = REPO_ROOT / "crates" / "happy-core" / "src" / "tool_spec.rs"
 CONTRACT_FILE = REPO_ROOT / "adapters" / "tool_contracts" / "code_graph_tools.json"
 
 
 def load_rust_tools() -> list[str]:
     text = RUST_FILE.read_text(encoding="utf-8")
-    start = text.find("pub static CODE_GRAPH_TOOL_NAMES")
-    end = text.find("];", start)
-    if start == -1 or end == -1:
-        raise RuntimeError("Could not locate CODE_GRAPH_TOOL_NAMES in Rust source")
-    block = text[start:end]
-    return re.findall(r'\("([a-z_]+)",\s*"', block)
+    start = text.find("pub static CODE_GRAPH_TOOLS")
+    if start == -1:
+        raise RuntimeError("Could not locate CODE_GRAPH_TOOLS in Rust source")
+    return re.findall(r'ToolSpec \{\s*name: "([a-z_]+)"', text[start:])
 
 
 def load_contract_tools() -> list[str]:
@@ -0,0 +1,102 @@
+"""Tests for the RepositoryGraph and BM25Index Python bindings."""
+
+import tempfile
+from pathlib import Path
+
+import pytest
+
+# The native extension must be built via `maturin develop --features python`
+from happy_code import BM25Index, RepositoryGraph
+
+pytestmark = pytest.mark.skipif(
+    RepositoryGraph is None, reason="happy_core extension not built with the python feature"
+)
+
+
+@pytest.fixture(scope="module")
+def repo_dir():
+    with tempfile.TemporaryDirectory() as tmpdir:
+        root = Path(tmpdir)
+        (root / "helpers.py").write_text(
+            "def helper(query):\n"
+            "    return query.strip().lower()\n"
+        )
+        (root / "service.py").write_text(
+            "from helpers import helper\n\n"
+            "class Service:\n"
+            "    def search(self, query):\n"
+            "        return helper(query)\n"
+        )
+        yield tmpdir
+
+
+@pytest.fixture(scope="module")
+def graph(repo_dir):
+    return RepositoryGraph.build(repo_dir)
+
+
+class TestRepositoryGraph:
+    def test_find_callers_returns_node_dicts(self, graph):
+        callers = graph.find_callers("helper")
+        assert [c["name"] for c in callers] == ["search"]
+        caller = callers[0]
+        assert caller["kind"] == "Method"
+        assert caller["qualified_name"] == "Service.search"
+        assert caller["file_path"].endswith("service.py")
+        assert caller["start_line"] == 4
+
+    def test_find_callees(self, graph):
+        assert [c["name"] for c in graph.find_callees("Service.search")] == ["helper"]
+
+    def test_get_dependencies(self, graph):
+        deps = graph.get_dependencies("service.py")
+        assert any(d["file_path"].endswith("helpers.py") for d in deps)
+
+    def test_get_related(self, graph):
+        related = graph.get_related("helper", max_hops=1, direction="in")
+        assert "search" in [r["name"] for r in related]
+
+    def test_find_path(self, graph):
+        path = graph.find_path("search", "helper")
+        assert path is not None and len(path) == 2
+
+    def test_get_source(self, graph):
+        assert graph.get_source("helper").startswith("def helper(query):")
+
+    def test_missing_symbol_raises_key_error(self, graph):
+        with pytest.raises(KeyError):
+            graph.find_callers("no_such_function")
+        with pytest.raises(KeyError):
+            graph.get_source("no_such_function")
+        with pytest.raises(KeyError):
+            graph.find_path("helper", "no_such_function")
+
+    def test_unknown_file_raises_value_error(self, graph):
+        with pytest.raises(ValueError):
+            graph.get_dependencies("missing.py")
+
+    def test_stats(self, graph):
+        stats = graph.stats()
+        assert stats["files"] == 2
+        assert stats["nodes"] > 0
+
+    def test_elements_round_trip(self, graph, repo_dir, tmp_path):
+        snapshot = str(tmp_path / "elements.bin")
+        graph.save_elements(snapshot)
+        loaded = RepositoryGraph.load_elements(snapshot, repo_dir)
+        assert loaded.stats()["elements"] == graph.stats()["elements"]
+        assert [c["name"] for c in loaded.find_callers("helper")] == ["search"]
+
+    def test_load_missing_snapshot_raises_os_error(self, repo_dir, tmp_path):
+        with pytest.raises(OSError):
+            RepositoryGraph.load_elements(str(tmp_path / "missing.bin"), repo_dir)
+
+
+class TestBM25Index:
+    def test_search(self, graph):
+        index = BM25Index(graph)
+        assert len(index) > 0
+        hits = index.search("helper query", 3)
+        assert hits
+        assert isinstance(hits[0]["id"], str)
+        assert hits[0]["score"] > 0
@@ -352,6 +352,7 @@ class TestOrchestratorRun:
     def test_run_prefers_graph_rpc_when_configured(self, monkeypatch):
         """run() should use GraphRpcRepo when endpoint+token are provided."""
         from happy_code import orchestrator
+        from happy_code.graph_rpc import REQUIRED_CAPABILITIES
 
         repo_instance = MagicMock()
         graph_rpc_cls = MagicMock(return_value=repo_instance)
@@ -407,6 +408,7 @@ class TestOrchestratorRun:
             "127.0.0.1:1234",
             "token",
             path="/repo",
+            min_capabilities=REQUIRED_CAPABILITIES,
         )
         happy_repo_cls.from_elements_file.assert_not_called()
         happy_repo_cls.assert_not_called()
@@ -414,6 +416,7 @@ class TestOrchestratorRun:
     def test_run_falls_back_to_snapshot_when_rpc_unavailable(self, monkeypatch):
         """run() should fallback to elements snapshot if graph RPC init fails."""
         from happy_code import orchestrator
+        from happy_code.graph_rpc import REQUIRED_CAPABILITIES
 
         repo_instance = MagicMock()
         graph_rpc_cls = MagicMock(side_effect=RuntimeError("rpc down"))
@@ -470,6 +473,7 @@ class TestOrchestratorRun:
             "127.0.0.1:1234",
             "token",
             path="/repo",
+            min_capabilities=REQUIRED_CAPABILITIES,
         )
         happy_repo_cls.from_elements_file.assert_called_once_with(
             "/tmp/elements.bin",
@@ -576,3 +580,56 @@ class TestOrchestratorRun:
 
         assert result == "ok"
         repo_cls.assert_called_once_with("/repo")
+
+
+class TestGraphRpc:
+    def test_min_capabilities_sent_once_and_incompatibility_raised(self):
+        """The first request carries min_capabilities; a mismatch raises GraphRpcIncompatible."""
+        import json
+        import socket
+        import threading
+
+        import pytest
+
+        from happy_code.graph_rpc import GraphRpcIncompatible, GraphRpcRepo
+
+        server = socket.create_server(("127.0.0.1", 0))
+        port = server.getsockname()[1]
+        requests = []
+
+        def serve():
+            conn, _ = server.accept()
+            with conn, conn.makefile("r") as reader, conn.makefile("w") as writer:
+                for line in reader:
+                    request = json.loads(line)
+                    requests.append(request)
+                    if "min_capabilities" in request:
+                        response = {
+                            "ok": False,
+                            "error": "incompatible graph RPC server: missing method 'teleport'",
+                            "incompatible": {"missing_methods": ["teleport"]},
+                        }
+                    else:
+                        response = {"ok": True, "result": {"nodes": 1}}
+                    writer.write(json.dumps(response) + "\n")
+                    writer.flush()
+
+        thread = threading.Thread(target=serve, daemon=True)
+        thread.start()
+        repo = GraphRpcRepo(
+            f"127.0.0.1:{port}",
+            "token",
+            min_capabilities={"methods": ["teleport"]},
+        )
+        try:
+            with pytest.raises(GraphRpcIncompatible) as excinfo:
+                repo.stats()
+            assert excinfo.value.missing == {"missing_methods": ["teleport"]}
+            assert repo.stats() == {"nodes": 1}
+        finally:
+            repo.close()
+            server.close()
+        thread.join(timeout=5)
+
+        assert requests[0]["min_capabilities"] == {"methods": ["teleport"]}
+        assert "min_capabilities" not in requests[1]
@@ -1740,6 +1740,12 @@ impl App {
             AppEvent::FileSearchResult { query, matches } => {
                 self.chat_widget.apply_file_search_result(query, matches);
             }
+            AppEvent::RecentFileMentioned(path) => {
+                self.chat_widget.note_file_mention(&path);
+            }
+            AppEvent::ToggleRecentFilePin(path) => {
+                self.chat_widget.toggle_recent_file_pin(&path);
+            }
             AppEvent::RateLimitSnapshotFetched(snapshot) => {
                 self.chat_widget.on_rate_limit_snapshot(Some(snapshot));
             }
@@ -2472,6 +2478,15 @@ impl App {
             AppEvent::OpenPermissionsPopup => {
                 self.chat_widget.open_permissions_popup();
             }
+            AppEvent::RememberApproval { rule, scope } => {
+                self.chat_widget.remember_approval(rule, scope);
+            }
+            AppEvent::OpenRememberedApprovals => {
+                self.chat_widget.open_remembered_approvals_popup();
+            }
+            AppEvent::RevokeApproval(rule) => {
+                self.chat_widget.revoke_approval(&rule);
+            }
             AppEvent::OpenReviewBranchPicker(cwd) => {
                 self.chat_widget.show_review_branch_picker(&cwd).await;
             }
@@ -18,6 +18,8 @@ use codex_protocol::ThreadId;
 use codex_protocol::openai_models::ModelPreset;
 use codex_utils_approval_presets::ApprovalPreset;
 
+use crate::approval_rules::ApprovalRule;
+use crate::approval_rules::RuleScope;
 use crate::bottom_pane::ApprovalRequest;
 use crate::bottom_pane::StatusLineItem;
 use crate::history_cell::HistoryCell;
@@ -88,6 +90,12 @@ pub(crate) enum AppEvent {
         matches: Vec<FileMatch>,
     },
 
+    /// A file was picked from the `@` popup; path relative to the cwd.
+    RecentFileMentioned(PathBuf),
+
+    /// Pin or unpin a file in the `@` popup's recent list.
+    ToggleRecentFilePin(PathBuf),
+
     /// Result of refreshing rate limits
     RateLimitSnapshotFetched(RateLimitSnapshot),
 
@@ -305,6 +313,18 @@ pub(crate) enum AppEvent {
     /// Re-open the permissions presets popup.
     OpenPermissionsPopup,
 
+    /// Remember an approval rule chosen in an approval prompt.
+    RememberApproval {
+        rule: ApprovalRule,
+        scope: RuleScope,
+    },
+
+    /// Open the list of remembered approvals.
+    OpenRememberedApprovals,
+
+    /// Forget a remembered approval rule.
+    RevokeApproval(ApprovalRule),
+
     /// Open the branch picker option from the review popup.
     OpenReviewBranchPicker(PathBuf),
 
@@ -0,0 +1,775 @@
+//! Remembered approvals: standing answers given from an approval prompt,
+//! consulted before the next prompt is shown.
+//!
+//! Command rules match whole words, so `cargo test` covers
+//! `cargo test -p core` but not `cargo testify`. A `bash -lc` script is
+//! covered only when it splits into plain commands that each match a rule.
+//! Write rules match files under a directory. Commands that look dangerous
+//! are never remembered and never approved by a rule, and neither are
+//! programs that run arbitrary code (shells, interpreters, `sudo`).
+
+use std::io;
+use std::path::Component;
+use std::path::Path;
+use std::path::PathBuf;
+
+use codex_core::bash::extract_bash_command;
+use codex_core::bash::parse_shell_lc_plain_commands;
+use codex_core::is_dangerous_command::command_might_be_dangerous;
+use codex_core::protocol::FileChange;
+use happy_core::permissions::RepoPermissions;
+use happy_core::permissions::permissions_path;
+
+/// Words in a proposed command prefix: the program and its subcommand, e.g.
+/// `cargo test`. A bare program such as `rm` would cover too much, so
+/// commands without a subcommand get no offer.
+const PREFIX_WORDS: usize = 2;
+
+/// Programs that run whatever code or command they are handed; a rule for
+/// any of them would approve everything.
+const CODE_RUNNERS: &[&str] = &[
+    "bash",
+    "sh",
+    "zsh",
+    "fish",
+    "dash",
+    "ksh",
+    "csh",
+    "tcsh",
+    "pwsh",
+    "powershell",
+    "python",
+    "node",
+    "deno",
+    "bun",
+    "ruby",
+    "perl",
+    "php",
+    "lua",
+    "osascript",
+    "env",
+    "sudo",
+    "doas",
+    "xargs",
+    "nohup",
+    "nice",
+    "timeout",
+    "time",
+    "exec",
+    "eval",
+    "command",
+    "watch",
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum ApprovalRule {
+    /// Commands whose words start with `prefix`.
+    Command { prefix: Vec<String> },
+    /// Writes to files under `dir`, relative to the session root; an empty
+    /// `dir` covers the whole root.
+    Write { dir: PathBuf },
+}
+
+/// How long a remembered rule lasts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum RuleScope {
+    /// Until the session ends.
+    Session,
+    /// Saved under `CODEX_HOME` for this repo and loaded by later sessions.
+    Repo,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct RememberedRule {
+    pub rule: ApprovalRule,
+    pub scope: RuleScope,
+}
+
+impl ApprovalRule {
+    /// The rule to offer when approving `command`: its program plus a
+    /// subcommand-like first argument. Scripts with more than one command,
+    /// commands without a subcommand, code runners and dangerous commands
+    /// get no offer.
+    pub(crate) fn for_command(command: &[String]) -> Option<Self> {
+        if never_remember(command) {
+            return None;
+        }
+        let [words] = plain_commands(command)?.try_into().ok()?;
+        let program = words.first()?;
+        let prefix: Vec<String> = std::iter::once(program)
+            .chain(words.iter().skip(1).take_while(|word| is_subcommand(word)))
+            .take(PREFIX_WORDS)
+            .cloned()
+            .collect();
+        let rule = Self::Command { prefix };
+        rule.is_rememberable().then_some(rule)
+    }
+
+    /// The rule to offer when approving `changes`: the deepest directory
+    /// holding every touched file, as long as it is inside `root`.
+    pub(crate) fn for_changes<'a>(
+        changes: impl IntoIterator<Item = (&'a PathBuf, &'a FileChange)>,
+        root: &Path,
+    ) -> Option<Self> {
+        let mut dir: Option<PathBuf> = None;
+        for path in changed_paths(changes) {
+            let parent = absolute(&path, root)?.parent()?.to_path_buf();
+            dir = Some(match dir {
+                Some(dir) => common_ancestor(&dir, &parent),
+                None => parent,
+            });
+        }
+        let dir = dir?.strip_prefix(root).ok()?.to_path_buf();
+        Some(Self::Write { dir })
+    }
+
+    /// Short description, e.g. `` `cargo test` commands ``.
+    pub(crate) fn describe(&self) -> String {
+        match self {
+            Self::Command { prefix } => {
+                let rendered = shlex::try_join(prefix.iter().map(String::as_str))
+                    .unwrap_or_else(|_| prefix.join(" "));
+                format!("`{rendered}` commands")
+            }
+            Self::Write { dir } if dir.as_os_str().is_empty() => "edits under `./`".to_string(),
+            Self::Write { dir } => format!("edits under `{}/`", dir.display()),
+        }
+    }
+
+    fn allows_words(&self, words: &[String]) -> bool {
+        matches!(self, Self::Command { prefix } if !prefix.is_empty() && words.starts_with(prefix))
+    }
+
+    /// `path` is relative to the session root.
+    fn allows_write(&self, path: &Path) -> bool {
+        matches!(self, Self::Write { dir } if path.starts_with(dir))
+    }
+
+    fn is_rememberable(&self) -> bool {
+        match self {
+            Self::Command { prefix } => {
+                prefix.len() >= PREFIX_WORDS
+                    && !runs_arbitrary_code(&prefix[0])
+                    && !never_remember(prefix)
+            }
+            Self::Write { dir } => dir
+                .components()
+                .all(|component| matches!(component, Component::Normal(_))),
+        }
+    }
+}
+
+/// The rules remembered for the session rooted at `root`.
+#[derive(Debug)]
+pub(crate) struct ApprovalRules {
+    root: PathBuf,
+    /// Where repo-scoped rules are saved, under `CODEX_HOME`.
+    saved_path: PathBuf,
+    rules: Vec<RememberedRule>,
+}
+
+impl ApprovalRules {
+    pub(crate) fn new(codex_home: &Path, root: &Path) -> Self {
+        Self {
+            root: root.to_path_buf(),
+            saved_path: permissions_path(codex_home, root),
+            rules: Vec::new(),
+        }
+    }
+
+    /// Start from the rules saved for `root` under `codex_home`, skipping
+    /// entries that could never have been remembered.
+    pub(crate) fn load(codex_home: &Path, root: &Path) -> io::Result<Self> {
+        let mut rules = Self::new(codex_home, root);
+        let saved = RepoPermissions::load(&rules.saved_path)?;
+        let commands = saved
+            .commands
+            .iter()
+            .filter_map(|command| shlex::split(command))
+            .map(|prefix| ApprovalRule::Command { prefix });
+        let writes = saved
+            .write_dirs
+            .into_iter()
+            .map(|dir| ApprovalRule::Write { dir });
+        for rule in commands.chain(writes) {
+            if rule.is_rememberable() && !rules.rules.iter().any(|r| r.rule == rule) {
+                rules.rules.push(RememberedRule {
+                    rule,
+                    scope: RuleScope::Repo,
+                });
+            }
+        }
+        Ok(rules)
+    }
+
+    pub(crate) fn rules(&self) -> &[RememberedRule] {
+        &self.rules
+    }
+
+    /// Whether a rule approves running `command` without asking.
+    pub(crate) fn allows_command(&self, command: &[String]) -> bool {
+        if never_remember(command) {
+            return false;
+        }
+        let Some(commands) = plain_commands(command) else {
+            return false;
+        };
+        !commands.is_empty()
+            && commands.iter().all(|words| {
+                self.rules
+                    .iter()
+                    .any(|remembered| remembered.rule.allows_words(words))
+            })
+    }
+
+    /// Whether rules approve every write in `changes` without asking.
+    pub(crate) fn allows_changes<'a>(
+        &self,
+        changes: impl IntoIterator<Item = (&'a PathBuf, &'a FileChange)>,
+    ) -> bool {
+        let paths = changed_paths(changes);
+        !paths.is_empty()
+            && paths.iter().all(|path| {
+                absolute(path, &self.root)
+                    .and_then(|path| path.strip_prefix(&self.root).ok().map(Path::to_path_buf))
+                    .is_some_and(|path| {
+                        self.rules
+                            .iter()
+                            .any(|remembered| remembered.rule.allows_write(&path))
+                    })
+            })
+    }
+
+    /// Remember `rule`; a repo-scoped rule is also saved to disk. Rules that
+    /// may never be remembered are ignored.
+    pub(crate) fn remember(&mut self, rule: ApprovalRule, scope: RuleScope) -> io::Result<()> {
+        if !rule.is_rememberable() {
+            return Ok(());
+        }
+        match self.rules.iter_mut().find(|r| r.rule == rule) {
+            Some(existing) if existing.scope == RuleScope::Repo || scope == RuleScope::Session => {
+                return Ok(());
+            }
+            Some(existing) => existing.scope = scope,
+            None => self.rules.push(RememberedRule { rule, scope }),
+        }
+        if scope == RuleScope::Repo {
+            self.save()?;
+        }
+        Ok(())
+    }
+
+    /// Forget `rule`, removing it from the saved rules if it was saved.
+    pub(crate) fn revoke(&mut self, rule: &ApprovalRule) -> io::Result<()> {
+        let Some(index) = self.rules.iter().position(|r| &r.rule == rule) else {
+            return Ok(());
+        };
+        if self.rules.remove(index).scope == RuleScope::Repo {
+            self.save()?;
+        }
+        Ok(())
+    }
+
+    fn save(&self) -> io::Result<()> {
+        let mut saved = RepoPermissions::default();
+        for remembered in self.rules.iter().filter(|r| r.scope == RuleScope::Repo) {
+            match &remembered.rule {
+                ApprovalRule::Command { prefix } => saved.commands.push(
+                    shlex::try_join(prefix.iter().map(String::as_str))
+                        .unwrap_or_else(|_| prefix.join(" ")),
+                ),
+                ApprovalRule::Write { dir } => saved.write_dirs.push(dir.clone()),
+            }
+        }
+        saved.save(&self.saved_path)
+    }
+}
+
+/// Commands no rule may cover, whatever the user approved before.
+fn never_remember(command: &[String]) -> bool {
+    command_might_be_dangerous(command)
+        || plain_commands(command)
+            .is_some_and(|commands| commands.iter().any(|words| is_destructive(words)))
+}
+
+/// Whether `words` deletes or discards work, or runs inline code. Flags are
+/// parsed, so `rm -fr` and `rm -r -f` count like `rm -rf`.
+fn is_destructive(words: &[String]) -> bool {
+    let Some((program, args)) = words.split_first() else {
+        return false;
+    };
+    let flags = Flags::parse(args);
+    match program_name(program) {
+        "rm" => flags.has_any(&['r', 'R', 'f'], &["--recursive", "--force"]),
+        "git" => is_destructive_git(args),
+        "find" => args.iter().any(|arg| arg == "-delete" || arg == "-exec"),
+        name if CODE_RUNNERS.contains(&name) => {
+            flags.has_any(&['c', 'e'], &["--command", "--eval"])
+        }
+        _ => false,
+    }
+}
+
+/// `git reset --hard`, `git clean -f`, `git push --force`, `git branch -D`
+/// and `git checkout --force`, after any global options.
+fn is_destructive_git(args: &[String]) -> bool {
+    let mut rest = args.iter().map(String::as_str);
+    let subcommand = loop {
+        match rest.next() {
+            Some("-C" | "-c" | "--git-dir" | "--work-tree" | "--namespace") => {
+                rest.next();
+            }
+            Some(arg) if arg.starts_with('-') => {}
+            Some(subcommand) => break subcommand,
+            None => return false,
+        }
+    };
+    let args: Vec<String> = rest.map(str::to_string).collect();
+    let flags = Flags::parse(&args);
+    match subcommand {
+        "reset" => flags.has_any(&[], &["--hard"]),
+        "clean" => flags.has_any(&['f'], &["--force"]),
+        "push" => {
+            flags.has_any(
+                &['f'],
+                &["--force", "--force-with-lease", "--mirror", "--delete"],
+            ) || args.iter().any(|arg| arg.starts_with('+'))
+        }
+        "branch" => flags.has_any(&['D'], &[]),
+        "checkout" => flags.has_any(&['f'], &["--force"]),
+        _ => false,
+    }
+}
+
+/// The options among a command's arguments, up to `--`. Short options may
+/// be bundled (`-fr`) and come after operands (`rm build -r`).
+#[derive(Default)]
+struct Flags<'a> {
+    short: Vec<char>,
+    long: Vec<&'a str>,
+}
+
+impl<'a> Flags<'a> {
+    fn parse(args: &'a [String]) -> Self {
+        let mut flags = Self::default();
+        for arg in args.iter().take_while(|arg| *arg != "--") {
+            if arg.starts_with("--") {
+                flags.long.push(arg.split('=').next().unwrap_or(arg));
+            } else if let Some(short) = arg.strip_prefix('-') {
+                flags.short.extend(short.chars());
+            }
+        }
+        flags
+    }
+
+    /// `long` options are given with their dashes, e.g. `--force`.
+    fn has_any(&self, short: &[char], long: &[&str]) -> bool {
+        self.short.iter().any(|c| short.contains(c))
+            || self.long.iter().any(|flag| long.contains(flag))
+    }
+}
+
+/// Whether `program` runs arbitrary code, e.g. `bash`, `/usr/bin/python3`.
+fn runs_arbitrary_code(program: &str) -> bool {
+    CODE_RUNNERS.contains(&program_name(program))
+}
+
+/// `program` without its directory or a version suffix: `/usr/bin/python3.12`
+/// gives `python`.
+fn program_name(program: &str) -> &str {
+    let name = program.rsplit('/').next().unwrap_or(program);
+    let trimmed = name.trim_end_matches(|c: char| c.is_ascii_digit() || c == '.');
+    if trimmed.is_empty() { name } else { trimmed }
+}
+
+/// The plain commands `command` runs: itself, or the commands of a
+/// `bash -lc` script made only of words and safe operators.
+fn plain_commands(command: &[String]) -> Option<Vec<Vec<String>>> {
+    if extract_bash_command(command).is_some() {
+        parse_shell_lc_plain_commands(command)
+    } else {
+        Some(vec![command.to_vec()])
+    }
+}
+
+fn is_subcommand(word: &str) -> bool {
+    !word.starts_with('-')
+        && word
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | ':'))
+}
+
+/// Every path a patch writes, including move destinations.
+fn changed_paths<'a>(
+    changes: impl IntoIterator<Item = (&'a PathBuf, &'a FileChange)>,
+) -> Vec<PathBuf> {
+    let mut paths = Vec::new();
+    for (path, change) in changes {
+        paths.push(path.clone());
+        if let FileChange::Update {
+            move_path: Some(dest),
+            ..
+        } = change
+        {
+            paths.push(dest.clone());
+        }
+    }
+    paths
+}
+
+/// `path` made absolute against `root`, or `None` if it climbs with `..`,
+/// which could step outside any directory rule.
+fn absolute(path: &Path, root: &Path) -> Option<PathBuf> {
+    let joined = root.join(path);
+    let mut normalized = PathBuf::new();
+    for component in joined.components() {
+        match component {
+            Component::ParentDir => return None,
+            Component::CurDir => {}
+            other => normalized.push(other),
+        }
+    }
+    Some(normalized)
+}
+
+fn common_ancestor(a: &Path, b: &Path) -> PathBuf {
+    a.components()
+        .zip(b.components())
+        .take_while(|(x, y)| x == y)
+        .map(|(x, _)| x)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use std::collections::HashMap;
+
+    fn words(items: &[&str]) -> Vec<String> {
+        items.iter().map(ToString::to_string).collect()
+    }
+
+    fn bash(script: &str) -> Vec<String> {
+        words(&["bash", "-lc", script])
+    }
+
+    fn add(paths: &[&str]) -> HashMap<PathBuf, FileChange> {
+        paths
+            .iter()
+            .map(|path| {
+                (
+                    PathBuf::from(path),
+                    FileChange::Add {
+                        content: String::new(),
+                    },
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn command_rules_match_whole_word_prefixes() {
+        let root = PathBuf::from("/repo");
+        let rule = ApprovalRule::for_command(&bash("cargo test -p core")).unwrap();
+        assert_eq!(
+            rule,
+            ApprovalRule::Command {
+                prefix: words(&["cargo", "test"])
+            }
+        );
+        assert_eq!(rule.describe(), "`cargo test` commands");
+
+        let mut rules = ApprovalRules::new(Path::new("/codex-home"), &root);
+        rules.remember(rule, RuleScope::Session).unwrap();
+        assert!(rules.allows_command(&words(&["cargo", "test"])));
+        assert!(rules.allows_command(&bash("cargo test --all && cargo test -p tui")));
+        assert!(!rules.allows_command(&words(&["cargo", "testify"])));
+        assert!(!rules.allows_command(&words(&["cargo", "build"])));
+        assert!(!rules.allows_command(&bash("cargo test && cargo publish")));
+        assert!(!rules.allows_command(&bash("cargo test > out.txt")));
+        assert!(!rules.allows_command(&bash("cargo test $(whoami)")));
+    }
+
+    #[test]
+    fn proposed_prefixes_stop_at_flags_and_paths() {
+        let prefix = |command: &[&str]| match ApprovalRule::for_command(&words(command)) {
+            Some(ApprovalRule::Command { prefix }) => Some(prefix),
+            _ => None,
+        };
+        assert_eq!(
+            prefix(&["npm", "run", "lint", "--fix"]),
+            Some(words(&["npm", "run"]))
+        );
+        assert_eq!(
+            prefix(&["git", "status", "--short"]),
+            Some(words(&["git", "status"]))
+        );
+        // A bare program would cover every use of it.
+        assert_eq!(prefix(&["ls"]), None);
+        assert_eq!(prefix(&["ls", "-la", "src"]), None);
+        assert_eq!(prefix(&["pytest", "tests/a.py"]), None);
+        assert_eq!(ApprovalRule::for_command(&bash("ls && pwd")), None);
+        assert_eq!(ApprovalRule::for_command(&bash("cat <<EOF\nx\nEOF")), None);
+    }
+
+    #[test]
+    fn code_runners_are_never_offered() {
+        for command in [
+            words(&["python", "-m", "pytest"]),
+            words(&["/usr/bin/python3.12", "manage", "migrate"]),
+            words(&["node", "scripts"]),
+            words(&["sudo", "apt"]),
+            words(&["env", "cargo", "test"]),
+            words(&["xargs", "cargo"]),
+            bash("bash -c 'cargo test'"),
+        ] {
+            assert_eq!(ApprovalRule::for_command(&command), None, "{command:?}");
+        }
+
+        let mut rules = ApprovalRules::new(Path::new("/codex-home"), Path::new("/repo"));
+        rules
+            .remember(
+                ApprovalRule::Command {
+                    prefix: words(&["python", "-c"]),
+                },
+                RuleScope::Session,
+            )
+            .unwrap();
+        rules
+            .remember(
+                ApprovalRule::Command {
+                    prefix: words(&["bash"]),
+                },
+                RuleScope::Session,
+            )
+            .unwrap();
+        assert!(rules.rules().is_empty());
+    }
+
+    #[test]
+    fn write_rules_cover_directories_not_siblings_or_parents() {
+        let root = PathBuf::from("/repo");
+        let changes = add(&["src/app/main.rs", "src/app/ui/view.rs"]);
+        let rule = ApprovalRule::for_changes(&changes, &root).unwrap();
+        assert_eq!(
+            rule,
+            ApprovalRule::Write {
+                dir: PathBuf::from("src/app")
+            }
+        );
+        assert_eq!(rule.describe(), "edits under `src/app/`");
+
+        let mut rules = ApprovalRules::new(Path::new("/codex-home"), &root);
+        rules.remember(rule, RuleScope::Session).unwrap();
+        assert!(rules.allows_changes(&add(&["src/app/lib.rs", "/repo/src/app/x/y.rs"])));
+        assert!(!rules.allows_changes(&add(&["src/app/lib.rs", "src/main.rs"])));
+        assert!(!rules.allows_changes(&add(&["src/application/lib.rs"])));
+        assert!(!rules.allows_changes(&add(&["src/app/../../secrets.txt"])));
+        let moved: HashMap<PathBuf, FileChange> = [(
+            PathBuf::from("src/app/a.rs"),
+            FileChange::Update {
+                unified_diff: String::new(),
+                move_path: Some(PathBuf::from("/repo/b.rs")),
+            },
+        )]
+        .into_iter()
+        .collect();
+        assert!(!rules.allows_changes(&moved));
+        assert!(!rules.allows_changes(&HashMap::<PathBuf, FileChange>::new()));
+
+        assert_eq!(
+            ApprovalRule::for_changes(&add(&["README.md"]), &root)
+                .unwrap()
+                .describe(),
+            "edits under `./`"
+        );
+        assert_eq!(
+            ApprovalRule::for_changes(&add(&["/etc/hosts"]), &root),
+            None
+        );
+        assert_eq!(
+            ApprovalRule::for_changes(&add(&["../other/x.rs"]), &root),
+            None
+        );
+    }
+
+    #[test]
+    fn dangerous_commands_are_never_remembered() {
+        let root = PathBuf::from("/repo");
+        assert_eq!(
+            ApprovalRule::for_command(&words(&["rm", "-rf", "build"])),
+            None
+        );
+        assert_eq!(ApprovalRule::for_command(&bash("rm -f out.log")), None);
+
+        let mut rules = ApprovalRules::new(Path::new("/codex-home"), &root);
+        rules
+            .remember(
+                ApprovalRule::Command {
+                    prefix: words(&["rm", "-rf"]),
+                },
+                RuleScope::Session,
+            )
+            .unwrap();
+        assert!(rules.rules().is_empty());
+
+        // A broader rule still never approves a dangerous command, however
+        // its flags are spelled.
+        let rule = ApprovalRule::for_command(&words(&["rm", "build"])).unwrap();
+        rules.remember(rule, RuleScope::Session).unwrap();
+        assert!(rules.allows_command(&words(&["rm", "build"])));
+        assert!(!rules.allows_command(&words(&["rm", "build", "-rf"])));
+        assert!(!rules.allows_command(&words(&["rm", "build", "-r", "-f"])));
+        assert!(!rules.allows_command(&words(&["rm", "build", "--recursive"])));
+        assert!(!rules.allows_command(&bash("rm build -fr")));
+        assert!(!rules.allows_command(&words(&["sudo", "rm", "-rf", "/"])));
+
+        let git_reset = ApprovalRule::for_command(&words(&["git", "reset", "HEAD~1"])).unwrap();
+        rules.remember(git_reset, RuleScope::Session).unwrap();
+        assert!(rules.allows_command(&words(&["git", "reset", "HEAD~1"])));
+        assert!(!rules.allows_command(&words(&["git", "reset", "--hard", "HEAD~1"])));
+        assert!(!rules.allows_command(&words(&["git", "-C", "sub", "reset", "--hard"])));
+    }
+
+    #[test]
+    fn destructive_commands_are_recognized_by_their_flags() {
+        let destructive: &[&[&str]] = &[
+            &["rm", "-fr", "build"],
+            &["rm", "-r", "-f", "build"],
+            &["rm", "--force", "out.log"],
+            &["/bin/rm", "-R", "build"],
+            &["python", "-c", "print(1)"],
+            &["python3", "-Wignore", "-c", "print(1)"],
+            &["node", "--eval", "1"],
+            &["sh", "-ec", "true"],
+            &["git", "reset", "--hard"],
+            &["git", "clean", "-fdx"],
+            &["git", "push", "--force-with-lease"],
+            &["git", "push", "origin", "+main"],
+            &["git", "branch", "-D", "topic"],
+            &["find", ".", "-name", "*.o", "-delete"],
+        ];
+        for command in destructive {
+            assert!(never_remember(&words(command)), "{command:?}");
+            assert_eq!(ApprovalRule::for_command(&words(command)), None);
+        }
+        let harmless: &[&[&str]] = &[
+            &["rm", "build"],
+            &["git", "reset", "HEAD~1"],
+            &["git", "push", "origin", "main"],
+            &["git", "branch", "-d", "topic"],
+            &["cargo", "test", "--", "-f"],
+        ];
+        for command in harmless {
+            assert!(!never_remember(&words(command)), "{command:?}");
+        }
+    }
+
+    #[test]
+    fn repo_rules_persist_and_revoke() {
+        let home = tempfile::tempdir().unwrap();
+        let home = home.path();
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let saved_path = permissions_path(home, root);
+        let cargo_test = ApprovalRule::Command {
+            prefix: words(&["cargo", "test"]),
+        };
+        let src = ApprovalRule::Write {
+            dir: PathBuf::from("src"),
+        };
+        let git_status = ApprovalRule::Command {
+            prefix: words(&["git", "status"]),
+        };
+
+        let mut rules = ApprovalRules::new(home, root);
+        rules.remember(cargo_test.clone(), RuleScope::Repo).unwrap();
+        rules.remember(src.clone(), RuleScope::Repo).unwrap();
+        rules
+            .remember(git_status.clone(), RuleScope::Session)
+            .unwrap();
+        // Remembering for the session again does not downgrade a repo rule.
+        rules
+            .remember(cargo_test.clone(), RuleScope::Session)
+            .unwrap();
+        // Saved under the codex home, never inside the repo itself.
+        assert!(!root.join(".happy").exists());
+        assert_eq!(
+            RepoPermissions::load(&saved_path).unwrap(),
+            RepoPermissions {
+                commands: vec!["cargo test".to_string()],
+                write_dirs: vec![PathBuf::from("src")],
+            }
+        );
+
+        let loaded = ApprovalRules::load(home, root).unwrap();
+        assert_eq!(
+            loaded.rules(),
+            &[
+                RememberedRule {
+                    rule: cargo_test.clone(),
+                    scope: RuleScope::Repo,
+                },
+                RememberedRule {
+                    rule: src.clone(),
+                    scope: RuleScope::Repo,
+                },
+            ]
+        );
+
+        rules.revoke(&cargo_test).unwrap();
+        rules.revoke(&git_status).unwrap();
+        assert!(!rules.allows_command(&words(&["cargo", "test"])));
+        assert!(!rules.allows_command(&words(&["git", "status"])));
+        assert_eq!(ApprovalRules::load(home, root).unwrap().rules().len(), 1);
+
+        rules.revoke(&src).unwrap();
+        assert!(!saved_path.exists());
+    }
+
+    #[test]
+    fn rules_shipped_inside_the_repo_are_ignored() {
+        let home = tempfile::tempdir().unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join(".happy")).unwrap();
+        std::fs::write(
+            dir.path().join(".happy/permissions.toml"),
+            "commands = [\"cargo test\"]\nwrite_dirs = [\"\"]\n",
+        )
+        .unwrap();
+
+        let rules = ApprovalRules::load(home.path(), dir.path()).unwrap();
+        assert!(rules.rules().is_empty());
+    }
+
+    #[test]
+    fn load_skips_rules_that_could_not_have_been_remembered() {
+        let home = tempfile::tempdir().unwrap();
+        let root = Path::new("/work/repo");
+        RepoPermissions {
+            commands: vec![
+                "rm -rf".to_string(),
+                "rm -r -f".to_string(),
+                "python".to_string(),
+                "bash -lc".to_string(),
+                "make".to_string(),
+                "cargo test".to_string(),
+            ],
+            write_dirs: vec![PathBuf::from("/etc"), PathBuf::from("../up")],
+        }
+        .save(&permissions_path(home.path(), root))
+        .unwrap();
+
+        let rules = ApprovalRules::load(home.path(), root).unwrap();
+        assert_eq!(
+            rules.rules(),
+            &[RememberedRule {
+                rule: ApprovalRule::Command {
+                    prefix: words(&["cargo", "test"])
+                },
+                scope: RuleScope::Repo,
+            }]
+        );
+    }
+}
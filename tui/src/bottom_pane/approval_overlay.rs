@@ -3,6 +3,8 @@ use std::path::PathBuf;
 
 use crate::app_event::AppEvent;
 use crate::app_event_sender::AppEventSender;
+use crate::approval_rules::ApprovalRule;
+use crate::approval_rules::RuleScope;
 use crate::bottom_pane::BottomPaneView;
 use crate::bottom_pane::CancellationEvent;
 use crate::bottom_pane::list_selection_view::ListSelectionView;
@@ -112,11 +114,13 @@ impl ApprovalOverlay {
             ApprovalVariant::Exec {
                 network_approval_context,
                 proposed_execpolicy_amendment,
+                remember,
                 ..
             } => (
                 exec_options(
                     proposed_execpolicy_amendment.clone(),
                     network_approval_context.as_ref(),
+                    remember.clone(),
                 ),
                 network_approval_context.as_ref().map_or_else(
                     || "Would you like to run the following command?".to_string(),
@@ -128,8 +132,8 @@ impl ApprovalOverlay {
                     },
                 ),
             ),
-            ApprovalVariant::ApplyPatch { .. } => (
-                patch_options(),
+            ApprovalVariant::ApplyPatch { remember, .. } => (
+                patch_options(remember.clone()),
                 "Would you like to make the following edits?".to_string(),
             ),
             ApprovalVariant::McpElicitation { server_name, .. } => (
@@ -187,6 +191,21 @@ impl ApprovalOverlay {
                 (ApprovalVariant::ApplyPatch { id, .. }, ApprovalDecision::Review(decision)) => {
                     self.handle_patch_decision(id, decision.clone());
                 }
+                (ApprovalVariant::Exec { id, .. }, ApprovalDecision::Remember { rule, scope }) => {
+                    self.remember(rule, *scope);
+                    self.app_event_tx.send(AppEvent::CodexOp(Op::ExecApproval {
+                        id: id.to_string(),
+                        turn_id: None,
+                        decision: ReviewDecision::Approved,
+                    }));
+                }
+                (
+                    ApprovalVariant::ApplyPatch { id, .. },
+                    ApprovalDecision::Remember { rule, scope },
+                ) => {
+                    self.remember(rule, *scope);
+                    self.handle_patch_decision(id, ReviewDecision::Approved);
+                }
                 (
                     ApprovalVariant::McpElicitation {
                         server_name,
@@ -214,6 +233,13 @@ impl ApprovalOverlay {
         }));
     }
 
+    fn remember(&self, rule: &ApprovalRule, scope: RuleScope) {
+        self.app_event_tx.send(AppEvent::RememberApproval {
+            rule: rule.clone(),
+            scope,
+        });
+    }
+
     fn handle_patch_decision(&self, id: &str, decision: ReviewDecision) {
         self.app_event_tx.send(AppEvent::CodexOp(Op::PatchApproval {
             id: id.to_string(),
@@ -370,12 +396,18 @@ impl From<ApprovalRequest> for ApprovalRequestState {
                     first.spans.insert(0, Span::from("$ "));
                 }
                 header.extend(full_cmd_lines);
+                let remember = if network_approval_context.is_none() {
+                    ApprovalRule::for_command(&command)
+                } else {
+                    None
+                };
                 Self {
                     variant: ApprovalVariant::Exec {
                         id,
                         command,
                         network_approval_context,
                         proposed_execpolicy_amendment,
+                        remember,
                     },
                     header: Box::new(Paragraph::new(header).wrap(Wrap { trim: false })),
                 }
@@ -396,9 +428,10 @@ impl From<ApprovalRequest> for ApprovalRequestState {
                     ));
                     header.push(Box::new(Line::from("")));
                 }
+                let remember = ApprovalRule::for_changes(&changes, &cwd);
                 header.push(DiffSummary::new(changes, cwd).into());
                 Self {
-                    variant: ApprovalVariant::ApplyPatch { id },
+                    variant: ApprovalVariant::ApplyPatch { id, remember },
                     header: Box::new(ColumnRenderable::with(header)),
                 }
             }
@@ -432,9 +465,11 @@ enum ApprovalVariant {
         command: Vec<String>,
         network_approval_context: Option<NetworkApprovalContext>,
         proposed_execpolicy_amendment: Option<ExecPolicyAmendment>,
+        remember: Option<ApprovalRule>,
     },
     ApplyPatch {
         id: String,
+        remember: Option<ApprovalRule>,
     },
     McpElicitation {
         server_name: String,
@@ -445,6 +480,11 @@ enum ApprovalVariant {
 #[derive(Clone)]
 enum ApprovalDecision {
     Review(ReviewDecision),
+    /// Approve and remember `rule` so matching requests skip the prompt.
+    Remember {
+        rule: ApprovalRule,
+        scope: RuleScope,
+    },
     McpElicitation(ElicitationAction),
 }
 
@@ -467,6 +507,7 @@ impl ApprovalOption {
 fn exec_options(
     proposed_execpolicy_amendment: Option<ExecPolicyAmendment>,
     network_approval_context: Option<&NetworkApprovalContext>,
+    remember: Option<ApprovalRule>,
 ) -> Vec<ApprovalOption> {
     if network_approval_context.is_some() {
         return vec![
@@ -515,6 +556,7 @@ fn exec_options(
             additional_shortcuts: vec![key_hint::plain(KeyCode::Char('p'))],
         })
     }))
+    .chain(remember_options(remember))
     .chain([ApprovalOption {
         label: "No, and tell Codex what to do differently".to_string(),
         decision: ApprovalDecision::Review(ReviewDecision::Abort),
@@ -524,7 +566,7 @@ fn exec_options(
     .collect()
 }
 
-fn patch_options() -> Vec<ApprovalOption> {
+fn patch_options(remember: Option<ApprovalRule>) -> Vec<ApprovalOption> {
     vec![
         ApprovalOption {
             label: "Yes, proceed".to_string(),
@@ -538,11 +580,43 @@ fn patch_options() -> Vec<ApprovalOption> {
             display_shortcut: None,
             additional_shortcuts: vec![key_hint::plain(KeyCode::Char('a'))],
         },
+    ]
+    .into_iter()
+    .chain(remember_options(remember))
+    .chain([ApprovalOption {
+        label: "No, and tell Codex what to do differently".to_string(),
+        decision: ApprovalDecision::Review(ReviewDecision::Abort),
+        display_shortcut: Some(key_hint::plain(KeyCode::Esc)),
+        additional_shortcuts: vec![key_hint::plain(KeyCode::Char('n'))],
+    }])
+    .collect()
+}
+
+/// "Approve and remember" options for `rule`, for this session (`s`) or
+/// saved for the repo (`r`).
+fn remember_options(rule: Option<ApprovalRule>) -> Vec<ApprovalOption> {
+    let Some(rule) = rule else {
+        return Vec::new();
+    };
+    let description = rule.describe();
+    vec![
+        ApprovalOption {
+            label: format!("Yes, and allow {description} this session"),
+            decision: ApprovalDecision::Remember {
+                rule: rule.clone(),
+                scope: RuleScope::Session,
+            },
+            display_shortcut: None,
+            additional_shortcuts: vec![key_hint::plain(KeyCode::Char('s'))],
+        },
         ApprovalOption {
-            label: "No, and tell Codex what to do differently".to_string(),
-            decision: ApprovalDecision::Review(ReviewDecision::Abort),
-            display_shortcut: Some(key_hint::plain(KeyCode::Esc)),
-            additional_shortcuts: vec![key_hint::plain(KeyCode::Char('n'))],
+            label: format!("Yes, and always allow {description} in this repo"),
+            decision: ApprovalDecision::Remember {
+                rule,
+                scope: RuleScope::Repo,
+            },
+            display_shortcut: None,
+            additional_shortcuts: vec![key_hint::plain(KeyCode::Char('r'))],
         },
     ]
 }
@@ -656,6 +730,65 @@ mod tests {
         );
     }
 
+    #[test]
+    fn remember_option_records_rule_and_approves() {
+        let (tx, mut rx) = unbounded_channel::<AppEvent>();
+        let tx = AppEventSender::new(tx);
+        let mut view = ApprovalOverlay::new(
+            ApprovalRequest::Exec {
+                id: "test".to_string(),
+                command: vec!["cargo".into(), "test".into(), "-p".into(), "tui".into()],
+                reason: None,
+                network_approval_context: None,
+                proposed_execpolicy_amendment: None,
+            },
+            tx,
+            Features::with_defaults(),
+        );
+        view.handle_key_event(KeyEvent::new(KeyCode::Char('r'), KeyModifiers::NONE));
+
+        let events: Vec<AppEvent> = std::iter::from_fn(|| rx.try_recv().ok()).collect();
+        assert!(matches!(
+            events.as_slice(),
+            [
+                AppEvent::RememberApproval {
+                    rule: ApprovalRule::Command { prefix },
+                    scope: RuleScope::Repo,
+                },
+                AppEvent::CodexOp(Op::ExecApproval {
+                    decision: ReviewDecision::Approved,
+                    ..
+                }),
+            ] if prefix == &["cargo".to_string(), "test".to_string()]
+        ));
+    }
+
+    #[test]
+    fn dangerous_command_offers_no_remember_options() {
+        let labels = |command: Vec<String>| -> Vec<String> {
+            exec_options(None, None, ApprovalRule::for_command(&command))
+                .into_iter()
+                .map(|option| option.label)
+                .collect()
+        };
+        assert_eq!(
+            labels(vec!["cargo".into(), "test".into()]),
+            vec![
+                "Yes, proceed".to_string(),
+                "Yes, and allow `cargo test` commands this session".to_string(),
+                "Yes, and always allow `cargo test` commands in this repo".to_string(),
+                "No, and tell Codex what to do differently".to_string(),
+            ]
+        );
+        assert_eq!(
+            labels(vec!["rm".into(), "-rf".into(), "target".into()]),
+            vec![
+                "Yes, proceed".to_string(),
+                "No, and tell Codex what to do differently".to_string(),
+            ]
+        );
+    }
+
     #[test]
     fn header_includes_command_snippet() {
         let (tx, _rx) = unbounded_channel::<AppEvent>();
@@ -697,6 +830,7 @@ mod tests {
         let options = exec_options(
             Some(ExecPolicyAmendment::new(vec!["curl".to_string()])),
             Some(&network_context),
+            None,
         );
 
         let labels: Vec<String> = options.into_iter().map(|option| option.label).collect();
@@ -169,6 +169,7 @@ use crate::bottom_pane::prompt_args::parse_slash_name;
 use crate::bottom_pane::prompt_args::prompt_argument_names;
 use crate::bottom_pane::prompt_args::prompt_command_with_arg_placeholders;
 use crate::bottom_pane::prompt_args::prompt_has_numeric_placeholders;
+use crate::recent_files::RecentFile;
 use crate::render::Insets;
 use crate::render::RectExt;
 use crate::render::renderable::Renderable;
@@ -296,6 +297,8 @@ pub(crate) struct ChatComposer {
     use_shift_enter_hint: bool,
     dismissed_file_popup_token: Option<String>,
     current_file_query: Option<String>,
+    /// Listed by the file popup while nothing follows the `@`.
+    recent_files: Vec<RecentFile>,
     pending_pastes: Vec<(String, String)>,
     large_paste_counters: HashMap<usize, usize>,
     has_focus: bool,
@@ -404,6 +407,7 @@ impl ChatComposer {
             use_shift_enter_hint,
             dismissed_file_popup_token: None,
             current_file_query: None,
+            recent_files: Vec::new(),
             pending_pastes: Vec::new(),
             large_paste_counters: HashMap::new(),
             has_focus: has_input_focus,
@@ -1075,6 +1079,16 @@ impl ChatComposer {
         }
     }
 
+    /// Replace the recent files listed by a bare `@`.
+    pub(crate) fn set_recent_files(&mut self, recent_files: Vec<RecentFile>) {
+        self.recent_files = recent_files;
+        if self.current_file_query.is_none()
+            && let ActivePopup::File(popup) = &mut self.active_popup
+        {
+            popup.set_empty_prompt(&self.recent_files);
+        }
+    }
+
     /// Show the transient "press again to quit" hint for `key`.
     ///
     /// The owner (`BottomPane`/`ChatWidget`) is responsible for scheduling a
@@ -1476,6 +1490,17 @@ impl ChatComposer {
                 popup.move_down();
                 (InputResult::None, true)
             }
+            KeyEvent {
+                code: KeyCode::Char('o'),
+                modifiers: KeyModifiers::CONTROL,
+                ..
+            } => {
+                if let Some(sel) = popup.selected_match() {
+                    self.app_event_tx
+                        .send(AppEvent::ToggleRecentFilePin(sel.clone()));
+                }
+                (InputResult::None, true)
+            }
             KeyEvent {
                 code: KeyCode::Esc, ..
             } => {
@@ -1499,6 +1524,8 @@ impl ChatComposer {
                     return (InputResult::None, true);
                 };
 
+                self.app_event_tx
+                    .send(AppEvent::RecentFileMentioned(sel.clone()));
                 let sel_path = sel.to_string_lossy().to_string();
                 // If selected path looks like an image (png/jpeg), attach as image instead of inserting text.
                 let is_image = Self::is_image_path(&sel_path);
@@ -3185,7 +3212,7 @@ impl ChatComposer {
         match &mut self.active_popup {
             ActivePopup::File(popup) => {
                 if query.is_empty() {
-                    popup.set_empty_prompt();
+                    popup.set_empty_prompt(&self.recent_files);
                 } else {
                     popup.set_query(&query);
                 }
@@ -3193,7 +3220,7 @@ impl ChatComposer {
             _ => {
                 let mut popup = FileSearchPopup::new();
                 if query.is_empty() {
-                    popup.set_empty_prompt();
+                    popup.set_empty_prompt(&self.recent_files);
                 } else {
                     popup.set_query(&query);
                 }
@@ -5,6 +5,7 @@ use ratatui::buffer::Buffer;
 use ratatui::layout::Rect;
 use ratatui::widgets::WidgetRef;
 
+use crate::recent_files::RecentFile;
 use crate::render::Insets;
 use crate::render::RectExt;
 
@@ -24,6 +25,8 @@ pub(crate) struct FileSearchPopup {
     waiting: bool,
     /// Cached matches; paths relative to the search dir.
     matches: Vec<FileMatch>,
+    /// Pinned files, marked as such wherever they are listed.
+    pinned: Vec<PathBuf>,
     /// Shared selection/scroll state.
     state: ScrollState,
 }
@@ -35,6 +38,7 @@ impl FileSearchPopup {
             pending_query: String::new(),
             waiting: true,
             matches: Vec::new(),
+            pinned: Vec::new(),
             state: ScrollState::new(),
         }
     }
@@ -52,14 +56,30 @@ impl FileSearchPopup {
     }
 
     /// Put the popup into an "idle" state used for an empty query (just "@").
-    /// Shows a hint instead of matches until the user types more characters.
-    pub(crate) fn set_empty_prompt(&mut self) {
+    /// Lists the session's recent files until the user types more characters.
+    pub(crate) fn set_empty_prompt(&mut self, recent: &[RecentFile]) {
         self.display_query.clear();
         self.pending_query.clear();
         self.waiting = false;
-        self.matches.clear();
+        self.matches = recent
+            .iter()
+            .map(|file| FileMatch {
+                score: 0,
+                path: file.path.clone(),
+                root: PathBuf::new(),
+                indices: None,
+            })
+            .collect();
+        self.pinned = recent
+            .iter()
+            .filter(|file| file.pinned)
+            .map(|file| file.path.clone())
+            .collect();
         // Reset selection/scroll state when showing the empty prompt.
         self.state.reset();
+        let len = self.matches.len();
+        self.state.clamp_selection(len);
+        self.state.ensure_visible(len, len.min(MAX_POPUP_ROWS));
     }
 
     /// Replace matches when a `FileSearchResult` arrives.
@@ -124,7 +144,7 @@ impl WidgetRef for &FileSearchPopup {
                         .as_ref()
                         .map(|v| v.iter().map(|&i| i as usize).collect()),
                     display_shortcut: None,
-                    description: None,
+                    description: self.pinned.contains(&m.path).then(|| "pinned".to_string()),
                     category_tag: None,
                     wrap_indent: None,
                     is_disabled: false,
@@ -135,6 +155,8 @@ impl WidgetRef for &FileSearchPopup {
 
         let empty_message = if self.waiting {
             "loading..."
+        } else if self.display_query.is_empty() {
+            "no recent files"
         } else {
             "no matches"
         };
@@ -21,6 +21,7 @@ use crate::bottom_pane::queued_user_messages::QueuedUserMessages;
 use crate::bottom_pane::unified_exec_footer::UnifiedExecFooter;
 use crate::key_hint;
 use crate::key_hint::KeyBinding;
+use crate::recent_files::RecentFile;
 use crate::render::renderable::FlexRenderable;
 use crate::render::renderable::Renderable;
 use crate::render::renderable::RenderableItem;
@@ -887,6 +888,11 @@ impl BottomPane {
         self.request_redraw();
     }
 
+    pub(crate) fn set_recent_files(&mut self, recent_files: Vec<RecentFile>) {
+        self.composer.set_recent_files(recent_files);
+        self.request_redraw();
+    }
+
     pub(crate) fn attach_image(&mut self, path: PathBuf) {
         if self.view_stack.is_empty() {
             self.composer.attach_image(path);
@@ -37,6 +37,9 @@ use std::sync::atomic::Ordering;
 use std::time::Duration;
 use std::time::Instant;
 
+use crate::approval_rules::ApprovalRule;
+use crate::approval_rules::ApprovalRules;
+use crate::approval_rules::RuleScope;
 use crate::bottom_pane::StatusLineItem;
 use crate::bottom_pane::StatusLineSetupView;
 use crate::status::RateLimitWindowDisplay;
@@ -59,6 +62,8 @@ use codex_core::find_thread_name_by_id;
 use codex_core::git_info::current_branch_name;
 use codex_core::git_info::get_git_repo_root;
 use codex_core::git_info::local_git_branches;
+use codex_core::handoff::HANDOFF_EXTENSION;
+use codex_core::handoff::export_session;
 use codex_core::models_manager::manager::ModelsManager;
 use codex_core::project_doc::DEFAULT_PROJECT_DOC_FILENAME;
 use codex_core::protocol::AgentMessageDeltaEvent;
@@ -69,6 +74,7 @@ use codex_core::protocol::AgentReasoningRawContentDeltaEvent;
 use codex_core::protocol::AgentReasoningRawContentEvent;
 use codex_core::protocol::ApplyPatchApprovalRequestEvent;
 use codex_core::protocol::BackgroundEventEvent;
+use codex_core::protocol::CodeGraphUpdatedEvent;
 use codex_core::protocol::CodexErrorInfo;
 use codex_core::protocol::CreditsSnapshot;
 use codex_core::protocol::DeprecationNoticeEvent;
@@ -92,6 +98,7 @@ use codex_core::protocol::McpToolCallEndEvent;
 use codex_core::protocol::Op;
 use codex_core::protocol::PatchApplyBeginEvent;
 use codex_core::protocol::RateLimitSnapshot;
+use codex_core::protocol::ReviewDecision;
 use codex_core::protocol::ReviewRequest;
 use codex_core::protocol::ReviewTarget;
 use codex_core::protocol::SkillMetadata as ProtocolSkillMetadata;
@@ -99,6 +106,7 @@ use codex_core::protocol::StreamErrorEvent;
 use codex_core::protocol::TerminalInteractionEvent;
 use codex_core::protocol::TokenUsage;
 use codex_core::protocol::TokenUsageInfo;
+use codex_core::protocol::ToolCallResultEvent;
 use codex_core::protocol::TurnAbortReason;
 use codex_core::protocol::TurnCompleteEvent;
 use codex_core::protocol::TurnDiffEvent;
@@ -136,6 +144,10 @@ use crossterm::event::KeyCode;
 use crossterm::event::KeyEvent;
 use crossterm::event::KeyEventKind;
 use crossterm::event::KeyModifiers;
+use happy_core::bookmarks::Bookmarks;
+use happy_core::graph::RepositoryGraph;
+use happy_core::indexer::IndexOptions;
+use happy_core::store::cache::LoadOptions;
 use rand::Rng;
 use ratatui::buffer::Buffer;
 use ratatui::layout::Rect;
@@ -201,6 +213,9 @@ use crate::key_hint;
 use crate::key_hint::KeyBinding;
 use crate::markdown::append_markdown;
 use crate::multi_agents;
+use crate::recent_files::FileActivity;
+use crate::recent_files::RecentFiles;
+use crate::recent_files::UI_STATE_FILE;
 use crate::render::Insets;
 use crate::render::renderable::ColumnRenderable;
 use crate::render::renderable::FlexRenderable;
@@ -623,7 +638,13 @@ pub(crate) struct ChatWidget {
     status_line_branch_pending: bool,
     // True once we've attempted a branch lookup for the current CWD.
     status_line_branch_lookup_complete: bool,
+    // True from `/reindex` until the rebuilt code graph is reported.
+    code_graph_reindex_pending: bool,
     external_editor_state: ExternalEditorState,
+    // Approvals remembered from approval prompts, checked before showing one.
+    approval_rules: ApprovalRules,
+    // Files read, edited, or mentioned this session, listed by a bare `@`.
+    recent_files: RecentFiles,
 }
 
 /// Snapshot of active-cell state that affects transcript overlay rendering.
@@ -1930,6 +1951,10 @@ impl ChatWidget {
     }
 
     fn on_patch_apply_begin(&mut self, event: PatchApplyBeginEvent) {
+        for path in event.changes.keys() {
+            self.recent_files.touch(path, FileActivity::Edit);
+        }
+        self.sync_recent_files();
         self.add_to_history(history_cell::new_patch_event(
             event.changes,
             &self.config.cwd,
@@ -2133,6 +2158,32 @@ impl ChatWidget {
         self.request_redraw();
     }
 
+    /// Report a rebuild asked for with `/reindex`. The watcher's updates
+    /// after edits are not worth a history cell.
+    fn on_code_graph_updated(&mut self, ev: CodeGraphUpdatedEvent) {
+        if ev.full_rebuild && std::mem::take(&mut self.code_graph_reindex_pending) {
+            self.add_info_message(
+                format!(
+                    "Code graph re-indexed: {} files, {} elements.",
+                    ev.file_count, ev.element_count
+                ),
+                None,
+            );
+        }
+    }
+
+    /// Files read with `read_file` or returned by code graph tools count as
+    /// reads for the recent files.
+    fn on_tool_call_result(&mut self, ev: ToolCallResultEvent) {
+        let mut read_files = false;
+        for path in &ev.touched_files {
+            read_files |= self.recent_files.touch(path, FileActivity::Read);
+        }
+        if read_files {
+            self.sync_recent_files();
+        }
+    }
+
     fn on_background_event(&mut self, message: String) {
         debug!("BackgroundEvent: {message}");
         self.bottom_pane.ensure_status_indicator();
@@ -2388,6 +2439,18 @@ impl ChatWidget {
 
     pub(crate) fn handle_exec_approval_now(&mut self, ev: ExecApprovalRequestEvent) {
         self.flush_answer_stream_with_separator();
+        if ev.network_approval_context.is_none() && self.approval_rules.allows_command(&ev.command)
+        {
+            self.add_boxed_history(history_cell::new_remembered_approval_cell(Some(
+                &ev.command,
+            )));
+            self.submit_op(Op::ExecApproval {
+                id: ev.call_id,
+                turn_id: None,
+                decision: ReviewDecision::Approved,
+            });
+            return;
+        }
         let command = shlex::try_join(ev.command.iter().map(String::as_str))
             .unwrap_or_else(|_| ev.command.join(" "));
         self.notify(Notification::ExecApprovalRequested { command });
@@ -2406,6 +2469,14 @@ impl ChatWidget {
 
     pub(crate) fn handle_apply_patch_approval_now(&mut self, ev: ApplyPatchApprovalRequestEvent) {
         self.flush_answer_stream_with_separator();
+        if self.approval_rules.allows_changes(&ev.changes) {
+            self.add_boxed_history(history_cell::new_remembered_approval_cell(None));
+            self.submit_op(Op::PatchApproval {
+                id: ev.call_id,
+                decision: ReviewDecision::Approved,
+            });
+            return;
+        }
 
         let request = ApprovalRequest::ApplyPatch {
             id: ev.call_id,
@@ -2448,6 +2519,17 @@ impl ChatWidget {
     pub(crate) fn handle_exec_begin_now(&mut self, ev: ExecCommandBeginEvent) {
         // Ensure the status indicator is visible while the command runs.
         self.bottom_pane.ensure_status_indicator();
+        let mut read_files = false;
+        for parsed in &ev.parsed_cmd {
+            if let ParsedCommand::Read { path, .. } = parsed {
+                read_files |= self
+                    .recent_files
+                    .touch(&ev.cwd.join(path), FileActivity::Read);
+            }
+        }
+        if read_files {
+            self.sync_recent_files();
+        }
         self.running_commands.insert(
             ev.call_id.clone(),
             RunningCommand {
@@ -2605,6 +2687,8 @@ impl ChatWidget {
         let active_cell = Some(Self::placeholder_session_header_cell(&config));
 
         let current_cwd = Some(config.cwd.clone());
+        let approval_rules = load_approval_rules(&config.codex_home, &config.cwd);
+        let recent_files = load_recent_files(&config.cwd);
         let mut widget = Self {
             app_event_tx: app_event_tx.clone(),
             frame_requester: frame_requester.clone(),
@@ -2688,7 +2772,10 @@ impl ChatWidget {
             status_line_branch_cwd: None,
             status_line_branch_pending: false,
             status_line_branch_lookup_complete: false,
+            code_graph_reindex_pending: false,
             external_editor_state: ExternalEditorState::Closed,
+            approval_rules,
+            recent_files,
         };
 
         widget.prefetch_rate_limits();
@@ -2702,6 +2789,7 @@ impl ChatWidget {
             widget.config.features.enabled(Feature::CollaborationModes),
         );
         widget.sync_personality_command_enabled();
+        widget.sync_recent_files();
         #[cfg(target_os = "windows")]
         widget.bottom_pane.set_windows_degraded_sandbox_active(
             codex_core::windows_sandbox::ELEVATED_SANDBOX_NUX_ENABLED
@@ -2769,6 +2857,8 @@ impl ChatWidget {
         let active_cell = Some(Self::placeholder_session_header_cell(&config));
         let current_cwd = Some(config.cwd.clone());
 
+        let approval_rules = load_approval_rules(&config.codex_home, &config.cwd);
+        let recent_files = load_recent_files(&config.cwd);
         let mut widget = Self {
             app_event_tx: app_event_tx.clone(),
             frame_requester: frame_requester.clone(),
@@ -2852,7 +2942,10 @@ impl ChatWidget {
             status_line_branch_cwd: None,
             status_line_branch_pending: false,
             status_line_branch_lookup_complete: false,
+            code_graph_reindex_pending: false,
             external_editor_state: ExternalEditorState::Closed,
+            approval_rules,
+            recent_files,
         };
 
         widget.prefetch_rate_limits();
@@ -2866,6 +2959,7 @@ impl ChatWidget {
             widget.config.features.enabled(Feature::CollaborationModes),
         );
         widget.sync_personality_command_enabled();
+        widget.sync_recent_files();
 
         widget
     }
@@ -2922,6 +3016,8 @@ impl ChatWidget {
             settings: fallback_default,
         };
 
+        let approval_rules = load_approval_rules(&config.codex_home, &config.cwd);
+        let recent_files = load_recent_files(&config.cwd);
         let mut widget = Self {
             app_event_tx: app_event_tx.clone(),
             frame_requester: frame_requester.clone(),
@@ -3005,7 +3101,10 @@ impl ChatWidget {
             status_line_branch_cwd: None,
             status_line_branch_pending: false,
             status_line_branch_lookup_complete: false,
+            code_graph_reindex_pending: false,
             external_editor_state: ExternalEditorState::Closed,
+            approval_rules,
+            recent_files,
         };
 
         widget.prefetch_rate_limits();
@@ -3019,6 +3118,7 @@ impl ChatWidget {
             widget.config.features.enabled(Feature::CollaborationModes),
         );
         widget.sync_personality_command_enabled();
+        widget.sync_recent_files();
         #[cfg(target_os = "windows")]
         widget.bottom_pane.set_windows_degraded_sandbox_active(
             codex_core::windows_sandbox::ELEVATED_SANDBOX_NUX_ENABLED
@@ -3278,6 +3378,9 @@ impl ChatWidget {
                 self.otel_manager.counter("codex.thread.rename", 1, &[]);
                 self.show_rename_prompt();
             }
+            SlashCommand::Handoff => {
+                self.export_handoff(None);
+            }
             SlashCommand::Model => {
                 self.open_model_popup();
             }
@@ -3410,6 +3513,17 @@ impl ChatWidget {
             SlashCommand::Mention => {
                 self.insert_str("@");
             }
+            SlashCommand::Recent => {
+                self.add_recent_files_output();
+            }
+            SlashCommand::Bookmarks => {
+                self.add_bookmarks_output();
+            }
+            SlashCommand::Reindex => {
+                self.code_graph_reindex_pending = true;
+                self.submit_op(Op::ReindexCodeGraph);
+                self.add_info_message("Re-indexing the code graph…".to_string(), None);
+            }
             SlashCommand::Skills => {
                 self.open_skills_menu();
             }
@@ -3531,6 +3645,15 @@ impl ChatWidget {
                     .send(AppEvent::CodexOp(Op::SetThreadName { name }));
                 self.bottom_pane.drain_pending_submission_state();
             }
+            SlashCommand::Handoff if !trimmed.is_empty() => {
+                let Some((prepared_args, _prepared_elements)) =
+                    self.bottom_pane.prepare_inline_args_submission(false)
+                else {
+                    return;
+                };
+                self.export_handoff(Some(PathBuf::from(prepared_args.trim())));
+                self.bottom_pane.drain_pending_submission_state();
+            }
             SlashCommand::Plan if !trimmed.is_empty() => {
                 self.dispatch_command(cmd);
                 if self.active_mode_kind() != ModeKind::Plan {
@@ -3593,6 +3716,41 @@ impl ChatWidget {
         }
     }
 
+    /// Write this session to a handoff bundle at `output` (relative to the
+    /// working directory), or `<thread id>.happysession` there by default.
+    fn export_handoff(&mut self, output: Option<PathBuf>) {
+        let Some(thread_id) = self.thread_id else {
+            self.add_info_message("Session is not ready yet.".to_string(), None);
+            return;
+        };
+        let output = self.config.cwd.join(
+            output.unwrap_or_else(|| PathBuf::from(format!("{thread_id}.{HANDOFF_EXTENSION}"))),
+        );
+        let codex_home = self.config.codex_home.clone();
+        let effective_config = self.config.config_layer_stack.effective_config();
+        let tx = self.app_event_tx.clone();
+        tokio::spawn(async move {
+            let cell = match export_session(
+                &codex_home,
+                &thread_id.to_string(),
+                &effective_config,
+                &output,
+            )
+            .await
+            {
+                Ok(_) => history_cell::new_info_event(
+                    format!("Wrote handoff bundle {}", output.display()),
+                    Some(format!(
+                        "Import it with: happycode session import {}",
+                        output.display()
+                    )),
+                ),
+                Err(err) => history_cell::new_error_event(format!("Handoff failed: {err}")),
+            };
+            tx.send(AppEvent::InsertHistoryCell(Box::new(cell)));
+        });
+    }
+
     fn show_rename_prompt(&mut self) {
         let tx = self.app_event_tx.clone();
         let has_name = self
@@ -4085,6 +4243,8 @@ impl ChatWidget {
                     force_reload: true,
                 });
             }
+            EventMsg::CodeGraphUpdated(ev) => self.on_code_graph_updated(ev),
+            EventMsg::ToolCallResult(ev) => self.on_tool_call_result(ev),
             EventMsg::ShutdownComplete => self.on_shutdown_complete(),
             EventMsg::TurnDiff(TurnDiffEvent { unified_diff }) => self.on_turn_diff(unified_diff),
             EventMsg::DeprecationNotice(ev) => self.on_deprecation_notice(ev),
@@ -4585,6 +4745,70 @@ impl ChatWidget {
         self.add_to_history(history_cell::new_unified_exec_processes_output(processes));
     }
 
+    pub(crate) fn add_recent_files_output(&mut self) {
+        self.add_to_history(history_cell::new_recent_files_output(
+            &self.recent_files.ranked(),
+        ));
+    }
+
+    /// Resolve the repo's bookmarks against its cached index in the
+    /// background, so stale ones are flagged.
+    fn add_bookmarks_output(&mut self) {
+        let root = self.config.cwd.clone();
+        let tx = self.app_event_tx.clone();
+        tokio::spawn(async move {
+            let cell: Box<dyn HistoryCell> = match tokio::task::spawn_blocking(move || {
+                let bookmarks = Bookmarks::load(&root)?;
+                let options = IndexOptions::load(&root);
+                let elements =
+                    happy_core::store::cache::load_or_index(&root, &LoadOptions::new(&options))
+                        .elements;
+                let mut graph = RepositoryGraph::new();
+                graph.build_from_elements(&elements, &root.to_string_lossy());
+                Ok::<_, std::io::Error>(bookmarks.resolve(&graph))
+            })
+            .await
+            {
+                Ok(Ok(resolved)) => Box::new(history_cell::new_bookmarks_output(&resolved)),
+                Ok(Err(err)) => Box::new(history_cell::new_error_event(format!(
+                    "Failed to read bookmarks: {err}"
+                ))),
+                Err(err) => Box::new(history_cell::new_error_event(format!(
+                    "Failed to load bookmarks: {err}"
+                ))),
+            };
+            tx.send(AppEvent::InsertHistoryCell(cell));
+        });
+    }
+
+    /// Record a file picked from the `@` popup.
+    pub(crate) fn note_file_mention(&mut self, path: &Path) {
+        if self
+            .recent_files
+            .touch(&self.config.cwd.join(path), FileActivity::Mention)
+        {
+            self.sync_recent_files();
+        }
+    }
+
+    pub(crate) fn toggle_recent_file_pin(&mut self, path: &Path) {
+        match self.recent_files.toggle_pin(&self.config.cwd.join(path)) {
+            Ok(pinned) => {
+                let verb = if pinned { "Pinned" } else { "Unpinned" };
+                self.add_info_message(format!("{verb} {}", path.display()), None);
+            }
+            Err(err) => {
+                self.add_error_message(format!("Failed to save {UI_STATE_FILE}: {err}"));
+            }
+        }
+        self.sync_recent_files();
+    }
+
+    fn sync_recent_files(&mut self) {
+        self.bottom_pane
+            .set_recent_files(self.recent_files.ranked());
+    }
+
     fn clean_background_terminals(&mut self) {
         self.submit_op(Op::CleanBackgroundTerminals);
         self.add_info_message("Stopping all background terminals.".to_string(), None);
@@ -5482,6 +5706,20 @@ impl ChatWidget {
             .into()
         });
 
+        if !self.approval_rules.rules().is_empty() {
+            let count = self.approval_rules.rules().len();
+            items.push(SelectionItem {
+                name: "Remembered approvals".to_string(),
+                description: Some(format!(
+                    "{count} {} that skip the approval prompt; review or revoke",
+                    if count == 1 { "rule" } else { "rules" }
+                )),
+                actions: vec![Box::new(|tx| tx.send(AppEvent::OpenRememberedApprovals))],
+                dismiss_on_select: true,
+                ..Default::default()
+            });
+        }
+
         self.bottom_pane.show_selection_view(SelectionViewParams {
             title: Some("Update Model Permissions".to_string()),
             footer_note,
@@ -5492,6 +5730,59 @@ impl ChatWidget {
         });
     }
 
+    /// List remembered approvals; selecting one revokes it.
+    pub(crate) fn open_remembered_approvals_popup(&mut self) {
+        let items: Vec<SelectionItem> = self
+            .approval_rules
+            .rules()
+            .iter()
+            .map(|remembered| {
+                let rule = remembered.rule.clone();
+                let description = match remembered.scope {
+                    RuleScope::Session => "this session".to_string(),
+                    RuleScope::Repo => "saved for this repo".to_string(),
+                };
+                SelectionItem {
+                    name: remembered.rule.describe(),
+                    description: Some(description),
+                    actions: vec![Box::new(move |tx| {
+                        tx.send(AppEvent::RevokeApproval(rule.clone()));
+                    })],
+                    dismiss_on_select: true,
+                    ..Default::default()
+                }
+            })
+            .collect();
+        self.bottom_pane.show_selection_view(SelectionViewParams {
+            title: Some("Remembered Approvals".to_string()),
+            subtitle: Some("Select a rule to revoke it.".to_string()),
+            footer_hint: Some(standard_popup_hint_line()),
+            items,
+            ..Default::default()
+        });
+    }
+
+    pub(crate) fn remember_approval(&mut self, rule: ApprovalRule, scope: RuleScope) {
+        let description = rule.describe();
+        if let Err(err) = self.approval_rules.remember(rule, scope) {
+            self.add_error_message(format!("Failed to save remembered approvals: {err}"));
+            return;
+        }
+        let message = match scope {
+            RuleScope::Session => format!("Allowing {description} for this session"),
+            RuleScope::Repo => format!("Allowing {description} in this repo"),
+        };
+        self.add_info_message(message, Some("Revoke it from /permissions.".to_string()));
+    }
+
+    pub(crate) fn revoke_approval(&mut self, rule: &ApprovalRule) {
+        if let Err(err) = self.approval_rules.revoke(rule) {
+            self.add_error_message(format!("Failed to save remembered approvals: {err}"));
+            return;
+        }
+        self.add_info_message(format!("Revoked approval for {}", rule.describe()), None);
+    }
+
     pub(crate) fn open_experimental_popup(&mut self) {
         let features: Vec<ExperimentalFeatureItem> = FEATURES
             .iter()
@@ -7165,6 +7456,24 @@ fn has_websocket_timing_metrics(summary: RuntimeMetricsSummary) -> bool {
         || summary.responses_api_engine_service_tbt_ms > 0
 }
 
+/// Approvals saved under `codex_home` for the repo at `cwd`; an unreadable
+/// file starts the session without them.
+fn load_approval_rules(codex_home: &Path, cwd: &Path) -> ApprovalRules {
+    ApprovalRules::load(codex_home, cwd).unwrap_or_else(|err| {
+        warn!("ignoring remembered approvals: {err}");
+        ApprovalRules::new(codex_home, cwd)
+    })
+}
+
+/// Pins saved for the repo at `cwd`; an unreadable file starts the session
+/// without them.
+fn load_recent_files(cwd: &Path) -> RecentFiles {
+    RecentFiles::load(cwd).unwrap_or_else(|err| {
+        warn!("ignoring {UI_STATE_FILE}: {err}");
+        RecentFiles::new(cwd)
+    })
+}
+
 impl Drop for ChatWidget {
     fn drop(&mut self) {
         self.stop_rate_limit_poller();
@@ -60,6 +60,7 @@ use codex_core::protocol::ThreadRolledBackEvent;
 use codex_core::protocol::TokenCountEvent;
 use codex_core::protocol::TokenUsage;
 use codex_core::protocol::TokenUsageInfo;
+use codex_core::protocol::ToolCallResultEvent;
 use codex_core::protocol::TurnCompleteEvent;
 use codex_core::protocol::TurnStartedEvent;
 use codex_core::protocol::UndoCompletedEvent;
@@ -1591,6 +1592,8 @@ async fn make_chatwidget_manual(
         },
     };
     let current_collaboration_mode = base_mode;
+    let approval_rules = ApprovalRules::new(&cfg.codex_home, &cfg.cwd);
+    let recent_files = RecentFiles::new(&cfg.cwd);
     let mut widget = ChatWidget {
         app_event_tx,
         codex_op_tx: op_tx,
@@ -1665,7 +1668,10 @@ async fn make_chatwidget_manual(
         status_line_branch_cwd: None,
         status_line_branch_pending: false,
         status_line_branch_lookup_complete: false,
+        code_graph_reindex_pending: false,
         external_editor_state: ExternalEditorState::Closed,
+        approval_rules,
+        recent_files,
     };
     widget.set_model(&resolved_model);
     (widget, rx, op_rx)
@@ -2514,6 +2520,123 @@ async fn exec_approval_emits_proposed_command_and_decision_history() {
     );
 }
 
+#[tokio::test]
+async fn remembered_command_approval_skips_the_modal() {
+    let (mut chat, mut rx, mut op_rx) = make_chatwidget_manual(None).await;
+    chat.remember_approval(
+        ApprovalRule::Command {
+            prefix: vec!["cargo".into(), "test".into()],
+        },
+        RuleScope::Session,
+    );
+    drain_insert_history(&mut rx);
+
+    let ev = ExecApprovalRequestEvent {
+        call_id: "call-remembered".into(),
+        approval_id: Some("call-remembered".into()),
+        turn_id: "turn-remembered".into(),
+        command: vec!["bash".into(), "-lc".into(), "cargo test -p core".into()],
+        cwd: std::env::current_dir().unwrap_or_else(|_| PathBuf::from(".")),
+        reason: None,
+        network_approval_context: None,
+        proposed_execpolicy_amendment: None,
+        parsed_cmd: vec![],
+    };
+    chat.handle_codex_event(Event {
+        id: "sub-remembered".into(),
+        msg: EventMsg::ExecApprovalRequest(ev),
+    });
+
+    assert!(chat.bottom_pane.no_modal_or_popup_active());
+    match op_rx.try_recv() {
+        Ok(Op::ExecApproval { id, decision, .. }) => {
+            assert_eq!(id, "call-remembered");
+            assert_eq!(decision, ReviewDecision::Approved);
+        }
+        other => panic!("expected Op::ExecApproval, got {other:?}"),
+    }
+    let cells = drain_insert_history(&mut rx);
+    let text = cells
+        .iter()
+        .map(|lines| lines_to_single_string(lines))
+        .collect::<String>();
+    assert!(text.contains("by a remembered approval"), "{text}");
+}
+
+#[tokio::test]
+async fn edited_and_mentioned_files_show_up_in_recent() {
+    let (mut chat, mut rx, _op_rx) = make_chatwidget_manual(None).await;
+    chat.handle_codex_event(Event {
+        id: "s1".into(),
+        msg: EventMsg::PatchApplyBegin(PatchApplyBeginEvent {
+            call_id: "c1".into(),
+            turn_id: "turn-c1".into(),
+            auto_approved: true,
+            changes: HashMap::from([(
+                PathBuf::from("src/app.rs"),
+                FileChange::Add {
+                    content: "fn main() {}\n".to_string(),
+                },
+            )]),
+        }),
+    });
+    chat.note_file_mention(Path::new("docs/guide.md"));
+    drain_insert_history(&mut rx);
+
+    let ranked: Vec<PathBuf> = chat
+        .recent_files
+        .ranked()
+        .into_iter()
+        .map(|file| file.path)
+        .collect();
+    assert_eq!(
+        ranked,
+        vec![PathBuf::from("src/app.rs"), PathBuf::from("docs/guide.md")]
+    );
+
+    chat.dispatch_command(SlashCommand::Recent);
+    let cells = drain_insert_history(&mut rx);
+    let text = cells
+        .iter()
+        .map(|lines| lines_to_single_string(lines))
+        .collect::<String>();
+    assert!(text.contains("@src/app.rs"), "{text}");
+    assert!(text.contains("@docs/guide.md"), "{text}");
+}
+
+#[tokio::test]
+async fn files_touched_by_tool_calls_show_up_in_recent() {
+    let (mut chat, _rx, _op_rx) = make_chatwidget_manual(None).await;
+    let cwd = chat.config.cwd.clone();
+    chat.handle_codex_event(Event {
+        id: "s1".into(),
+        msg: EventMsg::ToolCallResult(ToolCallResultEvent {
+            call_id: "c1".into(),
+            tool_name: "read_file".into(),
+            touched_files: vec![cwd.join("src/lib.rs")],
+        }),
+    });
+    chat.handle_codex_event(Event {
+        id: "s1".into(),
+        msg: EventMsg::ToolCallResult(ToolCallResultEvent {
+            call_id: "c2".into(),
+            tool_name: "find_callers".into(),
+            touched_files: vec![cwd.join("src/app.rs"), PathBuf::from("/elsewhere/x.rs")],
+        }),
+    });
+
+    let ranked: Vec<PathBuf> = chat
+        .recent_files
+        .ranked()
+        .into_iter()
+        .map(|file| file.path)
+        .collect();
+    assert_eq!(
+        ranked,
+        vec![PathBuf::from("src/app.rs"), PathBuf::from("src/lib.rs")]
+    );
+}
+
 #[tokio::test]
 async fn exec_approval_decision_truncates_multiline_and_long_commands() {
     let (mut chat, mut rx, _op_rx) = make_chatwidget_manual(None).await;
@@ -110,6 +110,19 @@ pub struct Cli {
     #[arg(long = "no-alt-screen", default_value_t = false)]
     pub no_alt_screen: bool,
 
+    /// Increase code graph log verbosity (`-v` for debug, `-vv` for trace).
+    #[arg(long = "verbose", short = 'v', action = clap::ArgAction::Count)]
+    pub verbose: u8,
+
+    /// Also write JSON-formatted logs to this file, relative to the working root
+    /// (e.g. `.happy/logs/happy.log`). Overrides `[logging] file` in `.happy/agent.toml`.
+    #[arg(long = "log-file", value_name = "FILE", value_hint = ValueHint::FilePath)]
+    pub log_file: Option<PathBuf>,
+
+    /// Index the code graph from scratch instead of loading `.happy/elements.bin`.
+    #[arg(long = "reindex", default_value_t = false)]
+    pub reindex: bool,
+
     #[clap(skip)]
     pub config_overrides: CliConfigOverrides,
 }
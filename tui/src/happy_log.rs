@@ -0,0 +1,160 @@
+//! JSON-lines file sink for the code graph log (`[logging] file` in
+//! `.happy/agent.toml`, or `--log-file`).
+//!
+//! The TUI owns the terminal, so nothing here ever writes to stderr: every
+//! event goes to the file as one JSON object per line.
+
+use std::fmt;
+use std::fs::OpenOptions;
+use std::path::Path;
+
+use serde_json::Map;
+use serde_json::Value;
+use tracing::Event;
+use tracing::Subscriber;
+use tracing::field::Field;
+use tracing::field::Visit;
+use tracing_appender::non_blocking::NonBlocking;
+use tracing_appender::non_blocking::WorkerGuard;
+use tracing_subscriber::fmt::FmtContext;
+use tracing_subscriber::fmt::FormatEvent;
+use tracing_subscriber::fmt::FormatFields;
+use tracing_subscriber::fmt::format::Writer;
+use tracing_subscriber::registry::LookupSpan;
+
+/// Formats each event as `{"timestamp", "level", "target", "spans", "fields"}`.
+pub(crate) struct JsonLines;
+
+impl<S, N> FormatEvent<S, N> for JsonLines
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    N: for<'a> FormatFields<'a> + 'static,
+{
+    fn format_event(
+        &self,
+        ctx: &FmtContext<'_, S, N>,
+        mut writer: Writer<'_>,
+        event: &Event<'_>,
+    ) -> fmt::Result {
+        let mut fields = JsonVisitor::default();
+        event.record(&mut fields);
+
+        let spans: Vec<Value> = ctx
+            .event_scope()
+            .map(|scope| {
+                scope
+                    .from_root()
+                    .map(|span| Value::String(span.name().to_string()))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let metadata = event.metadata();
+        let line = serde_json::json!({
+            "timestamp": chrono::Utc::now()
+                .to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+            "level": metadata.level().as_str(),
+            "target": metadata.target(),
+            "spans": spans,
+            "fields": Value::Object(fields.0),
+        });
+        writeln!(writer, "{line}")
+    }
+}
+
+#[derive(Default)]
+struct JsonVisitor(Map<String, Value>);
+
+impl Visit for JsonVisitor {
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.0.insert(field.name().to_string(), value.into());
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.0.insert(field.name().to_string(), value.into());
+    }
+
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.0.insert(field.name().to_string(), value.into());
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.0.insert(field.name().to_string(), value.into());
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.insert(field.name().to_string(), value.into());
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.0
+            .insert(field.name().to_string(), format!("{value:?}").into());
+    }
+}
+
+/// Open `path` for appending (creating parent directories) and wrap it in a
+/// non-blocking writer. Keep the guard alive for the life of the process.
+pub(crate) fn open_log_file(path: &Path) -> std::io::Result<(NonBlocking, WorkerGuard)> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut opts = OpenOptions::new();
+    opts.create(true).append(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        opts.mode(0o600);
+    }
+    Ok(tracing_appender::non_blocking(opts.open(path)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use tracing_subscriber::EnvFilter;
+    use tracing_subscriber::prelude::*;
+
+    #[test]
+    fn json_lines_include_span_and_fields() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let path = dir.path().join(".happy/logs/happy.log");
+        let (writer, guard) = open_log_file(&path).expect("open log file");
+
+        let layer = tracing_subscriber::fmt::layer()
+            .event_format(JsonLines)
+            .with_writer(writer)
+            .with_filter(EnvFilter::new("happy_core=debug"));
+        let subscriber = tracing_subscriber::registry().with(layer);
+        tracing::subscriber::with_default(subscriber, || {
+            let span = tracing::info_span!(target: "happy_core::indexer", "walk");
+            let _entered = span.enter();
+            tracing::debug!(
+                target: "happy_core::indexer",
+                file = "src/lib.rs",
+                duration_ms = 12u64,
+                elements = 3u64,
+                "parsed file"
+            );
+            tracing::debug!(target: "codex_core", "filtered out");
+        });
+        drop(guard);
+
+        let text = std::fs::read_to_string(&path).expect("read log");
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), 1, "{text}");
+        let line: Value = serde_json::from_str(lines[0]).expect("json line");
+        assert_eq!(line["level"], "DEBUG");
+        assert_eq!(line["target"], "happy_core::indexer");
+        assert_eq!(line["spans"], serde_json::json!(["walk"]));
+        assert_eq!(line["fields"]["message"], "parsed file");
+        assert_eq!(line["fields"]["file"], "src/lib.rs");
+        assert_eq!(line["fields"]["duration_ms"], 12);
+        assert_eq!(line["fields"]["elements"], 3);
+        assert!(
+            line["timestamp"]
+                .as_str()
+                .is_some_and(|ts| ts.ends_with('Z'))
+        );
+    }
+}
@@ -21,6 +21,7 @@ use crate::exec_command::relativize_to_home;
 use crate::exec_command::strip_bash_lc_and_escape;
 use crate::live_wrap::take_prefix_by_width;
 use crate::markdown::append_markdown;
+use crate::recent_files::RecentFile;
 use crate::render::line_utils::line_to_static;
 use crate::render::line_utils::prefix_lines;
 use crate::render::line_utils::push_owned_lines;
@@ -58,6 +59,8 @@ use codex_protocol::request_user_input::RequestUserInputAnswer;
 use codex_protocol::request_user_input::RequestUserInputQuestion;
 use codex_protocol::user_input::TextElement;
 use codex_utils_cli::format_env_display::format_env_display;
+use happy_core::bookmarks::BookmarkStatus;
+use happy_core::bookmarks::ResolvedBookmark;
 use image::DynamicImage;
 use image::ImageReader;
 use ratatui::prelude::*;
@@ -769,6 +772,82 @@ pub(crate) fn new_unified_exec_processes_output(
     CompositeHistoryCell::new(vec![Box::new(command), Box::new(summary)])
 }
 
+pub(crate) fn new_recent_files_output(files: &[RecentFile]) -> CompositeHistoryCell {
+    let command = PlainHistoryCell::new(vec!["/recent".magenta().into()]);
+    let mut lines: Vec<Line<'static>> = vec![vec!["Recent files".bold()].into(), "".into()];
+    if files.is_empty() {
+        lines.push(
+            "  • No files read, edited, or mentioned yet."
+                .italic()
+                .into(),
+        );
+    } else {
+        for file in files {
+            let mut line: Line<'static> =
+                vec!["  • ".into(), format!("@{}", file.path.display()).into()].into();
+            if file.pinned {
+                line.push_span(" (pinned)".dim());
+            }
+            lines.push(line);
+        }
+    }
+    lines.push("".into());
+    lines.push(
+        "  Type @ to pick one; ctrl + o in the list pins or unpins it."
+            .dim()
+            .into(),
+    );
+    CompositeHistoryCell::new(vec![
+        Box::new(command),
+        Box::new(PlainHistoryCell::new(lines)),
+    ])
+}
+
+pub(crate) fn new_bookmarks_output(bookmarks: &[ResolvedBookmark]) -> CompositeHistoryCell {
+    let command = PlainHistoryCell::new(vec!["/bookmarks".magenta().into()]);
+    let mut lines: Vec<Line<'static>> = vec![vec!["Bookmarks".bold()].into(), "".into()];
+    if bookmarks.is_empty() {
+        lines.push("  • No bookmarks yet.".italic().into());
+    }
+    for entry in bookmarks {
+        let key = &entry.bookmark.key;
+        let mut line: Line<'static> = vec![
+            "  • ".into(),
+            format!("{} ", key.kind.as_str()).dim(),
+            key.name.clone().bold(),
+            "  ".into(),
+        ]
+        .into();
+        match &entry.status {
+            BookmarkStatus::Live(element) => {
+                line.push_span(format!("{}:{}", element.relative_path, element.start_line).cyan());
+            }
+            BookmarkStatus::Renamed { element, .. } => {
+                line.push_span(format!("{}:{}", element.relative_path, element.start_line).cyan());
+                line.push_span(format!(" (now {})", element.name).dim());
+            }
+            BookmarkStatus::Stale => {
+                line.push_span(key.relative_path.clone().dim());
+                line.push_span(" stale".red());
+            }
+        }
+        lines.push(line);
+        if !entry.bookmark.note.is_empty() {
+            lines.push(vec!["    ".into(), entry.bookmark.note.clone().into()].into());
+        }
+    }
+    lines.push("".into());
+    lines.push(
+        "  Add one with `happycode bookmark add <symbol> -m <note>` or ask the agent to."
+            .dim()
+            .into(),
+    );
+    CompositeHistoryCell::new(vec![
+        Box::new(command),
+        Box::new(PlainHistoryCell::new(lines)),
+    ])
+}
+
 fn truncate_exec_snippet(full_cmd: &str) -> String {
     let mut snippet = match full_cmd.split_once('\n') {
         Some((first, _)) => format!("{first} ..."),
@@ -863,6 +942,25 @@ pub fn new_approval_decision_cell(
     ))
 }
 
+/// A request a remembered approval answered without a prompt; `command` is
+/// `None` for edits.
+pub(crate) fn new_remembered_approval_cell(command: Option<&[String]>) -> Box<dyn HistoryCell> {
+    let mut summary: Vec<Span<'static>> = vec!["Approved".bold()];
+    match command {
+        Some(command) => {
+            summary.push(" codex to run ".into());
+            summary.push(Span::from(exec_snippet(command)).dim());
+        }
+        None => summary.push(" edits".into()),
+    }
+    summary.push(" by a remembered approval".into());
+    Box::new(PrefixedWrappedHistoryCell::new(
+        Line::from(summary),
+        "✔ ".green(),
+        "  ",
+    ))
+}
+
 /// Cyan history cell line showing the current review status.
 pub(crate) fn new_review_status_line(message: String) -> PlainHistoryCell {
     PlainHistoryCell {
@@ -60,6 +60,7 @@ mod app;
 mod app_backtrack;
 mod app_event;
 mod app_event_sender;
+mod approval_rules;
 mod ascii_animation;
 mod bottom_pane;
 mod chatwidget;
@@ -77,6 +78,7 @@ mod external_editor;
 mod file_search;
 mod frames;
 mod get_git_diff;
+mod happy_log;
 mod history_cell;
 pub mod insert_history;
 mod key_hint;
@@ -92,6 +94,7 @@ pub mod onboarding;
 mod oss_selection;
 mod pager_overlay;
 pub mod public_widgets;
+mod recent_files;
 mod render;
 mod resume_picker;
 mod selection_list;
@@ -127,6 +130,10 @@ pub use public_widgets::composer_input::ComposerAction;
 pub use public_widgets::composer_input::ComposerInput;
 // (tests access modules directly within the crate)
 
+/// Log filter used when `RUST_LOG` is unset.
+const DEFAULT_LOG_DIRECTIVES: &str =
+    "codex_core=info,codex_tui=info,codex_rmcp_client=info,happy_core=info";
+
 pub async fn run_main(
     mut cli: Cli,
     codex_linux_sandbox_exe: Option<PathBuf>,
@@ -281,6 +288,7 @@ pub async fn run_main(
         config_profile: cli.config_profile.clone(),
         codex_linux_sandbox_exe,
         show_raw_agent_reasoning: cli.oss.then_some(true),
+        reindex_code_graph: cli.reindex.then_some(true),
         additional_writable_roots: additional_dirs,
         ..Default::default()
     };
@@ -343,11 +351,20 @@ pub async fn run_main(
     // Wrap file in non‑blocking writer.
     let (non_blocking, _guard) = non_blocking(log_file);
 
+    // `[logging]` from `.happy/agent.toml` refines the defaults for the code graph.
+    #[allow(clippy::print_stderr)]
+    let happy_logging =
+        happy_core::logging::LoggingConfig::load(&config.cwd).unwrap_or_else(|err| {
+            eprintln!("Ignoring invalid [logging] config in .happy/agent.toml: {err}");
+            happy_core::logging::LoggingConfig::default()
+        });
+    let default_directives = happy_logging
+        .directives(DEFAULT_LOG_DIRECTIVES, cli.verbose)
+        .unwrap_or_else(|_| DEFAULT_LOG_DIRECTIVES.to_string());
+
     // use RUST_LOG env var, default to info for codex crates.
     let env_filter = || {
-        EnvFilter::try_from_default_env().unwrap_or_else(|_| {
-            EnvFilter::new("codex_core=info,codex_tui=info,codex_rmcp_client=info")
-        })
+        EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(&default_directives))
     };
 
     let file_layer = tracing_subscriber::fmt::layer()
@@ -363,6 +380,35 @@ pub async fn run_main(
         )
         .with_filter(env_filter());
 
+    // Optional JSON-lines sink for greppable code graph logs. Like the main log
+    // file, it never touches stderr, so it can't corrupt the alternate screen.
+    let happy_log_path = cli
+        .log_file
+        .as_ref()
+        .map(|file| config.cwd.join(file))
+        .or_else(|| happy_logging.log_file(&config.cwd));
+    let (happy_json_layer, _happy_log_guard) = match happy_log_path {
+        Some(path) => match happy_log::open_log_file(&path) {
+            Ok((writer, guard)) => (
+                Some(
+                    tracing_subscriber::fmt::layer()
+                        .event_format(happy_log::JsonLines)
+                        .with_writer(writer)
+                        .with_filter(env_filter()),
+                ),
+                Some(guard),
+            ),
+            Err(err) => {
+                #[allow(clippy::print_stderr)]
+                {
+                    eprintln!("Could not open log file {}: {err}", path.display());
+                }
+                (None, None)
+            }
+        },
+        None => (None, None),
+    };
+
     let feedback = codex_feedback::CodexFeedback::new();
     let feedback_layer = feedback.logger_layer();
     let feedback_metadata_layer = feedback.metadata_layer();
@@ -412,6 +458,7 @@ pub async fn run_main(
 
     let _ = tracing_subscriber::registry()
         .with(file_layer)
+        .with(happy_json_layer)
         .with(feedback_layer)
         .with(feedback_metadata_layer)
         .with(log_db_layer)
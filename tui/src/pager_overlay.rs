@@ -14,6 +14,11 @@
 //! `TranscriptOverlay::sync_live_tail` uses the key to decide when the cached tail must be
 //! recomputed. `ChatWidget` is responsible for producing a key that changes when the active cell
 //! mutates in place or when its transcript output is time-dependent.
+//!
+//! Scroll position is kept as a [`ScrollAnchor`] (entry index plus row within that entry) rather
+//! than a row offset, so output streaming in below what the user is reading never moves it, and
+//! re-wrapping at a new width keeps the same entry at the top. Following the end of the
+//! transcript is the `ScrollAnchor::End` state.
 
 use std::io::Result;
 use std::sync::Arc;
@@ -131,25 +136,43 @@ fn render_key_hints(area: Rect, buf: &mut Buffer, pairs: &[(&[KeyBinding], &str)
     Paragraph::new(vec![Line::from(spans).dim()]).render_ref(area, buf);
 }
 
+/// Where the top of the pager viewport is pinned.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ScrollAnchor {
+    /// Follow the end of the content, so appended output stays in view.
+    End,
+    /// Show row `line` of renderable `chunk` at the top of the viewport. The row offset is
+    /// recomputed from the renderables on every render, so content appended after `chunk` never
+    /// moves the view and entries above it that grow or re-wrap only shift the computed offset.
+    Line { chunk: usize, line: usize },
+}
+
+impl ScrollAnchor {
+    const TOP: Self = Self::Line { chunk: 0, line: 0 };
+}
+
 /// Generic widget for rendering a pager view.
 struct PagerView {
     renderables: Vec<Box<dyn Renderable>>,
-    scroll_offset: usize,
+    anchor: ScrollAnchor,
     title: String,
     last_content_height: Option<usize>,
     last_rendered_height: Option<usize>,
+    /// Content width of the last render, used to map key scrolling onto the anchor.
+    last_width: Option<u16>,
     /// If set, on next render ensure this chunk is visible.
     pending_scroll_chunk: Option<usize>,
 }
 
 impl PagerView {
-    fn new(renderables: Vec<Box<dyn Renderable>>, title: String, scroll_offset: usize) -> Self {
+    fn new(renderables: Vec<Box<dyn Renderable>>, title: String, anchor: ScrollAnchor) -> Self {
         Self {
             renderables,
-            scroll_offset,
+            anchor,
             title,
             last_content_height: None,
             last_rendered_height: None,
+            last_width: None,
             pending_scroll_chunk: None,
         }
     }
@@ -168,18 +191,19 @@ impl PagerView {
         self.update_last_content_height(content_area.height);
         let content_height = self.content_height(content_area.width);
         self.last_rendered_height = Some(content_height);
+        self.last_width = Some(content_area.width);
         // If there is a pending request to scroll a specific chunk into view,
         // satisfy it now that wrapping is up to date for this width.
         if let Some(idx) = self.pending_scroll_chunk.take() {
             self.ensure_chunk_visible(idx, content_area);
         }
-        self.scroll_offset = self
-            .scroll_offset
-            .min(content_height.saturating_sub(content_area.height as usize));
+        // Clamp only for display; the anchor itself is kept so it still points at the same
+        // entry if the content grows or the width changes back.
+        let scroll_offset = self.scroll_offset(content_area.width, content_area.height as usize);
 
-        self.render_content(content_area, buf);
+        self.render_content(content_area, buf, scroll_offset);
 
-        self.render_bottom_bar(area, content_area, buf, content_height);
+        self.render_bottom_bar(area, content_area, buf, content_height, scroll_offset);
     }
 
     fn render_header(&self, area: Rect, buf: &mut Buffer) {
@@ -190,8 +214,8 @@ impl PagerView {
         header.dim().render_ref(area, buf);
     }
 
-    fn render_content(&self, area: Rect, buf: &mut Buffer) {
-        let mut y = -(self.scroll_offset as isize);
+    fn render_content(&self, area: Rect, buf: &mut Buffer, scroll_offset: usize) {
+        let mut y = -(scroll_offset as isize);
         let mut drawn_bottom = area.y;
         for renderable in &self.renderables {
             let top = y;
@@ -232,6 +256,7 @@ impl PagerView {
         content_area: Rect,
         buf: &mut Buffer,
         total_len: usize,
+        scroll_offset: usize,
     ) {
         let sep_y = content_area.bottom();
         let sep_rect = Rect::new(full_area.x, sep_y, full_area.width, 1);
@@ -246,8 +271,7 @@ impl PagerView {
             if max_scroll == 0 {
                 100
             } else {
-                (((self.scroll_offset.min(max_scroll)) as f32 / max_scroll as f32) * 100.0).round()
-                    as u8
+                (((scroll_offset.min(max_scroll)) as f32 / max_scroll as f32) * 100.0).round() as u8
             }
         };
         let pct_text = format!(" {percent}% ");
@@ -259,39 +283,39 @@ impl PagerView {
     }
 
     fn handle_key_event(&mut self, tui: &mut tui::Tui, key_event: KeyEvent) -> Result<()> {
+        let viewport_area = tui.terminal.viewport_area;
+        let width = self
+            .last_width
+            .unwrap_or_else(|| self.content_area(viewport_area).width);
+        let page_height = self.page_height(viewport_area);
+        let half_page = (self.content_area(viewport_area).height as isize + 1) / 2;
         match key_event {
             e if KEY_UP.is_press(e) || KEY_K.is_press(e) => {
-                self.scroll_offset = self.scroll_offset.saturating_sub(1);
+                self.scroll_by(-1, width, page_height);
             }
             e if KEY_DOWN.is_press(e) || KEY_J.is_press(e) => {
-                self.scroll_offset = self.scroll_offset.saturating_add(1);
+                self.scroll_by(1, width, page_height);
             }
             e if KEY_PAGE_UP.is_press(e)
                 || KEY_SHIFT_SPACE.is_press(e)
                 || KEY_CTRL_B.is_press(e) =>
             {
-                let page_height = self.page_height(tui.terminal.viewport_area);
-                self.scroll_offset = self.scroll_offset.saturating_sub(page_height);
+                self.scroll_by(-(page_height as isize), width, page_height);
             }
             e if KEY_PAGE_DOWN.is_press(e) || KEY_SPACE.is_press(e) || KEY_CTRL_F.is_press(e) => {
-                let page_height = self.page_height(tui.terminal.viewport_area);
-                self.scroll_offset = self.scroll_offset.saturating_add(page_height);
+                self.scroll_by(page_height as isize, width, page_height);
             }
             e if KEY_CTRL_D.is_press(e) => {
-                let area = self.content_area(tui.terminal.viewport_area);
-                let half_page = (area.height as usize).saturating_add(1) / 2;
-                self.scroll_offset = self.scroll_offset.saturating_add(half_page);
+                self.scroll_by(half_page, width, page_height);
             }
             e if KEY_CTRL_U.is_press(e) => {
-                let area = self.content_area(tui.terminal.viewport_area);
-                let half_page = (area.height as usize).saturating_add(1) / 2;
-                self.scroll_offset = self.scroll_offset.saturating_sub(half_page);
+                self.scroll_by(-half_page, width, page_height);
             }
             e if KEY_HOME.is_press(e) => {
-                self.scroll_offset = 0;
+                self.anchor = ScrollAnchor::TOP;
             }
             e if KEY_END.is_press(e) => {
-                self.scroll_offset = usize::MAX;
+                self.anchor = ScrollAnchor::End;
             }
             _ => {
                 return Ok(());
@@ -325,11 +349,66 @@ impl PagerView {
 }
 
 impl PagerView {
+    /// Content row the anchor points at for `width`, before clamping to the last page.
+    fn anchor_offset(&self, width: u16) -> usize {
+        let ScrollAnchor::Line { chunk, line } = self.anchor else {
+            return usize::MAX;
+        };
+        let mut top = 0;
+        for (idx, renderable) in self.renderables.iter().enumerate() {
+            let height = renderable.desired_height(width) as usize;
+            if idx == chunk {
+                // The entry may have re-wrapped to fewer rows since the anchor was set.
+                return top + line.min(height.saturating_sub(1));
+            }
+            top += height;
+        }
+        top
+    }
+
+    /// The anchor that puts content row `offset` at the top of the viewport for `width`.
+    fn anchor_at(&self, offset: usize, width: u16) -> ScrollAnchor {
+        let mut top = 0;
+        for (chunk, renderable) in self.renderables.iter().enumerate() {
+            let height = renderable.desired_height(width) as usize;
+            if offset < top + height {
+                return ScrollAnchor::Line {
+                    chunk,
+                    line: offset - top,
+                };
+            }
+            top += height;
+        }
+        ScrollAnchor::End
+    }
+
+    /// First visible content row for a viewport `height` rows tall.
+    fn scroll_offset(&self, width: u16, height: usize) -> usize {
+        self.anchor_offset(width)
+            .min(self.content_height(width).saturating_sub(height))
+    }
+
+    /// Anchor the viewport at content row `offset`. Reaching the last page switches to following
+    /// the end, as if `End` had been pressed.
+    fn scroll_to(&mut self, offset: usize, width: u16, height: usize) {
+        let max_scroll = self.content_height(width).saturating_sub(height);
+        self.anchor = if offset >= max_scroll {
+            ScrollAnchor::End
+        } else {
+            self.anchor_at(offset, width)
+        };
+    }
+
+    fn scroll_by(&mut self, delta: isize, width: u16, height: usize) {
+        let current = self.scroll_offset(width, height);
+        self.scroll_to(current.saturating_add_signed(delta), width, height);
+    }
+
     fn is_scrolled_to_bottom(&self) -> bool {
-        if self.scroll_offset == usize::MAX {
+        if self.anchor == ScrollAnchor::End {
             return true;
         }
-        let Some(height) = self.last_content_height else {
+        let (Some(height), Some(width)) = (self.last_content_height, self.last_width) else {
             return false;
         };
         if self.renderables.is_empty() {
@@ -342,7 +421,7 @@ impl PagerView {
             return true;
         }
         let max_scroll = total_height.saturating_sub(height);
-        self.scroll_offset >= max_scroll
+        self.anchor_offset(width) >= max_scroll
     }
 
     /// Request that the given text chunk index be scrolled into view on next render.
@@ -361,12 +440,14 @@ impl PagerView {
             .map(|r| r.desired_height(area.width) as usize)
             .sum();
         let last = first + self.renderables[idx].desired_height(area.width) as usize;
-        let current_top = self.scroll_offset;
+        let height = area.height as usize;
+        let current_top = self.scroll_offset(area.width, height);
         let current_bottom = current_top.saturating_add(area.height.saturating_sub(1) as usize);
         if first < current_top {
-            self.scroll_offset = first;
+            self.scroll_to(first, area.width, height);
         } else if last > current_bottom {
-            self.scroll_offset = last.saturating_sub(area.height.saturating_sub(1) as usize);
+            let top = last.saturating_sub(area.height.saturating_sub(1) as usize);
+            self.scroll_to(top, area.width, height);
         }
     }
 }
@@ -458,7 +539,7 @@ impl TranscriptOverlay {
             view: PagerView::new(
                 Self::render_cells(&transcript_cells, None),
                 "T R A N S C R I P T".to_string(),
-                usize::MAX,
+                ScrollAnchor::End,
             ),
             cells: transcript_cells,
             highlight_cell: None,
@@ -535,7 +616,7 @@ impl TranscriptOverlay {
             self.view.renderables.push(tail);
         }
         if follow_bottom {
-            self.view.scroll_offset = usize::MAX;
+            self.view.anchor = ScrollAnchor::End;
         }
     }
 
@@ -555,7 +636,7 @@ impl TranscriptOverlay {
         }
         self.rebuild_renderables();
         if follow_bottom {
-            self.view.scroll_offset = usize::MAX;
+            self.view.anchor = ScrollAnchor::End;
         }
     }
 
@@ -603,7 +684,7 @@ impl TranscriptOverlay {
             }
         }
         if follow_bottom {
-            self.view.scroll_offset = usize::MAX;
+            self.view.anchor = ScrollAnchor::End;
         }
     }
 
@@ -720,7 +801,7 @@ impl StaticOverlay {
 
     pub(crate) fn with_renderables(renderables: Vec<Box<dyn Renderable>>, title: String) -> Self {
         Self {
-            view: PagerView::new(renderables, title, 0),
+            view: PagerView::new(renderables, title, ScrollAnchor::TOP),
             is_done: false,
         }
     }
@@ -1017,7 +1098,7 @@ mod tests {
         let mut buf = Buffer::empty(area);
 
         overlay.render(area, &mut buf);
-        overlay.view.scroll_offset = 0;
+        overlay.view.anchor = ScrollAnchor::TOP;
         overlay.render(area, &mut buf);
 
         let snapshot = buffer_to_text(&buf, area);
@@ -1048,7 +1129,7 @@ mod tests {
             lines: vec!["tail".into()],
         }));
 
-        assert_eq!(overlay.view.scroll_offset, usize::MAX);
+        assert_eq!(overlay.view.anchor, ScrollAnchor::End);
     }
 
     #[test]
@@ -1066,13 +1147,13 @@ mod tests {
         term.draw(|f| overlay.render(f.area(), f.buffer_mut()))
             .expect("draw");
 
-        overlay.view.scroll_offset = 0;
+        overlay.view.anchor = ScrollAnchor::TOP;
 
         overlay.insert_cell(Arc::new(TestCell {
             lines: vec!["tail".into()],
         }));
 
-        assert_eq!(overlay.view.scroll_offset, 0);
+        assert_eq!(overlay.view.anchor, ScrollAnchor::TOP);
     }
 
     #[test]
@@ -1129,12 +1210,14 @@ mod tests {
 
         // Prime layout so last_content_height is populated and paging uses the real content height.
         let mut buf = Buffer::empty(area);
-        overlay.view.scroll_offset = 0;
+        overlay.view.anchor = ScrollAnchor::TOP;
         overlay.render(area, &mut buf);
         let page_height = overlay.view.page_height(area);
+        let page = page_height as isize;
+        let width = area.width;
 
         // Scenario 1: starting from the top, PageDown should show the next page of content.
-        overlay.view.scroll_offset = 0;
+        overlay.view.anchor = ScrollAnchor::TOP;
         let page1 = transcript_line_numbers(&mut overlay, area);
         let page1_len = page1.len();
         let expected_page1: Vec<usize> = (0..page1_len).collect();
@@ -1143,7 +1226,7 @@ mod tests {
             "first page should start at line-00 and show a full page of content"
         );
 
-        overlay.view.scroll_offset = overlay.view.scroll_offset.saturating_add(page_height);
+        overlay.view.scroll_by(page, width, page_height);
         let page2 = transcript_line_numbers(&mut overlay, area);
         assert_eq!(
             page2.len(),
@@ -1158,11 +1241,11 @@ mod tests {
 
         // Scenario 2: from an interior offset (start=3), PageDown then PageUp should round-trip.
         let interior_offset = 3usize;
-        overlay.view.scroll_offset = interior_offset;
+        overlay.view.scroll_to(interior_offset, width, page_height);
         let before = transcript_line_numbers(&mut overlay, area);
-        overlay.view.scroll_offset = overlay.view.scroll_offset.saturating_add(page_height);
+        overlay.view.scroll_by(page, width, page_height);
         let _ = transcript_line_numbers(&mut overlay, area);
-        overlay.view.scroll_offset = overlay.view.scroll_offset.saturating_sub(page_height);
+        overlay.view.scroll_by(-page, width, page_height);
         let after = transcript_line_numbers(&mut overlay, area);
         assert_eq!(
             before, after,
@@ -1170,11 +1253,11 @@ mod tests {
         );
 
         // Scenario 3: from the top of the second page, PageUp then PageDown should round-trip.
-        overlay.view.scroll_offset = page_height;
+        overlay.view.scroll_to(page_height, width, page_height);
         let before2 = transcript_line_numbers(&mut overlay, area);
-        overlay.view.scroll_offset = overlay.view.scroll_offset.saturating_sub(page_height);
+        overlay.view.scroll_by(-page, width, page_height);
         let _ = transcript_line_numbers(&mut overlay, area);
-        overlay.view.scroll_offset = overlay.view.scroll_offset.saturating_add(page_height);
+        overlay.view.scroll_by(page, width, page_height);
         let after2 = transcript_line_numbers(&mut overlay, area);
         assert_eq!(
             before2, after2,
@@ -1199,7 +1282,7 @@ mod tests {
         let pv = PagerView::new(
             vec![paragraph_block("a", 2), paragraph_block("b", 3)],
             "T".to_string(),
-            0,
+            ScrollAnchor::TOP,
         );
 
         assert_eq!(pv.content_height(80), 5);
@@ -1214,11 +1297,11 @@ mod tests {
                 paragraph_block("c", 3),
             ],
             "T".to_string(),
-            0,
+            ScrollAnchor::TOP,
         );
         let area = Rect::new(0, 0, 20, 8);
 
-        pv.scroll_offset = 0;
+        pv.anchor = ScrollAnchor::TOP;
         let content_area = pv.content_area(area);
         pv.ensure_chunk_visible(2, content_area);
 
@@ -1249,19 +1332,23 @@ mod tests {
                 paragraph_block("c", 3),
             ],
             "T".to_string(),
-            0,
+            ScrollAnchor::TOP,
         );
         let area = Rect::new(0, 0, 20, 3);
 
-        pv.scroll_offset = 6;
+        pv.scroll_to(6, area.width, area.height as usize);
         pv.ensure_chunk_visible(0, area);
 
-        assert_eq!(pv.scroll_offset, 0);
+        assert_eq!(pv.anchor, ScrollAnchor::TOP);
     }
 
     #[test]
     fn pager_view_is_scrolled_to_bottom_accounts_for_wrapped_height() {
-        let mut pv = PagerView::new(vec![paragraph_block("a", 10)], "T".to_string(), 0);
+        let mut pv = PagerView::new(
+            vec![paragraph_block("a", 10)],
+            "T".to_string(),
+            ScrollAnchor::TOP,
+        );
         let area = Rect::new(0, 0, 20, 8);
         let mut buf = Buffer::empty(area);
 
@@ -1272,7 +1359,7 @@ mod tests {
             "expected view to report not at bottom when offset < max"
         );
 
-        pv.scroll_offset = usize::MAX;
+        pv.anchor = ScrollAnchor::End;
         pv.render(area, &mut buf);
 
         assert!(
@@ -1280,4 +1367,109 @@ mod tests {
             "expected view to report at bottom after scrolling to end"
         );
     }
+
+    fn numbered_cells(count: usize) -> Vec<Arc<dyn HistoryCell>> {
+        (0..count)
+            .map(|i| {
+                Arc::new(TestCell {
+                    lines: vec![Line::from(format!("line-{i:02}"))],
+                }) as Arc<dyn HistoryCell>
+            })
+            .collect()
+    }
+
+    fn stream_key(revision: u64) -> Option<ActiveCellTranscriptKey> {
+        Some(ActiveCellTranscriptKey {
+            revision,
+            is_stream_continuation: false,
+            animation_tick: None,
+        })
+    }
+
+    #[test]
+    fn transcript_overlay_streaming_keeps_mid_history_view_fixed() {
+        let mut overlay = TranscriptOverlay::new(numbered_cells(30));
+        let area = Rect::new(0, 0, 40, 15);
+        let before_scroll = transcript_line_numbers(&mut overlay, area);
+        let page_height = overlay.view.page_height(area);
+        overlay.view.scroll_to(10, area.width, page_height);
+        let before = transcript_line_numbers(&mut overlay, area);
+        assert_ne!(before, before_scroll);
+
+        // Stream a growing live tail, commit it, and stream more below it.
+        for revision in 1..=5u64 {
+            overlay.sync_live_tail(area.width, stream_key(revision), |_| {
+                Some(
+                    (0..revision * 4)
+                        .map(|i| Line::from(format!("delta {i}")))
+                        .collect(),
+                )
+            });
+            assert_eq!(transcript_line_numbers(&mut overlay, area), before);
+        }
+        overlay.insert_cell(Arc::new(TestCell {
+            lines: vec![Line::from("line-99")],
+        }));
+        overlay.sync_live_tail(area.width, stream_key(6), |_| {
+            Some(vec![Line::from("tool result")])
+        });
+
+        assert_eq!(transcript_line_numbers(&mut overlay, area), before);
+        assert!(!overlay.is_scrolled_to_bottom());
+    }
+
+    #[test]
+    fn transcript_overlay_follows_streaming_output_when_anchored_at_end() {
+        let mut overlay = TranscriptOverlay::new(numbered_cells(30));
+        let area = Rect::new(0, 0, 40, 15);
+        assert!(transcript_line_numbers(&mut overlay, area).contains(&29));
+
+        for revision in 1..=3u64 {
+            overlay.sync_live_tail(area.width, stream_key(revision), |_| {
+                Some(
+                    (0..revision * 6)
+                        .map(|i| Line::from(format!("line-{}", 50 + i)))
+                        .collect(),
+                )
+            });
+            let visible = transcript_line_numbers(&mut overlay, area);
+            assert_eq!(visible.last(), Some(&(50 + revision as usize * 6 - 1)));
+            assert_eq!(overlay.view.anchor, ScrollAnchor::End);
+        }
+
+        // Scrolling back down to the last page resumes following.
+        let page_height = overlay.view.page_height(area);
+        overlay.view.scroll_by(-3, area.width, page_height);
+        assert!(matches!(overlay.view.anchor, ScrollAnchor::Line { .. }));
+        overlay.view.scroll_by(3, area.width, page_height);
+        assert_eq!(overlay.view.anchor, ScrollAnchor::End);
+    }
+
+    #[test]
+    fn pager_view_anchor_survives_rewrap_at_new_width() {
+        let long = "word ".repeat(12);
+        let renderables: Vec<Box<dyn Renderable>> = (0..12)
+            .map(|i| {
+                let paragraph = Paragraph::new(Text::from(vec![
+                    Line::from(format!("entry{i:02}")),
+                    Line::from(long.clone()),
+                ]))
+                .wrap(Wrap { trim: false });
+                Box::new(CachedRenderable::new(paragraph)) as Box<dyn Renderable>
+            })
+            .collect();
+        let mut pv = PagerView::new(renderables, "T".to_string(), ScrollAnchor::TOP);
+        pv.anchor = ScrollAnchor::Line { chunk: 7, line: 0 };
+
+        for width in [80, 20, 30] {
+            let area = Rect::new(0, 0, width, 10);
+            let mut buf = Buffer::empty(area);
+            pv.render(area, &mut buf);
+            let content_area = pv.content_area(area);
+            let first_row: String = (content_area.x..content_area.right())
+                .map(|x| buf[(x, content_area.y)].symbol().to_string())
+                .collect();
+            assert_eq!(first_row.trim_end(), "entry07", "width {width}");
+        }
+    }
 }
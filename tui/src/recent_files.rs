@@ -0,0 +1,290 @@
+//! Files the session has been working with, ranked for the Recent section of
+//! the `@` file popup and for `/recent`.
+//!
+//! Every read, edit, or mention adds a weight to the file's score, and all
+//! scores decay by [`DECAY`] for each activity recorded after them. A file
+//! touched several times stays ahead of one touched once a little later.
+//! Pinned files are listed first regardless of score and are saved per repo
+//! in `.happy/ui_state.json`.
+
+use std::collections::HashMap;
+use std::io;
+use std::path::Component;
+use std::path::Path;
+use std::path::PathBuf;
+
+use serde::Deserialize;
+use serde::Serialize;
+
+/// Most files listed, pinned ones included unless there are more pins.
+pub(crate) const RECENT_FILES_LIMIT: usize = 10;
+
+/// Per-repo UI state, relative to the session root.
+pub(crate) const UI_STATE_FILE: &str = ".happy/ui_state.json";
+
+/// Factor applied to every score per activity recorded after it.
+const DECAY: f64 = 0.8;
+
+/// How a file came up in the session.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum FileActivity {
+    /// Read by a command the agent ran.
+    Read,
+    /// Picked from the `@` file popup.
+    Mention,
+    /// Changed by a patch.
+    Edit,
+}
+
+impl FileActivity {
+    fn weight(self) -> f64 {
+        match self {
+            FileActivity::Read => 1.0,
+            FileActivity::Mention => 2.0,
+            FileActivity::Edit => 3.0,
+        }
+    }
+}
+
+/// One entry of the ranked list.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct RecentFile {
+    /// Relative to the session root.
+    pub(crate) path: PathBuf,
+    pub(crate) pinned: bool,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct UiState {
+    #[serde(default)]
+    pinned_files: Vec<PathBuf>,
+    /// Keys this module does not own, written back unchanged.
+    #[serde(flatten)]
+    other: serde_json::Map<String, serde_json::Value>,
+}
+
+#[derive(Debug)]
+pub(crate) struct RecentFiles {
+    root: PathBuf,
+    /// Relative path -> score and the tick it was last brought up to date.
+    scores: HashMap<PathBuf, (f64, u64)>,
+    pinned: Vec<PathBuf>,
+    /// Number of activities recorded so far.
+    tick: u64,
+}
+
+impl RecentFiles {
+    pub(crate) fn new(root: &Path) -> Self {
+        Self {
+            root: root.to_path_buf(),
+            scores: HashMap::new(),
+            pinned: Vec::new(),
+            tick: 0,
+        }
+    }
+
+    /// Start a session with the pins saved for the repo at `root`.
+    pub(crate) fn load(root: &Path) -> io::Result<Self> {
+        let mut files = Self::new(root);
+        files.pinned = read_ui_state(root)?
+            .pinned_files
+            .iter()
+            .filter_map(|path| relative(root, path))
+            .collect();
+        files.pinned.dedup();
+        Ok(files)
+    }
+
+    /// Record that `path` (absolute, or relative to the root) came up.
+    /// Paths outside the root are ignored; returns whether it was recorded.
+    pub(crate) fn touch(&mut self, path: &Path, activity: FileActivity) -> bool {
+        let Some(path) = relative(&self.root, path) else {
+            return false;
+        };
+        self.tick += 1;
+        let score = self.score(&path) + activity.weight();
+        self.scores.insert(path, (score, self.tick));
+        true
+    }
+
+    /// Pinned files in pin order, then the highest scoring others, up to
+    /// [`RECENT_FILES_LIMIT`] in total.
+    pub(crate) fn ranked(&self) -> Vec<RecentFile> {
+        let mut others: Vec<(f64, &PathBuf)> = self
+            .scores
+            .keys()
+            .filter(|path| !self.pinned.contains(path))
+            .map(|path| (self.score(path), path))
+            .collect();
+        others.sort_by(|(a_score, a), (b_score, b)| {
+            b_score.total_cmp(a_score).then_with(|| a.cmp(b))
+        });
+        let room = RECENT_FILES_LIMIT.saturating_sub(self.pinned.len());
+        self.pinned
+            .iter()
+            .map(|path| RecentFile {
+                path: path.clone(),
+                pinned: true,
+            })
+            .chain(others.into_iter().take(room).map(|(_, path)| RecentFile {
+                path: path.clone(),
+                pinned: false,
+            }))
+            .collect()
+    }
+
+    /// Pin `path` or unpin it if it already is, and save the pins. Returns
+    /// whether the file is pinned now.
+    pub(crate) fn toggle_pin(&mut self, path: &Path) -> io::Result<bool> {
+        let Some(path) = relative(&self.root, path) else {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("{} is outside {}", path.display(), self.root.display()),
+            ));
+        };
+        let pinned = match self.pinned.iter().position(|p| *p == path) {
+            Some(index) => {
+                self.pinned.remove(index);
+                false
+            }
+            None => {
+                self.pinned.push(path);
+                true
+            }
+        };
+        let mut state = read_ui_state(&self.root)?;
+        state.pinned_files = self.pinned.clone();
+        let path = self.root.join(UI_STATE_FILE);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let text = serde_json::to_string_pretty(&state).map_err(io::Error::other)?;
+        std::fs::write(path, text)?;
+        Ok(pinned)
+    }
+
+    /// The score of `path` decayed to the current tick.
+    fn score(&self, path: &Path) -> f64 {
+        self.scores.get(path).map_or(0.0, |&(score, at)| {
+            score * DECAY.powi((self.tick - at).min(i32::MAX as u64) as i32)
+        })
+    }
+}
+
+fn read_ui_state(root: &Path) -> io::Result<UiState> {
+    let text = match std::fs::read_to_string(root.join(UI_STATE_FILE)) {
+        Ok(text) => text,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(UiState::default()),
+        Err(err) => return Err(err),
+    };
+    serde_json::from_str(&text).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+/// `path` relative to `root`, when it lies inside it.
+fn relative(root: &Path, path: &Path) -> Option<PathBuf> {
+    let path = if path.is_absolute() {
+        path.strip_prefix(root).ok()?
+    } else {
+        path
+    };
+    let mut relative = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::Normal(part) => relative.push(part),
+            Component::CurDir => {}
+            _ => return None,
+        }
+    }
+    (!relative.as_os_str().is_empty()).then_some(relative)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn paths(files: &[RecentFile]) -> Vec<&str> {
+        files
+            .iter()
+            .map(|file| file.path.to_str().expect("utf-8 path"))
+            .collect()
+    }
+
+    #[test]
+    fn frequent_files_outrank_a_single_later_touch() {
+        let root = Path::new("/repo");
+        let mut files = RecentFiles::new(root);
+        assert!(files.touch(Path::new("/repo/src/lib.rs"), FileActivity::Edit));
+        assert!(files.touch(Path::new("src/main.rs"), FileActivity::Read));
+        assert_eq!(paths(&files.ranked()), vec!["src/lib.rs", "src/main.rs"]);
+        assert!((files.score(Path::new("src/lib.rs")) - 2.4).abs() < 1e-9);
+
+        for _ in 0..3 {
+            files.touch(Path::new("./src/main.rs"), FileActivity::Read);
+        }
+        // 1 decayed three times plus the reads: ((1 * .8 + 1) * .8 + 1) * .8 + 1.
+        assert!((files.score(Path::new("src/main.rs")) - 2.952).abs() < 1e-9);
+        // 3 decayed four times.
+        assert!((files.score(Path::new("src/lib.rs")) - 1.2288).abs() < 1e-9);
+        assert_eq!(paths(&files.ranked()), vec!["src/main.rs", "src/lib.rs"]);
+
+        assert!(!files.touch(Path::new("/elsewhere/a.rs"), FileActivity::Edit));
+        assert!(!files.touch(Path::new("../a.rs"), FileActivity::Edit));
+    }
+
+    #[test]
+    fn ranking_is_capped_and_keeps_the_most_recent() {
+        let mut files = RecentFiles::new(Path::new("/repo"));
+        for i in 0..RECENT_FILES_LIMIT + 2 {
+            files.touch(Path::new(&format!("f{i:02}.rs")), FileActivity::Read);
+        }
+        let ranked = files.ranked();
+        assert_eq!(ranked.len(), RECENT_FILES_LIMIT);
+        assert_eq!(ranked[0].path, PathBuf::from("f11.rs"));
+        assert!(!ranked.iter().any(|file| file.path == Path::new("f00.rs")));
+    }
+
+    #[test]
+    fn pins_persist_per_repo_and_stay_on_top() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        std::fs::create_dir_all(dir.path().join(".happy")).expect("create .happy");
+        std::fs::write(dir.path().join(UI_STATE_FILE), r#"{"theme": "dark"}"#)
+            .expect("write ui state");
+
+        let mut files = RecentFiles::load(dir.path()).expect("load");
+        files.touch(Path::new("src/app.rs"), FileActivity::Edit);
+        assert!(files.toggle_pin(Path::new("docs/guide.md")).expect("pin"));
+        assert_eq!(
+            files.ranked(),
+            vec![
+                RecentFile {
+                    path: PathBuf::from("docs/guide.md"),
+                    pinned: true,
+                },
+                RecentFile {
+                    path: PathBuf::from("src/app.rs"),
+                    pinned: false,
+                },
+            ]
+        );
+
+        let saved: serde_json::Value = serde_json::from_str(
+            &std::fs::read_to_string(dir.path().join(UI_STATE_FILE)).expect("read ui state"),
+        )
+        .expect("json");
+        assert_eq!(saved["theme"], "dark");
+        assert_eq!(saved["pinned_files"], serde_json::json!(["docs/guide.md"]));
+
+        let mut files = RecentFiles::load(dir.path()).expect("reload");
+        assert_eq!(paths(&files.ranked()), vec!["docs/guide.md"]);
+        assert!(!files.toggle_pin(Path::new("docs/guide.md")).expect("unpin"));
+        assert!(files.ranked().is_empty());
+        assert!(
+            RecentFiles::load(dir.path())
+                .expect("reload")
+                .pinned
+                .is_empty()
+        );
+        assert!(files.toggle_pin(Path::new("/elsewhere/x.rs")).is_err());
+    }
+}
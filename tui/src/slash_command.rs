@@ -26,6 +26,7 @@ pub enum SlashCommand {
     New,
     Resume,
     Fork,
+    Handoff,
     Init,
     Compact,
     Plan,
@@ -34,6 +35,9 @@ pub enum SlashCommand {
     // Undo,
     Diff,
     Mention,
+    Recent,
+    Bookmarks,
+    Reindex,
     Status,
     DebugConfig,
     Statusline,
@@ -67,10 +71,14 @@ impl SlashCommand {
             SlashCommand::Rename => "rename the current thread",
             SlashCommand::Resume => "resume a saved chat",
             SlashCommand::Fork => "fork the current chat",
+            SlashCommand::Handoff => "export this chat as a bundle a teammate can import",
             // SlashCommand::Undo => "ask Codex to undo a turn",
             SlashCommand::Quit | SlashCommand::Exit => "exit Codex",
             SlashCommand::Diff => "show git diff (including untracked files)",
             SlashCommand::Mention => "mention a file",
+            SlashCommand::Recent => "list files this session read, edited, or mentioned",
+            SlashCommand::Bookmarks => "list bookmarked code elements and their notes",
+            SlashCommand::Reindex => "rebuild the code graph of the working directory",
             SlashCommand::Skills => "use skills to improve how Codex performs specific tasks",
             SlashCommand::Status => "show current session configuration and token usage",
             SlashCommand::DebugConfig => "show config layers and requirement sources for debugging",
@@ -111,6 +119,7 @@ impl SlashCommand {
             self,
             SlashCommand::Review
                 | SlashCommand::Rename
+                | SlashCommand::Handoff
                 | SlashCommand::Plan
                 | SlashCommand::SandboxReadRoot
         )
@@ -122,6 +131,7 @@ impl SlashCommand {
             SlashCommand::New
             | SlashCommand::Resume
             | SlashCommand::Fork
+            | SlashCommand::Handoff
             | SlashCommand::Init
             | SlashCommand::Compact
             // | SlashCommand::Undo
@@ -140,6 +150,9 @@ impl SlashCommand {
             SlashCommand::Diff
             | SlashCommand::Rename
             | SlashCommand::Mention
+            | SlashCommand::Recent
+            | SlashCommand::Bookmarks
+            | SlashCommand::Reindex
             | SlashCommand::Skills
             | SlashCommand::Status
             | SlashCommand::DebugConfig